@@ -0,0 +1,71 @@
+//! Lookup speed of the four large mapping tables (country, clinical size
+//! type, MU characteristic, MU unit). Run with `cargo bench --bench
+//! mapping_lookup`; compare two implementations of the tables with
+//! `--save-baseline before` / `--baseline before` as for `parse_transform`.
+//!
+//! One round looks up every ISO country code EUDAMED uses and every CST / MU
+//! code in the ranges EUDAMED assigns, hits and misses alike, through the
+//! public `mappings` functions — so the input does not depend on how the
+//! tables are stored.
+
+// The modules are linted as part of the binary; here most of them are unused.
+#![allow(dead_code, unused_imports, clippy::all)]
+
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../src/firstbase.rs"]
+mod firstbase;
+#[path = "../src/gtin.rs"]
+mod gtin;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/http_cache.rs"]
+mod http_cache;
+#[path = "../src/log_console.rs"]
+mod log_console;
+#[path = "../src/mappings.rs"]
+mod mappings;
+#[path = "../src/version_db.rs"]
+mod version_db;
+#[path = "../src/warnings.rs"]
+mod warnings;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn lookups(c: &mut Criterion) {
+    let countries = mappings::ACTOR_COUNTRY_CODES;
+    let cst: Vec<String> = (1..=70)
+        .map(|n| format!("CST{}", n))
+        .chain(["CST999".to_string()])
+        .collect();
+    let mu: Vec<String> = (1..=180)
+        .map(|n| format!("MU{:02}", n))
+        .chain(["MU999".to_string()])
+        .collect();
+
+    c.bench_function(
+        &format!(
+            "mapping_lookup ({} lookups)",
+            countries.len() + cst.len() + 2 * mu.len()
+        ),
+        |b| {
+            b.iter(|| {
+                for code in countries {
+                    black_box(mappings::country_numeric(black_box(code)));
+                }
+                for code in &cst {
+                    black_box(mappings::clinical_size_type_to_gs1(black_box(code)));
+                }
+                for code in &mu {
+                    black_box(mappings::mu_code_to_characteristic_code(black_box(code)));
+                    black_box(mappings::measurement_unit_to_gs1(black_box(code)));
+                }
+            })
+        },
+    );
+}
+
+criterion_group!(benches, lookups);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+//! Parse and transform throughput per device, so a feature that slows down the
+//! hot path of a multi-GB run shows up here first. Run with `cargo bench`.
+//!
+//! The binary crate has no library target, so the modules under test are
+//! compiled into the bench directly; they only depend on each other.
+
+// The modules are linted as part of the binary; here most of them are unused.
+#![allow(dead_code, unused_imports, clippy::all)]
+
+#[path = "../src/actor_contacts.rs"]
+mod actor_contacts;
+#[path = "../src/api_detail.rs"]
+mod api_detail;
+#[path = "../src/api_json.rs"]
+mod api_json;
+#[path = "../src/avp.rs"]
+mod avp;
+#[path = "../src/certificates.rs"]
+mod certificates;
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../src/dates.rs"]
+mod dates;
+#[path = "../src/discontinued.rs"]
+mod discontinued;
+#[path = "../src/eudamed.rs"]
+mod eudamed;
+#[path = "../src/firstbase.rs"]
+mod firstbase;
+#[path = "../src/gpc.rs"]
+mod gpc;
+#[path = "../src/gtin.rs"]
+mod gtin;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/http_cache.rs"]
+mod http_cache;
+#[path = "../src/log_console.rs"]
+mod log_console;
+#[path = "../src/mappings.rs"]
+mod mappings;
+#[path = "../src/ordering.rs"]
+mod ordering;
+#[path = "../src/parties.rs"]
+mod parties;
+#[path = "../src/swiss_market.rs"]
+mod swiss_market;
+#[path = "../src/transform.rs"]
+mod transform;
+#[path = "../src/transform_detail.rs"]
+mod transform_detail;
+#[path = "../src/tri_state.rs"]
+mod tri_state;
+#[path = "../src/version_db.rs"]
+mod version_db;
+#[path = "../src/warnings.rs"]
+mod warnings;
+
+use std::hint::black_box;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const LISTING: &str = include_str!("fixtures/listing.ndjson");
+const DETAIL: &str = include_str!("fixtures/detail.json");
+const BASIC_UDI: &str = include_str!("fixtures/basic_udi.json");
+const XML: &str = include_str!("fixtures/pull_response.xml");
+
+fn config() -> config::Config {
+    config::load_config(Path::new("config.sample.toml")).unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let listing = LISTING.trim();
+    c.bench_function("parse_api_device", |b| {
+        b.iter(|| api_json::parse_api_device(black_box(listing)).unwrap())
+    });
+    c.bench_function("parse_listing_index_entry", |b| {
+        b.iter(|| api_json::parse_listing_index_entry(black_box(listing)).unwrap())
+    });
+    c.bench_function("parse_api_detail", |b| {
+        b.iter(|| api_detail::parse_api_detail(black_box(DETAIL)).unwrap())
+    });
+    c.bench_function("parse_pull_responses (XML)", |b| {
+        b.iter(|| eudamed::parse_pull_responses(black_box(XML)).unwrap())
+    });
+}
+
+fn transform(c: &mut Criterion) {
+    let config = config();
+    let detail = api_detail::parse_api_detail(DETAIL).unwrap();
+    let basic_udi = api_detail::parse_basic_udi_di(BASIC_UDI).unwrap();
+    c.bench_function("transform_detail_device", |b| {
+        b.iter(|| {
+            transform_detail::transform_detail_device(black_box(&detail), &config, Some(&basic_udi))
+        })
+    });
+    let responses = eudamed::parse_pull_responses(XML).unwrap();
+    c.bench_function("transform (XML)", |b| {
+        b.iter(|| transform::transform(black_box(&responses[0]), &config).unwrap())
+    });
+}
+
+criterion_group!(benches, parse, transform);
+criterion_main!(benches);
@@ -0,0 +1,197 @@
+//! Manufacturer (EMA), procedure pack producer (EPP) and authorised
+//! representative (EAR) contacts as most inputs give them: the SRN and at
+//! best a name. With `[actors] enrich = true` they are completed from the
+//! actor registry that `sync-actors` keeps in the `actors` table of
+//! `db/version_tracking.db`: name, structured address, telephone and e-mail.
+//!
+//! Only what a contact lacks is filled in; an SRN missing from the registry
+//! leaves its contact as it is. The table is read once per run, on first
+//! use. Applied next to `parties.rs`, at the end of every transform path and
+//! again after the listing merge.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::firstbase::{
+    CatalogueItemChildItemLink, CodeValue, CommunicationChannel, FirstbaseDocument,
+    StructuredAddress, TargetMarketCommunicationChannel, TradeItem, TradeItemContactInformation,
+};
+use crate::mappings;
+use crate::version_db::{self, ActorRecord};
+
+const ACTOR_CONTACT_TYPES: [&str; 3] = ["EMA", "EPP", "EAR"];
+
+static REGISTRY: OnceLock<HashMap<String, ActorRecord>> = OnceLock::new();
+
+/// The synced actors by SRN; empty without a version DB or `sync-actors` run.
+fn registry() -> &'static HashMap<String, ActorRecord> {
+    REGISTRY.get_or_init(|| {
+        let path = Path::new(version_db::VERSION_DB_PATH);
+        let actors = if path.exists() {
+            version_db::open_db(path).and_then(|conn| version_db::load_actors(&conn))
+        } else {
+            Ok(Vec::new())
+        };
+        match actors {
+            Ok(actors) => {
+                if actors.is_empty() {
+                    eprintln!(
+                        "[actors] enrich: no actors in {} (run sync-actors)",
+                        path.display()
+                    );
+                }
+                actors.into_iter().map(|a| (a.srn.clone(), a)).collect()
+            }
+            Err(e) => {
+                eprintln!("[actors] enrich: cannot read {}: {}", path.display(), e);
+                HashMap::new()
+            }
+        }
+    })
+}
+
+fn channel(code: &'static str, value: &str) -> TargetMarketCommunicationChannel {
+    TargetMarketCommunicationChannel {
+        channels: vec![CommunicationChannel {
+            channel_code: CodeValue { value: code.into() },
+            value: value.to_string(),
+        }],
+    }
+}
+
+/// Fill the name, address and channels `contact` lacks from `actor`. The
+/// structured street is often empty in the registry; the full
+/// `geographical_address` stands in for it then.
+fn complete(contact: &mut TradeItemContactInformation, actor: &ActorRecord) {
+    if contact.contact_name.as_deref().is_none_or(str::is_empty) && !actor.name.is_empty() {
+        contact.contact_name = Some(actor.name.clone());
+    }
+    let street = if actor.street_name.is_empty() {
+        &actor.geographical_address
+    } else {
+        &actor.street_name
+    };
+    if contact.addresses.is_empty() && !(street.is_empty() && actor.city_name.is_empty()) {
+        contact.addresses.push(StructuredAddress {
+            city: actor.city_name.clone(),
+            country_code: CodeValue {
+                value: mappings::country_alpha2_to_numeric(&actor.country_iso2),
+            },
+            postal_code: actor.postal_zone.clone(),
+            street: street.clone(),
+            street_number: (!actor.building_number.is_empty())
+                .then(|| actor.building_number.clone()),
+        });
+    }
+    if contact.communication_channels.is_empty() {
+        if !actor.telephone.is_empty() {
+            contact
+                .communication_channels
+                .push(channel("TELEPHONE", &actor.telephone));
+        }
+        if !actor.email.is_empty() {
+            contact
+                .communication_channels
+                .push(channel("EMAIL", &actor.email));
+        }
+    }
+}
+
+fn enrich_contacts(
+    contacts: &mut [TradeItemContactInformation],
+    actors: &HashMap<String, ActorRecord>,
+) {
+    for contact in contacts {
+        if !ACTOR_CONTACT_TYPES.contains(&contact.contact_type.value.as_ref()) {
+            continue;
+        }
+        let actor = contact
+            .party_identification
+            .iter()
+            .filter(|p| p.type_code == "SRN")
+            .find_map(|p| actors.get(&p.value));
+        if let Some(actor) = actor {
+            complete(contact, actor);
+        }
+    }
+}
+
+/// Complete the actor contacts of `item` from `actors`.
+pub fn enrich(item: &mut TradeItem, actors: &HashMap<String, ActorRecord>) {
+    enrich_contacts(&mut item.contact_information, actors);
+}
+
+/// Complete the actor contacts of every level of `doc` when `[actors]
+/// enrich` is on.
+pub fn apply_document(doc: &mut FirstbaseDocument, config: &Config) {
+    fn walk(links: &mut [CatalogueItemChildItemLink], actors: &HashMap<String, ActorRecord>) {
+        for link in links {
+            enrich(&mut link.catalogue_item.trade_item, actors);
+            walk(&mut link.catalogue_item.children, actors);
+        }
+    }
+    if config.actors.enrich {
+        let actors = registry();
+        enrich(&mut doc.trade_item, actors);
+        walk(&mut doc.children, actors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firstbase::AdditionalPartyIdentification;
+
+    #[test]
+    fn completes_contacts_from_registry() {
+        let contact = |kind: &str, srn: &str, name: Option<&str>| TradeItemContactInformation {
+            contact_type: CodeValue {
+                value: kind.to_string().into(),
+            },
+            party_identification: vec![AdditionalPartyIdentification {
+                type_code: "SRN".to_string(),
+                value: srn.to_string(),
+            }],
+            contact_name: name.map(str::to_string),
+            addresses: vec![],
+            communication_channels: vec![],
+        };
+        let actors = HashMap::from([(
+            "DE-MF-000012345".to_string(),
+            ActorRecord {
+                srn: "DE-MF-000012345".to_string(),
+                name: "Example Medical GmbH".to_string(),
+                country_iso2: "DE".to_string(),
+                street_name: "Hauptstrasse".to_string(),
+                building_number: "5".to_string(),
+                postal_zone: "10115".to_string(),
+                city_name: "Berlin".to_string(),
+                email: "info@example.de".to_string(),
+                ..Default::default()
+            },
+        )]);
+        let mut contacts = vec![
+            contact("EMA", "DE-MF-000012345", Some("Example")),
+            contact("EAR", "DE-AR-000000001", None),
+            contact("EPD", "DE-MF-000012345", None),
+        ];
+        enrich_contacts(&mut contacts, &actors);
+
+        let ema = &contacts[0];
+        assert_eq!(ema.contact_name.as_deref(), Some("Example"));
+        assert_eq!(ema.addresses.len(), 1);
+        let address = &ema.addresses[0];
+        assert_eq!(address.city, "Berlin");
+        assert_eq!(address.country_code.value.as_ref(), "276");
+        assert_eq!(address.street_number.as_deref(), Some("5"));
+        assert_eq!(ema.communication_channels.len(), 1);
+        assert_eq!(
+            ema.communication_channels[0].channels[0].value,
+            "info@example.de"
+        );
+        assert!(contacts[1].contact_name.is_none());
+        assert!(contacts[2].addresses.is_empty());
+    }
+}
@@ -102,6 +102,7 @@ pub struct ApiDeviceDetail {
 #[allow(dead_code)]
 pub struct DiIdentifier {
     pub uuid: Option<String>,
+    #[serde(default, deserialize_with = "crate::gtin::owned_code")]
     pub code: Option<String>,
     pub issuing_agency: Option<RefCode>,
     #[serde(rename = "type")]
@@ -431,14 +432,14 @@ impl ApiDeviceDetail {
         }
     }
 
-    /// Get trade name texts as (language_code, text) pairs
-    pub fn trade_name_texts(&self) -> Vec<(String, String)> {
-        extract_lang_texts(self.trade_name.as_ref())
+    /// Get trade name texts as (language_code, text) pairs; see `lang_texts`
+    pub fn trade_name_texts(&self, languages: &[String]) -> Vec<(String, String)> {
+        lang_texts(self.trade_name.as_ref(), languages, false)
     }
 
     /// Get additional description texts
-    pub fn additional_description_texts(&self) -> Vec<(String, String)> {
-        extract_lang_texts(self.additional_description.as_ref())
+    pub fn additional_description_texts(&self, languages: &[String]) -> Vec<(String, String)> {
+        lang_texts(self.additional_description.as_ref(), languages, false)
     }
 
     /// Get production identifier type codes for UDI PI
@@ -465,55 +466,71 @@ impl ApiDeviceDetail {
     }
 }
 
-fn extract_lang_texts(mlt: Option<&MultiLangText>) -> Vec<(String, String)> {
-    let raw: Vec<(String, String)> = mlt
-        .and_then(|t| t.texts.as_ref())
-        .map(|texts| {
-            texts
-                .iter()
-                .filter_map(|lt| {
-                    let text = lt.text.clone()?;
-                    if text.is_empty() {
-                        return None;
-                    }
-                    // Use explicit language code, or fall back to "en" when
-                    // language is null and allLanguagesApplicable is true
-                    let lang = lt
-                        .language
-                        .as_ref()
-                        .and_then(|l| l.iso_code.clone())
-                        .or_else(|| {
-                            if lt.all_languages_applicable == Some(true) {
-                                Some("en".to_string())
-                            } else {
-                                None
-                            }
-                        })?;
-                    Some((lang, text))
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-    // Merge duplicate languages with " / " (097.078: at most one iteration per languageCode)
-    merge_by_language(raw)
-}
-
-/// Merge entries with the same language code by joining texts with " / ".
-fn merge_by_language(entries: Vec<(String, String)>) -> Vec<(String, String)> {
-    let mut map: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+/// (language, text) pairs of a MultiLangText, one per language, in source order.
+///
+/// A text flagged `allLanguagesApplicable` (or, with `untagged_is_all`, one
+/// without a language) is replicated, at its own position, into each of
+/// `languages` — the target market's — that has no dedicated text; a
+/// dedicated text always wins.
+pub fn lang_texts(
+    mlt: Option<&MultiLangText>,
+    languages: &[String],
+    untagged_is_all: bool,
+) -> Vec<(String, String)> {
+    // (language, text); no language for an all-languages text
+    let mut entries: Vec<(Option<String>, String)> = Vec::new();
+    for lt in mlt.and_then(|t| t.texts.as_ref()).into_iter().flatten() {
+        let Some(text) = lt.text.clone().filter(|t| !t.is_empty()) else {
+            continue;
+        };
+        let lang = lt.language.as_ref().and_then(|l| l.iso_code.as_deref());
+        match lang {
+            _ if lt.all_languages_applicable == Some(true) => entries.push((None, text)),
+            Some(lang) => entries.push((
+                Some(crate::mappings::language_code(lang).into_owned()),
+                text,
+            )),
+            None if untagged_is_all => entries.push((None, text)),
+            None => {}
+        }
+    }
+    let dedicated: Vec<String> = entries.iter().filter_map(|(l, _)| l.clone()).collect();
+    let mut merged = Vec::new();
     for (lang, text) in entries {
-        map.entry(lang)
-            .and_modify(|existing| {
-                existing.push_str(" / ");
-                existing.push_str(&text);
-            })
-            .or_insert(text);
+        match lang {
+            Some(lang) => merge_by_language(&mut merged, lang, text),
+            None => {
+                for lang in languages {
+                    let lang = crate::mappings::language_code(lang).into_owned();
+                    if !dedicated.contains(&lang) {
+                        merge_by_language(&mut merged, lang, text.clone());
+                    }
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// Add `text` to `merged`, joined with " / " to an earlier text of the same
+/// language (097.078: at most one iteration per languageCode). Languages are
+/// normalized first, so "EN" and "en-GB" merge with "en".
+fn merge_by_language(merged: &mut Vec<(String, String)>, lang: String, text: String) {
+    match merged.iter_mut().find(|(l, _)| *l == lang) {
+        Some((_, existing)) => {
+            existing.push_str(" / ");
+            existing.push_str(&text);
+        }
+        None => merged.push((lang, text)),
     }
-    map.into_iter().collect()
 }
 
 /// Parse one NDJSON line into an ApiDeviceDetail
 pub fn parse_api_detail(json_line: &str) -> anyhow::Result<ApiDeviceDetail> {
+    // simd-json parses in place, so it gets a copy of the line.
+    #[cfg(feature = "simd-json")]
+    let detail: ApiDeviceDetail = simd_json::serde::from_slice(&mut json_line.as_bytes().to_vec())?;
+    #[cfg(not(feature = "simd-json"))]
     let detail: ApiDeviceDetail = serde_json::from_str(json_line)?;
     Ok(detail)
 }
@@ -662,8 +679,8 @@ impl BasicUdiDiData {
     }
 
     /// Extract medical purpose texts (for SPP devices)
-    pub fn medical_purpose_texts(&self) -> Vec<(String, String)> {
-        extract_lang_texts(self.medical_purpose.as_ref())
+    pub fn medical_purpose_texts(&self, languages: &[String]) -> Vec<(String, String)> {
+        lang_texts(self.medical_purpose.as_ref(), languages, false)
     }
 }
 
@@ -672,3 +689,53 @@ pub fn parse_basic_udi_di(json_str: &str) -> anyhow::Result<BasicUdiDiData> {
     let data: BasicUdiDiData = serde_json::from_str(json_str)?;
     Ok(data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_languages_texts_fill_the_market_languages() {
+        let mlt: MultiLangText = serde_json::from_str(
+            r#"{"texts":[
+                {"language":null,"text":"Catheter","allLanguagesApplicable":true},
+                {"language":{"isoCode":"FR"},"text":"Cathéter"},
+                {"language":null,"text":"untagged"}
+            ]}"#,
+        )
+        .unwrap();
+        let languages: Vec<String> = crate::config::market_languages("756")
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let pairs = |untagged_is_all| lang_texts(Some(&mlt), &languages, untagged_is_all);
+        assert_eq!(
+            pairs(false),
+            [
+                ("de".to_string(), "Catheter".to_string()),
+                ("it".to_string(), "Catheter".to_string()),
+                ("en".to_string(), "Catheter".to_string()),
+                ("fr".to_string(), "Cathéter".to_string()),
+            ]
+        );
+        assert_eq!(pairs(true)[0].1, "Catheter / untagged");
+
+        // Source order is kept; only the same language is merged.
+        let mlt: MultiLangText = serde_json::from_str(
+            r#"{"texts":[
+                {"language":{"isoCode":"IT"},"text":"Catetere"},
+                {"language":{"isoCode":"EN"},"text":"Catheter"},
+                {"language":{"isoCode":"it"},"text":"Sonda"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            lang_texts(Some(&mlt), &languages, false),
+            [
+                ("it".to_string(), "Catetere / Sonda".to_string()),
+                ("en".to_string(), "Catheter".to_string()),
+            ]
+        );
+        assert!(lang_texts(None, &languages, true).is_empty());
+    }
+}
@@ -1,71 +1,167 @@
-use serde::Deserialize;
-
-/// Represents one device record from the EUDAMED public API listing endpoint
-/// (GET /devices/udiDiData?page=N&pageSize=300)
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
-pub struct ApiDevice {
-    pub basic_udi: Option<String>,
-    pub primary_di: Option<String>,
-    pub uuid: Option<String>,
-    pub ulid: Option<String>,
-    pub risk_class: Option<RefCode>,
-    pub trade_name: Option<String>,
-    pub manufacturer_name: Option<String>,
-    pub manufacturer_srn: Option<String>,
-    pub device_status_type: Option<RefCode>,
-    pub manufacturer_status: Option<RefCode>,
-    pub latest_version: Option<bool>,
-    pub version_number: Option<serde_json::Value>,
-    pub reference: Option<String>,
-    pub issuing_agency: Option<serde_json::Value>,
-    pub container_package_count: Option<serde_json::Value>,
-    pub authorised_representative_srn: Option<String>,
-    pub authorised_representative_name: Option<String>,
-    pub sterile: Option<serde_json::Value>,
-    pub multi_component: Option<serde_json::Value>,
-    pub device_criterion: Option<serde_json::Value>,
-    pub device_name: Option<String>,
-    pub device_model: Option<String>,
-    #[serde(rename = "mfOrPrSrn")]
-    pub mf_or_pr_srn: Option<String>,
-    pub applicable_legislation: Option<serde_json::Value>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct RefCode {
-    pub code: Option<String>,
-}
-
-impl ApiDevice {
-    /// Extract the GS1-style risk class code from the refdata code
-    /// e.g. "refdata.risk-class.class-iib" → "CLASS_IIB"
-    pub fn risk_class_code(&self) -> Option<String> {
-        self.risk_class.as_ref()?.code.as_ref().map(|c| {
-            c.rsplit('.')
-                .next()
-                .unwrap_or(c)
-                .replace('-', "_")
-                .to_uppercase()
-        })
-    }
-
-    /// Extract device status code
-    /// e.g. "refdata.device-model-status.on-the-market" → "ON_THE_MARKET"
-    pub fn status_code(&self) -> Option<String> {
-        self.device_status_type.as_ref()?.code.as_ref().map(|c| {
-            c.rsplit('.')
-                .next()
-                .unwrap_or(c)
-                .replace('-', "_")
-                .to_uppercase()
-        })
-    }
-}
-
-/// Parse one NDJSON line into an ApiDevice
-pub fn parse_api_device(json_line: &str) -> anyhow::Result<ApiDevice> {
-    let device: ApiDevice = serde_json::from_str(json_line)?;
-    Ok(device)
-}
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::tri_state::TriState;
+
+/// Represents one device record from the EUDAMED public API listing endpoint
+/// (GET /devices/udiDiData?page=N&pageSize=300). Strings borrow from the
+/// NDJSON line unless they contain escapes (`Cow`), so a 100k-line file does
+/// not allocate a dozen `String`s per record just to copy them into the
+/// TradeItem.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub struct ApiDevice<'a> {
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub basic_udi: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "crate::gtin::code")]
+    pub primary_di: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub uuid: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub ulid: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub risk_class: Option<RefCode<'a>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub trade_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub manufacturer_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub manufacturer_srn: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub device_status_type: Option<RefCode<'a>>,
+    #[serde(borrow)]
+    pub manufacturer_status: Option<RefCode<'a>>,
+    pub latest_version: Option<bool>,
+    pub version_number: Option<serde_json::Value>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub reference: Option<Cow<'a, str>>,
+    pub issuing_agency: Option<serde_json::Value>,
+    pub container_package_count: Option<serde_json::Value>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub authorised_representative_srn: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub authorised_representative_name: Option<Cow<'a, str>>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub sterile: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub multi_component: Option<TriState>,
+    pub device_criterion: Option<serde_json::Value>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub device_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub device_model: Option<Cow<'a, str>>,
+    #[serde(rename = "mfOrPrSrn", borrow, default, deserialize_with = "borrowed")]
+    pub mf_or_pr_srn: Option<Cow<'a, str>>,
+    pub applicable_legislation: Option<serde_json::Value>,
+}
+
+/// The listing fields `merge_listing_data` fills into a detail record, keyed
+/// by `primary_di`. All other fields of the line are skipped, not built, so
+/// indexing a full listing dump does far less work than `ApiDevice` (no
+/// `serde_json::Value` per record).
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingIndexEntry<'a> {
+    #[serde(borrow, default, deserialize_with = "crate::gtin::code")]
+    pub primary_di: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub basic_udi: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub risk_class: Option<RefCode<'a>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub manufacturer_srn: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub manufacturer_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub authorised_representative_srn: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub authorised_representative_name: Option<Cow<'a, str>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RefCode<'a> {
+    #[serde(borrow, default, deserialize_with = "borrowed")]
+    pub code: Option<Cow<'a, str>>,
+}
+
+/// `Option<Cow<str>>` that borrows from the input when the JSON string has
+/// no escapes — `#[serde(borrow)]` alone only borrows a bare `Cow`, not one
+/// inside an `Option`.
+fn borrowed<'de: 'a, 'a, D: Deserializer<'de>>(d: D) -> Result<Option<Cow<'a, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Text<'a>(#[serde(borrow)] Cow<'a, str>);
+    Ok(Option::<Text>::deserialize(d)?.map(|t| t.0))
+}
+
+impl RefCode<'_> {
+    /// Last segment of the refdata code, GS1-style
+    /// e.g. "refdata.risk-class.class-iib" → "CLASS_IIB"
+    fn gs1_code(&self) -> Option<String> {
+        self.code.as_ref().map(|c| {
+            c.rsplit('.')
+                .next()
+                .unwrap_or(c)
+                .replace('-', "_")
+                .to_uppercase()
+        })
+    }
+}
+
+impl ApiDevice<'_> {
+    /// Extract the GS1-style risk class code from the refdata code
+    /// e.g. "refdata.risk-class.class-iib" → "CLASS_IIB"
+    pub fn risk_class_code(&self) -> Option<String> {
+        self.risk_class.as_ref()?.gs1_code()
+    }
+
+    /// Extract device status code
+    /// e.g. "refdata.device-model-status.on-the-market" → "ON_THE_MARKET"
+    pub fn status_code(&self) -> Option<String> {
+        self.device_status_type.as_ref()?.gs1_code()
+    }
+}
+
+impl ListingIndexEntry<'_> {
+    /// Same as `ApiDevice::risk_class_code`.
+    pub fn risk_class_code(&self) -> Option<String> {
+        self.risk_class.as_ref()?.gs1_code()
+    }
+}
+
+/// Parse one NDJSON line into an ApiDevice borrowing from it
+pub fn parse_api_device(json_line: &str) -> anyhow::Result<ApiDevice<'_>> {
+    let device: ApiDevice = serde_json::from_str(json_line)?;
+    Ok(device)
+}
+
+/// Parse one NDJSON listing line into its `ListingIndexEntry`
+pub fn parse_listing_index_entry(json_line: &str) -> anyhow::Result<ListingIndexEntry<'_>> {
+    Ok(serde_json::from_str(json_line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_borrow_from_the_line_unless_escaped() {
+        let line = r#"{"uuid":"u-1","tradeName":"Stent \"XL\"","riskClass":{"code":"refdata.risk-class.class-iib"},"reference":null}"#;
+        let device = parse_api_device(line).unwrap();
+        assert!(matches!(device.uuid, Some(Cow::Borrowed("u-1"))));
+        assert!(matches!(&device.trade_name, Some(Cow::Owned(s)) if s == "Stent \"XL\""));
+        assert!(device.reference.is_none() && device.primary_di.is_none());
+        assert_eq!(device.risk_class_code().as_deref(), Some("CLASS_IIB"));
+    }
+
+    #[test]
+    fn index_entry_reads_only_the_merged_fields() {
+        let line = r#"{"primaryDi":"07612345780313","issuingAgency":{"code":"x","nested":[1,{"a":null}]},"riskClass":{"code":"refdata.risk-class.class-iii"},"manufacturerSrn":"CH-MF-000012345","sterile":true}"#;
+        let entry = parse_listing_index_entry(line).unwrap();
+        assert_eq!(entry.primary_di.as_deref(), Some("07612345780313"));
+        assert_eq!(entry.risk_class_code().as_deref(), Some("CLASS_III"));
+        assert_eq!(entry.manufacturer_srn.as_deref(), Some("CH-MF-000012345"));
+        assert!(entry.basic_udi.is_none() && entry.authorised_representative_srn.is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! `[[avp]]` rules → the base unit's `AvpList`. GDSN's attribute-value pairs
+//! carry what has no dedicated field (EUDAMED UUID and version, customer
+//! flags). Each rule's value is a constant or a template over the device's
+//! facts; a rule whose placeholder has no value for the device is left out
+//! rather than sent empty.
+
+use crate::config::AvpRule;
+use crate::firstbase::{
+    AttributeValuePairList, CompoundStringAttributeValuePair, StringAttributeValuePair,
+};
+
+/// The facts a transform path knows about the device, as `(placeholder, value)`.
+pub type Facts<'a> = [(&'static str, Option<&'a str>)];
+
+/// A JSON version number (`3` or `"3"`) as fact text.
+pub fn json_text(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Expand `{name}` placeholders; `None` when one is unknown or empty.
+fn expand(template: &str, facts: &Facts) -> Option<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}')?;
+        let name = &rest[start + 1..end];
+        let value = facts
+            .iter()
+            .find(|(n, _)| *n == name)
+            .and_then(|(_, v)| *v)
+            .filter(|v| !v.is_empty())?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// The `AvpList` for one device; `None` without rules or values.
+pub fn build(rules: &[AvpRule], facts: &Facts) -> Option<AttributeValuePairList> {
+    let mut list = AttributeValuePairList::default();
+    for rule in rules {
+        let Some(value) = expand(&rule.value, facts) else {
+            continue;
+        };
+        if rule.code.is_some() || rule.code_list.is_some() || rule.code_list_version.is_some() {
+            list.compound_string_avp
+                .push(CompoundStringAttributeValuePair {
+                    attribute_name: rule.name.clone(),
+                    attribute_code: rule.code.clone(),
+                    code_list_name_code: rule.code_list.clone(),
+                    code_list_version: rule.code_list_version.clone(),
+                    value,
+                });
+        } else {
+            list.string_avp.push(StringAttributeValuePair {
+                attribute_name: rule.name.clone(),
+                value,
+            });
+        }
+    }
+    (!list.string_avp.is_empty() || !list.compound_string_avp.is_empty()).then_some(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_expand_or_drop_out() {
+        let rule = |name: &str, value: &str| AvpRule {
+            name: name.into(),
+            value: value.into(),
+            ..Default::default()
+        };
+        let rules = vec![
+            rule("eudamedUuid", "{uuid}"),
+            rule("eudamedVersion", "v{version} of {uuid}"),
+            rule("basicUdi", "{basic_udi}"),
+            rule("importedBy", "eudamed2firstbase"),
+            AvpRule {
+                code_list: Some("CUSTOM_FLAG".into()),
+                ..rule("customFlag", "TRUE")
+            },
+        ];
+        let facts: &Facts = &[
+            ("uuid", Some("c0ffee")),
+            ("version", Some("3")),
+            ("basic_udi", None),
+        ];
+        let list = build(&rules, facts).unwrap();
+        let pairs: Vec<(&str, &str)> = list
+            .string_avp
+            .iter()
+            .map(|p| (p.attribute_name.as_str(), p.value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("eudamedUuid", "c0ffee"),
+                ("eudamedVersion", "v3 of c0ffee"),
+                ("importedBy", "eudamed2firstbase"),
+            ]
+        );
+        assert_eq!(
+            list.compound_string_avp[0].code_list_name_code.as_deref(),
+            Some("CUSTOM_FLAG")
+        );
+        assert!(build(&rules[..1], &[("uuid", None)]).is_none());
+        assert!(build(&[], facts).is_none());
+    }
+}
@@ -0,0 +1,263 @@
+//! State backup and restore (`backup` / `restore` subcommands).
+//! One zip archive holds what cannot be downloaded or converted again:
+//! `config.toml`, the GUI `settings.json`, the SQLite databases in `db/`
+//! (version DB with `push_log`, push sessions/errors, conversion history and
+//! archived push payloads) and the `overrides/` directory. The search index is
+//! a rebuildable cache and is left out. Databases are copied with
+//! `VACUUM INTO`, so a backup taken while a `check` or the GUI is writing is
+//! still consistent. `manifest.json` lists every entry with its SHA256; restore
+//! verifies the whole archive before writing anything.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::version_db::hash_bytes;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Directory (relative to the data directory) with per-device field overrides.
+pub const OVERRIDES_DIR: &str = "overrides";
+
+/// Databases not worth backing up: rebuilt from `firstbase_json/` on demand.
+const SKIPPED_DBS: [&str; 1] = ["search_index.db"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// Path relative to the data directory, `/`-separated.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    /// eudamed2firstbase version that wrote the archive.
+    pub version: String,
+    pub created_at: String,
+    pub files: Vec<Entry>,
+}
+
+/// Files below `dir`, recursively, sorted.
+fn files_below(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(files_below(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+fn relative(data_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(data_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Consistent copy of a (possibly WAL-mode, in-use) SQLite database.
+fn snapshot_db(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let tmp = std::env::temp_dir().join(format!(
+        "e2f_backup_{}_{}",
+        std::process::id(),
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let _ = std::fs::remove_file(&tmp);
+    let conn = rusqlite::Connection::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    conn.execute("VACUUM INTO ?1", [tmp.to_string_lossy()])
+        .with_context(|| format!("Failed to snapshot {}", path.display()))?;
+    drop(conn);
+    let bytes = std::fs::read(&tmp)?;
+    let _ = std::fs::remove_file(&tmp);
+    Ok(bytes)
+}
+
+/// Write the backup of `data_dir` to `out` (a zip file). `config_fallback` is
+/// used when the data directory has no `config.toml` of its own (the CLI reads
+/// `./config.toml`).
+pub fn create(data_dir: &Path, config_fallback: &Path, out: &Path) -> anyhow::Result<Manifest> {
+    let mut sources: Vec<(String, Vec<u8>)> = Vec::new();
+    let config = data_dir.join("config.toml");
+    let config = if config.exists() {
+        config
+    } else {
+        config_fallback.to_path_buf()
+    };
+    if config.exists() {
+        sources.push(("config.toml".to_string(), std::fs::read(&config)?));
+    }
+    let settings = data_dir.join("settings.json");
+    if settings.exists() {
+        sources.push(("settings.json".to_string(), std::fs::read(&settings)?));
+    }
+    for db in files_below(&data_dir.join("db")) {
+        let name = db.file_name().unwrap_or_default().to_string_lossy();
+        if db.extension().map(|e| e == "db").unwrap_or(false)
+            && !SKIPPED_DBS.contains(&name.as_ref())
+        {
+            sources.push((relative(data_dir, &db), snapshot_db(&db)?));
+        }
+    }
+    for file in files_below(&data_dir.join(OVERRIDES_DIR)) {
+        sources.push((relative(data_dir, &file), std::fs::read(&file)?));
+    }
+    if sources.is_empty() {
+        bail!("Nothing to back up in {}", data_dir.display());
+    }
+
+    let manifest = Manifest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        files: sources
+            .iter()
+            .map(|(path, bytes)| Entry {
+                path: path.clone(),
+                size: bytes.len() as u64,
+                sha256: hash_bytes(bytes),
+            })
+            .collect(),
+    };
+    if let Some(parent) = out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(out)
+        .with_context(|| format!("Failed to create {}", out.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+    zip.start_file(MANIFEST, options)?;
+    serde_json::to_writer_pretty(&mut zip, &manifest)?;
+    for (path, bytes) in &sources {
+        zip.start_file(path.as_str(), options)?;
+        zip.write_all(bytes)?;
+    }
+    zip.finish()?;
+    Ok(manifest)
+}
+
+/// Restore the archive `archive` into `data_dir`. Existing files that differ
+/// from the backup are only replaced with `force`; the replaced databases'
+/// `-wal`/`-shm` files are removed so SQLite does not replay them.
+pub fn restore(archive: &Path, data_dir: &Path, force: bool) -> anyhow::Result<Manifest> {
+    let file = std::fs::File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .with_context(|| format!("Not a zip archive: {}", archive.display()))?;
+    let manifest: Manifest = {
+        let mut entry = zip
+            .by_name(MANIFEST)
+            .with_context(|| format!("{} is not an eudamed2firstbase backup", archive.display()))?;
+        let mut json = String::new();
+        entry.read_to_string(&mut json)?;
+        serde_json::from_str(&json)?
+    };
+
+    // Read and verify everything first: a damaged archive must not leave a
+    // half-restored data directory behind.
+    let mut contents = Vec::new();
+    for e in &manifest.files {
+        let target = Path::new(&e.path);
+        if target.is_absolute()
+            || target
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            bail!("Unsafe path in backup manifest: {}", e.path);
+        }
+        let mut bytes = Vec::new();
+        zip.by_name(&e.path)
+            .with_context(|| format!("{} missing from archive", e.path))?
+            .read_to_end(&mut bytes)?;
+        if hash_bytes(&bytes) != e.sha256 {
+            bail!("Checksum mismatch for {} — archive is damaged", e.path);
+        }
+        contents.push((data_dir.join(target), bytes));
+    }
+
+    let conflicts: Vec<String> = contents
+        .iter()
+        .filter(|(path, bytes)| {
+            std::fs::read(path)
+                .map(|existing| existing != *bytes)
+                .unwrap_or(false)
+        })
+        .map(|(path, _)| path.display().to_string())
+        .collect();
+    if !conflicts.is_empty() && !force {
+        bail!(
+            "Restore would overwrite {} existing file(s) ({}); re-run with --force",
+            conflicts.len(),
+            conflicts.join(", ")
+        );
+    }
+
+    for (path, bytes) in &contents {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if path.extension().map(|e| e == "db").unwrap_or(false) {
+            for suffix in ["-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+            }
+        }
+        let tmp = PathBuf::from(format!("{}.restore", path.display()));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to restore {}", path.display()))?;
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_round_trip_keeps_push_log() {
+        let root = std::env::temp_dir().join(format!("backup_test_{}", std::process::id()));
+        let (src, dst) = (root.join("src"), root.join("dst"));
+        std::fs::create_dir_all(src.join("db")).unwrap();
+        std::fs::create_dir_all(src.join(OVERRIDES_DIR)).unwrap();
+        std::fs::write(src.join("config.toml"), "[provider]\n").unwrap();
+        std::fs::write(src.join(OVERRIDES_DIR).join("u1.json"), "{}").unwrap();
+        let conn = rusqlite::Connection::open(src.join("db/version_tracking.db")).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+             CREATE TABLE push_log (uuid TEXT); INSERT INTO push_log VALUES ('u1');",
+        )
+        .unwrap();
+        std::fs::write(src.join("db/search_index.db"), "cache").unwrap();
+
+        let archive = root.join("backup.zip");
+        let manifest = create(&src, &root.join("none.toml"), &archive).unwrap();
+        drop(conn);
+        assert_eq!(manifest.files.len(), 3);
+        restore(&archive, &dst, false).unwrap();
+        let restored = rusqlite::Connection::open(dst.join("db/version_tracking.db")).unwrap();
+        let uuid: String = restored
+            .query_row("SELECT uuid FROM push_log", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(uuid, "u1");
+        assert!(dst.join("overrides/u1.json").exists());
+        assert!(!dst.join("db/search_index.db").exists());
+
+        std::fs::write(dst.join("config.toml"), "changed").unwrap();
+        assert!(restore(&archive, &dst, false).is_err());
+        restore(&archive, &dst, true).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
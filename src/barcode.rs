@@ -0,0 +1,695 @@
+//! Example UDI barcode images (`barcode` subcommand) for labeling review.
+//! Builds the GS1 element string from a GTIN plus the device's production
+//! identifier types (`UDIProductionIdentifierTypeCode`) — with placeholder
+//! lot/serial/date values — and renders it as GS1 DataMatrix (ECC 200) and/or
+//! GS1-128 (Code 128 with FNC1), written as PNG or SVG.
+//!
+//! Both encoders are self-contained: ASCII encodation + Reed-Solomon over
+//! GF(256) for DataMatrix (square symbols up to 52x52, i.e. 204 data
+//! codewords — ample for a UDI), code sets B/C for Code 128.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use rayon::prelude::*;
+
+use crate::report::{array_at, base_unit, firstbase_docs, str_at};
+
+/// Group separator: FNC1 after a variable-length AI that is not last.
+const GS: char = '\u{1d}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbology {
+    DataMatrix,
+    Gs1_128,
+}
+
+impl Symbology {
+    fn file_suffix(self) -> &'static str {
+        match self {
+            Symbology::DataMatrix => "datamatrix",
+            Symbology::Gs1_128 => "gs1-128",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+/// GS1 Application Identifiers for a GTIN and its production identifier
+/// types, fixed-length AIs first. Values other than the GTIN are examples.
+pub fn element_fields(gtin: &str, pi_types: &[String]) -> Vec<(&'static str, String)> {
+    let today = chrono::Local::now().date_naive();
+    let mut fields = vec![("01", gtin.to_string())];
+    let has = |code: &str| pi_types.iter().any(|p| p == code);
+    if has("MANUFACTURING_DATE") {
+        fields.push(("11", today.format("%y%m%d").to_string()));
+    }
+    if has("EXPIRATION_DATE") {
+        let expiry = today + chrono::Duration::days(730);
+        fields.push(("17", expiry.format("%y%m%d").to_string()));
+    }
+    if has("BATCH_NUMBER") {
+        fields.push(("10", "LOT0001".to_string()));
+    }
+    if has("SERIAL_NUMBER") {
+        fields.push(("21", "SN000001".to_string()));
+    }
+    if has("SOFTWARE_IDENTIFICATION") {
+        fields.push(("8012", "1.0.0".to_string()));
+    }
+    fields
+}
+
+/// Human-readable interpretation, e.g. `(01)08800042702694(10)LOT0001`.
+pub fn human_readable(fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .map(|(ai, v)| format!("({}){}", ai, v))
+        .collect()
+}
+
+/// Encoded data (without the leading FNC1): AIs concatenated, GS after each
+/// variable-length field that is followed by another field.
+fn element_data(fields: &[(&str, String)]) -> String {
+    let mut data = String::new();
+    for (i, (ai, value)) in fields.iter().enumerate() {
+        data.push_str(ai);
+        data.push_str(value);
+        let fixed = matches!(*ai, "01" | "11" | "17");
+        if !fixed && i + 1 < fields.len() {
+            data.push(GS);
+        }
+    }
+    data
+}
+
+// --- GS1 DataMatrix (ECC 200) -------------------------------------------------
+
+/// Square ECC 200 symbols: (symbol size, data region size, regions per side,
+/// data codewords, error correction codewords, interleaved blocks).
+const DM_SIZES: &[(usize, usize, usize, usize, usize, usize)] = &[
+    (10, 8, 1, 3, 5, 1),
+    (12, 10, 1, 5, 7, 1),
+    (14, 12, 1, 8, 10, 1),
+    (16, 14, 1, 12, 12, 1),
+    (18, 16, 1, 18, 14, 1),
+    (20, 18, 1, 22, 18, 1),
+    (22, 20, 1, 30, 20, 1),
+    (24, 22, 1, 36, 24, 1),
+    (26, 24, 1, 44, 28, 1),
+    (32, 14, 2, 62, 36, 1),
+    (36, 16, 2, 86, 42, 1),
+    (40, 18, 2, 114, 48, 1),
+    (44, 20, 2, 144, 56, 1),
+    (48, 22, 2, 174, 68, 1),
+    (52, 24, 2, 204, 84, 2),
+];
+
+/// ASCII encodation: digit pairs → 130+nn, FNC1 → 232, ASCII → c+1.
+fn dm_ascii_codewords(data: &str, gs1: bool) -> Vec<u8> {
+    let bytes = data.as_bytes();
+    let mut out = Vec::new();
+    if gs1 {
+        out.push(232);
+    }
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b.is_ascii_digit() && i + 1 < bytes.len() && bytes[i + 1].is_ascii_digit() {
+            out.push(130 + (b - b'0') * 10 + (bytes[i + 1] - b'0'));
+            i += 2;
+            continue;
+        }
+        if b == GS as u8 {
+            out.push(232);
+        } else if b < 128 {
+            out.push(b + 1);
+        } else {
+            out.push(235);
+            out.push(b - 127);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// GF(256) exp/log tables for the DataMatrix field polynomial 0x12D.
+fn gf_tables() -> ([u8; 255], [u8; 256]) {
+    let mut exp = [0u8; 255];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    for (i, e) in exp.iter_mut().enumerate() {
+        *e = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x >= 256 {
+            x ^= 0x12D;
+        }
+    }
+    (exp, log)
+}
+
+fn gf_mul(a: u8, b: u8, exp: &[u8; 255], log: &[u8; 256]) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    exp[(log[a as usize] as usize + log[b as usize] as usize) % 255]
+}
+
+/// Reed-Solomon check codewords; generator roots α^1..α^n.
+fn reed_solomon(data: &[u8], n: usize) -> Vec<u8> {
+    let (exp, log) = gf_tables();
+    // Generator coefficients, highest degree first (gen[0] = 1).
+    let mut gen = vec![1u8];
+    for i in 1..=n {
+        let root = exp[i % 255];
+        let mut next = vec![0u8; gen.len() + 1];
+        for (j, &g) in gen.iter().enumerate() {
+            next[j] ^= g;
+            next[j + 1] ^= gf_mul(g, root, &exp, &log);
+        }
+        gen = next;
+    }
+    let mut ecc = vec![0u8; n];
+    for &d in data {
+        let m = d ^ ecc[0];
+        ecc.rotate_left(1);
+        ecc[n - 1] = 0;
+        for j in 0..n {
+            ecc[j] ^= gf_mul(m, gen[j + 1], &exp, &log);
+        }
+    }
+    ecc
+}
+
+/// Codewords for one symbol size: data, pad, then interleaved ECC.
+fn dm_codewords(mut data: Vec<u8>, data_cw: usize, ecc_cw: usize, blocks: usize) -> Vec<u8> {
+    if data.len() < data_cw {
+        data.push(129);
+    }
+    while data.len() < data_cw {
+        let pos = data.len() + 1;
+        let mut pad = 129 + (149 * pos) % 253 + 1;
+        if pad > 254 {
+            pad -= 254;
+        }
+        data.push(pad as u8);
+    }
+    let mut all = data.clone();
+    all.resize(data_cw + ecc_cw, 0);
+    for b in 0..blocks {
+        let block: Vec<u8> = data.iter().skip(b).step_by(blocks).copied().collect();
+        let ecc = reed_solomon(&block, ecc_cw / blocks);
+        for (j, e) in ecc.into_iter().enumerate() {
+            all[data_cw + b + j * blocks] = e;
+        }
+    }
+    all
+}
+
+/// ECC 200 module placement (ISO/IEC 16022 Annex F) into the mapping matrix.
+struct Placement<'a> {
+    rows: usize,
+    cols: usize,
+    codewords: &'a [u8],
+    bits: Vec<Option<bool>>,
+}
+
+impl Placement<'_> {
+    fn module(&mut self, mut row: isize, mut col: isize, pos: usize, bit: u8) {
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        if row < 0 {
+            row += rows;
+            col += 4 - ((rows + 4) % 8);
+        }
+        if col < 0 {
+            col += cols;
+            row += 4 - ((cols + 4) % 8);
+        }
+        let v = (self.codewords[pos] >> (8 - bit)) & 1 == 1;
+        self.bits[row as usize * self.cols + col as usize] = Some(v);
+    }
+
+    fn utah(&mut self, r: isize, c: isize, pos: usize) {
+        self.module(r - 2, c - 2, pos, 1);
+        self.module(r - 2, c - 1, pos, 2);
+        self.module(r - 1, c - 2, pos, 3);
+        self.module(r - 1, c - 1, pos, 4);
+        self.module(r - 1, c, pos, 5);
+        self.module(r, c - 2, pos, 6);
+        self.module(r, c - 1, pos, 7);
+        self.module(r, c, pos, 8);
+    }
+
+    fn corner(&mut self, cells: [(isize, isize); 8], pos: usize) {
+        for (i, (r, c)) in cells.into_iter().enumerate() {
+            self.module(r, c, pos, i as u8 + 1);
+        }
+    }
+
+    fn place(mut self) -> Vec<bool> {
+        let (nr, nc) = (self.rows as isize, self.cols as isize);
+        let (mut pos, mut row, mut col) = (0usize, 4isize, 0isize);
+        loop {
+            if row == nr && col == 0 {
+                let cells = [
+                    (nr - 1, 0),
+                    (nr - 1, 1),
+                    (nr - 1, 2),
+                    (0, nc - 2),
+                    (0, nc - 1),
+                    (1, nc - 1),
+                    (2, nc - 1),
+                    (3, nc - 1),
+                ];
+                self.corner(cells, pos);
+                pos += 1;
+            }
+            if row == nr - 2 && col == 0 && nc % 4 != 0 {
+                let cells = [
+                    (nr - 3, 0),
+                    (nr - 2, 0),
+                    (nr - 1, 0),
+                    (0, nc - 4),
+                    (0, nc - 3),
+                    (0, nc - 2),
+                    (0, nc - 1),
+                    (1, nc - 1),
+                ];
+                self.corner(cells, pos);
+                pos += 1;
+            }
+            if row == nr - 2 && col == 0 && nc % 8 == 4 {
+                let cells = [
+                    (nr - 3, 0),
+                    (nr - 2, 0),
+                    (nr - 1, 0),
+                    (0, nc - 2),
+                    (0, nc - 1),
+                    (1, nc - 1),
+                    (2, nc - 1),
+                    (3, nc - 1),
+                ];
+                self.corner(cells, pos);
+                pos += 1;
+            }
+            if row == nr + 4 && col == 2 && nc % 8 == 0 {
+                let cells = [
+                    (nr - 1, 0),
+                    (nr - 1, nc - 1),
+                    (0, nc - 3),
+                    (0, nc - 2),
+                    (0, nc - 1),
+                    (1, nc - 3),
+                    (1, nc - 2),
+                    (1, nc - 1),
+                ];
+                self.corner(cells, pos);
+                pos += 1;
+            }
+            // Sweep up-right.
+            loop {
+                if row < nr && col >= 0 && self.bits[(row * nc + col) as usize].is_none() {
+                    self.utah(row, col, pos);
+                    pos += 1;
+                }
+                row -= 2;
+                col += 2;
+                if !(row >= 0 && col < nc) {
+                    break;
+                }
+            }
+            row += 1;
+            col += 3;
+            // Sweep down-left.
+            loop {
+                if row >= 0 && col < nc && self.bits[(row * nc + col) as usize].is_none() {
+                    self.utah(row, col, pos);
+                    pos += 1;
+                }
+                row += 2;
+                col -= 2;
+                if !(row < nr && col >= 0) {
+                    break;
+                }
+            }
+            row += 3;
+            col += 1;
+            if !(row < nr || col < nc) {
+                break;
+            }
+        }
+        // Fixed pattern in the bottom-right corner when unused.
+        let last = self.bits.len() - 1;
+        if self.bits[last].is_none() {
+            self.bits[last] = Some(true);
+            self.bits[last - self.cols - 1] = Some(true);
+        }
+        self.bits.into_iter().map(|b| b.unwrap_or(false)).collect()
+    }
+}
+
+/// Encode `data` (GS1 element string, GS as separator) as a square ECC 200
+/// symbol. Returns the module grid, `true` = dark, without quiet zone.
+pub fn datamatrix(data: &str, gs1: bool) -> anyhow::Result<Vec<Vec<bool>>> {
+    let cw = dm_ascii_codewords(data, gs1);
+    let Some(&(size, region, per_side, data_cw, ecc_cw, blocks)) =
+        DM_SIZES.iter().find(|s| s.3 >= cw.len())
+    else {
+        bail!(
+            "Data too long for DataMatrix ({} codewords): {}",
+            cw.len(),
+            data
+        );
+    };
+    let codewords = dm_codewords(cw, data_cw, ecc_cw, blocks);
+    let map_size = region * per_side;
+    let mapping = Placement {
+        rows: map_size,
+        cols: map_size,
+        codewords: &codewords,
+        bits: vec![None; map_size * map_size],
+    }
+    .place();
+
+    // Wrap each data region in its finder (solid left/bottom) and clock
+    // (alternating top/right) pattern.
+    let mut grid = vec![vec![false; size]; size];
+    for (y, row) in grid.iter_mut().enumerate() {
+        let (ry, iy) = (y / (region + 2), y % (region + 2));
+        for (x, cell) in row.iter_mut().enumerate() {
+            let (rx, ix) = (x / (region + 2), x % (region + 2));
+            *cell = if ix == 0 || iy == region + 1 {
+                true
+            } else if iy == 0 {
+                x % 2 == 0
+            } else if ix == region + 1 {
+                y % 2 == 1
+            } else {
+                let my = ry * region + iy - 1;
+                let mx = rx * region + ix - 1;
+                mapping[my * map_size + mx]
+            };
+        }
+    }
+    Ok(grid)
+}
+
+// --- GS1-128 (Code 128) -------------------------------------------------------
+
+/// Bar/space widths of Code 128 symbol values 0..=105, each 11 modules.
+const C128_PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232",
+];
+const C128_STOP: &str = "2331112";
+const C128_FNC1: usize = 102;
+const C128_CODE_B: usize = 100;
+const C128_CODE_C: usize = 99;
+const C128_START_B: usize = 104;
+const C128_START_C: usize = 105;
+
+fn digit_run(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// Symbol values for `data` with a leading FNC1, switching between code
+/// sets B and C to keep digit runs compact.
+fn c128_values(data: &str) -> anyhow::Result<Vec<usize>> {
+    let bytes = data.as_bytes();
+    if let Some(b) = bytes
+        .iter()
+        .find(|b| **b != GS as u8 && !(32..127).contains(*b))
+    {
+        bail!("Character not encodable in GS1-128: 0x{:02x}", b);
+    }
+    let mut in_c = digit_run(bytes) >= 2;
+    let mut values = vec![if in_c { C128_START_C } else { C128_START_B }, C128_FNC1];
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == GS as u8 {
+            values.push(C128_FNC1);
+            i += 1;
+            continue;
+        }
+        let run = digit_run(&bytes[i..]);
+        if in_c {
+            if run >= 2 {
+                values.push(((bytes[i] - b'0') * 10 + (bytes[i + 1] - b'0')) as usize);
+                i += 2;
+            } else {
+                values.push(C128_CODE_B);
+                in_c = false;
+            }
+        } else if run >= 4 && run.is_multiple_of(2) {
+            values.push(C128_CODE_C);
+            in_c = true;
+        } else {
+            values.push((bytes[i] - 32) as usize);
+            i += 1;
+        }
+    }
+    let checksum = values[0]
+        + values[1..]
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i + 1) * v)
+            .sum::<usize>();
+    values.push(checksum % 103);
+    Ok(values)
+}
+
+/// Encode `data` as GS1-128. Returns module colors (`true` = bar), without
+/// quiet zone.
+pub fn gs1_128(data: &str) -> anyhow::Result<Vec<bool>> {
+    let mut modules = Vec::new();
+    let values = c128_values(data)?;
+    let patterns = values.iter().map(|v| C128_PATTERNS[*v]).chain([C128_STOP]);
+    for pattern in patterns {
+        for (i, w) in pattern.bytes().enumerate() {
+            let bar = i % 2 == 0;
+            modules.extend(std::iter::repeat_n(bar, (w - b'0') as usize));
+        }
+    }
+    Ok(modules)
+}
+
+// --- Rendering ----------------------------------------------------------------
+
+/// Module grid (rows of dark/light) + caption → PNG or SVG file.
+fn render(
+    grid: &[Vec<bool>],
+    module_px: u32,
+    module_h: u32,
+    quiet: u32,
+    caption: &str,
+    format: ImageFormat,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let cols = grid.first().map(|r| r.len()).unwrap_or(0) as u32;
+    let rows = grid.len() as u32;
+    let width = (cols + 2 * quiet) * module_px;
+    let height = (rows * module_h + 2 * quiet) * module_px;
+    match format {
+        ImageFormat::Png => {
+            let mut img = image::GrayImage::from_pixel(width, height, image::Luma([255]));
+            for (y, row) in grid.iter().enumerate() {
+                for (x, &dark) in row.iter().enumerate() {
+                    if !dark {
+                        continue;
+                    }
+                    let x0 = (x as u32 + quiet) * module_px;
+                    let y0 = (y as u32 * module_h + quiet) * module_px;
+                    for py in y0..y0 + module_h * module_px {
+                        for px in x0..x0 + module_px {
+                            img.put_pixel(px, py, image::Luma([0]));
+                        }
+                    }
+                }
+            }
+            img.save(path)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        ImageFormat::Svg => {
+            let text_h = 16;
+            let mut svg = format!(
+                "<svg xmlns='http://www.w3.org/2000/svg' width='{w}' height='{h}' viewBox='0 0 {w} {h}' shape-rendering='crispEdges'>\
+                 <rect width='{w}' height='{h}' fill='#fff'/>",
+                w = width,
+                h = height + text_h,
+            );
+            for (y, row) in grid.iter().enumerate() {
+                for (x, &dark) in row.iter().enumerate() {
+                    if dark {
+                        svg.push_str(&format!(
+                            "<rect x='{}' y='{}' width='{}' height='{}'/>",
+                            (x as u32 + quiet) * module_px,
+                            (y as u32 * module_h + quiet) * module_px,
+                            module_px,
+                            module_h * module_px
+                        ));
+                    }
+                }
+            }
+            svg.push_str(&format!(
+                "<text x='{}' y='{}' font-family='monospace' font-size='12' text-anchor='middle'>{}</text></svg>",
+                width / 2,
+                height + text_h - 4,
+                caption
+            ));
+            std::fs::write(path, svg)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write example barcodes for one GTIN into `out_dir` as
+/// `<gtin>_<symbology>.<png|svg>`. Returns the written paths.
+pub fn write_barcodes(
+    gtin: &str,
+    pi_types: &[String],
+    symbologies: &[Symbology],
+    format: ImageFormat,
+    out_dir: &Path,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let gtin = crate::gtin::normalize(gtin)?;
+    let fields = element_fields(&gtin, pi_types);
+    let data = element_data(&fields);
+    let caption = human_readable(&fields);
+    let ext = match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Svg => "svg",
+    };
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let mut written = Vec::new();
+    for &symbology in symbologies {
+        let path = out_dir.join(format!("{}_{}.{}", gtin, symbology.file_suffix(), ext));
+        match symbology {
+            Symbology::DataMatrix => {
+                let grid = datamatrix(&data, true)?;
+                render(&grid, 8, 1, 2, &caption, format, &path)?;
+            }
+            Symbology::Gs1_128 => {
+                let grid = vec![gs1_128(&data)?];
+                render(&grid, 2, 40, 10, &caption, format, &path)?;
+            }
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Base-unit GTIN and production identifier types of a firstbase document.
+pub fn doc_udi(doc: &serde_json::Value) -> (String, Vec<String>) {
+    let base = base_unit(doc);
+    let pi_types = array_at(
+        base,
+        "/MedicalDeviceTradeItemModule/MedicalDeviceInformation/UDIProductionIdentifierTypeCode",
+    )
+    .iter()
+    .map(|p| str_at(p, "/Value").to_string())
+    .filter(|s| !s.is_empty())
+    .collect();
+    (str_at(base, "/Gtin").to_string(), pi_types)
+}
+
+/// Production identifier types recorded for `gtin` in the converted
+/// documents (any packaging level), or `None` when no document has it.
+pub fn lookup_pi_types(
+    firstbase_dir: &Path,
+    include_processed: bool,
+    gtin: &str,
+) -> Option<Vec<String>> {
+    let files = firstbase_docs(firstbase_dir, include_processed);
+    files.par_iter().find_map_any(|p| {
+        let content = std::fs::read_to_string(p).ok()?;
+        if !content.contains(gtin) {
+            return None;
+        }
+        let doc: serde_json::Value = serde_json::from_str(&content).ok()?;
+        let (base_gtin, pi_types) = doc_udi(&doc);
+        let top_gtin = str_at(&doc, "/DraftItem/TradeItem/Gtin");
+        (base_gtin == gtin || top_gtin == gtin).then_some(pi_types)
+    })
+}
+
+/// Bulk export: one set of barcodes per converted document (base-unit GTIN).
+/// Returns (devices written, devices skipped for a non-GS1 / invalid GTIN).
+pub fn export_all(
+    firstbase_dir: &Path,
+    include_processed: bool,
+    symbologies: &[Symbology],
+    format: ImageFormat,
+    out_dir: &Path,
+) -> anyhow::Result<(usize, usize)> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let files = firstbase_docs(firstbase_dir, include_processed);
+    let results: Vec<bool> = files
+        .par_iter()
+        .filter_map(|p| {
+            let content = std::fs::read_to_string(p).ok()?;
+            let doc: serde_json::Value = serde_json::from_str(&content).ok()?;
+            let (gtin, pi_types) = doc_udi(&doc);
+            Some(write_barcodes(&gtin, &pi_types, symbologies, format, out_dir).is_ok())
+        })
+        .collect();
+    let ok = results.iter().filter(|r| **r).count();
+    Ok((ok, results.len() - ok))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datamatrix_reed_solomon_matches_iso_example() {
+        // ISO/IEC 16022 Annex O: "123456" → 10x10, data 142 164 186.
+        let cw = dm_ascii_codewords("123456", false);
+        assert_eq!(cw, vec![142, 164, 186]);
+        assert_eq!(reed_solomon(&cw, 5), vec![114, 25, 5, 88, 102]);
+        let grid = datamatrix("123456", false).unwrap();
+        assert_eq!(grid.len(), 10);
+        // Finder: solid left column and bottom row.
+        assert!(grid.iter().all(|r| r[0]));
+        assert!(grid[9].iter().all(|m| *m));
+    }
+
+    #[test]
+    fn gs1_128_patterns_and_checksum() {
+        assert!(C128_PATTERNS
+            .iter()
+            .all(|p| p.bytes().map(|b| (b - b'0') as u32).sum::<u32>() == 11));
+        // (01)08800042702694: Start C, FNC1, 7 digit pairs, check, stop.
+        let values = c128_values("0108800042702694").unwrap();
+        assert_eq!(values[..2], [C128_START_C, C128_FNC1]);
+        assert_eq!(values.len(), 2 + 8 + 1);
+        assert_eq!(gs1_128("0108800042702694").unwrap().len(), 11 * 11 + 13);
+    }
+
+    #[test]
+    fn element_string_separates_variable_fields() {
+        let pi = vec!["SERIAL_NUMBER".to_string(), "BATCH_NUMBER".to_string()];
+        let fields = element_fields("08800042702694", &pi);
+        let data = element_data(&fields);
+        assert_eq!(data, "010880004270269410LOT0001\u{1d}21SN000001");
+        assert!(crate::gtin::normalize("8800042702694").is_ok());
+        assert!(crate::gtin::normalize("08800042702695").is_err());
+    }
+}
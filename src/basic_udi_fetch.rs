@@ -0,0 +1,132 @@
+//! `--fetch-missing` for the NDJSON detail modes: a detail record whose Basic
+//! UDI-DI is in neither the cache nor the export is completed from EUDAMED
+//! while converting (implantable, measuring function, active, reusable,
+//! tissue flags, risk class, legislation, manufacturer, AR).
+//!
+//! Fetched once per Basic UDI-DI, not per device: the base unit's listing row
+//! gives the Basic UDI-DI code, and every UDI-DI of that code shares the
+//! record the first one fetched (the others wait for it instead of asking
+//! EUDAMED again; a failed fetch is not repeated for the code). Devices
+//! without a listing row are fetched on their own.
+//! The conversion threads share one rate limiter, and each fetched record is
+//! cached in `eudamed_json/basic/` under the UUID it was fetched for.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::api_detail::BasicUdiDiData;
+use crate::download;
+
+type Record = Option<Arc<BasicUdiDiData>>;
+type Fetch = Box<dyn Fn(&str) -> Option<BasicUdiDiData> + Send + Sync>;
+
+pub struct BasicUdiFetcher {
+    fetch: Fetch,
+    cache_dir: &'static str,
+    by_code: Mutex<HashMap<String, Arc<OnceLock<Record>>>>,
+    fetched: AtomicUsize,
+    shared: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl BasicUdiFetcher {
+    /// Fetch from EUDAMED (`basicUdiData/udiDiData/<uuid>`) at the EUDAMED
+    /// pace of `[http]`, caching in `cache_dir`.
+    pub fn new(cache_dir: &'static str) -> Self {
+        let interval = download::DownloadConfig::default().rate_interval_ms;
+        let limiter = download::RateLimiter::new(std::time::Duration::from_millis(interval));
+        Self::with_fetch(
+            cache_dir,
+            Box::new(move |uuid| {
+                limiter.acquire();
+                crate::fetch_basic_udi_di(uuid, std::path::Path::new(cache_dir))
+            }),
+        )
+    }
+
+    fn with_fetch(cache_dir: &'static str, fetch: Fetch) -> Self {
+        BasicUdiFetcher {
+            fetch,
+            cache_dir,
+            by_code: Mutex::new(HashMap::new()),
+            fetched: Default::default(),
+            shared: Default::default(),
+            failed: Default::default(),
+        }
+    }
+
+    fn fetch_counted(&self, uuid: &str) -> Record {
+        let data = (self.fetch)(uuid).map(Arc::new);
+        let counter = if data.is_some() {
+            &self.fetched
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        data
+    }
+
+    /// The Basic UDI-DI record of device `uuid`, whose listing row names
+    /// `basic_udi_code` (if it has one).
+    pub fn get(&self, basic_udi_code: Option<&str>, uuid: &str) -> Record {
+        let Some(code) = basic_udi_code.filter(|c| !c.is_empty()) else {
+            return self.fetch_counted(uuid);
+        };
+        let slot = {
+            let mut by_code = self.by_code.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(by_code.entry(code.to_string()).or_default())
+        };
+        let mut first = false;
+        let record = slot.get_or_init(|| {
+            first = true;
+            self.fetch_counted(uuid)
+        });
+        if !first && record.is_some() {
+            self.shared.fetch_add(1, Ordering::Relaxed);
+        }
+        record.clone()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Basic UDI-DI fetched on demand: {} ({} more devices sharing one, {} not available) -> {}",
+            self.fetched.load(Ordering::Relaxed),
+            self.shared.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+            self.cache_dir
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_fetch_per_basic_udi() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&calls);
+        let fetcher = BasicUdiFetcher::with_fetch(
+            "basic",
+            Box::new(move |uuid| {
+                log.lock().unwrap().push(uuid.to_string());
+                (uuid != "u-missing").then(|| {
+                    crate::api_detail::parse_basic_udi_di(&format!(r#"{{"uuid":"{}"}}"#, uuid))
+                        .unwrap()
+                })
+            }),
+        );
+
+        let a = fetcher.get(Some("BUDI-1"), "u-1").unwrap();
+        let b = fetcher.get(Some("BUDI-1"), "u-2").unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(fetcher.get(Some("BUDI-2"), "u-missing").is_none());
+        assert!(fetcher.get(Some("BUDI-2"), "u-3").is_none());
+        assert!(fetcher.get(None, "u-4").is_some());
+        assert_eq!(*calls.lock().unwrap(), ["u-1", "u-missing", "u-4"]);
+        assert!(fetcher.summary().starts_with(
+            "Basic UDI-DI fetched on demand: 2 (1 more devices sharing one, 1 not available)"
+        ));
+    }
+}
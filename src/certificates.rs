@@ -0,0 +1,224 @@
+//! Notified body certificates of a Basic UDI-DI
+//! (`deviceCertificateInfoListForDisplay`) in the documents: the permit of
+//! `RegulatoryInformation` (number, notified body, validity) and the
+//! `CertificationInformationModule` with every certificate. Shared by the
+//! detail path (Basic UDI-DI record) and the EUDAMED JSON path (the record
+//! itself); the XML and listing inputs carry no certificates.
+
+use crate::api_detail::DeviceCertificate;
+use crate::firstbase::{
+    AdditionalPartyIdentification, Certification, CertificationInformation,
+    CertificationInformationModule, RegulatoryInformation,
+};
+
+/// The certificate behind `RegulatoryInformation`'s permit fields: of those
+/// with a number, preferably one under `reg_act`, then a valid one, then the
+/// latest expiry.
+pub fn permit<'a>(certs: &'a [DeviceCertificate], reg_act: &str) -> Option<&'a DeviceCertificate> {
+    let act = reg_act.to_ascii_lowercase();
+    let code = |r: &Option<crate::api_detail::RefCode>| {
+        r.as_ref()
+            .and_then(|r| r.code.as_deref())
+            .unwrap_or_default()
+            .to_string()
+    };
+    certs
+        .iter()
+        .filter(|c| {
+            c.certificate_number
+                .as_deref()
+                .is_some_and(|n| !n.is_empty())
+        })
+        .max_by_key(|c| {
+            (
+                code(&c.certificate_type).contains(&act),
+                code(&c.status).ends_with(".valid"),
+                c.certificate_expiry.clone(),
+            )
+        })
+}
+
+/// `RegulatoryInformation` under `reg_act` with the permit fields of its
+/// certificate (`permit`), if any.
+pub fn regulatory_information(reg_act: &str, certs: &[DeviceCertificate]) -> RegulatoryInformation {
+    let permit = permit(certs, reg_act);
+    RegulatoryInformation {
+        act: reg_act.to_string(),
+        agency: "EU".to_string(),
+        permit_identification: permit.and_then(|c| c.certificate_number.clone()),
+        agency_identification: permit
+            .and_then(|c| c.notified_body.as_ref()?.srn.clone())
+            .map(|srn| {
+                vec![AdditionalPartyIdentification {
+                    type_code: "EU_NOTIFIED_BODY_NUMBER".to_string(),
+                    value: srn,
+                }]
+            })
+            .unwrap_or_default(),
+        permit_start: permit.and_then(|c| {
+            c.starting_validity_date
+                .clone()
+                .or_else(|| c.issue_date.clone())
+        }),
+        permit_end: permit.and_then(|c| c.certificate_expiry.clone()),
+    }
+}
+
+/// One `CertificationInformation` per certificate of a known type: the
+/// EUDAMED certificate type mapped to the GS1 CertificationStandard code,
+/// the notified body, number, revision and validity.
+pub fn certification_module(certs: &[DeviceCertificate]) -> Option<CertificationInformationModule> {
+    let mut infos = Vec::new();
+
+    for cert in certs {
+        let type_code = cert.certificate_type.as_ref()?.code.as_ref()?;
+        let suffix = type_code.rsplit('.').next().unwrap_or(type_code);
+
+        // Map EUDAMED certificate types to GS1 CertificationStandard
+        // DeviceCertificateInfo (manufacturer-provided) + CertificateLink (NB-provided)
+        let standard = match suffix {
+            "technical-documentation" => {
+                if type_code.contains("mdr") {
+                    "MDR_TECHNICAL_DOCUMENTATION"
+                } else if type_code.contains("ivdr") {
+                    "IVDR_TECHNICAL_DOCUMENTATION"
+                } else {
+                    continue;
+                }
+            }
+            "type-examination" => {
+                if type_code.contains("mdr") {
+                    "MDR_TYPE_EXAMINATION"
+                } else if type_code.contains("ivdr") {
+                    "IVDR_TYPE_EXAMINATION"
+                } else {
+                    continue;
+                }
+            }
+            // NB-provided MDR/IVDR certificates (CertificateLink: FLD-UDID-360)
+            "quality-management-system" => {
+                if type_code.contains("mdr") {
+                    "MDR_QUALITY_MANAGEMENT_SYSTEM"
+                } else if type_code.contains("ivdr") {
+                    "IVDR_QUALITY_MANAGEMENT_SYSTEM"
+                } else {
+                    continue;
+                }
+            }
+            "quality-assurance" => {
+                if type_code.contains("mdr") {
+                    "MDR_QUALITY_ASSURANCE"
+                } else if type_code.contains("ivdr") {
+                    "IVDR_QUALITY_ASSURANCE"
+                } else {
+                    continue;
+                }
+            }
+            // MDD legacy certificates (097.105)
+            "ii-4" => "MDD_II_4",
+            "ii-excluding-4" => "MDD_II_EX_4",
+            "iii" if type_code.contains("mdd") => "MDD_III",
+            "iv" => "MDD_IV",
+            "v" => "MDD_V",
+            "vi" => "MDD_VI",
+            _ => continue,
+        };
+
+        let nb = cert.notified_body.as_ref();
+        let nb_number = nb.and_then(|n| n.srn.clone());
+        infos.push(CertificationInformation {
+            // 097.042: additionalCertificationOrganisationIdentifier with EU_NOTIFIED_BODY_NUMBER
+            additional_org_ids: nb_number
+                .map(|num| {
+                    vec![AdditionalPartyIdentification {
+                        type_code: "EU_NOTIFIED_BODY_NUMBER".to_string(),
+                        value: num,
+                    }]
+                })
+                .unwrap_or_default(),
+            agency: nb.and_then(|n| n.name.clone()),
+            organisation_identifier: None,
+            standard: standard.to_string(),
+            certifications: {
+                let mut cs = Vec::new();
+                // FLD-UDID-347/346: startingValidityDate, fallback to issueDate
+                let start = cert
+                    .starting_validity_date
+                    .clone()
+                    .or_else(|| cert.issue_date.clone());
+                if cert.certificate_number.is_some()
+                    || cert.certificate_expiry.is_some()
+                    || start.is_some()
+                {
+                    cs.push(Certification {
+                        // FLD-UDID-61/344 (097.105): CertificationValue = certificate number
+                        value: cert.certificate_number.clone(),
+                        // FLD-UDID-62/345: CertificationIdentification = revision number
+                        identification: cert.certificate_revision.clone(),
+                        // FLD-UDID-64/348: CertificationEffectiveEndDateTime = expiry date
+                        effective_end: cert.certificate_expiry.clone(),
+                        effective_start: start,
+                    });
+                }
+                cs
+            },
+        });
+    }
+
+    if infos.is_empty() {
+        None
+    } else {
+        Some(CertificationInformationModule { infos })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permit_prefers_the_act_then_valid_certificates() {
+        let certs: Vec<DeviceCertificate> = serde_json::from_str(
+            r#"[
+                {"certificateNumber": "MDD-1", "certificateExpiry": "2030-01-01",
+                 "certificateType": {"code": "refdata.certificate-type.mdd.ii-4"},
+                 "notifiedBody": {"name": "NB One", "srn": "0123"}},
+                {"certificateNumber": "MDR-OLD", "certificateExpiry": "2026-01-01",
+                 "certificateType": {"code": "refdata.certificate-type.mdr.technical-documentation"},
+                 "status": {"code": "refdata.certificate-status.expired"}},
+                {"certificateNumber": "MDR-2", "certificateExpiry": "2029-05-26",
+                 "certificateType": {"code": "refdata.certificate-type.mdr.quality-management-system"},
+                 "notifiedBody": {"name": "NB Two", "srn": "2797"},
+                 "startingValidityDate": "2024-05-27",
+                 "status": {"code": "refdata.certificate-status.valid"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let mdr = regulatory_information("MDR", &certs);
+        assert_eq!(mdr.permit_identification.as_deref(), Some("MDR-2"));
+        assert_eq!(mdr.agency_identification[0].value, "2797");
+        assert_eq!(mdr.permit_start.as_deref(), Some("2024-05-27"));
+        assert_eq!(mdr.permit_end.as_deref(), Some("2029-05-26"));
+        assert_eq!(
+            permit(&certs, "MDD").and_then(|c| c.certificate_number.as_deref()),
+            Some("MDD-1")
+        );
+        assert!(regulatory_information("MDR", &[])
+            .permit_identification
+            .is_none());
+
+        let module = certification_module(&certs).unwrap();
+        let standards: Vec<_> = module.infos.iter().map(|i| i.standard.as_str()).collect();
+        assert_eq!(
+            standards,
+            [
+                "MDD_II_4",
+                "MDR_TECHNICAL_DOCUMENTATION",
+                "MDR_QUALITY_MANAGEMENT_SYSTEM"
+            ]
+        );
+        assert_eq!(module.infos[0].agency.as_deref(), Some("NB One"));
+        assert!(certification_module(&[]).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+//! Command layer shared by the GUI and the CLI. Every action of the GUI Run
+//! button is a numbered mode of `gui::run_pipeline` (the number is also what
+//! the job queue stores); `ACTIONS` names them for the `run` subcommand, which
+//! drives the same worker headlessly with the GUI's saved settings
+//! (`gui::run_headless`). Validation goes through `validate_doc` and
+//! `validation::scan` in both front ends.
+
+use std::path::Path;
+
+/// What an action works on besides the saved settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// The SRNs of the settings, or `--srn`.
+    Srns,
+    /// Device UUIDs (`--uuids` / `--uuid-file`).
+    Uuids,
+    /// Nothing.
+    None,
+}
+
+#[derive(Debug)]
+pub struct Action {
+    /// `gui::run_pipeline` mode.
+    pub mode: u8,
+    /// Name on the command line (`run <name>`).
+    pub name: &'static str,
+    /// Name in the job queue and the log.
+    pub label: &'static str,
+    pub input: Input,
+}
+
+pub const ACTIONS: [Action; 10] = [
+    Action {
+        mode: 0,
+        name: "pipeline",
+        label: "full pipeline (download + convert + push)",
+        input: Input::Srns,
+    },
+    Action {
+        mode: 1,
+        name: "convert-push",
+        label: "convert & push (all existing files)",
+        input: Input::Srns,
+    },
+    Action {
+        mode: 2,
+        name: "convert-push-srn",
+        label: "convert & push (SRN filter)",
+        input: Input::Srns,
+    },
+    Action {
+        mode: 3,
+        name: "repush-failed",
+        label: "repush failed (from last push session)",
+        input: Input::None,
+    },
+    Action {
+        mode: 4,
+        name: "repush-srn",
+        label: "repush SRN (restore from processed/ + push)",
+        input: Input::Srns,
+    },
+    Action {
+        mode: 5,
+        name: "reconvert-repush-srn",
+        label: "reconvert + repush SRN (force-reconvert from detail/)",
+        input: Input::Srns,
+    },
+    Action {
+        mode: 6,
+        name: "stale-cleaner",
+        label: "StaleCleaner (force-reload detail+BUDI from EUDAMED, reconvert + push)",
+        input: Input::Srns,
+    },
+    Action {
+        mode: 7,
+        name: "convert-push-dropped",
+        label: "convert & push (dropped files)",
+        input: Input::Uuids,
+    },
+    Action {
+        mode: 8,
+        name: "convert",
+        label: "convert (selected devices)",
+        input: Input::Uuids,
+    },
+    Action {
+        mode: 9,
+        name: "convert-push",
+        label: "convert & push (selected devices)",
+        input: Input::Uuids,
+    },
+];
+
+/// Label of a pipeline mode.
+pub fn label(mode: u8) -> &'static str {
+    ACTIONS
+        .iter()
+        .find(|a| a.mode == mode)
+        .map_or("unknown", |a| a.label)
+}
+
+/// The action `run <name>` means; `with_uuids` picks the device-list variant
+/// of names both variants share ("convert-push").
+pub fn find(name: &str, with_uuids: bool) -> Option<&'static Action> {
+    let matching = || ACTIONS.iter().filter(move |a| a.name == name);
+    matching()
+        .find(|a| (a.input == Input::Uuids) == with_uuids)
+        .or_else(|| matching().next())
+}
+
+/// Offline findings (`report::findings`) of one converted document as it is
+/// on disk.
+pub fn validate_doc(path: &Path) -> Vec<&'static str> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .map(|doc| crate::report::findings(&doc))
+        .unwrap_or_else(|| vec!["document not readable"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_lists_pick_the_selection_variant() {
+        assert_eq!(find("convert-push", false).unwrap().mode, 1);
+        assert_eq!(find("convert-push", true).unwrap().mode, 9);
+        assert_eq!(find("repush-failed", true).unwrap().mode, 3);
+        assert!(find("push", false).is_none());
+        let mut modes: Vec<u8> = ACTIONS.iter().map(|a| a.mode).collect();
+        modes.dedup();
+        assert_eq!(modes, (0..10).collect::<Vec<u8>>());
+        assert_eq!(label(5), ACTIONS[5].label);
+    }
+}
@@ -1,148 +1,650 @@
-use anyhow::Result;
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::path::Path;
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct Config {
-    pub provider: Provider,
-    pub target_market: TargetMarket,
-    pub gpc: Gpc,
-    #[serde(default)]
-    pub endocrine_substances: HashMap<String, EndocrineSubstanceIds>,
-    /// Gmail service-account settings for the `mailto` command.
-    /// Optional — only needed when sending emails.
-    #[serde(default)]
-    pub gmail: Gmail,
-    /// Google Sheet holding the customer SRN worklist, read by `sync-srns`.
-    /// Optional — only needed for the nightly SRN sync.
-    #[serde(default)]
-    pub sheet: Sheet,
-    /// Recipients/sender for the GS1 Production push report. Kept out of source
-    /// so no mail addresses are committed; real values live in the gitignored
-    /// `config.toml`. Env vars GS1_REPORT_TO / GS1_REPORT_FROM still override.
-    #[serde(default)]
-    pub gs1_report: Gs1Report,
-}
-
-/// GS1 push-report mail settings (see `send_gs1_prod_report`). Store real
-/// addresses in `config.toml` (gitignored) or set GS1_REPORT_TO / GS1_REPORT_FROM.
-#[derive(Deserialize, Debug, Clone, Default)]
-pub struct Gs1Report {
-    /// Recipient(s); comma-separated for several (delivered via the `To:` header).
-    #[serde(default)]
-    pub to: String,
-    /// Sender address (the Gmail service account impersonates this user).
-    #[serde(default)]
-    pub from: String,
-}
-
-/// Google Sheet source for the customer SRN worklist (`sync-srns`).
-/// Uses the same service account as `[gmail]` (p12 + service_email).
-/// Store the real spreadsheet id in `config.toml` (gitignored).
-#[derive(Deserialize, Debug, Clone)]
-pub struct Sheet {
-    /// Spreadsheet id (the long token in the sheet URL).
-    #[serde(default)]
-    pub spreadsheet_id: String,
-    /// A1 range whose first column holds the SRNs (header row tolerated).
-    #[serde(default = "default_srn_range")]
-    pub srn_range: String,
-    /// A1 range whose first column holds the customer GTIN worklist
-    /// (`sync-gtins`, header row tolerated). Same spreadsheet, separate tab.
-    #[serde(default = "default_gtin_range")]
-    pub gtin_range: String,
-}
-
-fn default_srn_range() -> String {
-    "eudamed2firstbase_SRN!B1:B".to_string()
-}
-
-fn default_gtin_range() -> String {
-    "eudamed2firstbase_GTIN!B1:B".to_string()
-}
-
-impl Default for Sheet {
-    fn default() -> Self {
-        Sheet {
-            spreadsheet_id: String::new(),
-            srn_range: default_srn_range(),
-            gtin_range: default_gtin_range(),
-        }
-    }
-}
-
-/// Gmail service-account credentials used by the `mailto` command.
-/// Store real values in `config.toml` (which is gitignored).
-/// See `config.sample.toml` for the expected format.
-#[derive(Deserialize, Debug, Clone, Default)]
-pub struct Gmail {
-    /// Path to the Google service account `.p12` key file.
-    #[serde(default)]
-    pub p12_key: String,
-    /// Service account email address
-    /// (e.g. `name@my-project.iam.gserviceaccount.com`).
-    #[serde(default)]
-    pub service_email: String,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct Provider {
-    pub gln: String,
-    pub party_name: String,
-    /// Default recipient GLN for `cargo run check` pushes.
-    /// Can be overridden at runtime with the FIRSTBASE_PUBLISH_GLN env var.
-    #[serde(default)]
-    pub publish_gln: String,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct TargetMarket {
-    pub country_code: String,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct Gpc {
-    pub segment_code: String,
-    pub class_code: String,
-    pub family_code: String,
-    pub category_code: String,
-    pub category_name: String,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct EndocrineSubstanceIds {
-    pub ec_number: Option<String>,
-    pub cas_number: Option<String>,
-}
-
-const DEFAULT_CONFIG: &str = r#"
-[provider]
-gln         = "7612345000480"
-party_name  = "EUDAMED Public Importer"
-publish_gln = "7612345000527"
-
-[target_market]
-country_code = "097"
-
-[gpc]
-segment_code = "51000000"
-class_code = "51150100"
-family_code = "51150000"
-category_code = "10005844"
-category_name = "Medical Devices"
-
-[endocrine_substances.Estradiol]
-ec_number = "200-023-8"
-cas_number = "50-28-2"
-"#;
-
-pub fn load_config(path: &Path) -> Result<Config> {
-    let content = if path.exists() {
-        std::fs::read_to_string(path)?
-    } else {
-        DEFAULT_CONFIG.to_string()
-    };
-    let config: Config = toml::from_str(&content)?;
-    Ok(config)
-}
+use anyhow::Result;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    pub provider: Provider,
+    pub target_market: TargetMarket,
+    pub gpc: Gpc,
+    /// GPC brick by EMDN code or prefix, ahead of `[gpc]` (see `gpc.rs`).
+    #[serde(default)]
+    pub gpc_by_emdn: HashMap<String, GpcBrick>,
+    #[serde(default)]
+    pub endocrine_substances: HashMap<String, EndocrineSubstanceIds>,
+    /// Gmail service-account settings for the `mailto` command.
+    /// Optional — only needed when sending emails.
+    #[serde(default)]
+    pub gmail: Gmail,
+    /// Google Sheet holding the customer SRN worklist, read by `sync-srns`.
+    /// Optional — only needed for the nightly SRN sync.
+    #[serde(default)]
+    pub sheet: Sheet,
+    /// Recipients/sender for the GS1 Production push report. Kept out of source
+    /// so no mail addresses are committed; real values live in the gitignored
+    /// `config.toml`. Env vars GS1_REPORT_TO / GS1_REPORT_FROM still override.
+    #[serde(default)]
+    pub gs1_report: Gs1Report,
+    /// UK Responsible Person for the MHRA registration sheet (`export --mhra`).
+    /// Optional — the columns stay empty when unset.
+    #[serde(default)]
+    pub mhra: Mhra,
+    /// How long local state is kept, applied by the `maintenance` command.
+    #[serde(default)]
+    pub retention: Retention,
+    /// Where the push log and conversion history live (see `storage.rs`).
+    #[serde(default)]
+    pub storage: Storage,
+    /// Time zones of the datetimes in the output documents (see `dates.rs`).
+    #[serde(default)]
+    pub dates: Dates,
+    /// Attribute-value pairs added to every base unit (`[[avp]]`, see `avp.rs`).
+    #[serde(default)]
+    pub avp: Vec<AvpRule>,
+    /// Device-level (Basic UDI-DI) records, which carry no GTIN (see `no_gtin.rs`).
+    #[serde(default)]
+    pub device_level: DeviceLevel,
+    /// Brand owner / manufacturer GLNs of the trade items (see `parties.rs`).
+    #[serde(default)]
+    pub parties: Parties,
+    /// Swiss availability and CH-REP for target market 756 (see `swiss_market.rs`).
+    #[serde(default)]
+    pub swiss_market: SwissMarket,
+    /// Conversion warnings silenced by code (see `warnings.rs`).
+    #[serde(default)]
+    pub warnings: Warnings,
+    /// Timeouts, pacing and retries of the HTTP clients (see `http.rs`).
+    #[serde(default)]
+    pub http: Http,
+    /// Actor contacts completed from the synced registry (see `actor_contacts.rs`).
+    #[serde(default)]
+    pub actors: Actors,
+    /// Parallelism of the EUDAMED downloads (see `fetch_pool.rs`).
+    #[serde(default)]
+    pub download: Download,
+    /// GS1 firstbase push settings.
+    #[serde(default)]
+    pub push: Push,
+}
+
+/// `[push]`: how `push_to_firstbase` talks to GS1 firstbase.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Push {
+    /// RequestStatus polling after CreateMany / AddMany (see `status_poll.rs`).
+    #[serde(default)]
+    pub poll: Poll,
+    /// CreateMany chunking and pacing (see `push_batch.rs`).
+    #[serde(default)]
+    pub batch: Batch,
+    /// `[push.environments.<name>]`: named environments for `--env`
+    /// (see `push_env.rs`).
+    #[serde(default)]
+    pub environments: BTreeMap<String, PushEnvironment>,
+}
+
+/// `[push.batch]`: unset fields fall back to the built-in values (see
+/// `push_batch.rs`).
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct Batch {
+    /// Items of the first CreateMany chunk.
+    pub size: Option<usize>,
+    /// Bounds of the adaptive chunk size; equal values pin it.
+    pub min_size: Option<usize>,
+    pub max_size: Option<usize>,
+    /// Largest request body in bytes; a longer chunk is split.
+    pub max_payload_bytes: Option<usize>,
+    /// Pause between two chunks, in seconds.
+    pub pause_secs: Option<f64>,
+}
+
+/// `[push.environments.<name>]`: a firstbase environment of the CLI pushes.
+/// Unset values fall back to the environment variables, the keychain and
+/// `[provider]`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PushEnvironment {
+    /// Production class (polling of `[push.poll.production]`, `--yes` for
+    /// withdraw); default only for `production` itself.
+    #[serde(default)]
+    pub production: Option<bool>,
+    /// Base URL; required for an environment other than `test` / `production`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Product API base URL of `readback`; built in for `test` / `production`.
+    #[serde(default)]
+    pub product_api_base: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// DataSource GLN (`[provider] gln`).
+    #[serde(default)]
+    pub gln: Option<String>,
+    /// Recipient GLN (`[provider] publish_gln`).
+    #[serde(default)]
+    pub publish_gln: Option<String>,
+}
+
+/// `[push.poll]`: the polling of both environments, refined in
+/// `[push.poll.test]` / `[push.poll.production]`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Poll {
+    #[serde(flatten)]
+    pub defaults: PollPolicy,
+    #[serde(default)]
+    pub test: PollPolicy,
+    #[serde(default)]
+    pub production: PollPolicy,
+}
+
+/// Unset fields fall back to the built-in values (see `status_poll.rs`).
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct PollPolicy {
+    /// Wait before the first poll, in seconds.
+    pub interval_secs: Option<f64>,
+    /// Each wait is the previous one times this.
+    pub factor: Option<f64>,
+    /// Longest wait between two polls, in seconds.
+    pub max_interval_secs: Option<f64>,
+    /// Give a request up (no verdict) after this many seconds of polling.
+    pub timeout_secs: Option<u64>,
+    /// Random spread of each wait, as a share of it (0.2 = ±20%).
+    pub jitter: Option<f64>,
+}
+
+/// `[download]`: worker counts; the rate stays `[http]`'s EUDAMED interval.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Download {
+    /// Concurrent requests of `fetch-details` unless `--threads` is given.
+    pub detail_threads: Option<usize>,
+}
+
+/// `[actors]`: use the `sync-actors` registry in the conversion.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Actors {
+    /// Fill name, address, telephone and e-mail of EMA / EPP / EAR contacts.
+    #[serde(default)]
+    pub enrich: bool,
+}
+
+/// `[http]`: the policy of all hosts, refined per host in `[http.hosts]`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Http {
+    #[serde(flatten)]
+    pub defaults: HostPolicy,
+    /// Outbound proxy, `http://[user:pass@]host:port` or `socks5://…`;
+    /// `"none"` connects directly. Unset: `HTTPS_PROXY` / `ALL_PROXY` /
+    /// `HTTP_PROXY` from the environment.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Hosts reached without the proxy (a key also covers its subdomains),
+    /// added to `NO_PROXY`.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// PEM file of the root certificates to trust instead of the built-in
+    /// Mozilla roots (a private CA in front of the corporate proxy).
+    #[serde(default)]
+    pub ca_bundle: Option<String>,
+    /// Keep raw EUDAMED responses and revalidate them with conditional
+    /// requests (see `http_cache.rs`).
+    #[serde(default)]
+    pub cache: bool,
+    /// Directory of the response cache; default `eudamed_json/http_cache`.
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+    /// By host; a key also covers its subdomains.
+    #[serde(default)]
+    pub hosts: HashMap<String, HostPolicy>,
+}
+
+/// Unset fields fall back to the built-in values (see `http.rs`).
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct HostPolicy {
+    /// Minimum milliseconds between two requests.
+    pub interval_ms: Option<u64>,
+    /// Whole-request timeout in seconds; 0 = none.
+    pub timeout_secs: Option<u64>,
+    /// Attempts per request, including the first.
+    pub max_retries: Option<u32>,
+    /// First retry wait, doubled per attempt.
+    pub backoff_base_ms: Option<u64>,
+    /// Longest wait between attempts, also caps `Retry-After`.
+    pub backoff_max_ms: Option<u64>,
+}
+
+/// Warning codes (`W001` …) not to log or report.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Warnings {
+    /// For every device.
+    #[serde(default)]
+    pub suppress: Vec<String>,
+    /// For single devices, by GTIN.
+    #[serde(default)]
+    pub suppress_by_gtin: HashMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SwissMarket {
+    #[serde(default)]
+    pub availability: ChAvailability,
+    /// Default Swiss placing-on-market date (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// Default CH-REP for non-Swiss manufacturers.
+    #[serde(default)]
+    pub ch_rep: Option<ChRep>,
+    /// Per manufacturer SRN, ahead of the defaults above.
+    #[serde(default)]
+    pub by_srn: HashMap<String, SwissManufacturer>,
+}
+
+/// When a base unit is available in Switzerland.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChAvailability {
+    /// Never added.
+    None,
+    /// Whenever it is on an EU market.
+    #[default]
+    Eu,
+    /// Also without EU market data (then as its original market).
+    Always,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SwissManufacturer {
+    #[serde(default)]
+    pub start_date: Option<String>,
+    #[serde(default)]
+    pub ch_rep: Option<ChRep>,
+}
+
+/// Swiss authorised representative, identified by its CHRN.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ChRep {
+    pub chrn: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// `BrandOwner` / `ManufacturerOfTradeItem` parties; a role without a GLN
+/// is left out of the documents.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Parties {
+    #[serde(default)]
+    pub brand_owner: Option<Party>,
+    #[serde(default)]
+    pub manufacturer: Option<Party>,
+    /// Per manufacturer SRN, ahead of the defaults above (role by role).
+    #[serde(default)]
+    pub by_srn: HashMap<String, SrnParties>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SrnParties {
+    #[serde(default)]
+    pub brand_owner: Option<Party>,
+    #[serde(default)]
+    pub manufacturer: Option<Party>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Party {
+    pub gln: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct DeviceLevel {
+    #[serde(default)]
+    pub no_gtin: NoGtinStrategy,
+}
+
+/// What to make of a device-level record, which has no GTIN to push under.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoGtinStrategy {
+    /// No document; the record is listed in `no_gtin_<time>.txt`.
+    #[default]
+    Skip,
+    /// Resolve the Basic UDI-DI's GS1 UDI-DIs on EUDAMED, one document each.
+    Lookup,
+    /// A document keyed by the Basic UDI-DI in `firstbase_json/placeholders/`,
+    /// which the push never reads.
+    Placeholder,
+}
+
+/// One `[[avp]]` entry: an attribute without a dedicated GDSN field, sent in
+/// the trade item's `AvpList`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AvpRule {
+    /// `AttributeName`, e.g. `eudamedUuid`.
+    pub name: String,
+    /// Constant or template with `{uuid}`, `{version}`, `{version_date}`,
+    /// `{primary_di}`, `{basic_udi}`; the pair is left out when a placeholder
+    /// has no value for the device.
+    pub value: String,
+    /// Set any of these for a `CompoundStringAVP` (a value from a code list).
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub code_list: Option<String>,
+    #[serde(default)]
+    pub code_list_version: Option<String>,
+}
+
+/// Time zones (IANA names) of the output datetimes.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct Dates {
+    /// Zone of the synchronisation and sales-condition datetimes; their UTC
+    /// offset is written out. Default `UTC`.
+    #[serde(default = "default_timezone")]
+    pub timezone: Tz,
+    /// Zone of EUDAMED's offset-less timestamps (`versionDate`). Default
+    /// `Europe/Brussels`.
+    #[serde(default = "default_eudamed_timezone")]
+    pub eudamed_timezone: Tz,
+}
+
+fn default_timezone() -> Tz {
+    Tz::UTC
+}
+
+fn default_eudamed_timezone() -> Tz {
+    Tz::Europe__Brussels
+}
+
+impl Default for Dates {
+    fn default() -> Self {
+        Dates {
+            timezone: default_timezone(),
+            eudamed_timezone: default_eudamed_timezone(),
+        }
+    }
+}
+
+/// Storage backend for the shared records (push log, conversion history).
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Storage {
+    /// `sqlite` (default: the local `db/version_tracking.db`) or `postgres`
+    /// (needs a build with `--features postgres`).
+    #[serde(default)]
+    pub backend: String,
+    /// libpq-style connection string, e.g.
+    /// `host=db.example.com user=eudamed password=… dbname=eudamed`.
+    #[serde(default)]
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    pub postgres_url: String,
+}
+
+impl Storage {
+    /// Whether the store is the local SQLite version DB.
+    pub fn is_local(&self) -> bool {
+        matches!(self.backend.as_str(), "" | "sqlite")
+    }
+}
+
+/// Retention periods in days for the `maintenance` command; 0 keeps forever.
+/// Files age by modification time, DB rows by their timestamp column.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Retention {
+    /// Downloaded EUDAMED source files (`eudamed_json/detail`, `eudamed_json/basic`,
+    /// `ndjson/`, `xml/`). A pruned detail/basic file is re-fetched by the next download.
+    #[serde(default = "default_raw_days")]
+    pub raw_days: u32,
+    /// Push logs, reports and exports under `log/`, and delta change lists.
+    #[serde(default = "default_log_days")]
+    pub log_days: u32,
+    /// Pushed documents in `firstbase_json/processed/` and `swissdamed_json/processed/`.
+    /// They are the baseline for `--delta`/`--changed-only`, so kept by default.
+    #[serde(default)]
+    pub processed_days: u32,
+    /// `push_log` rows (the submission record), kept by default.
+    #[serde(default)]
+    pub push_log_days: u32,
+    /// `device_history` rows (conversion history), kept by default.
+    #[serde(default)]
+    pub history_days: u32,
+}
+
+fn default_raw_days() -> u32 {
+    90
+}
+
+fn default_log_days() -> u32 {
+    365
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            raw_days: default_raw_days(),
+            log_days: default_log_days(),
+            processed_days: 0,
+            push_log_days: 0,
+            history_days: 0,
+        }
+    }
+}
+
+/// UK Responsible Person details written into every `export --mhra` row.
+/// Non-UK manufacturers register in GB through their UKRP.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Mhra {
+    #[serde(default)]
+    pub uk_responsible_person: String,
+    #[serde(default)]
+    pub ukrp_address: String,
+}
+
+/// GS1 push-report mail settings (see `send_gs1_prod_report`). Store real
+/// addresses in `config.toml` (gitignored) or set GS1_REPORT_TO / GS1_REPORT_FROM.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Gs1Report {
+    /// Recipient(s); comma-separated for several (delivered via the `To:` header).
+    #[serde(default)]
+    pub to: String,
+    /// Sender address (the Gmail service account impersonates this user).
+    #[serde(default)]
+    pub from: String,
+}
+
+/// Google Sheet source for the customer SRN worklist (`sync-srns`).
+/// Uses the same service account as `[gmail]` (p12 + service_email).
+/// Store the real spreadsheet id in `config.toml` (gitignored).
+#[derive(Deserialize, Debug, Clone)]
+pub struct Sheet {
+    /// Spreadsheet id (the long token in the sheet URL).
+    #[serde(default)]
+    pub spreadsheet_id: String,
+    /// A1 range whose first column holds the SRNs (header row tolerated).
+    #[serde(default = "default_srn_range")]
+    pub srn_range: String,
+    /// A1 range whose first column holds the customer GTIN worklist
+    /// (`sync-gtins`, header row tolerated). Same spreadsheet, separate tab.
+    #[serde(default = "default_gtin_range")]
+    pub gtin_range: String,
+}
+
+fn default_srn_range() -> String {
+    "eudamed2firstbase_SRN!B1:B".to_string()
+}
+
+fn default_gtin_range() -> String {
+    "eudamed2firstbase_GTIN!B1:B".to_string()
+}
+
+impl Default for Sheet {
+    fn default() -> Self {
+        Sheet {
+            spreadsheet_id: String::new(),
+            srn_range: default_srn_range(),
+            gtin_range: default_gtin_range(),
+        }
+    }
+}
+
+/// Gmail service-account credentials used by the `mailto` command.
+/// Store real values in `config.toml` (which is gitignored).
+/// See `config.sample.toml` for the expected format.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Gmail {
+    /// Path to the Google service account `.p12` key file.
+    #[serde(default)]
+    pub p12_key: String,
+    /// Service account email address
+    /// (e.g. `name@my-project.iam.gserviceaccount.com`).
+    #[serde(default)]
+    pub service_email: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Provider {
+    pub gln: String,
+    pub party_name: String,
+    /// Default recipient GLN for `cargo run check` pushes.
+    /// Can be overridden at runtime with the FIRSTBASE_PUBLISH_GLN env var.
+    #[serde(default)]
+    pub publish_gln: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TargetMarket {
+    pub country_code: String,
+    /// Languages a text flagged "all languages applicable" is written in;
+    /// empty = the market's defaults (`market_languages`).
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
+impl TargetMarket {
+    /// The configured text languages, else the market's defaults.
+    pub fn text_languages(&self) -> Vec<String> {
+        if !self.languages.is_empty() {
+            return self.languages.clone();
+        }
+        market_languages(&self.country_code)
+            .iter()
+            .map(|l| l.to_string())
+            .collect()
+    }
+}
+
+/// Default text languages of a GDSN target market: its official languages
+/// plus English, which EUDAMED's "all languages" texts were written as before.
+pub fn market_languages(country_code: &str) -> &'static [&'static str] {
+    match country_code {
+        "097" | "040" | "276" => &["de", "en"],
+        "756" => &["de", "fr", "it", "en"],
+        "250" => &["fr", "en"],
+        "380" => &["it", "en"],
+        _ => &["en"],
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Gpc {
+    pub segment_code: String,
+    pub class_code: String,
+    pub family_code: String,
+    pub category_code: String,
+    pub category_name: String,
+}
+
+/// A `[gpc_by_emdn]` entry: the brick code alone (`"C01" = "10005845"`) or
+/// a table that also overrides the name and the codes above the brick; what
+/// is left out comes from `[gpc]`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum GpcBrick {
+    Code(String),
+    Table {
+        category_code: String,
+        category_name: Option<String>,
+        class_code: Option<String>,
+        family_code: Option<String>,
+        segment_code: Option<String>,
+    },
+}
+
+impl GpcBrick {
+    /// `[gpc]` with this brick.
+    pub fn over(&self, default: &Gpc) -> Gpc {
+        match self {
+            GpcBrick::Code(code) => Gpc {
+                category_code: code.clone(),
+                ..default.clone()
+            },
+            GpcBrick::Table {
+                category_code,
+                category_name,
+                class_code,
+                family_code,
+                segment_code,
+            } => Gpc {
+                segment_code: segment_code
+                    .clone()
+                    .unwrap_or_else(|| default.segment_code.clone()),
+                class_code: class_code
+                    .clone()
+                    .unwrap_or_else(|| default.class_code.clone()),
+                family_code: family_code
+                    .clone()
+                    .unwrap_or_else(|| default.family_code.clone()),
+                category_code: category_code.clone(),
+                category_name: category_name
+                    .clone()
+                    .unwrap_or_else(|| default.category_name.clone()),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EndocrineSubstanceIds {
+    pub ec_number: Option<String>,
+    pub cas_number: Option<String>,
+}
+
+const DEFAULT_CONFIG: &str = r#"
+[provider]
+gln         = "7612345000480"
+party_name  = "EUDAMED Public Importer"
+publish_gln = "7612345000527"
+
+[target_market]
+country_code = "097"
+
+[gpc]
+segment_code = "51000000"
+class_code = "51150100"
+family_code = "51150000"
+category_code = "10005844"
+category_name = "Medical Devices"
+
+[endocrine_substances.Estradiol]
+ec_number = "200-023-8"
+cas_number = "50-28-2"
+"#;
+
+pub fn load_config(path: &Path) -> Result<Config> {
+    let content = if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        DEFAULT_CONFIG.to_string()
+    };
+    let config: Config = toml::from_str(&content)?;
+    // Warnings are raised deep in the mappings, without the config at hand.
+    crate::warnings::configure(&config.warnings);
+    crate::http::configure(&config.http)?;
+    Ok(config)
+}
+
+/// The built-in default config for tests; unlike `load_config` it reads no
+/// file and leaves the warning and HTTP settings alone.
+#[cfg(test)]
+pub fn test_config() -> Config {
+    toml::from_str(DEFAULT_CONFIG).unwrap()
+}
@@ -0,0 +1,277 @@
+//! Credentials in the platform keychain (`login` / `logout` subcommands):
+//! macOS Keychain, Windows Credential Manager, Secret Service on Linux, via
+//! the `keyring` crate. One entry per service (`eudamed2firstbase` /
+//! `firstbase` | `swissdamed`) holding `{"id", "secret"}` as JSON.
+//!
+//! Lookup order everywhere: environment variables, then the keychain. The GUI
+//! keeps the secret out of `settings.json` once the keychain accepted it and
+//! falls back to the plaintext file only where no keychain is available.
+//!
+//! The GUI credential screen can also keep a separate firstbase login per
+//! environment (`firstbase:test`, `firstbase:production`); `load_env` falls
+//! back to the shared entry, so a single login keeps working for both.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+const SERVICE: &str = "eudamed2firstbase";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// GS1 firstbase: account email + password.
+    Firstbase,
+    /// swissdamed M2M API: client id + client secret.
+    Swissdamed,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Firstbase, Kind::Swissdamed];
+
+    pub fn parse(s: &str) -> Option<Kind> {
+        match s.to_ascii_lowercase().as_str() {
+            "firstbase" | "gs1" => Some(Kind::Firstbase),
+            "swissdamed" => Some(Kind::Swissdamed),
+            _ => None,
+        }
+    }
+
+    fn account(self) -> &'static str {
+        match self {
+            Kind::Firstbase => "firstbase",
+            Kind::Swissdamed => "swissdamed",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::Firstbase => "GS1 firstbase",
+            Kind::Swissdamed => "swissdamed",
+        }
+    }
+
+    /// Prompts for the id and the secret.
+    pub fn prompts(self) -> (&'static str, &'static str) {
+        match self {
+            Kind::Firstbase => ("Email", "Password"),
+            Kind::Swissdamed => ("Client ID", "Client Secret"),
+        }
+    }
+
+    /// Environment variables for the id and the secret.
+    pub fn env_vars(self) -> (&'static str, &'static str) {
+        match self {
+            Kind::Firstbase => ("FIRSTBASE_EMAIL", "FIRSTBASE_PASSWORD"),
+            Kind::Swissdamed => ("SWISSDAMED_CLIENT_ID", "SWISSDAMED_CLIENT_SECRET"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credential {
+    pub id: String,
+    pub secret: String,
+}
+
+impl Credential {
+    pub fn is_complete(&self) -> bool {
+        !self.id.is_empty() && !self.secret.is_empty()
+    }
+}
+
+/// Last value read from / written to the keychain per account, so the GUI's
+/// save-on-change does not hit the keychain for every frame.
+static KNOWN: Mutex<Option<HashMap<String, Credential>>> = Mutex::new(None);
+
+fn remember(account: &str, cred: Option<&Credential>) {
+    let mut known = KNOWN.lock().unwrap_or_else(|e| e.into_inner());
+    let map = known.get_or_insert_with(HashMap::new);
+    match cred {
+        Some(c) => map.insert(account.to_string(), c.clone()),
+        None => map.remove(account),
+    };
+}
+
+/// Keychain account of `kind`, scoped to an environment if given.
+fn account(kind: Kind, env: Option<&str>) -> String {
+    match env {
+        Some(env) => format!("{}:{}", kind.account(), env),
+        None => kind.account().to_string(),
+    }
+}
+
+fn load_account(account: &str) -> Option<Credential> {
+    let json = keyring::Entry::new(SERVICE, account)
+        .and_then(|e| e.get_password())
+        .ok()?;
+    let cred: Credential = serde_json::from_str(&json).ok()?;
+    remember(account, Some(&cred));
+    Some(cred)
+}
+
+fn store_account(kind: Kind, account: &str, cred: &Credential) -> anyhow::Result<()> {
+    let unchanged = KNOWN
+        .lock()
+        .map(|k| k.as_ref().and_then(|m| m.get(account)) == Some(cred))
+        .unwrap_or(false);
+    if unchanged {
+        return Ok(());
+    }
+    let json = serde_json::to_string(cred)?;
+    keyring::Entry::new(SERVICE, account)
+        .and_then(|e| e.set_password(&json))
+        .map_err(|e| anyhow::anyhow!("Keychain unavailable for {}: {}", kind.label(), e))?;
+    remember(account, Some(cred));
+    Ok(())
+}
+
+fn delete_account(kind: Kind, account: &str) -> anyhow::Result<bool> {
+    remember(account, None);
+    match keyring::Entry::new(SERVICE, account).and_then(|e| e.delete_credential()) {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(anyhow::anyhow!(
+            "Keychain unavailable for {}: {}",
+            kind.label(),
+            e
+        )),
+    }
+}
+
+/// The stored credential, if any (`None` also when no keychain is available).
+pub fn load(kind: Kind) -> Option<Credential> {
+    load_account(&account(kind, None))
+}
+
+/// Store `cred` in the keychain.
+pub fn store(kind: Kind, cred: &Credential) -> anyhow::Result<()> {
+    store_account(kind, &account(kind, None), cred)
+}
+
+/// Remove the stored credential. Returns false if there was none.
+pub fn delete(kind: Kind) -> anyhow::Result<bool> {
+    delete_account(kind, &account(kind, None))
+}
+
+/// The credential stored for environment `env` (e.g. "production"), else
+/// the shared one.
+pub fn load_env(kind: Kind, env: &str) -> Option<Credential> {
+    load_account(&account(kind, Some(env))).or_else(|| load(kind))
+}
+
+/// Only the entry of `env`, without the fallback (credential screen).
+pub fn load_env_only(kind: Kind, env: &str) -> Option<Credential> {
+    load_account(&account(kind, Some(env)))
+}
+
+pub fn store_env(kind: Kind, env: &str, cred: &Credential) -> anyhow::Result<()> {
+    store_account(kind, &account(kind, Some(env)), cred)
+}
+
+pub fn delete_env(kind: Kind, env: &str) -> anyhow::Result<bool> {
+    delete_account(kind, &account(kind, Some(env)))
+}
+
+/// One row of the GUI credential screen: a keychain entry being edited.
+#[derive(Debug, Clone)]
+pub struct Slot {
+    pub kind: Kind,
+    /// Environment scope (firstbase only).
+    pub env: Option<&'static str>,
+    pub cred: Credential,
+    /// The entry exists in the keychain.
+    pub stored: bool,
+    /// Result of the last save or connection test; the test thread writes it.
+    pub status: Arc<Mutex<String>>,
+}
+
+impl Slot {
+    /// firstbase Test, firstbase Production, swissdamed — as stored.
+    pub fn all() -> Vec<Slot> {
+        [
+            (Kind::Firstbase, Some("test")),
+            (Kind::Firstbase, Some("production")),
+            (Kind::Swissdamed, None),
+        ]
+        .into_iter()
+        .map(|(kind, env)| {
+            let stored = match env {
+                Some(env) => load_env_only(kind, env),
+                None => load(kind),
+            };
+            Slot {
+                kind,
+                env,
+                stored: stored.is_some(),
+                cred: stored.unwrap_or_default(),
+                status: Arc::default(),
+            }
+        })
+        .collect()
+    }
+
+    pub fn title(&self) -> String {
+        match self.env {
+            Some(env) => format!("{} · {}", self.kind.label(), env),
+            None => self.kind.label().to_string(),
+        }
+    }
+
+    pub fn set_status(&self, status: String) {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = status;
+    }
+
+    pub fn status(&self) -> String {
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        match self.env {
+            Some(env) => store_env(self.kind, env, &self.cred)?,
+            None => store(self.kind, &self.cred)?,
+        }
+        self.stored = true;
+        Ok(())
+    }
+
+    pub fn delete(&mut self) -> anyhow::Result<bool> {
+        self.stored = false;
+        match self.env {
+            Some(env) => delete_env(self.kind, env),
+            None => delete(self.kind),
+        }
+    }
+}
+
+/// Both environment variables of `kind`, possibly empty.
+fn from_env_vars(kind: Kind) -> Credential {
+    let (id_var, secret_var) = kind.env_vars();
+    Credential {
+        id: std::env::var(id_var).unwrap_or_default(),
+        secret: std::env::var(secret_var).unwrap_or_default(),
+    }
+}
+
+/// Credential for a CLI push: both environment variables when set, else the
+/// keychain. Empty when neither has it.
+pub fn resolve(kind: Kind) -> Credential {
+    let from_env = from_env_vars(kind);
+    if from_env.is_complete() {
+        return from_env;
+    }
+    load(kind).unwrap_or(from_env)
+}
+
+/// `resolve` for a push to environment `env`: its keychain entry
+/// (`firstbase:<env>`) before the shared one.
+pub fn resolve_env(kind: Kind, env: &str) -> Credential {
+    let from_env = from_env_vars(kind);
+    if from_env.is_complete() {
+        return from_env;
+    }
+    load_env(kind, env).unwrap_or(from_env)
+}
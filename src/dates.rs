@@ -0,0 +1,104 @@
+//! Datetimes written into the firstbase documents: the
+//! `TradeItemSynchronisationDates` and the sales-condition start/end
+//! datetimes. All carry the explicit UTC offset of `[dates] timezone`
+//! (default UTC) instead of a naive local time or a hardcoded `+00:00`.
+//! EUDAMED's own timestamps (`versionDate`) have no offset; they are read in
+//! `[dates] eudamed_timezone` (default Europe/Brussels) and converted.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use crate::config::Dates;
+
+const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// Time of day of a sales-condition start date (as in the GS1 reference files).
+pub const START_OF_DAY: NaiveTime = NaiveTime::from_hms_opt(13, 0, 0).unwrap();
+/// Time of day of a sales-condition end date.
+pub const END_OF_DAY: NaiveTime = NaiveTime::from_hms_opt(21, 0, 0).unwrap();
+
+/// `at` in the output zone.
+pub fn format(dates: &Dates, at: DateTime<Utc>) -> String {
+    at.with_timezone(&dates.timezone).format(FORMAT).to_string()
+}
+
+/// The current time.
+pub fn now(dates: &Dates) -> String {
+    format(dates, Utc::now())
+}
+
+/// The current time `days` days ahead.
+pub fn days_from_now(dates: &Dates, days: i64) -> String {
+    format(dates, Utc::now() + chrono::Duration::days(days))
+}
+
+/// An EUDAMED timestamp (`2024-06-12T09:41:27`, fractional seconds or an
+/// offset allowed) in the output zone; `None` when it does not parse.
+pub fn eudamed_timestamp(dates: &Dates, s: &str) -> Option<String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(s) {
+        return Some(format(dates, at.with_timezone(&Utc)));
+    }
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    // `earliest`: a time in the autumn DST overlap is taken as the first.
+    let at = dates
+        .eudamed_timezone
+        .from_local_datetime(&naive)
+        .earliest()?;
+    Some(format(dates, at.with_timezone(&Utc)))
+}
+
+/// EUDAMED date (`2026-02-03`, `2026-02-03+01:00`) at `time` of that day in
+/// the output zone. A value with a time part is converted as a timestamp;
+/// anything unparsable is passed through unchanged.
+pub fn day_at(dates: &Dates, date: &str, time: NaiveTime) -> String {
+    if date.contains('T') {
+        return eudamed_timestamp(dates, date).unwrap_or_else(|| date.to_string());
+    }
+    let day = date.get(..10).unwrap_or(date);
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| {
+            dates
+                .timezone
+                .from_local_datetime(&d.and_time(time))
+                .earliest()
+        })
+        .map(|at| at.format(FORMAT).to_string())
+        .unwrap_or_else(|| date.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_carry_the_offset_of_the_output_zone() {
+        let utc = Dates::default();
+        assert_eq!(
+            day_at(&utc, "2026-02-03+01:00", START_OF_DAY),
+            "2026-02-03T13:00:00+00:00"
+        );
+        // Brussels summer time is UTC+2.
+        assert_eq!(
+            eudamed_timestamp(&utc, "2024-06-12T09:41:27.123").as_deref(),
+            Some("2024-06-12T07:41:27+00:00")
+        );
+
+        let zurich = Dates {
+            timezone: chrono_tz::Europe::Zurich,
+            eudamed_timezone: chrono_tz::UTC,
+        };
+        assert_eq!(
+            day_at(&zurich, "2024-03-01", END_OF_DAY),
+            "2024-03-01T21:00:00+01:00"
+        );
+        assert_eq!(
+            eudamed_timestamp(&zurich, "2024-06-12T09:41:27").as_deref(),
+            Some("2024-06-12T11:41:27+02:00")
+        );
+        assert_eq!(day_at(&zurich, "unknown", START_OF_DAY), "unknown");
+
+        let parsed: crate::config::Dates = toml::from_str("timezone = \"Europe/Zurich\"").unwrap();
+        assert_eq!(parsed.timezone, chrono_tz::Europe::Zurich);
+        assert!(toml::from_str::<crate::config::Dates>("timezone = \"Mars/Olympus\"").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Cross-file device deduplication for the NDJSON listing mode (`ndjson [dir]`).
+//! EUDAMED snapshots downloaded at different times overlap: the same device
+//! (same GTIN/primaryDi, or UUID when it has none) can appear in several input
+//! files in different versions. A pre-pass over all files keeps only the
+//! record with the highest `versionNumber` — ties broken by the latest
+//! `versionDate`, then by the later file/line — and lists every discarded
+//! record so the choice can be audited.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Just the identity/version fields of a listing record, borrowed from the
+/// line (plain `&str`: identifiers and dates carry no JSON escapes; a record
+/// that does fails to parse and is left to the conversion).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordKey<'a> {
+    uuid: Option<&'a str>,
+    primary_di: Option<&'a str>,
+    version_number: Option<serde_json::Value>,
+    version_date: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+struct Seen {
+    /// Index into the scanned files.
+    file: usize,
+    line: u32,
+    version: Option<u64>,
+    version_date: String,
+}
+
+impl Seen {
+    fn describe(&self, files: &[PathBuf]) -> String {
+        format!(
+            "{}:{} (v{}, {})",
+            files[self.file].display(),
+            self.line,
+            self.version
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            if self.version_date.is_empty() {
+                "no date"
+            } else {
+                &self.version_date
+            }
+        )
+    }
+}
+
+/// `versionNumber` as a number — EUDAMED sends it as number or string.
+pub fn version_of(v: Option<&serde_json::Value>) -> Option<u64> {
+    match v? {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whether `candidate` (read later) replaces `current`.
+fn supersedes(candidate: &Seen, current: &Seen) -> bool {
+    (candidate.version.unwrap_or(0), &candidate.version_date)
+        >= (current.version.unwrap_or(0), &current.version_date)
+}
+
+/// Outcome of the pre-pass.
+#[derive(Debug, Default)]
+pub struct Dedup {
+    /// `(file, 1-based line)` of every record to skip.
+    pub discarded: HashSet<(PathBuf, u32)>,
+    /// One line per discarded record: key, kept record, discarded record.
+    pub report: Vec<String>,
+}
+
+/// Scan `files` (in the given order) and pick the newest record per device.
+pub fn scan(files: &[PathBuf]) -> anyhow::Result<Dedup> {
+    let mut winners: HashMap<String, Seen> = HashMap::new();
+    let mut losers: Vec<(String, Seen)> = Vec::new();
+    for (f, file) in files.iter().enumerate() {
+        let input = crate::ndjson_input::Input::open(file)?;
+        // Keys are parsed in parallel, merged in file/line order.
+        crate::ndjson_input::stream(
+            &input,
+            |_| false,
+            |line_num, line| {
+                let Ok(rec) = serde_json::from_str::<RecordKey>(line) else {
+                    return Ok(None); // unparsable: reported by the conversion itself
+                };
+                let Some(key) = rec
+                    .primary_di
+                    .filter(|d| !d.is_empty())
+                    .or(rec.uuid.filter(|u| !u.is_empty()))
+                else {
+                    return Ok(None);
+                };
+                Ok(Some((
+                    key.to_string(),
+                    Seen {
+                        file: f,
+                        line: line_num,
+                        version: version_of(rec.version_number.as_ref()),
+                        version_date: rec.version_date.unwrap_or_default().to_string(),
+                    },
+                )))
+            },
+            |found| {
+                let Some((key, seen)) = found else {
+                    return Ok(());
+                };
+                match winners.get_mut(&key) {
+                    None => {
+                        winners.insert(key, seen);
+                    }
+                    Some(current) if supersedes(&seen, current) => {
+                        let old = std::mem::replace(current, seen);
+                        losers.push((key, old));
+                    }
+                    Some(_) => losers.push((key, seen)),
+                }
+                Ok(())
+            },
+        )?;
+    }
+
+    let mut dedup = Dedup::default();
+    losers.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.line.cmp(&b.1.line)));
+    for (key, lost) in losers {
+        let kept = &winners[&key];
+        dedup.report.push(format!(
+            "{}  kept {}  discarded {}",
+            key,
+            kept.describe(files),
+            lost.describe(files)
+        ));
+        dedup
+            .discarded
+            .insert((files[lost.file].clone(), lost.line));
+    }
+    Ok(dedup)
+}
+
+/// Write the discard report to `<output_dir>/dedup_<time>.txt`.
+pub fn write_report(output_dir: &Path, dedup: &Dedup) -> anyhow::Result<PathBuf> {
+    let path = output_dir.join(format!(
+        "dedup_{}.txt",
+        chrono::Local::now().format("%H.%M_%d.%m.%Y")
+    ));
+    std::fs::write(&path, dedup.report.join("\n") + "\n")?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_highest_version_across_files() {
+        let dir = std::env::temp_dir().join(format!("dedup_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.ndjson");
+        let b = dir.join("b.ndjson");
+        std::fs::write(
+            &a,
+            "{\"primaryDi\":\"1\",\"versionNumber\":3}\n{\"primaryDi\":\"2\",\"versionNumber\":\"1\",\"versionDate\":\"2024-05-01\"}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &b,
+            "{\"primaryDi\":\"1\",\"versionNumber\":2}\n{\"primaryDi\":\"2\",\"versionNumber\":1,\"versionDate\":\"2024-06-01\"}\n",
+        )
+        .unwrap();
+
+        let dedup = scan(&[a.clone(), b.clone()]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        // GTIN 1: v3 in a wins over v2 in b; GTIN 2: same version, later date in b wins.
+        assert_eq!(dedup.discarded.len(), 2);
+        assert!(dedup.discarded.contains(&(b, 1)));
+        assert!(dedup.discarded.contains(&(a, 2)));
+    }
+}
@@ -0,0 +1,195 @@
+//! Changed-fields delta payloads (`--delta` on the EUDAMED JSON convert).
+//! When a device is re-converted from a newer snapshot, the new firstbase
+//! document is compared with the previous output for the same UUID and
+//!
+//!   - a minimal correction payload is written to `firstbase_json/delta/<uuid>.json`:
+//!     the same DraftItem shape, but each TradeItem carries only its `Gtin` plus
+//!     the top-level attributes/modules that changed (whole module values), and
+//!     only the child items that changed themselves or below;
+//!   - one human-readable line per changed leaf is appended to the run's change
+//!     log `firstbase_json/delta/changes_<timestamp>.txt` (`GTIN path: old -> new`).
+//!
+//! Child items are matched by GTIN, not position. A device whose only change
+//! is `TradeItemSynchronisationDates` gets no payload.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+/// Sparse item (`{TradeItem, CatalogueItemChildItemLink, Identifier}` node)
+/// holding only what differs from `old`; `None` when nothing changed.
+fn sparse_item(old: Option<&Value>, new: &Value) -> Option<Value> {
+    let new_ti = new.get("TradeItem")?.as_object()?;
+    let old_ti = old.and_then(|o| o.get("TradeItem"));
+
+    let mut ti = Map::new();
+    for (key, value) in new_ti {
+        if old_ti.and_then(|o| o.get(key)) != Some(value) {
+            ti.insert(key.clone(), value.clone());
+        }
+    }
+    // Attributes dropped by the new conversion are sent as explicit nulls.
+    if let Some(old_ti) = old_ti.and_then(|o| o.as_object()) {
+        for key in old_ti.keys() {
+            if !new_ti.contains_key(key) {
+                ti.insert(key.clone(), Value::Null);
+            }
+        }
+    }
+
+    let old_links = old
+        .and_then(|o| o.get("CatalogueItemChildItemLink"))
+        .and_then(|l| l.as_array());
+    let mut links = Vec::new();
+    for link in new
+        .get("CatalogueItemChildItemLink")
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let Some(child) = link.get("CatalogueItem") else {
+            continue;
+        };
+        let gtin = child.pointer("/TradeItem/Gtin");
+        let old_link = old_links.and_then(|ls| {
+            ls.iter()
+                .find(|l| l.pointer("/CatalogueItem/TradeItem/Gtin") == gtin)
+        });
+        let old_child = old_link.and_then(|l| l.get("CatalogueItem"));
+        let quantity_changed = old_link.and_then(|l| l.get("Quantity")) != link.get("Quantity");
+        let sparse_child = sparse_item(old_child, child);
+        if sparse_child.is_none() && !quantity_changed {
+            continue;
+        }
+        let mut out = Map::new();
+        if let Some(q) = link.get("Quantity") {
+            out.insert("Quantity".to_string(), q.clone());
+        }
+        let child = sparse_child.unwrap_or_else(|| {
+            let mut c = Map::new();
+            let mut t = Map::new();
+            t.insert("Gtin".to_string(), gtin.cloned().unwrap_or(Value::Null));
+            c.insert("TradeItem".to_string(), Value::Object(t));
+            Value::Object(c)
+        });
+        out.insert("CatalogueItem".to_string(), child);
+        links.push(Value::Object(out));
+    }
+
+    // Synchronisation dates alone are not a correction; they ride along with
+    // real changes (GS1 needs the new LastChangeDateTime then).
+    let substantive = ti.keys().any(|k| k != "TradeItemSynchronisationDates");
+    if !substantive && links.is_empty() {
+        return None;
+    }
+    if let Some(gtin) = new_ti.get("Gtin") {
+        ti.insert("Gtin".to_string(), gtin.clone());
+    }
+    let mut item = Map::new();
+    item.insert("TradeItem".to_string(), Value::Object(ti));
+    if !links.is_empty() {
+        item.insert(
+            "CatalogueItemChildItemLink".to_string(),
+            Value::Array(links),
+        );
+    }
+    if let Some(id) = new.get("Identifier") {
+        item.insert("Identifier".to_string(), id.clone());
+    }
+    Some(Value::Object(item))
+}
+
+/// Minimal correction payload `{"DraftItem": ...}` for `new` against `old`,
+/// or `None` when the documents are identical.
+pub fn delta_document(old: &Value, new: &Value) -> Option<Value> {
+    let item = sparse_item(old.get("DraftItem"), new.get("DraftItem")?)?;
+    let mut doc = Map::new();
+    doc.insert("DraftItem".to_string(), item);
+    Some(Value::Object(doc))
+}
+
+fn flatten(value: &Value, path: String, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let p = if path.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}/{}", path, k)
+                };
+                flatten(v, p, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten(v, format!("{}[{}]", path, i), out);
+            }
+        }
+        Value::String(s) => {
+            out.insert(path, s.clone());
+        }
+        Value::Null => {}
+        other => {
+            out.insert(path, other.to_string());
+        }
+    }
+}
+
+/// Changed leaves between two documents as `(path, old, new)`; an empty side
+/// means the attribute was added or removed.
+pub fn changed_fields(old: &Value, new: &Value) -> Vec<(String, String, String)> {
+    let (mut a, mut b) = (BTreeMap::new(), BTreeMap::new());
+    flatten(old, String::new(), &mut a);
+    flatten(new, String::new(), &mut b);
+    let mut changes = Vec::new();
+    for (path, new_value) in &b {
+        match a.get(path) {
+            Some(old_value) if old_value == new_value => {}
+            old_value => changes.push((
+                path.clone(),
+                old_value.cloned().unwrap_or_default(),
+                new_value.clone(),
+            )),
+        }
+    }
+    for (path, old_value) in &a {
+        if !b.contains_key(path) {
+            changes.push((path.clone(), old_value.clone(), String::new()));
+        }
+    }
+    changes.sort();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_keeps_only_changed_modules_and_children() {
+        let old = serde_json::json!({"DraftItem": {
+            "TradeItem": {"Gtin": "1", "A": {"x": 1}, "B": "same"},
+            "CatalogueItemChildItemLink": [
+                {"Quantity": 10, "CatalogueItem": {"TradeItem": {"Gtin": "2", "C": "old"}}},
+                {"Quantity": 5, "CatalogueItem": {"TradeItem": {"Gtin": "3", "D": "same"}}}
+            ],
+            "Identifier": "Draft_u"}});
+        let mut new = old.clone();
+        new["DraftItem"]["TradeItem"]["A"]["x"] = 2.into();
+        new["DraftItem"]["CatalogueItemChildItemLink"][0]["CatalogueItem"]["TradeItem"]["C"] =
+            "new".into();
+
+        let delta = delta_document(&old, &new).unwrap();
+        assert_eq!(
+            delta,
+            serde_json::json!({"DraftItem": {
+                "TradeItem": {"Gtin": "1", "A": {"x": 2}},
+                "CatalogueItemChildItemLink": [
+                    {"Quantity": 10, "CatalogueItem": {"TradeItem": {"Gtin": "2", "C": "new"}}}
+                ],
+                "Identifier": "Draft_u"}})
+        );
+        assert!(delta_document(&old, &old).is_none());
+        assert_eq!(changed_fields(&old, &new).len(), 2);
+    }
+}
@@ -0,0 +1,131 @@
+//! Custom-made devices and systems / procedure packs, told apart by the
+//! `deviceCriterion` / `containerType` of device-level and listing records.
+//!
+//! - A system or procedure pack (MDR Art. 22) goes through the SPP branch of
+//!   the device-level transform: ContactType EPP, `SystemOrProcedurePackTypeCode`
+//!   and its medical purpose instead of the device flags (097.016 / 097.049 /
+//!   097.050), like `transform_detail` does from `multiComponent.criterion`.
+//!   Only under MDR; elsewhere the record stays a standard device. The pack
+//!   is still the base unit (`BASE_UNIT_OR_EACH`).
+//! - A custom-made device has no UDI-DI (MDR Art. 27(1)) and cannot be
+//!   registered on firstbase: it gets no document and is listed in
+//!   `firstbase_json/excluded_<time>.txt` at the end of the run.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Report rows of this run: device, criterion / container type, reason.
+static REPORT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Standard,
+    /// `type_code` is the `SystemOrProcedurePackTypeCode`: SYSTEM or
+    /// PROCEDURE_PACK.
+    SystemOrProcedurePack {
+        type_code: &'static str,
+    },
+    CustomMade,
+}
+
+impl Category {
+    /// Why firstbase cannot take a device of this category; `None` when it can.
+    pub fn excluded_reason(self) -> Option<&'static str> {
+        match self {
+            Category::CustomMade => Some("custom-made device without UDI-DI (MDR Art. 27(1))"),
+            _ => None,
+        }
+    }
+}
+
+/// The category from a record's device criterion and container type, plain
+/// (`SPP`, `CUSTOM_MADE`) or refdata codes (`refdata.container-type.system`).
+pub fn classify(device_criterion: Option<&str>, container_type: Option<&str>) -> Category {
+    let values: Vec<String> = [device_criterion, container_type]
+        .into_iter()
+        .flatten()
+        .map(|v| {
+            v.trim()
+                .rsplit('.')
+                .next()
+                .unwrap_or(v)
+                .to_ascii_uppercase()
+                .replace(['-', ' '], "_")
+        })
+        .collect();
+    let any = |f: &dyn Fn(&str) -> bool| values.iter().any(|v| f(v));
+    if any(&|v| v.contains("CUSTOM")) {
+        Category::CustomMade
+    } else if any(&|v| v.contains("SYSTEM")) {
+        Category::SystemOrProcedurePack {
+            type_code: "SYSTEM",
+        }
+    } else if any(&|v| v == "SPP" || v.contains("PROCEDURE_PACK")) {
+        Category::SystemOrProcedurePack {
+            type_code: "PROCEDURE_PACK",
+        }
+    } else {
+        Category::Standard
+    }
+}
+
+/// Record `device` (UUID or GTIN) as left out for `reason`; `source` is the
+/// criterion / container type it was classified from.
+pub fn exclude(device: &str, source: &str, reason: &str) {
+    if let Ok(mut report) = REPORT.lock() {
+        report.push(format!("{}\t{}\t{}", device, source, reason));
+    }
+}
+
+/// Write this run's excluded devices to `<output_dir>/excluded_<time>.txt`
+/// (tab-separated device, criterion / container type, reason); the path and
+/// count, `None` when nothing was excluded.
+pub fn write_report(output_dir: &Path) -> anyhow::Result<Option<(PathBuf, usize)>> {
+    let rows = match REPORT.lock() {
+        Ok(mut rows) => std::mem::take(&mut *rows),
+        Err(_) => return Ok(None),
+    };
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(format!(
+        "excluded_{}.txt",
+        chrono::Local::now().format("%H.%M_%d.%m.%Y")
+    ));
+    std::fs::write(&path, rows.join("\n") + "\n")?;
+    Ok(Some((path, rows.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn criteria_and_container_types() {
+        assert_eq!(classify(Some("STANDARD"), None), Category::Standard);
+        assert_eq!(classify(None, None), Category::Standard);
+        assert_eq!(
+            classify(Some("SPP"), None),
+            Category::SystemOrProcedurePack {
+                type_code: "PROCEDURE_PACK"
+            }
+        );
+        assert_eq!(
+            classify(Some("STANDARD"), Some("refdata.container-type.spp-system")),
+            Category::SystemOrProcedurePack {
+                type_code: "SYSTEM"
+            }
+        );
+        assert_eq!(
+            classify(Some("SPP"), Some("Procedure pack")),
+            Category::SystemOrProcedurePack {
+                type_code: "PROCEDURE_PACK"
+            }
+        );
+        let custom = classify(Some("refdata.device-criterion.custom-made"), Some("SYSTEM"));
+        assert_eq!(custom, Category::CustomMade);
+        assert!(custom.excluded_reason().is_some());
+        assert!(classify(Some("SPP"), None).excluded_reason().is_none());
+    }
+}
@@ -0,0 +1,275 @@
+//! `--risk-class` / `--legislation`: restrict a run to part of the devices,
+//! e.g. all class III MDR devices, instead of filtering the JSON afterwards.
+//!
+//! Applied twice. At download time on the listing rows (`riskClass.code`,
+//! `applicableLegislation`), so details of other devices are never fetched
+//! and `--N` counts matching devices. At transform time on the finished
+//! trade item (risk class of classification system 76/85, `RegulatoryAct`),
+//! which covers every input mode and the devices downloaded before.
+//!
+//! Values are comma separated and may repeat the flag: risk classes as `III`,
+//! `IIb`, `class-iii`, `EU_CLASS_IIB` or `C`; legislations as MDR, IVDR, MDD,
+//! AIMDD or IVDD. A device passes when it matches one value of each given
+//! flag. Without a legislation it is derived from the risk class
+//! (`mappings::regulation_from_risk_class_refdata`); without a risk class it
+//! fails a `--risk-class` filter.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use anyhow::Result;
+
+use crate::firstbase::TradeItem;
+
+const RISK_CLASSES: [&str; 8] = ["I", "IIA", "IIB", "III", "A", "B", "C", "D"];
+const LEGISLATIONS: [&str; 5] = ["MDR", "IVDR", "MDD", "AIMDD", "IVDD"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// GS1 codes, `EU_CLASS_III` …
+    pub risk_classes: Vec<String>,
+    /// `MDR` …
+    pub legislations: Vec<String>,
+}
+
+static ACTIVE: RwLock<Option<Filter>> = RwLock::new(None);
+static FILTERED: AtomicUsize = AtomicUsize::new(0);
+
+/// `EU_CLASS_IIB` from `IIb`, `class-iib`, `refdata.risk-class.class-iib`,
+/// `CLASS_IIB` or `EU_CLASS_IIB`; `None` for other codes.
+pub fn normalize_risk_class(value: &str) -> Option<String> {
+    let value = value.trim();
+    let last = value.rsplit('.').next().unwrap_or(value);
+    let upper = last.to_ascii_uppercase().replace('-', "_");
+    let core = upper.strip_prefix("EU_").unwrap_or(&upper);
+    let core = core.strip_prefix("CLASS_").unwrap_or(core);
+    RISK_CLASSES
+        .contains(&core)
+        .then(|| format!("EU_CLASS_{}", core))
+}
+
+/// `MDR` from `mdr` or `refdata.applicable-legislation.mdr`.
+fn normalize_legislation(value: &str) -> String {
+    let value = value.trim();
+    value
+        .rsplit('.')
+        .next()
+        .unwrap_or(value)
+        .to_ascii_uppercase()
+}
+
+impl Filter {
+    /// `--risk-class <v,...>` and `--legislation <v,...>`, both optional.
+    pub fn from_args(args: &[String]) -> Result<Filter> {
+        let values = |flag: &str| -> Result<Vec<String>> {
+            let mut values = Vec::new();
+            for (i, _) in args.iter().enumerate().filter(|(_, a)| *a == flag) {
+                let list = args
+                    .get(i + 1)
+                    .filter(|v| !v.starts_with("--"))
+                    .ok_or_else(|| anyhow::anyhow!("{} requires a value", flag))?;
+                values.extend(
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .map(str::to_string),
+                );
+            }
+            Ok(values)
+        };
+        let mut filter = Filter::default();
+        for value in values("--risk-class")? {
+            let code = normalize_risk_class(&value).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown --risk-class {:?} (expected I, IIa, IIb, III, A, B, C or D)",
+                    value
+                )
+            })?;
+            if !filter.risk_classes.contains(&code) {
+                filter.risk_classes.push(code);
+            }
+        }
+        for value in values("--legislation")? {
+            let act = normalize_legislation(&value);
+            if !LEGISLATIONS.contains(&act.as_str()) {
+                anyhow::bail!(
+                    "Unknown --legislation {:?} (expected {})",
+                    value,
+                    LEGISLATIONS.join(", ")
+                );
+            }
+            if !filter.legislations.contains(&act) {
+                filter.legislations.push(act);
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.risk_classes.is_empty() && self.legislations.is_empty()
+    }
+
+    /// Whether a device with this risk class and legislation (any of the
+    /// accepted spellings, refdata codes included) passes.
+    pub fn matches(&self, risk_class: Option<&str>, legislation: Option<&str>) -> bool {
+        let risk_class = risk_class.filter(|r| !r.is_empty());
+        if !self.risk_classes.is_empty()
+            && !risk_class
+                .and_then(normalize_risk_class)
+                .is_some_and(|r| self.risk_classes.contains(&r))
+        {
+            return false;
+        }
+        if self.legislations.is_empty() {
+            return true;
+        }
+        let act = match legislation.filter(|l| !l.is_empty()) {
+            Some(l) => normalize_legislation(l),
+            None => match risk_class {
+                Some(r) => gs1_regulation(r).to_string(),
+                None => return false,
+            },
+        };
+        self.legislations.contains(&act)
+    }
+
+    /// A listing row of the EUDAMED device search.
+    pub fn matches_listing(&self, item: &serde_json::Value) -> bool {
+        let risk_class = item
+            .get("riskClass")
+            .and_then(|r| r.get("code").or(Some(r)))
+            .and_then(|r| r.as_str());
+        let legislation = item
+            .get("applicableLegislation")
+            .and_then(|l| l.get("code").or(Some(l)))
+            .and_then(|l| l.as_str());
+        self.matches(risk_class, legislation)
+    }
+
+    /// A converted trade item.
+    pub fn matches_trade_item(&self, item: &TradeItem) -> bool {
+        let risk_class = item
+            .classification
+            .additional_classifications
+            .iter()
+            .filter(|c| matches!(c.system_code.value.as_ref(), "76" | "85"))
+            .find_map(|c| c.values.first())
+            .map(|v| v.code_value.as_str());
+        let act = item
+            .regulated_trade_item_module
+            .as_ref()
+            .and_then(|m| m.info.first())
+            .map(|i| i.act.as_str());
+        self.matches(risk_class, act)
+    }
+}
+
+/// Regulatory act of a refdata or GS1 risk class code (`EU_CLASS_C` → IVDR).
+fn gs1_regulation(risk_class: &str) -> &'static str {
+    let upper = risk_class.to_ascii_uppercase();
+    if upper.starts_with("IVDD") {
+        return "IVDD";
+    }
+    match normalize_risk_class(risk_class).as_deref() {
+        Some("EU_CLASS_A" | "EU_CLASS_B" | "EU_CLASS_C" | "EU_CLASS_D") => "IVDR",
+        Some(_) => "MDR",
+        None => {
+            let code = risk_class.to_ascii_lowercase();
+            let act = crate::mappings::regulation_from_risk_class_refdata(&code);
+            LEGISLATIONS
+                .into_iter()
+                .find(|l| *l == act)
+                .unwrap_or("MDR")
+        }
+    }
+}
+
+/// Use `filter` for this run; an empty one turns filtering off.
+pub fn configure(filter: Filter) {
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = (!filter.is_empty()).then_some(filter);
+    }
+}
+
+/// The run's filter, if any.
+pub fn active() -> Option<Filter> {
+    ACTIVE.read().ok().and_then(|a| a.clone())
+}
+
+/// Whether the run's filter passes `item`; a rejected item is counted.
+pub fn admits(item: &TradeItem) -> bool {
+    let admitted = ACTIVE
+        .read()
+        .map(|a| a.as_ref().is_none_or(|f| f.matches_trade_item(item)))
+        .unwrap_or(true);
+    if !admitted {
+        FILTERED.fetch_add(1, Ordering::Relaxed);
+    }
+    admitted
+}
+
+/// `admits` for a listing row.
+pub fn admits_listing(item: &serde_json::Value) -> bool {
+    let admitted = ACTIVE
+        .read()
+        .map(|a| a.as_ref().is_none_or(|f| f.matches_listing(item)))
+        .unwrap_or(true);
+    if !admitted {
+        FILTERED.fetch_add(1, Ordering::Relaxed);
+    }
+    admitted
+}
+
+/// One line for the end of the run: how many devices the filter left out.
+pub fn summary() -> Option<String> {
+    let filter = active()?;
+    let mut flags = Vec::new();
+    if !filter.risk_classes.is_empty() {
+        flags.push(format!("--risk-class {}", filter.risk_classes.join(",")));
+    }
+    if !filter.legislations.is_empty() {
+        flags.push(format!("--legislation {}", filter.legislations.join(",")));
+    }
+    Some(format!(
+        "{} device(s) outside {}",
+        FILTERED.load(Ordering::Relaxed),
+        flags.join(" ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_risk_class_and_legislation() {
+        let args = |s: &str| s.split(' ').map(str::to_string).collect::<Vec<_>>();
+        let filter = Filter::from_args(&args(
+            "detail d.ndjson --risk-class III,iib --legislation mdr",
+        ))
+        .unwrap();
+        assert_eq!(filter.risk_classes, ["EU_CLASS_III", "EU_CLASS_IIB"]);
+        assert_eq!(filter.legislations, ["MDR"]);
+        assert!(Filter::from_args(&args("x --risk-class IV")).is_err());
+        assert!(Filter::from_args(&args("x --legislation")).is_err());
+        assert!(Filter::from_args(&args("x")).unwrap().is_empty());
+
+        assert!(filter.matches(Some("refdata.risk-class.class-iii"), None));
+        assert!(filter.matches(Some("EU_CLASS_IIB"), Some("MDR")));
+        assert!(!filter.matches(Some("EU_CLASS_IIB"), Some("MDD")));
+        assert!(!filter.matches(Some("class-iia"), Some("MDR")));
+        assert!(!filter.matches(None, Some("MDR")));
+
+        let ivdr = Filter::from_args(&args("x --legislation IVDR")).unwrap();
+        assert!(ivdr.matches(Some("EU_CLASS_C"), None));
+        assert!(!ivdr.matches(Some("refdata.risk-class.class-iii"), None));
+        assert!(ivdr.matches_listing(&serde_json::json!({
+            "riskClass": {"code": "refdata.risk-class.class-d"},
+            "applicableLegislation": "refdata.applicable-legislation.ivdr"
+        })));
+        assert!(!filter.matches_listing(&serde_json::json!({
+            "riskClass": {"code": "refdata.risk-class.class-iii"},
+            "applicableLegislation": "refdata.applicable-legislation.mdd"
+        })));
+    }
+}
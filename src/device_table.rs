@@ -0,0 +1,277 @@
+//! Rows of the GUI device table: one summary per converted device in
+//! `firstbase_json/` (+ `processed/`), with the text search, column sort and
+//! status / risk class / location filters applied outside the UI code. The
+//! table itself (`App::render_devices`) only draws the visible rows.
+
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::report::{array_at, base_unit, firstbase_docs, risk_class, str_at};
+
+#[derive(Debug, Clone, Default)]
+pub struct DeviceRow {
+    pub uuid: String,
+    pub gtin: String,
+    pub trade_name: String,
+    pub manufacturer: String,
+    pub srn: String,
+    pub risk_class: String,
+    pub regulation: String,
+    pub status: String,
+    /// Still in `firstbase_json/` (not pushed, or rejected) vs. `processed/`.
+    pub pending: bool,
+    pub path: PathBuf,
+    /// Lower-cased GTIN, trade name, manufacturer and SRN for the search box.
+    haystack: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Gtin,
+    TradeName,
+    Manufacturer,
+    Srn,
+    RiskClass,
+    Regulation,
+    Status,
+    Location,
+}
+
+impl Column {
+    pub const ALL: [Column; 8] = [
+        Column::Gtin,
+        Column::TradeName,
+        Column::Manufacturer,
+        Column::Srn,
+        Column::RiskClass,
+        Column::Regulation,
+        Column::Status,
+        Column::Location,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Gtin => "GTIN",
+            Column::TradeName => "Trade name",
+            Column::Manufacturer => "Manufacturer",
+            Column::Srn => "SRN",
+            Column::RiskClass => "Risk class",
+            Column::Regulation => "Regulation",
+            Column::Status => "Status",
+            Column::Location => "Location",
+        }
+    }
+}
+
+impl DeviceRow {
+    pub fn cell(&self, column: Column) -> &str {
+        match column {
+            Column::Gtin => &self.gtin,
+            Column::TradeName => &self.trade_name,
+            Column::Manufacturer => &self.manufacturer,
+            Column::Srn => &self.srn,
+            Column::RiskClass => &self.risk_class,
+            Column::Regulation => &self.regulation,
+            Column::Status => &self.status,
+            Column::Location => {
+                if self.pending {
+                    "pending"
+                } else {
+                    "processed"
+                }
+            }
+        }
+    }
+}
+
+fn row_for(doc: &serde_json::Value, uuid: &str, path: &Path, pending: bool) -> DeviceRow {
+    let top = doc
+        .pointer("/DraftItem/TradeItem")
+        .unwrap_or(&serde_json::Value::Null);
+    let base = base_unit(doc);
+    let manufacturer = [base, top].iter().find_map(|item| {
+        array_at(item, "/TradeItemContactInformation")
+            .iter()
+            .find(|c| str_at(c, "/ContactTypeCode/Value") == "EMA")
+    });
+    let srn = manufacturer
+        .and_then(|c| {
+            array_at(c, "/AdditionalPartyIdentification")
+                .iter()
+                .find(|p| str_at(p, "/AdditionalPartyIdentificationTypeCode") == "SRN")
+        })
+        .map(|p| str_at(p, "/Value"))
+        .unwrap_or("");
+    let mut row = DeviceRow {
+        uuid: uuid.to_string(),
+        gtin: str_at(top, "/Gtin").to_string(),
+        trade_name: str_at(
+            base,
+            "/TradeItemDescriptionModule/TradeItemDescriptionInformation/TradeItemDescription/0/Value",
+        )
+        .to_string(),
+        manufacturer: manufacturer
+            .map(|c| str_at(c, "/ContactName"))
+            .unwrap_or("")
+            .to_string(),
+        srn: srn.to_string(),
+        risk_class: risk_class(base).to_string(),
+        regulation: str_at(
+            base,
+            "/RegulatedTradeItemModule/RegulatoryInformation/0/RegulatoryAct",
+        )
+        .to_string(),
+        status: str_at(
+            base,
+            "/MedicalDeviceTradeItemModule/MedicalDeviceInformation/EUMedicalDeviceStatusCode/Value",
+        )
+        .to_string(),
+        pending,
+        path: path.to_path_buf(),
+        haystack: String::new(),
+    };
+    row.haystack = format!(
+        "{} {} {} {} {}",
+        row.gtin, row.trade_name, row.manufacturer, row.srn, row.uuid
+    )
+    .to_lowercase();
+    row
+}
+
+/// Summaries of every per-UUID document, read in parallel. A device pending
+/// again after a push exists in both dirs; the pending copy is listed.
+pub fn load(firstbase_dir: &Path) -> Vec<DeviceRow> {
+    let mut rows: Vec<DeviceRow> = firstbase_docs(firstbase_dir, true)
+        .par_iter()
+        .filter_map(|p| {
+            let doc: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(p).ok()?).ok()?;
+            let uuid = p.file_stem()?.to_string_lossy().to_string();
+            Some(row_for(&doc, &uuid, p, p.parent() == Some(firstbase_dir)))
+        })
+        .collect();
+    rows.sort_by(|a, b| (&a.uuid, !a.pending).cmp(&(&b.uuid, !b.pending)));
+    rows.dedup_by(|b, a| a.uuid == b.uuid);
+    rows
+}
+
+/// Search text, sort and filters of the table.
+#[derive(Debug, Clone)]
+pub struct View {
+    pub query: String,
+    pub sort: Column,
+    pub ascending: bool,
+    /// Exact-match column filters; `None` = all.
+    pub status: Option<String>,
+    pub risk_class: Option<String>,
+    pub pending: Option<bool>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            query: String::new(),
+            sort: Column::Gtin,
+            ascending: true,
+            status: None,
+            risk_class: None,
+            pending: None,
+        }
+    }
+}
+
+impl View {
+    /// Click on a column header: sort by it, or flip the direction.
+    pub fn toggle_sort(&mut self, column: Column) {
+        if self.sort == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.sort = column;
+            self.ascending = true;
+        }
+    }
+
+    /// Indices into `rows` that pass the search and filters, in sort order.
+    pub fn apply(&self, rows: &[DeviceRow]) -> Vec<usize> {
+        let words: Vec<String> = self
+            .query
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect();
+        let mut visible: Vec<usize> = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| words.iter().all(|w| r.haystack.contains(w.as_str())))
+            .filter(|(_, r)| self.status.as_ref().is_none_or(|s| &r.status == s))
+            .filter(|(_, r)| self.risk_class.as_ref().is_none_or(|c| &r.risk_class == c))
+            .filter(|(_, r)| self.pending.is_none_or(|p| r.pending == p))
+            .map(|(i, _)| i)
+            .collect();
+        visible.sort_by(|&a, &b| {
+            let order = rows[a]
+                .cell(self.sort)
+                .cmp(rows[b].cell(self.sort))
+                .then_with(|| rows[a].gtin.cmp(&rows[b].gtin));
+            if self.ascending {
+                order
+            } else {
+                order.reverse()
+            }
+        });
+        visible
+    }
+}
+
+/// Distinct non-empty values of `column`, sorted, for the filter drop-downs.
+pub fn distinct(rows: &[DeviceRow], column: Column) -> Vec<String> {
+    let mut values: Vec<String> = rows
+        .iter()
+        .map(|r| r.cell(column))
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_filter_and_sort() {
+        let row = |gtin: &str, name: &str, class: &str, pending: bool| {
+            let doc = serde_json::json!({"DraftItem": {"TradeItem": {
+                "Gtin": gtin,
+                "IsTradeItemABaseUnit": true,
+                "TradeItemDescriptionModule": {"TradeItemDescriptionInformation": {
+                    "TradeItemDescription": [{"Value": name}]}},
+                "GdsnTradeItemClassification": {"AdditionalTradeItemClassification": [{
+                    "AdditionalTradeItemClassificationSystemCode": {"Value": "76"},
+                    "AdditionalTradeItemClassificationValue": [{
+                        "AdditionalTradeItemClassificationCodeValue": class}]}]}
+            }}});
+            row_for(&doc, gtin, Path::new("x.json"), pending)
+        };
+        let rows = vec![
+            row("3", "Stent", "EU_CLASS_III", true),
+            row("1", "Grasper", "EU_CLASS_IIA", false),
+            row("2", "Grasper XL", "EU_CLASS_IIA", true),
+        ];
+        let mut view = View::default();
+        assert_eq!(view.apply(&rows), vec![1, 2, 0]);
+        view.query = "grasp".to_string();
+        view.toggle_sort(Column::Gtin);
+        assert_eq!(view.apply(&rows), vec![2, 1]);
+        view.query.clear();
+        view.risk_class = Some("EU_CLASS_IIA".to_string());
+        view.pending = Some(true);
+        assert_eq!(view.apply(&rows), vec![2]);
+        assert_eq!(
+            distinct(&rows, Column::RiskClass),
+            vec!["EU_CLASS_IIA", "EU_CLASS_III"]
+        );
+    }
+}
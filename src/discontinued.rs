@@ -0,0 +1,172 @@
+//! `DiscontinuedDateTime` of devices no longer placed on the market. It used
+//! to be the conversion date + 1 day; now it is derived from the device's
+//! own data, in this order:
+//!
+//! 1. the latest market end date (`EndAvailabilityDateTime` of its sales
+//!    conditions);
+//! 2. the date of the status change (`deviceStatus.statusDate`);
+//! 3. the conversion date + 1 day, as before.
+//!
+//! Markets without an end date end at the discontinued date. The push still
+//! moves a date that is not safely after the registration forward (910.005,
+//! `restamp_discontinued_date` in `gui.rs`) and sends discontinued devices
+//! already accepted in the environment with `Change` instead of
+//! `Add` (see `push_command`).
+
+use chrono::DateTime;
+
+use crate::config::Dates;
+use crate::dates;
+use crate::firstbase::{
+    CatalogueItemChildItemLink, FirstbaseDocument, SalesInformationModule, TradeItem,
+};
+
+/// `DocumentCommand` of a push: a new item is added, a discontinued one that
+/// firstbase already has is changed.
+pub const ADD: &str = "Add";
+pub const CHANGE: &str = "Change";
+
+/// EUDAMED status (plain or GS1 code) of a device no longer on the market.
+pub fn is_no_longer(status: &str) -> bool {
+    matches!(
+        status,
+        "NO_LONGER_PLACED_ON_THE_MARKET" | "NO_LONGER_ON_THE_MARKET" | "NO_LONGER_PLACED_ON_MARKET"
+    )
+}
+
+/// The discontinued datetime from the market end dates of `sales`, else
+/// `status_date`, else tomorrow.
+pub fn date(
+    zones: &Dates,
+    status_date: Option<&str>,
+    sales: Option<&SalesInformationModule>,
+) -> String {
+    let market_end = sales
+        .into_iter()
+        .flat_map(|m| &m.sales.conditions)
+        .flat_map(|c| &c.countries)
+        .filter_map(|c| c.end_datetime.as_deref())
+        .filter_map(|end| Some((DateTime::parse_from_rfc3339(end).ok()?, end)))
+        .max_by_key(|(at, _)| *at)
+        .map(|(_, end)| end.to_string());
+    market_end
+        .or_else(|| {
+            status_date
+                .filter(|d| !d.is_empty())
+                .map(|d| dates::day_at(zones, d, dates::END_OF_DAY))
+        })
+        .unwrap_or_else(|| dates::days_from_now(zones, 1))
+}
+
+/// End every market of `sales` that has no end date at `discontinued`.
+pub fn close_markets(sales: &mut Option<SalesInformationModule>, discontinued: &str) {
+    for condition in sales.iter_mut().flat_map(|m| &mut m.sales.conditions) {
+        for country in &mut condition.countries {
+            if country.end_datetime.is_none() {
+                country.end_datetime = Some(discontinued.to_string());
+            }
+        }
+    }
+}
+
+/// Give every package level of a document with a discontinued base unit its
+/// status and date: a discontinued child under a parent on the market fails
+/// 910.004 / 910.005 / 097.040.
+pub fn apply_to_packages(doc: &mut FirstbaseDocument) {
+    fn base_unit(links: &[CatalogueItemChildItemLink]) -> Option<&TradeItem> {
+        links.iter().find_map(|link| {
+            let item = &link.catalogue_item.trade_item;
+            if item.is_base_unit {
+                Some(item)
+            } else {
+                base_unit(&link.catalogue_item.children)
+            }
+        })
+    }
+    fn set(links: &mut [CatalogueItemChildItemLink], status: &str, date: &str) {
+        for link in links {
+            let item = &mut link.catalogue_item.trade_item;
+            item.medical_device_module.info.eu_status.value = status.to_string().into();
+            item.synchronisation_dates.discontinued = Some(date.to_string());
+            set(&mut link.catalogue_item.children, status, date);
+        }
+    }
+
+    let Some(base) = base_unit(&doc.children) else {
+        return;
+    };
+    let Some(date) = base.synchronisation_dates.discontinued.clone() else {
+        return;
+    };
+    let status = base.medical_device_module.info.eu_status.value.to_string();
+    let top = &mut doc.trade_item;
+    top.medical_device_module.info.eu_status.value = status.clone().into();
+    top.synchronisation_dates.discontinued = Some(date.clone());
+    set(&mut doc.children, &status, &date);
+}
+
+/// `DocumentCommand` for a document: `Change` when it carries a
+/// `DiscontinuedDateTime` and the environment accepted it before, else `Add`.
+pub fn push_command(doc: &serde_json::Value, accepted_before: bool) -> &'static str {
+    let discontinued = doc
+        .pointer("/DraftItem/TradeItem/TradeItemSynchronisationDates/DiscontinuedDateTime")
+        .and_then(|v| v.as_str())
+        .is_some_and(|s| !s.is_empty());
+    if discontinued && accepted_before {
+        CHANGE
+    } else {
+        ADD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firstbase::*;
+
+    #[test]
+    fn market_end_before_status_date_before_tomorrow() {
+        let zones = Dates::default();
+        let country = |code: &'static str, end: Option<&str>| SalesConditionCountry {
+            country_code: CodeValue { value: code.into() },
+            end_datetime: end.map(str::to_string),
+            start_datetime: String::new(),
+        };
+        let mut sales = Some(SalesInformationModule {
+            sales: SalesInformation {
+                conditions: vec![TargetMarketSalesCondition {
+                    condition_code: CodeValue {
+                        value: "ORIGINAL_PLACED".into(),
+                    },
+                    countries: vec![
+                        country("276", Some("2025-03-31T21:00:00+00:00")),
+                        country("040", Some("2025-06-30T21:00:00+00:00")),
+                        country("250", None),
+                    ],
+                }],
+            },
+        });
+        let discontinued = date(&zones, Some("2025-01-15"), sales.as_ref());
+        assert_eq!(discontinued, "2025-06-30T21:00:00+00:00");
+        close_markets(&mut sales, &discontinued);
+        let countries = &sales.as_ref().unwrap().sales.conditions[0].countries;
+        assert_eq!(
+            countries[2].end_datetime.as_deref(),
+            Some(discontinued.as_str())
+        );
+
+        assert_eq!(
+            date(&zones, Some("2025-01-15"), None),
+            "2025-01-15T21:00:00+00:00"
+        );
+        assert!(date(&zones, None, None) > dates::now(&zones));
+        assert!(is_no_longer("NO_LONGER_PLACED_ON_MARKET"));
+
+        let doc = serde_json::json!({"DraftItem": {"TradeItem": {
+            "TradeItemSynchronisationDates": {"DiscontinuedDateTime": discontinued}
+        }}});
+        assert_eq!(push_command(&doc, true), CHANGE);
+        assert_eq!(push_command(&doc, false), ADD);
+        assert_eq!(push_command(&serde_json::json!({}), true), ADD);
+    }
+}
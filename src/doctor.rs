@@ -0,0 +1,507 @@
+//! `doctor`: checks the environment end to end before a run and prints one
+//! PASS / WARN / FAIL line per check, with what to do about a failure:
+//!
+//! - config: `config.toml` parses, GLNs have 13 digits and a valid check
+//!   digit, `[warnings]` only names known codes;
+//! - write access to the output directories (probe file, removed again);
+//! - EUDAMED: one single-row listing request;
+//! - GS1 firstbase: a token request with the CLI credential (`login` or
+//!   `FIRSTBASE_EMAIL` / `FIRSTBASE_PASSWORD`) in `FIRSTBASE_ENV`;
+//! - database: `db/version_tracking.db` passes `quick_check` and has every
+//!   table and column the current `version_db::open_db` creates; an older
+//!   schema is a warning, the next run adds what is missing;
+//! - mapping tables: no code mapped two ways, every GDSN market country has a
+//!   numeric code, the GPC bricks of `[gpc]` / `[gpc_by_emdn]` have 8 digits.
+//!
+//! `--offline` skips the two network checks. Only FAIL makes the exit code
+//! non-zero.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::config::{self, Config};
+use crate::{credentials, download, gtin, gui, mapping_matrix, mappings, version_db, warnings};
+
+/// Directories a run writes to, relative to the working directory.
+pub const OUTPUT_DIRS: [&str; 5] = [
+    "firstbase_json",
+    "processed",
+    download::DEFAULT_DATA_DIR,
+    "db",
+    "log",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a WARN / FAIL.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check; `offline` skips EUDAMED and GS1.
+pub fn run(config_path: &Path, offline: bool) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let config = match config::load_config(config_path) {
+        Ok(config) => {
+            let problems = config_problems(&config);
+            checks.push(if problems.is_empty() {
+                Check::pass("config", config_path.display().to_string())
+            } else {
+                Check::fail(
+                    "config",
+                    format!("{}: {}", config_path.display(), problems.join("; ")),
+                    format!("Correct these entries in {}", config_path.display()),
+                )
+            });
+            Some(config)
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "config",
+                format!("{}: {:#}", config_path.display(), e),
+                format!(
+                    "Copy config.sample.toml to {} and fill in [provider], or set {}",
+                    config_path.display(),
+                    crate::platform_dirs::CONFIG_ENV
+                ),
+            ));
+            None
+        }
+    };
+
+    checks.push(write_access(Path::new("."), &OUTPUT_DIRS));
+
+    if offline {
+        checks.push(Check::warn(
+            "EUDAMED API",
+            "skipped",
+            "Run without --offline",
+        ));
+        checks.push(Check::warn(
+            "GS1 firstbase",
+            "skipped",
+            "Run without --offline",
+        ));
+    } else {
+        checks.push(eudamed());
+        checks.push(match &config {
+            Some(config) => firstbase(config),
+            None => Check::fail(
+                "GS1 firstbase",
+                "not tested, the config did not load",
+                "Fix the config first (needs the Provider GLN)",
+            ),
+        });
+    }
+
+    checks.push(database(Path::new(version_db::VERSION_DB_PATH)));
+    checks.push(mapping_tables(config.as_ref()));
+    checks
+}
+
+/// Print `checks`; true when none failed.
+pub fn print(checks: &[Check]) -> bool {
+    for check in checks {
+        let status = match check.status {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+        };
+        println!("[{}] {:<14} {}", status, check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("       {:<14} → {}", "", hint);
+        }
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        checks.len() - failed - warned,
+        warned,
+        failed
+    );
+    failed == 0
+}
+
+fn is_gln(gln: &str) -> bool {
+    gln.len() == 13 && gtin::normalize(gln).is_ok()
+}
+
+/// Entries of a loaded config that would only fail later, at GS1.
+fn config_problems(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut gln = |what: String, gln: &str| {
+        if !is_gln(gln) {
+            problems.push(format!("{} '{}' is not a GLN", what, gln));
+        }
+    };
+    gln("[provider] gln".into(), &config.provider.gln);
+    if !config.provider.publish_gln.is_empty() {
+        gln(
+            "[provider] publish_gln".into(),
+            &config.provider.publish_gln,
+        );
+    }
+    let parties = &config.parties;
+    let by_srn = parties.by_srn.iter().flat_map(|(srn, p)| {
+        [
+            (
+                format!("[parties.by_srn.\"{}\"] brand_owner", srn),
+                &p.brand_owner,
+            ),
+            (
+                format!("[parties.by_srn.\"{}\"] manufacturer", srn),
+                &p.manufacturer,
+            ),
+        ]
+    });
+    for (what, party) in [
+        ("[parties] brand_owner".to_string(), &parties.brand_owner),
+        ("[parties] manufacturer".to_string(), &parties.manufacturer),
+    ]
+    .into_iter()
+    .chain(by_srn)
+    {
+        if let Some(party) = party {
+            gln(what, &party.gln);
+        }
+    }
+
+    let warning = &config.warnings;
+    for code in warning
+        .suppress
+        .iter()
+        .chain(warning.suppress_by_gtin.values().flatten())
+    {
+        let code = code.trim().to_ascii_uppercase();
+        if !warnings::Warning::ALL.iter().any(|w| w.code() == code) {
+            problems.push(format!("[warnings] unknown code '{}'", code));
+        }
+    }
+    problems
+}
+
+/// Create each of `dirs` under `base` and write and remove a probe file.
+fn write_access(base: &Path, dirs: &[&str]) -> Check {
+    let name = "write access";
+    let mut failed = Vec::new();
+    for dir in dirs {
+        let dir = base.join(dir);
+        let probe = dir.join(".doctor_probe");
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(&probe, b"ok"))
+            .and_then(|_| std::fs::remove_file(&probe));
+        if let Err(e) = result {
+            failed.push(format!("{}: {}", dir.display(), e));
+        }
+    }
+    let cwd = std::env::current_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_else(|_| base.display().to_string());
+    if failed.is_empty() {
+        Check::pass(name, format!("{} ({})", dirs.join(", "), cwd))
+    } else {
+        Check::fail(
+            name,
+            failed.join("; "),
+            format!(
+                "Fix the permissions under {} or set {} to a writable directory",
+                cwd,
+                crate::platform_dirs::HOME_ENV
+            ),
+        )
+    }
+}
+
+fn eudamed() -> Check {
+    let name = "EUDAMED API";
+    let url = format!(
+        "{}?page=0&pageSize=1&iso2Code=en&languageIso2Code=en",
+        download::EUDAMED_BASE_URL
+    );
+    let hint = "Check the network / proxy; EUDAMED is also down during its maintenance windows";
+    let started = std::time::Instant::now();
+    match download::eudamed_agent().get(&url).call() {
+        Ok(mut resp) => {
+            let status = resp.status().as_u16();
+            let body = resp.body_mut().read_to_string().unwrap_or_default();
+            let total = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v.get("totalElements").and_then(|t| t.as_u64()));
+            match (status, total) {
+                (200..=299, Some(total)) => Check::pass(
+                    name,
+                    format!(
+                        "{} devices listed ({} ms)",
+                        total,
+                        started.elapsed().as_millis()
+                    ),
+                ),
+                (429, _) => Check::warn(
+                    name,
+                    "HTTP 429 (rate limited)",
+                    "Wait a minute; another download may be using the shared budget",
+                ),
+                (200..=299, None) => Check::fail(name, "unexpected response (no listing)", hint),
+                _ => Check::fail(name, format!("HTTP {}", status), hint),
+            }
+        }
+        Err(e) => Check::fail(name, format!("network error: {}", e), hint),
+    }
+}
+
+fn firstbase(config: &Config) -> Check {
+    let name = "GS1 firstbase";
+    let cred = credentials::resolve(credentials::Kind::Firstbase);
+    if !cred.is_complete() {
+        return Check::fail(
+            name,
+            "no credentials",
+            "Run `eudamed2firstbase login` or set FIRSTBASE_EMAIL and FIRSTBASE_PASSWORD",
+        );
+    }
+    // Same environment choice as the CLI push.
+    let env = match std::env::var("FIRSTBASE_ENV").as_deref() {
+        Ok("Production") | Ok("production") | Ok("PROD") | Ok("prod") => {
+            gui::FirstbaseEnv::Production
+        }
+        _ => gui::FirstbaseEnv::Test,
+    };
+    let http_agent = crate::http::agent_for(env.api_base());
+    let http_post = |url: &str, _auth: &str, body: &str| -> anyhow::Result<String> {
+        let mut resp = http_agent
+            .post(url)
+            .header("Content-Type", "application/json")
+            .send(body.as_bytes())?;
+        let status = resp.status();
+        let resp_body = resp.body_mut().read_to_string()?;
+        if status.as_u16() >= 400 {
+            Err(anyhow::anyhow!("http {}: {}", status, resp_body))
+        } else {
+            Ok(resp_body)
+        }
+    };
+    match gui::firstbase_token(
+        &http_post,
+        env.api_base(),
+        &cred.id,
+        &cred.secret,
+        &config.provider.gln,
+    ) {
+        Ok(_) => Check::pass(name, format!("token for {} ({})", cred.id, env.key())),
+        Err(e) => Check::fail(
+            name,
+            format!("{} ({}): {:#}", cred.id, env.key(), e),
+            "Check the password and that the account belongs to the [provider] gln; `login` stores new credentials",
+        ),
+    }
+}
+
+/// Table → columns of `conn`.
+fn schema(conn: &Connection) -> rusqlite::Result<HashMap<String, Vec<String>>> {
+    let tables: Vec<String> = conn
+        .prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )?
+        .query_map([], |r| r.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut schema = HashMap::new();
+    for table in tables {
+        let columns = conn
+            .prepare(&format!("PRAGMA table_info({})", table))?
+            .query_map([], |r| r.get::<_, String>(1))?
+            .collect::<rusqlite::Result<_>>()?;
+        schema.insert(table, columns);
+    }
+    Ok(schema)
+}
+
+/// Tables / columns of the current schema (a fresh `open_db`) that `db` lacks.
+fn missing_schema(db: &Connection) -> anyhow::Result<Vec<String>> {
+    let reference_path =
+        std::env::temp_dir().join(format!("doctor_schema_{}.db", std::process::id()));
+    let reference = schema(&version_db::open_db(&reference_path)?)?;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", reference_path.display(), suffix));
+    }
+    let actual = schema(db)?;
+    let mut missing = Vec::new();
+    for (table, columns) in &reference {
+        match actual.get(table) {
+            None => missing.push(table.clone()),
+            Some(have) => missing.extend(
+                columns
+                    .iter()
+                    .filter(|c| !have.contains(c))
+                    .map(|c| format!("{}.{}", table, c)),
+            ),
+        }
+    }
+    missing.sort();
+    Ok(missing)
+}
+
+fn database(path: &Path) -> Check {
+    let name = "database";
+    if !path.exists() {
+        return Check::warn(
+            name,
+            format!("{} not created yet", path.display()),
+            "Created by the first download or convert",
+        );
+    }
+    let restore = "Restore db/ from a backup (`eudamed2firstbase restore <backup.zip>`)";
+    let conn = match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+        Ok(conn) => conn,
+        Err(e) => return Check::fail(name, format!("{}: {}", path.display(), e), restore),
+    };
+    match conn.query_row("PRAGMA quick_check", [], |r| r.get::<_, String>(0)) {
+        Ok(result) if result == "ok" => {}
+        Ok(result) => return Check::fail(name, format!("{}: {}", path.display(), result), restore),
+        Err(e) => return Check::fail(name, format!("{}: {}", path.display(), e), restore),
+    }
+    match missing_schema(&conn) {
+        Ok(missing) if missing.is_empty() => {
+            Check::pass(name, format!("{} (current schema)", path.display()))
+        }
+        Ok(missing) => Check::warn(
+            name,
+            format!(
+                "{}: older schema, lacks {}",
+                path.display(),
+                missing.join(", ")
+            ),
+            "Added by the next download or convert; nothing to do unless another tool reads the DB",
+        ),
+        Err(e) => Check::fail(name, format!("{}: {:#}", path.display(), e), restore),
+    }
+}
+
+fn mapping_tables(config: Option<&Config>) -> Check {
+    let name = "mapping tables";
+    let mut problems = Vec::new();
+
+    let rows = mapping_matrix::mapping_matrix();
+    let mut targets: HashMap<(&str, &str, &str), &str> = HashMap::new();
+    for row in &rows {
+        let key = (
+            row.source_path.as_str(),
+            row.source_value.as_str(),
+            row.target_path.as_str(),
+        );
+        if let Some(other) = targets.insert(key, &row.target_value) {
+            if other != row.target_value {
+                problems.push(format!(
+                    "{} '{}' maps to both '{}' and '{}'",
+                    row.source_path, row.source_value, other, row.target_value
+                ));
+            }
+        }
+    }
+
+    for iso2 in mappings::ACTOR_COUNTRY_CODES {
+        let numeric = mappings::country_numeric(iso2);
+        if mappings::is_valid_gdsn_market_country(iso2)
+            && !numeric.is_some_and(|n| n.len() == 3 && n.bytes().all(|b| b.is_ascii_digit()))
+        {
+            problems.push(format!("country {} has no numeric code", iso2));
+        }
+    }
+
+    if let Some(config) = config {
+        let brick = |code: &str| code.len() == 8 && code.bytes().all(|b| b.is_ascii_digit());
+        if !brick(&config.gpc.category_code) {
+            problems.push(format!(
+                "[gpc] category_code '{}' is not an 8-digit brick",
+                config.gpc.category_code
+            ));
+        }
+        let mut emdn: Vec<_> = config.gpc_by_emdn.iter().collect();
+        emdn.sort_by(|a, b| a.0.cmp(b.0));
+        for (code, entry) in emdn {
+            let gpc = entry.over(&config.gpc);
+            if !brick(&gpc.category_code) {
+                problems.push(format!(
+                    "[gpc_by_emdn] {} → '{}' is not an 8-digit brick",
+                    code, gpc.category_code
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Check::pass(name, format!("{} mapping rows consistent", rows.len()))
+    } else {
+        Check::fail(
+            name,
+            problems.join("; "),
+            "Correct the config entries; a built-in conflict is a converter bug, please report it",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_problems_and_write_probe() {
+        let mut config = config::test_config();
+        config.provider.gln = "7612345000480".to_string();
+        config.provider.publish_gln = "7612345000481".to_string();
+        config.warnings.suppress = vec!["w001".to_string(), "W999".to_string()];
+        let problems = config_problems(&config);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("publish_gln"));
+        assert!(problems[1].contains("W999"));
+
+        let dir = std::env::temp_dir().join(format!("doctor_test_{}", std::process::id()));
+        let check = write_access(&dir, &["a", "b/c"]);
+        assert_eq!(check.status, Status::Pass);
+        assert!(dir.join("b/c").is_dir());
+        assert!(!dir.join("a/.doctor_probe").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(mapping_tables(Some(&config)).status, Status::Pass);
+    }
+}
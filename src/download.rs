@@ -16,8 +16,9 @@ pub const DEFAULT_PAGE_SIZE: usize = 300;
 pub const DEFAULT_DATA_DIR: &str = "eudamed_json";
 
 /// Returns the application data directory.
-/// Under macOS App Sandbox, uses the container directory.
-/// Otherwise, uses the current working directory.
+/// Under macOS App Sandbox, uses the container directory. Otherwise
+/// `EUDAMED2FIRSTBASE_HOME`, an existing `~/eudamed2firstbase/`, or the
+/// platform data directory (see `platform_dirs.rs`).
 pub fn app_data_dir() -> PathBuf {
     // macOS sandbox: APP_SANDBOX_CONTAINER_ID env var is set
     if let Ok(container) = std::env::var("APP_SANDBOX_CONTAINER_ID") {
@@ -31,16 +32,25 @@ pub fn app_data_dir() -> PathBuf {
         }
     }
 
-    // All platforms: ~/eudamed2firstbase/
+    // Where installs before the platform directories keep their data:
     // Windows: %USERPROFILE%\eudamed2firstbase\
     // Linux/macOS: ~/eudamed2firstbase/
     #[cfg(target_os = "windows")]
     let home = std::env::var_os("USERPROFILE");
     #[cfg(not(target_os = "windows"))]
     let home = std::env::var_os("HOME");
-
-    if let Some(home) = home {
-        let dir = PathBuf::from(home).join("eudamed2firstbase");
+    let legacy = home
+        .map(|home| PathBuf::from(home).join("eudamed2firstbase"))
+        .filter(|dir| dir.is_dir());
+
+    let overridden =
+        std::env::var_os(crate::platform_dirs::HOME_ENV).is_some_and(|v| !v.is_empty());
+    let dir = if overridden {
+        crate::platform_dirs::data_dir()
+    } else {
+        legacy.or_else(crate::platform_dirs::data_dir)
+    };
+    if let Some(dir) = dir {
         let _ = std::fs::create_dir_all(&dir);
         return dir;
     }
@@ -69,6 +79,11 @@ pub enum DownloadEvent {
 /// Trait for receiving download progress. Implemented differently by GUI and CLI.
 pub trait DownloadProgress: Send + Sync {
     fn on_event(&self, event: DownloadEvent);
+
+    /// The run was cancelled (GUI job queue); checked before every request.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
 }
 
 /// CLI progress reporter — prints to stderr.
@@ -109,6 +124,7 @@ pub struct DownloadConfig {
     /// Minimum interval between EUDAMED requests (ms), enforced globally by the
     /// shared `RateLimiter`. ~1050 ms ≈ 57 req/min, just under the measured shared
     /// ~60-req/60 s per-IP budget → steady throughput with ~0 throttles.
+    /// Defaults to the EUDAMED `interval_ms` of `[http]` (see `http.rs`).
     pub rate_interval_ms: u64,
 }
 
@@ -128,7 +144,7 @@ impl Default for DownloadConfig {
             detail_threads: 6,
             listing_threads: 6,
             max_retries: 3,
-            rate_interval_ms: 1050,
+            rate_interval_ms: crate::http::policy(EUDAMED_BASE_URL).interval_ms,
         }
     }
 }
@@ -207,6 +223,9 @@ pub fn run_download(
         )?
     };
 
+    if progress.is_cancelled() {
+        anyhow::bail!("cancelled after the listing download");
+    }
     if uuid_versions.is_empty() {
         return Ok(DownloadResult {
             uuid_versions: Vec::new(),
@@ -286,6 +305,10 @@ pub fn run_download(
         basic_dir.display()
     ));
 
+    if progress.is_cancelled() {
+        anyhow::bail!("cancelled during the detail/Basic UDI-DI download");
+    }
+
     // --- Step 6: Completeness check + retry ---
     let missing_detail = need_download
         .iter()
@@ -619,19 +642,17 @@ impl RateLimiter {
 }
 
 /// A ureq agent that surfaces HTTP status (so 429 / `Retry-After` are readable
-/// instead of arriving as an opaque `Err`) with a generous global timeout.
+/// instead of arriving as an opaque `Err`) with the EUDAMED timeout of `[http]`.
 pub fn eudamed_agent() -> ureq::Agent {
-    ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .timeout_global(Some(std::time::Duration::from_secs(30)))
-        .build()
-        .new_agent()
+    crate::http::agent_for(EUDAMED_BASE_URL)
 }
 
 /// GET `url`, returning the body on 2xx. On **HTTP 429 it honors the
-/// `Retry-After` header** (waits the stated seconds, capped at 70) and retries;
-/// on any other non-2xx or a network error it does a short linear backoff.
-/// Returns `Err(reason)` only after `max_attempts`.
+/// `Retry-After` header** (waits the stated seconds, 60 when absent, capped at
+/// `backoff_max_ms`) and retries; on a 5xx or a network error it backs off
+/// exponentially (`http::backoff`). Other statuses fail at once; otherwise
+/// `Err(reason)` comes only after `max_attempts` (or the host's `max_retries`
+/// in `[http]`, when set).
 ///
 /// This is the single choke-point that makes every EUDAMED download — listings,
 /// detail, basic — survive the endpoints' rate limit (~60 req / 60 s on the
@@ -646,36 +667,64 @@ pub fn eudamed_get(
     url: &str,
     max_attempts: u32,
 ) -> Result<String, String> {
+    let policy = crate::http::policy(url);
+    let max_attempts = policy.max_retries.unwrap_or(max_attempts).max(1);
     let mut last = String::from("no response");
-    for attempt in 1..=max_attempts.max(1) {
+    // `[http] cache`: revalidate a cached response instead of refetching it.
+    let cached = crate::http_cache::lookup(url);
+    for attempt in 1..=max_attempts {
         // Proactive pacing: wait for a budget slot BEFORE every request so the
         // aggregate stays under EUDAMED's shared ~60/60 s limit and 429s (and
         // their 60 s Retry-After stalls) are avoided rather than reacted to.
         limiter.acquire();
-        match agent.get(url).call() {
+        let mut request = agent.get(url);
+        for (name, value) in cached.iter().flat_map(|c| c.validators()) {
+            request = request.header(name, value);
+        }
+        match request.call() {
             Ok(mut resp) => {
                 let status = resp.status().as_u16();
+                if status == 304 {
+                    if let Some(body) = cached.as_ref().and_then(|c| c.body()) {
+                        return Ok(body);
+                    }
+                }
                 if (200..300).contains(&status) {
-                    return resp.body_mut().read_to_string().map_err(|e| e.to_string());
+                    let header = |name: &str| {
+                        resp.headers()
+                            .get(name)
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::to_string)
+                    };
+                    let (etag, last_modified) = (header("etag"), header("last-modified"));
+                    let body = resp
+                        .body_mut()
+                        .read_to_string()
+                        .map_err(|e| e.to_string())?;
+                    crate::http_cache::store(url, etag.as_deref(), last_modified.as_deref(), &body);
+                    return Ok(body);
                 }
                 if status == 429 {
-                    let wait = resp
+                    let retry_after = resp
                         .headers()
                         .get("retry-after")
                         .and_then(|v| v.to_str().ok())
                         .and_then(|s| s.trim().parse::<u64>().ok())
-                        .unwrap_or(60)
-                        .min(70);
-                    last = format!("HTTP 429 (waited {}s)", wait);
+                        .unwrap_or(60);
+                    let wait = crate::http::backoff(&policy, attempt, Some(retry_after));
+                    last = format!("HTTP 429 (waited {}s)", wait.as_secs());
                     if attempt < max_attempts {
-                        std::thread::sleep(std::time::Duration::from_secs(wait));
+                        std::thread::sleep(wait);
                         continue;
                     }
                     return Err(last);
                 }
                 last = format!("HTTP {}", status);
+                if !crate::http::retryable(status) {
+                    return Err(last);
+                }
                 if attempt < max_attempts {
-                    std::thread::sleep(std::time::Duration::from_secs(attempt as u64));
+                    std::thread::sleep(crate::http::backoff(&policy, attempt, None));
                     continue;
                 }
                 return Err(last);
@@ -683,7 +732,7 @@ pub fn eudamed_get(
             Err(e) => {
                 last = format!("network error: {}", e);
                 if attempt < max_attempts {
-                    std::thread::sleep(std::time::Duration::from_secs(attempt as u64));
+                    std::thread::sleep(crate::http::backoff(&policy, attempt, None));
                     continue;
                 }
                 return Err(last);
@@ -713,6 +762,9 @@ fn download_listing_for_srn(
     let agent = eudamed_agent();
 
     loop {
+        if progress.is_cancelled() {
+            break;
+        }
         let url = format!(
             "{}?page={}&pageSize={}&srn={}&iso2Code=en&languageIso2Code=en",
             base_url, page, DEFAULT_PAGE_SIZE, srn
@@ -787,6 +839,10 @@ fn download_listing_for_srn(
                              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
                             rusqlite::params![uuid, mfr_srn, mfr_name, primary_di, trade_name, risk_class, device_status, version, budi_version, now],
                         );
+                        // Cached either way; only matching devices are fetched.
+                        if !crate::device_filter::admits_listing(item) {
+                            continue;
+                        }
 
                         entries.push((uuid.to_string(), version, budi_version));
                         srn_count += 1;
@@ -958,12 +1014,41 @@ fn download_listings(
 /// `primaryDi` filter, write it to listing_cache (with the real manufacturerSrn,
 /// so the device can also be pushed by SRN later), and return its
 /// (uuid, version, budi_version) tuple. Empty Vec when EUDAMED has no such device.
+/// The filter matches the code exactly as registered, so a worklist GTIN is
+/// padded to 14 digits and its shorter forms are tried in turn
+/// (`gtin::lookup_forms`).
 fn download_listing_for_gtin(
     base_url: &str,
     gtin: &str,
     conn: &Mutex<rusqlite::Connection>,
     limiter: &RateLimiter,
     progress: &dyn DownloadProgress,
+) -> Vec<(String, Option<u32>, Option<u32>)> {
+    let gtin14 = crate::gtin::from_worklist(gtin);
+    let forms = match &gtin14 {
+        Some(g) => crate::gtin::lookup_forms(g),
+        None => vec![gtin],
+    };
+    for code in forms {
+        let entries = query_primary_di(base_url, code, conn, limiter, progress);
+        if !entries.is_empty() {
+            return entries;
+        }
+    }
+    progress.on_event(DownloadEvent::Log(format!(
+        "  GTIN {}: no device in EUDAMED",
+        gtin
+    )));
+    Vec::new()
+}
+
+/// One `primaryDi` lookup for `download_listing_for_gtin`.
+fn query_primary_di(
+    base_url: &str,
+    gtin: &str,
+    conn: &Mutex<rusqlite::Connection>,
+    limiter: &RateLimiter,
+    progress: &dyn DownloadProgress,
 ) -> Vec<(String, Option<u32>, Option<u32>)> {
     let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
     let agent = eudamed_agent();
@@ -1026,21 +1111,17 @@ fn download_listing_for_gtin(
                          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
                         rusqlite::params![uuid, mfr_srn, mfr_name, primary_di, trade_name, risk_class, device_status, version, budi_version, now],
                     );
+                    if !crate::device_filter::admits_listing(item) {
+                        continue;
+                    }
                     entries.push((uuid.to_string(), version, budi_version));
                 }
             }
         }
     }
 
-    if entries.is_empty() {
-        progress.on_event(DownloadEvent::Log(format!(
-            "  GTIN {}: no device in EUDAMED",
-            gtin
-        )));
-    } else {
-        for (uuid, _, _) in &entries {
-            progress.on_event(DownloadEvent::Log(format!("  GTIN {} -> {}", gtin, uuid)));
-        }
+    for (uuid, _, _) in &entries {
+        progress.on_event(DownloadEvent::Log(format!("  GTIN {} -> {}", gtin, uuid)));
     }
     entries
 }
@@ -1081,6 +1162,9 @@ fn download_listings_by_gtin(
             gtins
                 .par_iter()
                 .map(|gtin| {
+                    if progress.is_cancelled() {
+                        return Vec::new();
+                    }
                     let entries =
                         download_listing_for_gtin(&base_url_owned, gtin, &conn, limiter, progress);
                     let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
@@ -1241,6 +1325,9 @@ fn parallel_fetch(
         .context("Failed to build rayon thread pool for parallel fetch")?
         .install(|| {
             need.par_iter().for_each(|uuid| {
+                if progress.is_cancelled() {
+                    return;
+                }
                 let full_url = format!("{}/{}?languageIso2Code=en", base_url_owned, uuid);
                 // Honors Retry-After on a 429 (replaces a 2-6 s linear backoff that
                 // could never clear EUDAMED's ~60 s rate window → "still missing").
@@ -0,0 +1,334 @@
+//! Ingestion of EUDAMED bulk download archives (zip of JSON/NDJSON/XML files).
+//! Entries are read one at a time straight out of the zip — no manual
+//! extraction step — classified, and routed to the directory the matching
+//! converter already reads from:
+//!
+//!   - UDI-DI detail JSON (`primaryDi` set)      → `eudamed_json/detail/<uuid>.json`
+//!   - Basic UDI-DI JSON (entry under `basic/`)  → `eudamed_json/basic/<stem>.json`
+//!   - device-level EUDAMED JSON (no primaryDi)  → `eudamed_json/detail/<uuid>.json`
+//!   - listing NDJSON (also `.ndjson.gz`/`.zst`) → `ndjson/<name>`
+//!   - PullResponse XML                          → `xml/<name>`
+//!
+//! A JSON entry holding a top-level array is split into one file per record.
+//! `ingest_file` routes a single loose file the same way (GUI drag-and-drop).
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+/// What an archive entry turned out to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Detail,
+    Basic,
+    Device,
+    Ndjson,
+    Xml,
+    Unknown,
+}
+
+/// Per-kind counters for one archive.
+#[derive(Debug, Default)]
+pub struct DumpStats {
+    pub detail: usize,
+    pub basic: usize,
+    pub device: usize,
+    pub ndjson: usize,
+    pub xml: usize,
+    pub skipped: usize,
+    pub errors: usize,
+}
+
+impl DumpStats {
+    fn count(&mut self, kind: EntryKind) {
+        match kind {
+            EntryKind::Detail => self.detail += 1,
+            EntryKind::Basic => self.basic += 1,
+            EntryKind::Device => self.device += 1,
+            EntryKind::Ndjson => self.ndjson += 1,
+            EntryKind::Xml => self.xml += 1,
+            EntryKind::Unknown => self.skipped += 1,
+        }
+    }
+}
+
+/// Classify one JSON record. `in_basic_dir` is true when the archive entry
+/// sits under a `basic/` folder — Basic UDI-DI records look like device-level
+/// records, so the folder is the only reliable signal.
+pub fn classify_json(value: &serde_json::Value, in_basic_dir: bool) -> EntryKind {
+    let Some(obj) = value.as_object() else {
+        return EntryKind::Unknown;
+    };
+    if obj.get("primaryDi").map(|v| !v.is_null()).unwrap_or(false) {
+        EntryKind::Detail
+    } else if in_basic_dir {
+        EntryKind::Basic
+    } else if obj.contains_key("uuid") || obj.contains_key("basicUdi") {
+        EntryKind::Device
+    } else {
+        EntryKind::Unknown
+    }
+}
+
+/// Where one file (or one record of a JSON array) was routed: its kind and
+/// the UUID / file name it was written under.
+#[derive(Debug, Clone)]
+pub struct Routed {
+    pub kind: EntryKind,
+    pub name: String,
+}
+
+/// Stream every entry of `archive_path` into `data_dir` (the directory that
+/// holds `eudamed_json/`, `ndjson/` and `xml/`).
+pub fn ingest_archive(archive_path: &Path, data_dir: &Path) -> anyhow::Result<DumpStats> {
+    Ok(ingest_zip(archive_path, data_dir)?.0)
+}
+
+fn ingest_zip(archive_path: &Path, data_dir: &Path) -> anyhow::Result<(DumpStats, Vec<Routed>)> {
+    let file = std::fs::File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Not a zip archive: {}", archive_path.display()))?;
+
+    let mut stats = DumpStats::default();
+    let mut routed = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        // enclosed_name() rejects absolute paths and `..` (zip-slip).
+        let Some(entry_path) = entry.enclosed_name() else {
+            eprintln!("  Skipping unsafe entry name: {}", entry.name());
+            stats.skipped += 1;
+            continue;
+        };
+        routed.extend(route(&entry_path, &mut entry, data_dir, &mut stats)?);
+    }
+
+    Ok((stats, routed))
+}
+
+/// Route one loose file (e.g. dropped onto the GUI) like an archive entry:
+/// a `.zip` is ingested as an archive, anything else by extension and shape.
+pub fn ingest_file(path: &Path, data_dir: &Path) -> anyhow::Result<(DumpStats, Vec<Routed>)> {
+    if path
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("zip"))
+        .unwrap_or(false)
+    {
+        return ingest_zip(path, data_dir);
+    }
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut stats = DumpStats::default();
+    let routed = route(path, &mut file, data_dir, &mut stats)?;
+    Ok((stats, routed))
+}
+
+/// A UUID-like file stem: ASCII letters, digits, `-` and `_` only.
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Classify `reader` (named `entry_path`) and write it below `data_dir`.
+fn route(
+    entry_path: &Path,
+    reader: &mut dyn Read,
+    data_dir: &Path,
+    stats: &mut DumpStats,
+) -> anyhow::Result<Vec<Routed>> {
+    let detail_dir = data_dir.join("eudamed_json/detail");
+    let basic_dir = data_dir.join("eudamed_json/basic");
+    let ndjson_dir = data_dir.join("ndjson");
+    let xml_dir = data_dir.join("xml");
+
+    let mut routed = Vec::new();
+    let Some(file_name) = entry_path.file_name().map(|n| n.to_os_string()) else {
+        return Ok(routed);
+    };
+    let stem = entry_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let ext = entry_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let in_basic_dir = entry_path
+        .parent()
+        .map(|p| {
+            p.components().any(|c| {
+                c.as_os_str()
+                    .to_string_lossy()
+                    .eq_ignore_ascii_case("basic")
+            })
+        })
+        .unwrap_or(false);
+
+    // Compressed listings stay compressed: the NDJSON converter reads them as is.
+    let ext = if crate::ndjson_input::is_ndjson(entry_path) {
+        "ndjson".to_string()
+    } else {
+        ext
+    };
+
+    match ext.as_str() {
+        "ndjson" | "xml" => {
+            let (dir, kind) = if ext == "ndjson" {
+                (&ndjson_dir, EntryKind::Ndjson)
+            } else {
+                (&xml_dir, EntryKind::Xml)
+            };
+            std::fs::create_dir_all(dir)?;
+            // Through a .part file: a dropped file may already be the
+            // destination, which File::create would truncate before the copy.
+            let dest = dir.join(&file_name);
+            let part = PathBuf::from(format!("{}.part", dest.display()));
+            let mut out = std::fs::File::create(&part)?;
+            std::io::copy(reader, &mut out)
+                .with_context(|| format!("Failed to extract {}", entry_path.display()))?;
+            drop(out);
+            std::fs::rename(&part, &dest)?;
+            stats.count(kind);
+            routed.push(Routed {
+                kind,
+                name: file_name.to_string_lossy().to_string(),
+            });
+        }
+        "json" => {
+            let mut content = String::new();
+            if let Err(e) = reader.read_to_string(&mut content) {
+                eprintln!("  Error reading {}: {}", entry_path.display(), e);
+                stats.errors += 1;
+                return Ok(routed);
+            }
+            let value: serde_json::Value = match serde_json::from_str(&content) {
+                Ok(v) => v,
+                Err(e) => {
+                    eprintln!("  Error parsing {}: {}", entry_path.display(), e);
+                    stats.errors += 1;
+                    return Ok(routed);
+                }
+            };
+            // A single record keeps its raw text (version tracking hashes the
+            // file as-is); array elements are re-serialized one per file.
+            let records: Vec<(serde_json::Value, Option<String>)> = match value {
+                serde_json::Value::Array(items) => items.into_iter().map(|v| (v, None)).collect(),
+                other => vec![(other, Some(content))],
+            };
+            for (record, raw) in records {
+                let kind = classify_json(&record, in_basic_dir);
+                let name = match kind {
+                    // Basic files are keyed by the UDI-DI uuid they belong to,
+                    // which only the entry name carries.
+                    EntryKind::Basic => stem.clone(),
+                    _ => record
+                        .get("uuid")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| stem.clone()),
+                };
+                // The name comes from the record: a crafted `uuid` must not
+                // reach outside eudamed_json/ (`../`, separators).
+                if !is_safe_name(&name) {
+                    eprintln!(
+                        "  Skipping record with unsafe name {:?} in {}",
+                        name,
+                        entry_path.display()
+                    );
+                    stats.errors += 1;
+                    continue;
+                }
+                let dir = match kind {
+                    EntryKind::Detail | EntryKind::Device => &detail_dir,
+                    EntryKind::Basic => &basic_dir,
+                    _ => {
+                        stats.count(EntryKind::Unknown);
+                        continue;
+                    }
+                };
+                let json = match raw {
+                    Some(raw) => raw,
+                    None => serde_json::to_string(&record)?,
+                };
+                std::fs::create_dir_all(dir)?;
+                std::fs::write(dir.join(format!("{}.json", name)), json)?;
+                stats.count(kind);
+                routed.push(Routed { kind, name });
+            }
+        }
+        _ => stats.count(EntryKind::Unknown),
+    }
+    Ok(routed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_json_routes_by_shape() {
+        let detail = serde_json::json!({"uuid": "u1", "primaryDi": {"code": "0123"}});
+        let device = serde_json::json!({"uuid": "u2", "primaryDi": null, "basicUdi": {}});
+        let other = serde_json::json!({"foo": 1});
+        assert_eq!(classify_json(&detail, false), EntryKind::Detail);
+        assert_eq!(classify_json(&device, false), EntryKind::Device);
+        assert_eq!(classify_json(&device, true), EntryKind::Basic);
+        assert_eq!(classify_json(&other, false), EntryKind::Unknown);
+        assert_eq!(
+            classify_json(&serde_json::json!([1]), false),
+            EntryKind::Unknown
+        );
+    }
+
+    #[test]
+    fn record_uuid_cannot_escape_the_data_dir() {
+        let dir = std::env::temp_dir().join(format!("e2fb-dump-{}", std::process::id()));
+        let data_dir = dir.join("data");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let records = serde_json::json!([
+            {"uuid": "../../../evil", "primaryDi": {"code": "0123"}},
+            {"uuid": "..\\evil", "primaryDi": {"code": "0123"}},
+            {"uuid": "3fe812ad-db51-4879-bea3-229de14a49bf", "primaryDi": {"code": "0123"}}
+        ])
+        .to_string();
+        let mut stats = DumpStats::default();
+        let routed = route(
+            Path::new("records.json"),
+            &mut records.as_bytes(),
+            &data_dir,
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(stats.errors, 2);
+        assert_eq!(routed.len(), 1);
+        assert_eq!(routed[0].name, "3fe812ad-db51-4879-bea3-229de14a49bf");
+        assert!(!dir.join("evil.json").exists());
+        assert!(!data_dir.join("eudamed_json").join("..\\evil.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn ingesting_a_file_already_in_place_keeps_it() {
+        let data_dir =
+            std::env::temp_dir().join(format!("e2fb-dump-in-place-{}", std::process::id()));
+        let ndjson_dir = data_dir.join("ndjson");
+        std::fs::create_dir_all(&ndjson_dir).unwrap();
+        let path = ndjson_dir.join("listing.ndjson");
+        let content = "{\"uuid\":\"u1\"}\n{\"uuid\":\"u2\"}\n";
+        std::fs::write(&path, content).unwrap();
+
+        let (stats, routed) = ingest_file(&path, &data_dir).unwrap();
+        assert_eq!(stats.ndjson, 1);
+        assert_eq!(routed[0].name, "listing.ndjson");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        assert!(!ndjson_dir.join("listing.ndjson.part").exists());
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
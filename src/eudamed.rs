@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use anyhow::{Context, Result};
 
 // ---- Domain structs (populated manually from DOM) ----
@@ -425,34 +427,193 @@ fn parse_udidi_data(node: &roxmltree::Node) -> MdrUdidiData {
     }
 }
 
-/// Parse EUDAMED PullResponse XML into typed structs
-pub fn parse_pull_response(xml_content: &str) -> Result<PullResponse> {
+/// The text of an XML file for `parse_pull_responses`. Most EUDAMED XML is
+/// UTF-8, but some exports arrive in ISO-8859-1, with a BOM or with stray
+/// invalid bytes; instead of failing the whole file, the encoding is detected
+/// and converted:
+///
+/// - a BOM (UTF-8, UTF-16LE/BE) decides and is dropped;
+/// - else a non-UTF-8 `encoding="…"` in the XML declaration;
+/// - else UTF-8. Invalid UTF-8 without a single valid multi-byte sequence is
+///   Latin-1 that was never declared and is read as windows-1252 (which
+///   covers ISO-8859-1's printable range); otherwise the invalid bytes
+///   become U+FFFD.
+///
+/// The second value describes the repair, `None` for clean UTF-8.
+pub fn decode_xml(bytes: &[u8]) -> (Cow<'_, str>, Option<String>) {
+    if let Some((encoding, bom_len)) = encoding_rs::Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        let note = if encoding == encoding_rs::UTF_8 {
+            "UTF-8 BOM removed".to_string()
+        } else {
+            format!("{} (BOM) converted to UTF-8", encoding.name())
+        };
+        return (text, Some(note));
+    }
+    if let Some(encoding) = declared_encoding(bytes).filter(|e| *e != encoding_rs::UTF_8) {
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        return (
+            text,
+            Some(format!("{} converted to UTF-8", encoding.name())),
+        );
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (Cow::Borrowed(text), None);
+    }
+    let multi_byte = bytes
+        .utf8_chunks()
+        .any(|chunk| chunk.valid().bytes().any(|b| !b.is_ascii()));
+    if multi_byte {
+        (
+            String::from_utf8_lossy(bytes),
+            Some("invalid UTF-8 bytes replaced".to_string()),
+        )
+    } else {
+        let (text, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(bytes);
+        (
+            text,
+            Some("undeclared windows-1252 converted to UTF-8".to_string()),
+        )
+    }
+}
+
+/// The `encoding` of the XML declaration, if it names one encoding_rs knows.
+fn declared_encoding(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let head = &bytes[..bytes.len().min(200)];
+    if !head.starts_with(b"<?xml") {
+        return None;
+    }
+    let decl = &head[..head.windows(2).position(|w| w == b"?>")?];
+    let at = decl.windows(8).position(|w| w == b"encoding")? + 8;
+    let rest = &decl[at..];
+    let quote = rest.iter().position(|&b| b == b'"' || b == b'\'')?;
+    let value = &rest[quote + 1..];
+    let end = value.iter().position(|&b| b == rest[quote])?;
+    encoding_rs::Encoding::for_label(&value[..end])
+}
+
+/// Parse EUDAMED PullResponse XML into typed structs. The payload may hold a
+/// single `<Device>` or many (bulk DTX export); one `PullResponse` is returned
+/// per device, each carrying the envelope's correlationID / creationDateTime.
+/// Devices may sit directly under `<payload>` or inside a wrapper element;
+/// `<Device>` elements nested inside a device are not devices of their own.
+pub fn parse_pull_responses(xml_content: &str) -> Result<Vec<PullResponse>> {
     let doc = roxmltree::Document::parse(xml_content).context("Failed to parse XML")?;
 
     let root = doc.root_element();
-    let mut response = PullResponse::default();
-
-    response.correlation_id = child_text(&root, "correlationID");
-    response.creation_date_time = child_text(&root, "creationDateTime");
+    let correlation_id = child_text(&root, "correlationID");
+    let creation_date_time = child_text(&root, "creationDateTime");
 
     // Find payload
     let payload = child_element(&root, "payload").context("Missing <payload> element")?;
 
-    // Find Device
-    let device_node =
-        child_element(&payload, "Device").context("Missing <Device> element in payload")?;
+    let is_device = |n: &roxmltree::Node| n.is_element() && local_name(n) == "Device";
+    let mut devices: Vec<roxmltree::Node> = payload.children().filter(is_device).collect();
+    if devices.is_empty() {
+        devices = payload
+            .children()
+            .filter(|n| n.is_element())
+            .flat_map(|wrapper| wrapper.children().filter(is_device))
+            .collect();
+    }
+
+    let responses = devices
+        .into_iter()
+        .map(|device_node| PullResponse {
+            correlation_id: correlation_id.clone(),
+            creation_date_time: creation_date_time.clone(),
+            device: parse_device(&device_node),
+        })
+        .collect();
 
-    response.device.device_type = xsi_type_local(&device_node);
+    Ok(responses)
+}
 
-    // Parse MDRBasicUDI
-    if let Some(basic) = child_element(&device_node, "MDRBasicUDI") {
-        response.device.mdr_basic_udi = Some(parse_basic_udi(&basic));
+fn parse_device(device_node: &roxmltree::Node) -> Device {
+    Device {
+        device_type: xsi_type_local(device_node),
+        mdr_basic_udi: child_element(device_node, "MDRBasicUDI").map(|n| parse_basic_udi(&n)),
+        mdr_udidi_data: child_element(device_node, "MDRUDIDIData").map(|n| parse_udidi_data(&n)),
     }
+}
 
-    // Parse MDRUDIDIData
-    if let Some(udidi) = child_element(&device_node, "MDRUDIDIData") {
-        response.device.mdr_udidi_data = Some(parse_udidi_data(&udidi));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = include_str!("../benches/fixtures/pull_response.xml");
+
+    /// The French trade name of the fixture, after `xml` was decoded and parsed.
+    fn french_trade_name(xml: &[u8]) -> (String, Option<String>) {
+        let (text, repair) = decode_xml(xml);
+        let responses = parse_pull_responses(&text).unwrap();
+        let names = responses[0]
+            .device
+            .mdr_udidi_data
+            .as_ref()
+            .and_then(|d| d.trade_names.as_ref())
+            .unwrap();
+        let fr = names
+            .iter()
+            .find(|n| n.language.as_deref() == Some("FR"))
+            .and_then(|n| n.text_value.clone())
+            .unwrap();
+        (fr, repair)
     }
 
-    Ok(response)
+    #[test]
+    fn xml_encodings_are_repaired() {
+        let xml = XML.replace("nom du produit", "dénomination");
+        let latin1 = |xml: &str| -> Vec<u8> {
+            xml.chars()
+                .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                .collect()
+        };
+
+        let (fr, repair) = french_trade_name(xml.as_bytes());
+        assert_eq!(fr, "54_Compl_MDR_IIb_dénomination");
+        assert!(repair.is_none());
+
+        let bom = [b"\xEF\xBB\xBF".as_slice(), xml.as_bytes()].concat();
+        assert_eq!(french_trade_name(&bom).0, fr);
+
+        let declared = latin1(&xml.replacen("UTF-8", "ISO-8859-1", 1));
+        let (declared_fr, repair) = french_trade_name(&declared);
+        assert_eq!(declared_fr, fr);
+        assert_eq!(repair.unwrap(), "windows-1252 converted to UTF-8");
+
+        // Declared UTF-8 but written in Latin-1.
+        assert_eq!(french_trade_name(&latin1(&xml)).0, fr);
+
+        let mut stray = xml.clone().into_bytes();
+        let at = xml.find("IIb_trade").unwrap();
+        stray[at] = 0xFF;
+        let (_, repair) = french_trade_name(&stray);
+        assert_eq!(repair.unwrap(), "invalid UTF-8 bytes replaced");
+    }
+
+    #[test]
+    fn only_top_level_devices_are_parsed() {
+        let start = XML.find("<device:Device ").unwrap();
+        let end = XML.find("</device:Device>").unwrap() + "</device:Device>".len();
+        let device = &XML[start..end];
+        // A Device nested inside a device is part of it, not one of its own.
+        let nested = device.replacen(
+            "<device:MDRBasicUDI>",
+            "<device:MDRBasicUDI><device:Device/>",
+            1,
+        );
+        assert_eq!(
+            parse_pull_responses(&XML.replace(device, &nested))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Bulk export: several devices inside a wrapper element.
+        let bulk = format!("<message:devices>{}{}</message:devices>", nested, device);
+        let responses = parse_pull_responses(&XML.replace(device, &bulk)).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(responses.iter().all(|r| r.device.mdr_basic_udi.is_some()));
+    }
 }
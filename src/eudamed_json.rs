@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::tri_state::TriState;
+
 /// Represents one device record from the EUDAMED JSON export files.
 /// These files contain device-level data with inline manufacturer and
 /// authorised representative information.
@@ -31,8 +33,10 @@ pub struct EudamedDevice {
     pub human_tissues: Option<bool>,
     pub human_product: Option<bool>,
     pub animal_tissues: Option<bool>,
-    pub microbial_substances: Option<serde_json::Value>,
-    pub sutures: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub microbial_substances: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub sutures: Option<TriState>,
 
     // Version info
     pub version_date: Option<String>,
@@ -45,20 +49,31 @@ pub struct EudamedDevice {
     pub special_device_type: Option<serde_json::Value>,
     pub special_device_type_applicable: Option<bool>,
     pub clinical_investigation_applicable: Option<bool>,
-    pub type_examination_applicable: Option<serde_json::Value>,
-    pub legacy_device_udi_di_applicable: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub type_examination_applicable: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub legacy_device_udi_di_applicable: Option<TriState>,
     pub nb_decision: Option<serde_json::Value>,
-    pub companion_diagnostics: Option<serde_json::Value>,
-    pub reagent: Option<serde_json::Value>,
-    pub instrument: Option<serde_json::Value>,
-    pub professional_testing: Option<serde_json::Value>,
-    pub kit: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub companion_diagnostics: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub reagent: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub instrument: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub professional_testing: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub kit: Option<TriState>,
     pub device: Option<bool>,
-    pub multi_component: Option<serde_json::Value>,
-    pub self_testing: Option<serde_json::Value>,
-    pub near_patient_testing: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub multi_component: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub self_testing: Option<TriState>,
+    #[serde(default, deserialize_with = "crate::tri_state::lenient")]
+    pub near_patient_testing: Option<TriState>,
     pub medical_purpose: Option<serde_json::Value>,
     pub basic_udi_type: Option<serde_json::Value>,
+    pub device_certificate_info_list_for_display: Option<Vec<crate::api_detail::DeviceCertificate>>,
 }
 
 #[derive(Deserialize, Debug)]
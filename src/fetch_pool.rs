@@ -0,0 +1,165 @@
+//! Bounded fetch pool with in-order output (`fetch-details`).
+//!
+//! `threads` workers claim the next item and fetch it; results are handed
+//! back in input order, whatever order the requests finish in. A worker only
+//! claims an item less than `threads * WINDOW_PER_THREAD` ahead of the next
+//! one to hand back, so one request stuck in a `Retry-After` wait holds at
+//! most that many records in memory, not the rest of the listing. Pacing is
+//! the caller's: every fetch of the detail download goes through the shared
+//! `RateLimiter`, so more threads overlap latency without raising the rate.
+
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Condvar, Mutex};
+
+/// Items a worker may run ahead of the output, per thread.
+const WINDOW_PER_THREAD: usize = 8;
+
+/// What the workers share.
+struct Claims {
+    /// Next item to claim.
+    next: usize,
+    /// Items handed back so far.
+    emitted: usize,
+    /// Cancelled, or `emit` failed.
+    stop: bool,
+}
+
+/// Fetch every item of `items` on `threads` workers and pass each result to
+/// `emit` in input order, with the item's index. Stops claiming items once
+/// `cancelled` returns true (the count handed back is then short of
+/// `items.len()`) or at the first `emit` error, which is returned. Returns
+/// the number of results handed back.
+pub fn fetch_ordered<T, R, F, C>(
+    items: &[T],
+    threads: usize,
+    fetch: F,
+    cancelled: C,
+    mut emit: impl FnMut(usize, R) -> anyhow::Result<()>,
+) -> anyhow::Result<usize>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    C: Fn() -> bool + Sync,
+{
+    let threads = threads.clamp(1, items.len().max(1));
+    let window = threads * WINDOW_PER_THREAD;
+    let claims = Mutex::new(Claims {
+        next: 0,
+        emitted: 0,
+        stop: false,
+    });
+    let room = Condvar::new();
+    let lock = || claims.lock().unwrap_or_else(|e| e.into_inner());
+    let stop = || {
+        lock().stop = true;
+        room.notify_all();
+    };
+
+    std::thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel::<(usize, R)>();
+        for _ in 0..threads {
+            let tx = tx.clone();
+            let (fetch, cancelled, claims, room) = (&fetch, &cancelled, &claims, &room);
+            scope.spawn(move || loop {
+                let index = {
+                    let mut c = claims.lock().unwrap_or_else(|e| e.into_inner());
+                    while !c.stop && c.next < items.len() && c.next >= c.emitted + window {
+                        c = room.wait(c).unwrap_or_else(|e| e.into_inner());
+                    }
+                    if c.stop || c.next >= items.len() {
+                        return;
+                    }
+                    if cancelled() {
+                        c.stop = true;
+                        room.notify_all();
+                        return;
+                    }
+                    c.next += 1;
+                    c.next - 1
+                };
+                if tx.send((index, fetch(&items[index]))).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = BTreeMap::new();
+        let mut emitted = 0;
+        for (index, result) in rx {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&emitted) {
+                if let Err(e) = emit(emitted, result) {
+                    stop();
+                    return Err(e);
+                }
+                emitted += 1;
+                lock().emitted = emitted;
+                room.notify_all();
+            }
+        }
+        Ok(emitted)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn results_in_input_order_within_the_window() {
+        let items: Vec<usize> = (0..60).collect();
+        let emitted = AtomicUsize::new(0);
+        let ahead = AtomicUsize::new(0);
+        let mut out = Vec::new();
+        let n = fetch_ordered(
+            &items,
+            4,
+            |&i| {
+                // Early items finish last.
+                std::thread::sleep(Duration::from_millis(((60 - i) % 7) as u64));
+                ahead.fetch_max(i - emitted.load(Ordering::SeqCst), Ordering::SeqCst);
+                i * 10
+            },
+            || false,
+            |index, value| {
+                assert_eq!(value, index * 10);
+                out.push(value);
+                emitted.store(index + 1, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(n, 60);
+        assert_eq!(out, (0..60).map(|i| i * 10).collect::<Vec<_>>());
+        assert!(ahead.load(Ordering::SeqCst) < 4 * WINDOW_PER_THREAD);
+
+        let fetched = AtomicUsize::new(0);
+        let error = fetch_ordered(
+            &items,
+            2,
+            |&i| {
+                fetched.fetch_add(1, Ordering::SeqCst);
+                i
+            },
+            || false,
+            |index, _| match index {
+                3 => Err(anyhow::anyhow!("disk full")),
+                _ => Ok(()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "disk full");
+        assert!(fetched.load(Ordering::SeqCst) < 60);
+
+        let cancelled = fetch_ordered(&items, 3, |&i| i, || true, |_, _| Ok(())).unwrap();
+        assert_eq!(cancelled, 0);
+        assert_eq!(
+            fetch_ordered(&[] as &[usize], 8, |&i| i, || false, |_, _| Ok(())).unwrap(),
+            0
+        );
+    }
+}
@@ -1,5 +1,13 @@
+use std::borrow::Cow;
+
 use serde::Serialize;
 
+/// A code value repeated across every document of a batch (country numerics,
+/// `UDI_REGISTRY`, unit and language codes, code-list values): borrowed from
+/// the literals and mapping tables instead of allocated per document. Only
+/// values outside the tables (passed through from EUDAMED) are owned.
+pub type Code = Cow<'static, str>;
+
 /// Top-level wrapper: {"DraftItem": {"TradeItem": ..., "Identifier": "Draft_<uuid>"}}
 #[derive(Serialize, Debug)]
 pub struct DraftItemDocument {
@@ -46,7 +54,7 @@ pub struct TradeItem {
     #[serde(rename = "IsBrandBankPublication")]
     pub is_brand_bank_publication: bool,
     #[serde(rename = "TargetSector")]
-    pub target_sector: Vec<String>,
+    pub target_sector: Vec<Code>,
     #[serde(
         rename = "ChemicalRegulationInformationModule",
         skip_serializing_if = "Option::is_none"
@@ -99,6 +107,13 @@ pub struct TradeItem {
     pub trade_channel_code: Vec<CodeValue>,
     #[serde(rename = "InformationProviderOfTradeItem")]
     pub information_provider: InformationProvider,
+    #[serde(rename = "BrandOwner", skip_serializing_if = "Option::is_none")]
+    pub brand_owner: Option<PartyInRole>,
+    #[serde(
+        rename = "ManufacturerOfTradeItem",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub manufacturer_of_trade_item: Vec<PartyInRole>,
     #[serde(rename = "GdsnTradeItemClassification")]
     pub classification: GdsnClassification,
     #[serde(
@@ -120,7 +135,7 @@ pub struct TradeItem {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub global_model_info: Vec<GlobalModelInformation>,
-    #[serde(rename = "Gtin")]
+    #[serde(rename = "Gtin", serialize_with = "crate::gtin::serialize")]
     pub gtin: String,
     #[serde(
         rename = "AdditionalTradeItemIdentification",
@@ -131,12 +146,14 @@ pub struct TradeItem {
     pub referenced_trade_items: Vec<ReferencedTradeItem>,
     #[serde(rename = "TradeItemInformation", skip_serializing_if = "Vec::is_empty")]
     pub trade_item_information: Vec<TradeItemInformation>,
+    #[serde(rename = "AvpList", skip_serializing_if = "Option::is_none")]
+    pub avp_list: Option<AttributeValuePairList>,
 }
 
 #[derive(Serialize, Debug, Default, Clone)]
 pub struct CodeValue {
     #[serde(rename = "Value")]
-    pub value: String,
+    pub value: Code,
 }
 
 #[derive(Serialize, Debug, Default)]
@@ -147,6 +164,15 @@ pub struct InformationProvider {
     pub party_name: String,
 }
 
+/// `BrandOwner` / `ManufacturerOfTradeItem` (see `parties.rs`).
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct PartyInRole {
+    #[serde(rename = "Gln")]
+    pub gln: String,
+    #[serde(rename = "PartyName", skip_serializing_if = "Option::is_none")]
+    pub party_name: Option<String>,
+}
+
 #[derive(Serialize, Debug, Default)]
 pub struct GdsnClassification {
     #[serde(rename = "GpcSegmentCode")]
@@ -194,7 +220,7 @@ pub struct NextLowerLevel {
 pub struct ChildTradeItem {
     #[serde(rename = "QuantityOfNextLowerLevelTradeItem")]
     pub quantity: u32,
-    #[serde(rename = "Gtin")]
+    #[serde(rename = "Gtin", serialize_with = "crate::gtin::serialize")]
     pub gtin: String,
 }
 
@@ -263,7 +289,7 @@ impl GlobalModelInformation {
 #[derive(Serialize, Debug, Clone)]
 pub struct LangValue {
     #[serde(rename = "LanguageCode")]
-    pub language_code: String,
+    pub language_code: Code,
     #[serde(rename = "Value")]
     pub value: String,
 }
@@ -523,7 +549,7 @@ pub struct ClinicalSizeOutput {
 #[derive(Serialize, Debug)]
 pub struct MeasurementValue {
     #[serde(rename = "MeasurementUnitCode")]
-    pub unit_code: String,
+    pub unit_code: Code,
     #[serde(rename = "Value")]
     pub value: f64,
 }
@@ -681,12 +707,31 @@ pub struct RegulatedTradeItemModule {
     pub info: Vec<RegulatoryInformation>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 pub struct RegulatoryInformation {
     #[serde(rename = "RegulatoryAct")]
     pub act: String,
     #[serde(rename = "RegulatoryAgency")]
     pub agency: String,
+    /// Certificate number of the permit (see `certificates::permit`).
+    #[serde(
+        rename = "RegulatoryPermitIdentification",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub permit_identification: Option<String>,
+    /// Notified body of the permit (`EU_NOTIFIED_BODY_NUMBER`).
+    #[serde(
+        rename = "RegulatoryAgencyIdentification",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub agency_identification: Vec<AdditionalPartyIdentification>,
+    #[serde(
+        rename = "PermitStartDateTime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub permit_start: Option<String>,
+    #[serde(rename = "PermitEndDateTime", skip_serializing_if = "Option::is_none")]
+    pub permit_end: Option<String>,
 }
 
 // --- Sales Information Module ---
@@ -807,12 +852,43 @@ pub struct CommunicationChannel {
     pub value: String,
 }
 
+// --- Attribute-value pairs (GS1 extension for attributes without a field) ---
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct AttributeValuePairList {
+    #[serde(rename = "StringAVP", skip_serializing_if = "Vec::is_empty")]
+    pub string_avp: Vec<StringAttributeValuePair>,
+    #[serde(rename = "CompoundStringAVP", skip_serializing_if = "Vec::is_empty")]
+    pub compound_string_avp: Vec<CompoundStringAttributeValuePair>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StringAttributeValuePair {
+    #[serde(rename = "AttributeName")]
+    pub attribute_name: String,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CompoundStringAttributeValuePair {
+    #[serde(rename = "AttributeName")]
+    pub attribute_name: String,
+    #[serde(rename = "AttributeCode", skip_serializing_if = "Option::is_none")]
+    pub attribute_code: Option<String>,
+    #[serde(rename = "CodeListNameCode", skip_serializing_if = "Option::is_none")]
+    pub code_list_name_code: Option<String>,
+    #[serde(rename = "CodeListVersion", skip_serializing_if = "Option::is_none")]
+    pub code_list_version: Option<String>,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
 // --- Referenced Trade Item (related legacy/regulation device) ---
 #[derive(Serialize, Debug)]
 pub struct ReferencedTradeItem {
     #[serde(rename = "ReferencedTradeItemTypeCode")]
     pub type_code: CodeValue,
-    #[serde(rename = "Gtin")]
+    #[serde(rename = "Gtin", serialize_with = "crate::gtin::serialize")]
     pub gtin: String,
 }
 
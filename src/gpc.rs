@@ -0,0 +1,187 @@
+//! GPC brick of a device. `[gpc]` used to be the brick of every document;
+//! `[gpc_by_emdn]` now picks one per device from its EMDN codes:
+//!
+//! 1. a key equal to one of the codes (exact hit);
+//! 2. else the longest key that is a prefix of one of them (`C01` for
+//!    `C0101`);
+//! 3. else `[gpc]`.
+//!
+//! Only the detail and XML paths carry EMDN codes; the listing and
+//! device-level paths keep `[gpc]`. Package levels take their base unit's
+//! brick. With `[gpc_by_emdn]` set, the step each device ended on is
+//! collected and written to `firstbase_json/gpc_<time>.txt` at the end of the
+//! run (CLI or GUI convert).
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::{Config, Gpc};
+use crate::firstbase::{
+    AdditionalClassification, CatalogueItemChildItemLink, FirstbaseDocument, GdsnClassification,
+    TradeItem,
+};
+
+/// Report rows of this run: device, EMDN codes, step, brick.
+static REPORT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// The brick for `emdn` and the step that chose it.
+fn resolve<'a>(config: &'a Config, emdn: &[&str]) -> (Cow<'a, Gpc>, String) {
+    let table = &config.gpc_by_emdn;
+    if let Some((code, brick)) = emdn.iter().find_map(|code| table.get_key_value(*code)) {
+        return (
+            Cow::Owned(brick.over(&config.gpc)),
+            format!("exact {}", code),
+        );
+    }
+    let prefix = table
+        .iter()
+        .filter_map(|(key, brick)| {
+            let of = emdn.iter().find(|code| code.starts_with(key.as_str()))?;
+            Some((key, of, brick))
+        })
+        .max_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| b.0.cmp(a.0)));
+    if let Some((key, of, brick)) = prefix {
+        return (
+            Cow::Owned(brick.over(&config.gpc)),
+            format!("prefix {} of {}", key, of),
+        );
+    }
+    let why = if emdn.is_empty() {
+        "default (no EMDN)"
+    } else {
+        "default (no match)"
+    };
+    (Cow::Borrowed(&config.gpc), why.to_string())
+}
+
+/// The GPC classification of a base unit with EMDN codes `emdn`; `device`
+/// (UUID or GTIN) names it in the report.
+pub fn classification(
+    config: &Config,
+    device: &str,
+    emdn: &[&str],
+    additional_classifications: Vec<AdditionalClassification>,
+) -> GdsnClassification {
+    let (gpc, step) = resolve(config, emdn);
+    if !config.gpc_by_emdn.is_empty() {
+        if let Ok(mut report) = REPORT.lock() {
+            report.push(format!(
+                "{}\t{}\t{}\t{}",
+                device,
+                emdn.join(" "),
+                step,
+                gpc.category_code
+            ));
+        }
+    }
+    GdsnClassification {
+        segment_code: gpc.segment_code.clone(),
+        class_code: gpc.class_code.clone(),
+        family_code: gpc.family_code.clone(),
+        category_code: gpc.category_code.clone(),
+        category_name: gpc.category_name.clone(),
+        additional_classifications,
+    }
+}
+
+/// Give every package level of `doc` the brick of its base unit.
+pub fn apply_to_packages(doc: &mut FirstbaseDocument) {
+    fn base_unit<'a>(
+        item: &'a TradeItem,
+        children: &'a [CatalogueItemChildItemLink],
+    ) -> Option<&'a TradeItem> {
+        if item.is_base_unit {
+            return Some(item);
+        }
+        children
+            .iter()
+            .find_map(|c| base_unit(&c.catalogue_item.trade_item, &c.catalogue_item.children))
+    }
+    fn set(item: &mut TradeItem, children: &mut [CatalogueItemChildItemLink], gpc: &Gpc) {
+        let c = &mut item.classification;
+        c.segment_code.clone_from(&gpc.segment_code);
+        c.class_code.clone_from(&gpc.class_code);
+        c.family_code.clone_from(&gpc.family_code);
+        c.category_code.clone_from(&gpc.category_code);
+        c.category_name.clone_from(&gpc.category_name);
+        for child in children {
+            set(
+                &mut child.catalogue_item.trade_item,
+                &mut child.catalogue_item.children,
+                gpc,
+            );
+        }
+    }
+
+    if doc.children.is_empty() {
+        return;
+    }
+    let Some(base) = base_unit(&doc.trade_item, &doc.children) else {
+        return;
+    };
+    let c = &base.classification;
+    let gpc = Gpc {
+        segment_code: c.segment_code.clone(),
+        class_code: c.class_code.clone(),
+        family_code: c.family_code.clone(),
+        category_code: c.category_code.clone(),
+        category_name: c.category_name.clone(),
+    };
+    set(&mut doc.trade_item, &mut doc.children, &gpc);
+}
+
+/// Write this run's rows to `<output_dir>/gpc_<time>.txt` (tab-separated
+/// device, EMDN codes, step, brick); `None` when nothing was recorded.
+pub fn write_report(output_dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let rows = match REPORT.lock() {
+        Ok(mut rows) => std::mem::take(&mut *rows),
+        Err(_) => return Ok(None),
+    };
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(format!(
+        "gpc_{}.txt",
+        chrono::Local::now().format("%H.%M_%d.%m.%Y")
+    ));
+    std::fs::write(&path, rows.join("\n") + "\n")?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bricks_fall_back_from_exact_to_prefix_to_default() {
+        let mut config = crate::config::test_config();
+        config.gpc_by_emdn = toml::from_str(
+            r#"
+            "C01" = "10000001"
+            "C0101" = { category_code = "10000002", category_name = "Catheters" }
+            "C010101" = "10000003"
+            "#,
+        )
+        .unwrap();
+        let brick = |emdn: &[&str]| {
+            let (gpc, step) = resolve(&config, emdn);
+            (gpc.category_code.clone(), gpc.category_name.clone(), step)
+        };
+
+        assert_eq!(brick(&["C010101"]).2, "exact C010101");
+        let (code, name, step) = brick(&["C01019901"]);
+        assert_eq!(
+            (code.as_str(), name.as_str(), step.as_str()),
+            ("10000002", "Catheters", "prefix C0101 of C01019901")
+        );
+        let (code, name, _) = brick(&["C0199"]);
+        assert_eq!(
+            (code.as_str(), name.as_str()),
+            ("10000001", "Medical Devices")
+        );
+        assert_eq!(brick(&["Z12"]).2, "default (no match)");
+        assert_eq!(brick(&[]).0, config.gpc.category_code);
+    }
+}
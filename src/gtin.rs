@@ -0,0 +1,200 @@
+//! GTINs are codes, not numbers: a GTIN-14 like `04034342074074` loses its
+//! leading zero as soon as anything treats it as a number (a spreadsheet cell
+//! in number format, a numeric JSON value). Everything here keeps them as
+//! strings and brings every form to the 14 digits GDSN expects:
+//!
+//! - `normalize`: GTIN-8/12/13/14 → 14 digits, check digit verified;
+//! - `serialize`: the `Gtin` fields of the firstbase documents, so all four
+//!   transform paths write the 14-digit form;
+//! - `code`: primary DI codes read from EUDAMED JSON, numbers included;
+//! - `from_worklist` / `lookup_forms`: GTINs from the Google Sheet or a
+//!   `--gtin-file`, and the forms tried against EUDAMED's `primaryDi` filter.
+//!
+//! Leading zeros do not change the check digit (the weights run from the
+//! right), so padding never turns a valid GTIN into an invalid one.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use anyhow::bail;
+use serde::de::{Deserializer, Visitor};
+use serde::Serializer;
+
+/// Normalize a GTIN-8/12/13/14 to 14 digits and verify its check digit.
+pub fn normalize(gtin: &str) -> anyhow::Result<String> {
+    let gtin = gtin.trim();
+    if !matches!(gtin.len(), 8 | 12 | 13 | 14) || !gtin.bytes().all(|b| b.is_ascii_digit()) {
+        bail!("Not a GS1 GTIN (8/12/13/14 digits): {}", gtin);
+    }
+    let padded = format!("{:0>14}", gtin);
+    let digits: Vec<u32> = padded.bytes().map(|b| (b - b'0') as u32).collect();
+    let sum: u32 = digits[..13]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+    if (10 - sum % 10) % 10 != digits[13] {
+        bail!("Invalid GTIN check digit: {}", gtin);
+    }
+    Ok(padded)
+}
+
+/// The 14-digit form of a valid GTIN; anything else (HIBC, IFA, a GTIN with a
+/// wrong check digit) unchanged, so GS1 still reports it.
+pub fn canonical(code: &str) -> Cow<'_, str> {
+    match normalize(code) {
+        Ok(gtin) if gtin != code => Cow::Owned(gtin),
+        _ => Cow::Borrowed(code),
+    }
+}
+
+/// `serialize_with` for the `Gtin` fields of the firstbase documents.
+pub fn serialize<S: Serializer>(code: &str, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&canonical(code))
+}
+
+/// A DI code that arrived as a JSON number: its leading zeros are gone, so
+/// pad it back to 14 digits.
+fn from_number(n: u64) -> String {
+    format!("{:014}", n)
+}
+
+/// `deserialize_with` for DI codes: a string (borrowed when it has no
+/// escapes) or a number.
+pub fn code<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Cow<'de, str>>, D::Error> {
+    struct Code;
+
+    impl<'de> Visitor<'de> for Code {
+        type Value = Option<Cow<'de, str>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a DI code as string or number")
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+
+        fn visit_borrowed_str<E>(self, s: &'de str) -> Result<Self::Value, E> {
+            Ok(Some(Cow::Borrowed(s)))
+        }
+
+        fn visit_str<E>(self, s: &str) -> Result<Self::Value, E> {
+            Ok(Some(Cow::Owned(s.to_string())))
+        }
+
+        fn visit_string<E>(self, s: String) -> Result<Self::Value, E> {
+            Ok(Some(Cow::Owned(s)))
+        }
+
+        fn visit_u64<E>(self, n: u64) -> Result<Self::Value, E> {
+            Ok(Some(Cow::Owned(from_number(n))))
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, n: i64) -> Result<Self::Value, E> {
+            u64::try_from(n)
+                .map(|n| Some(Cow::Owned(from_number(n))))
+                .map_err(|_| E::custom(format!("negative DI code {}", n)))
+        }
+    }
+
+    d.deserialize_option(Code)
+}
+
+/// `code` for an owned `Option<String>` field.
+pub fn owned_code<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    Ok(code(d)?.map(Cow::into_owned))
+}
+
+/// A worklist entry (Google Sheet cell, `--gtin-file` line) as a 14-digit
+/// GTIN: 8 to 14 digits, left-padded — a cell in number format drops the
+/// leading zeros, so 9 to 11 digits are a GTIN-12/13/14 that lost some.
+/// `None` for anything else (header, notes, HIBC).
+pub fn from_worklist(entry: &str) -> Option<String> {
+    let entry = entry.trim();
+    ((8..=14).contains(&entry.len()) && entry.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| format!("{:0>14}", entry))
+}
+
+/// The forms of a 14-digit GTIN to try against EUDAMED's `primaryDi` filter,
+/// which matches the code exactly as the manufacturer registered it: the
+/// 14 digits first, then the GTIN-13/12/8 its leading zeros allow.
+pub fn lookup_forms(gtin: &str) -> Vec<&str> {
+    let mut forms = vec![gtin];
+    if gtin.len() == 14 {
+        for len in [13, 12, 8] {
+            let (zeros, rest) = gtin.split_at(14 - len);
+            if zeros.bytes().all(|b| b == b'0') {
+                forms.push(rest);
+            }
+        }
+    }
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gtins_keep_their_leading_zeros() {
+        // GTIN-8/12/13/14 of the same item all normalize to one GTIN-14.
+        for gtin in [
+            "96385074",
+            "000096385074",
+            "0000096385074",
+            "00000096385074",
+        ] {
+            assert_eq!(normalize(gtin).unwrap(), "00000096385074");
+        }
+        assert_eq!(normalize("4034342074074").unwrap(), "04034342074074");
+        assert!(normalize("8800042702694").is_ok());
+        assert!(normalize("08800042702695").is_err()); // check digit
+        assert!(normalize("96385074000").is_err()); // 11 digits
+        assert!(normalize("+H123ABC1").is_err());
+
+        assert_eq!(canonical("4034342074074"), "04034342074074");
+        assert!(matches!(canonical("04034342074074"), Cow::Borrowed(_)));
+        assert_eq!(canonical("+H123ABC1"), "+H123ABC1");
+
+        #[derive(serde::Deserialize)]
+        struct Di<'a> {
+            #[serde(borrow, default, deserialize_with = "code")]
+            code: Option<Cow<'a, str>>,
+        }
+        let di = |json| serde_json::from_str::<Di>(json).unwrap().code;
+        assert_eq!(di(r#"{"code":4034342074074}"#).unwrap(), "04034342074074");
+        assert!(matches!(
+            di(r#"{"code":"04034342074074"}"#),
+            Some(Cow::Borrowed("04034342074074"))
+        ));
+        assert!(di(r#"{"code":null}"#).is_none());
+        assert!(di("{}").is_none());
+
+        assert_eq!(from_worklist(" 4034342074074 ").unwrap(), "04034342074074");
+        assert_eq!(from_worklist("96385074").unwrap(), "00000096385074");
+        assert!(from_worklist("GTIN").is_none());
+        assert!(from_worklist("1234567").is_none());
+        assert_eq!(
+            lookup_forms("00000096385074"),
+            [
+                "00000096385074",
+                "0000096385074",
+                "000096385074",
+                "96385074"
+            ]
+        );
+        assert_eq!(
+            lookup_forms("04034342074074"),
+            ["04034342074074", "4034342074074"]
+        );
+    }
+}
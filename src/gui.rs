@@ -10,7 +10,12 @@ use std::thread;
 use eframe::egui;
 
 use crate::download::{self, DownloadConfig, DownloadEvent, DownloadProgress};
-use crate::{installer, update};
+use crate::i18n::{self, tr};
+use crate::{
+    commands, credentials, device_table, dump, installer, jobs, log_console, notify, overrides,
+    packaging, payload_archive, provenance, push_batch, push_checkpoint, push_dashboard, push_lock,
+    readiness, rejection, search_index, stats, triage, update, validation, workspace,
+};
 
 /// Live progress for the in-app GitHub updater, shared between the
 /// install worker thread and the UI's banner renderer.
@@ -24,6 +29,28 @@ struct InstallProgress {
     detail: String,
 }
 
+/// Drop-down filter over `values` ("all" = `None`). Returns true on change.
+fn filter_combo(
+    ui: &mut egui::Ui,
+    lang: i18n::Lang,
+    label: &str,
+    selected: &mut Option<String>,
+    values: &[String],
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_label(label)
+        .selected_text(selected.as_deref().unwrap_or(tr(lang, "all")))
+        .show_ui(ui, |ui| {
+            changed |= ui
+                .selectable_value(selected, None, tr(lang, "all"))
+                .changed();
+            for v in values {
+                changed |= ui.selectable_value(selected, Some(v.clone()), v).changed();
+            }
+        });
+    changed
+}
+
 fn settings_path() -> PathBuf {
     download::app_data_dir().join("settings.json")
 }
@@ -76,6 +103,30 @@ impl FirstbaseEnv {
             FirstbaseEnv::Production => "https://webapi-firstbase.gs1.ch",
         }
     }
+
+    /// Base URL for the Product API (own items as stored, see `readback.rs`).
+    pub fn product_api_base(&self) -> &'static str {
+        match self {
+            FirstbaseEnv::Test => "https://test-productapi-firstbase.gs1.ch",
+            FirstbaseEnv::Production => "https://productapi-firstbase.gs1.ch",
+        }
+    }
+
+    /// Keychain scope of this environment's login.
+    pub fn key(&self) -> &'static str {
+        match self {
+            FirstbaseEnv::Test => "test",
+            FirstbaseEnv::Production => "production",
+        }
+    }
+
+    /// `firstbase_env` of the push log, session and retry queue rows.
+    pub fn label(&self) -> &'static str {
+        match self {
+            FirstbaseEnv::Test => "Test",
+            FirstbaseEnv::Production => "Production",
+        }
+    }
 }
 
 /// Persistent state saved between sessions.
@@ -107,19 +158,173 @@ pub struct Settings {
     // WhatsApp: JID of the group/user to send logs to.
     #[serde(default)]
     pub whatsapp_jid: String,
+    /// Workspace tabs; the first is always the working set.
+    #[serde(default)]
+    pub workspace_tabs: Vec<workspace::TabSpec>,
+    /// GUI language; defaults to the system locale.
+    #[serde(default)]
+    pub language: i18n::Lang,
+    /// No desktop notification when a job finishes in the background.
+    #[serde(default)]
+    pub mute_notifications: bool,
+    /// Release whose banner was dismissed (e.g. "v1.2.0"); a newer one shows again.
+    #[serde(default)]
+    pub dismissed_update: String,
+    /// Named environment of a CLI push (`--env`, see `push_env.rs`); the GUI
+    /// pushes to the built-in endpoint of `firstbase_env`.
+    #[serde(skip)]
+    pub firstbase_profile: Option<FirstbaseProfile>,
+}
+
+/// A named firstbase environment: its push log label and base URLs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FirstbaseProfile {
+    pub label: String,
+    pub api_base: String,
+    /// Empty when the environment has no Product API configured.
+    pub product_api_base: String,
+}
+
+impl Settings {
+    /// Base URL of the firstbase API pushed to.
+    pub fn firstbase_api_base(&self) -> &str {
+        self.firstbase_profile
+            .as_ref()
+            .map_or(self.firstbase_env.api_base(), |p| p.api_base.as_str())
+    }
+
+    /// Base URL of the Product API of the environment pushed to; empty when
+    /// none is configured.
+    pub fn firstbase_product_api_base(&self) -> &str {
+        self.firstbase_profile
+            .as_ref()
+            .map_or(self.firstbase_env.product_api_base(), |p| {
+                p.product_api_base.as_str()
+            })
+    }
+
+    /// `firstbase_env` of the push log, session and retry queue rows.
+    pub fn firstbase_label(&self) -> &str {
+        self.firstbase_profile
+            .as_ref()
+            .map_or(self.firstbase_env.label(), |p| p.label.as_str())
+    }
 }
 
 impl Settings {
     fn load() -> Self {
-        std::fs::read_to_string(&settings_path())
+        let mut settings: Settings = std::fs::read_to_string(settings_path())
             .ok()
             .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_default()
+            .unwrap_or_default();
+        // Keychain credentials win over anything left in settings.json.
+        for kind in credentials::Kind::ALL {
+            if let Some(cred) = settings.load_credential(kind) {
+                settings.set_credential(kind, cred);
+            }
+        }
+        settings
+    }
+
+    /// Environment variables (`FIRSTBASE_EMAIL`, `SWISSDAMED_CLIENT_ID`, ...)
+    /// over the saved credentials, and the defaults of unset fields. Shared by
+    /// the GUI and the headless `run` command.
+    fn apply_env(&mut self) {
+        if let Ok(v) = std::env::var("FIRSTBASE_EMAIL") {
+            if !v.is_empty() {
+                self.firstbase_email = v;
+            }
+        }
+        if let Ok(v) = std::env::var("FIRSTBASE_PASSWORD") {
+            if !v.is_empty() {
+                self.firstbase_password = v;
+            }
+        }
+        if self.provider_gln.is_empty() {
+            // Fall back to the GLN defined in config.toml rather than a hardcoded value.
+            let config_path = crate::platform_dirs::config_file();
+            if let Ok(cfg) = crate::config::load_config(&config_path) {
+                self.provider_gln = cfg.provider.gln;
+            }
+        }
+        // Swissdamed env vars
+        if let Ok(v) = std::env::var("SWISSDAMED_CLIENT_ID") {
+            if !v.is_empty() {
+                self.swissdamed_client_id = v;
+            }
+        }
+        if let Ok(v) = std::env::var("SWISSDAMED_CLIENT_SECRET") {
+            if !v.is_empty() {
+                self.swissdamed_client_secret = v;
+            }
+        }
+        if let Ok(v) = std::env::var("SWISSDAMED_BASE_URL") {
+            if !v.is_empty() {
+                self.swissdamed_base_url = v;
+            }
+        }
+        if self.swissdamed_base_url.is_empty() {
+            self.swissdamed_base_url = "https://playground.swissdamed.ch".to_string();
+        }
+    }
+
+    /// The keychain credential of `kind`; firstbase per environment.
+    fn load_credential(&self, kind: credentials::Kind) -> Option<credentials::Credential> {
+        match kind {
+            credentials::Kind::Firstbase => credentials::load_env(kind, self.firstbase_env.key()),
+            credentials::Kind::Swissdamed => credentials::load(kind),
+        }
+        .filter(|c| c.is_complete())
     }
 
+    /// Saves to settings.json; secrets the keychain accepted are left out of
+    /// the file (a plaintext secret from an older version migrates this way).
     fn save(&self) {
-        if let Ok(json) = serde_json::to_string_pretty(self) {
-            let _ = std::fs::write(&settings_path(), json);
+        let mut on_disk = self.clone();
+        for kind in credentials::Kind::ALL {
+            let cred = self.credential(kind);
+            let store = || match kind {
+                credentials::Kind::Firstbase => {
+                    credentials::store_env(kind, self.firstbase_env.key(), &cred)
+                }
+                credentials::Kind::Swissdamed => credentials::store(kind, &cred),
+            };
+            if cred.is_complete() && store().is_ok() {
+                on_disk.set_credential(
+                    kind,
+                    credentials::Credential {
+                        id: cred.id,
+                        secret: String::new(),
+                    },
+                );
+            }
+        }
+        let _ = crate::write_json(&settings_path(), &on_disk);
+    }
+
+    fn credential(&self, kind: credentials::Kind) -> credentials::Credential {
+        let (id, secret) = match kind {
+            credentials::Kind::Firstbase => (&self.firstbase_email, &self.firstbase_password),
+            credentials::Kind::Swissdamed => {
+                (&self.swissdamed_client_id, &self.swissdamed_client_secret)
+            }
+        };
+        credentials::Credential {
+            id: id.clone(),
+            secret: secret.clone(),
+        }
+    }
+
+    fn set_credential(&mut self, kind: credentials::Kind, cred: credentials::Credential) {
+        match kind {
+            credentials::Kind::Firstbase => {
+                self.firstbase_email = cred.id;
+                self.firstbase_password = cred.secret;
+            }
+            credentials::Kind::Swissdamed => {
+                self.swissdamed_client_id = cred.id;
+                self.swissdamed_client_secret = cred.secret;
+            }
         }
     }
 }
@@ -128,6 +333,8 @@ pub struct App {
     settings: Settings,
     last_saved_settings: String,
     log_lines: Vec<String>,
+    /// Classified `log_lines` with the console's filters.
+    log_console: log_console::Console,
     running: bool,
     rx: Option<mpsc::Receiver<WorkerMsg>>,
     show_credentials: bool,
@@ -147,6 +354,20 @@ pub struct App {
     /// Raw QR data last received — kept so we can redraw without regenerating.
     qr_data: Option<String>,
 
+    // --- Credential screen ---
+    /// Open credential window: one row per keychain entry.
+    credential_slots: Option<Vec<credentials::Slot>>,
+    /// Environment whose firstbase login is in the settings fields.
+    credentials_env: FirstbaseEnv,
+
+    // --- Job queue ---
+    /// Pipeline runs, queued/running/finished; saved in jobs.json.
+    jobs: jobs::Queue,
+    /// Id and cancel/pause handle of the running job.
+    job_running: Option<(u64, push_dashboard::Monitor)>,
+    /// Jobs restored from the last session wait for "Start queue".
+    jobs_held: bool,
+
     // --- GitHub in-app updater ---
     /// Receiver for the one-shot startup update check.
     update_rx: Option<mpsc::Receiver<Option<update::UpdateInfo>>>,
@@ -154,6 +375,8 @@ pub struct App {
     update_info: Option<update::UpdateInfo>,
     /// User dismissed the update banner this session.
     update_dismissed: bool,
+    /// Start of the last release check; `update::RECHECK` later it runs again.
+    update_checked: std::time::Instant,
     /// Receiver for in-app install events (download/swap progress).
     install_rx: Option<mpsc::Receiver<installer::InstallEvent>>,
     /// True while the in-app update is downloading/swapping.
@@ -162,6 +385,43 @@ pub struct App {
     install_error: Option<String>,
     /// Shared download/swap progress for the banner.
     install_progress: Arc<Mutex<InstallProgress>>,
+
+    // --- Device search (full-text index) ---
+    search_query: String,
+    search_hits: Vec<search_index::Hit>,
+    /// Result count / index error shown under the search box.
+    search_status: String,
+
+    // --- Drag-and-drop import ---
+    /// Dropped detail / device-level files waiting for "Convert & push":
+    /// (dropped file name, where it was routed).
+    dropped: Vec<(String, dump::Routed)>,
+    drop_status: String,
+
+    // --- Workspace tabs (device table, readiness, push dashboard per tab) ---
+    tabs: Vec<workspace::Tab>,
+    active_tab: usize,
+    /// Form of the "New tab" window while open.
+    new_tab: Option<workspace::TabSpec>,
+    /// Field override editor window (from the detail window or triage).
+    override_editor: Option<overrides::Editor>,
+
+    // --- Rejection triage ---
+    /// Open rejections by error code; loaded on first open and on Refresh.
+    triage_groups: Option<Vec<triage::Group>>,
+    triage_status: String,
+    /// Also list rejections that already have a decision.
+    triage_show_decided: bool,
+    // --- Pre-flight validation ---
+    /// Rules failed by the pending documents; `None` = rescan.
+    validation: Option<Vec<validation::Rule>>,
+    validation_status: String,
+    // --- Statistics section ---
+    /// Loaded when the section is opened; `None` = reload.
+    stats: Option<stats::Stats>,
+    /// Push environment of the push charts ("" = all).
+    stats_env: &'static str,
+    stats_status: String,
 }
 
 impl App {
@@ -171,54 +431,29 @@ impl App {
 
         let mut settings = Settings::load();
 
-        // Env vars override saved credentials
-        if let Ok(v) = std::env::var("FIRSTBASE_EMAIL") {
-            if !v.is_empty() {
-                settings.firstbase_email = v;
-            }
-        }
-        if let Ok(v) = std::env::var("FIRSTBASE_PASSWORD") {
-            if !v.is_empty() {
-                settings.firstbase_password = v;
-            }
-        }
-        if settings.provider_gln.is_empty() {
-            // Fall back to the GLN defined in config.toml rather than a hardcoded value.
-            let config_path = download::app_data_dir().join("config.toml");
-            let config_path = if config_path.exists() {
-                config_path
-            } else {
-                std::path::PathBuf::from("config.toml")
-            };
-            if let Ok(cfg) = crate::config::load_config(&config_path) {
-                settings.provider_gln = cfg.provider.gln;
-            }
-        }
-        // Swissdamed env vars
-        if let Ok(v) = std::env::var("SWISSDAMED_CLIENT_ID") {
-            if !v.is_empty() {
-                settings.swissdamed_client_id = v;
-            }
-        }
-        if let Ok(v) = std::env::var("SWISSDAMED_CLIENT_SECRET") {
-            if !v.is_empty() {
-                settings.swissdamed_client_secret = v;
-            }
-        }
-        if let Ok(v) = std::env::var("SWISSDAMED_BASE_URL") {
-            if !v.is_empty() {
-                settings.swissdamed_base_url = v;
-            }
-        }
-        if settings.swissdamed_base_url.is_empty() {
-            settings.swissdamed_base_url = "https://playground.swissdamed.ch".to_string();
-        }
+        settings.apply_env();
 
         let last_saved = serde_json::to_string(&settings).unwrap_or_default();
+        if settings.workspace_tabs.is_empty() {
+            settings.workspace_tabs = vec![workspace::TabSpec::working_set(
+                settings.firstbase_env.clone(),
+            )];
+        }
+        // The app opens on the first tab.
+        settings.firstbase_env = settings.workspace_tabs[0].env.clone();
+        let tabs = settings
+            .workspace_tabs
+            .iter()
+            .cloned()
+            .map(workspace::Tab::new)
+            .collect();
+        let jobs = jobs::Queue::load(&jobs::queue_path());
+        let credentials_env = settings.firstbase_env.clone();
         App {
             settings,
             last_saved_settings: last_saved,
             log_lines: Vec::new(),
+            log_console: log_console::Console::default(),
             running: false,
             rx: None,
             show_credentials: false,
@@ -230,13 +465,36 @@ impl App {
             download_status: None,
             qr_texture: None,
             qr_data: None,
+            credential_slots: None,
+            credentials_env,
+            jobs_held: jobs.queued() > 0,
+            jobs,
+            job_running: None,
             update_rx: Some(spawn_update_check()),
             update_info: None,
             update_dismissed: false,
+            update_checked: std::time::Instant::now(),
             install_rx: None,
             installing: false,
             install_error: None,
             install_progress: Arc::new(Mutex::new(InstallProgress::default())),
+            search_query: String::new(),
+            search_hits: Vec::new(),
+            search_status: String::new(),
+            dropped: Vec::new(),
+            drop_status: String::new(),
+            tabs,
+            active_tab: 0,
+            new_tab: None,
+            override_editor: None,
+            triage_groups: None,
+            triage_status: String::new(),
+            triage_show_decided: false,
+            validation: None,
+            validation_status: String::new(),
+            stats: None,
+            stats_env: "",
+            stats_status: String::new(),
         }
     }
 
@@ -246,9 +504,16 @@ impl App {
         if let Some(rx) = &self.update_rx {
             if let Ok(result) = rx.try_recv() {
                 self.update_rx = None;
-                self.update_info = result;
+                if let Some(info) = result {
+                    self.update_dismissed = info.pretty() == self.settings.dismissed_update;
+                    self.update_info = Some(info);
+                }
             }
+        } else if !self.installing && self.update_checked.elapsed() >= update::RECHECK {
+            self.update_checked = std::time::Instant::now();
+            self.update_rx = Some(spawn_update_check());
         }
+        ctx.request_repaint_after(update::RECHECK);
 
         if let Some(rx) = &self.install_rx {
             let mut done = false;
@@ -365,6 +630,7 @@ impl App {
     /// Render the "update available" banner (and install progress).
     /// Returns nothing; mutates state when the user clicks a button.
     fn render_update_banner(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
         if self.update_dismissed {
             return;
         }
@@ -385,30 +651,53 @@ impl App {
                     ui.colored_label(
                         egui::Color32::from_rgb(20, 60, 120),
                         format!(
-                            "⬆ Neue Version verfügbar: {} (installiert: v{})",
+                            "⬆ {}: {} ({}: v{})",
+                            tr(lang, "New version available"),
                             info.pretty(),
+                            tr(lang, "installed"),
                             env!("CARGO_PKG_VERSION")
                         ),
                     );
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if !self.installing && ui.button("Ausblenden").clicked() {
+                        if !self.installing
+                            && ui
+                                .button(tr(lang, "Dismiss"))
+                                .on_hover_text(tr(lang, "Hide until a newer release is out"))
+                                .clicked()
+                        {
                             self.update_dismissed = true;
+                            self.settings.dismissed_update = info.pretty();
                         }
                         if can_in_app {
                             let label = if self.installing {
-                                "Aktualisiere…"
+                                tr(lang, "Updating…")
                             } else {
-                                "Jetzt aktualisieren"
+                                tr(lang, "Update now")
                             };
                             let resp = ui.add_enabled(!self.installing, egui::Button::new(label));
                             if resp.clicked() {
                                 self.start_install();
                             }
-                        } else if ui.button("Release-Seite öffnen").clicked() {
+                        } else if ui.button(tr(lang, "Open release page")).clicked() {
                             let _ = open::that(&info.url);
                         }
                     });
                 });
+                if info.notes.iter().any(|(_, body)| !body.trim().is_empty()) {
+                    egui::CollapsingHeader::new(tr(lang, "What's new"))
+                        .id_salt("update_notes")
+                        .show(ui, |ui| {
+                            egui::ScrollArea::vertical()
+                                .max_height(200.0)
+                                .show(ui, |ui| {
+                                    for (tag, body) in &info.notes {
+                                        ui.strong(tag);
+                                        ui.label(body.trim());
+                                        ui.add_space(4.0);
+                                    }
+                                });
+                        });
+                }
                 if self.installing {
                     let p = self
                         .install_progress
@@ -423,7 +712,7 @@ impl App {
                         egui::ProgressBar::new(0.0).animate(true)
                     };
                     let phase_label = if p.phase.is_empty() {
-                        "Arbeite…".to_string()
+                        tr(lang, "Working…").to_string()
                     } else {
                         p.phase.clone()
                     };
@@ -432,37 +721,1530 @@ impl App {
                     if !p.detail.is_empty() {
                         ui.label(egui::RichText::new(p.detail).weak().small());
                     }
-                }
-                if let Some(err) = self.install_error.clone() {
-                    ui.add_space(4.0);
-                    ui.colored_label(
-                        egui::Color32::from_rgb(160, 30, 30),
-                        format!("Update fehlgeschlagen: {}", err),
-                    );
-                }
+                }
+                if let Some(err) = self.install_error.clone() {
+                    ui.add_space(4.0);
+                    ui.colored_label(
+                        egui::Color32::from_rgb(160, 30, 30),
+                        format!("{}: {}", tr(lang, "Update failed"), err),
+                    );
+                }
+            });
+        ui.add_space(6.0);
+    }
+
+    /// Device search over the full-text index (`db/search_index.db`): results
+    /// update as the user types; the index is built on first use.
+    fn render_search(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        egui::CollapsingHeader::new(tr(lang, "Search devices"))
+            .id_salt("Search devices")
+            .show(ui, |ui| {
+                let index_path = download::app_data_dir().join(search_index::INDEX_DB_PATH);
+                let mut run = false;
+                ui.horizontal(|ui| {
+                    run = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.search_query)
+                                .desired_width(260.0)
+                                .hint_text(tr(lang, "trade name, manufacturer, SRN, EMDN, GTIN…")),
+                        )
+                        .changed();
+                    if ui
+                        .add_enabled(!self.running, egui::Button::new(tr(lang, "Rebuild index")))
+                        .on_hover_text(tr(
+                            lang,
+                            "Re-index all converted devices in firstbase_json/ and processed/",
+                        ))
+                        .clicked()
+                    {
+                        match search_index::rebuild(
+                            &download::app_data_dir().join("firstbase_json"),
+                            &index_path,
+                        ) {
+                            Ok(n) => self.search_status = format!("Indexed {} devices", n),
+                            Err(e) => self.search_status = format!("Index error: {:#}", e),
+                        }
+                        run = true;
+                    }
+                });
+                if run {
+                    if !index_path.exists() {
+                        let _ = search_index::rebuild(
+                            &download::app_data_dir().join("firstbase_json"),
+                            &index_path,
+                        );
+                    }
+                    match search_index::open_index(&index_path)
+                        .and_then(|conn| search_index::search(&conn, &self.search_query, 50))
+                    {
+                        Ok(hits) => {
+                            self.search_status = if self.search_query.trim().is_empty() {
+                                String::new()
+                            } else {
+                                format!("{} match(es)", hits.len())
+                            };
+                            self.search_hits = hits;
+                        }
+                        Err(e) => self.search_status = format!("Search error: {:#}", e),
+                    }
+                }
+                if !self.search_status.is_empty() {
+                    ui.label(&self.search_status);
+                }
+                egui::Grid::new("search_hits").striped(true).show(ui, |ui| {
+                    for h in &self.search_hits {
+                        ui.monospace(&h.gtin);
+                        ui.label(&h.trade_name);
+                        ui.label(&h.manufacturer);
+                        ui.label(&h.emdn);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    /// Route dropped files like `ingest` does (detail / device-level JSON to
+    /// `eudamed_json/detail/`, Basic UDI-DI under a `basic/` folder to the
+    /// cache, NDJSON to `ndjson/`, XML to `xml/`, zip archives entry by entry)
+    /// and queue the detail and device-level records for conversion.
+    fn import_dropped(&mut self, paths: &[PathBuf]) {
+        let data_dir = download::app_data_dir();
+        let mut stats = dump::DumpStats::default();
+        for path in paths {
+            let file = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            match dump::ingest_file(path, &data_dir) {
+                Ok((s, routed)) => {
+                    stats.detail += s.detail;
+                    stats.basic += s.basic;
+                    stats.device += s.device;
+                    stats.ndjson += s.ndjson;
+                    stats.xml += s.xml;
+                    stats.skipped += s.skipped;
+                    stats.errors += s.errors;
+                    for r in routed {
+                        if matches!(r.kind, dump::EntryKind::Detail | dump::EntryKind::Device)
+                            && !self.dropped.iter().any(|(_, d)| d.name == r.name)
+                        {
+                            self.dropped.push((file.clone(), r));
+                        }
+                    }
+                }
+                Err(e) => {
+                    self.log_lines.push(format!("Drop {}: {:#}", file, e));
+                    stats.errors += 1;
+                }
+            }
+        }
+        self.drop_status = format!(
+            "Imported {} detail, {} device-level, {} basic, {} ndjson, {} xml ({} skipped, {} errors)",
+            stats.detail, stats.device, stats.basic, stats.ndjson, stats.xml, stats.skipped, stats.errors
+        );
+        if stats.ndjson + stats.xml > 0 {
+            self.drop_status.push_str(
+                " — NDJSON/XML are converted by the CLI (`eudamed2firstbase ndjson` / `xml`)",
+            );
+        }
+        self.log_lines.push(self.drop_status.clone());
+    }
+
+    /// Full-window hint while files are dragged over it.
+    fn render_drop_hint(&self, ctx: &egui::Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("drop_hint"),
+        ));
+        let rect = ctx.screen_rect();
+        painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(160));
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            tr(
+                self.settings.language,
+                "Drop EUDAMED JSON, NDJSON, XML or zip files to import",
+            ),
+            egui::FontId::proportional(22.0),
+            egui::Color32::WHITE,
+        );
+    }
+
+    /// Files dropped onto the window and waiting for conversion.
+    fn render_drop_queue(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        if self.dropped.is_empty() && self.drop_status.is_empty() {
+            return;
+        }
+        ui.group(|ui| {
+            ui.label(&self.drop_status);
+            if self.dropped.is_empty() {
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label(i18n::trf(
+                    lang,
+                    "{} file(s) queued for conversion",
+                    &[&self.dropped.len()],
+                ));
+                if ui
+                    .button(tr(lang, "Convert & push"))
+                    .on_hover_text(tr(
+                        lang,
+                        "Convert the dropped files and push only them (Dry run: convert only)",
+                    ))
+                    .clicked()
+                {
+                    self.pipeline_mode = 7;
+                    self.start_pipeline(ui.ctx().clone());
+                }
+                if ui.button(tr(lang, "Clear")).clicked() {
+                    self.dropped.clear();
+                    self.drop_status.clear();
+                }
+            });
+            egui::CollapsingHeader::new(tr(lang, "Queued"))
+                .id_salt("drop_queue")
+                .show(ui, |ui| {
+                    egui::Grid::new("drop_queue_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (file, routed) in &self.dropped {
+                                ui.label(file);
+                                ui.label(format!("{:?}", routed.kind).to_lowercase());
+                                ui.monospace(&routed.name);
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+        ui.add_space(4.0);
+    }
+
+    /// Tab bar of the workspace. The active tab's environment is the one in
+    /// the settings panel (switching tabs switches it); "+" opens a dataset
+    /// folder or another environment in a new tab.
+    fn render_workspace_tabs(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        if self.tabs[self.active_tab].spec.env != self.settings.firstbase_env {
+            self.tabs[self.active_tab].spec.env = self.settings.firstbase_env.clone();
+            self.save_tab_specs();
+        }
+        let mut select = None;
+        let mut close = None;
+        ui.horizontal_wrapped(|ui| {
+            for (i, tab) in self.tabs.iter().enumerate() {
+                if ui
+                    .selectable_label(i == self.active_tab, tab.title())
+                    .on_hover_text(tab.firstbase_dir().display().to_string())
+                    .clicked()
+                {
+                    select = Some(i);
+                }
+                if i > 0
+                    && ui
+                        .small_button("×")
+                        .on_hover_text(tr(lang, "Close tab"))
+                        .clicked()
+                {
+                    close = Some(i);
+                }
+            }
+            if ui
+                .button("+")
+                .on_hover_text(tr(lang, "Open a dataset or environment in a new tab"))
+                .clicked()
+            {
+                self.new_tab = Some(workspace::TabSpec {
+                    name: format!("Tab {}", self.tabs.len() + 1),
+                    ..Default::default()
+                });
+            }
+        });
+        if let Some(i) = close {
+            self.tabs.remove(i);
+            if self.active_tab >= i {
+                select = Some(self.active_tab.saturating_sub(1));
+            }
+            self.save_tab_specs();
+        }
+        if let Some(i) = select {
+            self.active_tab = i;
+            self.settings.firstbase_env = self.tabs[i].spec.env.clone();
+        }
+        ui.separator();
+
+        let mut create = None;
+        if let Some(spec) = self.new_tab.as_mut() {
+            let mut open = true;
+            egui::Window::new("New tab")
+                .open(&mut open)
+                .collapsible(false)
+                .show(ui.ctx(), |ui| {
+                    egui::Grid::new("new_tab").show(ui, |ui| {
+                        ui.label(tr(lang, "Name:"));
+                        ui.text_edit_singleline(&mut spec.name);
+                        ui.end_row();
+                        ui.label(tr(lang, "Dataset:"));
+                        ui.horizontal(|ui| {
+                            let mut folder = spec.dataset.is_some();
+                            ui.radio_value(&mut folder, false, tr(lang, "Working set"));
+                            ui.radio_value(&mut folder, true, tr(lang, "Folder"));
+                            match (folder, spec.dataset.is_some()) {
+                                (true, false) => spec.dataset = Some(PathBuf::new()),
+                                (false, true) => spec.dataset = None,
+                                _ => {}
+                            }
+                        });
+                        ui.end_row();
+                        if let Some(dir) = spec.dataset.as_mut() {
+                            ui.label(tr(lang, "Folder:"));
+                            let mut text = dir.display().to_string();
+                            if ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut text)
+                                        .desired_width(320.0)
+                                        .hint_text(tr(
+                                            lang,
+                                            "folder with converted <uuid>.json files",
+                                        )),
+                                )
+                                .changed()
+                            {
+                                *dir = PathBuf::from(text);
+                            }
+                            ui.end_row();
+                        }
+                        ui.label(tr(lang, "Environment:"));
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut spec.env, FirstbaseEnv::Test, "Test");
+                            ui.radio_value(
+                                &mut spec.env,
+                                FirstbaseEnv::Production,
+                                tr(lang, "Production"),
+                            );
+                        });
+                        ui.end_row();
+                    });
+                    let valid = !spec.name.trim().is_empty()
+                        && spec.dataset.as_ref().is_none_or(|d| d.is_dir());
+                    if ui
+                        .add_enabled(valid, egui::Button::new(tr(lang, "Open")))
+                        .clicked()
+                    {
+                        create = Some(spec.clone());
+                    }
+                });
+            if !open {
+                self.new_tab = None;
+            }
+        }
+        if let Some(spec) = create {
+            self.new_tab = None;
+            self.settings.firstbase_env = spec.env.clone();
+            self.tabs.push(workspace::Tab::new(spec));
+            self.active_tab = self.tabs.len() - 1;
+            self.save_tab_specs();
+        }
+    }
+
+    /// Mirror the tabs into the settings (saved with them).
+    fn save_tab_specs(&mut self) {
+        self.settings.workspace_tabs = self.tabs.iter().map(|t| t.spec.clone()).collect();
+    }
+
+    /// Table of all converted devices (`firstbase_json/` + `processed/`):
+    /// search over GTIN/trade name/manufacturer/SRN, click a header to sort,
+    /// filter by status, risk class and location. Only visible rows are drawn;
+    /// a click on the GTIN opens the document.
+    fn render_devices(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        let running = self.running;
+        let mut selection_action = None;
+        let tab = &mut self.tabs[self.active_tab];
+        egui::CollapsingHeader::new(tr(lang, "Devices"))
+            .id_salt("Devices")
+            .show(ui, |ui| {
+                let firstbase_dir = tab.firstbase_dir();
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut tab.device_view.query)
+                                .desired_width(260.0)
+                                .hint_text(tr(lang, "GTIN, trade name, manufacturer, SRN…")),
+                        )
+                        .changed();
+                    if ui
+                        .add_enabled(!running, egui::Button::new(tr(lang, "Reload")))
+                        .on_hover_text(tr(lang, "Re-read firstbase_json/ and processed/"))
+                        .clicked()
+                    {
+                        tab.device_rows = None;
+                    }
+                });
+                if tab.device_rows.is_none() {
+                    tab.device_rows = Some(device_table::load(&firstbase_dir));
+                    changed = true;
+                }
+                let rows = tab.device_rows.as_deref().unwrap_or_default();
+                ui.horizontal(|ui| {
+                    changed |= filter_combo(
+                        ui,
+                        lang,
+                        tr(lang, "Status"),
+                        &mut tab.device_view.status,
+                        &device_table::distinct(rows, device_table::Column::Status),
+                    );
+                    changed |= filter_combo(
+                        ui,
+                        lang,
+                        tr(lang, "Risk class"),
+                        &mut tab.device_view.risk_class,
+                        &device_table::distinct(rows, device_table::Column::RiskClass),
+                    );
+                    egui::ComboBox::from_label(tr(lang, "Location"))
+                        .selected_text(tr(
+                            lang,
+                            match tab.device_view.pending {
+                                None => "all",
+                                Some(true) => "pending",
+                                Some(false) => "processed",
+                            },
+                        ))
+                        .show_ui(ui, |ui| {
+                            for (value, label) in [
+                                (None, "all"),
+                                (Some(true), "pending"),
+                                (Some(false), "processed"),
+                            ] {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut tab.device_view.pending,
+                                        value,
+                                        tr(lang, label),
+                                    )
+                                    .changed();
+                            }
+                        });
+                });
+                if changed {
+                    tab.device_visible = tab.device_view.apply(rows);
+                }
+                ui.horizontal(|ui| {
+                    ui.label(i18n::trf(
+                        lang,
+                        "{} of {} devices, {} selected",
+                        &[&tab.device_visible.len(), &rows.len(), &tab.selected.len()],
+                    ));
+                    if ui
+                        .button(tr(lang, "Select shown"))
+                        .on_hover_text(tr(lang, "Add every device the search and filters show"))
+                        .clicked()
+                    {
+                        tab.selected.extend(
+                            tab.device_visible.iter().map(|&i| rows[i].uuid.clone()),
+                        );
+                    }
+                    if ui
+                        .add_enabled(
+                            !tab.selected.is_empty(),
+                            egui::Button::new(tr(lang, "Clear selection")),
+                        )
+                        .clicked()
+                    {
+                        tab.selected.clear();
+                    }
+                });
+                ui.add_enabled_ui(!tab.selected.is_empty(), |ui| {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(tr(lang, "Validate"))
+                            .on_hover_text(tr(lang, "Offline checks of the selected documents as they are now"))
+                            .clicked()
+                        {
+                            selection_action = Some(SelectionAction::Validate);
+                        }
+                        let working_set = tab.is_working_set();
+                        if ui
+                            .add_enabled(working_set, egui::Button::new(tr(lang, "Convert")))
+                            .on_hover_text(
+                                tr(lang, "Re-convert the selected devices from eudamed_json/detail/ and check them, no push"),
+                            )
+                            .clicked()
+                        {
+                            selection_action = Some(SelectionAction::Run(8));
+                        }
+                        if ui
+                            .add_enabled(
+                                working_set,
+                                egui::Button::new(tr(lang, "Convert & push")),
+                            )
+                            .on_hover_text(tr(lang, "Re-convert the selected devices and push only those"))
+                            .clicked()
+                        {
+                            selection_action = Some(SelectionAction::Run(9));
+                        }
+                    });
+                });
+
+                let mut sort_by = None;
+                let mut open = None;
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("devices")
+                    .striped(true)
+                    .resizable(true)
+                    .max_scroll_height(360.0)
+                    .column(egui_extras::Column::exact(20.0))
+                    .columns(
+                        egui_extras::Column::auto().at_least(60.0).clip(true),
+                        device_table::Column::ALL.len(),
+                    )
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            let mut all = !tab.device_visible.is_empty()
+                                && tab
+                                    .device_visible
+                                    .iter()
+                                    .all(|&i| tab.selected.contains(&rows[i].uuid));
+                            if ui
+                                .checkbox(&mut all, "")
+                                .on_hover_text(tr(lang, "Select / deselect the shown devices"))
+                                .changed()
+                            {
+                                for &i in &tab.device_visible {
+                                    if all {
+                                        tab.selected.insert(rows[i].uuid.clone());
+                                    } else {
+                                        tab.selected.remove(&rows[i].uuid);
+                                    }
+                                }
+                            }
+                        });
+                        for column in device_table::Column::ALL {
+                            header.col(|ui| {
+                                let arrow = match (
+                                    tab.device_view.sort == column,
+                                    tab.device_view.ascending,
+                                ) {
+                                    (true, true) => " ⬆",
+                                    (true, false) => " ⬇",
+                                    _ => "",
+                                };
+                                if ui.button(format!("{}{}", column.label(), arrow)).clicked() {
+                                    sort_by = Some(column);
+                                }
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(18.0, tab.device_visible.len(), |mut row| {
+                            let index = tab.device_visible[row.index()];
+                            let device = &rows[index];
+                            row.col(|ui| {
+                                let mut ticked = tab.selected.contains(&device.uuid);
+                                if ui.checkbox(&mut ticked, "").changed() {
+                                    if ticked {
+                                        tab.selected.insert(device.uuid.clone());
+                                    } else {
+                                        tab.selected.remove(&device.uuid);
+                                    }
+                                }
+                            });
+                            for column in device_table::Column::ALL {
+                                row.col(|ui| {
+                                    if column == device_table::Column::Gtin {
+                                        if ui.link(device.cell(column)).clicked() {
+                                            open = Some(index);
+                                        }
+                                    } else {
+                                        ui.label(device.cell(column));
+                                    }
+                                });
+                            }
+                        });
+                    });
+                if let Some(column) = sort_by {
+                    tab.device_view.toggle_sort(column);
+                    tab.device_visible = tab.device_view.apply(rows);
+                }
+                if let Some(device) = open.map(|i| &rows[i]) {
+                    let doc = std::fs::read_to_string(&device.path)
+                        .ok()
+                        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+                    let firstbase_dir = tab.firstbase_dir();
+                    let sources =
+                        provenance::Sources::load(&download::app_data_dir(), &device.uuid);
+                    tab.device_detail = Some(workspace::DeviceDetail {
+                        title: format!("{} — {}", device.gtin, device.uuid),
+                        json: doc
+                            .as_ref()
+                            .and_then(|v| serde_json::to_string_pretty(v).ok())
+                            .unwrap_or_else(|| format!("Cannot read {}", device.path.display())),
+                        path: device.path.clone(),
+                        packaging: doc.as_ref().map(packaging::tree),
+                        source: provenance::read(&firstbase_dir, &device.uuid),
+                        attributes: doc
+                            .as_ref()
+                            .map(|d| provenance::trace(d, &sources))
+                            .unwrap_or_default(),
+                        attribute_filter: String::new(),
+                    });
+                }
+            });
+
+        let mut close = false;
+        let mut edit: Option<(PathBuf, String)> = None;
+        if let Some(detail) = &mut tab.device_detail {
+            let mut open = true;
+            egui::Window::new(detail.title.as_str())
+                .open(&mut open)
+                .default_size([640.0, 520.0])
+                .show(ui.ctx(), |ui| {
+                    if ui
+                        .button(tr(lang, "Edit fields…"))
+                        .on_hover_text(tr(
+                            lang,
+                            "Override attributes of this device before the push",
+                        ))
+                        .clicked()
+                    {
+                        edit = Some((detail.path.clone(), String::new()));
+                    }
+                    if let Some(top) = &detail.packaging {
+                        let issues = top.issue_count();
+                        egui::CollapsingHeader::new(if issues == 0 {
+                            format!("Packaging ({} base unit(s))", top.base_units())
+                        } else {
+                            format!("Packaging — {} issue(s)", issues)
+                        })
+                        .id_salt("packaging")
+                        .default_open(issues > 0 || !top.children.is_empty())
+                        .show(ui, |ui| packaging_tree(ui, top, "pkg"));
+                    }
+                    egui::CollapsingHeader::new(tr(lang, "Where does each value come from?"))
+                        .id_salt("Where does each value come from?")
+                        .show(ui, |ui| attribute_origins(ui, lang, detail, &mut edit));
+                    egui::ScrollArea::both().show(ui, |ui| {
+                        ui.add(egui::TextEdit::multiline(&mut detail.json.as_str()).code_editor());
+                    });
+                });
+            close = !open;
+        }
+        if close {
+            tab.device_detail = None;
+        }
+        if let Some((path, focus)) = edit {
+            self.open_override_editor(&path, &focus);
+        }
+        self.render_override_editor(ui.ctx());
+        match selection_action {
+            Some(SelectionAction::Validate) => self.validate_selection(),
+            Some(SelectionAction::Run(mode)) => {
+                let uuids = self.tabs[self.active_tab]
+                    .selected
+                    .iter()
+                    .cloned()
+                    .collect();
+                self.enqueue(mode, uuids, ui.ctx().clone());
+            }
+            None => {}
+        }
+    }
+
+    /// Offline validation findings (`commands::validate_doc`) of the selected
+    /// documents into the log.
+    fn validate_selection(&mut self) {
+        let tab = &self.tabs[self.active_tab];
+        let Some(rows) = tab.device_rows.as_deref() else {
+            return;
+        };
+        self.log_lines.push(format!(
+            "[Validate] {} selected device(s) in {}",
+            tab.selected.len(),
+            tab.title()
+        ));
+        let mut failing = 0;
+        for row in rows.iter().filter(|r| tab.selected.contains(&r.uuid)) {
+            let findings = commands::validate_doc(&row.path);
+            if !findings.is_empty() {
+                failing += 1;
+                self.log_lines.push(format!(
+                    "  {} {}: {}",
+                    row.gtin,
+                    row.uuid,
+                    findings.join("; ")
+                ));
+            }
+        }
+        self.log_lines.push(format!(
+            "[Validate] {} of {} with findings",
+            failing,
+            tab.selected.len()
+        ));
+    }
+
+    fn open_override_editor(&mut self, doc_path: &Path, focus: &str) {
+        match overrides::Editor::open(&download::app_data_dir(), doc_path, focus) {
+            Ok(editor) => self.override_editor = Some(editor),
+            Err(e) => self
+                .log_lines
+                .push(format!("Cannot open {}: {:#}", doc_path.display(), e)),
+        }
+    }
+
+    /// Field override editor: every scalar attribute of the document by JSON
+    /// pointer, filterable. Editing a value marks it overridden; Save writes
+    /// `overrides/<uuid>.json` (re-applied at every push, so it survives
+    /// re-conversion) and patches the document on disk.
+    fn render_override_editor(&mut self, ctx: &egui::Context) {
+        let lang = self.settings.language;
+        let Some(editor) = self.override_editor.as_mut() else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("Overrides — {} ({})", editor.gtin, editor.uuid))
+            .id(egui::Id::new("override_editor"))
+            .open(&mut open)
+            .default_size([720.0, 520.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr(lang, "Filter:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut editor.filter)
+                            .desired_width(260.0)
+                            .hint_text(tr(lang, "attribute, e.g. Description")),
+                    );
+                    if ui.button(tr(lang, "Save")).clicked() {
+                        editor.status = match editor.save(&download::app_data_dir()) {
+                            Ok(n) => format!("Saved {} override(s)", n),
+                            Err(e) => format!("Save failed: {:#}", e),
+                        };
+                    }
+                });
+                if !editor.status.is_empty() {
+                    ui.label(&editor.status);
+                }
+                let visible = editor.visible();
+                ui.label(i18n::trf(
+                    lang,
+                    "{} of {} attributes",
+                    &[&visible.len(), &editor.fields.len()],
+                ))
+                .on_hover_text(tr(
+                    lang,
+                    "Unticking an override keeps the current value until the next conversion",
+                ));
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("override_fields")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for i in visible {
+                                let field = &mut editor.fields[i];
+                                ui.checkbox(&mut field.overridden, "")
+                                    .on_hover_text(tr(lang, "Override this attribute"));
+                                ui.monospace(&field.pointer);
+                                if ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut field.text)
+                                            .desired_width(280.0),
+                                    )
+                                    .changed()
+                                {
+                                    field.overridden = true;
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        if !open {
+            self.override_editor = None;
+        }
+    }
+
+    /// Registry readiness per environment: how many converted devices are
+    /// converted, validated, pushed, rejected, accepted or published.
+    fn render_readiness(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        let running = self.running;
+        let tab = &mut self.tabs[self.active_tab];
+        egui::CollapsingHeader::new(tr(lang, "Registry readiness"))
+            .id_salt("Registry readiness")
+            .show(ui, |ui| {
+                if ui
+                    .add_enabled(!running, egui::Button::new(tr(lang, "Refresh")))
+                    .on_hover_text(tr(
+                        lang,
+                        "Recompute from firstbase_json/, processed/ and the push log",
+                    ))
+                    .clicked()
+                {
+                    let data_dir = download::app_data_dir();
+                    // Only the working set's readiness is persisted to gtin_readiness.
+                    match crate::version_db::open_db(
+                        &data_dir.join(crate::version_db::VERSION_DB_PATH),
+                    )
+                    .and_then(|conn| {
+                        if tab.is_working_set() {
+                            readiness::refresh(&conn, &tab.firstbase_dir())
+                        } else {
+                            readiness::compute(&conn, &tab.firstbase_dir())
+                        }
+                    }) {
+                        Ok(rows) => {
+                            tab.readiness_counts = readiness::counts(&rows);
+                            tab.readiness_status = i18n::trf(
+                                lang,
+                                "{} devices",
+                                &[&rows
+                                    .iter()
+                                    .map(|r| r.uuid.as_str())
+                                    .collect::<std::collections::HashSet<_>>()
+                                    .len()],
+                            );
+                        }
+                        Err(e) => tab.readiness_status = format!("Readiness error: {:#}", e),
+                    }
+                }
+                if !tab.readiness_status.is_empty() {
+                    ui.label(&tab.readiness_status);
+                }
+                if tab.readiness_counts.is_empty() {
+                    return;
+                }
+                egui::Grid::new("readiness").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    for stage in readiness::Stage::ALL {
+                        ui.strong(stage.label());
+                    }
+                    ui.end_row();
+                    for (env, counts) in &tab.readiness_counts {
+                        ui.strong(env);
+                        for stage in readiness::Stage::ALL {
+                            ui.monospace(counts.get(&stage).copied().unwrap_or(0).to_string());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    /// Charts from the history and push databases: devices per risk class
+    /// and status, acceptance rate per push day, top rejection codes.
+    fn render_stats(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        egui::CollapsingHeader::new(tr(lang, "Statistics"))
+            .id_salt("Statistics")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.running, egui::Button::new(tr(lang, "Refresh")))
+                        .clicked()
+                    {
+                        self.stats = None;
+                    }
+                    let before = self.stats_env;
+                    for (env, label) in
+                        [("", "all"), ("TEST", "Test"), ("PRODUCTION", "Production")]
+                    {
+                        ui.selectable_value(&mut self.stats_env, env, tr(lang, label));
+                    }
+                    if self.stats_env != before {
+                        self.stats = None;
+                    }
+                });
+                if self.stats.is_none() {
+                    let db_path = download::app_data_dir().join(crate::version_db::VERSION_DB_PATH);
+                    let loaded = crate::version_db::open_db(&db_path)
+                        .and_then(|conn| stats::load(&conn, self.stats_env));
+                    self.stats_status = match &loaded {
+                        Ok(_) => String::new(),
+                        Err(e) => format!("Statistics error: {:#}", e),
+                    };
+                    self.stats = Some(loaded.unwrap_or_default());
+                }
+                if !self.stats_status.is_empty() {
+                    ui.label(&self.stats_status);
+                }
+                let Some(stats) = &self.stats else {
+                    return;
+                };
+                let blue = egui::Color32::from_rgb(70, 130, 200);
+                ui.strong(tr(lang, "Devices per risk class"));
+                bar_chart(ui, lang, &stats.risk_class, blue);
+                ui.strong(tr(lang, "Devices per status"));
+                bar_chart(ui, lang, &stats.status, blue);
+                ui.strong(tr(lang, "Push acceptance rate per day"));
+                acceptance_chart(ui, lang, &stats.acceptance);
+                ui.strong(tr(lang, "Top rejection codes"));
+                bar_chart(
+                    ui,
+                    lang,
+                    &stats.rejection_codes,
+                    egui::Color32::from_rgb(200, 80, 60),
+                );
+            });
+    }
+
+    /// Open GS1 rejections grouped by error code with the knowledge-base
+    /// explanation; each (code, GTIN) can be marked fixed at source, override
+    /// or ignore, one by one or for the whole group. Decided items are hidden
+    /// unless "Show decided" is on.
+    fn render_triage(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        egui::CollapsingHeader::new(tr(lang, "Rejection triage"))
+            .id_salt("Rejection triage")
+            .show(ui, |ui| {
+                let db_path = download::app_data_dir().join(crate::version_db::VERSION_DB_PATH);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.running, egui::Button::new(tr(lang, "Refresh")))
+                        .on_hover_text(tr(lang, "Reload the open rejections from the push log"))
+                        .clicked()
+                    {
+                        self.triage_groups = None;
+                    }
+                    ui.checkbox(&mut self.triage_show_decided, tr(lang, "Show decided"));
+                });
+                if self.triage_groups.is_none() {
+                    match crate::version_db::open_db(&db_path).and_then(|conn| triage::load(&conn))
+                    {
+                        Ok(groups) => {
+                            self.triage_status = i18n::trf(
+                                lang,
+                                "{} open rejection(s) in {} error code(s), {} undecided",
+                                &[
+                                    &groups.iter().map(|g| g.items.len()).sum::<usize>(),
+                                    &groups.len(),
+                                    &groups.iter().map(|g| g.undecided()).sum::<usize>(),
+                                ],
+                            );
+                            self.triage_groups = Some(groups);
+                        }
+                        Err(e) => {
+                            self.triage_status = format!("{}: {:#}", tr(lang, "Triage error"), e);
+                            self.triage_groups = Some(Vec::new());
+                        }
+                    }
+                }
+                if !self.triage_status.is_empty() {
+                    ui.label(&self.triage_status);
+                }
+
+                // (group index, item index or None for the whole group, decision)
+                let mut decided: Option<(usize, Option<usize>, Option<triage::Decision>)> = None;
+                let show_decided = self.triage_show_decided;
+                // (uuid, attribute) to open in the override editor
+                let mut edit: Option<(String, String)> = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("triage")
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for (g, group) in self.triage_groups.iter().flatten().enumerate() {
+                            if !show_decided && group.undecided() == 0 {
+                                continue;
+                            }
+                            egui::CollapsingHeader::new(i18n::trf(
+                                lang,
+                                "{} — {} device(s), {} undecided",
+                                &[&group.code, &group.items.len(), &group.undecided()],
+                            ))
+                            .id_salt(("triage_group", &group.code))
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(
+                                        group
+                                            .explanation
+                                            .unwrap_or(tr(lang, "No knowledge-base entry.")),
+                                    )
+                                    .italics(),
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.label(tr(lang, "Mark all:"));
+                                    for d in triage::Decision::ALL {
+                                        if ui.button(tr(lang, d.label())).clicked() {
+                                            decided = Some((g, None, Some(d)));
+                                        }
+                                    }
+                                });
+                                egui::Grid::new(("triage_items", &group.code))
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (i, item) in group.items.iter().enumerate() {
+                                            if !show_decided && item.decision.is_some() {
+                                                continue;
+                                            }
+                                            if ui
+                                                .link(&item.gtin)
+                                                .on_hover_text(tr(
+                                                    lang,
+                                                    "Edit fields of this device",
+                                                ))
+                                                .clicked()
+                                            {
+                                                edit = Some((
+                                                    item.uuid.clone(),
+                                                    item.attribute.clone(),
+                                                ));
+                                            }
+                                            ui.label(&item.env);
+                                            ui.label(&item.attribute);
+                                            ui.label(&item.description).on_hover_text(i18n::trf(
+                                                lang,
+                                                "{} — pushed {}",
+                                                &[&item.uuid, &item.session_ts],
+                                            ));
+                                            ui.horizontal(|ui| {
+                                                for d in triage::Decision::ALL {
+                                                    let selected = item.decision == Some(d);
+                                                    if ui
+                                                        .selectable_label(
+                                                            selected,
+                                                            tr(lang, d.label()),
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        decided = Some((
+                                                            g,
+                                                            Some(i),
+                                                            (!selected).then_some(d),
+                                                        ));
+                                                    }
+                                                }
+                                            });
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                        }
+                    });
+
+                if let (Some((g, item, decision)), Some(groups)) =
+                    (decided, self.triage_groups.as_mut())
+                {
+                    let group = &mut groups[g];
+                    let result = crate::version_db::open_db(&db_path).and_then(|conn| {
+                        for (i, it) in group.items.iter_mut().enumerate() {
+                            if item.is_none_or(|only| only == i) {
+                                triage::decide(&conn, &group.code, &it.gtin, decision)?;
+                                it.decision = decision;
+                            }
+                        }
+                        Ok(())
+                    });
+                    if let Err(e) = result {
+                        self.triage_status = format!("{}: {:#}", tr(lang, "Triage error"), e);
+                    }
+                }
+                if let Some((uuid, attribute)) = edit {
+                    let firstbase_dir = download::app_data_dir().join("firstbase_json");
+                    match overrides::doc_path(&firstbase_dir, &uuid) {
+                        Some(path) => self.open_override_editor(&path, &attribute),
+                        None => self.triage_status = format!("No converted document for {}", uuid),
+                    }
+                }
+            });
+    }
+
+    /// Pre-flight validation of the documents waiting for a push (report
+    /// findings and code-list misses), grouped by rule. "Fix…" opens the
+    /// device in the override editor, filtered on the attribute of the rule.
+    fn render_validation(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        egui::CollapsingHeader::new(tr(lang, "Pre-flight validation"))
+            .id_salt("Pre-flight validation")
+            .show(ui, |ui| {
+                let firstbase_dir = download::app_data_dir().join("firstbase_json");
+                if ui
+                    .add_enabled(!self.running, egui::Button::new(tr(lang, "Refresh")))
+                    .on_hover_text(tr(lang, "Check the converted documents again"))
+                    .clicked()
+                {
+                    self.validation = None;
+                }
+                if self.validation.is_none() {
+                    let rules = validation::scan(&firstbase_dir);
+                    self.validation_status = if rules.is_empty() {
+                        tr(lang, "No findings in the pending documents").to_string()
+                    } else {
+                        i18n::trf(
+                            lang,
+                            "{} finding(s) in {} rule(s)",
+                            &[
+                                &rules.iter().map(|r| r.issues.len()).sum::<usize>(),
+                                &rules.len(),
+                            ],
+                        )
+                    };
+                    self.validation = Some(rules);
+                }
+                ui.label(&self.validation_status);
+
+                // (document, attribute) to open in the override editor
+                let mut fix: Option<(PathBuf, &'static str)> = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("validation")
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for rule in self.validation.iter().flatten() {
+                            egui::CollapsingHeader::new(i18n::trf(
+                                lang,
+                                "{} — {} device(s)",
+                                &[&rule.rule, &rule.issues.len()],
+                            ))
+                            .id_salt(("validation_rule", &rule.rule))
+                            .show(ui, |ui| {
+                                egui::Grid::new(("validation_issues", &rule.rule))
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for issue in &rule.issues {
+                                            ui.label(&issue.gtin).on_hover_text(&issue.uuid);
+                                            ui.label(&issue.value);
+                                            if ui
+                                                .button(tr(lang, "Fix…"))
+                                                .on_hover_text(i18n::trf(
+                                                    lang,
+                                                    "Edit {} of this device",
+                                                    &[&rule.field],
+                                                ))
+                                                .clicked()
+                                            {
+                                                fix = Some((issue.path.clone(), rule.field));
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                        }
+                    });
+                if let Some((path, field)) = fix {
+                    self.open_override_editor(&path, field);
+                }
+            });
+    }
+
+    /// Render a download progress bar while a download phase is active, so the
+    /// user sees listing/detail/basic advancing (esp. during the now-paced,
+    /// rate-limited download that can take a while on large SRN sets).
+    fn render_download_status_bar(&self, ui: &mut egui::Ui) {
+        if !self.running {
+            return;
+        }
+        if let Some((phase, done, total)) = &self.download_status {
+            let frac = if *total > 0 {
+                (*done as f32 / *total as f32).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            ui.add(
+                egui::ProgressBar::new(frac)
+                    .text(format!("{}: {}/{}", phase, done, total))
+                    .animate(true),
+            );
+            ui.add_space(2.0);
+        }
+    }
+
+    /// Per-chunk push progress (CreateMany chunks, sized by `push_batch`): overall bar, a
+    /// Pause/Resume toggle that holds the push before the next chunk, and one
+    /// row per chunk with its state and counts; "View" opens the chunk's
+    /// RequestStatus response.
+    /// Credential window: the keychain logins per environment, each with
+    /// Save, Delete and a "Test connection" that requests a token.
+    fn render_credential_screen(&mut self, ctx: &egui::Context) {
+        let lang = self.settings.language;
+        let Some(slots) = self.credential_slots.as_mut() else {
+            return;
+        };
+        let mut open = true;
+        // Saved rows that are the active login (env of the settings panel).
+        let mut activate: Vec<(credentials::Kind, credentials::Credential)> = Vec::new();
+        let active_env = self.settings.firstbase_env.key();
+        egui::Window::new(tr(lang, "🔑 Credentials"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(tr(
+                        lang,
+                        "Stored in the system keychain. A firstbase login without its own \
+                         Test/Production entry uses the shared one.",
+                    ))
+                    .weak(),
+                );
+                egui::Grid::new("credential_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for slot in slots.iter_mut() {
+                            let (id_label, secret_label) = slot.kind.prompts();
+                            ui.strong(slot.title());
+                            ui.add(
+                                egui::TextEdit::singleline(&mut slot.cred.id)
+                                    .hint_text(tr(lang, id_label))
+                                    .desired_width(200.0),
+                            );
+                            ui.add(
+                                egui::TextEdit::singleline(&mut slot.cred.secret)
+                                    .hint_text(tr(lang, secret_label))
+                                    .password(true)
+                                    .desired_width(160.0),
+                            );
+                            ui.label(tr(
+                                lang,
+                                if slot.stored {
+                                    "🔒 stored"
+                                } else {
+                                    "not stored"
+                                },
+                            ));
+                            let complete = slot.cred.is_complete();
+                            if ui
+                                .add_enabled(complete, egui::Button::new(tr(lang, "Save")))
+                                .clicked()
+                            {
+                                match slot.save() {
+                                    Ok(()) => {
+                                        slot.set_status(tr(lang, "Saved").to_string());
+                                        if slot.env.is_none_or(|e| e == active_env) {
+                                            activate.push((slot.kind, slot.cred.clone()));
+                                        }
+                                    }
+                                    Err(e) => slot.set_status(e.to_string()),
+                                }
+                            }
+                            if ui
+                                .add_enabled(slot.stored, egui::Button::new(tr(lang, "Remove")))
+                                .clicked()
+                            {
+                                let status = match slot.delete() {
+                                    Ok(_) => tr(lang, "Removed from the keychain").to_string(),
+                                    Err(e) => e.to_string(),
+                                };
+                                slot.set_status(status);
+                            }
+                            if ui
+                                .add_enabled(
+                                    complete,
+                                    egui::Button::new(tr(lang, "Test connection")),
+                                )
+                                .on_hover_text(tr(lang, "Request a token with this login"))
+                                .clicked()
+                            {
+                                slot.set_status(tr(lang, "Testing…").to_string());
+                                let slot = slot.clone();
+                                let gln = self.settings.provider_gln.clone();
+                                let ctx = ctx.clone();
+                                thread::spawn(move || {
+                                    slot.set_status(match test_credential(&slot, &gln) {
+                                        Ok(()) => "✔ Login OK (token received)".to_string(),
+                                        Err(e) => format!("✘ {}", e),
+                                    });
+                                    ctx.request_repaint();
+                                });
+                            }
+                            ui.label(slot.status());
+                            ui.end_row();
+                        }
+                    });
+            });
+        for (kind, cred) in activate {
+            self.settings.set_credential(kind, cred);
+        }
+        if !open {
+            self.credential_slots = None;
+        }
+    }
+
+    /// The pipeline log with severity/step/text filters, export and copy.
+    fn render_log(&mut self, ui: &mut egui::Ui, id: &str) {
+        let lang = self.settings.language;
+        let console = &mut self.log_console;
+        console.sync(&self.log_lines);
+        let counts = log_console::Severity::ALL.map(|s| console.count(s));
+        ui.horizontal(|ui| {
+            let filter = &mut console.filter;
+            ui.checkbox(
+                &mut filter.info,
+                format!("{} ({})", tr(lang, "Info"), counts[0]),
+            );
+            ui.checkbox(
+                &mut filter.warning,
+                format!("{} ({})", tr(lang, "Warnings"), counts[1]),
+            );
+            ui.checkbox(
+                &mut filter.error,
+                format!("{} ({})", tr(lang, "Errors"), counts[2]),
+            );
+            egui::ComboBox::from_id_salt((id, "step"))
+                .selected_text(
+                    filter
+                        .step
+                        .map_or(tr(lang, "All steps"), |s| tr(lang, s.label())),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut filter.step, None, tr(lang, "All steps"));
+                    for step in log_console::Step::ALL {
+                        ui.selectable_value(&mut filter.step, Some(step), tr(lang, step.label()));
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut filter.text)
+                    .desired_width(140.0)
+                    .hint_text(tr(lang, "filter")),
+            );
+            if ui
+                .button(tr(lang, "Export"))
+                .on_hover_text(tr(lang, "Save the shown lines as TSV in the logs folder"))
+                .clicked()
+            {
+                console.status = match console.export(&logs_dir()) {
+                    Ok(path) => format!("Exported to {}", path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                };
+            }
+            if ui.button(tr(lang, "Copy")).clicked() {
+                let text: Vec<&str> = console
+                    .visible()
+                    .into_iter()
+                    .map(|i| console.entries[i].text.as_str())
+                    .collect();
+                ui.ctx().copy_text(text.join("\n"));
+            }
+        });
+        if !console.status.is_empty() {
+            ui.label(egui::RichText::new(&console.status).weak().small());
+        }
+        let visible = console.visible();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .id_salt(id)
+            .stick_to_bottom(true)
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, visible.len(), |ui, rows| {
+                for i in rows {
+                    let entry = &console.entries[visible[i]];
+                    let mut text = egui::RichText::new(&entry.text).monospace();
+                    text = match entry.severity {
+                        log_console::Severity::Info => text,
+                        log_console::Severity::Warning => {
+                            text.color(egui::Color32::from_rgb(200, 120, 0))
+                        }
+                        log_console::Severity::Error => {
+                            text.color(egui::Color32::from_rgb(200, 0, 0))
+                        }
+                    };
+                    ui.add(egui::Label::new(text).extend())
+                        .on_hover_text(format!("{} · {}", entry.time, entry.step.label()));
+                }
+            });
+    }
+
+    /// Queued, running and finished pipeline jobs with cancel/pause.
+    fn render_jobs(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        if self.jobs.jobs.is_empty() {
+            return;
+        }
+        let header = match self.jobs.queued() {
+            0 => tr(lang, "Jobs").to_string(),
+            n => tr(lang, "Jobs ({} queued)").replace("{}", &n.to_string()),
+        };
+        let mut remove: Option<u64> = None;
+        let mut changed = false;
+        egui::CollapsingHeader::new(header)
+            .id_salt("jobs")
+            .default_open(self.running || self.jobs_held)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if self.jobs_held
+                        && ui
+                            .button(tr(lang, "Start queue"))
+                            .on_hover_text(tr(lang, "Run the jobs left queued from the last session"))
+                            .clicked()
+                    {
+                        self.jobs_held = false;
+                    }
+                    if ui.button(tr(lang, "Clear finished")).clicked() {
+                        self.jobs.jobs.retain(|j| !j.state.is_final());
+                        changed = true;
+                    }
+                    let mut notify = !self.settings.mute_notifications;
+                    if ui
+                        .checkbox(&mut notify, tr(lang, "Notify when done"))
+                        .on_hover_text(
+                            tr(lang, "Desktop notification when a job finishes or fails while the window is in the background"),
+                        )
+                        .changed()
+                    {
+                        self.settings.mute_notifications = !notify;
+                    }
+                });
+                egui::Grid::new("jobs_grid").striped(true).show(ui, |ui| {
+                    for header in ["#", "Job", "SRNs", "State", "Queued", "Result", ""] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for job in self.jobs.jobs.iter().rev() {
+                        ui.label(job.id.to_string());
+                        ui.label(&job.label);
+                        ui.label(if job.uuids.is_empty() {
+                            job.srns.clone()
+                        } else {
+                            format!("{} dropped file(s)", job.uuids.len())
+                        });
+                        ui.label(job.state.label());
+                        ui.label(&job.queued_at);
+                        ui.label(&job.summary).on_hover_text(&job.finished_at);
+                        ui.horizontal(|ui| match job.state {
+                            jobs::JobState::Queued if ui.small_button(tr(lang, "Remove")).clicked() => {
+                                remove = Some(job.id);
+                            }
+                            jobs::JobState::Running => {
+                                let Some((_, monitor)) = &self.job_running else {
+                                    return;
+                                };
+                                let dashboard = monitor.snapshot();
+                                if dashboard.cancel_requested {
+                                    ui.label(tr(lang, "cancelling…"));
+                                    return;
+                                }
+                                if ui
+                                    .small_button(if dashboard.pause_requested {
+                                        tr(lang, "Resume")
+                                    } else {
+                                        tr(lang, "Pause")
+                                    })
+                                    .on_hover_text(tr(lang, "Takes effect before the next push chunk"))
+                                    .clicked()
+                                {
+                                    monitor.set_pause(!dashboard.pause_requested);
+                                }
+                                if ui
+                                    .small_button(tr(lang, "Cancel"))
+                                    .on_hover_text(
+                                        tr(lang, "Stop at the next device/chunk; unpushed files stay in firstbase_json/"),
+                                    )
+                                    .clicked()
+                                {
+                                    monitor.cancel();
+                                }
+                            }
+                            _ => {}
+                        });
+                        ui.end_row();
+                    }
+                });
             });
-        ui.add_space(6.0);
+        if let Some(id) = remove {
+            self.jobs
+                .finish(id, jobs::JobState::Cancelled, "Removed from the queue");
+            changed = true;
+        }
+        if changed {
+            self.jobs.save(&jobs::queue_path());
+        }
     }
 
-    /// Render a download progress bar while a download phase is active, so the
-    /// user sees listing/detail/basic advancing (esp. during the now-paced,
-    /// rate-limited download that can take a while on large SRN sets).
-    fn render_download_status_bar(&self, ui: &mut egui::Ui) {
-        if !self.running {
+    fn render_push_dashboard(&mut self, ui: &mut egui::Ui) {
+        let lang = self.settings.language;
+        let tab = &mut self.tabs[self.active_tab];
+        let dashboard = tab.push_monitor.snapshot();
+        if dashboard.chunks.is_empty() {
             return;
         }
-        if let Some((phase, done, total)) = &self.download_status {
-            let frac = if *total > 0 {
-                (*done as f32 / *total as f32).clamp(0.0, 1.0)
-            } else {
-                0.0
-            };
+        let completed = dashboard.completed();
+        ui.horizontal(|ui| {
             ui.add(
-                egui::ProgressBar::new(frac)
-                    .text(format!("{}: {}/{}", phase, done, total))
-                    .animate(true),
+                egui::ProgressBar::new(completed as f32 / dashboard.chunks.len() as f32)
+                    .desired_width(260.0)
+                    .text(i18n::trf(
+                        lang,
+                        "{} chunk {}/{}: {} of {} accepted, {} errors",
+                        &[
+                            &dashboard.env,
+                            &completed,
+                            &dashboard.chunks.len(),
+                            &dashboard.accepted(),
+                            &dashboard.items(),
+                            &dashboard.errors(),
+                        ],
+                    )),
             );
-            ui.add_space(2.0);
+            if !dashboard.finished {
+                let label = match (dashboard.pause_requested, dashboard.paused) {
+                    (false, _) => tr(lang, "Pause"),
+                    (true, false) => tr(lang, "Pausing after this chunk…"),
+                    (true, true) => tr(lang, "Resume"),
+                };
+                if ui
+                    .button(label)
+                    .on_hover_text(tr(lang, "Hold the push before the next chunk is submitted"))
+                    .clicked()
+                {
+                    tab.push_monitor.set_pause(!dashboard.pause_requested);
+                }
+                // Poll counts change without a log line.
+                ui.ctx()
+                    .request_repaint_after(std::time::Duration::from_secs(1));
+            }
+        });
+        egui::CollapsingHeader::new(tr(lang, "Chunks"))
+            .id_salt("push_chunks")
+            .show(ui, |ui| {
+                egui::Grid::new("push_chunk_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in [
+                            "#", "Items", "State", "Polls", "Accepted", "Errors", "Request", "",
+                        ] {
+                            ui.strong(tr(lang, header));
+                        }
+                        ui.end_row();
+                        for (i, c) in dashboard.chunks.iter().enumerate() {
+                            ui.label((i + 1).to_string());
+                            ui.label(c.items.to_string());
+                            ui.label(tr(lang, c.state.label()));
+                            ui.label(c.polls.to_string());
+                            ui.label(c.accepted.to_string());
+                            ui.label(c.errors.to_string());
+                            ui.monospace(&c.request_id);
+                            if ui
+                                .add_enabled(
+                                    !c.response.is_empty(),
+                                    egui::Button::new(tr(lang, "View")),
+                                )
+                                .clicked()
+                            {
+                                tab.push_chunk_detail = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some(i) = tab.push_chunk_detail {
+            let mut open = true;
+            let response = dashboard
+                .chunks
+                .get(i)
+                .map(|c| c.response.as_str())
+                .unwrap_or("");
+            egui::Window::new(format!("Chunk {} response", i + 1))
+                .open(&mut open)
+                .default_size([640.0, 520.0])
+                .show(ui.ctx(), |ui| {
+                    egui::ScrollArea::both().show(ui, |ui| {
+                        ui.add(egui::TextEdit::multiline(&mut &*response).code_editor());
+                    });
+                });
+            if !open {
+                tab.push_chunk_detail = None;
+            }
         }
     }
 
@@ -714,22 +2496,110 @@ impl App {
         self.qr_data = Some(data.to_string());
     }
 
+    /// Queue a run of `self.pipeline_mode`; it starts right away unless
+    /// another job is running.
     fn start_pipeline(&mut self, ctx: egui::Context) {
+        if !self.tabs[self.active_tab].is_working_set() {
+            self.log_lines.push(format!(
+                "Tab \"{}\" shows a dataset folder, not the working set — switch to a working-set tab to run the pipeline.",
+                self.tabs[self.active_tab].spec.name
+            ));
+            return;
+        }
         self.settings.save();
 
+        let uuids: Vec<String> = if self.pipeline_mode == 7 {
+            std::mem::take(&mut self.dropped)
+                .into_iter()
+                .map(|(_, routed)| routed.name)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.enqueue(self.pipeline_mode, uuids, ctx);
+    }
+
+    /// Queue a job of `mode` for the active tab; `uuids` for the modes that
+    /// work on a device list (7–9).
+    fn enqueue(&mut self, mode: u8, uuids: Vec<String>, ctx: egui::Context) {
+        if !self.tabs[self.active_tab].is_working_set() {
+            return;
+        }
+        self.jobs.push(
+            mode,
+            commands::label(mode),
+            &self.settings.srns,
+            uuids,
+            self.active_tab,
+        );
+        if self.running {
+            self.log_lines.push(format!(
+                "Queued: {} ({} job(s) waiting)",
+                commands::label(mode),
+                self.jobs.queued()
+            ));
+        }
+        self.jobs_held = false;
+        self.jobs.save(&jobs::queue_path());
+        self.start_next_job(ctx);
+    }
+
+    /// Start the oldest queued job if nothing is running.
+    fn start_next_job(&mut self, ctx: egui::Context) {
+        if self.running || self.jobs_held {
+            return;
+        }
+        let Some(job) = self.jobs.start_next() else {
+            return;
+        };
+        self.jobs.save(&jobs::queue_path());
+
         let (tx, rx) = mpsc::channel();
         self.rx = Some(rx);
         self.running = true;
         self.log_lines.clear();
         self.download_status = None;
-        self.log_lines.push("Pipeline started...".to_string());
-
-        let settings = self.settings.clone();
-        let pipeline_mode = self.pipeline_mode;
+        self.log_lines
+            .push(format!("Pipeline started (job #{})...", job.id));
+
+        let mut settings = self.settings.clone();
+        settings.srns = job.srns.clone();
+        let pipeline_mode = job.mode;
+        let queued = job.uuids.clone();
+        // The tab the job was queued from, unless it is gone or no longer
+        // a working-set tab.
+        let tab_index = if self.tabs.get(job.tab).is_some_and(|t| t.is_working_set()) {
+            job.tab
+        } else {
+            0
+        };
+        let tab = &mut self.tabs[tab_index];
+        tab.push_monitor = push_dashboard::Monitor::default();
+        let monitor = tab.push_monitor.clone();
+        self.job_running = Some((job.id, monitor.clone()));
 
         thread::spawn(move || {
+            silence_stderr();
+            let diag_tx = tx.clone();
+            let diag_ctx = ctx.clone();
             let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                run_pipeline(settings, tx.clone(), ctx.clone(), pipeline_mode);
+                // Converter warnings (normally stderr) into the log console.
+                log_console::capture(
+                    move |message| {
+                        let _ = diag_tx.send(WorkerMsg::Log(format!("[Convert] {}", message)));
+                        diag_ctx.request_repaint();
+                    },
+                    || {
+                        run_pipeline(
+                            settings,
+                            tx.clone(),
+                            ctx.clone(),
+                            pipeline_mode,
+                            monitor,
+                            queued,
+                        )
+                    },
+                );
             }));
             if let Err(panic_info) = result {
                 let msg = if let Some(s) = panic_info.downcast_ref::<String>() {
@@ -749,6 +2619,176 @@ impl App {
     }
 }
 
+/// Horizontal bars, one row per (label, count), scaled to the largest count.
+fn bar_chart(ui: &mut egui::Ui, lang: i18n::Lang, rows: &[(String, usize)], color: egui::Color32) {
+    if rows.is_empty() {
+        ui.label(egui::RichText::new(tr(lang, "No data")).weak());
+        return;
+    }
+    let max = rows.iter().map(|(_, n)| *n).max().unwrap_or(1).max(1);
+    egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+        for (label, count) in rows {
+            ui.label(label);
+            let (rect, response) =
+                ui.allocate_exact_size(egui::vec2(240.0, 12.0), egui::Sense::hover());
+            let mut bar = rect;
+            bar.set_width(rect.width() * *count as f32 / max as f32);
+            ui.painter().rect_filled(bar, 2.0, color);
+            response.on_hover_text(format!("{}: {}", label, count));
+            ui.monospace(count.to_string());
+            ui.end_row();
+        }
+    });
+}
+
+/// One column per push day, height = accepted share; hover for the counts.
+fn acceptance_chart(ui: &mut egui::Ui, lang: i18n::Lang, days: &[stats::Day]) {
+    if days.is_empty() {
+        ui.label(egui::RichText::new(tr(lang, "No data")).weak());
+        return;
+    }
+    const HEIGHT: f32 = 80.0;
+    let width = (days.len() as f32 * 10.0).clamp(120.0, 480.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(
+        rect,
+        0.0,
+        ui.visuals().widgets.noninteractive.bg_stroke,
+        egui::StrokeKind::Inside,
+    );
+    let step = rect.width() / days.len() as f32;
+    for (i, day) in days.iter().enumerate() {
+        let x = rect.left() + i as f32 * step;
+        let top = rect.bottom() - HEIGHT * day.rate() / 100.0;
+        let column = egui::Rect::from_min_max(
+            egui::pos2(x + 1.0, top),
+            egui::pos2(x + step - 1.0, rect.bottom()),
+        );
+        let color = if day.rejected == 0 {
+            egui::Color32::from_rgb(60, 160, 90)
+        } else {
+            egui::Color32::from_rgb(200, 150, 50)
+        };
+        painter.rect_filled(column, 0.0, color);
+        let hover = egui::Rect::from_min_max(
+            egui::pos2(x, rect.top()),
+            egui::pos2(x + step, rect.bottom()),
+        );
+        ui.interact(hover, ui.id().with(("acceptance", i)), egui::Sense::hover())
+            .on_hover_text(i18n::trf(
+                lang,
+                "{}: {}% ({} accepted, {} rejected)",
+                &[
+                    &day.day,
+                    &format!("{:.0}", day.rate()),
+                    &day.accepted,
+                    &day.rejected,
+                ],
+            ));
+    }
+    ui.label(egui::RichText::new(format!("{} … {}", days[0].day, days[days.len() - 1].day)).weak());
+}
+
+/// Provenance inspector of the device detail window: each attribute with
+/// the input it was taken from, filterable by pointer; ✏ opens the override
+/// editor on that attribute.
+fn attribute_origins(
+    ui: &mut egui::Ui,
+    lang: i18n::Lang,
+    detail: &mut workspace::DeviceDetail,
+    edit: &mut Option<(PathBuf, String)>,
+) {
+    match &detail.source {
+        Some(p) => ui.label(i18n::trf(
+            lang,
+            "Converted {} from {} (downloaded {})",
+            &[&p.converted_at, &p.location(), &p.downloaded_at],
+        )),
+        None => ui.label(
+            egui::RichText::new(tr(lang, "No provenance sidecar; sources looked up by UUID"))
+                .weak(),
+        ),
+    };
+    ui.horizontal(|ui| {
+        ui.label(tr(lang, "Filter:"));
+        ui.add(
+            egui::TextEdit::singleline(&mut detail.attribute_filter)
+                .desired_width(220.0)
+                .hint_text(tr(lang, "attribute, e.g. Description")),
+        );
+    });
+    let filter = detail.attribute_filter.to_lowercase();
+    egui::ScrollArea::vertical()
+        .id_salt("attribute_origins")
+        .max_height(260.0)
+        .show(ui, |ui| {
+            egui::Grid::new("attribute_origins_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    for a in detail
+                        .attributes
+                        .iter()
+                        .filter(|a| a.pointer.to_lowercase().contains(&filter))
+                    {
+                        ui.monospace(&a.pointer);
+                        ui.label(&a.value);
+                        let color = match a.origin {
+                            provenance::Origin::Override => egui::Color32::from_rgb(200, 120, 0),
+                            provenance::Origin::Converter => ui.visuals().weak_text_color(),
+                            _ => ui.visuals().text_color(),
+                        };
+                        ui.colored_label(color, tr(lang, a.origin.label()))
+                            .on_hover_text(&a.source);
+                        if ui
+                            .small_button("✏")
+                            .on_hover_text(tr(lang, "Override this attribute"))
+                            .clicked()
+                        {
+                            *edit = Some((detail.path.clone(), a.pointer.clone()));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// One level of the packaging hierarchy and, nested below it, its contents.
+fn packaging_tree(ui: &mut egui::Ui, node: &packaging::Node, id: &str) {
+    let label = format!(
+        "{}{} {}{}",
+        node.quantity
+            .map(|q| format!("{} × ", q))
+            .unwrap_or_default(),
+        if node.descriptor.is_empty() {
+            "?"
+        } else {
+            &node.descriptor
+        },
+        node.gtin,
+        if node.base_unit { "  (base unit)" } else { "" },
+    );
+    let show_issues = |ui: &mut egui::Ui| {
+        for issue in &node.issues {
+            ui.colored_label(egui::Color32::from_rgb(200, 60, 60), format!("⚠ {}", issue));
+        }
+    };
+    if node.children.is_empty() {
+        ui.monospace(label);
+        show_issues(ui);
+        return;
+    }
+    egui::CollapsingHeader::new(egui::RichText::new(label).monospace())
+        .id_salt(id)
+        .default_open(true)
+        .show(ui, |ui| {
+            show_issues(ui);
+            for (i, child) in node.children.iter().enumerate() {
+                packaging_tree(ui, child, &format!("{}/{}", id, i));
+            }
+        });
+}
+
 /// Spawn the one-shot GitHub release check on a worker thread so the UI
 /// never blocks on the network. Sends the result (Some when a newer
 /// release exists, None otherwise) back over the channel.
@@ -767,9 +2807,22 @@ impl eframe::App for App {
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let lang = self.settings.language;
         // Drain the GitHub update check + any in-app install events.
         self.pump_update_events(ctx);
 
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|f| f.path.clone())
+                .collect()
+        });
+        if !dropped.is_empty() {
+            self.import_dropped(&dropped);
+        }
+        self.render_drop_hint(ctx);
+
         // Drain messages from worker thread
         if let Some(ref rx) = self.rx {
             let mut qr_to_render: Option<String> = None;
@@ -797,9 +2850,43 @@ impl eframe::App for App {
                         self.download_status = Some((phase, done, total));
                     }
                     WorkerMsg::Done { ok, summary } => {
+                        let cancelled = self
+                            .job_running
+                            .as_ref()
+                            .is_some_and(|(_, m)| m.is_cancelled());
+                        if let Some((id, _)) = self.job_running.take() {
+                            let state = match (cancelled, ok) {
+                                (true, _) => jobs::JobState::Cancelled,
+                                (false, true) => jobs::JobState::Done,
+                                (false, false) => jobs::JobState::Failed,
+                            };
+                            self.jobs.finish(id, state, &summary);
+                            self.jobs.save(&jobs::queue_path());
+                            // Multi-hour runs: tell an operator who switched away.
+                            let background = ctx.input(|i| i.viewport().focused) != Some(true);
+                            if background && !cancelled && !self.settings.mute_notifications {
+                                let label = self
+                                    .jobs
+                                    .jobs
+                                    .iter()
+                                    .find(|j| j.id == id)
+                                    .map(|j| j.label.clone())
+                                    .unwrap_or_default();
+                                notify::send_background(
+                                    format!("eudamed2firstbase: {} {}", label, state.label()),
+                                    summary.clone(),
+                                );
+                                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                                    egui::UserAttentionType::Informational,
+                                ));
+                            }
+                        }
                         self.log_lines.push(String::new());
                         if ok {
                             self.log_lines.push(format!("=== DONE === {}", summary));
+                        } else if cancelled {
+                            self.log_lines
+                                .push(format!("=== CANCELLED === {}", summary));
                         } else {
                             self.log_lines.push(format!("=== FAILED === {}", summary));
                             // Surface failure in a modal error dialog so the user
@@ -808,6 +2895,8 @@ impl eframe::App for App {
                         }
                         self.running = false;
                         self.download_status = None;
+                        // Converted or pushed documents changed.
+                        self.validation = None;
                         self.qr_texture = None;
                         self.qr_data = None;
                         self.save_log();
@@ -823,6 +2912,7 @@ impl eframe::App for App {
                 ctx.request_repaint();
             }
         }
+        self.start_next_job(ctx.clone());
 
         // --- Everything in CentralPanel with manual splitter ---
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -846,14 +2936,31 @@ impl eframe::App for App {
                 ctx.load_texture("app-icon", color_image, egui::TextureOptions::LINEAR)
             });
             ui.horizontal(|ui| {
-                if ui.selectable_label(self.horizontal_split, "⬌ Horizontal").clicked() {
+                if ui.selectable_label(self.horizontal_split, tr(lang, "⬌ Horizontal")).clicked() {
                     self.horizontal_split = true;
                     self.split_size = ui.available_width() * 0.4;
                 }
-                if ui.selectable_label(!self.horizontal_split, "⬍ Vertical").clicked() {
+                if ui.selectable_label(!self.horizontal_split, tr(lang, "⬍ Vertical")).clicked() {
                     self.horizontal_split = false;
                     self.split_size = 300.0;
                 }
+                ui.separator();
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(lang.native_name())
+                    .show_ui(ui, |ui| {
+                        for l in i18n::Lang::ALL {
+                            ui.selectable_value(&mut self.settings.language, l, l.native_name());
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr(lang, "Language"));
+                if ui
+                    .button(tr(lang, "🔑 Credentials"))
+                    .on_hover_text(tr(lang, "Logins per environment in the system keychain, with a connection test"))
+                    .clicked()
+                {
+                    self.credential_slots = Some(credentials::Slot::all());
+                }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     let icon_button = ui.add(
                         egui::ImageButton::new(egui::load::SizedTexture::new(icon_texture.id(), egui::vec2(24.0, 24.0)))
@@ -869,6 +2976,8 @@ impl eframe::App for App {
             // GitHub in-app updater banner (only shows when a newer release exists)
             self.render_update_banner(ui);
 
+            self.render_workspace_tabs(ui);
+
             if self.horizontal_split {
                 // --- Horizontal: Settings left, Log right ---
                 let available_width = ui.available_width();
@@ -891,7 +3000,7 @@ impl eframe::App for App {
                     .id_salt("settings_horiz")
                     .show(&mut left_ui, |ui| {
                         ui.set_min_width(left_width - 20.0);
-                        ui.label("SRNs (one per line or space-separated):");
+                        ui.label(tr(lang, "SRNs (one per line or space-separated):"));
                         ui.add(
                             egui::TextEdit::multiline(&mut self.settings.srns)
                                 .desired_rows(8)
@@ -900,64 +3009,77 @@ impl eframe::App for App {
                         );
                         ui.add_space(4.0);
                         ui.horizontal(|ui| {
-                            ui.label("Limit per SRN:");
-                            ui.add(egui::TextEdit::singleline(&mut self.settings.limit).desired_width(60.0).hint_text("all"));
-                            ui.checkbox(&mut self.settings.dry_run, "Dry run");
+                            ui.label(tr(lang, "Limit per SRN:"));
+                            ui.add(egui::TextEdit::singleline(&mut self.settings.limit).desired_width(60.0).hint_text(tr(lang, "all")));
+                            ui.checkbox(&mut self.settings.dry_run, tr(lang, "Dry run"));
                         });
                         ui.horizontal(|ui| {
-                            ui.label("Target:");
+                            ui.label(tr(lang, "Target:"));
                             ui.radio_value(&mut self.settings.push_target, PushTarget::Firstbase, "GS1 firstbase");
                             ui.radio_value(&mut self.settings.push_target, PushTarget::Swissdamed, "Swissdamed");
                         });
                         ui.add_space(4.0);
                         match self.settings.push_target {
                             PushTarget::Firstbase => {
-                                ui.collapsing("GS1 firstbase Credentials", |ui| {
+                                egui::CollapsingHeader::new(tr(lang, "GS1 firstbase Credentials")).id_salt("GS1 firstbase Credentials").show(ui, |ui| {
                                     ui.horizontal(|ui| {
-                                        ui.label("Environment:");
+                                        ui.label(tr(lang, "Environment:"));
                                         ui.radio_value(&mut self.settings.firstbase_env, FirstbaseEnv::Test, "Test");
-                                        ui.radio_value(&mut self.settings.firstbase_env, FirstbaseEnv::Production, "Production");
+                                        ui.radio_value(&mut self.settings.firstbase_env, FirstbaseEnv::Production, tr(lang, "Production"));
                                     });
                                     if self.settings.firstbase_env == FirstbaseEnv::Production {
-                                        ui.colored_label(egui::Color32::from_rgb(200, 0, 0), "⚠ PRODUCTION — real data will be pushed!");
+                                        ui.colored_label(egui::Color32::from_rgb(200, 0, 0), tr(lang, "⚠ PRODUCTION — real data will be pushed!"));
                                     }
-                                    ui.horizontal(|ui| { ui.label("Email:"); ui.add(egui::TextEdit::singleline(&mut self.settings.firstbase_email).desired_width(200.0)); });
-                                    ui.horizontal(|ui| { ui.label("Password:"); ui.add(egui::TextEdit::singleline(&mut self.settings.firstbase_password).desired_width(200.0).password(true)); });
-                                    ui.horizontal(|ui| { ui.label("Provider GLN:"); ui.add(egui::TextEdit::singleline(&mut self.settings.provider_gln).desired_width(150.0)); });
-                                    ui.horizontal(|ui| { ui.label("Publish To GLN:"); ui.add(egui::TextEdit::singleline(&mut self.settings.publish_to_gln).desired_width(150.0)); });
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Email:")); ui.add(egui::TextEdit::singleline(&mut self.settings.firstbase_email).desired_width(200.0)); });
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Password:")); ui.add(egui::TextEdit::singleline(&mut self.settings.firstbase_password).desired_width(200.0).password(true)); });
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Provider GLN:")); ui.add(egui::TextEdit::singleline(&mut self.settings.provider_gln).desired_width(150.0)); });
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Publish To GLN:")); ui.add(egui::TextEdit::singleline(&mut self.settings.publish_to_gln).desired_width(150.0)); });
                                 });
                             }
                             PushTarget::Swissdamed => {
-                                ui.collapsing("Swissdamed Credentials", |ui| {
-                                    ui.horizontal(|ui| { ui.label("Client ID:"); ui.add(egui::TextEdit::singleline(&mut self.settings.swissdamed_client_id).desired_width(200.0)); });
-                                    ui.horizontal(|ui| { ui.label("Client Secret:"); ui.add(egui::TextEdit::singleline(&mut self.settings.swissdamed_client_secret).desired_width(200.0).password(true)); });
-                                    ui.horizontal(|ui| { ui.label("Base URL:"); ui.add(egui::TextEdit::singleline(&mut self.settings.swissdamed_base_url).desired_width(250.0)); });
+                                egui::CollapsingHeader::new(tr(lang, "Swissdamed Credentials")).id_salt("Swissdamed Credentials").show(ui, |ui| {
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Client ID:")); ui.add(egui::TextEdit::singleline(&mut self.settings.swissdamed_client_id).desired_width(200.0)); });
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Client Secret:")); ui.add(egui::TextEdit::singleline(&mut self.settings.swissdamed_client_secret).desired_width(200.0).password(true)); });
+                                    ui.horizontal(|ui| { ui.label(tr(lang, "Base URL:")); ui.add(egui::TextEdit::singleline(&mut self.settings.swissdamed_base_url).desired_width(250.0)); });
                                 });
                             }
                         }
                         ui.add_space(4.0);
+                        self.render_drop_queue(ui);
+                        self.render_search(ui);
+                        ui.add_space(4.0);
+                        self.render_devices(ui);
+                        ui.add_space(4.0);
+                        self.render_readiness(ui);
+                        ui.add_space(4.0);
+                        self.render_triage(ui);
+                        ui.add_space(4.0);
+                        self.render_validation(ui);
+                        ui.add_space(4.0);
+                        self.render_stats(ui);
+                        ui.add_space(4.0);
                         ui.collapsing("WhatsApp", |ui| {
                             ui.horizontal(|ui| {
-                                ui.label("Phone / Group:");
+                                ui.label(tr(lang, "Phone / Group:"));
                                 ui.add(egui::TextEdit::singleline(&mut self.settings.whatsapp_jid).desired_width(260.0).hint_text("+41 79 236 45 44  or  120363…@g.us"));
                             });
                             ui.horizontal(|ui| {
                                 let can_run = !self.running;
-                                if ui.add_enabled(can_run, egui::Button::new("Pair / Link Device"))
-                                    .on_hover_text("Show QR code to link this device to WhatsApp")
+                                if ui.add_enabled(can_run, egui::Button::new(tr(lang, "Pair / Link Device")))
+                                    .on_hover_text(tr(lang, "Show QR code to link this device to WhatsApp"))
                                     .clicked()
                                 {
                                     self.start_whatsapp_pair(ctx.clone());
                                 }
                                 let can_send = can_run && !self.settings.whatsapp_jid.trim().is_empty();
-                                if ui.add_enabled(can_send, egui::Button::new("Send latest Prod log").fill(egui::Color32::from_rgb(255, 230, 230)))
-                                    .on_hover_text("Send the most recent log/firstbase_prod/*.log.html")
+                                if ui.add_enabled(can_send, egui::Button::new(tr(lang, "Send latest Prod log")).fill(egui::Color32::from_rgb(255, 230, 230)))
+                                    .on_hover_text(tr(lang, "Send the most recent log/firstbase_prod/*.log.html"))
                                     .clicked()
                                 {
                                     self.send_latest_log_via_whatsapp(ctx.clone(), Some(FirstbaseEnv::Production));
                                 }
-                                if ui.add_enabled(can_send, egui::Button::new("Send latest Test log").fill(egui::Color32::from_rgb(230, 240, 255)))
-                                    .on_hover_text("Send the most recent log/firstbase_test/*.log.html")
+                                if ui.add_enabled(can_send, egui::Button::new(tr(lang, "Send latest Test log")).fill(egui::Color32::from_rgb(230, 240, 255)))
+                                    .on_hover_text(tr(lang, "Send the most recent log/firstbase_test/*.log.html"))
                                     .clicked()
                                 {
                                     self.send_latest_log_via_whatsapp(ctx.clone(), Some(FirstbaseEnv::Test));
@@ -965,37 +3087,36 @@ impl eframe::App for App {
                             });
                         });
                         ui.add_space(4.0);
-                        let can_start = !self.running && !self.settings.srns.trim().is_empty();
+                        let can_start = !self.settings.srns.trim().is_empty();
                         let target_name = match self.settings.push_target { PushTarget::Firstbase => "firstbase", PushTarget::Swissdamed => "Swissdamed" };
-                        let btn = if self.settings.dry_run { "0: Download & Convert".to_string() } else { format!("0: DL+Push {}", target_name) };
+                        let btn = if self.settings.dry_run { tr(lang, "0: Download & Convert").to_string() } else { tr(lang, "0: DL+Push {}").replace("{}", target_name) };
                         if ui.add_enabled(can_start, egui::Button::new(&btn).min_size(egui::vec2(140.0, 28.0))).clicked() {
                             self.pipeline_mode = 0;
                             self.start_pipeline(ctx.clone());
                         }
-                        if ui.add_enabled(can_start, egui::Button::new("2: Convert & Push SRNs").min_size(egui::vec2(160.0, 28.0)))
-                            .on_hover_text("No download — find SRN products, convert & push").clicked() {
+                        if ui.add_enabled(can_start, egui::Button::new(tr(lang, "2: Convert & Push SRNs")).min_size(egui::vec2(160.0, 28.0)))
+                            .on_hover_text(tr(lang, "No download — find SRN products, convert & push")).clicked() {
                             self.pipeline_mode = 2;
                             self.start_pipeline(ctx.clone());
                         }
-                        let can_repush = !self.running;
-                        if ui.add_enabled(can_repush, egui::Button::new("3: Repush failed").min_size(egui::vec2(140.0, 28.0)))
-                            .on_hover_text("Push remaining files in firstbase_json/ (rejected from last push)").clicked() {
+                        if ui.add(egui::Button::new(tr(lang, "3: Repush failed")).min_size(egui::vec2(140.0, 28.0)))
+                            .on_hover_text(tr(lang, "Push remaining files in firstbase_json/ (rejected from last push)")).clicked() {
                             self.pipeline_mode = 3;
                             self.start_pipeline(ctx.clone());
                         }
-                        let can_repush_srn = !self.running && !self.settings.srns.trim().is_empty();
-                        if ui.add_enabled(can_repush_srn, egui::Button::new("4: Repush SRN").min_size(egui::vec2(140.0, 28.0)))
-                            .on_hover_text("Restore files for the given SRN(s) from processed/ and push (bypasses unchanged-skip)").clicked() {
+                        let can_repush_srn = !self.settings.srns.trim().is_empty();
+                        if ui.add_enabled(can_repush_srn, egui::Button::new(tr(lang, "4: Repush SRN")).min_size(egui::vec2(140.0, 28.0)))
+                            .on_hover_text(tr(lang, "Restore files for the given SRN(s) from processed/ and push (bypasses unchanged-skip)")).clicked() {
                             self.pipeline_mode = 4;
                             self.start_pipeline(ctx.clone());
                         }
-                        if ui.add_enabled(can_repush_srn, egui::Button::new("5: Reconvert + Repush SRN").min_size(egui::vec2(200.0, 28.0)))
-                            .on_hover_text("Re-run the converter for the given SRN(s) (picks up new GS1 fields, e.g. DescriptionShort), then push").clicked() {
+                        if ui.add_enabled(can_repush_srn, egui::Button::new(tr(lang, "5: Reconvert + Repush SRN")).min_size(egui::vec2(200.0, 28.0)))
+                            .on_hover_text(tr(lang, "Re-run the converter for the given SRN(s) (picks up new GS1 fields, e.g. DescriptionShort), then push")).clicked() {
                             self.pipeline_mode = 5;
                             self.start_pipeline(ctx.clone());
                         }
                         if ui.add_enabled(can_repush_srn, egui::Button::new("6: StaleCleaner").min_size(egui::vec2(150.0, 28.0)))
-                            .on_hover_text("Force-refetch detail + Basic UDI-DI fresh from EUDAMED for the given SRN(s) (heals stale/incomplete cache → fixes 097.025), then reconvert & push").clicked() {
+                            .on_hover_text(tr(lang, "Force-refetch detail + Basic UDI-DI fresh from EUDAMED for the given SRN(s) (heals stale/incomplete cache → fixes 097.025), then reconvert & push")).clicked() {
                             self.pipeline_mode = 6;
                             self.start_pipeline(ctx.clone());
                         }
@@ -1031,20 +3152,11 @@ impl eframe::App for App {
                     egui::vec2(available_width - left_width - 8.0, available_height),
                 );
                 let mut right_ui = ui.new_child(egui::UiBuilder::new().max_rect(right_rect));
-                right_ui.label("Log:");
+                right_ui.label(tr(lang, "Log:"));
                 self.render_download_status_bar(&mut right_ui);
-                egui::ScrollArea::vertical()
-                    .id_salt("log_horiz")
-                    .stick_to_bottom(true)
-                    .show(&mut right_ui, |ui| {
-                        let log_text = self.log_lines.join("\n");
-                        ui.add(
-                            egui::TextEdit::multiline(&mut log_text.as_str())
-                                .font(egui::TextStyle::Monospace)
-                                .desired_width(f32::INFINITY)
-                                .desired_rows(20)
-                        );
-                    });
+                self.render_jobs(&mut right_ui);
+                self.render_push_dashboard(&mut right_ui);
+                self.render_log(&mut right_ui, "log_horiz");
             } else {
                 // --- Vertical: Settings top, Log bottom ---
                 let available_height = ui.available_height();
@@ -1055,7 +3167,7 @@ impl eframe::App for App {
                     .max_height(self.split_size)
                     .show(ui, |ui| {
             // --- SRN input ---
-            ui.label("SRNs (one per line or space-separated):");
+            ui.label(tr(lang, "SRNs (one per line or space-separated):"));
             ui.add(
                 egui::TextEdit::multiline(&mut self.settings.srns)
                     .desired_rows(5)
@@ -1067,21 +3179,21 @@ impl eframe::App for App {
 
             // --- Options row ---
             ui.horizontal(|ui| {
-                ui.label("Limit per SRN:");
+                ui.label(tr(lang, "Limit per SRN:"));
                 ui.add(
                     egui::TextEdit::singleline(&mut self.settings.limit)
                         .desired_width(80.0)
-                        .hint_text("all"),
+                        .hint_text(tr(lang, "all")),
                 );
                 ui.add_space(16.0);
-                ui.checkbox(&mut self.settings.dry_run, "Dry run (download & convert only)");
+                ui.checkbox(&mut self.settings.dry_run, tr(lang, "Dry run (download & convert only)"));
             });
 
             ui.add_space(4.0);
 
             // --- Push target selector ---
             ui.horizontal(|ui| {
-                ui.label("Target:");
+                ui.label(tr(lang, "Target:"));
                 ui.radio_value(&mut self.settings.push_target, PushTarget::Firstbase, "GS1 firstbase");
                 ui.radio_value(&mut self.settings.push_target, PushTarget::Swissdamed, "Swissdamed");
             });
@@ -1091,28 +3203,28 @@ impl eframe::App for App {
             // --- Credentials (collapsible, conditional on target) ---
             match self.settings.push_target {
                 PushTarget::Firstbase => {
-                    ui.collapsing("GS1 firstbase Credentials", |ui| {
+                    egui::CollapsingHeader::new(tr(lang, "GS1 firstbase Credentials")).id_salt("GS1 firstbase Credentials").show(ui, |ui| {
                         self.show_credentials = true;
                         ui.horizontal(|ui| {
-                            ui.label("Environment:");
+                            ui.label(tr(lang, "Environment:"));
                             ui.radio_value(&mut self.settings.firstbase_env, FirstbaseEnv::Test, "Test");
-                            ui.radio_value(&mut self.settings.firstbase_env, FirstbaseEnv::Production, "Production");
+                            ui.radio_value(&mut self.settings.firstbase_env, FirstbaseEnv::Production, tr(lang, "Production"));
                         });
                         if self.settings.firstbase_env == FirstbaseEnv::Production {
                             ui.colored_label(
                                 egui::Color32::from_rgb(200, 0, 0),
-                                "⚠ PRODUCTION — real data will be pushed!",
+                                tr(lang, "⚠ PRODUCTION — real data will be pushed!"),
                             );
                         }
                         ui.horizontal(|ui| {
-                            ui.label("Email:");
+                            ui.label(tr(lang, "Email:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.firstbase_email)
                                     .desired_width(300.0),
                             );
                         });
                         ui.horizontal(|ui| {
-                            ui.label("Password:");
+                            ui.label(tr(lang, "Password:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.firstbase_password)
                                     .desired_width(300.0)
@@ -1120,14 +3232,14 @@ impl eframe::App for App {
                             );
                         });
                         ui.horizontal(|ui| {
-                            ui.label("Provider GLN:");
+                            ui.label(tr(lang, "Provider GLN:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.provider_gln)
                                     .desired_width(300.0),
                             );
                         });
                         ui.horizontal(|ui| {
-                            ui.label("Publish To GLN:");
+                            ui.label(tr(lang, "Publish To GLN:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.publish_to_gln)
                                     .desired_width(300.0)
@@ -1137,17 +3249,17 @@ impl eframe::App for App {
                     });
                 }
                 PushTarget::Swissdamed => {
-                    ui.collapsing("Swissdamed Credentials", |ui| {
+                    egui::CollapsingHeader::new(tr(lang, "Swissdamed Credentials")).id_salt("Swissdamed Credentials").show(ui, |ui| {
                         self.show_credentials = true;
                         ui.horizontal(|ui| {
-                            ui.label("Client ID:");
+                            ui.label(tr(lang, "Client ID:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.swissdamed_client_id)
                                     .desired_width(300.0),
                             );
                         });
                         ui.horizontal(|ui| {
-                            ui.label("Client Secret:");
+                            ui.label(tr(lang, "Client Secret:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.swissdamed_client_secret)
                                     .desired_width(300.0)
@@ -1155,7 +3267,7 @@ impl eframe::App for App {
                             );
                         });
                         ui.horizontal(|ui| {
-                            ui.label("API Base URL:");
+                            ui.label(tr(lang, "API Base URL:"));
                             ui.add(
                                 egui::TextEdit::singleline(&mut self.settings.swissdamed_base_url)
                                     .desired_width(300.0),
@@ -1167,9 +3279,28 @@ impl eframe::App for App {
 
             ui.add_space(4.0);
 
+            self.render_drop_queue(ui);
+            self.render_search(ui);
+            ui.add_space(4.0);
+
+            self.render_devices(ui);
+            ui.add_space(4.0);
+
+            self.render_readiness(ui);
+            ui.add_space(4.0);
+
+            self.render_triage(ui);
+            ui.add_space(4.0);
+
+            self.render_validation(ui);
+            ui.add_space(4.0);
+
+            self.render_stats(ui);
+            ui.add_space(4.0);
+
             ui.collapsing("WhatsApp", |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Phone / Group:");
+                    ui.label(tr(lang, "Phone / Group:"));
                     ui.add(
                         egui::TextEdit::singleline(&mut self.settings.whatsapp_jid)
                             .desired_width(320.0)
@@ -1179,8 +3310,8 @@ impl eframe::App for App {
                 ui.horizontal(|ui| {
                     let can_run = !self.running;
                     if ui
-                        .add_enabled(can_run, egui::Button::new("Pair / Link Device"))
-                        .on_hover_text("Show QR code to link this device to WhatsApp (first-run only; session persists in whatsapp/auth/)")
+                        .add_enabled(can_run, egui::Button::new(tr(lang, "Pair / Link Device")))
+                        .on_hover_text(tr(lang, "Show QR code to link this device to WhatsApp (first-run only; session persists in whatsapp/auth/)"))
                         .clicked()
                     {
                         self.start_whatsapp_pair(ctx.clone());
@@ -1189,11 +3320,11 @@ impl eframe::App for App {
                     if ui
                         .add_enabled(
                             can_send,
-                            egui::Button::new("Send latest Prod log")
+                            egui::Button::new(tr(lang, "Send latest Prod log"))
                                 .fill(egui::Color32::from_rgb(255, 230, 230)),
                         )
                         .on_hover_text(
-                            "Send the most recent log/firstbase_prod/*.log.html via WhatsApp",
+                            tr(lang, "Send the most recent log/firstbase_prod/*.log.html via WhatsApp"),
                         )
                         .clicked()
                     {
@@ -1205,11 +3336,11 @@ impl eframe::App for App {
                     if ui
                         .add_enabled(
                             can_send,
-                            egui::Button::new("Send latest Test log")
+                            egui::Button::new(tr(lang, "Send latest Test log"))
                                 .fill(egui::Color32::from_rgb(230, 240, 255)),
                         )
                         .on_hover_text(
-                            "Send the most recent log/firstbase_test/*.log.html via WhatsApp",
+                            tr(lang, "Send the most recent log/firstbase_test/*.log.html via WhatsApp"),
                         )
                         .clicked()
                     {
@@ -1225,15 +3356,13 @@ impl eframe::App for App {
                 PushTarget::Firstbase => "firstbase",
                 PushTarget::Swissdamed => "Swissdamed",
             };
-            let button_text = if self.running {
-                "Running...".to_string()
-            } else if self.settings.dry_run {
+            let button_text = if self.settings.dry_run {
                 "0: Download & Convert".to_string()
             } else {
-                format!("0: Download, Convert & Push to {}", target_name)
+                tr(lang, "0: Download, Convert & Push to {}").replace("{}", target_name)
             };
 
-            let can_start = !self.running && !self.settings.srns.trim().is_empty();
+            let can_start = !self.settings.srns.trim().is_empty();
 
             ui.horizontal(|ui| {
                 if ui
@@ -1251,7 +3380,7 @@ impl eframe::App for App {
                 };
                 if ui
                     .add_enabled(can_start, egui::Button::new(&convert_text).min_size(egui::vec2(170.0, 32.0)))
-                    .on_hover_text("Skip download, convert+push all existing files")
+                    .on_hover_text(tr(lang, "Skip download, convert+push all existing files"))
                     .clicked()
                 {
                     self.pipeline_mode = 1;
@@ -1265,25 +3394,25 @@ impl eframe::App for App {
                 };
                 if ui
                     .add_enabled(can_start, egui::Button::new(&srn_text).min_size(egui::vec2(170.0, 32.0)))
-                    .on_hover_text("No download — find SRN products in existing files, convert & push")
+                    .on_hover_text(tr(lang, "No download — find SRN products in existing files, convert & push"))
                     .clicked()
                 {
                     self.pipeline_mode = 2;
                     self.start_pipeline(ctx.clone());
                 }
 
-                let can_repush_srn = !self.running && !self.settings.srns.trim().is_empty();
+                let can_repush_srn = !self.settings.srns.trim().is_empty();
                 if ui
-                    .add_enabled(can_repush_srn, egui::Button::new("4: Repush SRN").min_size(egui::vec2(150.0, 32.0)))
-                    .on_hover_text("Restore files for the given SRN(s) from processed/ and push (bypasses unchanged-skip)")
+                    .add_enabled(can_repush_srn, egui::Button::new(tr(lang, "4: Repush SRN")).min_size(egui::vec2(150.0, 32.0)))
+                    .on_hover_text(tr(lang, "Restore files for the given SRN(s) from processed/ and push (bypasses unchanged-skip)"))
                     .clicked()
                 {
                     self.pipeline_mode = 4;
                     self.start_pipeline(ctx.clone());
                 }
                 if ui
-                    .add_enabled(can_repush_srn, egui::Button::new("5: Reconvert + Repush SRN").min_size(egui::vec2(210.0, 32.0)))
-                    .on_hover_text("Re-run the converter for the given SRN(s) (picks up new GS1 fields, e.g. DescriptionShort), then push")
+                    .add_enabled(can_repush_srn, egui::Button::new(tr(lang, "5: Reconvert + Repush SRN")).min_size(egui::vec2(210.0, 32.0)))
+                    .on_hover_text(tr(lang, "Re-run the converter for the given SRN(s) (picks up new GS1 fields, e.g. DescriptionShort), then push"))
                     .clicked()
                 {
                     self.pipeline_mode = 5;
@@ -1291,7 +3420,7 @@ impl eframe::App for App {
                 }
                 if ui
                     .add_enabled(can_repush_srn, egui::Button::new("6: StaleCleaner").min_size(egui::vec2(150.0, 32.0)))
-                    .on_hover_text("Force-refetch detail + Basic UDI-DI fresh from EUDAMED for the given SRN(s) (heals stale/incomplete cache → fixes 097.025), then reconvert & push")
+                    .on_hover_text(tr(lang, "Force-refetch detail + Basic UDI-DI fresh from EUDAMED for the given SRN(s) (heals stale/incomplete cache → fixes 097.025), then reconvert & push"))
                     .clicked()
                 {
                     self.pipeline_mode = 6;
@@ -1328,19 +3457,11 @@ impl eframe::App for App {
             }
 
             // --- Bottom: Log output ---
-            ui.label("Log:");
+            ui.label(tr(lang, "Log:"));
             self.render_download_status_bar(ui);
-            egui::ScrollArea::vertical()
-                .stick_to_bottom(true)
-                .show(ui, |ui| {
-                    let log_text = self.log_lines.join("\n");
-                    ui.add(
-                        egui::TextEdit::multiline(&mut log_text.as_str())
-                            .font(egui::TextStyle::Monospace)
-                            .desired_width(f32::INFINITY)
-                            .desired_rows(20)
-                    );
-                });
+            self.render_jobs(ui);
+            self.render_push_dashboard(ui);
+            self.render_log(ui, "log_vert");
             } // end vertical else
         });
 
@@ -1352,9 +3473,9 @@ impl eframe::App for App {
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
                 .show(ctx, |ui| {
-                    ui.label("Open WhatsApp on your phone:");
-                    ui.label("Settings  →  Linked Devices  →  Link a Device");
-                    ui.label("Then scan this QR code:");
+                    ui.label(tr(lang, "Open WhatsApp on your phone:"));
+                    ui.label(tr(lang, "Settings  →  Linked Devices  →  Link a Device"));
+                    ui.label(tr(lang, "Then scan this QR code:"));
                     ui.add_space(6.0);
                     let size = tex.size_vec2();
                     let target = egui::vec2(280.0, 280.0);
@@ -1364,9 +3485,9 @@ impl eframe::App for App {
                         size * scale,
                     )));
                     ui.add_space(6.0);
-                    ui.label("Waiting for scan…");
+                    ui.label(tr(lang, "Waiting for scan…"));
                     ui.add_space(4.0);
-                    if ui.button("Cancel").clicked() {
+                    if ui.button(tr(lang, "Cancel")).clicked() {
                         cancel = true;
                     }
                 });
@@ -1380,13 +3501,13 @@ impl eframe::App for App {
         // Error dialog (modal) — shown when the worker reports failure or a panic.
         if let Some(msg) = self.error_dialog.clone() {
             let mut close = false;
-            egui::Window::new("⚠  Error")
+            egui::Window::new(tr(lang, "⚠  Error"))
                 .collapsible(false)
                 .resizable(true)
                 .default_width(520.0)
                 .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
                 .show(ctx, |ui| {
-                    ui.label("The pipeline did not complete successfully:");
+                    ui.label(tr(lang, "The pipeline did not complete successfully:"));
                     ui.add_space(6.0);
                     egui::ScrollArea::vertical()
                         .max_height(260.0)
@@ -1400,7 +3521,7 @@ impl eframe::App for App {
                         });
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
-                        if ui.button("Copy to clipboard").clicked() {
+                        if ui.button(tr(lang, "Copy to clipboard")).clicked() {
                             ctx.output_mut(|o| o.copied_text = msg.clone());
                         }
                         if ui.button("OK").clicked() {
@@ -1413,6 +3534,17 @@ impl eframe::App for App {
             }
         }
 
+        // Switching the firstbase environment swaps in its keychain login
+        // before the auto-save would store the old one under the new env.
+        if self.settings.firstbase_env != self.credentials_env {
+            self.credentials_env = self.settings.firstbase_env.clone();
+            if let Some(cred) = self.settings.load_credential(credentials::Kind::Firstbase) {
+                self.settings
+                    .set_credential(credentials::Kind::Firstbase, cred);
+            }
+        }
+        self.render_credential_screen(ctx);
+
         // Auto-save settings when they change
         let current = serde_json::to_string(&self.settings).unwrap_or_default();
         if current != self.last_saved_settings {
@@ -1422,10 +3554,105 @@ impl eframe::App for App {
     }
 }
 
+/// One GS1 firstbase `/Account/Token` request through `http_post`
+/// (url, bearer, body); used by the push, the credential screen's test and
+/// `doctor`.
+pub fn firstbase_token(
+    http_post: &dyn Fn(&str, &str, &str) -> anyhow::Result<String>,
+    api_base: &str,
+    email: &str,
+    password: &str,
+    gln: &str,
+) -> anyhow::Result<String> {
+    let body = serde_json::json!({
+        "UserEmail": email,
+        "Password": password,
+        "Gln": gln,
+    });
+    let token_raw = http_post(
+        &format!("{}/Account/Token", api_base),
+        "",
+        &body.to_string(),
+    )?;
+    let token = token_raw.trim_matches('"').to_string();
+    if token.len() > 20 {
+        Ok(token)
+    } else {
+        Err(anyhow::anyhow!("Unexpected token response: {}", token_raw))
+    }
+}
+
+/// swissdamed OAuth2 client-credentials token.
+const SWISSDAMED_TOKEN_URL: &str = "https://3a5c95df-c59f-418a-96fc-b8531bf24be8.ciamlogin.com/3a5c95df-c59f-418a-96fc-b8531bf24be8/oauth2/v2.0/token";
+
+fn swissdamed_token(
+    http_agent: &ureq::Agent,
+    client_id: &str,
+    client_secret: &str,
+) -> anyhow::Result<String> {
+    let token_url = SWISSDAMED_TOKEN_URL;
+    let scope = "8d64e26d-ea71-4ab8-90d6-2acd795eb668/.default";
+    let form_body = format!(
+        "grant_type=client_credentials&client_id={}&client_secret={}&scope={}",
+        client_id, client_secret, scope
+    );
+    let mut resp = http_agent
+        .post(token_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .send(form_body.as_bytes())?;
+    let body: serde_json::Value = serde_json::from_str(&resp.body_mut().read_to_string()?)?;
+    body.get("access_token")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("No access_token: {}", body))
+}
+
+/// Exercise the login of a credential-screen row: a token request against
+/// its environment (firstbase with the configured Provider GLN) or swissdamed.
+fn test_credential(slot: &credentials::Slot, provider_gln: &str) -> anyhow::Result<()> {
+    match slot.kind {
+        credentials::Kind::Firstbase => {
+            let env = if slot.env == Some("production") {
+                FirstbaseEnv::Production
+            } else {
+                FirstbaseEnv::Test
+            };
+            let http_agent = crate::http::agent_for(env.api_base());
+            let http_post = |url: &str, _auth: &str, body: &str| -> anyhow::Result<String> {
+                let mut resp = http_agent
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .send(body.as_bytes())?;
+                let status = resp.status();
+                let resp_body = resp.body_mut().read_to_string()?;
+                if status.as_u16() >= 400 {
+                    Err(anyhow::anyhow!("http {}: {}", status, resp_body))
+                } else {
+                    Ok(resp_body)
+                }
+            };
+            firstbase_token(
+                &http_post,
+                env.api_base(),
+                &slot.cred.id,
+                &slot.cred.secret,
+                provider_gln,
+            )?;
+        }
+        credentials::Kind::Swissdamed => {
+            let http_agent = crate::http::agent_for(SWISSDAMED_TOKEN_URL);
+            swissdamed_token(&http_agent, &slot.cred.id, &slot.cred.secret)?;
+        }
+    }
+    Ok(())
+}
+
 /// GUI adapter for the shared download progress trait.
 struct GuiProgress {
     tx: mpsc::Sender<WorkerMsg>,
     ctx: egui::Context,
+    /// Cancel request of the job this download belongs to.
+    monitor: push_dashboard::Monitor,
 }
 
 impl DownloadProgress for GuiProgress {
@@ -1440,16 +3667,22 @@ impl DownloadProgress for GuiProgress {
         let _ = self.tx.send(msg);
         self.ctx.request_repaint();
     }
+
+    fn is_cancelled(&self) -> bool {
+        self.monitor.is_cancelled()
+    }
 }
 
-/// Run the full download → convert → push pipeline in a background thread.
-fn run_pipeline(
-    settings: Settings,
-    tx: mpsc::Sender<WorkerMsg>,
-    ctx: egui::Context,
-    pipeline_mode: u8,
-) {
-    // Redirect stderr to /dev/null to prevent eprintln! panics when GUI has no terminal
+/// What the selection bar of the device table asked for.
+enum SelectionAction {
+    Validate,
+    /// Queue pipeline mode 8 or 9 on the selected UUIDs.
+    Run(u8),
+}
+
+/// Redirect stderr to /dev/null to prevent eprintln! panics when the GUI has
+/// no terminal. Not for `run_headless`, whose caller reads stderr.
+fn silence_stderr() {
     #[cfg(unix)]
     {
         use std::os::unix::io::FromRawFd;
@@ -1465,10 +3698,21 @@ fn run_pipeline(
             }
         }
     }
+}
 
+/// Run the full download → convert → push pipeline in a background thread.
+fn run_pipeline(
+    settings: Settings,
+    tx: mpsc::Sender<WorkerMsg>,
+    ctx: egui::Context,
+    pipeline_mode: u8,
+    monitor: push_dashboard::Monitor,
+    queued: Vec<String>,
+) {
     let gui_progress = GuiProgress {
         tx: tx.clone(),
         ctx: ctx.clone(),
+        monitor: monitor.clone(),
     };
 
     let log = |msg: &str| {
@@ -1483,20 +3727,10 @@ fn run_pipeline(
         ctx.request_repaint();
     };
 
-    let mode_name = match pipeline_mode {
-        0 => "full pipeline (download + convert + push)",
-        1 => "convert & push (all existing files)",
-        2 => "convert & push (SRN filter)",
-        3 => "repush failed (from last push session)",
-        4 => "repush SRN (restore from processed/ + push)",
-        5 => "reconvert + repush SRN (force-reconvert from detail/)",
-        6 => "StaleCleaner (force-reload detail+BUDI from EUDAMED, reconvert + push)",
-        _ => "unknown",
-    };
     log(&format!(
         "eudamed2firstbase v{} — mode: {}",
         env!("CARGO_PKG_VERSION"),
-        mode_name
+        commands::label(pipeline_mode)
     ));
 
     // Mode 3: Repush failed — read rejected GTINs from DB, move from processed/, push
@@ -1658,7 +3892,7 @@ fn run_pipeline(
                 ctx.request_repaint();
                 // Mode 3 (Repush failed, ALL): intentionally unscoped — push every
                 // rejected file still in firstbase_json/, across all SRNs.
-                match push_to_firstbase(&settings, &log, None) {
+                match push_to_firstbase(&settings, &log, None, Some(&monitor)) {
                     Ok(out) => {
                         done(
                             true,
@@ -1740,7 +3974,8 @@ fn run_pipeline(
         .filter(|s| !s.is_empty())
         .collect();
 
-    if srns.is_empty() {
+    // Modes 7-9 convert a device list, whatever SRN the devices belong to.
+    if srns.is_empty() && !matches!(pipeline_mode, 7..=9) {
         done(false, "No SRNs provided");
         return;
     }
@@ -1844,10 +4079,7 @@ fn run_pipeline(
         // Re-pushing them hits G485 (discontinuedDateTime becomes a protected field
         // after first ACCEPTED, only updatable via DocumentCommand: "CORRECT").
         // Terminal lifecycle, no new content to deliver — skipping is correct.
-        let env_label_db = match settings.firstbase_env {
-            FirstbaseEnv::Test => "Test",
-            FirstbaseEnv::Production => "Production",
-        };
+        let env_label_db = settings.firstbase_label();
         let (uuids, skipped) =
             crate::version_db::filter_skip_no_longer_accepted(&conn, &uuids, env_label_db);
         if skipped > 0 {
@@ -1895,7 +4127,7 @@ fn run_pipeline(
 
         // Mode 5/6: reconvert from eudamed_json/detail/ before push.
         if pipeline_mode == 5 || pipeline_mode == 6 {
-            let config_path = app_data.join("config.toml");
+            let config_path = crate::platform_dirs::config_file();
             let fb_config = match crate::config::load_config(&config_path) {
                 Ok(c) => c,
                 Err(e) => {
@@ -1992,7 +4224,7 @@ fn run_pipeline(
         ctx.request_repaint();
         // SRN-scoped (Mode 4/5/6): push ONLY this run's UUIDs, not the whole
         // firstbase_json/ backlog of other SRNs' rejected files.
-        match push_to_firstbase(&settings, &log, Some(&uuids)) {
+        match push_to_firstbase(&settings, &log, Some(&uuids), Some(&monitor)) {
             Ok(out) => {
                 done(
                     true,
@@ -2028,7 +4260,22 @@ fn run_pipeline(
 
     let mut uuids: Vec<String>;
 
-    if pipeline_mode == 2 {
+    if pipeline_mode == 7 {
+        // Mode 7: files dropped onto the window, already routed to detail/.
+        log(&format!(
+            "[Drop] Converting {} dropped file(s) (no EUDAMED download)",
+            queued.len()
+        ));
+        uuids = queued;
+    } else if pipeline_mode == 8 || pipeline_mode == 9 {
+        // Mode 8/9: devices ticked in the device table, re-converted even
+        // when unchanged; mode 8 stops before the push.
+        log(&format!(
+            "[Selection] Converting {} selected device(s) (no EUDAMED download)",
+            queued.len()
+        ));
+        uuids = queued;
+    } else if pipeline_mode == 2 {
         // Mode 2: SRN filter — scan basic/ files for matching manufacturer SRN
         log("[SRN Filter] Scanning basic files for matching SRNs...");
         let srn_set: std::collections::HashSet<String> = srns.iter().cloned().collect();
@@ -2161,13 +4408,7 @@ fn run_pipeline(
             });
             ctx.request_repaint();
 
-            let config_path = download::app_data_dir().join("config.toml");
-            // Fall back to current dir if not in container
-            let config_path = if config_path.exists() {
-                config_path
-            } else {
-                PathBuf::from("config.toml")
-            };
+            let config_path = crate::platform_dirs::config_file();
             let config = match crate::config::load_config(&config_path) {
                 Ok(c) => c,
                 Err(e) => {
@@ -2178,10 +4419,26 @@ fn run_pipeline(
 
             let output_dir = download::app_data_dir().join("firstbase_json");
             let _ = std::fs::create_dir_all(&output_dir);
+            let selection = pipeline_mode == 8 || pipeline_mode == 9;
+            let mut no_detail = 0;
+            let mut without_gtin = crate::no_gtin::NoGtin::new(config.device_level.no_gtin);
 
             for uuid in &uuids {
+                if monitor.is_cancelled() {
+                    break;
+                }
                 let detail_path = detail_dir.join(format!("{}.json", uuid));
                 if !detail_path.exists() {
+                    if selection {
+                        // Nothing to convert from: push the document as it is.
+                        let output_path = output_dir.join(format!("{}.json", uuid));
+                        let processed_path =
+                            output_dir.join("processed").join(format!("{}.json", uuid));
+                        if !output_path.exists() && processed_path.exists() {
+                            let _ = std::fs::copy(&processed_path, &output_path);
+                        }
+                        no_detail += 1;
+                    }
                     continue;
                 }
 
@@ -2190,6 +4447,45 @@ fn run_pipeline(
                     Err(_) => continue,
                 };
 
+                // Same detection as `process_eudamed_json_dir`: a dropped file
+                // without primaryDi is device-level (Basic UDI-DI) data.
+                if (pipeline_mode == 7 || selection)
+                    && dump::classify_json(
+                        &serde_json::from_str(&json_content).unwrap_or_default(),
+                        false,
+                    ) == dump::EntryKind::Device
+                {
+                    match crate::eudamed_json::parse_eudamed_json(&json_content) {
+                        Ok(device) => {
+                            // No GTIN: [device_level] no_gtin decides (skip/lookup/placeholder)
+                            let outputs = without_gtin.convert(&device, uuid, &config);
+                            if !outputs.is_empty() {
+                                converted += 1;
+                            }
+                            for out in outputs {
+                                let dir = if out.placeholder {
+                                    output_dir.join(crate::no_gtin::PLACEHOLDER_DIR)
+                                } else {
+                                    output_dir.clone()
+                                };
+                                let _ = std::fs::create_dir_all(&dir);
+                                let draft_doc = crate::firstbase::DraftItemDocument {
+                                    draft_item: out.document,
+                                };
+                                let _ = crate::write_json(
+                                    &dir.join(format!("{}.json", out.stem)),
+                                    &draft_doc,
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            log(&format!("  Convert error {}: {}", uuid, e));
+                            convert_errors += 1;
+                        }
+                    }
+                    continue;
+                }
+
                 let mut version_rec = crate::version_db::extract_detail_versions(&json_content);
                 let budi_cache_path = basic_dir.join(format!("{}.json", uuid));
                 if let Ok(budi_json) = std::fs::read_to_string(&budi_cache_path) {
@@ -2202,7 +4498,7 @@ fn run_pipeline(
                     Err(_) => continue,
                 };
 
-                if !changes.has_any_change() {
+                if !changes.has_any_change() && !selection {
                     let processed_path =
                         output_dir.join("processed").join(format!("{}.json", uuid));
                     let output_path = output_dir.join(format!("{}.json", uuid));
@@ -2248,9 +4544,7 @@ fn run_pipeline(
                         };
 
                         let output_path = output_dir.join(format!("{}.json", uuid));
-                        if let Ok(json) = serde_json::to_string_pretty(&draft_doc) {
-                            let _ = std::fs::write(&output_path, &json);
-                        }
+                        let _ = crate::write_json(&output_path, &draft_doc);
 
                         let _ = crate::version_db::upsert_version(&conn, &version_rec);
                         converted += 1;
@@ -2263,14 +4557,65 @@ fn run_pipeline(
                     }
                 }
             }
-
-            log(&format!(
-                "Converted: {} new/changed, {} skipped (unchanged), {} errors -> {}",
-                converted,
-                skipped,
-                convert_errors,
-                output_dir.display()
-            ));
+
+            log(&format!(
+                "Converted: {} new/changed, {} skipped (unchanged), {} errors -> {}",
+                converted,
+                skipped,
+                convert_errors,
+                output_dir.display()
+            ));
+            crate::mappings::report_unknown_languages();
+            if let Ok(Some(path)) = crate::gpc::write_report(&output_dir) {
+                log(&format!("GPC bricks per device -> {}", path.display()));
+            }
+            if let Ok(Some((path, summary))) = crate::warnings::write_report(&output_dir) {
+                log(&format!(
+                    "[Convert] Warnings: {} -> {}",
+                    summary,
+                    path.display()
+                ));
+            }
+            if let Ok(Some((path, count))) = crate::device_category::write_report(&output_dir) {
+                log(&format!(
+                    "[Convert] {} device(s) firstbase cannot accept, no document -> {}",
+                    count,
+                    path.display()
+                ));
+            }
+            if let Ok(Some(report)) = without_gtin.write_report(&output_dir) {
+                log(&format!(
+                    "[Convert] {} device-level record(s) without GTIN, no document -> {}",
+                    without_gtin.skipped(),
+                    report.display()
+                ));
+            }
+            if selection {
+                if no_detail > 0 {
+                    log(&format!(
+                        "[Selection] {} device(s) have no eudamed_json/detail/<uuid>.json — kept as converted before",
+                        no_detail
+                    ));
+                }
+                let mut failing = 0;
+                for uuid in &uuids {
+                    let findings =
+                        std::fs::read_to_string(output_dir.join(format!("{}.json", uuid)))
+                            .ok()
+                            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+                            .map(|doc| crate::report::findings(&doc))
+                            .unwrap_or_default();
+                    if !findings.is_empty() {
+                        failing += 1;
+                        log(&format!("  {}: {}", uuid, findings.join("; ")));
+                    }
+                }
+                log(&format!(
+                    "[Selection] Validation: {} of {} with findings",
+                    failing,
+                    uuids.len()
+                ));
+            }
         }
         PushTarget::Swissdamed => {
             let _ = tx.send(WorkerMsg::Progress {
@@ -2283,6 +4628,9 @@ fn run_pipeline(
             let _ = std::fs::create_dir_all(&output_dir);
 
             for uuid in &uuids {
+                if monitor.is_cancelled() {
+                    break;
+                }
                 let detail_path = detail_dir.join(format!("{}.json", uuid));
                 let basic_path = basic_dir.join(format!("{}.json", uuid));
                 if !detail_path.exists() || !basic_path.exists() {
@@ -2315,21 +4663,21 @@ fn run_pipeline(
                         Err(_) => continue,
                     };
 
-                let is_spp = basic_udi.is_spp();
-                let payload = if is_spp {
-                    serde_json::to_string_pretty(&crate::swissdamed::to_spp_dto(
-                        &device, &basic_udi,
-                    ))
+                let out_path = output_dir.join(format!("{}.json", uuid));
+                let written = if basic_udi.is_spp() {
+                    crate::write_json(
+                        &out_path,
+                        &crate::swissdamed::to_spp_dto(&device, &basic_udi),
+                    )
                 } else {
-                    serde_json::to_string_pretty(&crate::swissdamed::to_mdr_dto(
-                        &device, &basic_udi,
-                    ))
+                    crate::write_json(
+                        &out_path,
+                        &crate::swissdamed::to_mdr_dto(&device, &basic_udi),
+                    )
                 };
 
-                match payload {
-                    Ok(json) => {
-                        let out_path = output_dir.join(format!("{}.json", uuid));
-                        let _ = std::fs::write(&out_path, &json);
+                match written {
+                    Ok(()) => {
                         converted += 1;
                     }
                     Err(e) => {
@@ -2350,8 +4698,20 @@ fn run_pipeline(
         }
     }
 
+    if monitor.is_cancelled() {
+        log("");
+        done(
+            false,
+            &format!(
+                "Cancelled after converting {} device(s); nothing pushed",
+                converted
+            ),
+        );
+        return;
+    }
+
     // --- Step 3: Push (if not dry run) ---
-    if settings.dry_run {
+    if settings.dry_run || pipeline_mode == 8 {
         log("");
         done(
             true,
@@ -2383,8 +4743,11 @@ fn run_pipeline(
             log("[Push] Pushing to GS1 firstbase Catalogue Item API...");
             ctx.request_repaint();
 
-            // Mode 0/1/2: push everything currently in firstbase_json/ (unscoped).
-            let push_result = push_to_firstbase(&settings, &log, None);
+            // Mode 0/1/2: push everything currently in firstbase_json/ (unscoped);
+            // Mode 7 only the dropped files, mode 9 only the selected devices.
+            let scope: Option<std::collections::HashSet<String>> =
+                matches!(pipeline_mode, 7 | 9).then(|| uuids.iter().cloned().collect());
+            let push_result = push_to_firstbase(&settings, &log, scope.as_ref(), Some(&monitor));
 
             match push_result {
                 Ok(out) => {
@@ -2565,9 +4928,11 @@ fn has_superior_regulation_twin(
 /// push, to **push-time + 2 days** so it is always safely after the registration
 /// timestamp (the +2 day margin also covers a multi-hour bulk push whose later
 /// batches register later). Only touches docs that already carry the field
-/// (NO_LONGER devices); returns true if updated.
+/// (NO_LONGER devices), and leaves a date derived from a market end that is
+/// later still (`discontinued.rs`); returns true if updated.
 fn restamp_discontinued_date(
     doc: &mut serde_json::Value,
+    zones: &crate::config::Dates,
     now: chrono::DateTime<chrono::Utc>,
 ) -> bool {
     let Some(sd) = doc.pointer_mut("/DraftItem/TradeItem/TradeItemSynchronisationDates") else {
@@ -2576,17 +4941,19 @@ fn restamp_discontinued_date(
     let Some(obj) = sd.as_object_mut() else {
         return false;
     };
-    let present = obj
+    let current = obj
         .get("DiscontinuedDateTime")
         .and_then(|v| v.as_str())
-        .map(|s| !s.is_empty())
-        .unwrap_or(false);
-    if !present {
+        .filter(|s| !s.is_empty());
+    let Some(current) = current else {
+        return false;
+    };
+    let earliest = now + chrono::Duration::days(2);
+    // A date derived from the market end that is already late enough stays.
+    if chrono::DateTime::parse_from_rfc3339(current).is_ok_and(|at| at >= earliest) {
         return false;
     }
-    let new_val = (now + chrono::Duration::days(2))
-        .format("%Y-%m-%dT%H:%M:%S")
-        .to_string();
+    let new_val = crate::dates::format(zones, earliest);
     obj.insert(
         "DiscontinuedDateTime".to_string(),
         serde_json::Value::String(new_val),
@@ -2630,102 +4997,148 @@ pub struct PushOutcome {
     pub transport_failed: u32,
 }
 
-pub fn push_to_firstbase(
-    settings: &Settings,
+/// Feed a finished chunk to `sizer`; on a new size, log it and re-plan the
+/// `remaining` items from chunk `next` on in the dashboard.
+fn adapt_batch_size(
+    sizer: &mut push_batch::BatchSizer,
+    chunk: push_batch::ChunkResult,
+    next: usize,
+    remaining: usize,
+    monitor: Option<&push_dashboard::Monitor>,
     log: &dyn Fn(&str),
-    uuid_filter: Option<&std::collections::HashSet<String>>,
-) -> anyhow::Result<PushOutcome> {
-    let api_base = settings.firstbase_env.api_base();
-    let env_label = match settings.firstbase_env {
-        FirstbaseEnv::Test => "TEST",
-        FirstbaseEnv::Production => "PRODUCTION",
+) {
+    let Some((size, reason)) = sizer.record(&chunk) else {
+        return;
     };
     log(&format!(
-        "Firstbase environment: {} ({})",
-        env_label, api_base
+        "  Chunk size {} ({} after {:.0}s, {} error(s) in {} item(s))",
+        size,
+        reason,
+        chunk.elapsed.as_secs_f64(),
+        chunk.errors,
+        chunk.items
     ));
-    let firstbase_dir = download::app_data_dir().join("firstbase_json");
-    let processed_dir = firstbase_dir.join("processed");
-    let _ = std::fs::create_dir_all(&processed_dir);
-
-    if let Some(allow) = uuid_filter {
-        log(&format!(
-            "Scoped push: limited to this run's {} UUID(s) — other files in firstbase_json/ are left untouched.",
-            allow.len()
-        ));
+    if let Some(m) = monitor {
+        m.replan(next, remaining, size);
     }
+}
 
-    // Collect pushable files (numeric GTIN). When a uuid_filter is set, skip any
-    // file whose stem (UUID) is not in the allowlist — keeps an SRN-scoped run
-    // from pushing unrelated rejected files left in firstbase_json/.
-    let mut files: Vec<std::path::PathBuf> = Vec::new();
-    if firstbase_dir.exists() {
-        for entry in std::fs::read_dir(&firstbase_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map(|e| e == "json").unwrap_or(false) {
-                if let Some(allow) = uuid_filter {
-                    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-                    if !allow.contains(stem) {
-                        continue;
-                    }
-                }
-                files.push(path);
-            }
-        }
-    }
+/// End of the CreateMany chunk from `start`: at most `size` items of one
+/// DocumentCommand, cut short where their items would outgrow
+/// `max_payload_bytes` (`push_batch::fit`).
+fn chunk_end(
+    pushable: &[(std::path::PathBuf, String, String, serde_json::Value)],
+    commands: &[&'static str],
+    start: usize,
+    size: usize,
+    max_payload_bytes: usize,
+) -> usize {
+    let end = (start + size).min(pushable.len());
+    let end = (start..end)
+        .find(|&i| commands[i] != commands[start])
+        .unwrap_or(end);
+    let item_bytes = pushable[start..end]
+        .iter()
+        .map(|(_, _, _, doc)| create_many_item(doc).map_or(0, |item| item.to_string().len()));
+    start + push_batch::fit(item_bytes, max_payload_bytes)
+}
 
-    if files.is_empty() {
-        log("No firstbase JSON files to push.");
-        return Ok(PushOutcome {
-            accepted: 0,
-            rejected: 0,
-            transport_failed: 0,
-        });
+/// The CreateMany item of a firstbase document; `None` without a numeric
+/// GTIN, which would fail the whole batch.
+fn create_many_item(doc: &serde_json::Value) -> Option<serde_json::Value> {
+    let draft = doc.get("DraftItem")?;
+    let gtin = draft.pointer("/TradeItem/Gtin")?.as_str()?;
+    if gtin.is_empty() || !gtin.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut item = serde_json::json!({
+        "Identifier": draft.get("Identifier")?,
+        "TradeItem": draft.get("TradeItem")?,
+    });
+    if let Some(children) = draft.get("CatalogueItemChildItemLink") {
+        item.as_object_mut()?
+            .insert("CatalogueItemChildItemLink".into(), children.clone());
     }
+    Some(item)
+}
 
-    log(&format!("Found {} firstbase JSON files", files.len()));
+/// The documents of a firstbase push in push order, each with its
+/// DocumentCommand, and those left out with the reason.
+struct PushPlan {
+    /// (file, Identifier, UUID, document as sent).
+    pushable: Vec<(std::path::PathBuf, String, String, serde_json::Value)>,
+    commands: Vec<&'static str>,
+    /// (UUID or file stem, reason).
+    skipped: Vec<(String, String)>,
+    skipped_no_gtin: u32,
+}
 
+/// Load `files` and decide what a push sends: G361 repair, field overrides,
+/// `discontinuedDateTime` re-stamp, numeric-GTIN filter, GTIN and
+/// cross-registration dedup and the Add / Change plan. With `apply`
+/// the repairs are written back and dropped duplicates move to `processed/`;
+/// without (dry run) every document stays as it is.
+fn plan_push(
+    settings: &Settings,
+    files: &[std::path::PathBuf],
+    zones: &crate::config::Dates,
+    firstbase_dir: &std::path::Path,
+    processed_dir: &std::path::Path,
+    apply: bool,
+    log: &dyn Fn(&str),
+) -> PushPlan {
+    let env_label = settings.firstbase_label();
     // Filter: only numeric GTINs (skip HIBC/IFA to prevent batch rejection)
     let mut pushable: Vec<(std::path::PathBuf, String, String, serde_json::Value)> = Vec::new();
-    let mut skipped_no_gtin = 0;
+    let mut skipped: Vec<(String, String)> = Vec::new();
+    let mut skipped_no_gtin = 0u32;
     let mut sanitized = 0u32;
+    let mut overridden = 0u32;
     let mut restamped = 0u32;
     // Single push-time reference so all docs in this run get a consistent
     // discontinuedDateTime safely after GS1's push-time registrationDateTime.
     let push_now = chrono::Utc::now();
-    for f in &files {
-        if let Ok(content) = std::fs::read_to_string(f) {
-            if let Ok(mut doc) = serde_json::from_str::<serde_json::Value>(&content) {
-                // Repair stale description-only globalModelInformation (G361/SCHEMA)
-                // and rewrite the file so it stops failing future pushes.
-                if sanitize_global_model_info(&mut doc) {
-                    if let Ok(fixed) = serde_json::to_string_pretty(&doc) {
-                        let _ = std::fs::write(f, fixed);
-                    }
-                    sanitized += 1;
-                }
-                // Re-stamp discontinuedDateTime to push-time + 2 days (in memory)
-                // so NO_LONGER devices clear GS1 910.005 (must be > registration).
-                if restamp_discontinued_date(&mut doc, push_now) {
-                    restamped += 1;
-                }
-                let gtin = doc
-                    .pointer("/DraftItem/TradeItem/Gtin")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                if !gtin.is_empty() && gtin.chars().all(|c| c.is_ascii_digit()) {
-                    let ident = doc
-                        .pointer("/DraftItem/Identifier")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    let uuid = ident.strip_prefix("Draft_").unwrap_or(&ident).to_string();
-                    pushable.push((f.clone(), ident, uuid, doc));
-                } else {
-                    skipped_no_gtin += 1;
-                }
+    for f in files {
+        let stem = f.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        let Some(mut doc) = std::fs::read_to_string(f)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        else {
+            skipped.push((stem.to_string(), "not readable".to_string()));
+            continue;
+        };
+        // Repair stale description-only globalModelInformation (G361/SCHEMA)
+        // and rewrite the file so it stops failing future pushes.
+        if sanitize_global_model_info(&mut doc) {
+            if apply {
+                let _ = crate::write_json(f, &doc);
             }
+            sanitized += 1;
+        }
+        // Field overrides from the GUI editor (they outlive re-conversion).
+        if overrides::apply(&mut doc, &overrides::load(&download::app_data_dir(), stem)) > 0 {
+            overridden += 1;
+        }
+        // Re-stamp discontinuedDateTime to push-time + 2 days (in memory)
+        // so NO_LONGER devices clear GS1 910.005 (must be > registration).
+        if restamp_discontinued_date(&mut doc, zones, push_now) {
+            restamped += 1;
+        }
+        let gtin = doc
+            .pointer("/DraftItem/TradeItem/Gtin")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if !gtin.is_empty() && gtin.chars().all(|c| c.is_ascii_digit()) {
+            let ident = doc
+                .pointer("/DraftItem/Identifier")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            let uuid = ident.strip_prefix("Draft_").unwrap_or(&ident).to_string();
+            pushable.push((f.clone(), ident, uuid, doc));
+        } else {
+            skipped_no_gtin += 1;
+            skipped.push((stem.to_string(), "no numeric GTIN".to_string()));
         }
     }
 
@@ -2754,6 +5167,10 @@ pub fn push_to_firstbase(
                     (prev_idx, i)
                 };
                 to_remove.push(drop);
+                skipped.push((
+                    pushable[drop].2.clone(),
+                    format!("duplicate GTIN {}, {} kept", gtin, pushable[keep].2),
+                ));
                 log(&format!(
                     "Dedup GTIN {}: keeping {} ({}), dropping {} ({})",
                     gtin,
@@ -2777,9 +5194,8 @@ pub fn push_to_firstbase(
         to_remove.sort_unstable_by(|a, b| b.cmp(a));
         for idx in to_remove {
             let (path, _, _, _) = &pushable[idx];
-            if let Some(name) = path.file_name() {
-                let dest = processed_dir.join(name);
-                let _ = std::fs::rename(path, &dest);
+            if let Some(name) = path.file_name().filter(|_| apply) {
+                let _ = std::fs::rename(path, processed_dir.join(name));
             }
             pushable.remove(idx);
         }
@@ -2810,7 +5226,11 @@ pub fn push_to_firstbase(
                 if gtin.is_empty() {
                     continue;
                 }
-                if has_superior_regulation_twin(&conn, gtin, uuid, &firstbase_dir, &processed_dir) {
+                if has_superior_regulation_twin(&conn, gtin, uuid, firstbase_dir, processed_dir) {
+                    skipped.push((
+                        uuid.clone(),
+                        format!("legacy twin of GTIN {}, MDR/IVDR twin already loaded", gtin),
+                    ));
                     log(&format!(
                         "Skip GTIN {}: superior MDR/IVDR twin already loaded, dropping legacy {} (SYS25 avoidance, #52)",
                         gtin, uuid
@@ -2821,9 +5241,8 @@ pub fn push_to_firstbase(
             to_remove.sort_unstable_by(|a, b| b.cmp(a));
             for idx in to_remove {
                 let (path, _, _, _) = &pushable[idx];
-                if let Some(name) = path.file_name() {
-                    let dest = processed_dir.join(name);
-                    let _ = std::fs::rename(path, &dest);
+                if let Some(name) = path.file_name().filter(|_| apply) {
+                    let _ = std::fs::rename(path, processed_dir.join(name));
                 }
                 pushable.remove(idx);
                 cross_dropped += 1;
@@ -2831,7 +5250,47 @@ pub fn push_to_firstbase(
         }
     }
 
+    // Push plan: discontinued items firstbase already has go out as
+    // Change, after all Add items (`discontinued.rs`).
+    let commands: Vec<&'static str> = {
+        let db_path = download::app_data_dir()
+            .join("db")
+            .join("version_tracking.db");
+        let accepted = crate::version_db::open_db(&db_path)
+            .map(|conn| {
+                let uuids: Vec<&str> = pushable.iter().map(|(_, _, u, _)| u.as_str()).collect();
+                crate::version_db::accepted_in_env(&conn, &uuids, env_label)
+            })
+            .unwrap_or_default();
+        let mut planned: Vec<_> = std::mem::take(&mut pushable)
+            .into_iter()
+            .map(|entry| {
+                let command =
+                    crate::discontinued::push_command(&entry.3, accepted.contains(&entry.2));
+                (command, entry)
+            })
+            .collect();
+        planned.sort_by_key(|(command, _)| *command != crate::discontinued::ADD);
+        planned
+            .into_iter()
+            .map(|(command, entry)| {
+                pushable.push(entry);
+                command
+            })
+            .collect()
+    };
+
     log(&format!("{} files with numeric GTIN (pushable), {} skipped (no GTIN), {} deduped (same GTIN, moved to processed/)", pushable.len(), skipped_no_gtin, deduped));
+    let changed = commands
+        .iter()
+        .filter(|c| **c == crate::discontinued::CHANGE)
+        .count();
+    if changed > 0 {
+        log(&format!(
+            "{} discontinued item(s) already accepted in {} go out as Change",
+            changed, env_label
+        ));
+    }
     if cross_dropped > 0 {
         log(&format!(
             "Cross-registration dedup: dropped {} legacy twin(s) whose GTIN an MDR/IVDR twin already loaded (moved to processed/, #52)",
@@ -2844,12 +5303,215 @@ pub fn push_to_firstbase(
             sanitized
         ));
     }
+    if overridden > 0 {
+        log(&format!(
+            "Applied field overrides to {} device(s) (overrides/)",
+            overridden
+        ));
+    }
     if restamped > 0 {
         log(&format!(
             "Re-stamped discontinuedDateTime to push-time +2d on {} NO_LONGER device(s) (GS1 910.005)",
             restamped
         ));
     }
+    PushPlan {
+        pushable,
+        commands,
+        skipped,
+        skipped_no_gtin,
+    }
+}
+
+/// Create the `push_log`, `push_session` and `push_error` tables and migrate
+/// older DBs; failures (columns already there) are ignored.
+pub fn ensure_push_log_schema(conn: &rusqlite::Connection) {
+    let _ = conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS push_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, uuid TEXT NOT NULL, gtin TEXT NOT NULL DEFAULT '',
+            pushed_at TEXT NOT NULL, request_id TEXT, status TEXT NOT NULL,
+            error_code TEXT, error_msg TEXT, publish_gln TEXT,
+            firstbase_env TEXT NOT NULL DEFAULT ''
+        );
+        CREATE TABLE IF NOT EXISTS push_session (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_ts TEXT NOT NULL,
+            version TEXT NOT NULL,
+            publish_gln TEXT NOT NULL,
+            total_pushable INTEGER NOT NULL DEFAULT 0,
+            skipped_no_gtin INTEGER NOT NULL DEFAULT 0,
+            total_accepted INTEGER NOT NULL DEFAULT 0,
+            total_rejected INTEGER NOT NULL DEFAULT 0,
+            firstbase_env TEXT NOT NULL DEFAULT '',
+            api_base TEXT NOT NULL DEFAULT ''
+        );
+        CREATE TABLE IF NOT EXISTS push_error (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            uuid TEXT NOT NULL DEFAULT '',
+            gtin TEXT NOT NULL DEFAULT '',
+            error_code TEXT NOT NULL DEFAULT '',
+            attribute_name TEXT NOT NULL DEFAULT '',
+            error_description TEXT NOT NULL DEFAULT '',
+            hint TEXT NOT NULL DEFAULT '',
+            FOREIGN KEY (session_id) REFERENCES push_session(id)
+        );
+    ",
+    );
+    // Migrations for existing DBs — column adds are idempotent failures if present.
+    let _ = conn.execute(
+        "ALTER TABLE push_error ADD COLUMN attribute_name TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE push_error ADD COLUMN hint TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE push_log ADD COLUMN firstbase_env TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE push_session ADD COLUMN firstbase_env TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE push_session ADD COLUMN api_base TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    let _ = conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_push_log_env     ON push_log(firstbase_env);
+         CREATE INDEX IF NOT EXISTS idx_push_session_env ON push_session(firstbase_env);",
+    );
+}
+
+pub fn push_to_firstbase(
+    settings: &Settings,
+    log: &dyn Fn(&str),
+    uuid_filter: Option<&std::collections::HashSet<String>>,
+    monitor: Option<&push_dashboard::Monitor>,
+) -> anyhow::Result<PushOutcome> {
+    let _lock = push_lock::acquire(settings.firstbase_label(), "push")?;
+    push_run(settings, log, uuid_filter, monitor, None)
+}
+
+/// Continue the interrupted push to the environment of `settings` from its
+/// checkpoint (`push_checkpoint.rs`); None when there is none.
+pub fn resume_push_to_firstbase(
+    settings: &Settings,
+    log: &dyn Fn(&str),
+    monitor: Option<&push_dashboard::Monitor>,
+) -> anyhow::Result<Option<PushOutcome>> {
+    let _lock = push_lock::acquire(settings.firstbase_label(), "push --resume")?;
+    let db_dir = download::app_data_dir().join("db");
+    std::fs::create_dir_all(&db_dir)?;
+    let conn = crate::version_db::open_db(&db_dir.join("version_tracking.db"))?;
+    let env = settings.firstbase_label();
+    let Some(checkpoint) = push_checkpoint::load(&conn, env)? else {
+        return Ok(None);
+    };
+    let sent = checkpoint
+        .chunks
+        .iter()
+        .filter(|c| !c.state.resend())
+        .count();
+    log(&format!(
+        "[Push] Resuming the push of {} to {}: {} device(s), {} of {} recorded chunk(s) taken by GS1",
+        checkpoint.started_at,
+        env,
+        checkpoint.uuids.len(),
+        sent,
+        checkpoint.chunks.len()
+    ));
+    let scope: std::collections::HashSet<String> = checkpoint.uuids.iter().cloned().collect();
+    let outcome = push_run(settings, log, Some(&scope), monitor, Some(checkpoint))?;
+    // Also when nothing was left to push.
+    push_checkpoint::clear(&conn, env)?;
+    Ok(Some(outcome))
+}
+
+fn push_run(
+    settings: &Settings,
+    log: &dyn Fn(&str),
+    uuid_filter: Option<&std::collections::HashSet<String>>,
+    monitor: Option<&push_dashboard::Monitor>,
+    resume: Option<push_checkpoint::Checkpoint>,
+) -> anyhow::Result<PushOutcome> {
+    let api_base = settings.firstbase_api_base();
+    let env_label = settings.firstbase_label().to_uppercase();
+    log(&format!(
+        "Firstbase environment: {} ({})",
+        env_label, api_base
+    ));
+    let firstbase_dir = download::app_data_dir().join("firstbase_json");
+    let processed_dir = firstbase_dir.join("processed");
+    let _ = std::fs::create_dir_all(&processed_dir);
+
+    if let Some(allow) = uuid_filter {
+        log(&format!(
+            "Scoped push: limited to this run's {} UUID(s) — other files in firstbase_json/ are left untouched.",
+            allow.len()
+        ));
+    }
+
+    // Collect pushable files (numeric GTIN). When a uuid_filter is set, skip any
+    // file whose stem (UUID) is not in the allowlist — keeps an SRN-scoped run
+    // from pushing unrelated rejected files left in firstbase_json/.
+    let mut files: Vec<std::path::PathBuf> = Vec::new();
+    if firstbase_dir.exists() {
+        for entry in std::fs::read_dir(&firstbase_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                if let Some(allow) = uuid_filter {
+                    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                    if !allow.contains(stem) {
+                        continue;
+                    }
+                }
+                files.push(path);
+            }
+        }
+    }
+
+    if files.is_empty() {
+        log("No firstbase JSON files to push.");
+        return Ok(PushOutcome {
+            accepted: 0,
+            rejected: 0,
+            transport_failed: 0,
+        });
+    }
+
+    log(&format!("Found {} firstbase JSON files", files.len()));
+
+    let config = crate::config::load_config(&crate::platform_dirs::config_file()).ok();
+    let zones = config.as_ref().map(|c| c.dates).unwrap_or_default();
+    // RequestStatus polling after CreateMany / AddMany, per environment.
+    let polling = config
+        .as_ref()
+        .map(|c| crate::status_poll::resolve(&c.push.poll, settings.firstbase_env.key()))
+        .unwrap_or_default();
+    // CreateMany chunk bounds, request body limit and pacing.
+    let limits = config
+        .as_ref()
+        .map(|c| push_batch::limits(&c.push.batch))
+        .unwrap_or_default();
+    let PushPlan {
+        mut pushable,
+        mut commands,
+        skipped_no_gtin,
+        ..
+    } = plan_push(
+        settings,
+        &files,
+        &zones,
+        &firstbase_dir,
+        &processed_dir,
+        true,
+        log,
+    );
     if pushable.is_empty() {
         return Ok(PushOutcome {
             accepted: 0,
@@ -2857,12 +5519,28 @@ pub fn push_to_firstbase(
             transport_failed: 0,
         });
     }
+    // Resumed: the recorded chunks go first, as they were sent, keyed by
+    // their first item.
+    let mut replayed = std::collections::BTreeMap::new();
+    if let Some(checkpoint) = &resume {
+        let uuids: Vec<&str> = pushable.iter().map(|(_, _, u, _)| u.as_str()).collect();
+        let (order, recorded) = push_checkpoint::replay(&uuids, &checkpoint.chunks);
+        let mut planned: Vec<Option<_>> = pushable
+            .drain(..)
+            .zip(commands.drain(..))
+            .map(Some)
+            .collect();
+        for i in order {
+            if let Some((item, command)) = planned[i].take() {
+                pushable.push(item);
+                commands.push(command);
+            }
+        }
+        replayed = recorded;
+    }
 
     // --- Helper: HTTP POST with JSON ---
-    let http_agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .build()
-        .new_agent();
+    let http_agent = crate::http::agent_for(api_base);
     let http_post = |url: &str, auth: &str, body: &str| -> anyhow::Result<String> {
         let mut req = http_agent
             .post(url)
@@ -2882,23 +5560,9 @@ pub fn push_to_firstbase(
 
     // --- Get token (with retry) ---
     let get_token = |email: &str, password: &str, gln: &str| -> anyhow::Result<String> {
-        let body = serde_json::json!({
-            "UserEmail": email,
-            "Password": password,
-            "Gln": gln,
-        });
         for attempt in 1..=3 {
-            match http_post(
-                &format!("{}/Account/Token", api_base),
-                "",
-                &body.to_string(),
-            ) {
-                Ok(token_raw) => {
-                    let token = token_raw.trim_matches('"').to_string();
-                    if token.len() > 20 {
-                        return Ok(token);
-                    }
-                }
+            match firstbase_token(&http_post, api_base, email, password, gln) {
+                Ok(token) => return Ok(token),
                 Err(e) => {
                     if attempt < 3 {
                         std::thread::sleep(std::time::Duration::from_secs(10));
@@ -2918,15 +5582,57 @@ pub fn push_to_firstbase(
         &settings.provider_gln,
     )?;
     log(&format!("Token obtained ({} chars)", token.len()));
+    log(&format!("RequestStatus polling: {}", polling));
+    log(&format!("CreateMany chunks: {}", limits));
+
+    // Per-chunk progress for `push --resume` (push_checkpoint.rs).
+    let checkpoint_env = settings.firstbase_label();
+    let checkpoint_ts = || chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let db_dir = download::app_data_dir().join("db");
+    let checkpoint = std::fs::create_dir_all(&db_dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| crate::version_db::open_db(&db_dir.join("version_tracking.db")))
+        .and_then(|conn| {
+            if resume.is_none() {
+                if let Some(old) = push_checkpoint::load(&conn, checkpoint_env)? {
+                    log(&format!(
+                        "[Push] WARNING: the interrupted push of {} ({} chunk(s) sent) is discarded — `push --resume` continues one instead",
+                        old.started_at,
+                        old.chunks.len()
+                    ));
+                }
+                let uuids: Vec<&str> = pushable.iter().map(|(_, _, u, _)| u.as_str()).collect();
+                push_checkpoint::begin(&conn, checkpoint_env, &uuids, &checkpoint_ts())?;
+            }
+            Ok(conn)
+        });
+    let checkpoint = match checkpoint {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            log(&format!(
+                "[Push] No checkpoint, this push cannot be resumed: {}",
+                e
+            ));
+            None
+        }
+    };
+    let record = |result: anyhow::Result<()>| {
+        if let Err(e) = result {
+            log(&format!("[Push] Checkpoint write failed: {}", e));
+        }
+    };
 
     let mut total_accepted: u32 = 0;
     let mut total_rejected: u32 = 0;
-    let batch_size = 100;
+    // CreateMany chunk size, adapted to GS1's response times (`push_batch`).
+    let mut sizer = push_batch::BatchSizer::new(limits);
+    // Lowered for the rest of the push when GS1 answers 413 anyway.
+    let mut max_payload_bytes = limits.max_payload_bytes;
 
     // Collect detailed results for HTML log
     let mut accepted_ids: Vec<String> = Vec::new();
     let mut rejected_gtins: std::collections::HashSet<String> = std::collections::HashSet::new();
-    let mut error_details: Vec<(String, String, String, String, String)> = Vec::new(); // (identifier, gtin, error_code, attribute_name, description)
+    let mut error_details: Vec<rejection::Rejection> = Vec::new();
     let mut raw_responses: Vec<String> = Vec::new();
 
     // --- CreateMany in batches ---
@@ -2935,51 +5641,136 @@ pub fn push_to_firstbase(
     // Items whose CreateMany batch never got a validation verdict from GS1
     // (batch-level transport failure / poll timeout) — see PushOutcome.
     let mut transport_failed_items: u32 = 0;
+    if let Some(m) = monitor {
+        m.start(&env_label, pushable.chunks(sizer.size()).map(|b| b.len()));
+    }
 
-    for (bi, batch) in pushable.chunks(batch_size).enumerate() {
-        let batch_start = bi * batch_size + 1;
-        let batch_end = (batch_start + batch.len()).min(total);
+    let mut next_item = 0;
+    for bi in 0.. {
+        if next_item >= total {
+            break;
+        }
+        // A chunk carries one DocumentCommand: it ends where the plan switches.
+        let command = commands[next_item];
+        // A recorded chunk is replayed as it was sent; a new one stops where
+        // the next recorded one starts.
+        let replay = replayed.remove(&next_item);
+        let end = match &replay {
+            Some((end, _)) => *end,
+            None => {
+                let end = chunk_end(
+                    &pushable,
+                    &commands,
+                    next_item,
+                    sizer.size(),
+                    max_payload_bytes,
+                );
+                replayed
+                    .range(next_item..)
+                    .next()
+                    .map_or(end, |(start, _)| end.min(*start))
+            }
+        };
+        let mut batch = &pushable[next_item..end];
+        if bi > 0 && !limits.pause.is_zero() {
+            std::thread::sleep(limits.pause);
+        }
+        if let Some(m) = monitor {
+            // Pausing happens between chunks; the token may expire meanwhile.
+            let resumed = bi > 0 && m.wait_while_paused(log);
+            // Cancelled: the remaining chunks stay in firstbase_json/ for the
+            // next push; what was submitted so far is published and stored.
+            if m.is_cancelled() {
+                log(&format!(
+                    "[Push] Cancelled — {} of {} item(s) not submitted",
+                    total - next_item,
+                    total
+                ));
+                break;
+            }
+            if resumed {
+                token = get_token(
+                    &settings.firstbase_email,
+                    &settings.firstbase_password,
+                    &settings.provider_gln,
+                )?;
+            }
+            // Cut short by a command switch or the payload limit.
+            m.update(bi, |c| {
+                c.items = batch.len();
+                c.state = push_dashboard::ChunkState::Submitting;
+            });
+            m.replan(bi + 1, total - end, sizer.size());
+        }
+        // Responses of this chunk, for the dashboard drill-down.
+        let responses_before = raw_responses.len();
+        let errors_before = error_details.len();
+        let batch_start = next_item + 1;
+        next_item += batch.len();
         log(&format!(
             "[Push] CreateMany batch {}: items {}-{} of {}",
             bi + 1,
             batch_start,
-            batch_end,
+            next_item,
             total
         ));
 
         // Build payload — double-check GTIN filter to prevent batch rejection
-        let items: Vec<serde_json::Value> = batch
-            .iter()
-            .filter_map(|(_, _, _, doc)| {
-                let draft = doc.get("DraftItem")?;
-                let gtin = draft.pointer("/TradeItem/Gtin")?.as_str()?;
-                if gtin.is_empty() || !gtin.chars().all(|c| c.is_ascii_digit()) {
-                    return None;
-                }
-                let mut item = serde_json::json!({
-                    "Identifier": draft.get("Identifier")?,
-                    "TradeItem": draft.get("TradeItem")?,
-                });
-                if let Some(children) = draft.get("CatalogueItemChildItemLink") {
-                    item.as_object_mut()?
-                        .insert("CatalogueItemChildItemLink".into(), children.clone());
-                }
-                Some(item)
+        let create_many = |batch: &[(std::path::PathBuf, String, String, serde_json::Value)]| {
+            serde_json::json!({
+                "DocumentCommand": command,
+                "Items": batch
+                    .iter()
+                    .filter_map(|(_, _, _, doc)| create_many_item(doc))
+                    .collect::<Vec<_>>(),
             })
-            .collect();
-
-        let payload = serde_json::json!({
-            "DocumentCommand": "Add",
-            "Items": items,
-        });
+        };
+        let mut payload = create_many(batch);
 
-        // Submit with retry for 429
+        // Submit with retry for 429; split on 413. A replayed chunk GS1 took
+        // a request for is not sent again.
+        let chunk_started = std::time::Instant::now();
         let mut req_id = String::new();
-        for attempt in 1..=3 {
+        let mut chunk_id = None;
+        let mut recorded_response = None;
+        let mut submit = true;
+        if let Some((_, chunk)) = replay {
+            log(&format!(
+                "  Resumed: {}",
+                match chunk.state {
+                    push_checkpoint::State::Sending =>
+                        "no answer from GS1 recorded before the run stopped — sending again",
+                    push_checkpoint::State::Submitted =>
+                        "submitted before the run stopped — polling its request",
+                    push_checkpoint::State::Done => "verdict recorded before the run stopped",
+                    push_checkpoint::State::Failed =>
+                        "no request from GS1 before the run stopped — sending again",
+                }
+            ));
+            submit = chunk.state.resend();
+            chunk_id = Some(chunk.id);
+            req_id = chunk.request_id;
+            recorded_response = chunk.response;
+        }
+        if let (true, Some(conn)) = (submit, &checkpoint) {
+            let uuids: Vec<&str> = batch.iter().map(|(_, _, u, _)| u.as_str()).collect();
+            match chunk_id {
+                Some(id) => record(push_checkpoint::resize(conn, id, &uuids)),
+                None => {
+                    match push_checkpoint::sending(conn, checkpoint_env, &uuids, &checkpoint_ts()) {
+                        Ok(id) => chunk_id = Some(id),
+                        Err(e) => record(Err(e)),
+                    }
+                }
+            }
+        }
+        let mut attempt = 1;
+        while submit && attempt <= 3 {
+            let body = payload.to_string();
             match http_post(
                 &format!("{}/CatalogueItem/Live/CreateMany", api_base),
                 &token,
-                &payload.to_string(),
+                &body,
             ) {
                 Ok(resp_body) => {
                     if let Ok(body) = serde_json::from_str::<serde_json::Value>(&resp_body) {
@@ -2991,12 +5782,41 @@ pub fn push_to_firstbase(
                     }
                     break;
                 }
+                Err(e) if e.to_string().starts_with("http 413") && batch.len() > 1 => {
+                    // Over GS1's limit despite max_payload_bytes: halve the
+                    // limit and send the chunk's first part; the rest follows.
+                    max_payload_bytes = body.len() / 2;
+                    let start = batch_start - 1;
+                    let keep =
+                        chunk_end(&pushable, &commands, start, batch.len(), max_payload_bytes)
+                            - start;
+                    next_item = start + keep;
+                    batch = &batch[..keep];
+                    payload = create_many(batch);
+                    log(&format!(
+                        "  413 payload too large ({} bytes) — sending items {}-{}, chunks now at most {} bytes",
+                        body.len(),
+                        batch_start,
+                        next_item,
+                        max_payload_bytes
+                    ));
+                    if let Some(m) = monitor {
+                        m.update(bi, |c| c.items = keep);
+                        m.replan(bi + 1, total - next_item, sizer.size());
+                    }
+                    if let (Some(conn), Some(id)) = (&checkpoint, chunk_id) {
+                        let uuids: Vec<&str> =
+                            batch.iter().map(|(_, _, u, _)| u.as_str()).collect();
+                        record(push_checkpoint::resize(conn, id, &uuids));
+                    }
+                }
                 Err(e) if e.to_string().contains("429") => {
                     log(&format!(
                         "  429 rate limited — waiting 60s (attempt {}/3)",
                         attempt
                     ));
                     std::thread::sleep(std::time::Duration::from_secs(60));
+                    attempt += 1;
                 }
                 Err(e) => {
                     let err_str = e.to_string();
@@ -3024,12 +5844,40 @@ pub fn push_to_firstbase(
             }
         }
 
+        if let (true, Some(conn), Some(id)) = (submit, &checkpoint, chunk_id) {
+            record(if req_id.is_empty() {
+                push_checkpoint::failed(conn, id, &checkpoint_ts())
+            } else {
+                push_checkpoint::submitted(conn, id, &req_id, &checkpoint_ts())
+            });
+        }
+
         if req_id.is_empty() {
             log(&format!(
                 "  FAIL: no RequestIdentifier — marking all {} items as rejected",
                 batch.len()
             ));
             total_rejected += batch.len() as u32;
+            if let Some(m) = monitor {
+                m.update(bi, |c| {
+                    c.state = push_dashboard::ChunkState::Failed;
+                    c.errors = batch.len() as u32;
+                    c.response = raw_responses[responses_before..].join("\n");
+                });
+            }
+            adapt_batch_size(
+                &mut sizer,
+                push_batch::ChunkResult {
+                    items: batch.len(),
+                    elapsed: chunk_started.elapsed(),
+                    errors: batch.len() as u32,
+                    unconfirmed: true,
+                },
+                bi + 1,
+                total - next_item,
+                monitor,
+                log,
+            );
             // Mark all GTINs in this batch as rejected
             for (_, _, _, doc) in batch {
                 let gtin = doc
@@ -3043,7 +5891,17 @@ pub fn push_to_firstbase(
             continue;
         }
 
-        log(&format!("  Submitted: {}", req_id));
+        if submit {
+            log(&format!("  Submitted: {}", req_id));
+        } else {
+            log(&format!("  Request: {}", req_id));
+        }
+        if let Some(m) = monitor {
+            m.update(bi, |c| {
+                c.state = push_dashboard::ChunkState::Polling;
+                c.request_id = req_id.clone();
+            });
+        }
 
         // Collect publish items for this batch. They are committed to the global
         // publish list only after the CreateMany batch clears the document-level
@@ -3083,9 +5941,14 @@ pub fn push_to_firstbase(
         // same phantom-success masking as issue #50.
         let mut got_terminal = false;
 
-        // Poll until Done
-        for poll in 1..=24 {
-            std::thread::sleep(std::time::Duration::from_secs(15));
+        // Poll until Done; a replayed chunk may have its verdict recorded.
+        let mut terminal = recorded_response.map(|body| ("Recorded".to_string(), body));
+        let replayed_verdict = terminal.is_some();
+        for (poll, wait) in polling.schedule().take_while(|_| !replayed_verdict) {
+            std::thread::sleep(wait);
+            if let Some(m) = monitor {
+                m.update(bi, |c| c.polls = poll);
+            }
             let poll_body = serde_json::json!({
                 "RequestIdentifier": req_id,
                 "IncludeGs1Response": true,
@@ -3102,217 +5965,7 @@ pub fn push_to_firstbase(
                             .and_then(|v| v.as_str())
                             .unwrap_or("unknown");
                         if status == "Done" || status == "Failed" {
-                            got_terminal = true;
-                            raw_responses
-                                .push(serde_json::to_string_pretty(&body).unwrap_or_default());
-                            let gs1 = body.pointer("/Gs1ResponseMessage/GS1Response");
-                            let mut batch_accepted = 0u32;
-                            let mut batch_rejected = 0u32;
-                            // Status=Failed with NO GS1Response at all (only an
-                            // ErrorDetails string, "Workflow ID: N/A") = GS1 never
-                            // processed the batch. Without this branch the batch
-                            // yields 0 errors and every item is silently counted
-                            // ACCEPTED (issue #50: the Mode-5 log claimed 158/0
-                            // while a 100-item batch was never created).
-                            if status == "Failed"
-                                && gs1
-                                    .and_then(|v| v.as_array())
-                                    .map_or(true, |r| r.is_empty())
-                            {
-                                let details = body
-                                    .get("ErrorDetails")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("(no ErrorDetails)")
-                                    .replace(['\r', '\n'], " ");
-                                batch_transport_failed = true;
-                                batch_rejected += 1;
-                                batch_doc_errors.push((
-                                    "BATCH_FAILED".to_string(),
-                                    details.chars().take(300).collect(),
-                                ));
-                            }
-                            if let Some(responses) = gs1.and_then(|v| v.as_array()) {
-                                for r in responses {
-                                    // Accepted
-                                    if let Some(tr) =
-                                        r.get("TransactionResponse").and_then(|v| v.as_array())
-                                    {
-                                        for t in tr {
-                                            batch_accepted += 1;
-                                            if let Some(ident) = t
-                                                .pointer("/TransactionIdentifier/Value")
-                                                .and_then(|v| v.as_str())
-                                            {
-                                                accepted_ids.push(ident.to_string());
-                                            }
-                                        }
-                                    }
-                                    // Errors from TransactionException
-                                    if let Some(te) =
-                                        r.get("TransactionException").and_then(|v| v.as_array())
-                                    {
-                                        for exc in te {
-                                            let ident = exc
-                                                .pointer("/TransactionIdentifier/Value")
-                                                .and_then(|v| v.as_str())
-                                                .unwrap_or("");
-                                            for ce in exc
-                                                .get("CommandException")
-                                                .and_then(|v| v.as_array())
-                                                .unwrap_or(&vec![])
-                                            {
-                                                for de in ce
-                                                    .get("DocumentException")
-                                                    .and_then(|v| v.as_array())
-                                                    .unwrap_or(&vec![])
-                                                {
-                                                    let doc_id = de
-                                                        .pointer("/DocumentIdentifier/Value")
-                                                        .and_then(|v| v.as_str())
-                                                        .unwrap_or(ident);
-                                                    for ae in de
-                                                        .get("AttributeException")
-                                                        .and_then(|v| v.as_array())
-                                                        .unwrap_or(&vec![])
-                                                    {
-                                                        let gtin = ae
-                                                            .get("Gtin")
-                                                            .and_then(|v| v.as_str())
-                                                            .unwrap_or("");
-                                                        let attr_name = ae
-                                                            .get("AttributeName")
-                                                            .and_then(|v| v.as_str())
-                                                            .unwrap_or("");
-                                                        if !gtin.is_empty() {
-                                                            rejected_gtins.insert(gtin.to_string());
-                                                        }
-                                                        for err in ae
-                                                            .get("GS1Error")
-                                                            .and_then(|v| v.as_array())
-                                                            .unwrap_or(&vec![])
-                                                        {
-                                                            batch_rejected += 1;
-                                                            let code = err
-                                                                .get("ErrorCode")
-                                                                .and_then(|v| v.as_str())
-                                                                .unwrap_or("");
-                                                            let desc = err
-                                                                .get("ErrorDescription")
-                                                                .and_then(|v| v.as_str())
-                                                                .unwrap_or("");
-                                                            error_details.push((
-                                                                doc_id.to_string(),
-                                                                gtin.to_string(),
-                                                                code.to_string(),
-                                                                attr_name.to_string(),
-                                                                desc.chars().take(200).collect(),
-                                                            ));
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    // Errors from GS1Exception
-                                    if let Some(ge) =
-                                        r.get("GS1Exception").and_then(|v| v.as_array())
-                                    {
-                                        for exc in ge {
-                                            if let Some(obj) = exc.as_object() {
-                                                // Document-level errors sit as a
-                                                // direct GS1Error array on the
-                                                // GS1Exception (no CommandException /
-                                                // per-item GTIN). These fail the whole
-                                                // batch document — e.g. a G361 XSD
-                                                // failure from one invalid item.
-                                                for err in obj
-                                                    .get("GS1Error")
-                                                    .and_then(|v| v.as_array())
-                                                    .unwrap_or(&vec![])
-                                                {
-                                                    let code = err
-                                                        .get("ErrorCode")
-                                                        .and_then(|v| v.as_str())
-                                                        .unwrap_or("");
-                                                    let desc = err
-                                                        .get("ErrorDescription")
-                                                        .and_then(|v| v.as_str())
-                                                        .unwrap_or("");
-                                                    batch_rejected += 1;
-                                                    batch_doc_errors.push((
-                                                        code.to_string(),
-                                                        desc.chars().take(200).collect(),
-                                                    ));
-                                                }
-                                                for ce in obj
-                                                    .get("CommandException")
-                                                    .and_then(|v| v.as_array())
-                                                    .unwrap_or(&vec![])
-                                                {
-                                                    for de in ce
-                                                        .get("DocumentException")
-                                                        .and_then(|v| v.as_array())
-                                                        .unwrap_or(&vec![])
-                                                    {
-                                                        let doc_id = de
-                                                            .pointer("/DocumentIdentifier/Value")
-                                                            .and_then(|v| v.as_str())
-                                                            .unwrap_or("");
-                                                        for ae in de
-                                                            .get("AttributeException")
-                                                            .and_then(|v| v.as_array())
-                                                            .unwrap_or(&vec![])
-                                                        {
-                                                            let gtin = ae
-                                                                .get("Gtin")
-                                                                .and_then(|v| v.as_str())
-                                                                .unwrap_or("");
-                                                            let attr_name = ae
-                                                                .get("AttributeName")
-                                                                .and_then(|v| v.as_str())
-                                                                .unwrap_or("");
-                                                            if !gtin.is_empty() {
-                                                                rejected_gtins
-                                                                    .insert(gtin.to_string());
-                                                            }
-                                                            for err in ae
-                                                                .get("GS1Error")
-                                                                .and_then(|v| v.as_array())
-                                                                .unwrap_or(&vec![])
-                                                            {
-                                                                batch_rejected += 1;
-                                                                let code = err
-                                                                    .get("ErrorCode")
-                                                                    .and_then(|v| v.as_str())
-                                                                    .unwrap_or("");
-                                                                let desc = err
-                                                                    .get("ErrorDescription")
-                                                                    .and_then(|v| v.as_str())
-                                                                    .unwrap_or("");
-                                                                error_details.push((
-                                                                    doc_id.to_string(),
-                                                                    gtin.to_string(),
-                                                                    code.to_string(),
-                                                                    attr_name.to_string(),
-                                                                    desc.chars()
-                                                                        .take(200)
-                                                                        .collect(),
-                                                                ));
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            total_accepted += batch_accepted;
-                            total_rejected += batch_rejected;
-                            log(&format!(
-                                "  Poll {}: {} ({} accepted, {} errors)",
-                                poll, status, batch_accepted, batch_rejected
-                            ));
+                            terminal = Some((format!("Poll {}", poll), body));
                             break;
                         }
                         if poll % 4 == 0 {
@@ -3326,8 +5979,74 @@ pub fn push_to_firstbase(
                 }
             }
         }
+        if let Some((source, body)) = &terminal {
+            got_terminal = true;
+            let status = body
+                .get("Status")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            raw_responses.push(serde_json::to_string_pretty(body).unwrap_or_default());
+            let gs1 = body.pointer("/Gs1ResponseMessage/GS1Response");
+            let mut batch_accepted = 0u32;
+            let mut batch_rejected = 0u32;
+            // Status=Failed with NO GS1Response at all (only an
+            // ErrorDetails string, "Workflow ID: N/A") = GS1 never
+            // processed the batch. Without this branch the batch
+            // yields 0 errors and every item is silently counted
+            // ACCEPTED (issue #50: the Mode-5 log claimed 158/0
+            // while a 100-item batch was never created).
+            if status == "Failed" && gs1.and_then(|v| v.as_array()).is_none_or(|r| r.is_empty()) {
+                let details = body
+                    .get("ErrorDetails")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("(no ErrorDetails)")
+                    .replace(['\r', '\n'], " ");
+                batch_transport_failed = true;
+                batch_rejected += 1;
+                batch_doc_errors.push((
+                    "BATCH_FAILED".to_string(),
+                    details.chars().take(300).collect(),
+                ));
+            }
+            if let Some(gs1) = gs1 {
+                let parsed = rejection::parse(gs1);
+                batch_accepted += parsed.accepted;
+                accepted_ids.extend(parsed.accepted_ids);
+                batch_rejected += (parsed.rejections.len() + parsed.document_errors.len()) as u32;
+                rejected_gtins.extend(
+                    parsed
+                        .rejections
+                        .iter()
+                        .filter(|r| !r.gtin.is_empty())
+                        .map(|r| r.gtin.clone()),
+                );
+                error_details.extend(parsed.rejections);
+                batch_doc_errors.extend(parsed.document_errors);
+            }
+            total_accepted += batch_accepted;
+            total_rejected += batch_rejected;
+            if let Some(m) = monitor {
+                m.update(bi, |c| {
+                    c.accepted = batch_accepted;
+                    c.errors = batch_rejected;
+                });
+            }
+            log(&format!(
+                "  {}: {} ({} accepted, {} errors)",
+                source, status, batch_accepted, batch_rejected
+            ));
+            if let (false, Some(conn), Some(id)) = (replayed_verdict, &checkpoint, chunk_id) {
+                record(push_checkpoint::done(
+                    conn,
+                    id,
+                    &req_id,
+                    body,
+                    &checkpoint_ts(),
+                ));
+            }
+        }
 
-        // Poll loop ended without a terminal Done/Failed status (24-poll
+        // Poll loop ended without a terminal Done/Failed status (`[push.poll]`
         // timeout or a poll network error): the batch outcome is unconfirmed.
         // Treat as a transport failure — keep the items for retry instead of
         // silently counting them accepted.
@@ -3340,6 +6059,16 @@ pub fn push_to_firstbase(
             ));
         }
 
+        // Per-item GS1 errors of this chunk, one line each for the log console,
+        // with the field-level hint under it.
+        for r in &error_details[errors_before..] {
+            log(&format!(
+                "  REJECTED {} {} {}: {}",
+                r.gtin, r.code, r.attribute, r.description
+            ));
+            log(&format!("    → {}", r.hint()));
+        }
+
         // A document/XSD-level failure (G361 + SCHEMA) fails the ENTIRE CreateMany
         // batch document — none of its items were created Live. Mark them all
         // rejected so they are kept in firstbase_json/ for retry and reported
@@ -3347,6 +6076,23 @@ pub fn push_to_firstbase(
         // and do not publish them via AddMany. (Fixes the masking where a failed
         // CreateMany batch — e.g. the v1.0.58 legacy-MDD globalModelDescription
         // schema error — still reported every item as accepted.)
+        if let Some(m) = monitor {
+            m.update(bi, |c| {
+                c.response = raw_responses[responses_before..].join("\n");
+                c.state = if batch_doc_errors.is_empty() {
+                    push_dashboard::ChunkState::Done
+                } else {
+                    // None of the chunk's items were created Live.
+                    c.accepted = 0;
+                    c.errors = c.errors.max(1);
+                    if batch_transport_failed {
+                        push_dashboard::ChunkState::Failed
+                    } else {
+                        push_dashboard::ChunkState::Rejected
+                    }
+                };
+            });
+        }
         if batch_doc_errors.is_empty() {
             all_publish_items.extend(batch_publish_items);
         } else {
@@ -3383,23 +6129,41 @@ pub fn push_to_firstbase(
                 // Attribute each batch-level error to every item in the batch so
                 // the per-device push_log error_code and push_error rows are populated.
                 for (code, desc) in &batch_doc_errors {
-                    error_details.push((
-                        ident.to_string(),
-                        gtin.to_string(),
-                        code.clone(),
-                        if batch_transport_failed {
+                    error_details.push(rejection::Rejection {
+                        document: ident.to_string(),
+                        gtin: gtin.to_string(),
+                        code: code.clone(),
+                        attribute: if batch_transport_failed {
                             "(batch transport)".to_string()
                         } else {
                             "(document-level)".to_string()
                         },
-                        desc.clone(),
-                    ));
+                        description: desc.clone(),
+                    });
                 }
             }
         }
 
-        // Throttle between batches
-        std::thread::sleep(std::time::Duration::from_secs(8));
+        // A recorded verdict says nothing about GS1's current pace.
+        if replayed_verdict {
+            continue;
+        }
+        adapt_batch_size(
+            &mut sizer,
+            push_batch::ChunkResult {
+                items: batch.len(),
+                elapsed: chunk_started.elapsed(),
+                errors: error_details.len().saturating_sub(errors_before) as u32,
+                unconfirmed: batch_transport_failed,
+            },
+            bi + 1,
+            total - next_item,
+            monitor,
+            log,
+        );
+    }
+    if let Some(m) = monitor {
+        m.finish();
     }
 
     // --- AddMany: publish to recipient ---
@@ -3417,7 +6181,11 @@ pub fn push_to_firstbase(
             settings.publish_to_gln
         ));
 
-        for (pi, pub_batch) in all_publish_items.chunks(batch_size).enumerate() {
+        // AddMany publish items are small; they keep the fixed chunk size.
+        for (pi, pub_batch) in all_publish_items.chunks(limits.start).enumerate() {
+            if pi > 0 && !limits.pause.is_zero() {
+                std::thread::sleep(limits.pause);
+            }
             let payload = serde_json::json!({ "Items": pub_batch });
 
             for attempt in 1..=3 {
@@ -3436,8 +6204,8 @@ pub fn push_to_firstbase(
                         if !pub_req_id.is_empty() {
                             log(&format!("  AddMany batch {}: {}", pi + 1, pub_req_id));
                             // Poll AddMany
-                            for poll in 1..=24 {
-                                std::thread::sleep(std::time::Duration::from_secs(15));
+                            for (poll, wait) in polling.schedule() {
+                                std::thread::sleep(wait);
                                 let poll_body = serde_json::json!({
                                     "RequestIdentifier": pub_req_id,
                                     "IncludeGs1Response": true,
@@ -3477,7 +6245,6 @@ pub fn push_to_firstbase(
                     }
                 }
             }
-            std::thread::sleep(std::time::Duration::from_secs(8));
         }
     }
 
@@ -3496,66 +6263,11 @@ pub fn push_to_firstbase(
             });
         }
     };
-    let _ = conn.execute_batch(
-        "
-        CREATE TABLE IF NOT EXISTS push_log (
-            id INTEGER PRIMARY KEY AUTOINCREMENT, uuid TEXT NOT NULL, gtin TEXT NOT NULL DEFAULT '',
-            pushed_at TEXT NOT NULL, request_id TEXT, status TEXT NOT NULL,
-            error_code TEXT, error_msg TEXT, publish_gln TEXT,
-            firstbase_env TEXT NOT NULL DEFAULT ''
-        );
-        CREATE TABLE IF NOT EXISTS push_session (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            session_ts TEXT NOT NULL,
-            version TEXT NOT NULL,
-            publish_gln TEXT NOT NULL,
-            total_pushable INTEGER NOT NULL DEFAULT 0,
-            skipped_no_gtin INTEGER NOT NULL DEFAULT 0,
-            total_accepted INTEGER NOT NULL DEFAULT 0,
-            total_rejected INTEGER NOT NULL DEFAULT 0,
-            firstbase_env TEXT NOT NULL DEFAULT '',
-            api_base TEXT NOT NULL DEFAULT ''
-        );
-        CREATE TABLE IF NOT EXISTS push_error (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            session_id INTEGER NOT NULL,
-            uuid TEXT NOT NULL DEFAULT '',
-            gtin TEXT NOT NULL DEFAULT '',
-            error_code TEXT NOT NULL DEFAULT '',
-            attribute_name TEXT NOT NULL DEFAULT '',
-            error_description TEXT NOT NULL DEFAULT '',
-            FOREIGN KEY (session_id) REFERENCES push_session(id)
-        );
-    ",
-    );
-    // Migrations for existing DBs — column adds are idempotent failures if present.
-    let _ = conn.execute(
-        "ALTER TABLE push_error ADD COLUMN attribute_name TEXT NOT NULL DEFAULT ''",
-        [],
-    );
-    let _ = conn.execute(
-        "ALTER TABLE push_log ADD COLUMN firstbase_env TEXT NOT NULL DEFAULT ''",
-        [],
-    );
-    let _ = conn.execute(
-        "ALTER TABLE push_session ADD COLUMN firstbase_env TEXT NOT NULL DEFAULT ''",
-        [],
-    );
-    let _ = conn.execute(
-        "ALTER TABLE push_session ADD COLUMN api_base TEXT NOT NULL DEFAULT ''",
-        [],
-    );
-    let _ = conn.execute_batch(
-        "CREATE INDEX IF NOT EXISTS idx_push_log_env     ON push_log(firstbase_env);
-         CREATE INDEX IF NOT EXISTS idx_push_session_env ON push_session(firstbase_env);",
-    );
+    ensure_push_log_schema(&conn);
 
     let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
-    let env_label = match settings.firstbase_env {
-        FirstbaseEnv::Test => "Test",
-        FirstbaseEnv::Production => "Production",
-    };
-    let api_base_str = settings.firstbase_env.api_base();
+    let env_label = settings.firstbase_label();
+    let api_base_str = settings.firstbase_api_base();
 
     // Insert push session (accepted/rejected updated after file move)
     let _ = conn.execute(
@@ -3579,18 +6291,38 @@ pub fn push_to_firstbase(
         })
         .collect();
 
-    // Insert error details with UUID and attribute_name
-    for (_, gtin, code, attr_name, desc) in &error_details {
-        let uuid = gtin_to_uuid.get(gtin).cloned().unwrap_or_default();
+    // Insert error details with UUID, attribute_name and the field-level hint
+    for r in &error_details {
+        let uuid = gtin_to_uuid.get(&r.gtin).cloned().unwrap_or_default();
         let _ = conn.execute(
-            "INSERT INTO push_error (session_id, uuid, gtin, error_code, attribute_name, error_description) VALUES (?1,?2,?3,?4,?5,?6)",
-            rusqlite::params![session_id, uuid, gtin, code, attr_name, desc],
+            "INSERT INTO push_error (session_id, uuid, gtin, error_code, attribute_name, error_description, hint) VALUES (?1,?2,?3,?4,?5,?6,?7)",
+            rusqlite::params![session_id, uuid, r.gtin, r.code, r.attribute, r.description, r.hint()],
         );
     }
 
-    // Insert per-item push_log with ACCEPTED/REJECTED + error codes
+    // Insert per-item push_log with ACCEPTED/REJECTED + error codes, and
+    // archive the raw payloads each pushed document was built from.
     let mut logged = 0;
-    for (_, _, uuid, doc) in &pushable {
+    let mut archived = 0;
+    let data_dir = download::app_data_dir();
+    // Shared store ([storage] backend = "postgres"): the push log is mirrored
+    // there so every machine sees every push.
+    let config_path = crate::platform_dirs::config_file();
+    let mut shared = match crate::config::load_config(&config_path)
+        .and_then(|c| crate::storage::open_shared(&c.storage))
+    {
+        Ok(store) => store,
+        Err(e) => {
+            log(&format!(
+                "[Push] Shared store unavailable, push log kept locally only: {}",
+                e
+            ));
+            None
+        }
+    };
+    let mut mirrored = 0;
+    let (mut queued, mut dequeued) = (0, 0);
+    for (path, _, uuid, doc) in &pushable {
         let gtin = doc
             .pointer("/DraftItem/TradeItem/Gtin")
             .and_then(|v| v.as_str())
@@ -3600,12 +6332,15 @@ pub fn push_to_firstbase(
         } else {
             "ACCEPTED"
         };
-        // Collect error codes for this GTIN
-        let error_codes: Vec<&str> = error_details
+        // Collect error codes and hints for this GTIN
+        let rejections: Vec<&rejection::Rejection> =
+            error_details.iter().filter(|r| r.gtin == gtin).collect();
+        let error_codes: Vec<&str> = rejections.iter().map(|r| r.code.as_str()).collect();
+        let hints = rejections
             .iter()
-            .filter(|(_, g, _, _, _)| g == gtin)
-            .map(|(_, _, c, _, _)| c.as_str())
-            .collect();
+            .map(|r| r.hint())
+            .collect::<Vec<_>>()
+            .join(" | ");
         let error_code_str = if error_codes.is_empty() {
             String::new()
         } else {
@@ -3614,16 +6349,89 @@ pub fn push_to_firstbase(
             dedup.dedup();
             dedup.join(",")
         };
-        let _ = conn.execute(
-            "INSERT INTO push_log (uuid,gtin,pushed_at,status,error_code,publish_gln,firstbase_env) VALUES (?1,?2,?3,?4,?5,?6,?7)",
-            rusqlite::params![uuid, gtin, now, status, error_code_str, settings.publish_to_gln, env_label],
+        let inserted = conn.execute(
+            "INSERT INTO push_log (uuid,gtin,pushed_at,status,error_code,error_msg,publish_gln,firstbase_env) VALUES (?1,?2,?3,?4,?5,?6,?7,?8)",
+            rusqlite::params![uuid, gtin, now, status, error_code_str, hints, settings.publish_to_gln, env_label],
         );
+        let push_log_id = conn.last_insert_rowid();
         logged += 1;
+        // Rejected devices wait in the retry queue (`push --retry`) until accepted.
+        let queue_result = if status == "REJECTED" {
+            crate::retry_queue::enqueue(
+                &conn,
+                env_label,
+                uuid,
+                gtin,
+                &error_codes,
+                &doc.to_string(),
+                &now,
+            )
+            .map(|()| queued += 1)
+        } else {
+            crate::retry_queue::remove(&conn, env_label, uuid)
+                .map(|removed| dequeued += removed as u32)
+        };
+        if let Err(e) = queue_result {
+            log(&format!(
+                "[Push] Retry queue write failed for {}: {}",
+                uuid, e
+            ));
+        }
+        if inserted.is_ok() {
+            let payloads = payload_archive::collect(&data_dir, uuid, path, &doc.to_string());
+            if payload_archive::store(&conn, push_log_id, uuid, &payloads).is_ok() {
+                archived += 1;
+            }
+        }
+        if let Some(store) = shared.as_mut() {
+            let errors: Vec<String> = rejections
+                .iter()
+                .map(|r| {
+                    if r.attribute.is_empty() {
+                        format!("{}: {}", r.code, r.description)
+                    } else {
+                        format!("{} [{}]: {}", r.code, r.attribute, r.description)
+                    }
+                })
+                .collect();
+            let row = crate::push_history::PushLogRow {
+                uuid: uuid.clone(),
+                gtin: gtin.to_string(),
+                pushed_at: now.clone(),
+                status: status.to_string(),
+                error_code: error_code_str,
+                error_msg: hints,
+                publish_gln: settings.publish_to_gln.clone(),
+                firstbase_env: env_label.to_string(),
+                session_id,
+                errors: errors.join(" | "),
+                ..Default::default()
+            };
+            match store.log_push(&row) {
+                Ok(()) => mirrored += 1,
+                Err(e) => {
+                    log(&format!("[Push] Shared store write failed: {}", e));
+                    shared = None;
+                }
+            }
+        }
+    }
+    if mirrored > 0 {
+        log(&format!(
+            "[Push] Mirrored {} push log rows to the shared store",
+            mirrored
+        ));
     }
     log(&format!(
-        "[Push] Logged {} items to push_log DB (session {})",
-        logged, session_id
+        "[Push] Logged {} items to push_log DB (session {}), raw payloads archived for {}",
+        logged, session_id, archived
     ));
+    if queued > 0 || dequeued > 0 {
+        log(&format!(
+            "[Push] Retry queue: {} rejected item(s) queued, {} cleared after acceptance (push --retry)",
+            queued, dequeued
+        ));
+    }
 
     // Move only ACCEPTED files to processed/ — rejected files stay for retry
     let mut moved = 0;
@@ -3648,6 +6456,10 @@ pub fn push_to_firstbase(
         "[Push] Moved {} accepted files to processed/, {} rejected files kept for retry",
         moved, kept
     ));
+    // Everything of this push is logged: nothing left to resume.
+    if let Some(conn) = &checkpoint {
+        record(push_checkpoint::clear(conn, checkpoint_env));
+    }
     log(&format!(
         "[Push] API response: {} error entries from {} rejected devices",
         total_rejected,
@@ -3693,6 +6505,114 @@ pub fn push_to_firstbase(
     })
 }
 
+/// Verdict counts of `dry_run_firstbase`.
+#[derive(Debug, Default)]
+pub struct DryRun {
+    pub add: u32,
+    pub change: u32,
+    pub invalid: u32,
+    pub skipped: u32,
+}
+
+/// A firstbase push up to the API call: `files` are planned exactly as
+/// `push_to_firstbase` would (repairs, overrides, dedup, Add /
+/// Change), but no document is written or moved and nothing is
+/// sent. Logs a verdict per device — would Add / would Change,
+/// invalid with the offline validation findings, would skip with the reason
+/// — and writes each CreateMany payload to `out_dir` as `batch_NNN.json` for
+/// review. Chunks are cut at the adaptive sizer's starting size and the
+/// payload limit of `[push.batch]`; a real push may resize them after the
+/// first verdict.
+pub fn dry_run_firstbase(
+    settings: &Settings,
+    files: &[std::path::PathBuf],
+    out_dir: &std::path::Path,
+    log: &dyn Fn(&str),
+) -> anyhow::Result<DryRun> {
+    let firstbase_dir = download::app_data_dir().join("firstbase_json");
+    let processed_dir = firstbase_dir.join("processed");
+    let config = crate::config::load_config(&crate::platform_dirs::config_file()).ok();
+    let zones = config.as_ref().map(|c| c.dates).unwrap_or_default();
+    let limits = config
+        .as_ref()
+        .map(|c| push_batch::limits(&c.push.batch))
+        .unwrap_or_default();
+    let plan = plan_push(
+        settings,
+        files,
+        &zones,
+        &firstbase_dir,
+        &processed_dir,
+        false,
+        log,
+    );
+    std::fs::create_dir_all(out_dir)?;
+    let mut outcome = DryRun::default();
+    let total = plan.pushable.len();
+    let mut start = 0;
+    for bi in 1.. {
+        if start >= total {
+            break;
+        }
+        let command = plan.commands[start];
+        let end = chunk_end(
+            &plan.pushable,
+            &plan.commands,
+            start,
+            limits.start,
+            limits.max_payload_bytes,
+        );
+        let batch = &plan.pushable[start..end];
+        let payload = serde_json::json!({
+            "DocumentCommand": command,
+            "Items": batch
+                .iter()
+                .filter_map(|(_, _, _, doc)| create_many_item(doc))
+                .collect::<Vec<_>>(),
+        });
+        let path = out_dir.join(format!("batch_{:03}.json", bi));
+        crate::write_json(&path, &payload)?;
+        log(&format!(
+            "[Dry run] CreateMany batch {}: items {}-{} of {} ({}) → {}",
+            bi,
+            start + 1,
+            end,
+            total,
+            command,
+            path.display()
+        ));
+        for (_, _, uuid, doc) in batch {
+            let gtin = doc
+                .pointer("/DraftItem/TradeItem/Gtin")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let findings = crate::report::findings(doc);
+            if !findings.is_empty() {
+                outcome.invalid += 1;
+                log(&format!(
+                    "  INVALID {} {}: {}",
+                    gtin,
+                    uuid,
+                    findings.join("; ")
+                ));
+                continue;
+            }
+            if command == crate::discontinued::ADD {
+                outcome.add += 1;
+            } else {
+                outcome.change += 1;
+            }
+            log(&format!("  would {} {} {}", command, gtin, uuid));
+        }
+        start = end;
+    }
+    for (uuid, reason) in &plan.skipped {
+        outcome.skipped += 1;
+        log(&format!("  would skip {}: {}", uuid, reason));
+    }
+    Ok(outcome)
+}
+
 /// Load the embedded app icon as an `egui::IconData`.
 /// Push pre-built Swissdamed JSON files to the Swissdamed M2M API.
 /// If `uuid_filter` is Some, only push files matching those UUIDs.
@@ -3767,28 +6687,14 @@ fn push_to_swissdamed(
     log(&format!("{} files pushable", pushable.len()));
 
     // --- OAuth2 token ---
-    let http_agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .timeout_global(Some(std::time::Duration::from_secs(30)))
-        .build()
-        .new_agent();
+    let http_agent = crate::http::agent_for(&base_url);
 
     let get_token = || -> anyhow::Result<String> {
-        let token_url = "https://3a5c95df-c59f-418a-96fc-b8531bf24be8.ciamlogin.com/3a5c95df-c59f-418a-96fc-b8531bf24be8/oauth2/v2.0/token";
-        let scope = "8d64e26d-ea71-4ab8-90d6-2acd795eb668/.default";
-        let form_body = format!(
-            "grant_type=client_credentials&client_id={}&client_secret={}&scope={}",
-            settings.swissdamed_client_id, settings.swissdamed_client_secret, scope
-        );
-        let mut resp = http_agent
-            .post(token_url)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .send(form_body.as_bytes())?;
-        let body: serde_json::Value = serde_json::from_str(&resp.body_mut().read_to_string()?)?;
-        body.get("access_token")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .ok_or_else(|| anyhow::anyhow!("No access_token: {}", body))
+        swissdamed_token(
+            &http_agent,
+            &settings.swissdamed_client_id,
+            &settings.swissdamed_client_secret,
+        )
     };
 
     log("[Push] Getting OAuth2 token...");
@@ -4364,9 +7270,9 @@ fn generate_push_html(
     // Full error details from push_error (first 500)
     {
         let mut stmt = conn.prepare(
-            "SELECT uuid, gtin, error_code, attribute_name, error_description FROM push_error WHERE session_id=?1 LIMIT 500"
+            "SELECT uuid, gtin, error_code, attribute_name, error_description, hint FROM push_error WHERE session_id=?1 LIMIT 500"
         )?;
-        let rows: Vec<(String, String, String, String, String)> = stmt
+        let rows: Vec<(String, String, String, String, String, String)> = stmt
             .query_map(rusqlite::params![session_id], |row| {
                 Ok((
                     row.get(0)?,
@@ -4374,6 +7280,7 @@ fn generate_push_html(
                     row.get(2)?,
                     row.get(3)?,
                     row.get(4)?,
+                    row.get(5)?,
                 ))
             })?
             .collect::<rusqlite::Result<_>>()?;
@@ -4385,21 +7292,22 @@ fn generate_push_html(
         )?;
 
         if !rows.is_empty() {
-            html.push_str(&format!("<h2 class='err'>Error Details ({} total)</h2><table><tr><th>#</th><th>UUID</th><th>GTIN</th><th>Error Code</th><th>Attribute</th><th>Description</th></tr>", total_errors));
-            for (i, (uuid, gtin, code, attr, desc)) in rows.iter().enumerate() {
+            html.push_str(&format!("<h2 class='err'>Error Details ({} total)</h2><table><tr><th>#</th><th>UUID</th><th>GTIN</th><th>Error Code</th><th>Attribute</th><th>Description</th><th>Hint</th></tr>", total_errors));
+            for (i, (uuid, gtin, code, attr, desc, hint)) in rows.iter().enumerate() {
                 html.push_str(&format!(
-                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
                     i + 1,
                     uuid,
                     gtin,
                     code,
                     attr,
-                    desc
+                    desc,
+                    hint
                 ));
             }
             if total_errors > 500 {
                 html.push_str(&format!(
-                    "<tr><td colspan='6'>... and {} more</td></tr>",
+                    "<tr><td colspan='7'>... and {} more</td></tr>",
                     total_errors - 500
                 ));
             }
@@ -4473,6 +7381,42 @@ pub fn run_gui() -> eframe::Result {
     )
 }
 
+/// Run pipeline `mode` without a window (CLI `run`): the GUI's saved settings
+/// with the same environment overrides, `srns` instead of the saved SRNs when
+/// given, log lines on stdout. Returns the summary; a failed run is an error.
+pub fn run_headless(mode: u8, srns: Option<String>, uuids: Vec<String>) -> anyhow::Result<String> {
+    let mut settings = Settings::load();
+    settings.apply_env();
+    if let Some(srns) = srns {
+        settings.srns = srns;
+    }
+    let (tx, rx) = mpsc::channel();
+    let worker = thread::spawn(move || {
+        run_pipeline(
+            settings,
+            tx,
+            egui::Context::default(),
+            mode,
+            push_dashboard::Monitor::default(),
+            uuids,
+        )
+    });
+    let mut result = Err(anyhow::anyhow!("Pipeline ended without a result"));
+    for msg in rx {
+        match msg {
+            WorkerMsg::Log(line) => println!("{}", line),
+            WorkerMsg::Progress { step, detail } => eprintln!("{}: {}", step, detail),
+            WorkerMsg::Done { ok: true, summary } => result = Ok(summary),
+            WorkerMsg::Done { ok: false, summary } => result = Err(anyhow::anyhow!(summary)),
+            WorkerMsg::QrCode(_) | WorkerMsg::DownloadStatus { .. } => {}
+        }
+    }
+    if worker.join().is_err() {
+        anyhow::bail!("Pipeline panicked");
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::doc_is_regulation;
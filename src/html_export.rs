@@ -0,0 +1,229 @@
+//! Static HTML device catalogue (`export --html`).
+//! Writes a self-contained site — `index.html` with a client-side search box
+//! plus `devices/<uuid>.html` per device listing every mapped attribute — so
+//! stakeholders without the app can review the converted firstbase data.
+//! No external assets: styles and the search script are inlined.
+//! Device pages name the source record (provenance sidecar) when there is one.
+
+use std::path::Path;
+
+use anyhow::Context;
+use rayon::prelude::*;
+
+use crate::provenance::Provenance;
+use crate::report::{array_at, base_unit, firstbase_docs, risk_class, str_at};
+
+const STYLE: &str =
+    "body{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;margin:20px;color:#222}\
+h1{font-size:20px}h2{font-size:16px;margin-top:24px}\
+table{border-collapse:collapse;width:100%;margin:10px 0;font-size:13px}\
+th,td{border:1px solid #ccc;padding:5px 8px;text-align:left;vertical-align:top}\
+th{background:#f0f0f0;position:sticky;top:0}\
+td.path{font-family:monospace;color:#555;white-space:nowrap}\
+#q{width:100%;padding:8px;font-size:14px;margin:8px 0;box-sizing:border-box}\
+.muted{color:#777;font-size:12px}";
+
+/// One row of the catalogue index.
+struct Entry {
+    uuid: String,
+    gtin: String,
+    name: String,
+    manufacturer: String,
+    risk_class: String,
+    regulation: String,
+    status: String,
+    /// `file[:line], downloaded <timestamp>` of the source record, or "".
+    source: String,
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Flatten a JSON value into `(path, value)` rows, e.g.
+/// `TradeItemDescriptionModule / … / TradeItemDescription[0] / Value`.
+fn flatten(value: &serde_json::Value, path: &str, out: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                let p = if path.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{} / {}", path, k)
+                };
+                flatten(v, &p, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten(v, &format!("{}[{}]", path, i), out);
+            }
+        }
+        serde_json::Value::Null => {}
+        serde_json::Value::String(s) => out.push((path.to_string(), s.clone())),
+        other => out.push((path.to_string(), other.to_string())),
+    }
+}
+
+fn entry_for(doc: &serde_json::Value, uuid: &str, provenance: Option<&Provenance>) -> Entry {
+    let top = doc
+        .pointer("/DraftItem/TradeItem")
+        .unwrap_or(&serde_json::Value::Null);
+    let base = base_unit(doc);
+    let name = str_at(
+        base,
+        "/TradeItemDescriptionModule/TradeItemDescriptionInformation/TradeItemDescription/0/Value",
+    );
+    let manufacturer = array_at(top, "/TradeItemContactInformation")
+        .iter()
+        .find(|c| str_at(c, "/ContactTypeCode/Value") == "EMA")
+        .map(|c| str_at(c, "/ContactName"))
+        .unwrap_or("");
+    let risk_class = risk_class(base);
+    Entry {
+        uuid: uuid.to_string(),
+        gtin: str_at(top, "/Gtin").to_string(),
+        name: name.to_string(),
+        manufacturer: manufacturer.to_string(),
+        risk_class: risk_class.to_string(),
+        regulation: str_at(
+            base,
+            "/RegulatedTradeItemModule/RegulatoryInformation/0/RegulatoryAct",
+        )
+        .to_string(),
+        status: str_at(
+            base,
+            "/MedicalDeviceTradeItemModule/MedicalDeviceInformation/EUMedicalDeviceStatusCode/Value",
+        )
+        .to_string(),
+        source: provenance
+            .map(|p| format!("{}, downloaded {}", p.location(), p.downloaded_at))
+            .unwrap_or_default(),
+    }
+}
+
+fn device_page(entry: &Entry, doc: &serde_json::Value) -> String {
+    let mut rows = Vec::new();
+    flatten(
+        doc.get("DraftItem").unwrap_or(&serde_json::Value::Null),
+        "",
+        &mut rows,
+    );
+    let title = if entry.name.is_empty() {
+        entry.gtin.clone()
+    } else {
+        entry.name.clone()
+    };
+    let mut html = format!(
+        "<!DOCTYPE html><html><head><meta charset='utf-8'><title>{title}</title>\
+         <style>{STYLE}</style></head><body>\
+         <p><a href='../index.html'>&larr; Catalogue</a></p>\
+         <h1>{title}</h1>\
+         <p><b>GTIN:</b> {gtin} &nbsp; <b>UUID:</b> {uuid}<br>\
+         <b>Manufacturer:</b> {mfr} &nbsp; <b>Risk class:</b> {risk} &nbsp; \
+         <b>Regulation:</b> {reg} &nbsp; <b>Status:</b> {status}</p>{source}\
+         <h2>Mapped attributes ({count})</h2>\
+         <table><tr><th>Attribute</th><th>Value</th></tr>",
+        title = escape(&title),
+        gtin = escape(&entry.gtin),
+        uuid = escape(&entry.uuid),
+        mfr = escape(&entry.manufacturer),
+        risk = escape(&entry.risk_class),
+        reg = escape(&entry.regulation),
+        status = escape(&entry.status),
+        source = if entry.source.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<p class='muted'><b>Source:</b> {}</p>",
+                escape(&entry.source)
+            )
+        },
+        count = rows.len(),
+    );
+    for (path, value) in &rows {
+        html.push_str(&format!(
+            "<tr><td class='path'>{}</td><td>{}</td></tr>",
+            escape(path),
+            escape(value)
+        ));
+    }
+    html.push_str("</table></body></html>");
+    html
+}
+
+fn index_page(entries: &[Entry]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html><html><head><meta charset='utf-8'><title>Device Catalogue</title>\
+         <style>{STYLE}</style></head><body>\
+         <h1>Device Catalogue ({count} devices)</h1>\
+         <p class='muted'>Generated {now} by eudamed2firstbase v{version}</p>\
+         <input id='q' type='search' placeholder='Search GTIN, name, manufacturer, risk class…' autofocus>\
+         <table id='t'><tr><th>GTIN</th><th>Trade name</th><th>Manufacturer</th>\
+         <th>Risk class</th><th>Regulation</th><th>Status</th></tr>",
+        count = entries.len(),
+        now = chrono::Local::now().format("%d.%m.%Y %H:%M"),
+        version = env!("CARGO_PKG_VERSION"),
+    );
+    for e in entries {
+        html.push_str(&format!(
+            "<tr><td><a href='devices/{uuid}.html'>{gtin}</a></td><td>{name}</td>\
+             <td>{mfr}</td><td>{risk}</td><td>{reg}</td><td>{status}</td></tr>",
+            uuid = escape(&e.uuid),
+            gtin = escape(&e.gtin),
+            name = escape(&e.name),
+            mfr = escape(&e.manufacturer),
+            risk = escape(&e.risk_class),
+            reg = escape(&e.regulation),
+            status = escape(&e.status),
+        ));
+    }
+    html.push_str(
+        "</table><script>\
+         const q=document.getElementById('q');\
+         const rows=Array.from(document.querySelectorAll('#t tr')).slice(1);\
+         q.addEventListener('input',()=>{const s=q.value.toLowerCase();\
+         for(const r of rows){r.style.display=r.textContent.toLowerCase().includes(s)?'':'none';}});\
+         </script></body></html>",
+    );
+    html
+}
+
+/// Export every per-UUID document in `firstbase_dir` (plus `processed/` when
+/// `include_processed`) as a static site under `out_dir`. Returns the device count.
+pub fn export_html(
+    firstbase_dir: &Path,
+    include_processed: bool,
+    out_dir: &Path,
+) -> anyhow::Result<usize> {
+    let files = firstbase_docs(firstbase_dir, include_processed);
+
+    let devices_dir = out_dir.join("devices");
+    std::fs::create_dir_all(&devices_dir)
+        .with_context(|| format!("Failed to create {}", devices_dir.display()))?;
+
+    let mut entries: Vec<Entry> = files
+        .par_iter()
+        .filter_map(|p| {
+            let content = std::fs::read_to_string(p).ok()?;
+            let doc: serde_json::Value = serde_json::from_str(&content).ok()?;
+            let uuid = p.file_stem()?.to_string_lossy().to_string();
+            let provenance = crate::provenance::read(firstbase_dir, &uuid);
+            let entry = entry_for(&doc, &uuid, provenance.as_ref());
+            std::fs::write(
+                devices_dir.join(format!("{}.html", uuid)),
+                device_page(&entry, &doc),
+            )
+            .ok()?;
+            Some(entry)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.gtin.cmp(&b.gtin).then_with(|| a.uuid.cmp(&b.uuid)));
+
+    std::fs::write(out_dir.join("index.html"), index_page(&entries))?;
+    Ok(entries.len())
+}
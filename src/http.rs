@@ -0,0 +1,328 @@
+//! Timeouts, pacing and retries of the HTTP clients, per host (`[http]` in
+//! config.toml). Every agent of the EUDAMED, GS1, swissdamed and Google
+//! clients comes from `agent_for`, and the EUDAMED retry loops wait
+//! `backoff` between attempts.
+//!
+//! A host's `Policy` is layered, later wins:
+//! 1. built-in defaults: 30 s timeout, 1 s backoff doubling up to 70 s;
+//! 2. the `[http]` keys themselves (all hosts);
+//! 3. built-in host values: EUDAMED (`ec.europa.eu`) one request per 1050 ms,
+//!    just under its shared ~60 req / 60 s budget; GS1 (`gs1.ch`) no timeout,
+//!    a large CreateMany can take minutes;
+//! 4. `[http.hosts."<host>"]`.
+//!
+//! A host key matches the host and its subdomains; the longest match wins.
+//! `max_retries` left unset keeps each caller's own attempt count.
+//!
+//! Every agent also goes through the same proxy and trusts the same roots:
+//! `proxy` (else the `HTTPS_PROXY` / `ALL_PROXY` / `HTTP_PROXY` environment
+//! with its `NO_PROXY`; `no_proxy` hosts and their subdomains are always
+//! reached directly) and `ca_bundle` (else the
+//! built-in Mozilla roots). Both are read once by `configure`, so a bad proxy
+//! URL or bundle fails the config load instead of every request.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ureq::tls::{Certificate, PemItem, RootCerts, TlsConfig};
+use ureq::Proxy;
+
+use crate::config::{HostPolicy, Http};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// Minimum gap between two requests to the host (`RateLimiter`).
+    pub interval_ms: u64,
+    /// Whole-request timeout; 0 = none.
+    pub timeout_secs: u64,
+    pub max_retries: Option<u32>,
+    pub backoff_base_ms: u64,
+    pub backoff_max_ms: u64,
+}
+
+const DEFAULT: Policy = Policy {
+    interval_ms: 0,
+    timeout_secs: 30,
+    max_retries: None,
+    backoff_base_ms: 1000,
+    backoff_max_ms: 70_000,
+};
+
+fn built_in(host: &str) -> HostPolicy {
+    match host {
+        "ec.europa.eu" => HostPolicy {
+            interval_ms: Some(1050),
+            ..Default::default()
+        },
+        "gs1.ch" => HostPolicy {
+            timeout_secs: Some(0),
+            ..Default::default()
+        },
+        _ => HostPolicy::default(),
+    }
+}
+
+const BUILT_IN_HOSTS: [&str; 2] = ["ec.europa.eu", "gs1.ch"];
+
+static SETTINGS: RwLock<Option<Http>> = RwLock::new(None);
+
+/// Proxy and root certificates of every agent.
+#[derive(Clone, Default)]
+struct Network {
+    proxy: Option<Proxy>,
+    /// `[http] no_proxy`.
+    direct: Vec<String>,
+    roots: Option<RootCerts>,
+}
+
+static NETWORK: RwLock<Option<Network>> = RwLock::new(None);
+
+/// The proxy of `http`: `proxy` unless `"none"`, else the environment's.
+fn proxy(http: &Http) -> Result<Option<Proxy>> {
+    match http.proxy.as_deref().map(str::trim) {
+        Some("" | "none") => Ok(None),
+        Some(url) => Proxy::new(url)
+            .map(Some)
+            .with_context(|| format!("[http] proxy {:?}", url)),
+        None => Ok(Proxy::try_from_env()),
+    }
+}
+
+/// The certificates of the PEM file `path`.
+fn load_roots(path: &str) -> Result<RootCerts> {
+    let pem = std::fs::read(path).with_context(|| format!("[http] ca_bundle {}", path))?;
+    let certs = ureq::tls::parse_pem(&pem)
+        .filter_map(|item| match item {
+            Ok(PemItem::Certificate(cert)) => Some(Ok(cert)),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<Result<Vec<Certificate<'static>>, _>>()
+        .with_context(|| format!("[http] ca_bundle {}", path))?;
+    if certs.is_empty() {
+        anyhow::bail!("[http] ca_bundle {}: no certificates", path);
+    }
+    Ok(RootCerts::Specific(Arc::new(certs)))
+}
+
+/// Take the `[http]` settings; called by `config::load_config`.
+pub fn configure(http: &Http) -> Result<()> {
+    let network = Network {
+        proxy: proxy(http)?,
+        direct: http.no_proxy.clone(),
+        roots: http.ca_bundle.as_deref().map(load_roots).transpose()?,
+    };
+    if let Ok(mut s) = SETTINGS.write() {
+        *s = Some(http.clone());
+    }
+    if let Ok(mut n) = NETWORK.write() {
+        *n = Some(network);
+    }
+    Ok(())
+}
+
+fn network() -> Network {
+    match NETWORK.read() {
+        Ok(n) => n.clone().unwrap_or_else(|| Network {
+            proxy: Proxy::try_from_env(),
+            ..Default::default()
+        }),
+        Err(_) => Network::default(),
+    }
+}
+
+/// The host of `url` (scheme, port and path dropped); `url` itself when it
+/// is a bare host.
+pub fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let end = rest.find(['/', ':', '?']).unwrap_or(rest.len());
+    &rest[..end]
+}
+
+fn matches(host: &str, key: &str) -> bool {
+    host == key
+        || host
+            .strip_suffix(key)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+fn layer(policy: &mut Policy, over: &HostPolicy) {
+    if let Some(v) = over.interval_ms {
+        policy.interval_ms = v;
+    }
+    if let Some(v) = over.timeout_secs {
+        policy.timeout_secs = v;
+    }
+    if over.max_retries.is_some() {
+        policy.max_retries = over.max_retries;
+    }
+    if let Some(v) = over.backoff_base_ms {
+        policy.backoff_base_ms = v;
+    }
+    if let Some(v) = over.backoff_max_ms {
+        policy.backoff_max_ms = v;
+    }
+}
+
+/// The policy of `url` (or host) under `http`.
+pub fn resolve(http: &Http, url: &str) -> Policy {
+    let host = host_of(url);
+    let mut policy = DEFAULT;
+    layer(&mut policy, &http.defaults);
+    if let Some(key) = BUILT_IN_HOSTS
+        .iter()
+        .filter(|k| matches(host, k))
+        .max_by_key(|k| k.len())
+    {
+        layer(&mut policy, &built_in(key));
+    }
+    if let Some((_, over)) = http
+        .hosts
+        .iter()
+        .filter(|(k, _)| matches(host, k))
+        .max_by_key(|(k, _)| k.len())
+    {
+        layer(&mut policy, over);
+    }
+    policy
+}
+
+/// The policy of `url` under the configured `[http]`.
+pub fn policy(url: &str) -> Policy {
+    match SETTINGS.read() {
+        Ok(s) => resolve(s.as_ref().unwrap_or(&Http::default()), url),
+        Err(_) => resolve(&Http::default(), url),
+    }
+}
+
+/// The response cache directory, when `[http] cache` is on.
+pub fn cache_dir() -> Option<std::path::PathBuf> {
+    let settings = SETTINGS.read().ok()?;
+    let http = settings.as_ref().filter(|h| h.cache)?;
+    Some(
+        http.cache_dir
+            .as_deref()
+            .unwrap_or(crate::http_cache::DEFAULT_CACHE_DIR)
+            .into(),
+    )
+}
+
+/// An agent for `url`'s host: its timeout, the proxy and roots of
+/// `[http]`, HTTP errors as readable statuses.
+pub fn agent_for(url: &str) -> ureq::Agent {
+    let timeout = policy(url).timeout_secs;
+    agent_with_timeout(url, (timeout > 0).then(|| Duration::from_secs(timeout)))
+}
+
+/// An agent for `url`'s host with an explicit global `timeout` (`None`:
+/// none, for large downloads), otherwise as `agent_for`.
+pub fn agent_with_timeout(url: &str, timeout: Option<Duration>) -> ureq::Agent {
+    let network = network();
+    let host = host_of(url);
+    let proxy = network
+        .proxy
+        .filter(|_| !network.direct.iter().any(|key| matches(host, key)));
+    let mut config = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .timeout_global(timeout)
+        .proxy(proxy);
+    if let Some(roots) = network.roots {
+        config = config.tls_config(TlsConfig::builder().root_certs(roots).build());
+    }
+    config.build().new_agent()
+}
+
+/// Percent-encoding for a URL path segment or query value: everything but
+/// `A-Za-z0-9-._~` (the A1 range of a sheet, a HIBC Basic UDI-DI with `+`,
+/// `/` and `$`).
+pub fn urlencode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 3);
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Whether a response status is worth another attempt (throttled or a
+/// server error).
+pub fn retryable(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+/// Wait before attempt `attempt + 1`: the server's `Retry-After` when given,
+/// else `backoff_base_ms` doubled per attempt; never above `backoff_max_ms`.
+pub fn backoff(policy: &Policy, attempt: u32, retry_after_secs: Option<u64>) -> Duration {
+    let ms = match retry_after_secs {
+        Some(secs) => secs.saturating_mul(1000),
+        None => policy
+            .backoff_base_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(20)),
+    };
+    Duration::from_millis(ms.min(policy.backoff_max_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_policies_layer_and_back_off() {
+        let http: Http = toml::from_str(
+            r#"
+            timeout_secs = 60
+            max_retries = 5
+            [hosts."europa.eu"]
+            backoff_max_ms = 10000
+            [hosts."ec.europa.eu"]
+            interval_ms = 2000
+            "#,
+        )
+        .unwrap();
+
+        let eudamed = resolve(&http, "https://ec.europa.eu/tools/eudamed/api/devices");
+        assert_eq!(eudamed.interval_ms, 2000);
+        assert_eq!(eudamed.timeout_secs, 60);
+        assert_eq!(eudamed.max_retries, Some(5));
+        assert_eq!(eudamed.backoff_max_ms, 70_000);
+        assert_eq!(resolve(&http, "data.europa.eu").backoff_max_ms, 10_000);
+        assert_eq!(
+            resolve(&http, "https://test-webapi-firstbase.gs1.ch:5443/Account").timeout_secs,
+            0
+        );
+        assert_eq!(resolve(&http, "https://notgs1.ch/").timeout_secs, 60);
+        let defaults = resolve(&Http::default(), "ec.europa.eu");
+        assert_eq!((defaults.interval_ms, defaults.max_retries), (1050, None));
+
+        assert_eq!(backoff(&eudamed, 1, None), Duration::from_secs(1));
+        assert_eq!(backoff(&eudamed, 3, None), Duration::from_secs(4));
+        assert_eq!(backoff(&eudamed, 30, None), Duration::from_secs(70));
+        assert_eq!(backoff(&eudamed, 1, Some(60)), Duration::from_secs(60));
+        assert_eq!(backoff(&eudamed, 1, Some(600)), Duration::from_secs(70));
+        assert!(retryable(429) && retryable(503) && !retryable(404));
+    }
+
+    #[test]
+    fn proxy_and_ca_bundle_settings() {
+        let http = |toml: &str| toml::from_str::<Http>(toml).unwrap();
+        let corporate = proxy(&http(r#"proxy = "http://proxy.example.com:3128""#))
+            .unwrap()
+            .unwrap();
+        assert_eq!(corporate.host(), "proxy.example.com");
+        assert_eq!(corporate.port(), 3128);
+        assert!(proxy(&http(r#"proxy = "none""#)).unwrap().is_none());
+        assert!(proxy(&http(r#"proxy = "ftp://proxy.example.com""#)).is_err());
+
+        let pem = std::env::temp_dir().join(format!("http_ca_{}.pem", std::process::id()));
+        std::fs::write(&pem, "not a certificate").unwrap();
+        let error = load_roots(pem.to_str().unwrap()).err().unwrap();
+        assert!(error.to_string().contains("no certificates"));
+        assert!(load_roots("http_test_missing.pem").is_err());
+        let _ = std::fs::remove_file(pem);
+    }
+}
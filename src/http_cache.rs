@@ -0,0 +1,162 @@
+//! On-disk cache of raw EUDAMED responses for conditional requests
+//! (`[http] cache = true`).
+//!
+//! Every 2xx body `eudamed_get` receives is kept under the cache directory
+//! (`[http] cache_dir`, default `eudamed_json/http_cache/`) as
+//! `<sha256 of the URL>.body` with a `.meta.json` holding the URL, `ETag`,
+//! `Last-Modified` and fetch time. The next request for the URL carries
+//! `If-None-Match` / `If-Modified-Since`; a `304 Not Modified` is answered
+//! from the cached body, so an unchanged record costs no transfer and the raw
+//! source of every converted record stays on disk for debugging a transform.
+//! Entries are written to a temporary file and renamed, so parallel fetches
+//! never read half a body. A request still takes its `RateLimiter` slot:
+//! EUDAMED counts a 304 against the budget like any other response.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Default cache directory, relative to the data directory.
+pub const DEFAULT_CACHE_DIR: &str = "eudamed_json/http_cache";
+
+/// Distinguishes the temporary files of concurrent writers.
+static TMP_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// Validators of a cached response.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Meta {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// RFC 3339.
+    pub fetched: String,
+}
+
+/// A cached response of `url`.
+pub struct Entry {
+    pub meta: Meta,
+    body: PathBuf,
+}
+
+impl Entry {
+    /// The conditional request headers for this entry.
+    pub fn validators(&self) -> Vec<(&'static str, &str)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.meta.etag {
+            headers.push(("If-None-Match", etag.as_str()));
+        }
+        if let Some(date) = &self.meta.last_modified {
+            headers.push(("If-Modified-Since", date.as_str()));
+        }
+        headers
+    }
+
+    /// The cached body; `None` when it went missing since the lookup.
+    pub fn body(&self) -> Option<String> {
+        std::fs::read_to_string(&self.body).ok()
+    }
+}
+
+fn paths(dir: &Path, url: &str) -> (PathBuf, PathBuf) {
+    let key = crate::version_db::hash_bytes(url.as_bytes());
+    (
+        dir.join(format!("{}.body", key)),
+        dir.join(format!("{}.meta.json", key)),
+    )
+}
+
+/// The entry of `url` in `dir`, if it has validators and a body.
+pub fn lookup_in(dir: &Path, url: &str) -> Option<Entry> {
+    let (body, meta) = paths(dir, url);
+    let meta: Meta = serde_json::from_str(&std::fs::read_to_string(meta).ok()?).ok()?;
+    // Another URL with the same hash would be a SHA256 collision; be safe anyway.
+    if meta.url != url || (meta.etag.is_none() && meta.last_modified.is_none()) {
+        return None;
+    }
+    body.exists().then_some(Entry { meta, body })
+}
+
+/// Keep `body` of `url` in `dir` with its validators; the body is kept even
+/// without validators (raw source), it is just never sent conditionally.
+pub fn store_in(
+    dir: &Path,
+    url: &str,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+    body: &str,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let (body_path, meta_path) = paths(dir, url);
+    let meta = Meta {
+        url: url.to_string(),
+        etag: etag.map(str::to_string),
+        last_modified: last_modified.map(str::to_string),
+        fetched: chrono::Utc::now().to_rfc3339(),
+    };
+    let write = |path: &Path, content: &[u8]| -> std::io::Result<()> {
+        let seq = TMP_SEQ.fetch_add(1, Ordering::Relaxed);
+        let tmp = PathBuf::from(format!(
+            "{}.{}-{}.tmp",
+            path.display(),
+            std::process::id(),
+            seq
+        ));
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, path)
+    };
+    write(&body_path, body.as_bytes())?;
+    write(
+        &meta_path,
+        serde_json::to_string(&meta)
+            .map_err(std::io::Error::other)?
+            .as_bytes(),
+    )
+}
+
+/// The entry of `url` in the configured cache; `None` when caching is off.
+pub fn lookup(url: &str) -> Option<Entry> {
+    lookup_in(&crate::http::cache_dir()?, url)
+}
+
+/// Keep a response in the configured cache, when caching is on. A failed
+/// write only costs the next conditional request, so it is not an error.
+pub fn store(url: &str, etag: Option<&str>, last_modified: Option<&str>, body: &str) {
+    if let Some(dir) = crate::http::cache_dir() {
+        if let Err(e) = store_in(&dir, url, etag, last_modified, body) {
+            crate::log_console::diag(&format!("HTTP cache {}: {}", dir.display(), e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_responses_come_back_with_their_validators() {
+        let dir = std::env::temp_dir().join(format!("http_cache_{}", std::process::id()));
+        let url = "https://ec.europa.eu/tools/eudamed/api/devices/udiDiData/u-1";
+        assert!(lookup_in(&dir, url).is_none());
+
+        store_in(&dir, url, Some("\"v7\""), None, r#"{"uuid":"u-1"}"#).unwrap();
+        let entry = lookup_in(&dir, url).unwrap();
+        assert_eq!(entry.validators(), [("If-None-Match", "\"v7\"")]);
+        assert_eq!(entry.body().as_deref(), Some(r#"{"uuid":"u-1"}"#));
+        assert!(lookup_in(&dir, &format!("{}?languageIso2Code=en", url)).is_none());
+
+        let dated = "Wed, 14 Oct 2026 08:00:00 GMT";
+        store_in(&dir, url, None, Some(dated), r#"{"uuid":"u-1","v":2}"#).unwrap();
+        let entry = lookup_in(&dir, url).unwrap();
+        assert_eq!(entry.validators(), [("If-Modified-Since", dated)]);
+        assert!(entry.body().unwrap().contains("\"v\":2"));
+
+        // Without validators the body is kept as raw source only.
+        store_in(&dir, url, None, None, "{}").unwrap();
+        assert!(lookup_in(&dir, url).is_none());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
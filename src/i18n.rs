@@ -0,0 +1,976 @@
+//! GUI string catalog. The English text is the key: `tr(lang, "Devices")`
+//! returns the German, French or Italian translation, or the English text
+//! when the catalog has none (log lines, GS1 messages and anything added
+//! later stay English until translated here). The language is a setting,
+//! defaulting to the system locale; the switcher sits next to the layout
+//! toggle.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Lang {
+    En,
+    De,
+    Fr,
+    It,
+}
+
+impl Default for Lang {
+    fn default() -> Lang {
+        Lang::detect()
+    }
+}
+
+impl Lang {
+    pub const ALL: [Lang; 4] = [Lang::En, Lang::De, Lang::Fr, Lang::It];
+
+    /// Name of the language in itself, for the switcher.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Lang::En => "English",
+            Lang::De => "Deutsch",
+            Lang::Fr => "Français",
+            Lang::It => "Italiano",
+        }
+    }
+
+    /// From `LC_ALL` / `LC_MESSAGES` / `LANG` (e.g. `de_CH.UTF-8`); English
+    /// when unset or another language.
+    pub fn detect() -> Lang {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|v| std::env::var(v).ok())
+            .find(|v| !v.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+        match locale.get(..2) {
+            Some("de") => Lang::De,
+            Some("fr") => Lang::Fr,
+            Some("it") => Lang::It,
+            _ => Lang::En,
+        }
+    }
+}
+
+/// English, German, French, Italian.
+const CATALOG: &[[&str; 4]] = &[
+    // Layout and language
+    [
+        "⬌ Horizontal",
+        "⬌ Horizontal",
+        "⬌ Horizontal",
+        "⬌ Orizzontale",
+    ],
+    ["⬍ Vertical", "⬍ Vertikal", "⬍ Vertical", "⬍ Verticale"],
+    ["Language", "Sprache", "Langue", "Lingua"],
+    // Settings panel
+    [
+        "SRNs (one per line or space-separated):",
+        "SRNs (eine pro Zeile oder durch Leerzeichen getrennt):",
+        "SRN (une par ligne ou séparées par des espaces) :",
+        "SRN (uno per riga o separati da spazi):",
+    ],
+    [
+        "Limit per SRN:",
+        "Limit pro SRN:",
+        "Limite par SRN :",
+        "Limite per SRN:",
+    ],
+    ["all", "alle", "tous", "tutti"],
+    ["Dry run", "Probelauf", "Simulation", "Simulazione"],
+    [
+        "Dry run (download & convert only)",
+        "Probelauf (nur herunterladen & konvertieren)",
+        "Simulation (téléchargement et conversion uniquement)",
+        "Simulazione (solo download e conversione)",
+    ],
+    ["Target:", "Ziel:", "Cible :", "Destinazione:"],
+    [
+        "GS1 firstbase Credentials",
+        "GS1 firstbase Zugangsdaten",
+        "Identifiants GS1 firstbase",
+        "Credenziali GS1 firstbase",
+    ],
+    [
+        "Swissdamed Credentials",
+        "Swissdamed Zugangsdaten",
+        "Identifiants Swissdamed",
+        "Credenziali Swissdamed",
+    ],
+    ["Environment:", "Umgebung:", "Environnement :", "Ambiente:"],
+    ["Production", "Produktion", "Production", "Produzione"],
+    ["Email:", "E-Mail:", "E-mail :", "E-mail:"],
+    ["Password:", "Passwort:", "Mot de passe :", "Password:"],
+    [
+        "Provider GLN:",
+        "Anbieter-GLN:",
+        "GLN du fournisseur :",
+        "GLN del fornitore:",
+    ],
+    [
+        "Publish To GLN:",
+        "Publizieren an GLN:",
+        "Publier vers GLN :",
+        "Pubblica a GLN:",
+    ],
+    ["Client ID:", "Client-ID:", "ID client :", "ID client:"],
+    [
+        "Client Secret:",
+        "Client-Secret:",
+        "Secret client :",
+        "Secret client:",
+    ],
+    ["Base URL:", "Basis-URL:", "URL de base :", "URL di base:"],
+    [
+        "API Base URL:",
+        "API-Basis-URL:",
+        "URL de base de l'API :",
+        "URL di base API:",
+    ],
+    [
+        "Phone / Group:",
+        "Telefon / Gruppe:",
+        "Téléphone / groupe :",
+        "Telefono / gruppo:",
+    ],
+    [
+        "Pair / Link Device",
+        "Gerät koppeln",
+        "Associer l'appareil",
+        "Collega dispositivo",
+    ],
+    [
+        "Send latest Prod log",
+        "Neuestes Prod-Log senden",
+        "Envoyer le dernier journal Prod",
+        "Invia l'ultimo log Prod",
+    ],
+    [
+        "Send latest Test log",
+        "Neuestes Test-Log senden",
+        "Envoyer le dernier journal Test",
+        "Invia l'ultimo log Test",
+    ],
+    ["Log:", "Protokoll:", "Journal :", "Log:"],
+    // Pipeline modes
+    [
+        "0: Download & Convert",
+        "0: Herunterladen & Konvertieren",
+        "0 : Télécharger et convertir",
+        "0: Scarica e converti",
+    ],
+    [
+        "0: Download, Convert & Push to {}",
+        "0: Herunterladen, konvertieren & an {} senden",
+        "0 : Télécharger, convertir et envoyer vers {}",
+        "0: Scarica, converti e invia a {}",
+    ],
+    [
+        "0: DL+Push {}",
+        "0: DL+Senden {}",
+        "0 : DL+Envoi {}",
+        "0: DL+Invio {}",
+    ],
+    [
+        "1: Convert only (all)",
+        "1: Nur konvertieren (alle)",
+        "1 : Convertir seulement (tous)",
+        "1: Solo conversione (tutti)",
+    ],
+    [
+        "1: Convert & Push (all)",
+        "1: Konvertieren & senden (alle)",
+        "1 : Convertir et envoyer (tous)",
+        "1: Converti e invia (tutti)",
+    ],
+    [
+        "2: Convert SRNs only",
+        "2: Nur SRNs konvertieren",
+        "2 : Convertir les SRN seulement",
+        "2: Solo conversione SRN",
+    ],
+    [
+        "2: Convert & Push SRNs",
+        "2: SRNs konvertieren & senden",
+        "2 : Convertir et envoyer les SRN",
+        "2: Converti e invia SRN",
+    ],
+    [
+        "3: Repush failed",
+        "3: Fehlgeschlagene erneut senden",
+        "3 : Renvoyer les échecs",
+        "3: Reinvia i falliti",
+    ],
+    [
+        "4: Repush SRN",
+        "4: SRN erneut senden",
+        "4 : Renvoyer SRN",
+        "4: Reinvia SRN",
+    ],
+    [
+        "5: Reconvert + Repush SRN",
+        "5: SRN neu konvertieren + senden",
+        "5 : Reconvertir + renvoyer SRN",
+        "5: Riconverti + reinvia SRN",
+    ],
+    // Sections
+    [
+        "Search devices",
+        "Produkte suchen",
+        "Rechercher des dispositifs",
+        "Cerca dispositivi",
+    ],
+    ["Devices", "Produkte", "Dispositifs", "Dispositivi"],
+    [
+        "Registry readiness",
+        "Registrierungsstand",
+        "État d'enregistrement",
+        "Stato di registrazione",
+    ],
+    [
+        "Rejection triage",
+        "Ablehnungen sichten",
+        "Tri des rejets",
+        "Analisi dei rifiuti",
+    ],
+    [
+        "Pre-flight validation",
+        "Vorabprüfung",
+        "Validation préalable",
+        "Convalida preliminare",
+    ],
+    ["Fix…", "Beheben…", "Corriger…", "Correggi…"],
+    ["Jobs", "Aufträge", "Tâches", "Processi"],
+    [
+        "Jobs ({} queued)",
+        "Aufträge ({} wartend)",
+        "Tâches ({} en attente)",
+        "Processi ({} in coda)",
+    ],
+    [
+        "Start queue",
+        "Warteschlange starten",
+        "Lancer la file",
+        "Avvia la coda",
+    ],
+    [
+        "Clear finished",
+        "Erledigte entfernen",
+        "Effacer les terminées",
+        "Rimuovi i completati",
+    ],
+    [
+        "Notify when done",
+        "Benachrichtigen, wenn fertig",
+        "Notifier à la fin",
+        "Notifica al termine",
+    ],
+    ["Statistics", "Statistik", "Statistiques", "Statistiche"],
+    [
+        "Devices per risk class",
+        "Produkte pro Risikoklasse",
+        "Dispositifs par classe de risque",
+        "Dispositivi per classe di rischio",
+    ],
+    [
+        "Devices per status",
+        "Produkte pro Status",
+        "Dispositifs par statut",
+        "Dispositivi per stato",
+    ],
+    [
+        "Push acceptance rate per day",
+        "Annahmequote pro Tag",
+        "Taux d'acceptation par jour",
+        "Tasso di accettazione al giorno",
+    ],
+    [
+        "Top rejection codes",
+        "Häufigste Ablehnungscodes",
+        "Codes de rejet les plus fréquents",
+        "Codici di rifiuto più frequenti",
+    ],
+    [
+        "Select shown",
+        "Angezeigte auswählen",
+        "Sélectionner l'affichage",
+        "Seleziona i visualizzati",
+    ],
+    [
+        "Clear selection",
+        "Auswahl aufheben",
+        "Effacer la sélection",
+        "Annulla selezione",
+    ],
+    ["Validate", "Prüfen", "Valider", "Verifica"],
+    ["Convert", "Konvertieren", "Convertir", "Converti"],
+    // Common buttons
+    ["Reload", "Neu laden", "Recharger", "Ricarica"],
+    ["Refresh", "Aktualisieren", "Actualiser", "Aggiorna"],
+    ["Clear", "Leeren", "Effacer", "Svuota"],
+    ["Remove", "Entfernen", "Retirer", "Rimuovi"],
+    ["Cancel", "Abbrechen", "Annuler", "Annulla"],
+    ["Pause", "Pause", "Pause", "Pausa"],
+    ["Resume", "Fortsetzen", "Reprendre", "Riprendi"],
+    ["Save", "Speichern", "Enregistrer", "Salva"],
+    [
+        "Convert & push",
+        "Konvertieren & senden",
+        "Convertir et envoyer",
+        "Converti e invia",
+    ],
+    [
+        "Edit fields…",
+        "Felder bearbeiten…",
+        "Modifier les champs…",
+        "Modifica campi…",
+    ],
+    // Update banner
+    [
+        "New version available",
+        "Neue Version verfügbar",
+        "Nouvelle version disponible",
+        "Nuova versione disponibile",
+    ],
+    ["installed", "installiert", "installée", "installata"],
+    ["Dismiss", "Ausblenden", "Masquer", "Nascondi"],
+    [
+        "Updating…",
+        "Aktualisiere…",
+        "Mise à jour…",
+        "Aggiornamento…",
+    ],
+    [
+        "Update now",
+        "Jetzt aktualisieren",
+        "Mettre à jour",
+        "Aggiorna ora",
+    ],
+    [
+        "Open release page",
+        "Release-Seite öffnen",
+        "Ouvrir la page de la version",
+        "Apri la pagina della versione",
+    ],
+    ["Working…", "Arbeite…", "En cours…", "In corso…"],
+    ["What's new", "Neuerungen", "Nouveautés", "Novità"],
+    [
+        "Update failed",
+        "Update fehlgeschlagen",
+        "Échec de la mise à jour",
+        "Aggiornamento non riuscito",
+    ],
+    [
+        "Where does each value come from?",
+        "Woher stammt jeder Wert?",
+        "D'où vient chaque valeur ?",
+        "Da dove viene ogni valore?",
+    ],
+    // Credential screen
+    [
+        "🔑 Credentials",
+        "🔑 Zugangsdaten",
+        "🔑 Identifiants",
+        "🔑 Credenziali",
+    ],
+    [
+        "Test connection",
+        "Verbindung testen",
+        "Tester la connexion",
+        "Verifica connessione",
+    ],
+    // Log console
+    ["Info", "Info", "Info", "Info"],
+    ["Warnings", "Warnungen", "Avertissements", "Avvisi"],
+    ["Errors", "Fehler", "Erreurs", "Errori"],
+    [
+        "All steps",
+        "Alle Schritte",
+        "Toutes les étapes",
+        "Tutte le fasi",
+    ],
+    ["download", "Download", "téléchargement", "download"],
+    ["convert", "Konvertierung", "conversion", "conversione"],
+    ["push", "Senden", "envoi", "invio"],
+    ["other", "Sonstiges", "autre", "altro"],
+    ["filter", "Filter", "filtre", "filtro"],
+    ["Export", "Exportieren", "Exporter", "Esporta"],
+    ["Copy", "Kopieren", "Copier", "Copia"],
+    // Error dialog
+    ["⚠  Error", "⚠  Fehler", "⚠  Erreur", "⚠  Errore"],
+    [
+        "The pipeline did not complete successfully:",
+        "Die Pipeline wurde nicht erfolgreich abgeschlossen:",
+        "Le traitement ne s'est pas terminé correctement :",
+        "La pipeline non è stata completata correttamente:",
+    ],
+    [
+        "Copy to clipboard",
+        "In die Zwischenablage kopieren",
+        "Copier dans le presse-papiers",
+        "Copia negli appunti",
+    ],
+    // Search and update banner
+    [
+        "trade name, manufacturer, SRN, EMDN, GTIN…",
+        "Handelsname, Hersteller, SRN, EMDN, GTIN…",
+        "nom commercial, fabricant, SRN, EMDN, GTIN…",
+        "nome commerciale, fabbricante, SRN, EMDN, GTIN…",
+    ],
+    ["Rebuild index", "Index neu aufbauen", "Reconstruire l'index", "Ricostruisci l'indice"],
+    [
+        "Re-index all converted devices in firstbase_json/ and processed/",
+        "Alle konvertierten Produkte in firstbase_json/ und processed/ neu indexieren",
+        "Réindexer tous les dispositifs convertis de firstbase_json/ et processed/",
+        "Reindicizza tutti i dispositivi convertiti in firstbase_json/ e processed/",
+    ],
+    [
+        "Hide until a newer release is out",
+        "Ausblenden, bis eine neuere Version erscheint",
+        "Masquer jusqu'à la prochaine version",
+        "Nascondi fino alla prossima versione",
+    ],
+    // Dropped files
+    [
+        "Drop EUDAMED JSON, NDJSON, XML or zip files to import",
+        "EUDAMED-JSON-, NDJSON-, XML- oder ZIP-Dateien zum Importieren ablegen",
+        "Déposez des fichiers EUDAMED JSON, NDJSON, XML ou zip à importer",
+        "Trascina file EUDAMED JSON, NDJSON, XML o zip da importare",
+    ],
+    [
+        "{} file(s) queued for conversion",
+        "{} Datei(en) zur Konvertierung vorgemerkt",
+        "{} fichier(s) en attente de conversion",
+        "{} file in attesa di conversione",
+    ],
+    [
+        "Convert the dropped files and push only them (Dry run: convert only)",
+        "Die abgelegten Dateien konvertieren und nur diese senden (Testlauf: nur konvertieren)",
+        "Convertir les fichiers déposés et n'envoyer qu'eux (essai : conversion seule)",
+        "Converti i file trascinati e invia solo quelli (prova: solo conversione)",
+    ],
+    ["Queued", "Vorgemerkt", "En attente", "In coda"],
+    // Workspace tabs
+    ["Close tab", "Tab schliessen", "Fermer l'onglet", "Chiudi scheda"],
+    [
+        "Open a dataset or environment in a new tab",
+        "Datensatz oder Umgebung in einem neuen Tab öffnen",
+        "Ouvrir un jeu de données ou un environnement dans un nouvel onglet",
+        "Apri un set di dati o un ambiente in una nuova scheda",
+    ],
+    ["Name:", "Name:", "Nom :", "Nome:"],
+    ["Dataset:", "Datensatz:", "Jeu de données :", "Set di dati:"],
+    [
+        "Working set",
+        "Arbeitsbestand",
+        "Jeu de travail",
+        "Set di lavoro",
+    ],
+    ["Folder", "Ordner", "Dossier", "Cartella"],
+    ["Folder:", "Ordner:", "Dossier :", "Cartella:"],
+    [
+        "folder with converted <uuid>.json files",
+        "Ordner mit konvertierten <uuid>.json-Dateien",
+        "dossier contenant des fichiers <uuid>.json convertis",
+        "cartella con file <uuid>.json convertiti",
+    ],
+    ["Open", "Öffnen", "Ouvrir", "Apri"],
+    // Device list
+    [
+        "GTIN, trade name, manufacturer, SRN…",
+        "GTIN, Handelsname, Hersteller, SRN…",
+        "GTIN, nom commercial, fabricant, SRN…",
+        "GTIN, nome commerciale, fabbricante, SRN…",
+    ],
+    [
+        "Re-read firstbase_json/ and processed/",
+        "firstbase_json/ und processed/ neu einlesen",
+        "Relire firstbase_json/ et processed/",
+        "Rileggi firstbase_json/ e processed/",
+    ],
+    ["Status", "Status", "Statut", "Stato"],
+    [
+        "Risk class",
+        "Risikoklasse",
+        "Classe de risque",
+        "Classe di rischio",
+    ],
+    ["Location", "Ablage", "Emplacement", "Posizione"],
+    ["pending", "ausstehend", "en attente", "in sospeso"],
+    ["processed", "verarbeitet", "traité", "elaborato"],
+    [
+        "{} of {} devices, {} selected",
+        "{} von {} Produkten, {} ausgewählt",
+        "{} sur {} dispositifs, {} sélectionné(s)",
+        "{} di {} dispositivi, {} selezionati",
+    ],
+    [
+        "Add every device the search and filters show",
+        "Alle Produkte hinzufügen, die Suche und Filter anzeigen",
+        "Ajouter tous les dispositifs affichés par la recherche et les filtres",
+        "Aggiungi tutti i dispositivi mostrati da ricerca e filtri",
+    ],
+    [
+        "Offline checks of the selected documents as they are now",
+        "Offline-Prüfung der ausgewählten Dokumente im aktuellen Stand",
+        "Contrôles hors ligne des documents sélectionnés dans leur état actuel",
+        "Controlli offline dei documenti selezionati nello stato attuale",
+    ],
+    [
+        "Re-convert the selected devices from eudamed_json/detail/ and check them, no push",
+        "Die ausgewählten Produkte aus eudamed_json/detail/ neu konvertieren und prüfen, ohne Senden",
+        "Reconvertir les dispositifs sélectionnés depuis eudamed_json/detail/ et les contrôler, sans envoi",
+        "Riconverti i dispositivi selezionati da eudamed_json/detail/ e verificali, senza invio",
+    ],
+    [
+        "Re-convert the selected devices and push only those",
+        "Die ausgewählten Produkte neu konvertieren und nur diese senden",
+        "Reconvertir les dispositifs sélectionnés et n'envoyer qu'eux",
+        "Riconverti i dispositivi selezionati e invia solo quelli",
+    ],
+    [
+        "Select / deselect the shown devices",
+        "Angezeigte Produkte aus- / abwählen",
+        "Sélectionner / désélectionner les dispositifs affichés",
+        "Seleziona / deseleziona i dispositivi mostrati",
+    ],
+    [
+        "Override attributes of this device before the push",
+        "Attribute dieses Produkts vor dem Senden überschreiben",
+        "Remplacer des attributs de ce dispositif avant l'envoi",
+        "Sovrascrivi attributi di questo dispositivo prima dell'invio",
+    ],
+    // Override editor and provenance
+    ["Filter:", "Filter:", "Filtre :", "Filtro:"],
+    [
+        "attribute, e.g. Description",
+        "Attribut, z. B. Description",
+        "attribut, p. ex. Description",
+        "attributo, ad es. Description",
+    ],
+    [
+        "{} of {} attributes",
+        "{} von {} Attributen",
+        "{} sur {} attributs",
+        "{} di {} attributi",
+    ],
+    [
+        "Unticking an override keeps the current value until the next conversion",
+        "Ein abgewählter Override behält den aktuellen Wert bis zur nächsten Konvertierung",
+        "Décocher un remplacement conserve la valeur actuelle jusqu'à la prochaine conversion",
+        "Deselezionare una sovrascrittura mantiene il valore attuale fino alla prossima conversione",
+    ],
+    [
+        "Override this attribute",
+        "Dieses Attribut überschreiben",
+        "Remplacer cet attribut",
+        "Sovrascrivi questo attributo",
+    ],
+    [
+        "Converted {} from {} (downloaded {})",
+        "Konvertiert {} aus {} (heruntergeladen {})",
+        "Converti le {} depuis {} (téléchargé le {})",
+        "Convertito il {} da {} (scaricato il {})",
+    ],
+    [
+        "No provenance sidecar; sources looked up by UUID",
+        "Keine Herkunftsdatei; Quellen nach UUID gesucht",
+        "Pas de fichier de provenance ; sources recherchées par UUID",
+        "Nessun file di provenienza; fonti cercate per UUID",
+    ],
+    ["override", "Override", "remplacement", "sovrascrittura"],
+    [
+        "config default",
+        "Konfigurationsvorgabe",
+        "valeur par défaut de la config",
+        "predefinito della configurazione",
+    ],
+    ["detail", "Detail", "détail", "dettaglio"],
+    [
+        "Basic UDI-DI (merge)",
+        "Basic UDI-DI (zusammengeführt)",
+        "Basic UDI-DI (fusion)",
+        "Basic UDI-DI (unione)",
+    ],
+    ["listing", "Liste", "liste", "elenco"],
+    [
+        "converter (mapped/derived)",
+        "Konverter (zugeordnet/abgeleitet)",
+        "convertisseur (mappé/dérivé)",
+        "convertitore (mappato/derivato)",
+    ],
+    // Readiness
+    [
+        "Recompute from firstbase_json/, processed/ and the push log",
+        "Aus firstbase_json/, processed/ und dem Sendeprotokoll neu berechnen",
+        "Recalculer depuis firstbase_json/, processed/ et le journal d'envoi",
+        "Ricalcola da firstbase_json/, processed/ e dal registro di invio",
+    ],
+    ["{} devices", "{} Produkte", "{} dispositifs", "{} dispositivi"],
+    // Triage
+    [
+        "Reload the open rejections from the push log",
+        "Offene Ablehnungen aus dem Sendeprotokoll neu laden",
+        "Recharger les rejets ouverts depuis le journal d'envoi",
+        "Ricarica i rifiuti aperti dal registro di invio",
+    ],
+    [
+        "Show decided",
+        "Entschiedene anzeigen",
+        "Afficher les décidés",
+        "Mostra i decisi",
+    ],
+    [
+        "{} open rejection(s) in {} error code(s), {} undecided",
+        "{} offene Ablehnung(en) in {} Fehlercode(s), {} unentschieden",
+        "{} rejet(s) ouvert(s) dans {} code(s) d'erreur, {} non décidé(s)",
+        "{} rifiuti aperti in {} codici di errore, {} non decisi",
+    ],
+    [
+        "Triage error",
+        "Fehler beim Sichten",
+        "Erreur de tri",
+        "Errore di analisi",
+    ],
+    [
+        "{} — {} device(s), {} undecided",
+        "{} — {} Produkt(e), {} unentschieden",
+        "{} — {} dispositif(s), {} non décidé(s)",
+        "{} — {} dispositivi, {} non decisi",
+    ],
+    ["Mark all:", "Alle markieren:", "Tout marquer :", "Segna tutti:"],
+    [
+        "No knowledge-base entry.",
+        "Kein Eintrag in der Wissensbasis.",
+        "Aucune entrée dans la base de connaissances.",
+        "Nessuna voce nella base di conoscenza.",
+    ],
+    [
+        "Edit fields of this device",
+        "Felder dieses Produkts bearbeiten",
+        "Modifier les champs de ce dispositif",
+        "Modifica i campi di questo dispositivo",
+    ],
+    [
+        "{} — pushed {}",
+        "{} — gesendet {}",
+        "{} — envoyé le {}",
+        "{} — inviato il {}",
+    ],
+    [
+        "fixed at source",
+        "an der Quelle behoben",
+        "corrigé à la source",
+        "corretto alla fonte",
+    ],
+    ["ignore", "ignorieren", "ignorer", "ignora"],
+    // Validation
+    [
+        "Check the converted documents again",
+        "Konvertierte Dokumente erneut prüfen",
+        "Contrôler à nouveau les documents convertis",
+        "Verifica di nuovo i documenti convertiti",
+    ],
+    [
+        "No findings in the pending documents",
+        "Keine Befunde in den ausstehenden Dokumenten",
+        "Aucun constat dans les documents en attente",
+        "Nessun rilievo nei documenti in sospeso",
+    ],
+    [
+        "{} finding(s) in {} rule(s)",
+        "{} Befund(e) in {} Regel(n)",
+        "{} constat(s) dans {} règle(s)",
+        "{} rilievi in {} regole",
+    ],
+    [
+        "{} — {} device(s)",
+        "{} — {} Produkt(e)",
+        "{} — {} dispositif(s)",
+        "{} — {} dispositivi",
+    ],
+    [
+        "Edit {} of this device",
+        "{} dieses Produkts bearbeiten",
+        "Modifier {} de ce dispositif",
+        "Modifica {} di questo dispositivo",
+    ],
+    // Credential screen
+    [
+        "Stored in the system keychain. A firstbase login without its own Test/Production entry uses the shared one.",
+        "Im Schlüsselbund des Systems gespeichert. Ein firstbase-Login ohne eigenen Test-/Produktionseintrag verwendet den gemeinsamen.",
+        "Enregistrés dans le trousseau du système. Un identifiant firstbase sans entrée Test/Production propre utilise l'entrée commune.",
+        "Salvate nel portachiavi di sistema. Un accesso firstbase senza una propria voce Test/Produzione usa quella comune.",
+    ],
+    ["🔒 stored", "🔒 gespeichert", "🔒 enregistré", "🔒 salvato"],
+    ["not stored", "nicht gespeichert", "non enregistré", "non salvato"],
+    [
+        "Request a token with this login",
+        "Mit diesem Login ein Token anfordern",
+        "Demander un jeton avec cet identifiant",
+        "Richiedi un token con questo accesso",
+    ],
+    ["Saved", "Gespeichert", "Enregistré", "Salvato"],
+    [
+        "Removed from the keychain",
+        "Aus dem Schlüsselbund entfernt",
+        "Retiré du trousseau",
+        "Rimosso dal portachiavi",
+    ],
+    ["Testing…", "Teste…", "Test en cours…", "Verifica in corso…"],
+    ["Email", "E-Mail", "E-mail", "E-mail"],
+    ["Password", "Passwort", "Mot de passe", "Password"],
+    ["Client ID", "Client-ID", "ID client", "ID client"],
+    [
+        "Client Secret",
+        "Client-Secret",
+        "Secret client",
+        "Segreto client",
+    ],
+    [
+        "Logins per environment in the system keychain, with a connection test",
+        "Logins pro Umgebung im Schlüsselbund des Systems, mit Verbindungstest",
+        "Identifiants par environnement dans le trousseau du système, avec test de connexion",
+        "Accessi per ambiente nel portachiavi di sistema, con verifica della connessione",
+    ],
+    [
+        "⚠ PRODUCTION — real data will be pushed!",
+        "⚠ PRODUKTION — es werden echte Daten gesendet!",
+        "⚠ PRODUCTION — des données réelles seront envoyées !",
+        "⚠ PRODUZIONE — verranno inviati dati reali!",
+    ],
+    // Log console and jobs
+    [
+        "Save the shown lines as TSV in the logs folder",
+        "Angezeigte Zeilen als TSV im Log-Ordner speichern",
+        "Enregistrer les lignes affichées en TSV dans le dossier des journaux",
+        "Salva le righe mostrate come TSV nella cartella dei log",
+    ],
+    [
+        "Run the jobs left queued from the last session",
+        "Die aus der letzten Sitzung wartenden Aufträge ausführen",
+        "Exécuter les tâches restées en attente de la dernière session",
+        "Esegui i processi rimasti in coda dall'ultima sessione",
+    ],
+    [
+        "Desktop notification when a job finishes or fails while the window is in the background",
+        "Desktop-Benachrichtigung, wenn ein Auftrag im Hintergrund fertig wird oder fehlschlägt",
+        "Notification du bureau lorsqu'une tâche se termine ou échoue pendant que la fenêtre est en arrière-plan",
+        "Notifica desktop quando un processo termina o fallisce mentre la finestra è in secondo piano",
+    ],
+    // Push dashboard
+    ["cancelling…", "breche ab…", "annulation…", "annullamento…"],
+    [
+        "Takes effect before the next push chunk",
+        "Wirkt vor dem nächsten Sendepaket",
+        "Prend effet avant le prochain lot d'envoi",
+        "Ha effetto prima del prossimo blocco di invio",
+    ],
+    [
+        "Stop at the next device/chunk; unpushed files stay in firstbase_json/",
+        "Beim nächsten Produkt/Paket anhalten; nicht gesendete Dateien bleiben in firstbase_json/",
+        "Arrêter au prochain dispositif/lot ; les fichiers non envoyés restent dans firstbase_json/",
+        "Fermati al prossimo dispositivo/blocco; i file non inviati restano in firstbase_json/",
+    ],
+    [
+        "{} chunk {}/{}: {} of {} accepted, {} errors",
+        "{} Paket {}/{}: {} von {} angenommen, {} Fehler",
+        "{} lot {}/{} : {} sur {} acceptés, {} erreurs",
+        "{} blocco {}/{}: {} di {} accettati, {} errori",
+    ],
+    [
+        "Hold the push before the next chunk is submitted",
+        "Senden anhalten, bevor das nächste Paket übermittelt wird",
+        "Suspendre l'envoi avant la soumission du prochain lot",
+        "Sospendi l'invio prima che venga inviato il prossimo blocco",
+    ],
+    [
+        "Pausing after this chunk…",
+        "Pause nach diesem Paket…",
+        "Pause après ce lot…",
+        "Pausa dopo questo blocco…",
+    ],
+    ["Chunks", "Pakete", "Lots", "Blocchi"],
+    ["Items", "Einträge", "Éléments", "Elementi"],
+    ["State", "Zustand", "État", "Stato"],
+    ["Polls", "Abfragen", "Interrogations", "Interrogazioni"],
+    ["Accepted", "Angenommen", "Acceptés", "Accettati"],
+    ["Request", "Anfrage", "Requête", "Richiesta"],
+    ["View", "Anzeigen", "Afficher", "Mostra"],
+    ["queued", "wartend", "en attente", "in coda"],
+    ["submitting", "wird übermittelt", "soumission", "invio"],
+    ["polling", "wird abgefragt", "interrogation", "interrogazione"],
+    ["done", "erledigt", "terminé", "completato"],
+    ["rejected", "abgelehnt", "rejeté", "rifiutato"],
+    ["failed", "fehlgeschlagen", "échoué", "fallito"],
+    // Statistics
+    ["No data", "Keine Daten", "Aucune donnée", "Nessun dato"],
+    [
+        "{}: {}% ({} accepted, {} rejected)",
+        "{}: {}% ({} angenommen, {} abgelehnt)",
+        "{} : {} % ({} acceptés, {} rejetés)",
+        "{}: {}% ({} accettati, {} rifiutati)",
+    ],
+    // Pipeline buttons
+    [
+        "No download — find SRN products, convert & push",
+        "Kein Download — SRN-Produkte suchen, konvertieren & senden",
+        "Sans téléchargement — trouver les produits SRN, convertir et envoyer",
+        "Nessun download — trova i prodotti SRN, converti e invia",
+    ],
+    [
+        "No download — find SRN products in existing files, convert & push",
+        "Kein Download — SRN-Produkte in vorhandenen Dateien suchen, konvertieren & senden",
+        "Sans téléchargement — trouver les produits SRN dans les fichiers existants, convertir et envoyer",
+        "Nessun download — trova i prodotti SRN nei file esistenti, converti e invia",
+    ],
+    [
+        "Skip download, convert+push all existing files",
+        "Ohne Download alle vorhandenen Dateien konvertieren + senden",
+        "Sans téléchargement, convertir + envoyer tous les fichiers existants",
+        "Senza download, converti + invia tutti i file esistenti",
+    ],
+    [
+        "Push remaining files in firstbase_json/ (rejected from last push)",
+        "Verbleibende Dateien in firstbase_json/ senden (beim letzten Senden abgelehnt)",
+        "Envoyer les fichiers restants de firstbase_json/ (rejetés au dernier envoi)",
+        "Invia i file rimasti in firstbase_json/ (rifiutati all'ultimo invio)",
+    ],
+    [
+        "Restore files for the given SRN(s) from processed/ and push (bypasses unchanged-skip)",
+        "Dateien der angegebenen SRN(s) aus processed/ wiederherstellen und senden (auch unveränderte)",
+        "Restaurer les fichiers des SRN indiqués depuis processed/ et les envoyer (même inchangés)",
+        "Ripristina i file degli SRN indicati da processed/ e inviali (anche se invariati)",
+    ],
+    [
+        "Re-run the converter for the given SRN(s) (picks up new GS1 fields, e.g. DescriptionShort), then push",
+        "Konverter für die angegebenen SRN(s) neu ausführen (übernimmt neue GS1-Felder, z. B. DescriptionShort), dann senden",
+        "Relancer le convertisseur pour les SRN indiqués (prend en compte les nouveaux champs GS1, p. ex. DescriptionShort), puis envoyer",
+        "Riesegui il convertitore per gli SRN indicati (include i nuovi campi GS1, ad es. DescriptionShort), poi invia",
+    ],
+    [
+        "Force-refetch detail + Basic UDI-DI fresh from EUDAMED for the given SRN(s) (heals stale/incomplete cache → fixes 097.025), then reconvert & push",
+        "Detail + Basic UDI-DI der angegebenen SRN(s) neu von EUDAMED laden (repariert veralteten/unvollständigen Cache → behebt 097.025), dann neu konvertieren & senden",
+        "Retélécharger détail + Basic UDI-DI depuis EUDAMED pour les SRN indiqués (répare un cache périmé/incomplet → corrige 097.025), puis reconvertir et envoyer",
+        "Riscarica dettaglio + Basic UDI-DI da EUDAMED per gli SRN indicati (ripara cache obsoleta/incompleta → corregge 097.025), poi riconverti e invia",
+    ],
+    // WhatsApp
+    [
+        "Show QR code to link this device to WhatsApp",
+        "QR-Code anzeigen, um dieses Gerät mit WhatsApp zu verknüpfen",
+        "Afficher le code QR pour associer cet appareil à WhatsApp",
+        "Mostra il codice QR per collegare questo dispositivo a WhatsApp",
+    ],
+    [
+        "Show QR code to link this device to WhatsApp (first-run only; session persists in whatsapp/auth/)",
+        "QR-Code anzeigen, um dieses Gerät mit WhatsApp zu verknüpfen (nur beim ersten Mal; die Sitzung bleibt in whatsapp/auth/)",
+        "Afficher le code QR pour associer cet appareil à WhatsApp (la première fois seulement ; la session reste dans whatsapp/auth/)",
+        "Mostra il codice QR per collegare questo dispositivo a WhatsApp (solo la prima volta; la sessione resta in whatsapp/auth/)",
+    ],
+    [
+        "Send the most recent log/firstbase_prod/*.log.html via WhatsApp",
+        "Neuestes log/firstbase_prod/*.log.html per WhatsApp senden",
+        "Envoyer le dernier log/firstbase_prod/*.log.html par WhatsApp",
+        "Invia l'ultimo log/firstbase_prod/*.log.html tramite WhatsApp",
+    ],
+    [
+        "Send the most recent log/firstbase_test/*.log.html via WhatsApp",
+        "Neuestes log/firstbase_test/*.log.html per WhatsApp senden",
+        "Envoyer le dernier log/firstbase_test/*.log.html par WhatsApp",
+        "Invia l'ultimo log/firstbase_test/*.log.html tramite WhatsApp",
+    ],
+    [
+        "Send the most recent log/firstbase_prod/*.log.html",
+        "Neuestes log/firstbase_prod/*.log.html senden",
+        "Envoyer le dernier log/firstbase_prod/*.log.html",
+        "Invia l'ultimo log/firstbase_prod/*.log.html",
+    ],
+    [
+        "Send the most recent log/firstbase_test/*.log.html",
+        "Neuestes log/firstbase_test/*.log.html senden",
+        "Envoyer le dernier log/firstbase_test/*.log.html",
+        "Invia l'ultimo log/firstbase_test/*.log.html",
+    ],
+    [
+        "Open WhatsApp on your phone:",
+        "WhatsApp auf dem Telefon öffnen:",
+        "Ouvrez WhatsApp sur votre téléphone :",
+        "Apri WhatsApp sul telefono:",
+    ],
+    [
+        "Settings  →  Linked Devices  →  Link a Device",
+        "Einstellungen  →  Verknüpfte Geräte  →  Gerät hinzufügen",
+        "Réglages  →  Appareils connectés  →  Connecter un appareil",
+        "Impostazioni  →  Dispositivi collegati  →  Collega un dispositivo",
+    ],
+    [
+        "Then scan this QR code:",
+        "Dann diesen QR-Code scannen:",
+        "Puis scannez ce code QR :",
+        "Poi scansiona questo codice QR:",
+    ],
+    [
+        "Waiting for scan…",
+        "Warte auf Scan…",
+        "En attente du scan…",
+        "In attesa della scansione…",
+    ],
+];
+
+/// `text` in `lang`; `text` itself when English or not in the catalog.
+pub fn tr(lang: Lang, text: &'static str) -> &'static str {
+    let column = match lang {
+        Lang::En => return text,
+        Lang::De => 1,
+        Lang::Fr => 2,
+        Lang::It => 3,
+    };
+    CATALOG
+        .iter()
+        .find(|entry| entry[0] == text)
+        .map_or(text, |entry| entry[column])
+}
+
+/// `tr` of a text with `{}` placeholders, filled with `args` in order.
+pub fn trf(lang: Lang, text: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    for (i, part) in tr(lang, text).split("{}").enumerate() {
+        if i > 0 {
+            if let Some(arg) = args.next() {
+                out.push_str(&arg.to_string());
+            }
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_is_complete_and_keeps_placeholders() {
+        for (i, entry) in CATALOG.iter().enumerate() {
+            assert!(
+                CATALOG[..i].iter().all(|e| e[0] != entry[0]),
+                "duplicate key {:?}",
+                entry[0]
+            );
+            for text in entry {
+                assert!(!text.is_empty(), "empty translation of {:?}", entry[0]);
+                assert_eq!(
+                    text.matches("{}").count(),
+                    entry[0].matches("{}").count(),
+                    "placeholder mismatch in {:?}",
+                    text
+                );
+            }
+        }
+        assert_eq!(tr(Lang::De, "Devices"), "Produkte");
+        assert_eq!(tr(Lang::Fr, "not in the catalog"), "not in the catalog");
+        assert_eq!(
+            trf(Lang::De, "{} of {} attributes", &[&3, &12]),
+            "3 von 12 Attributen"
+        );
+    }
+}
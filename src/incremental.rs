@@ -0,0 +1,167 @@
+//! Incremental NDJSON syncs (`--incremental`). The per-device `download`
+//! already skips devices whose listing version did not move (`udi_versions`);
+//! this does the same for the NDJSON pipeline, so a nightly run over a full
+//! listing only fetches and converts what changed.
+//!
+//! `sync_versions` in `db/version_tracking.db` keeps the `versionNumber` /
+//! `versionDate` of every device last handled, per scope: `listing` for the
+//! `ndjson` mode convert, `detail` for `fetch-details`. A record is changed
+//! when its (version, date) is above the stored one (no version counts as
+//! 0, like `dedup.rs`) or the UUID is new. Versions are recorded only after
+//! the step finished, so a failed run repeats its devices next time.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Listing,
+    Detail,
+}
+
+impl Scope {
+    fn as_str(self) -> &'static str {
+        match self {
+            Scope::Listing => "listing",
+            Scope::Detail => "detail",
+        }
+    }
+}
+
+/// Identity and version of one listing / detail record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seen {
+    pub uuid: String,
+    pub version: Option<u64>,
+    pub version_date: String,
+}
+
+impl Seen {
+    /// From an NDJSON line; `None` without a `uuid`.
+    pub fn from_line(line: &str) -> Option<Seen> {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        let uuid = value.get("uuid")?.as_str().filter(|u| !u.is_empty())?;
+        Some(Seen {
+            uuid: uuid.to_string(),
+            version: crate::dedup::version_of(value.get("versionNumber")),
+            version_date: value
+                .get("versionDate")
+                .and_then(|d| d.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+
+    fn key(&self) -> (u64, &str) {
+        (self.version.unwrap_or(0), &self.version_date)
+    }
+}
+
+/// The stored versions of one scope.
+pub struct State {
+    conn: Connection,
+    scope: Scope,
+    stored: HashMap<String, Seen>,
+}
+
+impl State {
+    pub fn open(db_path: &Path, scope: Scope) -> anyhow::Result<State> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = crate::version_db::open_db(db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sync_versions (
+                scope TEXT NOT NULL,
+                uuid TEXT NOT NULL,
+                version_number INTEGER,
+                version_date TEXT NOT NULL DEFAULT '',
+                synced_at TEXT NOT NULL,
+                PRIMARY KEY (scope, uuid)
+            );",
+        )?;
+        let stored = conn
+            .prepare(
+                "SELECT uuid, version_number, version_date FROM sync_versions WHERE scope = ?1",
+            )?
+            .query_map([scope.as_str()], |r| {
+                Ok(Seen {
+                    uuid: r.get(0)?,
+                    version: r.get::<_, Option<i64>>(1)?.map(|v| v as u64),
+                    version_date: r.get(2)?,
+                })
+            })?
+            .map(|s| s.map(|s| (s.uuid.clone(), s)))
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(State {
+            conn,
+            scope,
+            stored,
+        })
+    }
+
+    /// Whether `seen` is new or newer than the last recorded version.
+    pub fn changed(&self, seen: &Seen) -> bool {
+        self.stored
+            .get(&seen.uuid)
+            .is_none_or(|stored| seen.key() > stored.key())
+    }
+
+    /// Store `seen` as the handled versions.
+    pub fn record(&mut self, seen: &[Seen]) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO sync_versions (scope, uuid, version_number, version_date, synced_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for s in seen {
+                stmt.execute(params![
+                    self.scope.as_str(),
+                    s.uuid,
+                    s.version.map(|v| v as i64),
+                    s.version_date,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()?;
+        for s in seen {
+            self.stored.insert(s.uuid.clone(), s.clone());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_newer_versions_are_changed() {
+        let db = std::env::temp_dir().join(format!("incremental_{}.db", std::process::id()));
+        let seen = |line: &str| Seen::from_line(line).unwrap();
+        let v2 = seen(r#"{"uuid":"u-1","versionNumber":2,"versionDate":"2024-05-01T10:00:00"}"#);
+        assert_eq!(v2.version, Some(2));
+        assert!(Seen::from_line(r#"{"primaryDi":"x"}"#).is_none());
+
+        let mut state = State::open(&db, Scope::Detail).unwrap();
+        assert!(state.changed(&v2));
+        state.record(std::slice::from_ref(&v2)).unwrap();
+
+        let state = State::open(&db, Scope::Detail).unwrap();
+        assert!(!state.changed(&v2));
+        assert!(state.changed(&seen(r#"{"uuid":"u-1","versionNumber":"3"}"#)));
+        assert!(state.changed(&seen(
+            r#"{"uuid":"u-1","versionNumber":2,"versionDate":"2024-06-01T08:00:00"}"#
+        )));
+        assert!(!state.changed(&seen(r#"{"uuid":"u-1","versionNumber":1}"#)));
+        assert!(State::open(&db, Scope::Listing).unwrap().changed(&v2));
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", db.display(), suffix));
+        }
+    }
+}
@@ -516,7 +516,7 @@ fn download_to_temp(
 ) -> Result<PathBuf, String> {
     // No global timeout — release artifacts can be tens of MB on a slow
     // link. ureq still applies per-read timeouts via its defaults.
-    let agent: ureq::Agent = ureq::Agent::config_builder().build().into();
+    let agent = crate::http::agent_with_timeout(url, None);
     let resp = agent
         .get(url)
         .header("User-Agent", "eudamed2firstbase-installer")
@@ -0,0 +1,182 @@
+//! GUI job queue (the "Jobs" section). Every pipeline run — download, convert,
+//! dry-run validation, push — is a job; pressing a pipeline button while one
+//! is running queues the next instead of being refused. The queue is saved in
+//! `jobs.json` in the data directory after every change, so a restart shows
+//! what ran, what was cancelled and what is still waiting: a job that was
+//! running when the app quit comes back as `Interrupted`, and restored queued
+//! jobs wait until the user starts the queue again. Cancel and pause go
+//! through the job's `push_dashboard::Monitor`.
+
+use std::path::{Path, PathBuf};
+
+/// Finished jobs kept in `jobs.json`.
+const HISTORY: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+    /// Was running when the app quit.
+    Interrupted,
+}
+
+impl JobState {
+    pub fn label(self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done => "done",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+            JobState::Interrupted => "interrupted",
+        }
+    }
+
+    pub fn is_final(self) -> bool {
+        !matches!(self, JobState::Queued | JobState::Running)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Job {
+    pub id: u64,
+    /// Pipeline mode (see `run_pipeline`).
+    pub mode: u8,
+    pub label: String,
+    /// SRN filter at the time the job was queued.
+    #[serde(default)]
+    pub srns: String,
+    /// Device UUIDs of a dropped-files job (mode 7).
+    #[serde(default)]
+    pub uuids: Vec<String>,
+    /// Workspace tab whose push dashboard shows the job.
+    #[serde(default)]
+    pub tab: usize,
+    pub state: JobState,
+    #[serde(default)]
+    pub summary: String,
+    pub queued_at: String,
+    #[serde(default)]
+    pub finished_at: String,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Queue {
+    pub jobs: Vec<Job>,
+    #[serde(default)]
+    next_id: u64,
+}
+
+pub fn queue_path() -> PathBuf {
+    crate::download::app_data_dir().join("jobs.json")
+}
+
+fn now() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl Queue {
+    /// Saved queue; a job still marked running was interrupted by a quit.
+    pub fn load(path: &Path) -> Queue {
+        let mut queue: Queue = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        for job in &mut queue.jobs {
+            if job.state == JobState::Running {
+                job.state = JobState::Interrupted;
+                job.summary = "App quit while the job was running".to_string();
+            }
+        }
+        queue
+    }
+
+    /// Write the queue, dropping the oldest finished jobs beyond `HISTORY`.
+    pub fn save(&mut self, path: &Path) {
+        let finished = self.jobs.iter().filter(|j| j.state.is_final()).count();
+        let mut excess = finished.saturating_sub(HISTORY);
+        self.jobs.retain(|j| {
+            if excess > 0 && j.state.is_final() {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+        let _ = crate::write_json(path, self);
+    }
+
+    pub fn push(
+        &mut self,
+        mode: u8,
+        label: &str,
+        srns: &str,
+        uuids: Vec<String>,
+        tab: usize,
+    ) -> u64 {
+        self.next_id = self
+            .next_id
+            .max(self.jobs.iter().map(|j| j.id).max().unwrap_or(0))
+            + 1;
+        self.jobs.push(Job {
+            id: self.next_id,
+            mode,
+            label: label.to_string(),
+            srns: srns.to_string(),
+            uuids,
+            tab,
+            state: JobState::Queued,
+            summary: String::new(),
+            queued_at: now(),
+            finished_at: String::new(),
+        });
+        self.next_id
+    }
+
+    pub fn queued(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|j| j.state == JobState::Queued)
+            .count()
+    }
+
+    /// Mark the oldest queued job running and return it.
+    pub fn start_next(&mut self) -> Option<Job> {
+        let job = self.jobs.iter_mut().find(|j| j.state == JobState::Queued)?;
+        job.state = JobState::Running;
+        Some(job.clone())
+    }
+
+    pub fn finish(&mut self, id: u64, state: JobState, summary: &str) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.state = state;
+            job.summary = summary.to_string();
+            job.finished_at = now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_job_is_interrupted_after_restart() {
+        let path = std::env::temp_dir().join(format!("e2f_jobs_{}.json", std::process::id()));
+        let mut queue = Queue::default();
+        let first = queue.push(1, "convert & push", "", Vec::new(), 0);
+        queue.push(7, "dropped files", "", vec!["u1".to_string()], 0);
+        assert_eq!(queue.start_next().map(|j| j.id), Some(first));
+        queue.save(&path);
+
+        let mut restored = Queue::load(&path);
+        assert_eq!(restored.jobs[0].state, JobState::Interrupted);
+        assert_eq!(restored.queued(), 1);
+        let next = restored.start_next().unwrap();
+        assert_eq!((next.mode, next.uuids), (7, vec!["u1".to_string()]));
+        assert!(restored.push(0, "full", "", Vec::new(), 0) > next.id);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
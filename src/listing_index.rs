@@ -0,0 +1,249 @@
+//! Listing index of the detail NDJSON mode (`detail <details> [listing]`): the
+//! listing fields merged into each detail record, keyed by GTIN. Held in a
+//! `HashMap` by default; with `--spill-index` it goes to a throwaway SQLite
+//! file in `db/` instead, for machines where the index of a full listing dump
+//! does not fit in RAM. Lookups come from the conversion workers, so the
+//! spilled index keeps one read connection per rayon thread.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Context;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+
+use crate::{api_json, ndjson_input};
+
+/// Listing data we want to merge into detail-based records
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingData {
+    pub basic_udi: String,
+    pub risk_class_code: Option<String>,
+    pub manufacturer_srn: Option<String>,
+    pub manufacturer_name: Option<String>,
+    pub authorised_representative_srn: Option<String>,
+    pub authorised_representative_name: Option<String>,
+}
+
+pub enum ListingIndex {
+    Memory(HashMap<String, ListingData>),
+    Disk(Spill),
+}
+
+/// The spilled index; the file is deleted when it is dropped.
+pub struct Spill {
+    path: PathBuf,
+    len: usize,
+    readers: Vec<Mutex<Connection>>,
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        self.readers.clear();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+const SCHEMA: &str = "CREATE TABLE listing (
+    gtin TEXT PRIMARY KEY,
+    basic_udi TEXT NOT NULL,
+    risk_class_code TEXT,
+    manufacturer_srn TEXT,
+    manufacturer_name TEXT,
+    authorised_representative_srn TEXT,
+    authorised_representative_name TEXT
+) WITHOUT ROWID;";
+
+/// Parse the listing NDJSON at `path` into (GTIN, data) pairs, in parallel,
+/// handing them to `insert` in input order (so the last line for a GTIN wins).
+fn scan(
+    path: &Path,
+    mut insert: impl FnMut(String, ListingData) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let input = ndjson_input::Input::open(path)?;
+    ndjson_input::stream(
+        &input,
+        |_| false,
+        |_, trimmed| {
+            let Ok(entry) = api_json::parse_listing_index_entry(trimmed) else {
+                return Ok(None);
+            };
+            let risk_class_code = entry.risk_class_code();
+            // The entry is dropped right after: move its strings, one copy
+            // out of the line at most (none for escaped, already owned ones).
+            let owned = |s: Option<Cow<str>>| s.map(|s| s.into_owned());
+            Ok(entry.primary_di.filter(|g| !g.is_empty()).map(|gtin| {
+                (
+                    gtin.into_owned(),
+                    ListingData {
+                        basic_udi: owned(entry.basic_udi).unwrap_or_default(),
+                        risk_class_code,
+                        manufacturer_srn: owned(entry.manufacturer_srn),
+                        manufacturer_name: owned(entry.manufacturer_name),
+                        authorised_representative_srn: owned(entry.authorised_representative_srn),
+                        authorised_representative_name: owned(entry.authorised_representative_name),
+                    },
+                )
+            }))
+        },
+        |record| match record {
+            Some((gtin, listing)) => insert(gtin, listing),
+            None => Ok(()),
+        },
+    )
+}
+
+impl ListingIndex {
+    pub fn empty() -> ListingIndex {
+        ListingIndex::Memory(HashMap::new())
+    }
+
+    /// Index the listing NDJSON at `path`; in memory, or spilled to a file in
+    /// `spill_dir`.
+    pub fn load(path: &Path, spill_dir: Option<&Path>) -> anyhow::Result<ListingIndex> {
+        let Some(dir) = spill_dir else {
+            let mut index = HashMap::new();
+            scan(path, |gtin, listing| {
+                index.insert(gtin, listing);
+                Ok(())
+            })?;
+            return Ok(ListingIndex::Memory(index));
+        };
+
+        std::fs::create_dir_all(dir)?;
+        let db_path = dir.join(format!("listing_index_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        let mut conn = Connection::open(&db_path)
+            .with_context(|| format!("Failed to create {}", db_path.display()))?;
+        // A throwaway file: no journal, no fsync.
+        conn.execute_batch("PRAGMA journal_mode=OFF; PRAGMA synchronous=OFF;")?;
+        conn.execute_batch(SCHEMA)?;
+        let tx = conn.transaction()?;
+        {
+            let mut stmt =
+                tx.prepare("INSERT OR REPLACE INTO listing VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+            scan(path, |gtin, l| {
+                stmt.execute(params![
+                    gtin,
+                    l.basic_udi,
+                    l.risk_class_code,
+                    l.manufacturer_srn,
+                    l.manufacturer_name,
+                    l.authorised_representative_srn,
+                    l.authorised_representative_name,
+                ])?;
+                Ok(())
+            })?;
+        }
+        tx.commit()?;
+        let len: i64 = conn.query_row("SELECT COUNT(*) FROM listing", [], |r| r.get(0))?;
+        drop(conn);
+
+        let readers = (0..=rayon::current_num_threads())
+            .map(|_| {
+                Connection::open_with_flags(
+                    &db_path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )
+                .map(Mutex::new)
+            })
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ListingIndex::Disk(Spill {
+            path: db_path,
+            len: len as usize,
+            readers,
+        }))
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ListingIndex::Memory(index) => index.len(),
+            ListingIndex::Disk(spill) => spill.len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, gtin: &str) -> Option<Cow<'_, ListingData>> {
+        match self {
+            ListingIndex::Memory(index) => index.get(gtin).map(Cow::Borrowed),
+            ListingIndex::Disk(spill) => {
+                // Each rayon worker has its own connection; 0 is the caller's.
+                let slot = rayon::current_thread_index().map_or(0, |i| i + 1);
+                let conn = spill.readers[slot % spill.readers.len()]
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner());
+                conn.prepare_cached("SELECT * FROM listing WHERE gtin = ?1")
+                    .and_then(|mut stmt| {
+                        stmt.query_row([gtin], |r| {
+                            Ok(ListingData {
+                                basic_udi: r.get(1)?,
+                                risk_class_code: r.get(2)?,
+                                manufacturer_srn: r.get(3)?,
+                                manufacturer_name: r.get(4)?,
+                                authorised_representative_srn: r.get(5)?,
+                                authorised_representative_name: r.get(6)?,
+                            })
+                        })
+                        .optional()
+                    })
+                    .ok()
+                    .flatten()
+                    .map(Cow::Owned)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spilled_index_matches_the_in_memory_one() {
+        let dir = std::env::temp_dir().join(format!("e2f_listing_index_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("listing.ndjson");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"primaryDi":"07612345000001","basicUdi":"B1","manufacturerSrn":"CH-MF-1"}"#,
+                "\n",
+                r#"{"primaryDi":"07612345000002","riskClass":{"code":"refdata.risk-class.class-iia"}}"#,
+                "\n",
+                r#"{"primaryDi":"","basicUdi":"ignored"}"#,
+                "\n",
+                r#"{"primaryDi":"07612345000001","basicUdi":"B1-new"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let memory = ListingIndex::load(&path, None).unwrap();
+        let disk = ListingIndex::load(&path, Some(&dir)).unwrap();
+        assert_eq!((memory.len(), disk.len()), (2, 2));
+        for gtin in ["07612345000001", "07612345000002", "07612345000003"] {
+            assert_eq!(memory.get(gtin), disk.get(gtin));
+        }
+        let first = disk.get("07612345000001").unwrap();
+        assert_eq!(
+            (first.basic_udi.as_str(), first.manufacturer_srn.as_deref()),
+            ("B1-new", None)
+        );
+        assert_eq!(
+            disk.get("07612345000002")
+                .unwrap()
+                .risk_class_code
+                .as_deref(),
+            Some("CLASS_IIA")
+        );
+
+        drop(disk);
+        std::fs::remove_file(&path).unwrap();
+        // The spill file is gone with the index.
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+//! GUI log console: the pipeline log as structured entries (severity,
+//! pipeline step, device) with filters and export. Log lines stay plain
+//! strings everywhere else; `classify` derives the structure from the
+//! conventions the pipeline already follows (`[Push]`/`[Convert]` prefixes,
+//! `WARNING`/`error`/`REJECTED` wording, device UUIDs and GTINs in the text).
+//!
+//! Converter warnings used to go to stderr only, which the GUI worker
+//! discards. `diag` sends them to the console when the current thread has a
+//! sink installed (`capture`), and to stderr otherwise (CLI).
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Error];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Download,
+    Convert,
+    Push,
+    Other,
+}
+
+impl Step {
+    pub const ALL: [Step; 4] = [Step::Download, Step::Convert, Step::Push, Step::Other];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Step::Download => "download",
+            Step::Convert => "convert",
+            Step::Push => "push",
+            Step::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Local time the line reached the console.
+    pub time: String,
+    pub severity: Severity,
+    pub step: Step,
+    /// First device UUID or GTIN in the line.
+    pub device: String,
+    pub text: String,
+}
+
+/// A 36-char UUID or a 14-digit GTIN in `text`.
+fn device_of(text: &str) -> String {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .find(|w| {
+            (w.len() == 36
+                && w.matches('-').count() == 4
+                && w.chars().all(|c| c.is_ascii_hexdigit() || c == '-'))
+                || (w.len() == 14 && w.chars().all(|c| c.is_ascii_digit()))
+        })
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Structure of one log line; `step` is the step of the previous line when
+/// the line carries no marker itself (indented continuation lines).
+pub fn classify(line: &str, step: Step) -> Entry {
+    // "(12 accepted, 0 errors)" is a success.
+    let lower = line.to_lowercase().replace(" 0 error", "");
+    let severity = if line.contains("=== FAILED")
+        || line.contains("REJECTED")
+        || line.contains("TRANSPORT FAILURE")
+        || lower.contains("error")
+        || lower.contains("failed")
+        || lower.contains("panicked")
+    {
+        Severity::Error
+    } else if lower.contains("warning")
+        || lower.contains("warn ")
+        || lower.contains("cancelled")
+        || lower.contains("missing")
+        || lower.contains("skipping")
+    {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+    let step =
+        if line.starts_with("[Push]") || line.contains("CreateMany") || line.contains("AddMany") {
+            Step::Push
+        } else if line.starts_with("[Convert]") || lower.starts_with("converted") {
+            Step::Convert
+        } else if line.starts_with("[Download]")
+            || line.starts_with("[Listing]")
+            || line.starts_with("[GTIN]")
+            || lower.starts_with("details:")
+            || lower.starts_with("basic udi-di:")
+        {
+            Step::Download
+        } else if line.starts_with(' ') {
+            step
+        } else {
+            Step::Other
+        };
+    Entry {
+        time: chrono::Local::now().format("%H:%M:%S").to_string(),
+        severity,
+        step,
+        device: device_of(line),
+        text: line.to_string(),
+    }
+}
+
+/// What the console shows.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub info: bool,
+    pub warning: bool,
+    pub error: bool,
+    pub step: Option<Step>,
+    /// Case-insensitive substring of the text.
+    pub text: String,
+}
+
+impl Default for Filter {
+    fn default() -> Filter {
+        Filter {
+            info: true,
+            warning: true,
+            error: true,
+            step: None,
+            text: String::new(),
+        }
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let severity = match entry.severity {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Error => self.error,
+        };
+        severity
+            && self.step.is_none_or(|s| s == entry.step)
+            && (self.text.is_empty()
+                || entry
+                    .text
+                    .to_lowercase()
+                    .contains(&self.text.to_lowercase()))
+    }
+}
+
+/// Classified copy of the log, appended to as lines arrive.
+#[derive(Debug, Default)]
+pub struct Console {
+    pub entries: Vec<Entry>,
+    pub filter: Filter,
+    /// Result of the last export.
+    pub status: String,
+}
+
+impl Console {
+    /// Classify the lines added since the last call; starts over when the
+    /// log was cleared (new pipeline run).
+    pub fn sync(&mut self, lines: &[String]) {
+        if lines.len() < self.entries.len() {
+            self.entries.clear();
+        }
+        let mut step = self.entries.last().map_or(Step::Other, |e| e.step);
+        for line in &lines[self.entries.len()..] {
+            let entry = classify(line, step);
+            step = entry.step;
+            self.entries.push(entry);
+        }
+    }
+
+    /// Indices of the entries the filter lets through.
+    pub fn visible(&self) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|&i| self.filter.matches(&self.entries[i]))
+            .collect()
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.severity == severity)
+            .count()
+    }
+
+    /// Write the visible entries as TSV (time, severity, step, device, text).
+    pub fn export(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "console_{}.tsv",
+            chrono::Local::now().format("%Y-%m-%d_%H%M%S")
+        ));
+        let mut f = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        writeln!(f, "time\tseverity\tstep\tdevice\ttext")?;
+        for i in self.visible() {
+            let e = &self.entries[i];
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}",
+                e.time,
+                e.severity.label(),
+                e.step.label(),
+                e.device,
+                e.text.replace('\t', " ")
+            )?;
+        }
+        Ok(path)
+    }
+}
+
+type Sink = Box<dyn Fn(&str)>;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Route `diag` output of this thread to `sink` while `f` runs.
+pub fn capture<R>(sink: impl Fn(&str) + 'static, f: impl FnOnce() -> R) -> R {
+    SINK.with(|s| *s.borrow_mut() = Some(Box::new(sink)));
+    let result = f();
+    SINK.with(|s| *s.borrow_mut() = None);
+    result
+}
+
+/// A converter warning: to the GUI console if this thread captures, else stderr.
+pub fn diag(message: &str) {
+    let sent = SINK.with(|s| match &*s.borrow() {
+        Some(sink) => {
+            sink(message);
+            true
+        }
+        None => false,
+    });
+    if !sent {
+        eprintln!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuation_lines_inherit_the_step() {
+        let lines: Vec<String> = [
+            "[Push] CreateMany batch 1: items 1-2 of 2",
+            "  REJECTED 07612345678901 097.020 tradeItemCountryOfSale: missing",
+            "  Poll 3: DONE (1 accepted, 0 errors)",
+            "Converted: 3 to GS1 firstbase JSON",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let mut console = Console::default();
+        console.sync(&lines);
+        let e = &console.entries;
+        assert_eq!((e[1].severity, e[1].step), (Severity::Error, Step::Push));
+        assert_eq!(e[1].device, "07612345678901");
+        assert_eq!((e[2].severity, e[2].step), (Severity::Info, Step::Push));
+        assert_eq!(e[3].step, Step::Convert);
+
+        console.filter.step = Some(Step::Push);
+        console.filter.info = false;
+        assert_eq!(console.visible(), vec![1]);
+        console.sync(&lines[..1]);
+        assert_eq!(console.entries.len(), 1);
+    }
+}
@@ -7,6 +7,9 @@
 use anyhow::{Context, Result};
 use std::process::Command;
 
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const SEND_URL: &str = "https://www.googleapis.com/gmail/v1/users/me/messages/send";
+
 /// MIME content type for a file name, by extension.
 fn content_type_for(file_name: &str) -> &'static str {
     let n = file_name.to_ascii_lowercase();
@@ -124,13 +127,10 @@ pub fn send_email_with_attachments(
     let encoded_message = url_engine.encode(raw_email.as_bytes());
     let payload = serde_json::json!({ "raw": encoded_message });
 
-    let agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .build()
-        .new_agent();
+    let agent = crate::http::agent_for(SEND_URL);
 
     let mut resp = agent
-        .post("https://www.googleapis.com/gmail/v1/users/me/messages/send")
+        .post(SEND_URL)
         .header("Authorization", &format!("Bearer {}", token))
         .header("Content-Type", "application/json")
         .send(payload.to_string().as_bytes())?;
@@ -252,7 +252,7 @@ fn get_gmail_access_token(pem_key: &str, service_email: &str, sub_email: &str) -
     let claims = Claims {
         iss: service_email.to_string(),
         scope: "https://www.googleapis.com/auth/gmail.send".to_string(),
-        aud: "https://oauth2.googleapis.com/token".to_string(),
+        aud: TOKEN_URL.to_string(),
         iat: now,
         exp: now + 3600,
         sub: sub_email.to_string(),
@@ -262,10 +262,7 @@ fn get_gmail_access_token(pem_key: &str, service_email: &str, sub_email: &str) -
     let key = EncodingKey::from_rsa_pem(pem_key.as_bytes())?;
     let jwt = encode(&header, &claims, &key)?;
 
-    let agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .build()
-        .new_agent();
+    let agent = crate::http::agent_for(TOKEN_URL);
 
     let form_body = format!(
         "grant_type={}&assertion={}",
@@ -273,7 +270,7 @@ fn get_gmail_access_token(pem_key: &str, service_email: &str, sub_email: &str) -
     );
 
     let mut resp = agent
-        .post("https://oauth2.googleapis.com/token")
+        .post(TOKEN_URL)
         .header("Content-Type", "application/x-www-form-urlencoded")
         .send(form_body.as_bytes())?;
 
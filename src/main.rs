@@ -1,36 +1,105 @@
 // Hide console window on Windows when running as GUI
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod actor_contacts;
 mod actors;
 mod api_detail;
 mod api_json;
+mod avp;
+mod backup;
+mod barcode;
+mod basic_udi_fetch;
+mod certificates;
+mod commands;
 mod config;
+mod credentials;
+mod dates;
+mod dedup;
+mod delta;
+mod device_category;
+mod device_filter;
+mod device_table;
+mod discontinued;
+mod doctor;
 mod download;
+mod dump;
 mod eudamed;
 mod eudamed_json;
+mod fetch_pool;
 mod firstbase;
+mod gpc;
+mod gtin;
 mod gui;
+mod html_export;
+mod http;
+mod http_cache;
+mod i18n;
+mod incremental;
 mod installer;
+mod jobs;
+mod listing_index;
+mod log_console;
 mod mail;
+mod maintenance;
+mod mapping_matrix;
 mod mappings;
+mod merge;
+mod mhra;
+mod ndjson_download;
+mod ndjson_input;
+mod no_gtin;
+mod notify;
+mod ordering;
+mod overrides;
+mod packaging;
+mod parties;
+mod payload_archive;
+mod platform_dirs;
+mod profile;
+mod provenance;
+mod push_batch;
+mod push_checkpoint;
+mod push_dashboard;
+mod push_env;
+mod push_history;
+mod push_lock;
+mod quarantine;
+mod readback;
+mod readiness;
+mod rejection;
+mod report;
+mod retry_queue;
 mod scan;
+mod search_index;
 mod sheet;
+mod snapshot_compare;
+mod stats;
+mod status_poll;
+mod storage;
+mod swiss_market;
 mod swissdamed;
+mod swissdamed_json;
 mod transform;
 mod transform_api;
 mod transform_detail;
 mod transform_eudamed_json;
+mod transform_swissdamed_json;
+mod tri_state;
+mod triage;
 mod update;
+mod validation;
 mod version_db;
+mod warnings;
 mod whatsapp;
+mod withdraw;
+mod workspace;
 mod xlsx_export;
 
 use anyhow::{Context, Result};
 use chrono::Local;
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::io::BufRead;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Default directory for cached Basic UDI-DI data
 const BASIC_UDI_CACHE_DIR: &str = "eudamed_json/basic";
@@ -50,10 +119,33 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    let config_path = Path::new("config.toml");
-    let config = config::load_config(config_path).context("Failed to load config.toml")?;
+    let args = platform_dirs::enter_working_dir(args);
+    let config_path = platform_dirs::config_file();
+
+    // Check config, output dirs, EUDAMED, GS1 credentials, DB schema and
+    // mapping tables; exit code 1 when a check fails. Ahead of the config
+    // load so a broken config.toml is reported, not fatal.
+    // Usage: cargo run doctor [--offline]
+    if args.get(1).map(|s| s.as_str()) == Some("doctor") {
+        let offline = args.iter().any(|a| a == "--offline");
+        if !doctor::print(&doctor::run(&config_path, offline)) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    match args.get(1).map(|s| s.as_str()) {
+    let config = config::load_config(&config_path)
+        .with_context(|| format!("Failed to load {}", config_path.display()))?;
+    // Changed-fields payloads for the EUDAMED JSON convert (see delta.rs).
+    let delta = args.iter().any(|a| a == "--delta");
+    // Don't queue re-converted devices whose document equals the pushed one.
+    let changed_only = args.iter().any(|a| a == "--changed-only");
+    // Only devices with a newer version than last time (see incremental.rs).
+    let incremental = args.iter().any(|a| a == "--incremental");
+    // Only these risk classes / legislations, at download and transform time.
+    device_filter::configure(device_filter::Filter::from_args(&args)?);
+
+    let result = match args.get(1).map(|s| s.as_str()) {
         Some("sync-srns") => {
             // Refresh the SRN worklist from the eudamed2firstbase_SRN Google Sheet.
             // Usage: cargo run sync-srns [outfile]   (default: srns_sheet.txt)
@@ -67,12 +159,7 @@ fn main() -> Result<()> {
                 .cloned()
                 .unwrap_or_else(|| "srns_sheet.txt".to_string());
 
-            let config_path = std::path::Path::new("config.toml");
-            let config_path = if config_path.exists() {
-                config_path.to_path_buf()
-            } else {
-                download::app_data_dir().join("config.toml")
-            };
+            let config_path = platform_dirs::config_file();
             let cfg = config::load_config(&config_path)?;
 
             let srns = match sheet::fetch_srns(&cfg) {
@@ -146,12 +233,7 @@ fn main() -> Result<()> {
                 .cloned()
                 .unwrap_or_else(|| "gtins_sheet.txt".to_string());
 
-            let config_path = std::path::Path::new("config.toml");
-            let config_path = if config_path.exists() {
-                config_path.to_path_buf()
-            } else {
-                download::app_data_dir().join("config.toml")
-            };
+            let config_path = platform_dirs::config_file();
             let cfg = config::load_config(&config_path)?;
 
             let gtins = match sheet::fetch_gtins(&cfg) {
@@ -252,7 +334,8 @@ fn main() -> Result<()> {
         }
         Some("check") => {
             // Check SRNs for updates, download changed, convert, and push to Firstbase
-            // Usage: cargo run check /tmp/srn_update [--threads N]
+            // Usage: cargo run check /tmp/srn_update [--threads N] [--env <name>]
+            // --env picks the firstbase environment as for `push`.
             // The SRN worklist file is OPTIONAL: it's the first positional
             // (non-flag) argument. Omit it to run a GTIN-ONLY check (then
             // --gtin-file is required) — the customer GTIN worklist is pushed on
@@ -359,14 +442,11 @@ fn main() -> Result<()> {
                     pending_uuids_file.display()
                 );
 
-                let config_path = download::app_data_dir().join("config.toml");
-                let config_path = if config_path.exists() {
-                    config_path
-                } else {
-                    std::path::PathBuf::from("config.toml")
-                };
+                let config_path = platform_dirs::config_file();
                 let fb_config = config::load_config(&config_path)?;
-                let pushed_ok = push_changed_to_firstbase(&fb_config, &uuids, &srns, &gtins)?;
+                let target = push_env::resolve(&fb_config, push_env::selected(&args).as_deref())?;
+                let pushed_ok =
+                    push_changed_to_firstbase(&fb_config, &target, Some(&uuids), &srns, &gtins)?;
                 if pushed_ok {
                     let _ = std::fs::remove_file(&pending_uuids_file);
                     eprintln!("Cleared pending push list (delivered to GS1).");
@@ -500,13 +580,9 @@ fn main() -> Result<()> {
             let detail_dir = data_dir.join("detail");
             let basic_dir = data_dir.join("basic");
 
-            let config_path = download::app_data_dir().join("config.toml");
-            let config_path = if config_path.exists() {
-                config_path
-            } else {
-                std::path::PathBuf::from("config.toml")
-            };
+            let config_path = platform_dirs::config_file();
             let fb_config = config::load_config(&config_path)?;
+            let target = push_env::resolve(&fb_config, push_env::selected(&args).as_deref())?;
 
             let basic_udi_cache = load_basic_udi_cache(&basic_dir);
             eprintln!(
@@ -545,10 +621,8 @@ fn main() -> Result<()> {
                     &device, &fb_config, basic_udi, uuid,
                 );
                 let draft_doc = firstbase::DraftItemDocument { draft_item: doc };
-                let out = serde_json::to_string_pretty(&draft_doc)
-                    .expect("Failed to serialize firstbase doc");
                 let out_path = output_dir.join(format!("{}.json", uuid));
-                let _ = std::fs::write(&out_path, &out);
+                let _ = write_json(&out_path, &draft_doc);
 
                 let mut version_rec = version_db::extract_detail_versions(&json_content);
                 version_rec.last_synced = Some(now_str.clone());
@@ -639,8 +713,13 @@ fn main() -> Result<()> {
                     }
                 }
             }
-            let pushed_ok =
-                push_changed_to_firstbase(&fb_config, &push_scope, &report_srns, &gtins)?;
+            let pushed_ok = push_changed_to_firstbase(
+                &fb_config,
+                &target,
+                Some(&push_scope),
+                &report_srns,
+                &gtins,
+            )?;
             if pushed_ok {
                 let _ = std::fs::remove_file(&pending_uuids_file);
             } else {
@@ -653,6 +732,39 @@ fn main() -> Result<()> {
             }
             Ok(())
         }
+        Some("download") if args.iter().any(|a| a == "--ndjson") => {
+            // The listing itself into ndjson/ (input of `ndjson` mode), one
+            // device per line, instead of per-device detail/Basic UDI-DI files.
+            // Usage: cargo run download --ndjson [--srn SRN ...] [--N] [--out <file>] [--incremental] [--convert]
+            // `--output-format` is checked before the download, not at `--convert`.
+            let output_format = OutputFormat::from_args(&args)?;
+            let (srns, _, limit, _) = parse_download_args(&args[2..]);
+            let out = args
+                .iter()
+                .position(|a| a == "--out")
+                .and_then(|i| args.get(i + 1))
+                .map(PathBuf::from)
+                .unwrap_or_else(ndjson_download::default_listing_path);
+            let request = ndjson_download::ListingRequest {
+                srns,
+                limit,
+                out,
+                rate_interval_ms: download::DownloadConfig::default().rate_interval_ms,
+                max_retries: 6,
+            };
+            let written = ndjson_download::download_listing(&request, &download::StderrProgress)?;
+            eprintln!("\nDone: {} devices -> {}", written, request.out.display());
+            if args.iter().any(|a| a == "--convert") {
+                eprintln!("\n=== Converting to firstbase JSON ===");
+                let dir = request
+                    .out
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                process_ndjson(dir, &config, output_format, incremental)?;
+            }
+            Ok(())
+        }
         Some("download") => {
             // Download from EUDAMED API (replaces download.sh)
             let (srns, mut gtins, limit, threads) = parse_download_args(&args[2..]);
@@ -672,10 +784,14 @@ fn main() -> Result<()> {
                 eprintln!(
                     "Usage: eudamed2firstbase download [--N] [--srn <SRN> ...] [--gtin <GTIN> ...] [--gtin-file <file>] [--threads N] [--convert]"
                 );
+                eprintln!(
+                    "       eudamed2firstbase download --ndjson [--N] [--srn <SRN> ...] [--out <file>] [--incremental] [--convert]"
+                );
                 eprintln!("  --N                Number of products per SRN (e.g. --10, --100)");
                 eprintln!("  --srn <SRN> ...    Filter by manufacturer/AR SRN(s)");
                 eprintln!("  --gtin <GTIN> ...  Fetch specific device(s) by UDI-DI primary code (GTIN); takes precedence over --srn");
                 eprintln!("  --gtin-file <file> Read GTINs from a file (one per line)");
+                eprintln!("  --ndjson           Page the listing (all devices without --srn) into ndjson/udi_di_<time>.ndjson");
                 eprintln!(
                     "  --threads N        Parallel threads for listings/lookups and downloads"
                 );
@@ -716,22 +832,118 @@ fn main() -> Result<()> {
                 eprintln!("\n=== Converting to firstbase JSON ===");
                 std::env::set_current_dir(download::app_data_dir())
                     .context("Failed to chdir to app data dir for convert")?;
-                process_eudamed_json_dir(Path::new("eudamed_json/detail"), &config)?;
+                process_eudamed_json_dir(
+                    Path::new("eudamed_json/detail"),
+                    &config,
+                    delta,
+                    changed_only,
+                )?;
+            }
+            Ok(())
+        }
+        Some("fetch-details") => {
+            // Detail record of every UUID of a listing NDJSON, one per line, for
+            // `detail` mode (see ndjson_download.rs).
+            // Usage: cargo run fetch-details <listing.ndjson> [--out <file>] [--threads N] [--incremental] [--convert]
+            let output_format = OutputFormat::from_args(&args)?;
+            let Some(listing) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: eudamed2firstbase fetch-details <listing.ndjson> [--out <file>] [--threads N] [--incremental] [--convert]");
+                std::process::exit(1);
+            };
+            let listing = PathBuf::from(listing);
+            let out = args
+                .iter()
+                .position(|a| a == "--out")
+                .and_then(|i| args.get(i + 1))
+                .map(PathBuf::from)
+                .unwrap_or_else(|| ndjson_download::default_detail_path(&listing));
+            let defaults = download::DownloadConfig::default();
+            let (_, _, _, threads) = parse_download_args(&args[2..]);
+            let request = ndjson_download::DetailRequest {
+                listing,
+                out,
+                threads: threads
+                    .or(config.download.detail_threads)
+                    .unwrap_or(defaults.detail_threads),
+                rate_interval_ms: defaults.rate_interval_ms,
+                max_retries: defaults.max_retries.max(4),
+                incremental,
+            };
+            let result = ndjson_download::download_details(&request, &download::StderrProgress)?;
+            eprintln!(
+                "\nDone: {} detail records -> {} ({} failed, {} unchanged)",
+                result.written,
+                request.out.display(),
+                result.failed.len(),
+                result.unchanged
+            );
+            if args.iter().any(|a| a == "--convert") {
+                eprintln!("\n=== Converting to firstbase JSON ===");
+                process_detail_ndjson(
+                    &request.out,
+                    Some(&request.listing),
+                    None,
+                    false,
+                    false,
+                    &config,
+                    output_format,
+                )?;
             }
             Ok(())
         }
         Some("ndjson") => {
             // Process NDJSON file(s) from ndjson/ directory (listing format)
-            let input_dir = args.get(2).map(|s| s.as_str()).unwrap_or("ndjson");
-            process_ndjson(Path::new(input_dir), &config)
+            let input_dir = args
+                .get(2)
+                .filter(|a| !a.starts_with("--"))
+                .map(|s| s.as_str())
+                .unwrap_or("ndjson");
+            process_ndjson(
+                Path::new(input_dir),
+                &config,
+                OutputFormat::from_args(&args)?,
+                incremental,
+            )
         }
         Some("firstbase") | Some("eudamed2firstbase") | Some("eudamed_json") => {
             // Convert EUDAMED JSON → GS1 Firstbase JSON
             let input_dir = args
                 .get(2)
+                .filter(|a| !a.starts_with("--"))
                 .map(|s| s.as_str())
                 .unwrap_or("eudamed_json/detail");
-            process_eudamed_json_dir(Path::new(input_dir), &config)
+            process_eudamed_json_dir(Path::new(input_dir), &config, delta, changed_only)
+        }
+        Some("ingest") => {
+            // Ingest an official EUDAMED bulk download archive (zip of JSON files)
+            // without manual extraction: each entry is classified and written to
+            // eudamed_json/detail, eudamed_json/basic, ndjson/ or xml/.
+            // Usage: cargo run ingest <archive.zip> [--convert]
+            let Some(archive) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: eudamed2firstbase ingest <archive.zip> [--convert]");
+                std::process::exit(1);
+            };
+            println!("Ingesting {}...", archive);
+            let stats = dump::ingest_archive(Path::new(archive), Path::new("."))?;
+            println!(
+                "  {} detail, {} basic, {} device-level, {} ndjson, {} xml ({} skipped, {} errors)",
+                stats.detail,
+                stats.basic,
+                stats.device,
+                stats.ndjson,
+                stats.xml,
+                stats.skipped,
+                stats.errors
+            );
+            if args.iter().any(|a| a == "--convert") {
+                process_eudamed_json_dir(
+                    Path::new("eudamed_json/detail"),
+                    &config,
+                    delta,
+                    changed_only,
+                )?;
+            }
+            Ok(())
         }
         Some("swissdamed") => {
             // Convert EUDAMED JSON → Swissdamed JSON (almost 1:1 mapping)
@@ -745,6 +957,15 @@ fn main() -> Result<()> {
                 .unwrap_or("eudamed_json/basic");
             process_swissdamed(Path::new(detail_dir), Path::new(basic_dir))
         }
+        Some("from-swissdamed") => {
+            // Convert swissdamed exports (M2M registration records) → GS1 Firstbase JSON
+            let input_dir = args
+                .get(2)
+                .filter(|a| !a.starts_with("--"))
+                .map(|s| s.as_str())
+                .unwrap_or("swissdamed_export");
+            process_swissdamed_export_dir(Path::new(input_dir), &config)
+        }
         Some("mailto") => {
             // Send one or more files as email attachments via Gmail API.
             // Credentials default to [gmail] in config.toml; --p12 overrides the key path.
@@ -967,11 +1188,9 @@ fn main() -> Result<()> {
             };
             eprintln!("Querying {} unique SRNs (10 parallel)...", unique.len());
 
-            let agent = ureq::Agent::config_builder()
-                .http_status_as_error(false)
-                .timeout_global(Some(std::time::Duration::from_secs(10)))
-                .build()
-                .new_agent();
+            let agent = http::agent_for(download::EUDAMED_BASE_URL);
+            let policy = http::policy(download::EUDAMED_BASE_URL);
+            let attempts = policy.max_retries.unwrap_or(3).max(1);
 
             let results: Vec<(String, i64)> = unique.par_iter().map(|srn| {
                 let url = format!(
@@ -979,7 +1198,7 @@ fn main() -> Result<()> {
                     srn
                 );
                 let mut count = -1i64;
-                for attempt in 1..=3 {
+                for attempt in 1..=attempts {
                     match agent.get(&url).call() {
                         Ok(mut resp) => {
                             let body = resp.body_mut().read_to_string().unwrap_or_default();
@@ -989,8 +1208,8 @@ fn main() -> Result<()> {
                                 .unwrap_or(-1);
                             break;
                         }
-                        Err(_) if attempt < 3 => {
-                            std::thread::sleep(std::time::Duration::from_secs(2 * attempt as u64));
+                        Err(_) if attempt < attempts => {
+                            std::thread::sleep(http::backoff(&policy, attempt, None));
                         }
                         Err(_) => {}
                     }
@@ -1037,7 +1256,7 @@ fn main() -> Result<()> {
             // in the app-data detail dir, writing to firstbase_json in parallel.
             // Unlike `check`, this ignores version tracking — every file is rewritten.
             let data_dir = download::app_data_dir();
-            let config_path = data_dir.join("config.toml");
+            let config_path = platform_dirs::config_file();
             let fb_config = if config_path.exists() {
                 config::load_config(&config_path)?
             } else {
@@ -1093,8 +1312,10 @@ fn main() -> Result<()> {
             // JSON itself hasn't changed but you want the new GS1 fields live
             // in Firstbase.
             //
-            // Env: FIRSTBASE_EMAIL, FIRSTBASE_PASSWORD, FIRSTBASE_PUBLISH_GLN
+            // Env: FIRSTBASE_EMAIL, FIRSTBASE_PASSWORD (else the keychain, see `login`), FIRSTBASE_PUBLISH_GLN
             //      (publish_gln falls back to config.toml's [provider].publish_gln)
+            // --env <name>: firstbase environment (test, production or a
+            //      [push.environments] entry, see push_env.rs); default FIRSTBASE_ENV, else test.
             eprintln!(
                 "eudamed2firstbase v{} — repush-srn",
                 env!("CARGO_PKG_VERSION")
@@ -1117,14 +1338,15 @@ fn main() -> Result<()> {
             } else {
                 args.iter()
                     .skip(2)
-                    .filter(|a| !a.starts_with("--"))
-                    .cloned()
+                    .zip(args.iter().skip(1))
+                    .filter(|(a, prev)| !a.starts_with("--") && *prev != "--env")
+                    .map(|(a, _)| a.clone())
                     .collect()
             };
 
             if srns.is_empty() {
-                eprintln!("Usage: eudamed2firstbase repush-srn [--reconvert|--force-reload] <SRN1> [SRN2 ...]");
-                eprintln!("   or: eudamed2firstbase repush-srn [--reconvert|--force-reload] --file <srns.txt>");
+                eprintln!("Usage: eudamed2firstbase repush-srn [--reconvert|--force-reload] [--env <name>] <SRN1> [SRN2 ...]");
+                eprintln!("   or: eudamed2firstbase repush-srn [--reconvert|--force-reload] [--env <name>] --file <srns.txt>");
                 eprintln!("   --force-reload: refetch detail + Basic UDI-DI fresh from EUDAMED (Mode 6 / StaleCleaner), implies --reconvert");
                 std::process::exit(1);
             }
@@ -1195,18 +1417,10 @@ fn main() -> Result<()> {
                 srns.len()
             );
 
-            // Target environment: FIRSTBASE_ENV=Production switches the CLI push
-            // to the GS1 production endpoint; anything else (incl. unset) = Test.
-            let fb_env = match std::env::var("FIRSTBASE_ENV").as_deref() {
-                Ok("Production") | Ok("production") | Ok("PROD") | Ok("prod") => {
-                    gui::FirstbaseEnv::Production
-                }
-                _ => gui::FirstbaseEnv::Test,
-            };
-            let env_label = match fb_env {
-                gui::FirstbaseEnv::Production => "Production",
-                gui::FirstbaseEnv::Test => "Test",
-            };
+            // Target environment: --env / FIRSTBASE_ENV (see push_env.rs);
+            // unset = Test.
+            let target = push_env::resolve(&config, push_env::selected(&args).as_deref())?;
+            let env_label = target.label.as_str();
 
             // Issue #10: skip NO_LONGER devices already ACCEPTED in this env.
             let (uuids, skipped) =
@@ -1253,7 +1467,7 @@ fn main() -> Result<()> {
             // next push picks up the latest converter logic. Bypasses
             // udi_versions cache by going straight from eudamed_json/detail/.
             if reconvert {
-                let config_path = data_dir.join("config.toml");
+                let config_path = platform_dirs::config_file();
                 let fb_config = if config_path.exists() {
                     config::load_config(&config_path)?
                 } else {
@@ -1327,42 +1541,30 @@ fn main() -> Result<()> {
             }
 
             // --- Push via gui::push_to_firstbase (same path as the GUI + `check`) ---
-            let email = std::env::var("FIRSTBASE_EMAIL").unwrap_or_default();
-            let password = std::env::var("FIRSTBASE_PASSWORD").unwrap_or_default();
-            let publish_gln = match std::env::var("FIRSTBASE_PUBLISH_GLN") {
-                Ok(v) if !v.is_empty() => v,
-                _ if !config.provider.publish_gln.is_empty() => config.provider.publish_gln.clone(),
-                _ => {
-                    eprintln!(
-                        "Set FIRSTBASE_PUBLISH_GLN or add publish_gln under [provider] in config.toml."
-                    );
-                    std::process::exit(1);
-                }
-            };
-            if email.is_empty() || password.is_empty() {
-                eprintln!("Set FIRSTBASE_EMAIL and FIRSTBASE_PASSWORD to push.");
+            if target.publish_gln.is_empty() {
+                eprintln!(
+                    "Set FIRSTBASE_PUBLISH_GLN or add publish_gln under [provider] in config.toml."
+                );
                 std::process::exit(1);
             }
-
-            let settings = gui::Settings {
-                firstbase_email: email,
-                firstbase_password: password,
-                publish_to_gln: publish_gln,
-                provider_gln: config.provider.gln.clone(),
-                firstbase_env: fb_env,
-                ..Default::default()
+            let settings = match target.login_settings() {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
             };
             eprintln!(
                 "Firstbase environment: {} ({})",
                 env_label,
-                settings.firstbase_env.api_base()
+                settings.firstbase_api_base()
             );
             let log_fn = |msg: &str| {
                 eprintln!("{}", msg);
             };
             // SRN-scoped (CLI mirror of Mode 4/5/6): push ONLY this run's UUIDs,
             // not the whole firstbase_json/ backlog of other SRNs.
-            match gui::push_to_firstbase(&settings, &log_fn, Some(&uuids)) {
+            match gui::push_to_firstbase(&settings, &log_fn, Some(&uuids), None) {
                 Ok(out) => {
                     eprintln!(
                         "\nDone: {} accepted, {} rejected.",
@@ -1376,7 +1578,7 @@ fn main() -> Result<()> {
                     }
                     // After a Production push, auto-email the GS1 report (errors-only
                     // CSV + full HTML log) to GS1. Never fail the run on a mail error.
-                    if matches!(settings.firstbase_env, gui::FirstbaseEnv::Production) {
+                    if settings.firstbase_label() == gui::FirstbaseEnv::Production.label() {
                         // repush-srn is SRN-scoped — no GTIN worklist.
                         if let Err(e) =
                             send_gs1_prod_report(&config, out.accepted, out.rejected, &srns, &[])
@@ -1391,6 +1593,286 @@ fn main() -> Result<()> {
             }
             Ok(())
         }
+        Some("push") => {
+            // Push given firstbase documents without the GUI: CreateMany +
+            // RequestStatus polling through gui::push_to_firstbase, scoped to
+            // these files. Files outside firstbase_json/ are copied in first, so
+            // accepted ones move to processed/ and rejected ones stay for retry
+            // like after any other push. Credentials as for `check`
+            // (FIRSTBASE_EMAIL / FIRSTBASE_PASSWORD or `login`). Exits 1 unless
+            // every batch was delivered.
+            // Usage: cargo run push <file.json> [file2.json ...]
+            //        cargo run push --file <list.txt>   (one path per line)
+            //   --env <name> firstbase environment: test, production or a
+            //                [push.environments] entry (see push_env.rs);
+            //                default FIRSTBASE_ENV, else test.
+            //   --dry-run    plan, validate and write the CreateMany payloads to
+            //                --out <dir> (default log/dry_run/<time>/) instead of
+            //                pushing; no credentials needed, nothing is moved.
+            //                Exits 1 when a device has validation findings.
+            //   --retry      (also) push the devices rejected before and queued
+            //                for retry in this environment (see retry_queue.rs).
+            //   --resume     continue the push to this environment that stopped
+            //                midway (crash, sleep, network loss) from its
+            //                checkpoint: answered chunks are not sent again
+            //                (see push_checkpoint.rs); no files needed.
+            //   --readback   then read the accepted devices back from the
+            //                Product API and report the fields firstbase
+            //                dropped, normalized or changed (see `readback`);
+            //                exits 1 on a dropped or changed field.
+            let mut files: Vec<String> = Vec::new();
+            let mut skip_next = false;
+            for a in args.iter().skip(2) {
+                if skip_next {
+                    skip_next = false;
+                } else if a == "--file" || a == "--out" || a == "--env" {
+                    skip_next = true;
+                } else if !a.starts_with("--") {
+                    files.push(a.clone());
+                }
+            }
+            if let Some(list) = args
+                .iter()
+                .position(|a| a == "--file")
+                .and_then(|i| args.get(i + 1))
+            {
+                files.extend(
+                    std::fs::read_to_string(list)
+                        .with_context(|| format!("Failed to read {}", list))?
+                        .lines()
+                        .map(|l| l.trim().to_string())
+                        .filter(|l| !l.is_empty() && !l.starts_with('#')),
+                );
+            }
+            let target = push_env::resolve(&config, push_env::selected(&args).as_deref())?;
+            if args.iter().any(|a| a == "--resume") {
+                if !push_changed_to_firstbase(&config, &target, None, &[], &[])? {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            let retry = args.iter().any(|a| a == "--retry");
+            if retry {
+                let queued = retry_push_files(&target.label)?;
+                if queued.is_empty() && files.is_empty() {
+                    println!("Retry queue for {} is empty.", target.label);
+                    return Ok(());
+                }
+                eprintln!("{} queued device(s) to retry", queued.len());
+                files.extend(queued);
+            }
+            if files.is_empty() {
+                eprintln!(
+                    "Usage: eudamed2firstbase push [--env <name>] [--dry-run [--out <dir>]] [--retry] [--readback] <file.json> [file2.json ...]"
+                );
+                eprintln!("   or: eudamed2firstbase push [--env <name>] --resume");
+                eprintln!(
+                    "   or: eudamed2firstbase push [--env <name>] [--dry-run [--out <dir>]] [--retry] [--readback] --file <list.txt>"
+                );
+                std::process::exit(1);
+            }
+            if args.iter().any(|a| a == "--dry-run") {
+                let out = args
+                    .iter()
+                    .position(|a| a == "--out")
+                    .and_then(|i| args.get(i + 1))
+                    .map(std::path::PathBuf::from)
+                    .unwrap_or_else(|| {
+                        download::app_data_dir()
+                            .join("log")
+                            .join("dry_run")
+                            .join(Local::now().format("%H.%M_%d.%m.%Y").to_string())
+                    });
+                let settings = target.settings();
+                eprintln!(
+                    "Dry run against {} ({}) — nothing is sent",
+                    target.label, target.api_base
+                );
+                let paths: Vec<std::path::PathBuf> =
+                    files.iter().map(std::path::PathBuf::from).collect();
+                let outcome =
+                    gui::dry_run_firstbase(&settings, &paths, &out, &|msg| eprintln!("{}", msg))?;
+                println!(
+                    "Dry run: {} would Add, {} would Change, {} invalid, {} skipped — payloads in {}",
+                    outcome.add,
+                    outcome.change,
+                    outcome.invalid,
+                    outcome.skipped,
+                    out.display()
+                );
+                if outcome.invalid > 0 {
+                    anyhow::bail!("Dry run: {} device(s) with findings", outcome.invalid);
+                }
+                return Ok(());
+            }
+            let firstbase_dir = download::app_data_dir().join("firstbase_json");
+            std::fs::create_dir_all(&firstbase_dir)?;
+            let mut uuids = std::collections::HashSet::new();
+            for file in &files {
+                uuids.insert(stage_push_file(Path::new(file), &firstbase_dir)?);
+            }
+            eprintln!(
+                "{} document(s) staged in {}",
+                uuids.len(),
+                firstbase_dir.display()
+            );
+            let started = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let delivered = push_changed_to_firstbase(&config, &target, Some(&uuids), &[], &[])?;
+            if args.iter().any(|a| a == "--readback") {
+                let ids: Vec<String> = uuids.into_iter().collect();
+                let outcome =
+                    readback::run(&target.login_settings()?, &ids, Some(&started), &|msg| {
+                        eprintln!("{}", msg)
+                    })?;
+                if outcome.failed() {
+                    std::process::exit(1);
+                }
+            }
+            if !delivered {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some("readback") => {
+            // Read accepted devices back from the firstbase Product API and
+            // diff them against the documents as pushed: fields firstbase
+            // dropped, normalized or changed (see readback.rs). Results in the
+            // `readback` table. Credentials and --env as for `push`. Exits 1
+            // when a device is missing or lost / changed a field.
+            // Usage: cargo run readback [--env <name>]          (latest push)
+            //        cargo run readback [--env <name>] <uuid|gtin> [...]
+            let mut ids: Vec<String> = Vec::new();
+            let mut skip_next = false;
+            for a in args.iter().skip(2) {
+                if skip_next {
+                    skip_next = false;
+                } else if a == "--env" {
+                    skip_next = true;
+                } else if !a.starts_with("--") {
+                    ids.push(a.clone());
+                }
+            }
+            let target = push_env::resolve(&config, push_env::selected(&args).as_deref())?;
+            let outcome = readback::run(&target.login_settings()?, &ids, None, &|msg| {
+                eprintln!("{}", msg)
+            })?;
+            if outcome.failed() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some("withdraw") => {
+            // Correct devices published in error: withdraw them from a target
+            // market (CatalogueItem/Live/WithdrawMany) or, with --unpublish,
+            // remove their publication to a recipient GLN
+            // (CatalogueItemPublication/DeleteMany). RequestStatus is polled
+            // like a push and every device gets a push_log row (WITHDRAWN /
+            // UNPUBLISHED, or *_FAILED with the GS1 codes). Credentials and
+            // --env as for `push`. Exits 1 unless all were confirmed.
+            // Usage: cargo run withdraw <uuid|file.json> [...]
+            //        cargo run withdraw --file <list.txt>   (one per line)
+            //   --unpublish            delete the publication instead of withdrawing
+            //   --gln <gln>            recipient (default FIRSTBASE_PUBLISH_GLN /
+            //                          [provider] publish_gln): the wrong GLN
+            //   --target-market <code> target market to withdraw from, when the
+            //                          pushed document carried the wrong one
+            //   --dry-run              print the request items, send nothing
+            //   --env <name>           firstbase environment (see `push`)
+            //   --yes                  required for a production environment
+            let value_of = |flag: &str| {
+                args.iter()
+                    .position(|a| a == flag)
+                    .and_then(|i| args.get(i + 1))
+                    .cloned()
+            };
+            let mut ids: Vec<String> = Vec::new();
+            let mut skip_next = false;
+            for a in args.iter().skip(2) {
+                if skip_next {
+                    skip_next = false;
+                } else if a == "--file" || a == "--gln" || a == "--target-market" || a == "--env" {
+                    skip_next = true;
+                } else if !a.starts_with("--") {
+                    ids.push(a.clone());
+                }
+            }
+            if let Some(list) = value_of("--file") {
+                ids.extend(
+                    std::fs::read_to_string(&list)
+                        .with_context(|| format!("Failed to read {}", list))?
+                        .lines()
+                        .map(|l| l.trim().to_string())
+                        .filter(|l| !l.is_empty() && !l.starts_with('#')),
+                );
+            }
+            if ids.is_empty() {
+                eprintln!(
+                    "Usage: eudamed2firstbase withdraw [--unpublish] [--gln <gln>] [--target-market <code>] [--env <name>] [--dry-run] [--yes] <uuid|file.json> [...]"
+                );
+                eprintln!("   or: eudamed2firstbase withdraw [options] --file <list.txt>");
+                std::process::exit(1);
+            }
+            let kind = if args.iter().any(|a| a == "--unpublish") {
+                withdraw::Kind::Unpublish
+            } else {
+                withdraw::Kind::Withdraw
+            };
+            let firstbase_dir = download::app_data_dir().join("firstbase_json");
+            let target_market = value_of("--target-market");
+            let targets = ids
+                .iter()
+                .map(|id| {
+                    let path = if Path::new(id).is_file() {
+                        PathBuf::from(id)
+                    } else {
+                        withdraw::document(&firstbase_dir, id).ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "No firstbase document for {} in {}",
+                                id,
+                                firstbase_dir.display()
+                            )
+                        })?
+                    };
+                    withdraw::target(&path, target_market.as_deref())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut env = push_env::resolve(&config, push_env::selected(&args).as_deref())?;
+            if let Some(gln) = value_of("--gln").filter(|g| !g.is_empty()) {
+                env.publish_gln = gln;
+            }
+            if env.publish_gln.is_empty() {
+                anyhow::bail!(
+                    "No recipient GLN: pass --gln, set FIRSTBASE_PUBLISH_GLN or publish_gln under [provider]"
+                );
+            }
+            if args.iter().any(|a| a == "--dry-run") {
+                for t in &targets {
+                    println!("{}", withdraw::item(kind, t, &env.gln, &env.publish_gln));
+                }
+                eprintln!(
+                    "Dry run: {} item(s) for {} ({}) — nothing sent",
+                    targets.len(),
+                    env.label,
+                    env.api_base
+                );
+                return Ok(());
+            }
+            if matches!(env.env, gui::FirstbaseEnv::Production)
+                && !args.iter().any(|a| a == "--yes")
+            {
+                anyhow::bail!(
+                    "Withdrawing {} device(s) from {}, a production environment: check with --dry-run, then repeat with --yes",
+                    targets.len(),
+                    env.label
+                );
+            }
+            let settings = env.login_settings()?;
+            let outcome = withdraw::run(&settings, kind, &targets, &|msg| eprintln!("{}", msg))?;
+            if outcome.failed > 0 {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
         Some("gs1-report") => {
             // Manually (re)send the GS1 Production push report (errors CSV +
             // devices CSV + HTML log) for the latest Production session. Mirrors
@@ -1451,8 +1933,11 @@ fn main() -> Result<()> {
             Ok(())
         }
         Some("status") => {
-            // Live snapshot of EUDAMED ingest + Firstbase push state.
+            // Live snapshot of EUDAMED ingest + Firstbase push state, plus the
+            // registry readiness per environment (persisted to gtin_readiness).
             // Reads the version DB (WAL mode, safe alongside a running `check`).
+            // Usage: cargo run status              (overview)
+            //        cargo run status <gtin|uuid>  (readiness of one device per environment)
             let data_dir = download::app_data_dir();
             let db_path = data_dir.join("db").join("version_tracking.db");
             if !db_path.exists() {
@@ -1559,84 +2044,1037 @@ fn main() -> Result<()> {
             } else {
                 println!("  last push session  (none yet)");
             }
+
+            let rows = readiness::refresh(&conn, &data_dir.join("firstbase_json"))?;
+            println!();
+            println!("[Registry readiness]");
+            for (env, counts) in readiness::counts(&rows) {
+                let stages: Vec<String> = readiness::Stage::ALL
+                    .iter()
+                    .map(|s| format!("{} {}", counts.get(s).copied().unwrap_or(0), s.label()))
+                    .collect();
+                println!("  {:<17} {}", env, stages.join(", "));
+            }
+            if let Some(key) = args.get(2) {
+                let device: Vec<&readiness::Readiness> = rows
+                    .iter()
+                    .filter(|r| &r.uuid == key || &r.gtin == key)
+                    .collect();
+                if device.is_empty() {
+                    eprintln!("No converted device with GTIN or UUID {}", key);
+                    std::process::exit(1);
+                }
+                println!();
+                println!("[{}]", key);
+                for r in device {
+                    let push = if r.push_status.is_empty() {
+                        "never pushed".to_string()
+                    } else {
+                        format!("last push {} at {}", r.push_status, r.pushed_at)
+                    };
+                    println!(
+                        "  {:<11} {:<10} {} gtin={} {}{}",
+                        r.env,
+                        r.stage.label(),
+                        r.uuid,
+                        r.gtin,
+                        push,
+                        if r.pending && r.push_status == "ACCEPTED" {
+                            ", re-converted since"
+                        } else {
+                            ""
+                        }
+                    );
+                    if !r.findings.is_empty() {
+                        println!("              findings: {}", r.findings);
+                    }
+                }
+            }
             Ok(())
         }
-        Some("scan") => {
-            // Fast parallel scan of firstbase JSON files — outputs "filepath\tGTIN" per line
-            let input_dir = args.get(2).map(|s| s.as_str()).unwrap_or("firstbase_json");
-            scan::scan_dir(Path::new(input_dir))
-        }
-        Some("xlsx") => {
-            // Convert detail NDJSON to XLSX
-            let input_file = args
-                .get(2)
-                .map(|s| s.as_str())
-                .unwrap_or("ndjson/eudamed_10k_details.ndjson");
-            let basic_udi_cache = load_basic_udi_cache(Path::new(BASIC_UDI_CACHE_DIR));
-            if !basic_udi_cache.is_empty() {
+        Some("search") => {
+            // Full-text search over the converted devices (trade names,
+            // descriptions, manufacturer/AR names + SRNs, EMDN, GTIN, references).
+            // Usage: cargo run search <words...> [--limit N]   (every word matches as a prefix)
+            //        cargo run search --reindex                (rebuild from firstbase_json/ + processed/)
+            // The index is built on first use and kept current by the EUDAMED JSON convert.
+            let data_dir = download::app_data_dir();
+            let index_path = data_dir.join(search_index::INDEX_DB_PATH);
+            let reindex = args.iter().any(|a| a == "--reindex");
+            if reindex || !index_path.exists() {
+                let count = search_index::rebuild(&data_dir.join("firstbase_json"), &index_path)?;
+                println!("Indexed {} devices -> {}", count, index_path.display());
+            }
+            let limit = args
+                .iter()
+                .position(|a| a == "--limit")
+                .and_then(|i| args.get(i + 1))
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(25);
+            let query = args
+                .iter()
+                .enumerate()
+                .skip(2)
+                .filter(|(i, a)| !a.starts_with("--") && args[i - 1] != "--limit")
+                .map(|(_, a)| a.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if query.is_empty() {
+                if !reindex {
+                    eprintln!("Usage: eudamed2firstbase search <words...> [--limit N]");
+                    eprintln!("       eudamed2firstbase search --reindex");
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            let conn = search_index::open_index(&index_path)?;
+            let hits = search_index::search(&conn, &query, limit)?;
+            for h in &hits {
                 println!(
-                    "Loaded {} Basic UDI-DI records from cache",
-                    basic_udi_cache.len()
+                    "{:<14}  {}  {}  [{}]  {}",
+                    h.gtin, h.trade_name, h.manufacturer, h.emdn, h.uuid
                 );
             }
-            let result = xlsx_export::ndjson_to_xlsx(Path::new(input_file), &basic_udi_cache)?;
-            println!("  -> {}", result);
+            println!(
+                "{} match(es) of {} indexed devices",
+                hits.len(),
+                search_index::count(&conn)?
+            );
             Ok(())
         }
-        Some("detail") => {
-            // Process detail NDJSON, optionally merging with listing data
-            let detail_file = args
-                .get(2)
-                .map(|s| s.as_str())
-                .unwrap_or("ndjson/eudamed_10k_details.ndjson");
-            let listing_file = args.get(3).map(|s| s.as_str());
-            process_detail_ndjson(Path::new(detail_file), listing_file.map(Path::new), &config)
-        }
-        Some("xml") | None => {
-            // Original XML mode (default)
-            process_xml_dir(&config)
-        }
-        Some(other) => {
-            // Check if it's a file path
-            let path = Path::new(other);
-            if path.exists() && path.extension().map(|e| e == "ndjson").unwrap_or(false) {
-                process_ndjson_file(path, &config)
-            } else if path.exists() && path.extension().map(|e| e == "xml").unwrap_or(false) {
-                let output_dir = Path::new("firstbase_json");
-                std::fs::create_dir_all(output_dir)?;
-                let output = process_xml_file(path, output_dir, &config)?;
-                println!("  -> {}", output);
-                Ok(())
-            } else {
-                eprintln!("Usage: eudamed2firstbase [xml|ndjson [dir]|detail <details.ndjson> [listing.ndjson]|eudamed_json [dir]]");
-                eprintln!("       eudamed2firstbase <file.ndjson>");
-                eprintln!("       eudamed2firstbase <file.xml>");
+        Some("compare-snapshots") => {
+            // Devices added/removed/modified between two EUDAMED downloads,
+            // grouped by manufacturer (monthly CH-REP overview).
+            // Usage: cargo run compare-snapshots <old_dir> <new_dir> [--csv] [--out <file>]
+            //   each dir: a snapshot root with detail/ (+ basic/), or a flat dir of <uuid>.json
+            //   default: <data dir>/log/snapshot_compare_<date>.txt (.csv with --csv)
+            let dirs: Vec<&String> = args
+                .iter()
+                .enumerate()
+                .skip(2)
+                .filter(|(i, a)| !a.starts_with("--") && args[i - 1] != "--out")
+                .map(|(_, a)| a)
+                .collect();
+            let [old_dir, new_dir] = dirs[..] else {
+                eprintln!("Usage: eudamed2firstbase compare-snapshots <old_dir> <new_dir> [--csv] [--out <file>]");
                 std::process::exit(1);
+            };
+            let csv = args.iter().any(|a| a == "--csv");
+            let out_path = args
+                .iter()
+                .position(|a| a == "--out")
+                .and_then(|i| args.get(i + 1))
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| {
+                    download::app_data_dir().join("log").join(format!(
+                        "snapshot_compare_{}.{}",
+                        Local::now().format("%d.%m.%Y"),
+                        if csv { "csv" } else { "txt" }
+                    ))
+                });
+            let cmp = snapshot_compare::compare(Path::new(old_dir), Path::new(new_dir))?;
+            let content = if csv {
+                snapshot_compare::render_csv(&cmp)
+            } else {
+                snapshot_compare::render_text(&cmp)
+            };
+            if let Some(parent) = out_path.parent() {
+                std::fs::create_dir_all(parent)?;
             }
+            std::fs::write(&out_path, content)
+                .with_context(|| format!("Failed to write {}", out_path.display()))?;
+            println!(
+                "{} added, {} removed, {} modified, {} unchanged ({} manufacturers) -> {}",
+                cmp.count(snapshot_compare::Change::Added),
+                cmp.count(snapshot_compare::Change::Removed),
+                cmp.count(snapshot_compare::Change::Modified),
+                cmp.unchanged,
+                cmp.by_manufacturer.len(),
+                out_path.display()
+            );
+            Ok(())
         }
-    }
-}
-
-/// After a Production push, email a report to GS1: a separate errors-only CSV
-/// (the rejected GTINs + their GS1 codes) plus the full HTML push log. If the
-/// HTML log would push the message over the size limit it is dropped and only
-/// the errors-only CSV is sent. Configurable via env:
-///   GS1_REPORT_TO    (else config.toml [gs1_report] to; comma-separated)
-///   GS1_REPORT_FROM  (else config.toml [gs1_report] from)
-///   GS1_REPORT_DISABLE=1  to skip entirely
-/// No mail addresses are hardcoded — they live in the gitignored config.toml.
-/// Gmail credentials come from `[gmail]` in config.toml (p12 + service account).
-/// Never fails the push: returns Err only to be logged as non-fatal by the caller.
-/// Build the Firstbase push settings (credentials from env; recipient GLN and
-/// target env from env, falling back to config.toml) and push ONLY `uuids`
-/// (scoped). On a Production push, auto-email the GS1 report. Shared by `check`
-/// and `check --push-only`.
-///
-/// Returns `Ok(true)` when the push was FULLY delivered to GS1 (every
-/// CreateMany batch got a validation verdict — even if some items were
-/// validation-rejected), and `Ok(false)` on a transport failure: a whole-push
-/// outage (503 / token / network), a config skip (missing creds/GLN), or a
-/// batch-level failure where GS1 never processed one or more batches
+        Some("history") => {
+            // Conversion history from the version DB: every version of a device
+            // the converter produced (with the run + converter version) and the
+            // fields that changed against the previous version, or the list of
+            // recent conversion runs.
+            // Usage: cargo run history <gtin|uuid>
+            //        cargo run history --runs [N]   (default: last 20 runs)
+            let db_path = download::app_data_dir()
+                .join("db")
+                .join("version_tracking.db");
+            if config.storage.is_local() && !db_path.exists() {
+                eprintln!("No DB at {}. Nothing to report yet.", db_path.display());
+                return Ok(());
+            }
+            let mut store = storage::open(&config.storage, &db_path)?;
+            if args.iter().any(|a| a == "--runs") {
+                let limit = args
+                    .iter()
+                    .position(|a| a == "--runs")
+                    .and_then(|i| args.get(i + 1))
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(20);
+                for run in store.recent_runs(limit)? {
+                    println!(
+                        "#{:<5} {} - {}  v{}  {} ({})  {} files: {} converted, {} skipped, {} errors  output {}",
+                        run.id,
+                        run.started_at,
+                        if run.finished_at.is_empty() {
+                            "unfinished"
+                        } else {
+                            &run.finished_at
+                        },
+                        run.converter_version,
+                        run.mode,
+                        run.input_dir,
+                        run.totals.input_files,
+                        run.totals.converted,
+                        run.totals.skipped,
+                        run.totals.errors,
+                        &run.totals.output_hash[..run.totals.output_hash.len().min(12)],
+                    );
+                }
+                return Ok(());
+            }
+            let Some(key) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: eudamed2firstbase history <gtin|uuid>");
+                eprintln!("       eudamed2firstbase history --runs [N]");
+                std::process::exit(1);
+            };
+            let rows = store.device_history(key)?;
+            if rows.is_empty() {
+                println!("No conversion history for {}", key);
+                return Ok(());
+            }
+            let mut previous: HashMap<String, (String, String, String)> = HashMap::new();
+            // Last stored document per UUID, for the field-level diff.
+            let mut documents: HashMap<String, serde_json::Value> = HashMap::new();
+            for (h, converter_version) in &rows {
+                let current = (
+                    h.risk_class.clone(),
+                    h.regulatory_act.clone(),
+                    h.device_status.clone(),
+                );
+                let mut notes = Vec::new();
+                if let Some(before) = previous.get(&h.uuid) {
+                    if before.0 != current.0 {
+                        notes.push(format!("risk class {} -> {}", before.0, current.0));
+                    }
+                    if before.1 != current.1 {
+                        notes.push(format!("regulation {} -> {}", before.1, current.1));
+                    }
+                    if before.2 != current.2 {
+                        notes.push(format!("status {} -> {}", before.2, current.2));
+                    }
+                }
+                println!(
+                    "{}  run #{} (v{})  {} {}  UDI v{} BUDI v{}  {} {} {}  [{}]{}",
+                    h.seen_at,
+                    h.run_id,
+                    converter_version,
+                    h.gtin,
+                    h.uuid,
+                    h.udi_version
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    h.budi_version
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    h.regulatory_act,
+                    h.risk_class,
+                    h.device_status,
+                    h.change_summary,
+                    if notes.is_empty() {
+                        String::new()
+                    } else {
+                        format!("  {}", notes.join(", "))
+                    }
+                );
+                if !h.source_file.is_empty() {
+                    println!(
+                        "    source {}{} (downloaded {})",
+                        h.source_file,
+                        h.source_line.map(|l| format!(":{}", l)).unwrap_or_default(),
+                        if h.downloaded_at.is_empty() {
+                            "n/a"
+                        } else {
+                            &h.downloaded_at
+                        }
+                    );
+                }
+                let document = h
+                    .document
+                    .as_deref()
+                    .and_then(version_db::decompress_json)
+                    .and_then(|j| serde_json::from_str::<serde_json::Value>(&j).ok());
+                match (documents.get(&h.uuid), &document) {
+                    (Some(old), Some(new)) => {
+                        for (field, before, after) in delta::changed_fields(old, new) {
+                            // Sync dates change on every conversion.
+                            if !field.contains("TradeItemSynchronisationDates") {
+                                println!("    ~ {}: {} -> {}", field, before, after);
+                            }
+                        }
+                    }
+                    _ if previous.contains_key(&h.uuid) => {
+                        println!("    (no stored document, no field diff)");
+                    }
+                    _ => {}
+                }
+                if let Some(document) = document {
+                    documents.insert(h.uuid.clone(), document);
+                } else {
+                    documents.remove(&h.uuid);
+                }
+                previous.insert(h.uuid.clone(), current);
+            }
+            Ok(())
+        }
+        Some("maintenance") => {
+            // Apply the [retention] periods from config.toml (old downloads,
+            // logs, pushed documents, push_log/device_history rows, orphaned
+            // provenance sidecars) and vacuum the SQLite databases.
+            // Usage: cargo run maintenance [--dry-run]
+            let dry_run = args.iter().any(|a| a == "--dry-run");
+            let data_dir = download::app_data_dir();
+            let outcome = maintenance::run(&data_dir, &config.retention, dry_run)?;
+            let verb = if dry_run { "would delete" } else { "deleted" };
+            let mb = |bytes: u64| bytes as f64 / 1_048_576.0;
+            for p in &outcome.pruned {
+                if p.days == 0 {
+                    println!("{}: kept forever", p.label);
+                } else {
+                    println!(
+                        "{} older than {} days: {} {} files ({:.1} MB)",
+                        p.label,
+                        p.days,
+                        verb,
+                        p.files,
+                        mb(p.bytes)
+                    );
+                }
+            }
+            println!(
+                "Orphaned provenance sidecars: {} {}",
+                verb, outcome.orphaned_sidecars
+            );
+            for (label, days, rows) in [
+                (
+                    "push_log",
+                    config.retention.push_log_days,
+                    outcome.push_log_rows,
+                ),
+                (
+                    "device_history",
+                    config.retention.history_days,
+                    outcome.history_rows,
+                ),
+            ] {
+                if days == 0 {
+                    println!("{} rows: kept forever", label);
+                } else {
+                    println!("{} rows older than {} days: {} {}", label, days, verb, rows);
+                }
+            }
+            if outcome.payload_blobs > 0 {
+                println!(
+                    "Archived push payloads no longer referenced: deleted {}",
+                    outcome.payload_blobs
+                );
+            }
+            for v in &outcome.vacuumed {
+                println!(
+                    "Vacuumed {}: {:.1} MB -> {:.1} MB",
+                    v.path.display(),
+                    mb(v.before),
+                    mb(v.after)
+                );
+            }
+            if dry_run {
+                println!("Dry run: nothing deleted, databases not vacuumed");
+            }
+            Ok(())
+        }
+        Some("login") => {
+            // Store GS1 firstbase / swissdamed credentials in the platform
+            // keychain (macOS Keychain, Windows Credential Manager, Secret Service).
+            // Used by the GUI and CLI pushes when the env vars are not set.
+            // Usage: cargo run login [firstbase|swissdamed] [--from-env]
+            //   --from-env  take FIRSTBASE_EMAIL/FIRSTBASE_PASSWORD (or SWISSDAMED_CLIENT_ID/_SECRET)
+            let kind = match args.get(2).filter(|a| !a.starts_with("--")) {
+                None => credentials::Kind::Firstbase,
+                Some(k) => {
+                    match credentials::Kind::parse(k) {
+                        Some(kind) => kind,
+                        None => {
+                            eprintln!("Usage: eudamed2firstbase login [firstbase|swissdamed] [--from-env]");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            };
+            let cred = if args.iter().any(|a| a == "--from-env") {
+                let (id_var, secret_var) = kind.env_vars();
+                credentials::Credential {
+                    id: std::env::var(id_var).unwrap_or_default(),
+                    secret: std::env::var(secret_var).unwrap_or_default(),
+                }
+            } else {
+                let (id_prompt, secret_prompt) = kind.prompts();
+                eprint!("{} {}: ", kind.label(), id_prompt);
+                std::io::Write::flush(&mut std::io::stderr())?;
+                let mut id = String::new();
+                std::io::stdin().read_line(&mut id)?;
+                credentials::Credential {
+                    id: id.trim().to_string(),
+                    secret: rpassword::prompt_password(format!(
+                        "{} {}: ",
+                        kind.label(),
+                        secret_prompt
+                    ))?,
+                }
+            };
+            if !cred.is_complete() {
+                let (id_prompt, secret_prompt) = kind.prompts();
+                eprintln!("Both {} and {} are required.", id_prompt, secret_prompt);
+                std::process::exit(1);
+            }
+            credentials::store(kind, &cred)?;
+            println!(
+                "Stored {} credentials for {} in the keychain",
+                kind.label(),
+                cred.id
+            );
+            Ok(())
+        }
+        Some("logout") => {
+            // Remove stored credentials from the keychain.
+            // Usage: cargo run logout [firstbase|swissdamed|--all]
+            let kinds: Vec<credentials::Kind> = match args.get(2).map(|a| a.as_str()) {
+                None => vec![credentials::Kind::Firstbase],
+                Some("--all") => credentials::Kind::ALL.to_vec(),
+                Some(k) => match credentials::Kind::parse(k) {
+                    Some(kind) => vec![kind],
+                    None => {
+                        eprintln!("Usage: eudamed2firstbase logout [firstbase|swissdamed|--all]");
+                        std::process::exit(1);
+                    }
+                },
+            };
+            for kind in kinds {
+                if credentials::delete(kind)? {
+                    println!("Removed {} credentials from the keychain", kind.label());
+                } else {
+                    println!("No {} credentials stored", kind.label());
+                }
+            }
+            Ok(())
+        }
+        Some("backup") => {
+            // Bundle config.toml, settings.json, the SQLite databases (push_log,
+            // push sessions, conversion history, payload archive) and overrides/
+            // into one zip, for moving to another machine or recovering after disk loss.
+            // Usage: cargo run backup [out.zip]
+            //   default: <data dir>/backups/eudamed2firstbase_backup_<timestamp>.zip
+            let data_dir = download::app_data_dir();
+            let out = args
+                .get(2)
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| {
+                    data_dir.join("backups").join(format!(
+                        "eudamed2firstbase_backup_{}.zip",
+                        chrono::Local::now().format("%Y%m%d_%H%M%S")
+                    ))
+                });
+            let manifest = backup::create(&data_dir, &config_path, &out)?;
+            for e in &manifest.files {
+                println!("  {:<40} {:>12} bytes", e.path, e.size);
+            }
+            println!(
+                "Backed up {} files to {}",
+                manifest.files.len(),
+                out.display()
+            );
+            if manifest.files.iter().any(|e| e.path == "config.toml") {
+                println!("The archive contains the credentials from config.toml — store it safely");
+            }
+            Ok(())
+        }
+        Some("restore") => {
+            // Restore a `backup` archive into the data directory. Verifies every
+            // checksum first; refuses to overwrite differing files without --force.
+            // Usage: cargo run restore <backup.zip> [--force]
+            let Some(archive) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: eudamed2firstbase restore <backup.zip> [--force]");
+                std::process::exit(1);
+            };
+            let force = args.iter().any(|a| a == "--force");
+            let data_dir = download::app_data_dir();
+            let manifest = backup::restore(Path::new(archive), &data_dir, force)?;
+            println!(
+                "Restored {} files from a v{} backup of {} into {}",
+                manifest.files.len(),
+                manifest.version,
+                manifest.created_at,
+                data_dir.display()
+            );
+            if manifest.files.iter().any(|e| e.path == "config.toml") {
+                println!(
+                    "config.toml was restored to the data directory; the CLI reads {}",
+                    platform_dirs::config_file().display()
+                );
+            }
+            Ok(())
+        }
+        Some("run") => {
+            // Any action of the GUI Run button, headless: the same worker with
+            // the GUI's saved settings and credentials (see commands.rs).
+            // Usage: cargo run run <action> [--srn <SRN1,SRN2>] [--uuid-file <file>]
+            //        cargo run run validate [--uuid-file <file>]
+            //        cargo run run list
+            //   --uuid-file  one device UUID per line (convert / convert-push)
+            let action = args.get(2).map(|s| s.as_str()).unwrap_or("list");
+            let srns = args
+                .iter()
+                .position(|a| a == "--srn")
+                .and_then(|i| args.get(i + 1))
+                .cloned();
+            let uuids: Vec<String> = match args
+                .iter()
+                .position(|a| a == "--uuid-file")
+                .and_then(|i| args.get(i + 1))
+            {
+                Some(path) => std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path))?
+                    .lines()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect(),
+                None => Vec::new(),
+            };
+            if action == "list" {
+                for a in &commands::ACTIONS {
+                    let input = match a.input {
+                        commands::Input::Srns => " [--srn]",
+                        commands::Input::Uuids => " --uuid-file",
+                        commands::Input::None => "",
+                    };
+                    println!("{:<24} {}{}", a.name, a.label, input);
+                }
+                println!(
+                    "{:<24} offline findings of the pending documents [--uuid-file]",
+                    "validate"
+                );
+                return Ok(());
+            }
+            if action == "validate" {
+                // Same checks as the GUI "Pre-flight validation" panel and the
+                // Validate button of the device selection.
+                let firstbase_dir = download::app_data_dir().join("firstbase_json");
+                let failing = if uuids.is_empty() {
+                    let rules = validation::scan(&firstbase_dir);
+                    for rule in &rules {
+                        println!("{} — {} device(s)", rule.rule, rule.issues.len());
+                        for issue in &rule.issues {
+                            println!("  {} {} {}", issue.gtin, issue.uuid, issue.value);
+                        }
+                    }
+                    rules.iter().map(|r| r.issues.len()).sum()
+                } else {
+                    let mut failing = 0;
+                    for uuid in &uuids {
+                        let findings = match overrides::doc_path(&firstbase_dir, uuid) {
+                            Some(path) => commands::validate_doc(&path),
+                            None => vec!["no converted document"],
+                        };
+                        if !findings.is_empty() {
+                            failing += 1;
+                            println!("  {}: {}", uuid, findings.join("; "));
+                        }
+                    }
+                    failing
+                };
+                if failing > 0 {
+                    anyhow::bail!("Validation: {} finding(s)", failing);
+                }
+                println!("Validation: no findings");
+                return Ok(());
+            }
+            let Some(action) = commands::find(action, !uuids.is_empty()) else {
+                eprintln!(
+                    "Usage: eudamed2firstbase run <action> [--srn <SRN1,SRN2>] [--uuid-file <file>]  (actions: run list)"
+                );
+                std::process::exit(2);
+            };
+            if action.input == commands::Input::Uuids && uuids.is_empty() {
+                anyhow::bail!("run {} needs --uuid-file", action.name);
+            }
+            let summary = gui::run_headless(action.mode, srns, uuids)?;
+            println!("{}", summary);
+            Ok(())
+        }
+        Some("report") => {
+            // PDF batch summary: device counts, validation findings, last push
+            // results and unmapped codes, for attaching to a submission/deliverable.
+            // Usage: cargo run report [--out <file.pdf>] [--pending]
+            //   --pending  only the documents still awaiting push (skip processed/)
+            let data_dir = download::app_data_dir();
+            let out = args
+                .iter()
+                .position(|a| a == "--out")
+                .and_then(|i| args.get(i + 1))
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| {
+                    data_dir.join("log").join(format!(
+                        "report_{}.pdf",
+                        Local::now().format("%H.%M_%d.%m.%Y")
+                    ))
+                });
+            let include_processed = !args.iter().any(|a| a == "--pending");
+            let db_path = data_dir.join("db").join("version_tracking.db");
+            let conn = if db_path.exists() {
+                version_db::open_db(&db_path).ok()
+            } else {
+                None
+            };
+            let summary = report::collect_summary(
+                &data_dir.join("firstbase_json"),
+                include_processed,
+                conn.as_ref(),
+            );
+            report::write_pdf(&summary, &out)?;
+            println!(
+                "Report: {} devices, {} findings, {} unmapped codes -> {}",
+                summary.devices,
+                summary.findings.values().sum::<usize>(),
+                summary.unmapped.values().sum::<usize>(),
+                out.display()
+            );
+            Ok(())
+        }
+        Some("export") => {
+            // Static HTML device catalogue for stakeholders without the app:
+            // index.html with search + devices/<uuid>.html per device.
+            // Usage: cargo run export --html [out_dir] [--pending]
+            //   default out_dir: <data dir>/catalogue
+            // Same documents re-serialized for another GDSN datapool:
+            // Usage: cargo run export --profile <atrify|1worldsync|file.toml> [out_dir] [--pending]
+            //   default out_dir: <data dir>/export_<profile name>
+            // UK MHRA device registration sheet (one XLSX row per device):
+            // Usage: cargo run export --mhra [out.xlsx] [--pending]
+            //   default: <data dir>/xlsx/mhra_registration_<date>.xlsx
+            //   --pending  only the documents still awaiting push (skip processed/)
+            // Push history (push_log + session + GS1 error explanations) for archiving:
+            // Usage: cargo run export --push-log [out.csv|out.json] [--json]
+            //   default: <data dir>/log/push_log_<date>.csv (.json with --json)
+            // Raw payloads archived with a push (EUDAMED source record, Basic UDI-DI
+            // JSON, firstbase document as sent), byte-identical to the originals:
+            // Usage: cargo run export --payload <uuid|gtin> [out_dir] [--push <push_log id>]
+            //   default: the latest push -> <data dir>/payloads/<uuid>_<push_log id>/
+            let profile_arg = args
+                .iter()
+                .position(|a| a == "--profile")
+                .and_then(|i| args.get(i + 1));
+            let html = args.iter().any(|a| a == "--html");
+            let mhra = args.iter().any(|a| a == "--mhra");
+            let push_log = args.iter().any(|a| a == "--push-log");
+            let payload_arg = args
+                .iter()
+                .position(|a| a == "--payload")
+                .and_then(|i| args.get(i + 1));
+            if [
+                html,
+                mhra,
+                push_log,
+                profile_arg.is_some(),
+                payload_arg.is_some(),
+            ]
+            .iter()
+            .filter(|m| **m)
+            .count()
+                != 1
+            {
+                eprintln!("Usage: eudamed2firstbase export --html [out_dir] [--pending]");
+                eprintln!("       eudamed2firstbase export --profile <atrify|1worldsync|file.toml> [out_dir] [--pending]");
+                eprintln!("       eudamed2firstbase export --mhra [out.xlsx] [--pending]");
+                eprintln!("       eudamed2firstbase export --push-log [out.csv|out.json] [--json]");
+                eprintln!("       eudamed2firstbase export --payload <uuid|gtin> [out_dir] [--push <push_log id>]");
+                std::process::exit(1);
+            }
+            let data_dir = download::app_data_dir();
+            let firstbase_dir = data_dir.join("firstbase_json");
+            let out_arg = args
+                .iter()
+                .enumerate()
+                .skip(2)
+                .find(|(i, a)| {
+                    !a.starts_with("--")
+                        && !["--profile", "--payload", "--push"].contains(&args[i - 1].as_str())
+                })
+                .map(|(_, a)| std::path::PathBuf::from(a));
+            let include_processed = !args.iter().any(|a| a == "--pending");
+            if push_log {
+                let db_path = data_dir.join("db").join("version_tracking.db");
+                if config.storage.is_local() && !db_path.exists() {
+                    eprintln!("No DB at {}. Nothing to export yet.", db_path.display());
+                    return Ok(());
+                }
+                let json = args.iter().any(|a| a == "--json")
+                    || out_arg
+                        .as_ref()
+                        .is_some_and(|p| p.extension().is_some_and(|e| e == "json"));
+                let out_path = out_arg.unwrap_or_else(|| {
+                    data_dir.join("log").join(format!(
+                        "push_log_{}.{}",
+                        Local::now().format("%d.%m.%Y"),
+                        if json { "json" } else { "csv" }
+                    ))
+                });
+                let rows = storage::open(&config.storage, &db_path)?.push_log_rows()?;
+                push_history::write_push_log(&rows, &out_path, json)?;
+                println!(
+                    "Exported {} push log rows -> {}",
+                    rows.len(),
+                    out_path.display()
+                );
+                return Ok(());
+            }
+            if let Some(key) = payload_arg {
+                let db_path = data_dir.join(version_db::VERSION_DB_PATH);
+                if !db_path.exists() {
+                    eprintln!("No DB at {}. Nothing to export yet.", db_path.display());
+                    return Ok(());
+                }
+                let conn = version_db::open_db(&db_path)?;
+                let push_id = args
+                    .iter()
+                    .position(|a| a == "--push")
+                    .and_then(|i| args.get(i + 1))
+                    .and_then(|n| n.parse::<i64>().ok());
+                let (push_id, uuid) = match push_id {
+                    Some(id) => (id, key.clone()),
+                    None => match payload_archive::latest_push(&conn, key)? {
+                        Some((id, uuid, pushed_at, status)) => {
+                            println!(
+                                "Latest archived push of {}: #{} {} {}",
+                                key, id, pushed_at, status
+                            );
+                            (id, uuid)
+                        }
+                        None => {
+                            println!("No archived payloads for {}", key);
+                            return Ok(());
+                        }
+                    },
+                };
+                let payloads = payload_archive::load(&conn, push_id)?;
+                if payloads.is_empty() {
+                    println!("No archived payloads for push #{}", push_id);
+                    return Ok(());
+                }
+                let out_dir = out_arg.unwrap_or_else(|| {
+                    data_dir
+                        .join("payloads")
+                        .join(format!("{}_{}", uuid, push_id))
+                });
+                std::fs::create_dir_all(&out_dir)?;
+                for p in &payloads {
+                    let path = out_dir.join(format!("{}.{}", p.kind, p.extension()));
+                    std::fs::write(&path, &p.bytes)?;
+                    println!(
+                        "  {} ({} bytes, from {})",
+                        path.display(),
+                        p.bytes.len(),
+                        p.source
+                    );
+                }
+                return Ok(());
+            }
+            if mhra {
+                let out_path = out_arg.unwrap_or_else(|| {
+                    data_dir.join("xlsx").join(format!(
+                        "mhra_registration_{}.xlsx",
+                        Local::now().format("%d.%m.%Y")
+                    ))
+                });
+                let count =
+                    mhra::export_mhra(&firstbase_dir, include_processed, &config.mhra, &out_path)?;
+                println!("Exported {} devices -> {}", count, out_path.display());
+                return Ok(());
+            }
+            if let Some(name) = profile_arg {
+                let profile = profile::Profile::load(name)?;
+                let out_dir =
+                    out_arg.unwrap_or_else(|| data_dir.join(format!("export_{}", profile.name)));
+                let count =
+                    profile::export_profile(&profile, &firstbase_dir, include_processed, &out_dir)?;
+                println!(
+                    "Exported {} devices ({} profile) -> {}",
+                    count,
+                    profile.name,
+                    out_dir.display()
+                );
+                return Ok(());
+            }
+            let out_dir = out_arg.unwrap_or_else(|| data_dir.join("catalogue"));
+            let count = html_export::export_html(&firstbase_dir, include_processed, &out_dir)?;
+            println!(
+                "Exported {} devices -> {}",
+                count,
+                out_dir.join("index.html").display()
+            );
+            Ok(())
+        }
+        Some("barcode") => {
+            // Example UDI barcodes (GS1 DataMatrix / GS1-128) for labeling review.
+            // Usage: cargo run barcode <gtin> [--pi BATCH_NUMBER,EXPIRATION_DATE]
+            //        cargo run barcode --all [--pending]
+            //   --symbology datamatrix|gs1-128  (default: both)
+            //   --svg                           SVG instead of PNG
+            //   --out <dir>                     default: <data dir>/barcodes
+            // Without --pi the production identifier types are taken from the
+            // converted firstbase document carrying the GTIN.
+            let data_dir = download::app_data_dir();
+            let firstbase_dir = data_dir.join("firstbase_json");
+            let opt = |name: &str| {
+                args.iter()
+                    .position(|a| a == name)
+                    .and_then(|i| args.get(i + 1))
+            };
+            let symbologies = match opt("--symbology").map(|s| s.as_str()) {
+                None => vec![barcode::Symbology::DataMatrix, barcode::Symbology::Gs1_128],
+                Some("datamatrix") => vec![barcode::Symbology::DataMatrix],
+                Some("gs1-128") => vec![barcode::Symbology::Gs1_128],
+                Some(other) => anyhow::bail!(
+                    "Unknown --symbology '{}' (expected datamatrix or gs1-128)",
+                    other
+                ),
+            };
+            let format = if args.iter().any(|a| a == "--svg") {
+                barcode::ImageFormat::Svg
+            } else {
+                barcode::ImageFormat::Png
+            };
+            let out_dir = opt("--out")
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| data_dir.join("barcodes"));
+            let include_processed = !args.iter().any(|a| a == "--pending");
+
+            if args.iter().any(|a| a == "--all") {
+                let (written, skipped) = barcode::export_all(
+                    &firstbase_dir,
+                    include_processed,
+                    &symbologies,
+                    format,
+                    &out_dir,
+                )?;
+                println!(
+                    "Barcodes: {} devices written, {} skipped (non-GS1 GTIN) -> {}",
+                    written,
+                    skipped,
+                    out_dir.display()
+                );
+                return Ok(());
+            }
+
+            let Some(gtin) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: eudamed2firstbase barcode <gtin> [--pi TYPE,...] [--symbology datamatrix|gs1-128] [--svg] [--out <dir>]");
+                eprintln!("       eudamed2firstbase barcode --all [--pending] [--symbology ...] [--svg] [--out <dir>]");
+                std::process::exit(1);
+            };
+            let gtin14 = gtin::normalize(gtin)?;
+            let pi_types: Vec<String> = match opt("--pi") {
+                Some(list) => list
+                    .split(',')
+                    .map(|s| s.trim().to_uppercase())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                None => barcode::lookup_pi_types(&firstbase_dir, true, &gtin14)
+                    .unwrap_or_else(|| {
+                        eprintln!(
+                            "  No converted document for GTIN {} — encoding (01) only; use --pi to add production identifiers",
+                            gtin14
+                        );
+                        Vec::new()
+                    }),
+            };
+            for path in barcode::write_barcodes(&gtin14, &pi_types, &symbologies, format, &out_dir)?
+            {
+                println!("{}", path.display());
+            }
+            Ok(())
+        }
+        Some("mappings") => {
+            // Complete EUDAMED -> GS1 field and code mapping for audits and
+            // customer documentation (source path, target path, rule).
+            // Usage: cargo run mappings export [--json] [--out <file>]
+            //   default: <data dir>/mappings/mapping_matrix.csv (.json with --json)
+            if args.get(2).map(|s| s.as_str()) != Some("export") {
+                eprintln!("Usage: eudamed2firstbase mappings export [--json] [--out <file>]");
+                std::process::exit(1);
+            }
+            let json = args.iter().any(|a| a == "--json");
+            let out_path = args
+                .iter()
+                .position(|a| a == "--out")
+                .and_then(|i| args.get(i + 1))
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| {
+                    download::app_data_dir().join("mappings").join(if json {
+                        "mapping_matrix.json"
+                    } else {
+                        "mapping_matrix.csv"
+                    })
+                });
+            let count = mapping_matrix::export_mappings(&out_path, json)?;
+            println!("Exported {} mapping rows -> {}", count, out_path.display());
+            Ok(())
+        }
+        Some("scan") => {
+            // Fast parallel scan of firstbase JSON files — outputs "filepath\tGTIN" per line
+            let input_dir = args.get(2).map(|s| s.as_str()).unwrap_or("firstbase_json");
+            scan::scan_dir(Path::new(input_dir))
+        }
+        Some("xlsx") => {
+            // Convert detail NDJSON to XLSX
+            let input_file = args
+                .get(2)
+                .map(|s| s.as_str())
+                .unwrap_or("ndjson/eudamed_10k_details.ndjson");
+            let basic_udi_cache = load_basic_udi_cache(Path::new(BASIC_UDI_CACHE_DIR));
+            if !basic_udi_cache.is_empty() {
+                println!(
+                    "Loaded {} Basic UDI-DI records from cache",
+                    basic_udi_cache.len()
+                );
+            }
+            let result = xlsx_export::ndjson_to_xlsx(Path::new(input_file), &basic_udi_cache)?;
+            println!("  -> {}", result);
+            Ok(())
+        }
+        Some("detail") => {
+            // Process detail NDJSON, optionally merging with listing data
+            let detail_file = args
+                .get(2)
+                .filter(|a| !a.starts_with("--"))
+                .map(|s| s.as_str())
+                .unwrap_or("ndjson/eudamed_10k_details.ndjson");
+            let listing_file = args
+                .get(3)
+                .filter(|a| !a.starts_with("--"))
+                .map(|s| s.as_str());
+            process_detail_ndjson(
+                Path::new(detail_file),
+                listing_file.map(Path::new),
+                None,
+                args.iter().any(|a| a == "--spill-index"),
+                args.iter().any(|a| a == "--fetch-missing"),
+                &config,
+                OutputFormat::from_args(&args)?,
+            )
+        }
+        Some("merge") => {
+            // Three-way merge: listing + detail + Basic UDI-DI export per GTIN
+            let files: Vec<&str> = args[2..]
+                .iter()
+                .take_while(|a| !a.starts_with("--"))
+                .map(|s| s.as_str())
+                .collect();
+            let [detail_file, listing_file, basic_udi_file] = files[..] else {
+                eprintln!("Usage: eudamed2firstbase merge <details.ndjson> <listing.ndjson> <basic_udi.ndjson|dir> [--spill-index] [--fetch-missing] [--output-format json|ndjson]");
+                std::process::exit(1);
+            };
+            process_detail_ndjson(
+                Path::new(detail_file),
+                Some(Path::new(listing_file)),
+                Some(Path::new(basic_udi_file)),
+                args.iter().any(|a| a == "--spill-index"),
+                args.iter().any(|a| a == "--fetch-missing"),
+                &config,
+                OutputFormat::from_args(&args)?,
+            )
+        }
+        Some("xml") | None => {
+            // Original XML mode (default)
+            process_xml_dir(&config, OutputFormat::from_args(&args)?)
+        }
+        Some(other) => {
+            // Check if it's a file path
+            let path = Path::new(other);
+            if path.exists() && ndjson_input::is_ndjson(path) {
+                process_ndjson_file(
+                    path,
+                    &config,
+                    OutputFormat::from_args(&args)?,
+                    &HashSet::new(),
+                )
+            } else if path.exists() && path.extension().map(|e| e == "xml").unwrap_or(false) {
+                let output_dir = Path::new("firstbase_json");
+                std::fs::create_dir_all(output_dir)?;
+                let output =
+                    process_xml_file(path, output_dir, &config, OutputFormat::from_args(&args)?)?;
+                println!("  -> {}", output);
+                Ok(())
+            } else {
+                eprintln!("Usage: eudamed2firstbase [xml|ndjson [dir]|detail <details.ndjson> [listing.ndjson] [--spill-index] [--fetch-missing]|eudamed_json [dir]] [--output-format json|ndjson]");
+                eprintln!("       eudamed2firstbase ingest <archive.zip> [--convert]");
+                eprintln!("       eudamed2firstbase merge <details.ndjson> <listing.ndjson> <basic_udi.ndjson|dir>");
+                eprintln!("       eudamed2firstbase <file.ndjson>");
+                eprintln!("       eudamed2firstbase <file.xml>");
+                std::process::exit(1);
+            }
+        }
+    };
+    // Language tags a conversion passed through unnormalized, once per run.
+    mappings::report_unknown_languages();
+    match gpc::write_report(Path::new("firstbase_json")) {
+        Ok(Some(path)) => println!("GPC bricks per device -> {}", path.display()),
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to write GPC report: {:#}", e),
+    }
+    match warnings::write_report(Path::new("firstbase_json")) {
+        Ok(Some((path, summary))) => println!("Warnings: {} -> {}", summary, path.display()),
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to write warnings report: {:#}", e),
+    }
+    match device_category::write_report(Path::new("firstbase_json")) {
+        Ok(Some((path, count))) => println!(
+            "Excluded: {} device(s) firstbase cannot accept -> {}",
+            count,
+            path.display()
+        ),
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to write exclusion report: {:#}", e),
+    }
+    if let Some(summary) = device_filter::summary() {
+        println!("Filtered: {}", summary);
+    }
+    result
+}
+
+/// After a Production push, email a report to GS1: a separate errors-only CSV
+/// (the rejected GTINs + their GS1 codes) plus the full HTML push log. If the
+/// HTML log would push the message over the size limit it is dropped and only
+/// the errors-only CSV is sent. Configurable via env:
+///   GS1_REPORT_TO    (else config.toml [gs1_report] to; comma-separated)
+///   GS1_REPORT_FROM  (else config.toml [gs1_report] from)
+///   GS1_REPORT_DISABLE=1  to skip entirely
+/// No mail addresses are hardcoded — they live in the gitignored config.toml.
+/// Gmail credentials come from `[gmail]` in config.toml (p12 + service account).
+/// Never fails the push: returns Err only to be logged as non-fatal by the caller.
+/// Build the Firstbase push settings (credentials from env; recipient GLN and
+/// target env from env, falling back to config.toml) and push ONLY `uuids`
+/// (scoped), or with None continue the interrupted push of the environment
+/// (`push --resume`). On a Production push, auto-email the GS1 report. Shared
+/// by `check`, `check --push-only` and `push`.
+///
+/// Returns `Ok(true)` when the push was FULLY delivered to GS1 (every
+/// CreateMany batch got a validation verdict — even if some items were
+/// validation-rejected), and `Ok(false)` on a transport failure: a whole-push
+/// outage (503 / token / network), a config skip (missing creds/GLN), or a
+/// batch-level failure where GS1 never processed one or more batches
 /// (`PushOutcome::transport_failed > 0`, issue #50 / GDSN-10393). Callers use
 /// this to decide whether to clear the pending-push list; on `Ok(false)` the
 /// list is kept, and since accepted files move to `processed/`, the next
@@ -1644,49 +3082,43 @@ fn main() -> Result<()> {
 /// logged, not propagated, so a retry after an outage still exits cleanly.
 fn push_changed_to_firstbase(
     fb_config: &config::Config,
-    uuids: &std::collections::HashSet<String>,
+    target: &push_env::Target,
+    uuids: Option<&std::collections::HashSet<String>>,
     srns: &[String],
     gtin_worklist: &[String],
 ) -> anyhow::Result<bool> {
     eprintln!("\n=== Pushing to GS1 Firstbase API ===");
-    let email = std::env::var("FIRSTBASE_EMAIL").unwrap_or_default();
-    let password = std::env::var("FIRSTBASE_PASSWORD").unwrap_or_default();
-    // Env var takes priority; fall back to publish_gln from config.toml.
-    let publish_gln = match std::env::var("FIRSTBASE_PUBLISH_GLN") {
-        Ok(v) if !v.is_empty() => v,
-        _ if !fb_config.provider.publish_gln.is_empty() => fb_config.provider.publish_gln.clone(),
-        _ => {
-            eprintln!("Set FIRSTBASE_PUBLISH_GLN (recipient GLN) or add publish_gln under [provider] in config.toml. Skipping push.");
-            return Ok(false);
-        }
-    };
-    if email.is_empty() || password.is_empty() {
-        eprintln!("Set FIRSTBASE_EMAIL and FIRSTBASE_PASSWORD to push. Skipping push.");
+    // Recipient: the environment's publish_gln, FIRSTBASE_PUBLISH_GLN or
+    // [provider] publish_gln (see push_env.rs).
+    if target.publish_gln.is_empty() {
+        eprintln!("Set FIRSTBASE_PUBLISH_GLN (recipient GLN) or add publish_gln under [provider] in config.toml. Skipping push.");
         return Ok(false);
     }
-
-    // Target environment: FIRSTBASE_ENV=Production (anything else = Test),
-    // mirroring repush-srn. Default Test keeps ad-hoc `check` runs safe.
-    let fb_env = match std::env::var("FIRSTBASE_ENV").as_deref() {
-        Ok("Production") | Ok("production") | Ok("PROD") | Ok("prod") => {
-            gui::FirstbaseEnv::Production
+    let settings = match target.login_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}. Skipping push.", e);
+            return Ok(false);
         }
-        _ => gui::FirstbaseEnv::Test,
-    };
-
-    // provider_gln comes from config.toml, not a hardcoded default.
-    let settings = gui::Settings {
-        firstbase_email: email,
-        firstbase_password: password,
-        publish_to_gln: publish_gln,
-        provider_gln: fb_config.provider.gln.clone(),
-        firstbase_env: fb_env,
-        ..Default::default()
     };
     let log_fn = |msg: &str| {
         eprintln!("{}", msg);
     };
-    match gui::push_to_firstbase(&settings, &log_fn, Some(uuids)) {
+    let pushed = match uuids {
+        Some(uuids) => gui::push_to_firstbase(&settings, &log_fn, Some(uuids), None),
+        None => match gui::resume_push_to_firstbase(&settings, &log_fn, None) {
+            Ok(Some(out)) => Ok(out),
+            Ok(None) => {
+                eprintln!(
+                    "No interrupted push to {} to resume.",
+                    settings.firstbase_label()
+                );
+                return Ok(true);
+            }
+            Err(e) => Err(e),
+        },
+    };
+    match pushed {
         Ok(out) => {
             eprintln!(
                 "\nDone: {} accepted, {} rejected.",
@@ -1694,7 +3126,7 @@ fn push_changed_to_firstbase(
             );
             // After a Production push, auto-email the GS1 report (non-fatal — a
             // mail error never fails the run).
-            if matches!(settings.firstbase_env, gui::FirstbaseEnv::Production) {
+            if settings.firstbase_label() == gui::FirstbaseEnv::Production.label() {
                 if let Err(e) =
                     send_gs1_prod_report(fb_config, out.accepted, out.rejected, srns, gtin_worklist)
                 {
@@ -1726,6 +3158,67 @@ fn push_changed_to_firstbase(
     }
 }
 
+/// Put the firstbase document `path` for `push` into `firstbase_dir` (copied
+/// unless it is already there) and return its stem, the UUID the push is
+/// scoped to. Anything but a DraftItem document is an error.
+fn stage_push_file(path: &Path, firstbase_dir: &Path) -> Result<String> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let doc: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not JSON", path.display()))?;
+    if doc.pointer("/DraftItem/TradeItem").is_none() {
+        anyhow::bail!("{} is not a firstbase DraftItem document", path.display());
+    }
+    let (Some(name), Some(stem)) = (path.file_name(), path.file_stem()) else {
+        anyhow::bail!("{} has no file name", path.display());
+    };
+    let dest = firstbase_dir.join(name);
+    let same = dest.canonicalize().ok() == path.canonicalize().ok() && dest.exists();
+    if !same {
+        std::fs::copy(path, &dest)
+            .with_context(|| format!("Failed to copy {} to {}", path.display(), dest.display()))?;
+    }
+    Ok(stem.to_string_lossy().to_string())
+}
+
+/// The documents of the devices queued for retry in `env` (`push --retry`):
+/// `firstbase_json/<uuid>.json` as it is now, so fixes made since the
+/// rejection are pushed; a document no longer there is restored from the
+/// queue as it was last sent.
+fn retry_push_files(env: &str) -> Result<Vec<String>> {
+    let data_dir = download::app_data_dir();
+    let db_dir = data_dir.join("db");
+    std::fs::create_dir_all(&db_dir)?;
+    let conn = version_db::open_db(&db_dir.join("version_tracking.db"))?;
+    let firstbase_dir = data_dir.join("firstbase_json");
+    std::fs::create_dir_all(&firstbase_dir)?;
+    let mut files = Vec::new();
+    for entry in retry_queue::entries(&conn, env)? {
+        let path = firstbase_dir.join(format!("{}.json", entry.uuid));
+        if !path.exists() {
+            let Some(doc) = retry_queue::payload(&conn, env, &entry.uuid)? else {
+                continue;
+            };
+            std::fs::write(&path, doc)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        eprintln!(
+            "Retry {} (GTIN {}, {} rejected push(es), last {}): {}",
+            entry.uuid,
+            entry.gtin,
+            entry.attempts,
+            entry.queued_at,
+            if entry.error_codes.is_empty() {
+                "no error code".to_string()
+            } else {
+                entry.error_codes.join(", ")
+            }
+        );
+        files.push(path.to_string_lossy().to_string());
+    }
+    Ok(files)
+}
+
 fn send_gs1_prod_report(
     config: &config::Config,
     accepted: u32,
@@ -2136,7 +3629,7 @@ fn parse_download_args(
     (srns, gtins, limit, threads)
 }
 
-fn process_xml_dir(config: &config::Config) -> Result<()> {
+fn process_xml_dir(config: &config::Config, output_format: OutputFormat) -> Result<()> {
     let input_dir = Path::new("xml");
     let output_dir = Path::new("firstbase_json");
     let processed_dir = input_dir.join("processed");
@@ -2149,7 +3642,7 @@ fn process_xml_dir(config: &config::Config) -> Result<()> {
         let path = entry.path();
         if path.extension().map(|e| e == "xml").unwrap_or(false) {
             println!("Processing: {}", path.display());
-            match process_xml_file(&path, output_dir, config) {
+            match process_xml_file(&path, output_dir, config, output_format) {
                 Ok(output_path) => {
                     println!("  -> {}", output_path);
                     processed += 1;
@@ -2196,47 +3689,189 @@ fn process_xml_dir(config: &config::Config) -> Result<()> {
     Ok(())
 }
 
+/// Convert one EUDAMED PullResponse XML. A single-device file is written as
+/// one FirstbaseDocument (unchanged behaviour); a bulk DTX export with many
+/// `<Device>` elements becomes an array of DraftItem documents, one per device,
+/// in `firstbase_<stem>_<dd.mm.YYYY>.json` (or `.ndjson`). Devices that fail to transform are
+/// reported and skipped; the file only fails if none converted.
 fn process_xml_file(
     input_path: &Path,
     output_dir: &Path,
     config: &config::Config,
+    output_format: OutputFormat,
 ) -> Result<String> {
-    let xml_content = std::fs::read_to_string(input_path).context("Failed to read XML file")?;
-
-    let response =
-        eudamed::parse_pull_response(&xml_content).context("Failed to parse EUDAMED XML")?;
+    let xml_bytes = std::fs::read(input_path).context("Failed to read XML file")?;
+    let (xml_content, repair) = eudamed::decode_xml(&xml_bytes);
+    if let Some(repair) = repair {
+        log_console::diag(&format!("  Encoding: {}", repair));
+    }
 
-    let document = transform::transform(&response, config)
-        .context("Failed to transform to firstbase format")?;
+    let responses =
+        eudamed::parse_pull_responses(&xml_content).context("Failed to parse EUDAMED XML")?;
+    if responses.is_empty() {
+        anyhow::bail!("Failed to parse EUDAMED XML: no <Device> element in payload");
+    }
+    let envelope = &responses[0];
+    log_console::diag(&format!(
+        "  Message {} of {}: {} device(s)",
+        envelope
+            .correlation_id
+            .as_deref()
+            .unwrap_or("(no correlationID)"),
+        envelope
+            .creation_date_time
+            .as_deref()
+            .unwrap_or("(no creationDateTime)"),
+        responses.len()
+    ));
 
     let now = Local::now();
-    let filename = format!("firstbase_{}.json", now.format("%d.%m.%Y"));
+
+    if responses.len() == 1 {
+        let document = transform::transform(&responses[0], config)
+            .context("Failed to transform to firstbase format")?;
+        if !device_filter::admits(&document.trade_item) {
+            return Ok(format!("{} (filtered out)", input_path.display()));
+        }
+
+        let filename = format!("firstbase_{}.json", now.format("%d.%m.%Y"));
+        let output_path = output_dir.join(&filename);
+
+        write_json(&output_path, &document)?;
+
+        return Ok(output_path.display().to_string());
+    }
+
+    let stem = input_path.file_stem().unwrap_or_default().to_string_lossy();
+    let filename = format!(
+        "firstbase_{}_{}.{}",
+        stem,
+        now.format("%d.%m.%Y"),
+        output_format.extension()
+    );
     let output_path = output_dir.join(&filename);
 
-    let json = serde_json::to_string_pretty(&document)?;
-    std::fs::write(&output_path, json)?;
+    let mut writer = BatchWriter::create(&output_path, output_format)?;
+    let mut errors = 0;
+    for (i, response) in responses.iter().enumerate() {
+        match transform::transform(response, config) {
+            Ok(document) if !device_filter::admits(&document.trade_item) => {}
+            Ok(document) => writer.write(&firstbase::DraftItemDocument {
+                draft_item: document,
+            })?,
+            Err(e) => {
+                if errors < 5 {
+                    eprintln!(
+                        "  Device {} ({}): {:#}",
+                        i + 1,
+                        response
+                            .device
+                            .device_type
+                            .as_deref()
+                            .unwrap_or("no xsi:type"),
+                        e
+                    );
+                }
+                errors += 1;
+            }
+        }
+    }
+    let converted = writer.count;
+    let size = writer.finish()?;
+    if converted == 0 {
+        let _ = std::fs::remove_file(&output_path);
+        anyhow::bail!(
+            "Failed to transform to firstbase format: all {} devices failed",
+            responses.len()
+        );
+    }
 
-    Ok(output_path.display().to_string())
+    Ok(format!(
+        "{} ({} devices, {} errors, {})",
+        output_path.display(),
+        converted,
+        errors,
+        format_size(size),
+    ))
 }
 
-fn process_ndjson(input_dir: &Path, config: &config::Config) -> Result<()> {
+fn process_ndjson(
+    input_dir: &Path,
+    config: &config::Config,
+    output_format: OutputFormat,
+    incremental: bool,
+) -> Result<()> {
     let output_dir = Path::new("firstbase_json");
     std::fs::create_dir_all(output_dir)?;
 
-    let mut total_processed = 0;
-    for entry in std::fs::read_dir(input_dir).context("Failed to read ndjson/ directory")? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().map(|e| e == "ndjson").unwrap_or(false) {
-            println!("Processing: {}", path.display());
-            match process_ndjson_file(&path, config) {
-                Ok(()) => {
-                    total_processed += 1;
-                }
-                Err(e) => {
-                    eprintln!("  Error: {:#}", e);
+    let mut files: Vec<PathBuf> = std::fs::read_dir(input_dir)
+        .context("Failed to read ndjson/ directory")?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| ndjson_input::is_ndjson(p))
+        .collect();
+    files.sort();
+
+    // Snapshots overlap: keep only the newest version of each device across files.
+    let mut dedup = dedup::scan(&files)?;
+    if !dedup.discarded.is_empty() {
+        let report_path = dedup::write_report(output_dir, &dedup)?;
+        println!(
+            "Deduplicated: {} older/duplicate device record(s) discarded -> {}",
+            dedup.discarded.len(),
+            report_path.display()
+        );
+    }
+
+    // --incremental: devices converted before in the same version are skipped
+    // like superseded ones; the versions of a file are recorded once it is done.
+    let mut sync = None;
+    let mut changed: HashMap<PathBuf, Vec<incremental::Seen>> = HashMap::new();
+    if incremental {
+        let state = incremental::State::open(
+            Path::new(version_db::VERSION_DB_PATH),
+            incremental::Scope::Listing,
+        )?;
+        let mut unchanged = Vec::new();
+        for path in &files {
+            let input = ndjson_input::Input::open(path)?;
+            ndjson_input::stream(
+                &input,
+                |line_num| dedup.discarded.contains(&(path.clone(), line_num)),
+                |line_num, line| Ok((line_num, incremental::Seen::from_line(line))),
+                |(line_num, seen)| {
+                    match seen {
+                        Some(seen) if !state.changed(&seen) => {
+                            unchanged.push((path.clone(), line_num))
+                        }
+                        Some(seen) => changed.entry(path.clone()).or_default().push(seen),
+                        None => {}
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        println!(
+            "Incremental: {} unchanged device(s) skipped, {} new/changed",
+            unchanged.len(),
+            changed.values().map(Vec::len).sum::<usize>()
+        );
+        dedup.discarded.extend(unchanged);
+        sync = Some(state);
+    }
+
+    let mut total_processed = 0;
+    for path in &files {
+        println!("Processing: {}", path.display());
+        match process_ndjson_file(path, config, output_format, &dedup.discarded) {
+            Ok(()) => {
+                total_processed += 1;
+                if let (Some(state), Some(seen)) = (&mut sync, changed.get(path)) {
+                    state.record(seen)?;
                 }
             }
+            Err(e) => {
+                eprintln!("  Error: {:#}", e);
+            }
         }
     }
 
@@ -2244,97 +3879,157 @@ fn process_ndjson(input_dir: &Path, config: &config::Config) -> Result<()> {
     Ok(())
 }
 
-fn process_ndjson_file(input_path: &Path, config: &config::Config) -> Result<()> {
+/// Convert one listing NDJSON file; lines in `discarded` (superseded by a
+/// newer version of the device elsewhere, see `dedup`) are skipped.
+fn process_ndjson_file(
+    input_path: &Path,
+    config: &config::Config,
+    output_format: OutputFormat,
+    discarded: &HashSet<(PathBuf, u32)>,
+) -> Result<()> {
     let output_dir = Path::new("firstbase_json");
     std::fs::create_dir_all(output_dir)?;
 
-    let file = std::fs::File::open(input_path).context("Failed to open NDJSON file")?;
-    let reader = std::io::BufReader::new(file);
-
-    let mut trade_items = Vec::new();
-    let mut errors = 0;
-    let mut line_num = 0;
-
-    for line in reader.lines() {
-        line_num += 1;
-        let line = line?;
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        match api_json::parse_api_device(trimmed) {
-            Ok(device) => {
-                let trade_item = transform_api::transform_api_device(&device, config);
-                let uuid = device.uuid.as_deref().unwrap_or("unknown");
-                let document = firstbase::FirstbaseDocument {
-                    trade_item,
-                    children: Vec::new(),
-                    identifier: format!("Draft_{}", uuid),
-                };
-                trade_items.push(firstbase::DraftItemDocument {
-                    draft_item: document,
-                });
-            }
-            Err(e) => {
-                if errors < 5 {
-                    eprintln!("  Line {}: {}", line_num, e);
-                }
-                errors += 1;
-            }
-        }
-    }
+    let input = ndjson_input::Input::open(input_path).context("Failed to open NDJSON file")?;
 
     // Generate output filename
     let now = Local::now();
-    let stem = input_path.file_stem().unwrap_or_default().to_string_lossy();
-    let filename = format!("firstbase_{}_{}.json", stem, now.format("%d.%m.%Y"));
+    let stem = ndjson_input::stem(input_path);
+    let filename = format!(
+        "firstbase_{}_{}.{}",
+        stem,
+        now.format("%d.%m.%Y"),
+        output_format.extension()
+    );
     let output_path = output_dir.join(&filename);
 
-    let json = serde_json::to_string_pretty(&trade_items)?;
-    std::fs::write(&output_path, &json)?;
+    let mut writer = BatchWriter::create(&output_path, output_format)?;
+    let mut errors = 0;
+    let mut quarantine = quarantine::Quarantine::new(output_dir, input_path);
+    ndjson_input::stream(
+        &input,
+        |line_num| discarded.contains(&(input_path.to_path_buf(), line_num)),
+        |line_num, trimmed| {
+            Ok(match api_json::parse_api_device(trimmed) {
+                Ok(device) => {
+                    let criterion = device.device_criterion.as_ref().and_then(|c| c.as_str());
+                    let category = device_category::classify(criterion, None);
+                    if let Some(reason) = category.excluded_reason() {
+                        let id = device.uuid.as_deref().or(device.primary_di.as_deref());
+                        device_category::exclude(
+                            id.unwrap_or("unknown"),
+                            criterion.unwrap_or_default(),
+                            reason,
+                        );
+                        return Ok(Ok(None));
+                    }
+                    let trade_item = transform_api::transform_api_device(&device, config);
+                    if !device_filter::admits(&trade_item) {
+                        return Ok(Ok(None));
+                    }
+                    let uuid = device.uuid.as_deref().unwrap_or("unknown");
+                    if device.uuid.is_some() {
+                        provenance::write(
+                            output_dir,
+                            &provenance::Provenance::new(uuid, input_path, Some(line_num)),
+                        )?;
+                    }
+                    Ok(Some(firstbase::DraftItemDocument {
+                        draft_item: firstbase::FirstbaseDocument {
+                            trade_item,
+                            children: Vec::new(),
+                            identifier: format!("Draft_{}", uuid),
+                        },
+                    }))
+                }
+                Err(e) => Err((line_num, format!("{}", e), trimmed.to_string())),
+            })
+        },
+        |result| {
+            match result {
+                Ok(Some(doc)) => writer.write(&doc)?,
+                Ok(None) => {}
+                Err((line_num, e, raw)) => {
+                    if errors < 5 {
+                        eprintln!("  Line {}: {}", line_num, e);
+                    }
+                    quarantine.add(line_num, &raw, &e)?;
+                    errors += 1;
+                }
+            }
+            Ok(())
+        },
+    )?;
+
+    let devices = writer.count;
+    let size = writer.finish()?;
 
     println!(
         "  -> {} ({} devices, {} errors, {})",
         output_path.display(),
-        trade_items.len(),
+        devices,
         errors,
-        format_size(json.len()),
+        format_size(size),
     );
+    print_quarantine(quarantine)?;
+
+    Ok(())
+}
 
+/// Report where the lines that failed to parse went.
+fn print_quarantine(quarantine: quarantine::Quarantine) -> Result<()> {
+    if let Some((path, count)) = quarantine.finish()? {
+        println!("  Quarantined {} line(s) -> {}", count, path.display());
+    }
     Ok(())
 }
 
-/// Process detail NDJSON file, optionally merging with listing data for
-/// fields not available in the detail endpoint (manufacturer SRN/name,
-/// AR SRN/name, risk class, basic UDI).
+/// Process detail NDJSON file with the Basic UDI-DI record of each device
+/// (cache, export, or fetched with `fetch_missing`, see `basic_udi_fetch`);
+/// without one the listing data fills in what it can (manufacturer SRN/name,
+/// AR SRN/name, risk class, basic UDI). With a Basic UDI-DI export this is
+/// the three-way `merge` mode (see `merge.rs`).
 fn process_detail_ndjson(
     detail_path: &Path,
     listing_path: Option<&Path>,
+    basic_udi_export: Option<&Path>,
+    spill_index: bool,
+    fetch_missing: bool,
     config: &config::Config,
+    output_format: OutputFormat,
 ) -> Result<()> {
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
     let output_dir = Path::new("firstbase_json");
     std::fs::create_dir_all(output_dir)?;
 
     // Load listing data index if provided (keyed by GTIN / primaryDi)
+    let spill_dir = spill_index.then(|| Path::new("db"));
+    let load_started = std::time::Instant::now();
     let listing_index = if let Some(lp) = listing_path {
         println!("Loading listing data from {}...", lp.display());
-        load_listing_index(lp)?
+        listing_index::ListingIndex::load(lp, spill_dir)?
     } else {
         // Try default listing file
         let default_listing = Path::new("ndjson/eudamed_10k.ndjson");
         if default_listing.exists() {
             println!("Loading listing data from {}...", default_listing.display());
-            load_listing_index(default_listing)?
+            listing_index::ListingIndex::load(default_listing, spill_dir)?
         } else {
-            HashMap::new()
+            listing_index::ListingIndex::empty()
         }
     };
 
     if !listing_index.is_empty() {
         println!(
-            "  Loaded {} listing records for merging",
-            listing_index.len()
+            "  Loaded {} listing records for merging in {:.2?}{}",
+            listing_index.len(),
+            load_started.elapsed(),
+            if spill_index {
+                " (spilled to disk)"
+            } else {
+                ""
+            }
         );
     }
 
@@ -2347,151 +4042,155 @@ fn process_detail_ndjson(
         );
     }
 
-    let file = std::fs::File::open(detail_path)
-        .with_context(|| format!("Failed to open {}", detail_path.display()))?;
-    let reader = std::io::BufReader::new(file);
+    let basic_udi_export = match basic_udi_export {
+        Some(path) => {
+            println!("Loading Basic UDI-DI export from {}...", path.display());
+            let export = merge::load_basic_udi_export(path)?;
+            println!("  Loaded {} Basic UDI-DI records", export.len());
+            Some(export)
+        }
+        None => None,
+    };
+    let coverage = merge::Coverage::default();
+    let basic_udi_fetcher =
+        fetch_missing.then(|| basic_udi_fetch::BasicUdiFetcher::new(BASIC_UDI_CACHE_DIR));
 
-    // Read all lines first
-    let lines: Vec<(usize, String)> = reader
-        .lines()
-        .enumerate()
-        .filter_map(|(i, line)| {
-            let line = line.ok()?;
-            let trimmed = line.trim().to_string();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some((i + 1, trimmed))
-            }
-        })
-        .collect();
+    let input = ndjson_input::Input::open(detail_path)?;
 
-    // Process lines in parallel
-    let results: Vec<Result<firstbase::DraftItemDocument, (usize, String)>> = lines
-        .par_iter()
-        .map(|(line_num, trimmed)| {
-            match api_detail::parse_api_detail(trimmed) {
+    let now = Local::now();
+    let stem = ndjson_input::stem(detail_path);
+    let filename = format!(
+        "firstbase_{}_{}.{}",
+        stem,
+        now.format("%d.%m.%Y"),
+        output_format.extension()
+    );
+    let output_path = output_dir.join(&filename);
+
+    // Process lines in parallel, write results in input order
+    let mut writer = BatchWriter::create(&output_path, output_format)?;
+    let mut errors = 0;
+    let mut quarantine = quarantine::Quarantine::new(output_dir, detail_path);
+    let merged = AtomicUsize::new(0);
+    let merge_nanos = AtomicU64::new(0);
+    ndjson_input::stream(
+        &input,
+        |_| false,
+        |line_num, trimmed| {
+            Ok(match api_detail::parse_api_detail(trimmed) {
                 Ok(detail) => {
                     let uuid = detail.uuid.clone().unwrap_or_default();
-                    let basic_udi = basic_udi_cache.get(&uuid);
+                    // The base unit's listing row leads to its Basic UDI-DI in the
+                    // export (three-way merge) or on EUDAMED (--fetch-missing)
+                    let base_listing = (basic_udi_export.is_some() || basic_udi_fetcher.is_some())
+                        .then(|| listing_index.get(&detail.gtin()))
+                        .flatten();
+                    let basic_udi = basic_udi_export
+                        .as_ref()
+                        .zip(base_listing.as_ref())
+                        .and_then(|(export, listing)| export.get(&listing.basic_udi))
+                        .or_else(|| basic_udi_cache.get(&uuid));
+                    let fetched = match (&basic_udi_fetcher, basic_udi) {
+                        (Some(fetcher), None) if !uuid.is_empty() => {
+                            fetcher.get(base_listing.as_ref().map(|l| l.basic_udi.as_str()), &uuid)
+                        }
+                        _ => None,
+                    };
+                    let basic_udi = basic_udi.or(fetched.as_deref());
+                    if basic_udi_export.is_some() {
+                        coverage.count(base_listing.is_some(), basic_udi.is_some());
+                    }
                     let mut document = transform_detail::transform_detail_document(
                         &detail, config, basic_udi, &uuid,
                     );
 
-                    // Merge listing data (manufacturer, AR, risk class, basic UDI)
-                    let gtin = &document.trade_item.gtin;
-                    if let Some(listing) = listing_index.get(gtin) {
-                        merge_listing_data(&mut document.trade_item, listing);
+                    // Listing data (manufacturer, AR, risk class, basic UDI) only
+                    // stands in for a missing Basic UDI-DI record, which carries
+                    // all of it and the legislation the listing lacks (MDD vs MDR)
+                    let merge_started = std::time::Instant::now();
+                    let listing = match basic_udi_export {
+                        Some(_) => base_listing,
+                        None => listing_index.get(&document.trade_item.gtin),
+                    };
+                    if let Some(listing) = listing.filter(|_| basic_udi.is_none()) {
+                        merge_listing_data(&mut document.trade_item, &listing);
+                        // The merged manufacturer SRN decides parties and contacts.
+                        transform::finish_document(&mut document, config);
+                        merged.fetch_add(1, Ordering::Relaxed);
+                    }
+                    merge_nanos
+                        .fetch_add(merge_started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                    if !device_filter::admits(&document.trade_item) {
+                        return Ok(Ok(None));
                     }
 
                     let draft_doc = firstbase::DraftItemDocument {
                         draft_item: document,
                     };
 
-                    // Write individual file per UUID
+                    // Write individual file per UUID, with its provenance sidecar
                     if !uuid.is_empty() {
                         let individual_path = output_dir.join(format!("{}.json", uuid));
-                        if let Ok(individual_json) = serde_json::to_string_pretty(&draft_doc) {
-                            let _ = std::fs::write(&individual_path, &individual_json);
-                        }
+                        let _ = write_json(&individual_path, &draft_doc);
+                        let prov = provenance::Provenance::new(&uuid, detail_path, Some(line_num));
+                        let _ = provenance::write(output_dir, &prov);
                     }
 
-                    Ok(draft_doc)
+                    Ok(Some(draft_doc))
                 }
-                Err(e) => Err((*line_num, format!("{}", e))),
-            }
-        })
-        .collect();
-
-    // Collect results preserving order
-    let mut trade_items = Vec::new();
-    let mut errors = 0;
-    for result in results {
-        match result {
-            Ok(doc) => trade_items.push(doc),
-            Err((line_num, e)) => {
-                if errors < 10 {
-                    eprintln!("  Line {}: {}", line_num, e);
+                Err(e) => Err((line_num, format!("{}", e), trimmed.to_string())),
+            })
+        },
+        |result| {
+            match result {
+                Ok(Some(doc)) => writer.write(&doc)?,
+                Ok(None) => {}
+                Err((line_num, e, raw)) => {
+                    if errors < 10 {
+                        eprintln!("  Line {}: {}", line_num, e);
+                    }
+                    quarantine.add(line_num, &raw, &e)?;
+                    errors += 1;
                 }
-                errors += 1;
             }
-        }
-    }
+            Ok(())
+        },
+    )?;
 
     if errors > 10 {
         eprintln!("  ... and {} more errors", errors - 10);
     }
 
-    let now = Local::now();
-    let stem = detail_path
-        .file_stem()
-        .unwrap_or_default()
-        .to_string_lossy();
-    let filename = format!("firstbase_{}_{}.json", stem, now.format("%d.%m.%Y"));
-    let output_path = output_dir.join(&filename);
-
-    let json = serde_json::to_string_pretty(&trade_items)?;
-    std::fs::write(&output_path, &json)?;
+    let devices = writer.count;
+    let size = writer.finish()?;
 
     println!(
         "  -> {} ({} devices, {} errors, {})",
         output_path.display(),
-        trade_items.len(),
+        devices,
         errors,
-        format_size(json.len()),
+        format_size(size),
     );
-
-    Ok(())
-}
-
-/// Listing data we want to merge into detail-based records
-struct ListingData {
-    basic_udi: String,
-    risk_class_code: Option<String>,
-    manufacturer_srn: Option<String>,
-    manufacturer_name: Option<String>,
-    authorised_representative_srn: Option<String>,
-    authorised_representative_name: Option<String>,
-}
-
-fn load_listing_index(path: &Path) -> Result<HashMap<String, ListingData>> {
-    let file = std::fs::File::open(path)?;
-    let reader = std::io::BufReader::new(file);
-    let mut index = HashMap::new();
-
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        if let Ok(device) = api_json::parse_api_device(trimmed) {
-            if let Some(ref gtin) = device.primary_di {
-                if !gtin.is_empty() {
-                    index.insert(
-                        gtin.clone(),
-                        ListingData {
-                            basic_udi: device.basic_udi.clone().unwrap_or_default(),
-                            risk_class_code: device.risk_class_code(),
-                            manufacturer_srn: device.manufacturer_srn.clone(),
-                            manufacturer_name: device.manufacturer_name.clone(),
-                            authorised_representative_srn: device
-                                .authorised_representative_srn
-                                .clone(),
-                            authorised_representative_name: device
-                                .authorised_representative_name
-                                .clone(),
-                        },
-                    );
-                }
-            }
-        }
+    print_quarantine(quarantine)?;
+    if basic_udi_export.is_some() {
+        println!("  Merge: {}", coverage.summary());
+    }
+    if let Some(fetcher) = basic_udi_fetcher {
+        println!("  {}", fetcher.summary());
+    }
+    if !listing_index.is_empty() {
+        println!(
+            "  Listing merge: {} of {} devices completed from the listing (no Basic UDI-DI record), {:.2?} merging (summed over threads)",
+            merged.into_inner(),
+            devices,
+            std::time::Duration::from_nanos(merge_nanos.into_inner()),
+        );
     }
 
-    Ok(index)
+    Ok(())
 }
 
-fn merge_listing_data(trade_item: &mut firstbase::TradeItem, listing: &ListingData) {
+fn merge_listing_data(trade_item: &mut firstbase::TradeItem, listing: &listing_index::ListingData) {
     // Set basic UDI as global model number 1:1 (v1.0.64, Maik's mapping): the
     // real GMN for MDR/IVDR, the `B-<GTIN>` placeholder for legacy. No local GMN
     // gate — EUDAMED validates GS1 identifiers at registration. (097.116 on
@@ -2514,9 +4213,7 @@ fn merge_listing_data(trade_item: &mut firstbase::TradeItem, listing: &ListingDa
             trade_item.classification.additional_classifications.insert(
                 0,
                 firstbase::AdditionalClassification {
-                    system_code: firstbase::CodeValue {
-                        value: "76".to_string(),
-                    },
+                    system_code: firstbase::CodeValue { value: "76".into() },
                     values: vec![firstbase::AdditionalClassificationValue {
                         code_value: gs1_risk.to_string(),
                     }],
@@ -2544,7 +4241,7 @@ fn merge_listing_data(trade_item: &mut firstbase::TradeItem, listing: &ListingDa
                 .contact_information
                 .push(firstbase::TradeItemContactInformation {
                     contact_type: firstbase::CodeValue {
-                        value: "EMA".to_string(),
+                        value: "EMA".into(),
                     },
                     party_identification: vec![firstbase::AdditionalPartyIdentification {
                         type_code: "SRN".to_string(),
@@ -2558,17 +4255,18 @@ fn merge_listing_data(trade_item: &mut firstbase::TradeItem, listing: &ListingDa
     }
 
     // Add authorised representative contact (if not already added by Basic UDI-DI)
-    let has_ear = trade_item
-        .contact_information
-        .iter()
-        .any(|c| c.contact_type.value == "EAR");
+    let has_ear = trade_item.contact_information.iter().any(|c| {
+        // A CH-REP (`swiss_market.rs`) is no EU authorised representative
+        c.contact_type.value == "EAR"
+            && c.party_identification.iter().all(|p| p.type_code != "CHRN")
+    });
     if !has_ear {
         if let Some(ref srn) = listing.authorised_representative_srn {
             trade_item
                 .contact_information
                 .push(firstbase::TradeItemContactInformation {
                     contact_type: firstbase::CodeValue {
-                        value: "EAR".to_string(),
+                        value: "EAR".into(),
                     },
                     party_identification: vec![firstbase::AdditionalPartyIdentification {
                         type_code: "SRN".to_string(),
@@ -2580,13 +4278,29 @@ fn merge_listing_data(trade_item: &mut firstbase::TradeItem, listing: &ListingDa
                 });
         }
     }
+    // The merged classification and contacts back in their canonical order
+    ordering::classifications(&mut trade_item.classification.additional_classifications);
+    ordering::contacts(&mut trade_item.contact_information);
 }
 
 /// Process individual EUDAMED JSON files from a directory.
 /// Each input file produces one output file (one-to-one mapping).
 /// Uses version tracking DB to skip unchanged devices.
-fn process_eudamed_json_dir(input_dir: &Path, config: &config::Config) -> Result<()> {
+/// `delta`: also write changed-fields payloads + a change log against the
+/// previous output of each re-converted device (see `delta.rs`).
+/// `changed_only`: a re-converted device whose document equals the already
+/// pushed one in `processed/` (ignoring sync dates) is not queued for push.
+fn process_eudamed_json_dir(
+    input_dir: &Path,
+    config: &config::Config,
+    delta: bool,
+    changed_only: bool,
+) -> Result<()> {
     let output_dir = Path::new("firstbase_json");
+    let delta_dir = output_dir.join("delta");
+    let mut delta_docs = 0;
+    let mut not_queued = 0;
+    let mut change_log: Vec<String> = Vec::new();
     let processed_dir = input_dir.join("processed");
     std::fs::create_dir_all(output_dir)?;
 
@@ -2599,6 +4313,17 @@ fn process_eudamed_json_dir(input_dir: &Path, config: &config::Config) -> Result
         existing_count,
         db_path.display()
     );
+    // Conversion history: the local DB or the shared store from [storage].
+    let mut store = storage::open(&config.storage, db_path)?;
+    let run_id = store.begin_run("eudamed_json", input_dir)?;
+    let mut output_hashes: Vec<String> = Vec::new();
+    // Keep an existing search index current (a missing one is built in full by `search`).
+    let search_path = Path::new(search_index::INDEX_DB_PATH);
+    let search_conn = if search_path.exists() {
+        Some(search_index::open_index(search_path)?)
+    } else {
+        None
+    };
 
     // Load Basic UDI-DI cache
     let cache_dir = Path::new(BASIC_UDI_CACHE_DIR);
@@ -2617,6 +4342,8 @@ fn process_eudamed_json_dir(input_dir: &Path, config: &config::Config) -> Result
     let mut errors = 0;
     let mut processed_files = Vec::new();
     let mut change_summary: HashMap<String, u32> = HashMap::new();
+    let mut without_gtin = no_gtin::NoGtin::new(config.device_level.no_gtin);
+    let mut placeholders = 0;
 
     for entry in std::fs::read_dir(input_dir).context("Failed to read eudamed_json/ directory")? {
         let entry = entry?;
@@ -2677,7 +4404,7 @@ fn process_eudamed_json_dir(input_dir: &Path, config: &config::Config) -> Result
             *change_summary.entry(change_label.clone()).or_insert(0) += 1;
 
             // --- Convert ---
-            let result: anyhow::Result<firstbase::FirstbaseDocument> = if is_udi_di {
+            let result: anyhow::Result<Vec<no_gtin::Output>> = if is_udi_di {
                 // UDI-DI level file — reuse existing api_detail parser/transformer
                 // Fetch Basic UDI-DI on demand if not cached
                 if !basic_udi_cache.contains_key(&stem) {
@@ -2693,36 +4420,137 @@ fn process_eudamed_json_dir(input_dir: &Path, config: &config::Config) -> Result
                 }
                 api_detail::parse_api_detail(&json_content).map(|detail| {
                     let basic_udi = basic_udi_cache.get(&stem);
-                    transform_detail::transform_detail_document(&detail, config, basic_udi, &stem)
+                    vec![no_gtin::Output {
+                        stem: stem.clone(),
+                        document: transform_detail::transform_detail_document(
+                            &detail, config, basic_udi, &stem,
+                        ),
+                        placeholder: false,
+                    }]
                 })
             } else {
-                // Device level file (Basic UDI-DI)
-                eudamed_json::parse_eudamed_json(&json_content).map(|device| {
-                    let trade_item =
-                        transform_eudamed_json::transform_eudamed_device(&device, config);
-                    firstbase::FirstbaseDocument {
-                        trade_item,
-                        children: Vec::new(),
-                        identifier: format!("Draft_{}", stem),
-                    }
-                })
+                // Device level file (Basic UDI-DI): no GTIN, [device_level] no_gtin decides
+                eudamed_json::parse_eudamed_json(&json_content)
+                    .map(|device| without_gtin.convert(&device, &stem, config))
             };
 
+            // Outside --risk-class / --legislation: no output, version not recorded
+            let result = result.map(|outputs| {
+                outputs
+                    .into_iter()
+                    .filter(|o| device_filter::admits(&o.document.trade_item))
+                    .collect::<Vec<_>>()
+            });
             match result {
-                Ok(document) => {
-                    let draft_doc = firstbase::DraftItemDocument {
-                        draft_item: document,
-                    };
+                // Skipped device-level record, listed in the no_gtin or excluded report,
+                // or filtered out
+                Ok(outputs) if outputs.is_empty() => {
+                    processed_files.push(path);
+                }
+                Ok(outputs) => {
+                    for out in outputs {
+                        let draft_doc = firstbase::DraftItemDocument {
+                            draft_item: out.document,
+                        };
+                        let filename = format!("{}.json", out.stem);
+                        if out.placeholder {
+                            let dir = output_dir.join(no_gtin::PLACEHOLDER_DIR);
+                            std::fs::create_dir_all(&dir)?;
+                            write_json(&dir.join(&filename), &draft_doc)?;
+                            placeholders += 1;
+                            continue;
+                        }
+                        let output_path = output_dir.join(&filename);
+
+                        let json = serde_json::to_string_pretty(&draft_doc)?;
+                        // Previous output for the delta: still pending, or already
+                        // pushed and moved to processed/.
+                        let pending = output_path.exists();
+                        let previous = if delta || changed_only {
+                            [
+                                output_path.clone(),
+                                output_dir.join("processed").join(&filename),
+                            ]
+                            .iter()
+                            .find_map(|p| std::fs::read_to_string(p).ok())
+                            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+                        } else {
+                            None
+                        };
+                        let new: serde_json::Value = serde_json::to_value(&draft_doc)?;
+                        // --changed-only: the source changed but the document is the
+                        // one already pushed (apart from sync dates) — don't queue it.
+                        let unchanged_output = changed_only
+                            && !pending
+                            && previous
+                                .as_ref()
+                                .is_some_and(|old| delta::delta_document(old, &new).is_none());
+                        if unchanged_output {
+                            not_queued += 1;
+                        } else {
+                            std::fs::write(&output_path, &json)?;
+                        }
 
-                    let filename = path.file_name().unwrap_or_default().to_string_lossy();
-                    let output_path = output_dir.join(filename.as_ref());
+                        if let Some(old) = previous.filter(|_| delta) {
+                            if let Some(payload) = delta::delta_document(&old, &new) {
+                                std::fs::create_dir_all(&delta_dir)?;
+                                write_json(&delta_dir.join(&filename), &payload)?;
+                                delta_docs += 1;
+                                let gtin = new
+                                    .pointer("/DraftItem/TradeItem/Gtin")
+                                    .and_then(|g| g.as_str())
+                                    .unwrap_or(&stem);
+                                for (field, before, after) in delta::changed_fields(&old, &new) {
+                                    change_log.push(format!(
+                                        "{} {}: {} -> {}",
+                                        gtin, field, before, after
+                                    ));
+                                }
+                            }
+                        }
 
-                    let json = serde_json::to_string_pretty(&draft_doc)?;
-                    std::fs::write(&output_path, &json)?;
+                        // Provenance sidecar + conversion history: this device version,
+                        // its source record and the run that made it
+                        let prov = provenance::Provenance::new(&stem, &path, None);
+                        provenance::write(output_dir, &prov)?;
+                        let base = report::base_unit(&new);
+                        let output_hash = version_db::hash_json(&json);
+                        store.record_device_version(
+                        &version_db::DeviceHistoryRecord {
+                            run_id,
+                            uuid: stem.clone(),
+                            gtin: report::str_at(&new, "/DraftItem/TradeItem/Gtin").to_string(),
+                            seen_at: now_str.clone(),
+                            source_file: prov.source_file,
+                            source_line: prov.source_line,
+                            downloaded_at: prov.downloaded_at,
+                            detail_hash: version_rec.detail_hash.clone(),
+                            udi_version: version_rec.udi_version,
+                            budi_version: version_rec.budi_version,
+                            change_summary: change_label.clone(),
+                            risk_class: report::risk_class(base).to_string(),
+                            regulatory_act: report::str_at(
+                                base,
+                                "/RegulatedTradeItemModule/RegulatoryInformation/0/RegulatoryAct",
+                            )
+                            .to_string(),
+                            device_status: report::str_at(
+                                base,
+                                "/MedicalDeviceTradeItemModule/MedicalDeviceInformation/EUMedicalDeviceStatusCode/Value",
+                            )
+                            .to_string(),
+                            output_hash: output_hash.clone(),
+                            document: Some(version_db::compress_json(&new.to_string())),
+                        },
+                    )?;
+                        output_hashes.push(output_hash);
+                        if let Some(sc) = &search_conn {
+                            search_index::index_document(sc, &out.stem, &new)?;
+                        }
+                    }
 
                     // Update version DB after successful conversion
                     version_db::upsert_version(&conn, &version_rec)?;
-
                     processed += 1;
                     processed_files.push(path);
                 }
@@ -2753,6 +4581,59 @@ fn process_eudamed_json_dir(input_dir: &Path, config: &config::Config) -> Result
         errors,
         output_dir.display()
     );
+    if let Some(report) = without_gtin.write_report(output_dir)? {
+        println!(
+            "No GTIN: {} device-level records without a document -> {}",
+            without_gtin.skipped(),
+            report.display()
+        );
+    }
+    if placeholders > 0 {
+        println!(
+            "No GTIN: {} placeholder documents (not for push) -> {}",
+            placeholders,
+            output_dir.join(no_gtin::PLACEHOLDER_DIR).display()
+        );
+    }
+    output_hashes.sort();
+    store.finish_run(
+        run_id,
+        &version_db::RunTotals {
+            input_files: (processed + skipped + errors) as u64,
+            converted: processed as u64,
+            skipped: skipped as u64,
+            errors: errors as u64,
+            output_hash: version_db::hash_json(&output_hashes.concat()),
+        },
+    )?;
+    if changed_only {
+        println!(
+            "Changed-only: {} re-converted devices identical to the pushed version, not queued",
+            not_queued
+        );
+    }
+    println!(
+        "Conversion run #{} recorded in the history DB ({})",
+        run_id,
+        store.name()
+    );
+    if delta {
+        if change_log.is_empty() {
+            println!("Delta: no changed fields against the previous output");
+        } else {
+            let log_path = delta_dir.join(format!(
+                "changes_{}.txt",
+                Local::now().format("%H.%M_%d.%m.%Y")
+            ));
+            std::fs::write(&log_path, change_log.join("\n") + "\n")?;
+            println!(
+                "Delta: {} correction payloads, {} changed fields -> {}",
+                delta_docs,
+                change_log.len(),
+                log_path.display()
+            );
+        }
+    }
     Ok(())
 }
 
@@ -2794,14 +4675,12 @@ fn fetch_basic_udi_di_outcome(
         "https://ec.europa.eu/tools/eudamed/api/devices/basicUdiData/udiDiData/{}?languageIso2Code=en",
         uuid
     );
-    let agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .timeout_global(Some(std::time::Duration::from_secs(15)))
-        .build()
-        .new_agent();
+    let agent = http::agent_for(&url);
+    let policy = http::policy(&url);
+    let attempts = policy.max_retries.unwrap_or(4).max(1);
 
     let mut last_reason = BasicFetchReason::Network;
-    for attempt in 1..=4 {
+    for attempt in 1..=attempts {
         match agent.get(&url).call() {
             Ok(mut resp) => {
                 // http_status_as_error(false) ⇒ 404/429/5xx arrive here as Ok with a
@@ -2848,15 +4727,12 @@ fn fetch_basic_udi_di_outcome(
                         } else {
                             BasicFetchReason::Http(status)
                         };
-                        if attempt < 4 {
+                        if attempt < attempts {
                             // On a 429 throttle, wait the server-stated Retry-After
-                            // (capped at 70s); otherwise a short linear backoff.
-                            let wait = if status == 429 {
-                                retry_after_secs.unwrap_or(60).min(70)
-                            } else {
-                                attempt
-                            };
-                            std::thread::sleep(std::time::Duration::from_secs(wait));
+                            // (capped at `backoff_max_ms`); otherwise exponential.
+                            let retry_after =
+                                (status == 429).then(|| retry_after_secs.unwrap_or(60));
+                            std::thread::sleep(http::backoff(&policy, attempt, retry_after));
                             continue;
                         }
                         eprintln!(
@@ -2869,8 +4745,8 @@ fn fetch_basic_udi_di_outcome(
             }
             Err(e) => {
                 last_reason = BasicFetchReason::Network;
-                if attempt < 4 {
-                    std::thread::sleep(std::time::Duration::from_secs(attempt));
+                if attempt < attempts {
+                    std::thread::sleep(http::backoff(&policy, attempt, None));
                     continue;
                 }
                 eprintln!(
@@ -2941,13 +4817,11 @@ fn fetch_detail(uuid: &str, cache_dir: &Path) -> Option<api_detail::ApiDeviceDet
         "https://ec.europa.eu/tools/eudamed/api/devices/udiDiData/{}?languageIso2Code=en",
         uuid
     );
-    let agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .timeout_global(Some(std::time::Duration::from_secs(15)))
-        .build()
-        .new_agent();
+    let agent = http::agent_for(&url);
+    let policy = http::policy(&url);
+    let attempts = policy.max_retries.unwrap_or(4).max(1);
 
-    for attempt in 1..=4 {
+    for attempt in 1..=attempts {
         match agent.get(&url).call() {
             Ok(mut resp) => {
                 let body = resp.body_mut().read_to_string().unwrap_or_default();
@@ -2961,8 +4835,8 @@ fn fetch_detail(uuid: &str, cache_dir: &Path) -> Option<api_detail::ApiDeviceDet
                         return Some(data);
                     }
                     Err(_) => {
-                        if attempt < 4 {
-                            std::thread::sleep(std::time::Duration::from_secs(attempt));
+                        if attempt < attempts {
+                            std::thread::sleep(http::backoff(&policy, attempt, None));
                             continue;
                         }
                         eprintln!(
@@ -2974,8 +4848,8 @@ fn fetch_detail(uuid: &str, cache_dir: &Path) -> Option<api_detail::ApiDeviceDet
                 }
             }
             Err(e) => {
-                if attempt < 4 {
-                    std::thread::sleep(std::time::Duration::from_secs(attempt));
+                if attempt < attempts {
+                    std::thread::sleep(http::backoff(&policy, attempt, None));
                     continue;
                 }
                 eprintln!(
@@ -3123,8 +4997,9 @@ fn force_reload_eudamed(
     let mut basic_network = 0usize;
     let mut basic_empty = 0usize;
 
-    // ~54 req/min (just under the 60/60s budget) — paced by request start time.
-    let min_interval = Duration::from_millis(1100);
+    // Just under the 60/60s budget (EUDAMED `interval_ms` of `[http]`) — paced
+    // by request start time.
+    let min_interval = Duration::from_millis(http::policy(download::EUDAMED_BASE_URL).interval_ms);
     let mut last_start: Option<Instant> = None;
     for (i, uuid) in need.iter().enumerate() {
         if let Some(t) = last_start {
@@ -3212,15 +5087,13 @@ fn process_swissdamed(detail_dir: &Path, basic_dir: &Path) -> Result<()> {
             let endpoint = swissdamed::legislation_endpoint(&basic_udi);
             let is_spp = basic_udi.is_spp();
 
-            let payload = if is_spp {
-                serde_json::to_string_pretty(&swissdamed::to_spp_dto(&device, &basic_udi)).ok()?
-            } else {
-                serde_json::to_string_pretty(&swissdamed::to_mdr_dto(&device, &basic_udi)).ok()?
-            };
-
             // Write output
             let out_path = output_dir.join(format!("{}.json", stem));
-            std::fs::write(&out_path, &payload).ok()?;
+            if is_spp {
+                write_json(&out_path, &swissdamed::to_spp_dto(&device, &basic_udi)).ok()?;
+            } else {
+                write_json(&out_path, &swissdamed::to_mdr_dto(&device, &basic_udi)).ok()?;
+            }
 
             Some((stem, endpoint.to_string()))
         })
@@ -3240,6 +5113,99 @@ fn process_swissdamed(detail_dir: &Path, basic_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Convert every swissdamed export in `input_dir` (`.json`, one record or an
+/// array) to `firstbase_json/<correlation ID or UDI-DI>.json`; converted files
+/// move to `processed/`.
+fn process_swissdamed_export_dir(input_dir: &Path, config: &config::Config) -> Result<()> {
+    let output_dir = Path::new("firstbase_json");
+    let processed_dir = input_dir.join("processed");
+    std::fs::create_dir_all(output_dir)?;
+
+    let mut converted = 0;
+    let mut filtered = 0;
+    let mut errors = 0;
+    let mut processed_files = Vec::new();
+    for entry in std::fs::read_dir(input_dir)
+        .with_context(|| format!("Failed to read {} directory", input_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().map(|e| e != "json").unwrap_or(true) {
+            continue;
+        }
+        let file_stem = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let records = match std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| swissdamed_json::parse_swissdamed_json(&json))
+        {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("  Error in {}: {:#}", path.display(), e);
+                errors += 1;
+                continue;
+            }
+        };
+        let mut file_errors = 0;
+        for (i, record) in records.iter().enumerate() {
+            let fallback = if records.len() == 1 {
+                file_stem.clone()
+            } else {
+                format!("{}_{}", file_stem, i + 1)
+            };
+            let stem = record.stem(&fallback);
+            match transform_swissdamed_json::transform_swissdamed_document(record, config, &stem) {
+                Ok(document) if !device_filter::admits(&document.trade_item) => filtered += 1,
+                Ok(document) => {
+                    let output_path = output_dir.join(format!("{}.json", stem));
+                    write_json(
+                        &output_path,
+                        &firstbase::DraftItemDocument {
+                            draft_item: document,
+                        },
+                    )?;
+                    println!("  {} -> {}", path.display(), output_path.display());
+                    converted += 1;
+                }
+                Err(e) => {
+                    eprintln!("  Error in {} record {}: {:#}", path.display(), i + 1, e);
+                    file_errors += 1;
+                }
+            }
+        }
+        errors += file_errors;
+        if file_errors == 0 {
+            processed_files.push(path);
+        }
+    }
+
+    if !processed_files.is_empty() {
+        std::fs::create_dir_all(&processed_dir)?;
+        for path in &processed_files {
+            if let Some(name) = path.file_name() {
+                if let Err(e) = std::fs::rename(path, processed_dir.join(name)) {
+                    eprintln!(
+                        "  Warning: could not move {} to processed/: {}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    println!(
+        "\nConverted {} swissdamed records, {} filtered out, {} errors -> {}",
+        converted,
+        filtered,
+        errors,
+        output_dir.display()
+    );
+    Ok(())
+}
+
 /// Read SRNs from an xlsx file column (1-based)
 fn count_srns_xlsx(path: &str, col: usize) -> Result<Vec<String>> {
     use calamine::{open_workbook, Reader, Xlsx};
@@ -3407,15 +5373,8 @@ fn reconvert_uuids_from_detail(
         };
         let doc = transform_detail::transform_detail_document(&device, fb_config, basic_udi, &uuid);
         let draft_doc = firstbase::DraftItemDocument { draft_item: doc };
-        let out = match serde_json::to_string_pretty(&draft_doc) {
-            Ok(s) => s,
-            Err(_) => {
-                errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                return;
-            }
-        };
         let out_path = output_dir.join(format!("{}.json", uuid));
-        if std::fs::write(&out_path, &out).is_err() {
+        if write_json(&out_path, &draft_doc).is_err() {
             errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
             return;
         }
@@ -3453,6 +5412,149 @@ fn load_basic_udi_cache(cache_dir: &Path) -> HashMap<String, api_detail::BasicUd
         .collect()
 }
 
+/// Layout of the batch files written by the `xml`, `ndjson` and `detail` modes.
+/// `Ndjson` puts one compact DraftItem document per line so downstream jobs can
+/// stream the file instead of loading one giant JSON array.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// `--output-format json|ndjson` (default json). Parsed by the modes
+    /// that write batch files only, so a bad value never aborts another
+    /// subcommand.
+    fn from_args(args: &[String]) -> Result<OutputFormat> {
+        match args
+            .iter()
+            .position(|a| a == "--output-format")
+            .map(|i| args.get(i + 1).map(|s| s.as_str()))
+        {
+            None | Some(Some("json")) => Ok(OutputFormat::Json),
+            Some(Some("ndjson")) => Ok(OutputFormat::Ndjson),
+            Some(other) => anyhow::bail!(
+                "Unknown --output-format {:?} (expected json or ndjson)",
+                other.unwrap_or("")
+            ),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Incremental writer for batch files. Documents are serialized one at a time
+/// straight into a `BufWriter` (`[`, commas and `]` written by hand; the
+/// nested indent added by `Indented` on the way through), so memory stays
+/// flat regardless of dataset size — neither the whole Vec nor its pretty
+/// string has to be held at once. The `Json` layout is byte-identical to
+/// `serde_json::to_string_pretty` of the full array.
+struct BatchWriter {
+    out: std::io::BufWriter<std::fs::File>,
+    format: OutputFormat,
+    count: usize,
+    size: usize,
+}
+
+impl BatchWriter {
+    fn create(path: &Path, format: OutputFormat) -> Result<BatchWriter> {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(BatchWriter {
+            out: std::io::BufWriter::new(file),
+            format,
+            count: 0,
+            size: 0,
+        })
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> Result<()> {
+        use std::io::Write;
+        self.out.write_all(bytes)?;
+        self.size += bytes.len();
+        Ok(())
+    }
+
+    fn write<T: serde::Serialize>(&mut self, document: &T) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                self.emit(if self.count == 0 { b"[\n  " } else { b",\n  " })?;
+                serde_json::to_writer_pretty(Indented(self), document)?;
+            }
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(Counted(self), document)?;
+                self.emit(b"\n")?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Close the array (json) and flush; returns the bytes written.
+    fn finish(mut self) -> Result<usize> {
+        use std::io::Write;
+        if self.format == OutputFormat::Json {
+            self.emit(if self.count == 0 { b"[]" } else { b"\n]" })?;
+        }
+        self.out.flush()?;
+        Ok(self.size)
+    }
+}
+
+/// Serializer sink for one batch element: counted like `emit`.
+struct Counted<'a>(&'a mut BatchWriter);
+
+impl std::io::Write for Counted<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.out.write_all(buf)?;
+        self.0.size += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Like `Counted`, but re-indents the element one level, as the array printer
+/// would. Raw newlines only occur between tokens (strings escape them).
+struct Indented<'a>(&'a mut BatchWriter);
+
+impl std::io::Write for Indented<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut out = Counted(&mut *self.0);
+        for (i, part) in buf.split(|&b| b == b'\n').enumerate() {
+            if i > 0 {
+                out.write_all(b"\n  ")?;
+            }
+            out.write_all(part)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize `value` pretty-printed straight into `path` through a
+/// `BufWriter`, without first building the whole document as a `String`.
+/// Same bytes as `serde_json::to_string_pretty` + `fs::write`.
+pub fn write_json(path: &Path, value: &(impl serde::Serialize + ?Sized)) -> Result<()> {
+    use std::io::Write;
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut out = std::io::BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut out, value)?;
+    out.flush()?;
+    Ok(())
+}
+
 fn format_size(bytes: usize) -> String {
     if bytes >= 1_048_576 {
         format!("{:.1} MB", bytes as f64 / 1_048_576.0)
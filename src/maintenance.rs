@@ -0,0 +1,238 @@
+//! Local state maintenance (`maintenance` subcommand).
+//! Applies the `[retention]` periods from config.toml: deletes downloaded
+//! source files, logs and pushed documents older than their retention, prunes
+//! old `push_log`/`device_history` rows (with their archived push payloads)
+//! and orphaned provenance sidecars, then
+//! vacuums every SQLite database in `db/` so the freed pages go back to disk.
+//! `--dry-run` only counts what would go.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use rusqlite::Connection;
+
+use crate::config::Retention;
+use crate::provenance::PROVENANCE_DIR;
+
+/// Files deleted for one retention category.
+#[derive(Debug, Default)]
+pub struct Pruned {
+    pub label: &'static str,
+    pub days: u32,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Size of one database before and after `VACUUM`.
+#[derive(Debug)]
+pub struct Vacuumed {
+    pub path: PathBuf,
+    pub before: u64,
+    pub after: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub pruned: Vec<Pruned>,
+    pub orphaned_sidecars: usize,
+    pub push_log_rows: usize,
+    /// Archived raw payloads no longer referenced by any `push_log` row.
+    pub payload_blobs: usize,
+    pub history_rows: usize,
+    pub vacuumed: Vec<Vacuumed>,
+}
+
+fn cutoff(days: u32) -> Option<SystemTime> {
+    (days > 0).then(|| SystemTime::now() - Duration::from_secs(u64::from(days) * 86_400))
+}
+
+/// ISO timestamp `days` ago, comparable with the `%Y-%m-%dT%H:%M:%S[Z]`
+/// strings the DB stores.
+fn cutoff_timestamp(days: u32) -> Option<String> {
+    (days > 0).then(|| {
+        (chrono::Utc::now() - chrono::Duration::days(i64::from(days)))
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string()
+    })
+}
+
+/// Delete (or with `dry_run` count) the files below `dir` modified before `before`.
+fn prune_dir(dir: &Path, before: SystemTime, dry_run: bool, pruned: &mut Pruned) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            prune_dir(&path, before, dry_run, pruned);
+            continue;
+        }
+        if meta.modified().map(|t| t >= before).unwrap_or(true) {
+            continue;
+        }
+        if dry_run || std::fs::remove_file(&path).is_ok() {
+            pruned.files += 1;
+            pruned.bytes += meta.len();
+        }
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type='table' AND name=?1",
+        [table],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+/// Delete (or count) the rows of `table` whose `column` is older than `days`.
+fn prune_rows(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    days: u32,
+    dry_run: bool,
+) -> anyhow::Result<usize> {
+    let Some(before) = cutoff_timestamp(days) else {
+        return Ok(0);
+    };
+    if !table_exists(conn, table) {
+        return Ok(0);
+    }
+    let n = if dry_run {
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE {} < ?1", table, column),
+            [&before],
+            |r| r.get::<_, i64>(0),
+        )? as usize
+    } else {
+        conn.execute(
+            &format!("DELETE FROM {} WHERE {} < ?1", table, column),
+            [&before],
+        )?
+    };
+    Ok(n)
+}
+
+/// Provenance sidecars whose document is neither pending nor processed.
+fn prune_orphaned_sidecars(firstbase_dir: &Path, dry_run: bool) -> usize {
+    let Ok(entries) = std::fs::read_dir(firstbase_dir.join(PROVENANCE_DIR)) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if firstbase_dir.join(&name).exists()
+            || firstbase_dir.join("processed").join(&name).exists()
+        {
+            continue;
+        }
+        if dry_run || std::fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Apply `retention` to the data directory `data_dir`.
+pub fn run(data_dir: &Path, retention: &Retention, dry_run: bool) -> anyhow::Result<Outcome> {
+    let mut outcome = Outcome::default();
+    let firstbase_dir = data_dir.join("firstbase_json");
+    let categories: [(&'static str, u32, Vec<PathBuf>); 3] = [
+        (
+            "downloaded source files",
+            retention.raw_days,
+            vec![
+                data_dir.join("eudamed_json").join("detail"),
+                data_dir.join("eudamed_json").join("basic"),
+                data_dir.join("ndjson"),
+                data_dir.join("xml"),
+            ],
+        ),
+        (
+            "logs and reports",
+            retention.log_days,
+            vec![data_dir.join("log"), firstbase_dir.join("delta")],
+        ),
+        (
+            "pushed documents",
+            retention.processed_days,
+            vec![
+                firstbase_dir.join("processed"),
+                data_dir.join("swissdamed_json").join("processed"),
+            ],
+        ),
+    ];
+    for (label, days, dirs) in categories {
+        let mut pruned = Pruned {
+            label,
+            days,
+            ..Default::default()
+        };
+        if let Some(before) = cutoff(days) {
+            for dir in &dirs {
+                prune_dir(dir, before, dry_run, &mut pruned);
+            }
+        }
+        outcome.pruned.push(pruned);
+    }
+    outcome.orphaned_sidecars = prune_orphaned_sidecars(&firstbase_dir, dry_run);
+
+    let db_dir = data_dir.join("db");
+    let version_db = data_dir.join(crate::version_db::VERSION_DB_PATH);
+    if version_db.exists() {
+        let conn = Connection::open(&version_db)?;
+        outcome.push_log_rows = prune_rows(
+            &conn,
+            "push_log",
+            "pushed_at",
+            retention.push_log_days,
+            dry_run,
+        )?;
+        if !dry_run && table_exists(&conn, "push_log") {
+            outcome.payload_blobs = crate::payload_archive::prune_orphans(&conn)?;
+        }
+        outcome.history_rows = prune_rows(
+            &conn,
+            "device_history",
+            "seen_at",
+            retention.history_days,
+            dry_run,
+        )?;
+    }
+
+    if dry_run {
+        return Ok(outcome);
+    }
+    let mut dbs: Vec<PathBuf> = std::fs::read_dir(&db_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|e| e == "db").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    dbs.sort();
+    for path in dbs {
+        let wal = PathBuf::from(format!("{}-wal", path.display()));
+        let before = file_size(&path) + file_size(&wal);
+        let conn = Connection::open(&path)?;
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        drop(conn);
+        outcome.vacuumed.push(Vacuumed {
+            after: file_size(&path) + file_size(&wal),
+            path,
+            before,
+        });
+    }
+    Ok(outcome)
+}
@@ -0,0 +1,440 @@
+//! Machine-readable EUDAMED → GS1 mapping matrix (`mappings export`).
+//! One row per field mapping (EUDAMED API detail / Basic UDI-DI path →
+//! firstbase TradeItem path) and per code-list entry, for audits and
+//! customer documentation.
+//!
+//! Code rows are generated by running the `mappings.rs` functions over their
+//! source code lists (and the numeric CST / MU ranges), so the matrix always
+//! shows what the converter actually emits. Field rows describe the API detail
+//! path (`transform_detail.rs`); keep them in step when that mapping changes.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::mappings;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MappingRow {
+    /// `field` (attribute → attribute) or `code` (code-list value → value).
+    pub kind: &'static str,
+    pub source_path: String,
+    pub source_value: String,
+    pub target_path: String,
+    pub target_value: String,
+    pub rule: String,
+}
+
+const MDI: &str = "MedicalDeviceTradeItemModule/MedicalDeviceInformation";
+const HCI: &str = "HealthcareItemInformationModule/HealthcareItemInformation";
+const CLASSIFICATION: &str = "GdsnTradeItemClassification/AdditionalTradeItemClassification";
+
+/// Field mappings of the API detail path: (source, target, rule).
+/// `basicUdi.*` sources come from the device's Basic UDI-DI record; targets
+/// starting with `MDI/` or `HCI/` sit below the medical device / healthcare module.
+const FIELDS: &[(&str, &str, &str)] = &[
+    ("primaryDi.code", "Gtin", "only when issuingAgency is GS1"),
+    ("primaryDi.code", "AdditionalTradeItemIdentification/Value", "non-GS1 DI; type code from issuingAgency (issuing_agency_to_type_code)"),
+    ("secondaryDi.code", "AdditionalTradeItemIdentification/Value", "type code from issuingAgency, default GTIN_14"),
+    ("reference", "AdditionalTradeItemIdentification[MANUFACTURER_PART_NUMBER]/Value", "fallback primaryDi.code (097.006); truncated to 80 chars"),
+    ("basicUdi.deviceModel", "AdditionalTradeItemIdentification[MODEL_NUMBER]/Value", "truncated to 80 chars"),
+    ("basicUdi.basicUdi.code", "GlobalModelInformation/GlobalModelNumber", "1:1, omitted when empty"),
+    ("basicUdi.deviceName", "GlobalModelInformation/GlobalModelDescription[en]", "1:1"),
+    ("tradeName.texts[]", "TradeItemDescriptionModule/TradeItemDescriptionInformation/TradeItemDescription", "per language"),
+    ("tradeName.texts[]", "TradeItemDescriptionModule/TradeItemDescriptionInformation/DescriptionShort", "per language, truncated to 40 chars"),
+    ("additionalDescription.texts[]", "TradeItemDescriptionModule/TradeItemDescriptionInformation/AdditionalTradeItemDescription", "per language"),
+    ("additionalInformationUrl", "ReferencedFileDetailInformationModule/ReferencedFileHeader[IFU]/UniformResourceIdentifier", "IsPrimaryFile TRUE"),
+    ("versionDate", "TradeItemSynchronisationDates/EffectiveDateTime", "fallback conversion time"),
+    ("deviceStatus.type.code", "MDI/EUMedicalDeviceStatusCode/Value", "device_status_to_gs1"),
+    ("deviceStatus.type.code / statusDate, msWhereAvailable[].endDate", "TradeItemSynchronisationDates/DiscontinuedDateTime", "when no longer on the market: latest market end, else status date, else conversion date + 1 day; open markets end there"),
+    ("basicUdi.legislation.code", "RegulatedTradeItemModule/RegulatoryInformation/RegulatoryAct", "last segment uppercased; fallback regulation_from_risk_class_refdata; agency EU"),
+    ("basicUdi.deviceCertificateInfoListForDisplay[].certificateNumber", "RegulatedTradeItemModule/RegulatoryInformation/RegulatoryPermitIdentification", "one certificate: under the regulatory act, valid, latest expiry first"),
+    ("basicUdi.deviceCertificateInfoListForDisplay[].notifiedBody.srn", "RegulatedTradeItemModule/RegulatoryInformation/RegulatoryAgencyIdentification", "type EU_NOTIFIED_BODY_NUMBER; same certificate"),
+    ("basicUdi.deviceCertificateInfoListForDisplay[].startingValidityDate / issueDate / certificateExpiry", "RegulatedTradeItemModule/RegulatoryInformation/PermitStartDateTime, PermitEndDateTime", "same certificate; start falls back to issueDate"),
+    ("basicUdi.riskClass.code", "GdsnTradeItemClassification/AdditionalTradeItemClassification[76|85]/AdditionalTradeItemClassificationValue/AdditionalTradeItemClassificationCodeValue", "risk_class_refdata_to_gs1; system 85 for MDD/AIMDD/IVDD"),
+    ("cndNomenclatures[].code", "GdsnTradeItemClassification/AdditionalTradeItemClassification[88]/AdditionalTradeItemClassificationValue/AdditionalTradeItemClassificationCodeValue", "EMDN code 1:1"),
+    ("udiPiType.*", "MDI/UDIProductionIdentifierTypeCode/Value", "one code per true flag; omitted for legacy (097.095)"),
+    ("udiPiType.softwareIdentification", "MDI/SpecialDeviceTypeCode/Value", "SOFTWARE (097.091)"),
+    ("baseQuantity", "MDI/UdidDeviceCount", "omitted for legacy (097.095)"),
+    ("directMarkingDi.code", "MDI/DirectPartMarkingIdentifier/Value", "agency from issuingAgency; omitted for legacy"),
+    ("unitOfUse.code", "TradeItemInformation/TradeItemComponents/ComponentInformation/ComponentIdentification/Value", "component quantity = baseQuantity"),
+    ("linkedUdiDiView", "ReferencedTradeItem/Gtin", "REPLACED (legacy link) or REPLACED_BY"),
+    ("sterile", "MDI/TradeItemSterilityInformation/InitialManufacturerSterilisationCode/Value", "true → UNSPECIFIED, false → NOT_STERILISED"),
+    ("sterilization", "MDI/TradeItemSterilityInformation/InitialSterilisationPriorToUseCode/Value", "true → UNSPECIFIED, false → NO_STERILISATION_REQUIRED"),
+    ("singleUse", "MDI/HealthcareTradeItemReusabilityInformation/ManufacturerDeclaredReusabilityTypeCode/Value", "SINGLE_USE, LIMITED_REUSABLE (maxNumberOfReuses set) or REUSABLE"),
+    ("maxNumberOfReuses", "MDI/HealthcareTradeItemReusabilityInformation/MaximumCyclesReusable", "1:1"),
+    ("reprocessed", "MDI/IsReprocessedSingleUseDevice", "omitted for system/procedure packs"),
+    ("newDevice", "MDI/IsNewDevice", "defaults to false for IVDR/IVDD (097.047)"),
+    ("basicUdi.implantable", "MDI/IsTradeItemImplantable", "default false; omitted for system/procedure packs"),
+    ("basicUdi.sutures", "MDI/IsDeviceExemptFromImplantObligations", "only implantable EU_CLASS_IIB (097.015)"),
+    ("basicUdi.measuringFunction", "MDI/HasDeviceMeasuringFunction", "default false"),
+    ("basicUdi.active", "MDI/IsActiveDevice", "default false"),
+    ("basicUdi.administeringMedicine", "MDI/IsDeviceIntendedToAdministerOrRemoveMedicinalProduct", "default false"),
+    ("basicUdi.medicinalProduct", "MDI/IsDeviceMedicinalProduct", "default false"),
+    ("basicUdi.reusable", "MDI/IsReusableSurgicalInstrument", "default false"),
+    ("basicUdi.multiComponent.code", "MDI/MultiComponentDeviceTypeCode/Value", "criterion STANDARD: multi_component_to_gs1"),
+    ("basicUdi.multiComponent.code", "MDI/SystemOrProcedurePackTypeCode/Value", "criterion SPP under MDR: spp_type_to_gs1"),
+    ("basicUdi.medicalPurpose.texts[]", "MDI/SystemOrProcedurePackMedicalPurposeDescription", "SPP only; fallback basicUdi.deviceName"),
+    ("annexXVIApplicable + contactLenses/productsToBeIntroduced/fillingByInjection/equipmentForAdiposeTissue/emr/brainElectroStimulation", "MDI/AnnexXVIIntendedPurposeTypeCode/Value", "one code per true flag; omitted for legacy"),
+    ("latex", "HCI/DoesTradeItemContainLatex", "default FALSE"),
+    ("basicUdi.humanProduct", "HCI/DoesTradeItemContainHumanBloodDerivative", "default FALSE"),
+    ("basicUdi.humanTissues", "HCI/DoesTradeItemContainHumanTissue", "default FALSE"),
+    ("basicUdi.animalTissues", "HCI/DoesTradeItemContainAnimalTissue", "default false"),
+    ("clinicalSizes[].type.code", "HCI/ClinicalSize/ClinicalSizeTypeCode/Value", "clinical_size_type_to_gs1"),
+    ("clinicalSizes[].value / minimumValue / maximumValue / text", "HCI/ClinicalSize/ClinicalSizeValue, ClinicalSizeValueMaximum, ClinicalSizeValueText", "by precision code"),
+    ("clinicalSizes[].metricOfMeasurement.code", "HCI/ClinicalSize/ClinicalSizeValue/MeasurementUnitCode", "measurement_unit_to_gs1 (MU01..MU136)"),
+    ("clinicalSizes[].metricOfMeasurement.code", "HCI/ClinicalSize/ClinicalSizeCharacteristicsCode/Value", "mu_code_to_characteristic_code (MU137..MU176)"),
+    ("storageHandlingConditions[].typeCode", "HCI/ClinicalStorageHandlingInformation/ClinicalStorageHandlingTypeCode/Value", "storage_handling_to_gs1"),
+    ("storageHandlingConditions[].description", "HCI/ClinicalStorageHandlingInformation/ClinicalStorageHandlingDescription", "per language"),
+    ("criticalWarnings[].typeCode", "HCI/ClinicalWarning/ClinicalWarningCode", "last segment uppercased; agency EUDAMED"),
+    ("criticalWarnings[].description", "HCI/ClinicalWarning/WarningsOrContraIndicationDescription", "per language"),
+    ("medicinalProductSubstances[]", "ChemicalRegulationInformationModule/ChemicalRegulationInformation[WHO/INN]/RegulatedChemical", "RegulatedChemicalTypeCode MEDICINAL_PRODUCT"),
+    ("humanProductSubstances[]", "ChemicalRegulationInformationModule/ChemicalRegulationInformation[WHO/INN]/RegulatedChemical", "RegulatedChemicalTypeCode HUMAN_PRODUCT"),
+    ("cmrSubstances[]", "ChemicalRegulationInformationModule/ChemicalRegulationInformation[ECHA/ECICS]/RegulatedChemical", "CarcinogenicMutagenicReprotoxicTypeCode via cmr_type_to_gs1; omitted for legacy"),
+    ("endocrineDisruptingSubstances[]", "ChemicalRegulationInformationModule/ChemicalRegulationInformation[ECHA/ECICS]/RegulatedChemical", "ENDOCRINE_SUBSTANCE; omitted for legacy"),
+    ("placedOnTheMarket.iso2Code", "SalesInformationModule/SalesInformation/TargetMarketSalesConditions[ORIGINAL_PLACED]/SalesConditionTargetMarketCountry/CountryCode/Value", "country_alpha2_to_numeric; fallback manufacturer SRN country"),
+    ("marketInfoLink.msWhereAvailable[].country.iso2Code", "SalesInformationModule/SalesInformation/TargetMarketSalesConditions[ADDITIONAL_MARKET_AVAILABILITY]/SalesConditionTargetMarketCountry/CountryCode/Value", "country_alpha2_to_numeric; GB/XI skipped"),
+    ("marketInfoLink.msWhereAvailable[].startDate / endDate", "SalesInformationModule/SalesInformation/TargetMarketSalesConditions/SalesConditionTargetMarketCountry/StartAvailabilityDateTime, EndAvailabilityDateTime", "1:1"),
+    ("productDesigner", "TradeItemContactInformation[EPD]", "name, SRN, address (country_alpha2_to_numeric), phone, email"),
+    ("basicUdi.manufacturer.srn / name", "TradeItemContactInformation[EMA|EPP]", "EPP for MDR system/procedure packs (097.016), else EMA"),
+    ("basicUdi.authorisedRepresentative.srn / name", "TradeItemContactInformation[EAR]", "non-EU manufacturer SRN only (097.054)"),
+    ("basicUdi.deviceCertificateInfoListForDisplay[]", "CertificationInformationModule/CertificationInformation", "certificate type → CertificationStandard; NB number → AdditionalCertificationOrganisationIdentifier"),
+    ("config [provider]", "InformationProviderOfTradeItem/Gln, PartyName", "from config.toml"),
+    ("config [parties] / [parties.by_srn]", "BrandOwner/Gln, PartyName; ManufacturerOfTradeItem/Gln, PartyName", "from config.toml; per manufacturer SRN when configured, omitted when unset"),
+    ("config [swiss_market]", "SalesInformationModule/.../SalesConditionTargetMarketCountry[756]; TradeItemContactInformation[EAR, CHRN]", "target market 756 only; availability rule, start date and CH-REP per manufacturer SRN"),
+    ("config [gpc] / [gpc_by_emdn]", "GdsnTradeItemClassification/GpcSegmentCode .. GpcCategoryName", "from config.toml; brick by EMDN code or prefix when configured"),
+    ("config [target_market]", "TargetMarket/TargetMarketCountryCode/Value", "from config.toml"),
+    ("containedItem (recursive)", "CatalogueItemChildItemLink/CatalogueItem", "one package level per containedItem; numberOfItems → Quantity"),
+];
+
+fn field_row(source: &str, target: &str, rule: &str) -> MappingRow {
+    let target = target
+        .replace("MDI/", &format!("{}/", MDI))
+        .replace("HCI/", &format!("{}/", HCI));
+    MappingRow {
+        kind: "field",
+        source_path: source.to_string(),
+        source_value: String::new(),
+        target_path: target,
+        target_value: String::new(),
+        rule: rule.to_string(),
+    }
+}
+
+fn code_row(
+    source_path: &str,
+    source: &str,
+    target_path: &str,
+    target: &str,
+    rule: &str,
+) -> MappingRow {
+    MappingRow {
+        kind: "code",
+        source_path: source_path.to_string(),
+        source_value: source.to_string(),
+        target_path: target_path.to_string(),
+        target_value: target.to_string(),
+        rule: rule.to_string(),
+    }
+}
+
+/// Last segments of the EUDAMED refdata codes each mapping function handles.
+const RISK_CLASSES: &[&str] = &[
+    "class-i",
+    "class-iia",
+    "class-iib",
+    "class-iii",
+    "class-a",
+    "class-b",
+    "class-c",
+    "class-d",
+    "ivd-general",
+    "ivd-devices-self-testing",
+    "ivd-annex-ii-list-a",
+    "ivd-annex-ii-list-b",
+    "aimdd",
+];
+const DEVICE_STATUSES: &[&str] = &[
+    "ON_THE_MARKET",
+    "ON_MARKET",
+    "NO_LONGER_PLACED_ON_THE_MARKET",
+    "NO_LONGER_ON_THE_MARKET",
+    "NOT_INTENDED_FOR_EU_MARKET",
+];
+const ISSUING_AGENCIES: &[&str] = &["gs1", "hibcc", "iccbba", "ifa", "eudamed"];
+const MULTI_COMPONENTS: &[&str] = &[
+    "device",
+    "system",
+    "spp-system",
+    "procedure-pack",
+    "spp-procedure-pack",
+    "kit",
+];
+const SPP_TYPES: &[&str] = &[
+    "system",
+    "spp-system",
+    "procedure-pack",
+    "spp-procedure-pack",
+];
+const CMR_TYPES: &[&str] = &["1a", "1b", "2"];
+const SUBSTANCE_TYPES: &[&str] = &["MEDICINAL_PRODUCT_SUBSTANCE", "HUMAN_PRODUCT_SUBSTANCE"];
+const PI_FLAGS: &[(&str, &str)] = &[
+    ("batchNumber", "BATCH_NUMBER"),
+    ("serializationNumber", "SERIAL_NUMBER"),
+    ("manufacturingDate", "MANUFACTURING_DATE"),
+    ("expirationDate", "EXPIRATION_DATE"),
+    ("softwareIdentification", "SOFTWARE_IDENTIFICATION"),
+];
+
+fn code_rows() -> Vec<MappingRow> {
+    let mut rows = Vec::new();
+    let risk_target = format!(
+        "{}/AdditionalTradeItemClassificationValue/AdditionalTradeItemClassificationCodeValue",
+        CLASSIFICATION
+    );
+    let system_target = format!(
+        "{}/AdditionalTradeItemClassificationSystemCode/Value",
+        CLASSIFICATION
+    );
+    for rc in RISK_CLASSES {
+        let source = format!("refdata.risk-class.{}", rc);
+        rows.push(code_row(
+            "basicUdi.riskClass.code",
+            &source,
+            &risk_target,
+            mappings::risk_class_refdata_to_gs1(&source),
+            "risk_class_refdata_to_gs1",
+        ));
+        rows.push(code_row(
+            "basicUdi.riskClass.code",
+            &source,
+            &system_target,
+            mappings::risk_class_system_code(&source),
+            "risk_class_system_code",
+        ));
+        rows.push(code_row(
+            "basicUdi.riskClass.code",
+            &source,
+            "RegulatedTradeItemModule/RegulatoryInformation/RegulatoryAct",
+            mappings::regulation_from_risk_class_refdata(&source),
+            "regulation_from_risk_class_refdata (when basicUdi.legislation is missing)",
+        ));
+    }
+    let status_target = format!("{}/EUMedicalDeviceStatusCode/Value", MDI);
+    for status in DEVICE_STATUSES {
+        rows.push(code_row(
+            "deviceStatus.type.code",
+            status,
+            &status_target,
+            mappings::device_status_to_gs1(status),
+            "device_status_to_gs1",
+        ));
+    }
+    let pi_target = format!("{}/UDIProductionIdentifierTypeCode/Value", MDI);
+    for (flag, code) in PI_FLAGS {
+        rows.push(code_row(
+            &format!("udiPiType.{}", flag),
+            "true",
+            &pi_target,
+            code,
+            "ApiDeviceDetail::production_identifiers",
+        ));
+    }
+    for agency in ISSUING_AGENCIES {
+        rows.push(code_row(
+            "primaryDi/secondaryDi/directMarkingDi.issuingAgency.code",
+            &format!("refdata.issuing-agency.{}", agency),
+            "AdditionalTradeItemIdentification/AdditionalTradeItemIdentificationTypeCode",
+            mappings::issuing_agency_to_type_code(agency),
+            "issuing_agency_to_type_code",
+        ));
+    }
+    let mc_target = format!("{}/MultiComponentDeviceTypeCode/Value", MDI);
+    for mc in MULTI_COMPONENTS {
+        let source = format!("refdata.multi-component.{}", mc);
+        rows.push(code_row(
+            "basicUdi.multiComponent.code",
+            &source,
+            &mc_target,
+            mappings::multi_component_to_gs1(&source),
+            "multi_component_to_gs1 (criterion STANDARD)",
+        ));
+    }
+    let spp_target = format!("{}/SystemOrProcedurePackTypeCode/Value", MDI);
+    for spp in SPP_TYPES {
+        let source = format!("refdata.multi-component.{}", spp);
+        rows.push(code_row(
+            "basicUdi.multiComponent.code",
+            &source,
+            &spp_target,
+            mappings::spp_type_to_gs1(&source),
+            "spp_type_to_gs1 (criterion SPP)",
+        ));
+    }
+    for cmr in CMR_TYPES {
+        let source = format!("refdata.cmr-substance-type.{}", cmr);
+        rows.push(code_row(
+            "cmrSubstances[].type.code",
+            &source,
+            "ChemicalRegulationInformationModule/ChemicalRegulationInformation/RegulatedChemical/CarcinogenicMutagenicReprotoxicTypeCode/Value",
+            &mappings::cmr_type_to_gs1(&source),
+            "cmr_type_to_gs1",
+        ));
+    }
+    for substance in SUBSTANCE_TYPES {
+        rows.push(code_row(
+            "substance type",
+            substance,
+            "ChemicalRegulationInformationModule/ChemicalRegulationInformation/RegulatedChemical/RegulatedChemicalTypeCode/Value",
+            mappings::substance_type_to_gs1(substance),
+            "substance_type_to_gs1",
+        ));
+    }
+
+    // Numeric code lists: every code the functions translate (identity = unmapped).
+    let cst_target = format!("{}/ClinicalSize/ClinicalSizeTypeCode/Value", HCI);
+    for n in 1..=999 {
+        let cst = format!("CST{}", n);
+        let gs1 = mappings::clinical_size_type_to_gs1(&cst);
+        if gs1 != cst {
+            rows.push(code_row(
+                "clinicalSizes[].type.code",
+                &cst,
+                &cst_target,
+                gs1,
+                "clinical_size_type_to_gs1",
+            ));
+        }
+    }
+    for n in 1..=999 {
+        let mu = format!("MU{:02}", n);
+        if let Some(characteristic) = mappings::mu_code_to_characteristic_code(&mu) {
+            rows.push(code_row(
+                "clinicalSizes[].metricOfMeasurement.code",
+                &mu,
+                &format!("{}/ClinicalSize/ClinicalSizeCharacteristicsCode/Value", HCI),
+                characteristic,
+                "mu_code_to_characteristic_code",
+            ));
+            continue;
+        }
+        let unit = mappings::measurement_unit_to_gs1(&mu);
+        if unit != mu {
+            rows.push(code_row(
+                "clinicalSizes[].metricOfMeasurement.code",
+                &mu,
+                &format!("{}/ClinicalSize/ClinicalSizeValue/MeasurementUnitCode", HCI),
+                &unit,
+                if unit.is_empty() {
+                    "measurement_unit_to_gs1 (no UN/CEFACT unit, value dropped)"
+                } else {
+                    "measurement_unit_to_gs1"
+                },
+            ));
+        }
+    }
+    rows.push(code_row(
+        "storageHandlingConditions[].typeCode",
+        "SHCnnn (e.g. SHC012)",
+        &format!(
+            "{}/ClinicalStorageHandlingInformation/ClinicalStorageHandlingTypeCode/Value",
+            HCI
+        ),
+        &format!(
+            "SHCnn (e.g. {})",
+            mappings::storage_handling_to_gs1("SHC012")
+        ),
+        "storage_handling_to_gs1: number re-padded to two digits",
+    ));
+    for iso2 in mappings::ACTOR_COUNTRY_CODES {
+        let numeric = mappings::country_alpha2_to_numeric(iso2);
+        if numeric == *iso2 && *iso2 != "XI" {
+            continue;
+        }
+        rows.push(code_row(
+            "*.country.iso2Code",
+            iso2,
+            "SalesConditionTargetMarketCountry/CountryCode/Value, StructuredAddress/CountryCode/Value",
+            &numeric,
+            if mappings::is_valid_gdsn_market_country(iso2) {
+                "country_alpha2_to_numeric"
+            } else {
+                "country_alpha2_to_numeric (addresses only; not a GDSN sales market)"
+            },
+        ));
+    }
+    rows
+}
+
+/// The complete matrix: field rows first, then code rows.
+pub fn mapping_matrix() -> Vec<MappingRow> {
+    let mut rows: Vec<MappingRow> = FIELDS
+        .iter()
+        .map(|(source, target, rule)| field_row(source, target, rule))
+        .collect();
+    rows.extend(code_rows());
+    rows
+}
+
+fn csv_field(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Write the matrix to `out_path` as CSV, or JSON when `json`. Returns the row count.
+pub fn export_mappings(out_path: &Path, json: bool) -> anyhow::Result<usize> {
+    let rows = mapping_matrix();
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if json {
+        crate::write_json(out_path, &rows)?;
+    } else {
+        let mut csv =
+            String::from("kind,source_path,source_value,target_path,target_value,rule\r\n");
+        for r in &rows {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\r\n",
+                r.kind,
+                csv_field(&r.source_path),
+                csv_field(&r.source_value),
+                csv_field(&r.target_path),
+                csv_field(&r.target_value),
+                csv_field(&r.rule)
+            ));
+        }
+        std::fs::write(out_path, csv)
+            .with_context(|| format!("Failed to write {}", out_path.display()))?;
+    }
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_is_generated_from_mapping_functions() {
+        let rows = mapping_matrix();
+        let find = |source: &str, target_suffix: &str| {
+            rows.iter()
+                .find(|r| r.source_value == source && r.target_path.ends_with(target_suffix))
+                .map(|r| r.target_value.as_str())
+        };
+        assert_eq!(find("CST45", "ClinicalSizeTypeCode/Value"), Some("COLOUR"));
+        assert_eq!(
+            find("MU137", "ClinicalSizeCharacteristicsCode/Value"),
+            Some("PASSIVE")
+        );
+        assert_eq!(find("MU50", "MeasurementUnitCode"), Some("MMT"));
+        assert_eq!(
+            find("refdata.risk-class.ivd-general", "SystemCode/Value"),
+            Some("85")
+        );
+        // Unmapped gaps (identity) are not listed.
+        assert_eq!(find("CST64", "ClinicalSizeTypeCode/Value"), None);
+        assert!(rows
+            .iter()
+            .any(|r| r.kind == "field" && r.target_path == "Gtin"));
+    }
+}
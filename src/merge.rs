@@ -0,0 +1,146 @@
+//! Three-way merge (`merge` mode): the listing, detail and Basic UDI-DI
+//! exports joined into one record per GTIN before the transform.
+//!
+//! `detail` mode only gets Basic UDI-DI data from `basic_udi_cache/`, keyed
+//! by device UUID, and a device missing there is transformed without it; the
+//! listing merge (`merge_listing_data`) then fills in no more than the
+//! manufacturer, AR, risk class and Basic UDI-DI code. Here the Basic UDI-DI
+//! export is indexed by its code, and the detail record reaches it through
+//! its listing row (GTIN → `basicUdi`), so the transform sees all three
+//! sources; the cache only stands in for a code the export lacks. The listing
+//! row is the base unit's (`ApiDeviceDetail::gtin`), also for devices with
+//! a packaging hierarchy.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+
+use crate::api_detail::{self, BasicUdiDiData};
+use crate::ndjson_input;
+
+/// Basic UDI-DI records by Basic UDI-DI code.
+pub type BasicUdiExport = HashMap<String, BasicUdiDiData>;
+
+/// Load a Basic UDI-DI export: an NDJSON file (one record per line,
+/// compressed or not) or a directory of `*.json` records. Records without a
+/// code are left out; for a code given twice the later record wins.
+pub fn load_basic_udi_export(path: &Path) -> anyhow::Result<BasicUdiExport> {
+    let mut records = Vec::new();
+    if path.is_dir() {
+        let mut files: Vec<_> = std::fs::read_dir(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+            .collect();
+        files.sort();
+        for file in files {
+            let content = std::fs::read_to_string(&file)?;
+            match api_detail::parse_basic_udi_di(&content) {
+                Ok(record) => records.push(record),
+                Err(e) => eprintln!("  {}: {}", file.display(), e),
+            }
+        }
+    } else {
+        let input = ndjson_input::Input::open(path)?;
+        ndjson_input::stream(
+            &input,
+            |_| false,
+            |line_num, trimmed| {
+                Ok(api_detail::parse_basic_udi_di(trimmed)
+                    .map_err(|e| eprintln!("  Line {}: {}", line_num, e))
+                    .ok())
+            },
+            |record| {
+                records.extend(record);
+                Ok(())
+            },
+        )?;
+    }
+    Ok(records
+        .into_iter()
+        .filter_map(|record| Some((basic_udi_code(&record)?.to_string(), record)))
+        .collect())
+}
+
+fn basic_udi_code(record: &BasicUdiDiData) -> Option<&str> {
+    record
+        .basic_udi
+        .as_ref()?
+        .code
+        .as_deref()
+        .filter(|c| !c.is_empty())
+}
+
+/// How many devices each source reached, counted from the transform threads.
+#[derive(Default)]
+pub struct Coverage {
+    complete: AtomicUsize,
+    no_listing: AtomicUsize,
+    no_basic_udi: AtomicUsize,
+}
+
+impl Coverage {
+    pub fn count(&self, listing: bool, basic_udi: bool) {
+        let counter = match (listing, basic_udi) {
+            (true, true) => &self.complete,
+            (false, _) => &self.no_listing,
+            (true, false) => &self.no_basic_udi,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} complete (listing + detail + Basic UDI-DI), {} without listing row, {} without Basic UDI-DI",
+            self.complete.load(Ordering::Relaxed),
+            self.no_listing.load(Ordering::Relaxed),
+            self.no_basic_udi.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_is_indexed_by_basic_udi_code() {
+        let dir = std::env::temp_dir().join(format!("merge_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ndjson = dir.join("basic_udi.ndjson");
+        std::fs::write(
+            &ndjson,
+            concat!(
+                r#"{"uuid":"b-1","deviceName":"old","basicUdi":{"code":"B-1"}}"#,
+                "\n",
+                r#"{"uuid":"b-2","basicUdi":{"code":""}}"#,
+                "\n",
+                "not json\n",
+                r#"{"uuid":"b-3","deviceName":"new","basicUdi":{"code":"B-1"}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        let export = load_basic_udi_export(&ndjson).unwrap();
+        assert_eq!(export.len(), 1);
+        assert_eq!(export["B-1"].device_name.as_deref(), Some("new"));
+
+        let records = dir.join("records");
+        std::fs::create_dir_all(&records).unwrap();
+        std::fs::write(
+            records.join("b-9.json"),
+            r#"{"uuid":"b-9","basicUdi":{"code":"B-9"}}"#,
+        )
+        .unwrap();
+        assert!(load_basic_udi_export(&records).unwrap().contains_key("B-9"));
+
+        let coverage = Coverage::default();
+        coverage.count(true, true);
+        coverage.count(true, false);
+        coverage.count(false, true);
+        assert!(coverage.summary().starts_with("1 complete"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+//! UK MHRA device registration export (`export --mhra`).
+//! Flattens the converted firstbase documents into one XLSX row per device
+//! in the column layout of the MHRA DORS bulk device upload, so the same
+//! pipeline that feeds EUDAMED (EU) and firstbase/swissdamed (CH) also yields
+//! the GB registration sheet.
+//!
+//! EUDAMED carries EMDN, not GMDN: the GMDN columns stay empty for the
+//! manufacturer to fill and the EMDN code is given alongside as a lookup aid.
+//! UK device classes are derived from the EU risk class (CE-marked devices
+//! placed on the GB market keep their EU classification).
+
+use std::path::Path;
+
+use anyhow::Context;
+use rayon::prelude::*;
+use rust_xlsxwriter::{Format, Workbook};
+
+use crate::config::Mhra;
+use crate::report::{array_at, base_unit, firstbase_docs, risk_class, str_at};
+
+const HEADERS: &[&str] = &[
+    "Manufacturer Name",
+    "Manufacturer SRN",
+    "UK Responsible Person",
+    "UKRP Address",
+    "GMDN Code",
+    "GMDN Term",
+    "EMDN Code (reference)",
+    "Device Class (UK)",
+    "Legislation (EU basis)",
+    "Conformity Marking",
+    "Notified Body Number",
+    "Certificate Number",
+    "Certificate Expiry",
+    "Brand / Trade Name",
+    "Model / Version",
+    "Catalogue / Reference Number",
+    "Basic UDI-DI",
+    "UDI-DI (GTIN)",
+    "Sterile",
+    "Single Use",
+    "Contains Latex",
+    "Implantable",
+    "Measuring Function",
+    "Active Device",
+    "Contains Medicinal Substance",
+    "Contains Human Tissue",
+    "Contains Animal Tissue",
+    "Device Status",
+    "EUDAMED UUID",
+];
+
+/// EU risk class code (GS1, system 76/85) → UK MDR 2002 device class.
+pub fn uk_device_class(eu: &str) -> &str {
+    match eu {
+        "EU_CLASS_I" => "Class I",
+        "EU_CLASS_IIA" => "Class IIa",
+        "EU_CLASS_IIB" => "Class IIb",
+        "EU_CLASS_III" => "Class III",
+        "AIMDD" => "Active Implantable",
+        "IVDD_GENERAL" => "IVD General",
+        "IVDD_DEVICES_SELF_TESTING" => "IVD Self-Test",
+        "IVDD_ANNEX_II_LIST_A" => "IVD List A",
+        "IVDD_ANNEX_II_LIST_B" => "IVD List B",
+        "EU_CLASS_A" => "IVDR Class A",
+        "EU_CLASS_B" => "IVDR Class B",
+        "EU_CLASS_C" => "IVDR Class C",
+        "EU_CLASS_D" => "IVDR Class D",
+        other => other,
+    }
+}
+
+/// firstbase boolean (`true`, `"TRUE"`, `"FALSE"`, `"UNSPECIFIED"`) → Yes/No/"".
+fn yes_no(v: Option<&serde_json::Value>) -> String {
+    match v {
+        Some(serde_json::Value::Bool(true)) => "Yes".to_string(),
+        Some(serde_json::Value::Bool(false)) => "No".to_string(),
+        Some(serde_json::Value::String(s)) if s.eq_ignore_ascii_case("true") => "Yes".to_string(),
+        Some(serde_json::Value::String(s)) if s.eq_ignore_ascii_case("false") => "No".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn identification<'a>(item: &'a serde_json::Value, type_code: &str) -> &'a str {
+    array_at(item, "/AdditionalTradeItemIdentification")
+        .iter()
+        .find(|i| str_at(i, "/AdditionalTradeItemIdentificationTypeCode") == type_code)
+        .map(|i| str_at(i, "/Value"))
+        .unwrap_or("")
+}
+
+/// One registration row for a firstbase document (values in HEADERS order).
+pub fn device_row(doc: &serde_json::Value, uuid: &str, mhra: &Mhra) -> Vec<String> {
+    let top = doc
+        .pointer("/DraftItem/TradeItem")
+        .unwrap_or(&serde_json::Value::Null);
+    let base = base_unit(doc);
+    // Manufacturer (EMA), or the system/procedure pack producer (EPP) for packs;
+    // the contact may sit on the base unit or the top-level package.
+    let manufacturer = ["EMA", "EPP"].iter().find_map(|code| {
+        [base, top].iter().find_map(|item| {
+            array_at(item, "/TradeItemContactInformation")
+                .iter()
+                .find(|c| str_at(c, "/ContactTypeCode/Value") == *code)
+        })
+    });
+    let srn = manufacturer
+        .and_then(|c| {
+            array_at(c, "/AdditionalPartyIdentification")
+                .iter()
+                .find(|p| str_at(p, "/AdditionalPartyIdentificationTypeCode") == "SRN")
+        })
+        .map(|p| str_at(p, "/Value"))
+        .unwrap_or("");
+    let emdn = array_at(
+        base,
+        "/GdsnTradeItemClassification/AdditionalTradeItemClassification",
+    )
+    .iter()
+    .find(|c| str_at(c, "/AdditionalTradeItemClassificationSystemCode/Value") == "88")
+    .map(|c| {
+        str_at(
+            c,
+            "/AdditionalTradeItemClassificationValue/0/AdditionalTradeItemClassificationCodeValue",
+        )
+    })
+    .unwrap_or("");
+    let regulation = str_at(
+        base,
+        "/RegulatedTradeItemModule/RegulatoryInformation/0/RegulatoryAct",
+    );
+    let cert = array_at(
+        base,
+        "/CertificationInformationModule/CertificationInformation",
+    )
+    .first()
+    .unwrap_or(&serde_json::Value::Null);
+    let nb_number = array_at(cert, "/AdditionalCertificationOrganisationIdentifier")
+        .iter()
+        .find(|o| str_at(o, "/AdditionalPartyIdentificationTypeCode") == "EU_NOTIFIED_BODY_NUMBER")
+        .map(|o| str_at(o, "/Value"))
+        .unwrap_or("");
+    let mdi = base
+        .pointer("/MedicalDeviceTradeItemModule/MedicalDeviceInformation")
+        .unwrap_or(&serde_json::Value::Null);
+    let hci = base
+        .pointer("/HealthcareItemInformationModule/HealthcareItemInformation")
+        .unwrap_or(&serde_json::Value::Null);
+    let sterile = match str_at(
+        mdi,
+        "/TradeItemSterilityInformation/InitialManufacturerSterilisationCode/0/Value",
+    ) {
+        "" => String::new(),
+        "NOT_STERILISED" => "No".to_string(),
+        _ => "Yes".to_string(),
+    };
+    let single_use = match str_at(
+        mdi,
+        "/HealthcareTradeItemReusabilityInformation/ManufacturerDeclaredReusabilityTypeCode/Value",
+    ) {
+        "" => String::new(),
+        "SINGLE_USE" => "Yes".to_string(),
+        _ => "No".to_string(),
+    };
+    let name = str_at(
+        base,
+        "/TradeItemDescriptionModule/TradeItemDescriptionInformation/TradeItemDescription/0/Value",
+    );
+
+    vec![
+        manufacturer
+            .map(|c| str_at(c, "/ContactName"))
+            .unwrap_or("")
+            .to_string(),
+        srn.to_string(),
+        mhra.uk_responsible_person.clone(),
+        mhra.ukrp_address.clone(),
+        String::new(),
+        String::new(),
+        emdn.to_string(),
+        uk_device_class(risk_class(base)).to_string(),
+        regulation.to_string(),
+        if regulation.is_empty() {
+            String::new()
+        } else {
+            "CE".to_string()
+        },
+        nb_number.to_string(),
+        str_at(cert, "/Certification/0/CertificationValue").to_string(),
+        str_at(cert, "/Certification/0/CertificationEffectiveEndDateTime").to_string(),
+        name.to_string(),
+        identification(base, "MODEL_NUMBER").to_string(),
+        identification(base, "MANUFACTURER_PART_NUMBER").to_string(),
+        str_at(base, "/GlobalModelInformation/0/GlobalModelNumber").to_string(),
+        str_at(base, "/Gtin").to_string(),
+        sterile,
+        single_use,
+        yes_no(hci.get("DoesTradeItemContainLatex")),
+        yes_no(mdi.get("IsTradeItemImplantable")),
+        yes_no(mdi.get("HasDeviceMeasuringFunction")),
+        yes_no(mdi.get("IsActiveDevice")),
+        yes_no(mdi.get("IsDeviceMedicinalProduct")),
+        yes_no(hci.get("DoesTradeItemContainHumanTissue")),
+        yes_no(hci.get("DoesTradeItemContainAnimalTissue")),
+        str_at(mdi, "/EUMedicalDeviceStatusCode/Value").to_string(),
+        uuid.to_string(),
+    ]
+}
+
+/// Write the registration sheet for every per-UUID document in
+/// `firstbase_dir` (plus `processed/` when `include_processed`) to `out_path`.
+/// Returns the number of device rows.
+pub fn export_mhra(
+    firstbase_dir: &Path,
+    include_processed: bool,
+    mhra: &Mhra,
+    out_path: &Path,
+) -> anyhow::Result<usize> {
+    let files = firstbase_docs(firstbase_dir, include_processed);
+    let mut rows: Vec<Vec<String>> = files
+        .par_iter()
+        .filter_map(|p| {
+            let content = std::fs::read_to_string(p).ok()?;
+            let doc: serde_json::Value = serde_json::from_str(&content).ok()?;
+            let uuid = p.file_stem()?.to_string_lossy().to_string();
+            Some(device_row(&doc, &uuid, mhra))
+        })
+        .collect();
+    // Manufacturer, then UDI-DI, so the sheet reads per registrant.
+    rows.sort_by(|a, b| (&a[0], &a[17]).cmp(&(&b[0], &b[17])));
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Devices")?;
+    let header_fmt = Format::new().set_bold();
+    for (col, header) in HEADERS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_fmt)?;
+    }
+    for (i, row) in rows.iter().enumerate() {
+        for (col, value) in row.iter().enumerate() {
+            worksheet.write_string(i as u32 + 1, col as u16, value)?;
+        }
+    }
+    worksheet.autofit();
+
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    workbook
+        .save(out_path)
+        .with_context(|| format!("Failed to write {}", out_path.display()))?;
+    Ok(rows.len())
+}
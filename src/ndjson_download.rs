@@ -0,0 +1,405 @@
+//! EUDAMED downloads straight into the NDJSON inputs of the CLI converters,
+//! so no curl script is needed in front of them (`download --ndjson`).
+//!
+//! The public listing `GET /devices/udiDiData?page=N&pageSize=300` is paged
+//! to the end, optionally per SRN, and every `content` entry becomes one line
+//! of `ndjson/udi_di_<time>.ndjson`, the format `ndjson` mode reads. Requests
+//! go through `download::eudamed_get` and one `RateLimiter`, like the
+//! per-device download. The file is written as `<name>.part` and renamed
+//! when the last page is in; a page that still fails after the retries stops
+//! the run and leaves the `.part` file, never a silently short listing.
+//!
+//! `fetch-details` closes the gap to the detail transform: the UUIDs of a
+//! listing NDJSON are fetched from `GET /devices/udiDiData/{uuid}?languageIso2Code=en`
+//! on a bounded pool of `--threads` workers (`fetch_pool.rs`, same limiter)
+//! and streamed, in listing order and one compact record per line, into
+//! `details/<listing stem>.ndjson`, the input of `detail` / `merge` mode. A
+//! slow request holds back only the records queued behind it, never the
+//! other workers; a UUID that fails after the retries is logged and listed
+//! in `<out>.failed.txt` for a second run. With `incremental`
+//! only devices whose listing version is newer than at the last fetch are
+//! requested (`incremental.rs`, scope `detail`).
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+use crate::download::{
+    eudamed_agent, eudamed_get, DownloadEvent, DownloadProgress, RateLimiter, DEFAULT_PAGE_SIZE,
+    EUDAMED_BASE_URL,
+};
+use crate::fetch_pool::fetch_ordered;
+use crate::incremental::{Scope, Seen, State};
+use crate::version_db::VERSION_DB_PATH;
+
+/// Default directory of the listing files (input of `ndjson` mode).
+pub const LISTING_DIR: &str = "ndjson";
+/// Default directory of fetched detail files; not `ndjson/`, which `ndjson`
+/// mode would read as listings.
+pub const DETAIL_DIR: &str = "details";
+/// Detail records between two progress lines.
+const PROGRESS_EVERY: usize = 500;
+
+/// What `download_listing` fetches.
+pub struct ListingRequest {
+    /// Manufacturer / AR SRNs; empty = the whole public listing.
+    pub srns: Vec<String>,
+    /// Devices per SRN, or in total without SRNs.
+    pub limit: Option<usize>,
+    /// Output file.
+    pub out: PathBuf,
+    pub rate_interval_ms: u64,
+    pub max_retries: u32,
+}
+
+/// `ndjson/udi_di_<time>.ndjson`; names sort in download order, so the
+/// `ndjson` dedup pass prefers the newer file on equal versions.
+pub fn default_listing_path() -> PathBuf {
+    Path::new(LISTING_DIR).join(format!(
+        "udi_di_{}.ndjson",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ))
+}
+
+fn listing_url(page: usize, srn: Option<&str>) -> String {
+    let filter = srn.map(|s| format!("&srn={}", s)).unwrap_or_default();
+    format!(
+        "{}?page={}&pageSize={}{}&iso2Code=en&languageIso2Code=en",
+        EUDAMED_BASE_URL, page, DEFAULT_PAGE_SIZE, filter
+    )
+}
+
+/// The entries and `totalPages` of one listing page.
+fn parse_page(body: &str) -> anyhow::Result<(Vec<serde_json::Value>, usize)> {
+    let mut json: serde_json::Value =
+        serde_json::from_str(body).context("listing page is not JSON")?;
+    let total_pages = json.get("totalPages").and_then(|t| t.as_u64()).unwrap_or(1) as usize;
+    match json.get_mut("content").map(serde_json::Value::take) {
+        Some(serde_json::Value::Array(items)) => Ok((items, total_pages)),
+        _ => bail!("listing page without content"),
+    }
+}
+
+/// Page the listing (per SRN, or all of it) into `request.out`, one device per
+/// line. Returns the number of devices written.
+pub fn download_listing(
+    request: &ListingRequest,
+    progress: &dyn DownloadProgress,
+) -> anyhow::Result<usize> {
+    let log = |msg: String| progress.on_event(DownloadEvent::Log(msg));
+    if let Some(parent) = request.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let part = PathBuf::from(format!("{}.part", request.out.display()));
+    let mut out = std::io::BufWriter::new(
+        std::fs::File::create(&part)
+            .with_context(|| format!("Failed to create {}", part.display()))?,
+    );
+
+    let agent = eudamed_agent();
+    let limiter = RateLimiter::new(std::time::Duration::from_millis(request.rate_interval_ms));
+    let filters: Vec<Option<&str>> = if request.srns.is_empty() {
+        vec![None]
+    } else {
+        request.srns.iter().map(|s| Some(s.as_str())).collect()
+    };
+
+    let mut written = 0;
+    for (i, srn) in filters.iter().enumerate() {
+        let label = srn.unwrap_or("all devices");
+        let mut count = 0;
+        let mut page = 0;
+        loop {
+            if progress.is_cancelled() {
+                out.flush()?;
+                bail!(
+                    "cancelled at {} page {}; partial listing in {}",
+                    label,
+                    page,
+                    part.display()
+                );
+            }
+            let body = match eudamed_get(
+                &agent,
+                &limiter,
+                &listing_url(page, *srn),
+                request.max_retries,
+            ) {
+                Ok(body) => body,
+                Err(e) => {
+                    out.flush()?;
+                    bail!(
+                        "{} page {} failed: {}; partial listing in {}",
+                        label,
+                        page,
+                        e,
+                        part.display()
+                    );
+                }
+            };
+            let (items, total_pages) =
+                parse_page(&body).with_context(|| format!("{} page {}", label, page))?;
+            if items.is_empty() {
+                break;
+            }
+            let take = request
+                .limit
+                .map(|l| l.saturating_sub(count))
+                .unwrap_or(items.len());
+            let matching = items
+                .iter()
+                .filter(|item| crate::device_filter::admits_listing(item));
+            for item in matching.take(take) {
+                serde_json::to_writer(&mut out, item)?;
+                out.write_all(b"\n")?;
+                count += 1;
+            }
+            if page % 10 == 0 || page + 1 >= total_pages {
+                log(format!(
+                    "  {} page {}/{} — {} devices",
+                    label,
+                    page + 1,
+                    total_pages,
+                    count
+                ));
+            }
+            progress.on_event(DownloadEvent::Status {
+                phase: "Listings".into(),
+                done: page + 1,
+                total: total_pages,
+            });
+            page += 1;
+            if page >= total_pages || request.limit.is_some_and(|l| count >= l) {
+                break;
+            }
+        }
+        if srn.is_some() {
+            log(format!(
+                "  SRN {}: {} devices ({}/{})",
+                label,
+                count,
+                i + 1,
+                filters.len()
+            ));
+        }
+        written += count;
+    }
+
+    out.flush()?;
+    drop(out);
+    std::fs::rename(&part, &request.out)
+        .with_context(|| format!("Failed to rename {}", part.display()))?;
+    Ok(written)
+}
+
+/// What `download_details` fetches.
+pub struct DetailRequest {
+    /// Listing NDJSON (compressed or not) naming the devices by `uuid`.
+    pub listing: PathBuf,
+    pub out: PathBuf,
+    pub threads: usize,
+    pub rate_interval_ms: u64,
+    pub max_retries: u32,
+    /// Skip devices fetched before in the same version.
+    pub incremental: bool,
+}
+
+/// Summary of a detail fetch.
+pub struct DetailResult {
+    pub written: usize,
+    /// UUIDs without a record, also in `<out>.failed.txt`.
+    pub failed: Vec<String>,
+    /// Left out by `incremental`.
+    pub unchanged: usize,
+}
+
+/// `details/<stem of listing>.ndjson`.
+pub fn default_detail_path(listing: &Path) -> PathBuf {
+    Path::new(DETAIL_DIR).join(format!("{}.ndjson", crate::ndjson_input::stem(listing)))
+}
+
+/// The devices of a listing NDJSON (UUID and version), first occurrence
+/// order; lines without a UUID are skipped.
+pub fn listing_devices(listing: &Path) -> anyhow::Result<Vec<Seen>> {
+    let input = crate::ndjson_input::Input::open(listing)?;
+    let mut uuids = std::collections::HashSet::new();
+    let mut devices = Vec::new();
+    crate::ndjson_input::stream(
+        &input,
+        |_| false,
+        |_, line| Ok(Seen::from_line(line)),
+        |seen| {
+            if let Some(seen) = seen {
+                if uuids.insert(seen.uuid.clone()) {
+                    devices.push(seen);
+                }
+            }
+            Ok(())
+        },
+    )?;
+    Ok(devices)
+}
+
+/// A detail response as one NDJSON line; `None` unless it is a JSON object.
+fn detail_line(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value.is_object().then(|| value.to_string())
+}
+
+/// Fetch the detail record of every UUID in `request.listing` into
+/// `request.out` (see the module docs).
+pub fn download_details(
+    request: &DetailRequest,
+    progress: &dyn DownloadProgress,
+) -> anyhow::Result<DetailResult> {
+    let log = |msg: String| progress.on_event(DownloadEvent::Log(msg));
+    let mut devices = listing_devices(&request.listing)?;
+    log(format!(
+        "{} devices in {}",
+        devices.len(),
+        request.listing.display()
+    ));
+    let mut sync = None;
+    let mut unchanged = 0;
+    if request.incremental {
+        let state = State::open(Path::new(VERSION_DB_PATH), Scope::Detail)?;
+        let before = devices.len();
+        devices.retain(|d| state.changed(d));
+        unchanged = before - devices.len();
+        log(format!(
+            "Incremental: {} unchanged skipped, {} new/changed",
+            unchanged,
+            devices.len()
+        ));
+        sync = Some(state);
+    }
+    let uuids: Vec<&str> = devices.iter().map(|d| d.uuid.as_str()).collect();
+    if let Some(parent) = request.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let part = PathBuf::from(format!("{}.part", request.out.display()));
+    let mut out = std::io::BufWriter::new(
+        std::fs::File::create(&part)
+            .with_context(|| format!("Failed to create {}", part.display()))?,
+    );
+
+    let agent = eudamed_agent();
+    let limiter = RateLimiter::new(std::time::Duration::from_millis(request.rate_interval_ms));
+
+    let mut written = 0;
+    let mut failed = Vec::new();
+    let done = fetch_ordered(
+        &uuids,
+        request.threads,
+        |uuid| {
+            let url = format!("{}/{}?languageIso2Code=en", EUDAMED_BASE_URL, uuid);
+            let body = eudamed_get(&agent, &limiter, &url, request.max_retries)?;
+            detail_line(&body).ok_or_else(|| "not a detail record".to_string())
+        },
+        || progress.is_cancelled(),
+        |i, line| {
+            match line {
+                Ok(line) => {
+                    out.write_all(line.as_bytes())?;
+                    out.write_all(b"\n")?;
+                    written += 1;
+                }
+                Err(e) => {
+                    log(format!("  {}: {}", uuids[i], e));
+                    failed.push(uuids[i].to_string());
+                }
+            }
+            let done = i + 1;
+            if done % PROGRESS_EVERY == 0 || done == uuids.len() {
+                log(format!(
+                    "  detail {}/{} ({} failed)",
+                    done,
+                    uuids.len(),
+                    failed.len()
+                ));
+            }
+            progress.on_event(DownloadEvent::Status {
+                phase: "detail".into(),
+                done,
+                total: uuids.len(),
+            });
+            Ok(())
+        },
+    )?;
+    if done < uuids.len() {
+        out.flush()?;
+        bail!(
+            "cancelled after {} of {} devices; partial details in {}",
+            done,
+            uuids.len(),
+            part.display()
+        );
+    }
+
+    out.flush()?;
+    drop(out);
+    std::fs::rename(&part, &request.out)
+        .with_context(|| format!("Failed to rename {}", part.display()))?;
+    if !failed.is_empty() {
+        let list = PathBuf::from(format!("{}.failed.txt", request.out.display()));
+        std::fs::write(&list, failed.join("\n") + "\n")?;
+        log(format!(
+            "{} failed UUIDs -> {}",
+            failed.len(),
+            list.display()
+        ));
+    }
+    if let Some(state) = &mut sync {
+        devices.retain(|d| !failed.contains(&d.uuid));
+        state.record(&devices)?;
+    }
+    Ok(DetailResult {
+        written,
+        failed,
+        unchanged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_pages_and_urls() {
+        let (items, pages) = parse_page(
+            r#"{"content":[{"uuid":"a"},{"uuid":"b"}],"totalPages":7,"totalElements":2001}"#,
+        )
+        .unwrap();
+        assert_eq!((items.len(), pages), (2, 7));
+        assert_eq!(items[1]["uuid"], "b");
+        assert!(parse_page(r#"{"error":"busy"}"#).is_err());
+        assert!(parse_page("<html>").is_err());
+
+        assert!(listing_url(3, None).contains("?page=3&pageSize=300&iso2Code"));
+        assert!(listing_url(0, Some("CH-MF-000012345")).contains("&srn=CH-MF-000012345&"));
+        assert!(default_listing_path().starts_with("ndjson"));
+
+        let dir = std::env::temp_dir().join(format!("ndjson_download_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let listing = dir.join("udi_di_1.ndjson");
+        std::fs::write(
+            &listing,
+            "{\"uuid\":\"u-2\"}\n\n{\"uuid\":\"u-1\"}\nnot json\n{\"uuid\":\"u-2\"}\n",
+        )
+        .unwrap();
+        let devices = listing_devices(&listing).unwrap();
+        let uuids: Vec<_> = devices.iter().map(|d| d.uuid.as_str()).collect();
+        assert_eq!(uuids, ["u-2", "u-1"]);
+        assert_eq!(
+            default_detail_path(&listing),
+            Path::new("details/udi_di_1.ndjson")
+        );
+        assert_eq!(
+            detail_line("{\n  \"uuid\": \"u-1\"\n}").as_deref(),
+            Some(r#"{"uuid":"u-1"}"#)
+        );
+        assert!(detail_line("[]").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
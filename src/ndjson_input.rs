@@ -0,0 +1,300 @@
+//! Reading of large NDJSON inputs (multi-GB EUDAMED dumps). A plain file is
+//! memory-mapped and split into lines in place, so there is no read syscall
+//! per buffer and no `String` per line: parsers borrow straight from the map
+//! (see `api_json`). Gzip and zstd files (`.ndjson.gz`, `.ndjson.zst`,
+//! detected by their magic bytes) are decompressed while they are read, one
+//! chunk of lines at a time — never to disk, never whole. `stream` hands
+//! chunks of lines to the rayon pool and the results back in input order; the
+//! dedup pre-pass and both NDJSON converters scan through it.
+
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use rayon::prelude::*;
+
+/// Lines per parallel chunk: enough to keep every core busy, while only one
+/// chunk of results is held in memory at a time (a full EUDAMED dump no
+/// longer has to fit in RAM).
+pub const CHUNK: usize = 1024;
+
+/// Compression of an input file, from its first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Gzip,
+    Zstd,
+}
+
+impl Codec {
+    /// From the first bytes of `file` (read position is left after them).
+    fn sniff(file: &mut std::fs::File) -> std::io::Result<Option<Codec>> {
+        let mut magic = [0u8; 4];
+        let read = file.read(&mut magic)?;
+        Ok(Codec::detect(&magic[..read]))
+    }
+
+    fn detect(magic: &[u8]) -> Option<Codec> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(Codec::Gzip)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Codec::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// An NDJSON input file.
+pub struct Input {
+    source: Source,
+}
+
+enum Source {
+    /// `None` for an empty file, which cannot be mapped.
+    Mapped(Option<memmap2::Mmap>),
+    /// Decompressed on every `stream` over it.
+    Compressed(PathBuf, Codec),
+}
+
+impl Input {
+    pub fn open(path: &Path) -> anyhow::Result<Input> {
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let len = file.metadata()?.len();
+        if let Some(codec) = Codec::sniff(&mut file)? {
+            return Ok(Input {
+                source: Source::Compressed(path.to_path_buf(), codec),
+            });
+        }
+        // SAFETY: inputs are downloaded/dropped files nobody writes while a
+        // conversion runs; truncating one underneath us would be a bug anyway.
+        let map = if len == 0 {
+            None
+        } else {
+            Some(
+                unsafe { memmap2::Mmap::map(&file) }
+                    .with_context(|| format!("Failed to map {}", path.display()))?,
+            )
+        };
+        Ok(Input {
+            source: Source::Mapped(map),
+        })
+    }
+
+    /// Decompressing reader over a compressed input.
+    fn decoder(path: &Path, codec: Codec) -> anyhow::Result<Box<dyn BufRead>> {
+        let file = BufReader::new(
+            std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?,
+        );
+        let reader: Box<dyn Read> = match codec {
+            // Multi-member: `cat a.gz b.gz` and pigz output are valid gzip.
+            Codec::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Codec::Zstd => Box::new(
+                ruzstd::decoding::StreamingDecoder::new(file)
+                    .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?,
+            ),
+        };
+        Ok(Box::new(BufReader::with_capacity(1 << 20, reader)))
+    }
+}
+
+/// Reader over the decompressed content of `path`, compressed or not (for
+/// reading single records back, e.g. `payload_archive`).
+pub fn reader(path: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    match Codec::sniff(&mut file)? {
+        Some(codec) => Input::decoder(path, codec),
+        None => {
+            std::io::Seek::rewind(&mut file)?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+    }
+}
+
+/// Line `line_num` as text: trimmed, `None` when blank. Invalid UTF-8 is an
+/// error naming the line.
+fn text(line_num: u32, line: &[u8]) -> anyhow::Result<Option<&str>> {
+    match std::str::from_utf8(line) {
+        Ok(text) => {
+            let text = text.trim();
+            Ok((!text.is_empty()).then_some(text))
+        }
+        Err(e) => Err(anyhow::anyhow!("Line {}: {}", line_num, e)),
+    }
+}
+
+/// Run `convert` over the non-blank lines of `input` (trimmed, numbered from
+/// 1 as `BufRead::lines` would count them) on the rayon pool, `CHUNK` lines
+/// at a time, and hand each result to `write` in input order before the next
+/// chunk is converted. Lines for which `skip` is true are left out.
+pub fn stream<T: Send>(
+    input: &Input,
+    skip: impl Fn(u32) -> bool,
+    convert: impl Fn(u32, &str) -> anyhow::Result<T> + Sync,
+    mut write: impl FnMut(T) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut flush = |chunk: &[(u32, &str)]| -> anyhow::Result<()> {
+        let results: Vec<T> = chunk
+            .par_iter()
+            .map(|&(line_num, text)| convert(line_num, text))
+            .collect::<anyhow::Result<_>>()?;
+        results.into_iter().try_for_each(&mut write)
+    };
+
+    match &input.source {
+        Source::Mapped(map) => {
+            let bytes = map.as_deref().unwrap_or_default();
+            let mut chunk: Vec<(u32, &str)> = Vec::with_capacity(CHUNK);
+            for (i, line) in bytes.split(|&b| b == b'\n').enumerate() {
+                let line_num = i as u32 + 1;
+                if skip(line_num) {
+                    continue;
+                }
+                if let Some(text) = text(line_num, line)? {
+                    chunk.push((line_num, text));
+                }
+                if chunk.len() == CHUNK {
+                    flush(&chunk)?;
+                    chunk.clear();
+                }
+            }
+            flush(&chunk)
+        }
+        Source::Compressed(path, codec) => {
+            let mut reader = Input::decoder(path, *codec)?;
+            // One buffer per chunk: lines are appended, then sliced by offset.
+            let mut buf: Vec<u8> = Vec::new();
+            let mut lines: Vec<(u32, usize, usize)> = Vec::with_capacity(CHUNK);
+            let mut line_num = 0u32;
+            loop {
+                let start = buf.len();
+                let read = reader
+                    .read_until(b'\n', &mut buf)
+                    .with_context(|| format!("Failed to decompress {}", path.display()))?;
+                if read > 0 {
+                    line_num += 1;
+                    if skip(line_num) {
+                        buf.truncate(start);
+                    } else {
+                        lines.push((line_num, start, buf.len()));
+                    }
+                }
+                if lines.len() == CHUNK || (read == 0 && !lines.is_empty()) {
+                    let mut chunk: Vec<(u32, &str)> = Vec::with_capacity(lines.len());
+                    for &(n, from, to) in &lines {
+                        if let Some(text) = text(n, &buf[from..to])? {
+                            chunk.push((n, text));
+                        }
+                    }
+                    flush(&chunk)?;
+                    lines.clear();
+                    buf.clear();
+                }
+                if read == 0 {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Whether `path` is an NDJSON input, compressed or not (`.ndjson`,
+/// `.ndjson.gz`, `.ndjson.zst`).
+pub fn is_ndjson(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| base_name(&n.to_string_lossy()).is_some())
+        .unwrap_or(false)
+}
+
+/// File name of an NDJSON input without `.ndjson` and the compression
+/// suffix: `eudamed_10k.ndjson.gz` → `eudamed_10k` (output files are named
+/// after it).
+pub fn stem(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    base_name(&name).map(str::to_string).unwrap_or_else(|| {
+        path.file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+fn base_name(name: &str) -> Option<&str> {
+    let lower = name.to_ascii_lowercase();
+    [".ndjson", ".ndjson.gz", ".ndjson.zst"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map(|ext| &name[..name.len() - ext.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_keep_their_numbers_across_chunks() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("ndjson_input_{}.ndjson", std::process::id()));
+        let mut text = String::new();
+        for i in 1..=CHUNK * 2 + 5 {
+            text.push_str(&if i % 7 == 0 {
+                "  \r\n".to_string()
+            } else {
+                format!("{}\r\n", i)
+            });
+        }
+        let gz_path = path.with_extension("ndjson.gz");
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        std::io::Write::write_all(&mut gz, text.as_bytes()).unwrap();
+        std::fs::write(&gz_path, gz.finish().unwrap()).unwrap();
+        let zst_path = path.with_extension("ndjson.zst");
+        let zst = ruzstd::encoding::compress_to_vec(
+            text.as_bytes(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        std::fs::write(&zst_path, zst).unwrap();
+        std::fs::write(&path, text).unwrap();
+
+        for file in [&path, &gz_path, &zst_path] {
+            assert!(is_ndjson(file));
+            let input = Input::open(file).unwrap();
+            let mut seen = Vec::new();
+            stream(
+                &input,
+                |n| n == 3,
+                |n, line| Ok((n, line.parse::<u32>()?)),
+                |r| {
+                    seen.push(r);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            assert!(seen.iter().all(|(n, v)| n == v && n % 7 != 0 && *n != 3));
+            assert!(seen.windows(2).all(|w| w[0].0 < w[1].0));
+            assert_eq!(seen.last().unwrap().0, (CHUNK * 2 + 5) as u32);
+            std::fs::remove_file(file).unwrap();
+        }
+        assert_eq!(stem(&gz_path), stem(&path));
+
+        std::fs::write(&path, "").unwrap();
+        let mut count = 0;
+        stream(
+            &Input::open(&path).unwrap(),
+            |_| false,
+            |n, _| Ok(n),
+            |_| {
+                count += 1;
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(count, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+//! Device-level (Basic UDI-DI) records from `eudamed_json/` carry no GTIN, so
+//! `transform_eudamed_device` alone yields a document with an empty `Gtin`
+//! that GS1 cannot accept. `[device_level] no_gtin` decides what to do:
+//!
+//! - `skip` (default): no document; the record goes into
+//!   `firstbase_json/no_gtin_<time>.txt`;
+//! - `lookup`: resolve the Basic UDI-DI's UDI-DIs on the EUDAMED listing
+//!   (`basicUdi` filter) and write one document per GS1 UDI-DI,
+//!   `<uuid>_<gtin>.json`; a record without any is skipped as above;
+//! - `placeholder`: one document keyed by the Basic UDI-DI
+//!   (`Placeholder_<code>`, empty `Gtin`) in `firstbase_json/placeholders/`,
+//!   which the push never reads.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, NoGtinStrategy};
+use crate::device_category;
+use crate::download::{self, RateLimiter};
+use crate::eudamed_json::EudamedDevice;
+use crate::firstbase::FirstbaseDocument;
+use crate::transform_eudamed_json::transform_eudamed_device;
+
+/// Subdirectory of the output directory for placeholder documents.
+pub const PLACEHOLDER_DIR: &str = "placeholders";
+
+/// One document to write for a device-level record.
+pub struct Output {
+    /// File stem of the output (`<uuid>` or `<uuid>_<gtin>`).
+    pub stem: String,
+    pub document: FirstbaseDocument,
+    /// Not for push: goes to `PLACEHOLDER_DIR`.
+    pub placeholder: bool,
+}
+
+/// Applies the configured strategy over a conversion run and collects the
+/// report of records left without a document.
+pub struct NoGtin {
+    strategy: NoGtinStrategy,
+    lookup: Option<(ureq::Agent, RateLimiter)>,
+    report: Vec<String>,
+}
+
+impl NoGtin {
+    pub fn new(strategy: NoGtinStrategy) -> Self {
+        let lookup = (strategy == NoGtinStrategy::Lookup).then(|| {
+            let interval = download::DownloadConfig::default().rate_interval_ms;
+            (
+                download::eudamed_agent(),
+                RateLimiter::new(std::time::Duration::from_millis(interval)),
+            )
+        });
+        NoGtin {
+            strategy,
+            lookup,
+            report: Vec::new(),
+        }
+    }
+
+    /// The documents for `device` (file stem `stem`); empty when skipped, or
+    /// when firstbase cannot take the device at all (`device_category`).
+    pub fn convert(&mut self, device: &EudamedDevice, stem: &str, config: &Config) -> Vec<Output> {
+        let basic_udi = device.basic_udi_code();
+        let category = device_category::classify(
+            device.device_criterion.as_deref(),
+            device.container_type.as_deref(),
+        );
+        if let Some(reason) = category.excluded_reason() {
+            let source = [&device.device_criterion, &device.container_type]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" / ");
+            device_category::exclude(stem, &source, reason);
+            return Vec::new();
+        }
+        match self.strategy {
+            NoGtinStrategy::Skip => {
+                self.skip(stem, &basic_udi, "no GTIN in a device-level record");
+                Vec::new()
+            }
+            NoGtinStrategy::Placeholder => {
+                let key = if basic_udi.is_empty() {
+                    stem
+                } else {
+                    &basic_udi
+                };
+                vec![Output {
+                    stem: stem.to_string(),
+                    document: FirstbaseDocument {
+                        trade_item: transform_eudamed_device(device, config),
+                        children: Vec::new(),
+                        identifier: format!("Placeholder_{}", key),
+                    },
+                    placeholder: true,
+                }]
+            }
+            NoGtinStrategy::Lookup => {
+                let gtins = match self.lookup_gtins(&basic_udi) {
+                    Ok(gtins) => gtins,
+                    Err(e) => {
+                        self.skip(stem, &basic_udi, &format!("lookup failed: {}", e));
+                        return Vec::new();
+                    }
+                };
+                if gtins.is_empty() {
+                    self.skip(stem, &basic_udi, "no GS1 UDI-DI found on EUDAMED");
+                }
+                gtins
+                    .into_iter()
+                    .map(|gtin| {
+                        let mut trade_item = transform_eudamed_device(device, config);
+                        trade_item.gtin = gtin.clone();
+                        Output {
+                            stem: format!("{}_{}", stem, gtin),
+                            document: FirstbaseDocument {
+                                trade_item,
+                                children: Vec::new(),
+                                identifier: format!("Draft_{}_{}", stem, gtin),
+                            },
+                            placeholder: false,
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn skip(&mut self, stem: &str, basic_udi: &str, reason: &str) {
+        self.report
+            .push(format!("{}\t{}\t{}", stem, basic_udi, reason));
+    }
+
+    fn lookup_gtins(&self, basic_udi: &str) -> Result<Vec<String>, String> {
+        if basic_udi.is_empty() {
+            return Ok(Vec::new());
+        }
+        let Some((agent, limiter)) = &self.lookup else {
+            return Ok(Vec::new());
+        };
+        let body = download::eudamed_get(agent, limiter, &lookup_url(basic_udi), 4)?;
+        Ok(gtins_from_listing(&body, basic_udi))
+    }
+
+    /// Records left without a document.
+    pub fn skipped(&self) -> usize {
+        self.report.len()
+    }
+
+    /// Write the report to `<output_dir>/no_gtin_<time>.txt` (tab-separated
+    /// uuid, Basic UDI-DI, reason); `None` when nothing was skipped.
+    pub fn write_report(&self, output_dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+        if self.report.is_empty() {
+            return Ok(None);
+        }
+        let path = output_dir.join(format!(
+            "no_gtin_{}.txt",
+            chrono::Local::now().format("%H.%M_%d.%m.%Y")
+        ));
+        std::fs::write(&path, self.report.join("\n") + "\n")?;
+        Ok(Some(path))
+    }
+}
+
+/// Listing lookup of the UDI-DIs registered under `basic_udi`; encoded, as
+/// HIBC / IFA Basic UDI-DIs carry `+`, `/` and `$`.
+fn lookup_url(basic_udi: &str) -> String {
+    format!(
+        "{}?page=0&pageSize=300&basicUdi={}&iso2Code=en&languageIso2Code=en",
+        download::EUDAMED_BASE_URL,
+        crate::http::urlencode(basic_udi)
+    )
+}
+
+/// The GS1 UDI-DIs (14-digit GTINs) of a listing page that belong to
+/// `basic_udi` — the filter is not trusted to match exactly.
+fn gtins_from_listing(body: &str, basic_udi: &str) -> Vec<String> {
+    let json: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+    let mut gtins: Vec<String> = json
+        .get("content")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("basicUdi").and_then(|b| b.as_str()) == Some(basic_udi))
+        .filter_map(|item| item.get("primaryDi").and_then(|d| d.as_str()))
+        .filter_map(|di| crate::gtin::normalize(di).ok())
+        .collect();
+    gtins.sort();
+    gtins.dedup();
+    gtins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategies_for_records_without_gtin() {
+        let body = r#"{"content":[
+            {"basicUdi":"B-1","primaryDi":"4034342074074"},
+            {"basicUdi":"B-1","primaryDi":"+H123ABC1"},
+            {"basicUdi":"B-10","primaryDi":"08800042702694"},
+            {"basicUdi":"B-1","primaryDi":"04034342074074"}
+        ]}"#;
+        assert_eq!(gtins_from_listing(body, "B-1"), ["04034342074074"]);
+        assert!(gtins_from_listing("not json", "B-1").is_empty());
+        assert!(lookup_url("+H123/AB$1 C").contains("&basicUdi=%2BH123%2FAB%241%20C&"));
+
+        let device: EudamedDevice =
+            serde_json::from_str(r#"{"uuid":"u-1","basicUdi":{"code":"B-1"}}"#).unwrap();
+        let config = crate::config::test_config();
+
+        let mut skip = NoGtin::new(NoGtinStrategy::Skip);
+        assert!(skip.convert(&device, "u-1", &config).is_empty());
+        assert_eq!(skip.skipped(), 1);
+
+        let mut placeholder = NoGtin::new(NoGtinStrategy::Placeholder);
+        let out = placeholder.convert(&device, "u-1", &config);
+        assert_eq!(out.len(), 1);
+        assert!(out[0].placeholder);
+        assert_eq!(out[0].document.identifier, "Placeholder_B-1");
+        assert_eq!(placeholder.skipped(), 0);
+    }
+}
@@ -0,0 +1,57 @@
+//! Native desktop notifications for finished GUI jobs. The GUI only sends one
+//! when its window is not focused — an operator watching the log needs none.
+//! No notification crate: each platform's own tool shows it (`osascript` on
+//! macOS, `notify-send` on Linux, a WinRT toast via PowerShell on Windows),
+//! with title and body passed as arguments / environment so no quoting can
+//! break the script. A missing tool only costs the notification.
+
+use std::process::Command;
+
+/// Show `title`/`body` as a desktop notification (blocks until the tool exits).
+pub fn send(title: &str, body: &str) -> anyhow::Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args([
+                "-e",
+                "on run argv",
+                "-e",
+                "display notification (item 2 of argv) with title (item 1 of argv)",
+                "-e",
+                "end run",
+                title,
+                body,
+            ])
+            .status()?
+    } else if cfg!(target_os = "windows") {
+        Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+                 $t = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+                 $x = $t.GetElementsByTagName('text'); \
+                 $x.Item(0).AppendChild($t.CreateTextNode($env:E2F_TITLE)) > $null; \
+                 $x.Item(1).AppendChild($t.CreateTextNode($env:E2F_BODY)) > $null; \
+                 [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('eudamed2firstbase').Show([Windows.UI.Notifications.ToastNotification]::new($t))",
+            ])
+            .env("E2F_TITLE", title)
+            .env("E2F_BODY", body)
+            .status()?
+    } else {
+        Command::new("notify-send")
+            .args(["--app-name=eudamed2firstbase", title, body])
+            .status()?
+    };
+    anyhow::ensure!(status.success(), "notification tool exited with {}", status);
+    Ok(())
+}
+
+/// `send` on a background thread; failures go to stderr.
+pub fn send_background(title: String, body: String) {
+    std::thread::spawn(move || {
+        if let Err(e) = send(&title, &body) {
+            eprintln!("Desktop notification failed: {:#}", e);
+        }
+    });
+}
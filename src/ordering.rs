@@ -0,0 +1,304 @@
+//! Stable order for the arrays of a firstbase document, whatever order the
+//! source listed them in: EUDAMED does not keep texts, substances, contacts
+//! or market entries in a fixed order between downloads, and without this a
+//! re-converted device differs from its last push (and the `--delta` /
+//! `--changed-only` comparison sees a change) only because two entries
+//! swapped. Every transform path ends with `document` or `trade_item`.
+//!
+//! All sorts are stable and fall back to the values themselves, so equal
+//! input gives byte-identical output.
+
+use crate::firstbase::*;
+
+/// en, fr, de, it, then alphabetical.
+fn language_rank(lang: &str) -> (u8, &str) {
+    let rank = match lang {
+        "en" => 0,
+        "fr" => 1,
+        "de" => 2,
+        "it" => 3,
+        _ => 4,
+    };
+    (rank, lang)
+}
+
+/// Multilingual texts by language (see `language_rank`), then text.
+pub fn languages(values: &mut [LangValue]) {
+    values.sort_by(|a, b| {
+        language_rank(&a.language_code)
+            .cmp(&language_rank(&b.language_code))
+            .then_with(|| a.value.cmp(&b.value))
+    });
+}
+
+/// SERIAL_NUMBER, MANUFACTURING_DATE, BATCH_NUMBER, EXPIRATION_DATE,
+/// SOFTWARE_IDENTIFICATION, then alphabetical.
+pub fn production_identifiers(ids: &mut [CodeValue]) {
+    fn rank(id: &str) -> (u8, &str) {
+        let rank = match id {
+            "SERIAL_NUMBER" => 0,
+            "MANUFACTURING_DATE" => 1,
+            "BATCH_NUMBER" => 2,
+            "EXPIRATION_DATE" => 3,
+            "SOFTWARE_IDENTIFICATION" => 4,
+            _ => 5,
+        };
+        (rank, id)
+    }
+    ids.sort_by(|a, b| rank(&a.value).cmp(&rank(&b.value)));
+}
+
+/// WHO before ECHA; within an agency by chemical type (medicinal / endocrine
+/// first), then by name and identifier of the first chemical.
+pub fn chemicals(infos: &mut [ChemicalRegulationInformation]) {
+    fn first(info: &ChemicalRegulationInformation) -> Option<&RegulatedChemical> {
+        info.regulations.first().and_then(|r| r.chemicals.first())
+    }
+    fn key(info: &ChemicalRegulationInformation) -> (u8, u8, &str, &str, &str) {
+        let agency = match info.agency.as_str() {
+            "WHO" => 0,
+            "ECHA" => 1,
+            _ => 2,
+        };
+        let chemical = first(info);
+        let chemical_type = chemical
+            .map(|c| match c.chemical_type.value.as_ref() {
+                "MEDICINAL_PRODUCT" | "ENDOCRINE_SUBSTANCE" => 0,
+                "HUMAN_PRODUCT" | "CMR_SUBSTANCE" => 1,
+                _ => 2,
+            })
+            .unwrap_or(2);
+        (
+            agency,
+            chemical_type,
+            info.regulations
+                .first()
+                .map(|r| r.regulation_name.as_str())
+                .unwrap_or_default(),
+            chemical
+                .and_then(|c| c.chemical_name.as_deref())
+                .unwrap_or_default(),
+            chemical
+                .and_then(|c| c.identifier_ref.as_ref())
+                .map(|r| r.value.as_str())
+                .unwrap_or_default(),
+        )
+    }
+    for info in infos.iter_mut() {
+        for regulation in &mut info.regulations {
+            for chemical in &mut regulation.chemicals {
+                languages(&mut chemical.descriptions);
+            }
+        }
+    }
+    infos.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
+/// ORIGINAL_PLACED first, then by (first) country; countries of a condition
+/// by code.
+pub fn sales_conditions(conditions: &mut [TargetMarketSalesCondition]) {
+    for condition in conditions.iter_mut() {
+        condition
+            .countries
+            .sort_by(|a, b| a.country_code.value.cmp(&b.country_code.value));
+    }
+    let country = |c: &TargetMarketSalesCondition| {
+        c.countries
+            .first()
+            .map(|c| c.country_code.value.to_string())
+            .unwrap_or_default()
+    };
+    conditions.sort_by(|a, b| {
+        let a_orig = a.condition_code.value == "ORIGINAL_PLACED";
+        let b_orig = b.condition_code.value == "ORIGINAL_PLACED";
+        b_orig
+            .cmp(&a_orig)
+            .then_with(|| country(a).cmp(&country(b)))
+            .then_with(|| a.condition_code.value.cmp(&b.condition_code.value))
+    });
+}
+
+/// By classification system, values by code.
+pub fn classifications(classifications: &mut [AdditionalClassification]) {
+    for c in classifications.iter_mut() {
+        c.values.sort_by(|a, b| a.code_value.cmp(&b.code_value));
+    }
+    classifications.sort_by(|a, b| a.system_code.value.cmp(&b.system_code.value));
+}
+
+/// Manufacturer (EMA / EPP) first, then the AR (EAR), then other types; same
+/// type by SRN.
+pub fn contacts(contacts: &mut [TradeItemContactInformation]) {
+    fn key(c: &TradeItemContactInformation) -> (u8, &str, &str) {
+        let rank = match c.contact_type.value.as_ref() {
+            "EMA" | "EPP" => 0,
+            "EAR" => 1,
+            _ => 2,
+        };
+        let srn = c
+            .party_identification
+            .first()
+            .map(|p| p.value.as_str())
+            .unwrap_or_default();
+        (rank, &c.contact_type.value, srn)
+    }
+    contacts.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
+/// By type code, then value.
+pub fn identifications(ids: &mut [AdditionalTradeItemIdentification]) {
+    ids.sort_by(|a, b| {
+        a.type_code
+            .cmp(&b.type_code)
+            .then_with(|| a.value.cmp(&b.value))
+    });
+}
+
+/// Storage conditions and warnings by code, clinical sizes by type, precision
+/// and values; their texts by language.
+pub fn healthcare(info: &mut HealthcareItemInformation) {
+    for s in &mut info.storage_handling {
+        languages(&mut s.descriptions);
+    }
+    info.storage_handling
+        .sort_by(|a, b| a.type_code.value.cmp(&b.type_code.value));
+
+    fn first(values: &[MeasurementValue]) -> (f64, &str) {
+        values
+            .first()
+            .map(|v| (v.value, v.unit_code.as_ref()))
+            .unwrap_or((f64::MIN, ""))
+    }
+    for size in &mut info.clinical_sizes {
+        languages(&mut size.descriptions);
+    }
+    info.clinical_sizes.sort_by(|a, b| {
+        let (a_value, a_unit) = first(&a.values);
+        let (b_value, b_unit) = first(&b.values);
+        let (a_max, _) = first(&a.maximums);
+        let (b_max, _) = first(&b.maximums);
+        a.type_code
+            .value
+            .cmp(&b.type_code.value)
+            .then_with(|| a.precision.value.cmp(&b.precision.value))
+            .then_with(|| a_value.total_cmp(&b_value))
+            .then_with(|| a_max.total_cmp(&b_max))
+            .then_with(|| a_unit.cmp(b_unit))
+            .then_with(|| a.text.cmp(&b.text))
+    });
+
+    for w in &mut info.clinical_warnings {
+        languages(&mut w.descriptions);
+    }
+    info.clinical_warnings.sort_by(|a, b| {
+        a.agency_code
+            .value
+            .cmp(&b.agency_code.value)
+            .then_with(|| a.warning_code.cmp(&b.warning_code))
+    });
+}
+
+/// Put every array of `item` in its canonical order.
+pub fn trade_item(item: &mut TradeItem) {
+    classifications(&mut item.classification.additional_classifications);
+    contacts(&mut item.contact_information);
+    identifications(&mut item.additional_identification);
+    production_identifiers(&mut item.medical_device_module.info.production_identifier_types);
+    if let Some(module) = &mut item.healthcare_item_module {
+        healthcare(&mut module.info);
+    }
+    if let Some(module) = &mut item.chemical_regulation_module {
+        chemicals(&mut module.infos);
+    }
+    if let Some(module) = &mut item.sales_module {
+        sales_conditions(&mut module.sales.conditions);
+    }
+    if let Some(module) = &mut item.description_module {
+        languages(&mut module.info.description_short);
+        languages(&mut module.info.descriptions);
+        languages(&mut module.info.additional_descriptions);
+    }
+    for model in &mut item.global_model_info {
+        languages(&mut model.descriptions);
+    }
+    languages(
+        &mut item
+            .medical_device_module
+            .info
+            .system_or_procedure_pack_purpose,
+    );
+}
+
+/// `trade_item` for the document and all its package levels.
+pub fn document(doc: &mut FirstbaseDocument) {
+    fn children(links: &mut [CatalogueItemChildItemLink]) {
+        for link in links {
+            trade_item(&mut link.catalogue_item.trade_item);
+            children(&mut link.catalogue_item.children);
+        }
+    }
+    trade_item(&mut doc.trade_item);
+    children(&mut doc.children);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lang(code: &'static str, value: &str) -> LangValue {
+        LangValue {
+            language_code: code.into(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn source_order_does_not_leak_into_the_output() {
+        let mut a = vec![
+            lang("sv", "x"),
+            lang("de", "b"),
+            lang("en", "a"),
+            lang("cs", "y"),
+        ];
+        let mut b = vec![
+            lang("cs", "y"),
+            lang("en", "a"),
+            lang("sv", "x"),
+            lang("de", "b"),
+        ];
+        languages(&mut a);
+        languages(&mut b);
+        let codes = |v: &[LangValue]| {
+            v.iter()
+                .map(|l| l.language_code.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(codes(&a), ["en", "de", "cs", "sv"]);
+        assert_eq!(codes(&a), codes(&b));
+
+        let contact = |t: &'static str, srn: &str| TradeItemContactInformation {
+            contact_type: CodeValue { value: t.into() },
+            party_identification: vec![AdditionalPartyIdentification {
+                type_code: "SRN".to_string(),
+                value: srn.to_string(),
+            }],
+            contact_name: None,
+            addresses: Vec::new(),
+            communication_channels: Vec::new(),
+        };
+        let mut c = vec![contact("EAR", "CH-AR-1"), contact("EMA", "DE-MF-2")];
+        contacts(&mut c);
+        assert_eq!(c[0].contact_type.value, "EMA");
+
+        let mut ids = vec![
+            CodeValue {
+                value: "BATCH_NUMBER".into(),
+            },
+            CodeValue {
+                value: "SERIAL_NUMBER".into(),
+            },
+        ];
+        production_identifiers(&mut ids);
+        assert_eq!(ids[0].value, "SERIAL_NUMBER");
+    }
+}
@@ -0,0 +1,229 @@
+//! Per-device field overrides (GUI override editor). `overrides/<uuid>.json`
+//! in the data directory maps JSON pointers into the firstbase document to the
+//! value that must be pushed instead of the converted one, e.g.
+//! `{"/DraftItem/TradeItem/.../DescriptionShort/0/Value": "Stent 8mm"}`.
+//! The converters never read them; `push_to_firstbase` applies them to every
+//! document it loads, so an override survives any number of re-conversions.
+//! Saving in the editor also patches the document on disk right away.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::backup::OVERRIDES_DIR;
+
+/// JSON pointer → replacement value.
+pub type Overrides = BTreeMap<String, serde_json::Value>;
+
+fn file(data_dir: &Path, uuid: &str) -> PathBuf {
+    data_dir.join(OVERRIDES_DIR).join(format!("{}.json", uuid))
+}
+
+/// Overrides of one device (empty if none or unreadable).
+pub fn load(data_dir: &Path, uuid: &str) -> Overrides {
+    std::fs::read_to_string(file(data_dir, uuid))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Write the overrides of one device; an empty set removes the file.
+pub fn save(data_dir: &Path, uuid: &str, overrides: &Overrides) -> anyhow::Result<()> {
+    let path = file(data_dir, uuid);
+    if overrides.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    std::fs::create_dir_all(data_dir.join(OVERRIDES_DIR))?;
+    crate::write_json(&path, overrides)?;
+    Ok(())
+}
+
+/// Apply `overrides` to `doc`. Pointers whose target no longer exists in the
+/// document (the converted structure changed) are skipped. Returns the number
+/// of values that changed.
+pub fn apply(doc: &mut serde_json::Value, overrides: &Overrides) -> usize {
+    let mut changed = 0;
+    for (pointer, value) in overrides {
+        if let Some(target) = doc.pointer_mut(pointer) {
+            if target != value {
+                *target = value.clone();
+                changed += 1;
+            }
+        }
+    }
+    changed
+}
+
+/// The document of `uuid`: the pending copy in `firstbase_json/`, else the
+/// pushed one in `processed/`.
+pub fn doc_path(firstbase_dir: &Path, uuid: &str) -> Option<PathBuf> {
+    let name = format!("{}.json", uuid);
+    [
+        firstbase_dir.join(&name),
+        firstbase_dir.join("processed").join(&name),
+    ]
+    .into_iter()
+    .find(|p| p.exists())
+}
+
+/// Every scalar (string, number, bool) leaf of `doc` as (pointer, value).
+pub(crate) fn leaves(
+    value: &serde_json::Value,
+    pointer: String,
+    out: &mut Vec<(String, serde_json::Value)>,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                let key = k.replace('~', "~0").replace('/', "~1");
+                leaves(v, format!("{}/{}", pointer, key), out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                leaves(v, format!("{}/{}", pointer, i), out);
+            }
+        }
+        serde_json::Value::Null => {}
+        scalar => out.push((pointer, scalar.clone())),
+    }
+}
+
+/// One editable attribute in the editor.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub pointer: String,
+    /// Value in the document as loaded (JSON type decides how `text` parses).
+    pub original: serde_json::Value,
+    pub text: String,
+    /// Part of the saved overrides.
+    pub overridden: bool,
+}
+
+impl Field {
+    /// `text` as a value of the original's JSON type.
+    fn value(&self) -> anyhow::Result<serde_json::Value> {
+        Ok(match &self.original {
+            serde_json::Value::String(_) => serde_json::Value::String(self.text.clone()),
+            _ => serde_json::from_str(self.text.trim())
+                .map_err(|_| anyhow::anyhow!("{}: not a valid value", self.pointer))?,
+        })
+    }
+}
+
+pub(crate) fn text_of(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Override editor state of one device.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    pub uuid: String,
+    pub gtin: String,
+    pub doc_path: PathBuf,
+    pub fields: Vec<Field>,
+    /// Case-insensitive pointer filter; pre-set when opened for a finding.
+    pub filter: String,
+    pub status: String,
+}
+
+impl Editor {
+    /// Load the document and its saved overrides; `focus` pre-fills the filter.
+    pub fn open(data_dir: &Path, doc_path: &Path, focus: &str) -> anyhow::Result<Editor> {
+        let uuid = doc_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut doc: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(doc_path)?)?;
+        let saved = load(data_dir, &uuid);
+        apply(&mut doc, &saved);
+        let mut all = Vec::new();
+        leaves(&doc, String::new(), &mut all);
+        let fields = all
+            .into_iter()
+            .map(|(pointer, value)| Field {
+                text: text_of(&value),
+                overridden: saved.contains_key(&pointer),
+                original: value,
+                pointer,
+            })
+            .collect();
+        Ok(Editor {
+            gtin: crate::report::str_at(&doc, "/DraftItem/TradeItem/Gtin").to_string(),
+            uuid,
+            doc_path: doc_path.to_path_buf(),
+            fields,
+            filter: focus.to_string(),
+            status: String::new(),
+        })
+    }
+
+    /// Indices of the fields matching the filter.
+    pub fn visible(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.pointer.to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Current overrides: every field marked overridden.
+    pub fn overrides(&self) -> anyhow::Result<Overrides> {
+        self.fields
+            .iter()
+            .filter(|f| f.overridden)
+            .map(|f| Ok((f.pointer.clone(), f.value()?)))
+            .collect()
+    }
+
+    /// Persist the overrides and patch the document on disk.
+    pub fn save(&self, data_dir: &Path) -> anyhow::Result<usize> {
+        let overrides = self.overrides()?;
+        save(data_dir, &self.uuid, &overrides)?;
+        let mut doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&self.doc_path)?)?;
+        if apply(&mut doc, &overrides) > 0 {
+            crate::write_json(&self.doc_path, &doc)?;
+        }
+        Ok(overrides.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_survives_reconversion() {
+        let dir = std::env::temp_dir().join(format!("e2f_overrides_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let doc_path = dir.join("u1.json");
+        let converted = serde_json::json!({"DraftItem": {"TradeItem": {
+            "Gtin": "1", "Name": [{"Value": "old"}], "Count": 1}}});
+        std::fs::write(&doc_path, converted.to_string()).unwrap();
+
+        let mut editor = Editor::open(&dir, &doc_path, "name").unwrap();
+        assert_eq!(editor.visible().len(), 1);
+        let i = editor.visible()[0];
+        editor.fields[i].text = "new".to_string();
+        editor.fields[i].overridden = true;
+        assert_eq!(editor.save(&dir).unwrap(), 1);
+
+        // Re-conversion rewrites the document; the push re-applies the override.
+        let mut reconverted = converted.clone();
+        assert_eq!(apply(&mut reconverted, &load(&dir, "u1")), 1);
+        assert_eq!(
+            reconverted.pointer("/DraftItem/TradeItem/Name/0/Value"),
+            Some(&serde_json::json!("new"))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+//! Packaging hierarchy of a firstbase document (GUI device detail window):
+//! the `CatalogueItemChildItemLink` chain from the pushed top-level item down
+//! to the base unit, with the quantity of each level in its parent and the
+//! structural mistakes GS1 would reject or that point to a wrong package
+//! mapping (zero quantity, repeated GTIN, base unit not at the bottom).
+
+use crate::report::str_at;
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub gtin: String,
+    /// `TradeItemUnitDescriptorCode`, e.g. CASE, PACK_OR_INNER_PACK, BASE_UNIT_OR_EACH.
+    pub descriptor: String,
+    pub base_unit: bool,
+    /// Quantity of this item in its parent (`None` for the top level).
+    pub quantity: Option<u64>,
+    pub children: Vec<Node>,
+    pub issues: Vec<String>,
+}
+
+impl Node {
+    /// Base units contained in one of this item.
+    pub fn base_units(&self) -> u64 {
+        if self.children.is_empty() {
+            return 1;
+        }
+        self.children
+            .iter()
+            .map(|c| c.quantity.unwrap_or(0) * c.base_units())
+            .sum()
+    }
+
+    /// Issues of this level and all levels below.
+    pub fn issue_count(&self) -> usize {
+        self.issues.len() + self.children.iter().map(Node::issue_count).sum::<usize>()
+    }
+}
+
+fn node(item: &serde_json::Value, quantity: Option<u64>, seen: &mut Vec<String>) -> Node {
+    let trade_item = item.get("TradeItem").unwrap_or(&serde_json::Value::Null);
+    let gtin = str_at(trade_item, "/Gtin").to_string();
+    let mut issues = Vec::new();
+    if gtin.is_empty() {
+        issues.push("no GTIN".to_string());
+    } else if seen.contains(&gtin) {
+        issues.push(format!("GTIN {} repeats a higher level", gtin));
+    }
+    if quantity == Some(0) {
+        issues.push("quantity 0".to_string());
+    }
+    seen.push(gtin.clone());
+    let children: Vec<Node> = item
+        .get("CatalogueItemChildItemLink")
+        .and_then(|v| v.as_array())
+        .map(|links| {
+            links
+                .iter()
+                .map(|link| {
+                    node(
+                        link.get("CatalogueItem")
+                            .unwrap_or(&serde_json::Value::Null),
+                        Some(link.get("Quantity").and_then(|q| q.as_u64()).unwrap_or(0)),
+                        seen,
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    seen.pop();
+    let base_unit = trade_item.get("IsTradeItemABaseUnit") == Some(&serde_json::Value::Bool(true));
+    if base_unit && !children.is_empty() {
+        issues.push("base unit contains other items".to_string());
+    }
+    if !base_unit && children.is_empty() {
+        issues.push("lowest level is not a base unit".to_string());
+    }
+    Node {
+        gtin,
+        descriptor: str_at(trade_item, "/TradeItemUnitDescriptorCode/Value").to_string(),
+        base_unit,
+        quantity,
+        children,
+        issues,
+    }
+}
+
+/// The hierarchy of a document, top level (what is pushed) first.
+pub fn tree(doc: &serde_json::Value) -> Node {
+    node(
+        doc.get("DraftItem").unwrap_or(&serde_json::Value::Null),
+        None,
+        &mut Vec::new(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_of_boxes_of_base_units() {
+        let item = |gtin: &str, code: &str, base: bool, links: serde_json::Value| {
+            serde_json::json!({
+                "TradeItem": {"Gtin": gtin, "IsTradeItemABaseUnit": base,
+                              "TradeItemUnitDescriptorCode": {"Value": code}},
+                "CatalogueItemChildItemLink": links,
+            })
+        };
+        let base = item("1", "BASE_UNIT_OR_EACH", true, serde_json::json!([]));
+        let pack = item(
+            "2",
+            "PACK_OR_INNER_PACK",
+            false,
+            serde_json::json!([{"Quantity": 10, "CatalogueItem": base}]),
+        );
+        let doc = serde_json::json!({"DraftItem": item(
+            "3",
+            "CASE",
+            false,
+            serde_json::json!([{"Quantity": 5, "CatalogueItem": pack}]),
+        )});
+        let case = tree(&doc);
+        assert_eq!(case.descriptor, "CASE");
+        assert_eq!(case.base_units(), 50);
+        assert_eq!(case.issue_count(), 0);
+
+        let mut broken = doc.clone();
+        broken["DraftItem"]["CatalogueItemChildItemLink"][0]["Quantity"] = 0.into();
+        broken["DraftItem"]["CatalogueItemChildItemLink"][0]["CatalogueItem"]["TradeItem"]
+            ["Gtin"] = "3".into();
+        assert_eq!(
+            tree(&broken).children[0].issues,
+            vec!["GTIN 3 repeats a higher level", "quantity 0"]
+        );
+    }
+}
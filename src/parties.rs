@@ -0,0 +1,110 @@
+//! `BrandOwner` and `ManufacturerOfTradeItem` of a trade item, next to the
+//! information provider. Some datapool profiles require them; EUDAMED has no
+//! GLNs, so they come from `[parties]`: the party set for the manufacturer's
+//! SRN in `[parties.by_srn]`, else the default. Each role is left out of the
+//! document when neither is set.
+//!
+//! The manufacturer SRN is the one of the EMA / EPP contact; every package
+//! level takes the parties of its document. Applied at the end of every
+//! transform path, and again after the listing merge, which may add the
+//! manufacturer contact.
+
+use crate::config::{Config, Party};
+use crate::firstbase::{CatalogueItemChildItemLink, FirstbaseDocument, PartyInRole, TradeItem};
+
+/// SRN of the manufacturer (EMA) or procedure pack producer (EPP) contact.
+pub fn manufacturer_srn(item: &TradeItem) -> Option<&str> {
+    item.contact_information
+        .iter()
+        .filter(|c| matches!(c.contact_type.value.as_ref(), "EMA" | "EPP"))
+        .flat_map(|c| &c.party_identification)
+        .find(|p| p.type_code == "SRN")
+        .map(|p| p.value.as_str())
+}
+
+fn party(party: &Party) -> PartyInRole {
+    PartyInRole {
+        gln: party.gln.clone(),
+        party_name: party.name.clone().filter(|n| !n.is_empty()),
+    }
+}
+
+/// Brand owner and manufacturer for `srn`: the SRN's entry, role by role,
+/// ahead of the defaults.
+fn resolve(config: &Config, srn: Option<&str>) -> (Option<PartyInRole>, Vec<PartyInRole>) {
+    let parties = &config.parties;
+    let by_srn = srn.and_then(|srn| parties.by_srn.get(srn));
+    let brand_owner = by_srn
+        .and_then(|p| p.brand_owner.as_ref())
+        .or(parties.brand_owner.as_ref())
+        .filter(|p| !p.gln.is_empty())
+        .map(party);
+    let manufacturer = by_srn
+        .and_then(|p| p.manufacturer.as_ref())
+        .or(parties.manufacturer.as_ref())
+        .filter(|p| !p.gln.is_empty())
+        .map(party);
+    (brand_owner, manufacturer.into_iter().collect())
+}
+
+/// Set the parties of every level of `doc` from the first manufacturer SRN
+/// found in it, top level first.
+pub fn apply_document(doc: &mut FirstbaseDocument, config: &Config) {
+    fn find(links: &[CatalogueItemChildItemLink]) -> Option<&str> {
+        links.iter().find_map(|link| {
+            manufacturer_srn(&link.catalogue_item.trade_item)
+                .or_else(|| find(&link.catalogue_item.children))
+        })
+    }
+    fn set(
+        links: &mut [CatalogueItemChildItemLink],
+        brand_owner: &Option<PartyInRole>,
+        manufacturer: &[PartyInRole],
+    ) {
+        for link in links {
+            let item = &mut link.catalogue_item.trade_item;
+            item.brand_owner.clone_from(brand_owner);
+            item.manufacturer_of_trade_item = manufacturer.to_vec();
+            set(&mut link.catalogue_item.children, brand_owner, manufacturer);
+        }
+    }
+
+    let srn = manufacturer_srn(&doc.trade_item).or_else(|| find(&doc.children));
+    let (brand_owner, manufacturer) = resolve(config, srn);
+    set(&mut doc.children, &brand_owner, &manufacturer);
+    doc.trade_item.brand_owner = brand_owner;
+    doc.trade_item.manufacturer_of_trade_item = manufacturer;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srn_entry_wins_role_by_role() {
+        let mut config = crate::config::test_config();
+        assert_eq!(resolve(&config, Some("DE-MF-000000001")).0, None);
+
+        config.parties = toml::from_str(
+            r#"
+            brand_owner = { gln = "7610000000001", name = "Default Brand" }
+            manufacturer = { gln = "7610000000002" }
+
+            [by_srn."DE-MF-000000001"]
+            brand_owner = { gln = "4010000000003", name = "Acme GmbH" }
+            "#,
+        )
+        .unwrap();
+        let (brand_owner, manufacturer) = resolve(&config, Some("DE-MF-000000001"));
+        assert_eq!(brand_owner.unwrap().gln, "4010000000003");
+        assert_eq!(manufacturer[0].gln, "7610000000002");
+        assert_eq!(manufacturer[0].party_name, None);
+
+        let (brand_owner, _) = resolve(&config, Some("FR-MF-000000009"));
+        assert_eq!(
+            brand_owner.unwrap().party_name.as_deref(),
+            Some("Default Brand")
+        );
+        assert_eq!(resolve(&config, None).1.len(), 1);
+    }
+}
@@ -0,0 +1,260 @@
+//! Raw payload archive for pushes. Every pushed UUID gets the exact bytes it
+//! was built from stored next to its `push_log` row — the EUDAMED source
+//! record (detail JSON file or NDJSON line, located via the provenance
+//! sidecar), the cached Basic UDI-DI JSON and the firstbase document as sent —
+//! gzipped in the version DB, so an accepted submission can be reproduced
+//! bit-for-bit after EUDAMED has moved on. Blobs are stored once per SHA256
+//! (`raw_blob`) and referenced per push (`push_payload`); `export --payload`
+//! writes them back out.
+
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+use crate::version_db::{compress, decompress};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS raw_blob (
+        sha256 TEXT PRIMARY KEY,
+        size INTEGER NOT NULL,
+        data BLOB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS push_payload (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        push_log_id INTEGER NOT NULL,
+        uuid TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        source TEXT NOT NULL DEFAULT '',
+        sha256 TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_push_payload_log ON push_payload(push_log_id);
+    CREATE INDEX IF NOT EXISTS idx_push_payload_uuid ON push_payload(uuid);
+";
+
+/// One archived payload.
+#[derive(Debug, Clone)]
+pub struct Payload {
+    /// `source` (EUDAMED record), `basic` (Basic UDI-DI) or `firstbase` (as pushed).
+    pub kind: String,
+    /// Where the bytes came from: `file` or `file:line`.
+    pub source: String,
+    pub bytes: Vec<u8>,
+}
+
+impl Payload {
+    /// File extension matching the payload content.
+    pub fn extension(&self) -> &'static str {
+        if self.source.contains(".xml") {
+            "xml"
+        } else {
+            "json"
+        }
+    }
+}
+
+pub fn ensure_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+/// Paths in provenance sidecars are relative to the directory the conversion
+/// ran in — normally the data directory.
+fn resolve(data_dir: &Path, file: &str) -> PathBuf {
+    let path = PathBuf::from(file);
+    if path.is_absolute() || path.exists() {
+        path
+    } else {
+        data_dir.join(path)
+    }
+}
+
+/// Exact bytes of the 1-based `line` of `path` (decompressed for `.ndjson.gz`
+/// / `.zst` inputs), without the trailing `\n`.
+fn read_line(path: &Path, line: u32) -> Option<Vec<u8>> {
+    let mut reader = crate::ndjson_input::reader(path).ok()?;
+    let mut buf = Vec::new();
+    for _ in 0..line {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf).ok()? == 0 {
+            return None;
+        }
+    }
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+    }
+    Some(buf)
+}
+
+/// The payloads to archive for `uuid`: its EUDAMED source record and Basic
+/// UDI-DI JSON (whichever can still be found) plus `pushed`, the document sent.
+pub fn collect(data_dir: &Path, uuid: &str, doc_path: &Path, pushed: &str) -> Vec<Payload> {
+    let firstbase_dir = data_dir.join("firstbase_json");
+    let mut payloads = Vec::new();
+    let source = match crate::provenance::read(&firstbase_dir, uuid) {
+        Some(prov) => {
+            let path = resolve(data_dir, &prov.source_file);
+            let bytes = match prov.source_line {
+                Some(line) => read_line(&path, line),
+                None => std::fs::read(&path).ok(),
+            };
+            bytes.map(|b| (prov.location(), b))
+        }
+        None => {
+            let path = data_dir
+                .join("eudamed_json/detail")
+                .join(format!("{}.json", uuid));
+            std::fs::read(&path)
+                .ok()
+                .map(|b| (path.display().to_string(), b))
+        }
+    };
+    if let Some((source, bytes)) = source {
+        payloads.push(Payload {
+            kind: "source".to_string(),
+            source,
+            bytes,
+        });
+    }
+    let basic = data_dir
+        .join("eudamed_json/basic")
+        .join(format!("{}.json", uuid));
+    if let Ok(bytes) = std::fs::read(&basic) {
+        payloads.push(Payload {
+            kind: "basic".to_string(),
+            source: basic.display().to_string(),
+            bytes,
+        });
+    }
+    payloads.push(Payload {
+        kind: "firstbase".to_string(),
+        source: doc_path.display().to_string(),
+        bytes: pushed.as_bytes().to_vec(),
+    });
+    payloads
+}
+
+/// Archive `payloads` for the `push_log` row `push_log_id`.
+pub fn store(
+    conn: &Connection,
+    push_log_id: i64,
+    uuid: &str,
+    payloads: &[Payload],
+) -> anyhow::Result<()> {
+    ensure_schema(conn)?;
+    for p in payloads {
+        let sha256 = crate::version_db::hash_bytes(&p.bytes);
+        conn.execute(
+            "INSERT OR IGNORE INTO raw_blob (sha256, size, data) VALUES (?1, ?2, ?3)",
+            params![sha256, p.bytes.len() as i64, compress(&p.bytes)],
+        )?;
+        conn.execute(
+            "INSERT INTO push_payload (push_log_id, uuid, kind, source, sha256)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![push_log_id, uuid, p.kind, p.source, sha256],
+        )?;
+    }
+    Ok(())
+}
+
+/// The archived payloads of one push.
+pub fn load(conn: &Connection, push_log_id: i64) -> anyhow::Result<Vec<Payload>> {
+    ensure_schema(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT p.kind, p.source, b.data FROM push_payload p
+         JOIN raw_blob b ON b.sha256 = p.sha256
+         WHERE p.push_log_id = ?1 ORDER BY p.id",
+    )?;
+    let rows = stmt
+        .query_map(params![push_log_id], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, Vec<u8>>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    rows.into_iter()
+        .map(|(kind, source, data)| {
+            let bytes = decompress(&data)
+                .ok_or_else(|| anyhow::anyhow!("Corrupt archived {} payload", kind))?;
+            Ok(Payload {
+                kind,
+                source,
+                bytes,
+            })
+        })
+        .collect()
+}
+
+/// Latest push of a device (UUID or GTIN) that has archived payloads:
+/// `(push_log id, uuid, pushed_at, status)`.
+pub fn latest_push(
+    conn: &Connection,
+    key: &str,
+) -> anyhow::Result<Option<(i64, String, String, String)>> {
+    ensure_schema(conn)?;
+    let row = conn.query_row(
+        "SELECT l.id, l.uuid, l.pushed_at, l.status FROM push_log l
+         WHERE (l.uuid = ?1 OR l.gtin = ?1)
+           AND EXISTS (SELECT 1 FROM push_payload p WHERE p.push_log_id = l.id)
+         ORDER BY l.id DESC LIMIT 1",
+        params![key],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+    );
+    match row {
+        Ok(r) => Ok(Some(r)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Drop payloads whose `push_log` row is gone and blobs nothing references.
+/// Returns the number of deleted blobs.
+pub fn prune_orphans(conn: &Connection) -> anyhow::Result<usize> {
+    ensure_schema(conn)?;
+    conn.execute(
+        "DELETE FROM push_payload WHERE push_log_id NOT IN (SELECT id FROM push_log)",
+        [],
+    )?;
+    Ok(conn.execute(
+        "DELETE FROM raw_blob WHERE sha256 NOT IN (SELECT sha256 FROM push_payload)",
+        [],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_ndjson_line_byte_for_byte() {
+        let dir = std::env::temp_dir().join(format!("payload_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ndjson = dir.join("listing.ndjson");
+        std::fs::write(&ndjson, "{\"uuid\":\"a\"}\n{\"uuid\":\"b\",  \"x\":1}\r\n").unwrap();
+        let prov = crate::provenance::Provenance::new("b", &ndjson, Some(2));
+        crate::provenance::write(&dir.join("firstbase_json"), &prov).unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE push_log (id INTEGER PRIMARY KEY, uuid TEXT, gtin TEXT, pushed_at TEXT, status TEXT);
+             INSERT INTO push_log VALUES (1, 'b', '1', 't1', 'ACCEPTED'), (2, 'b', '1', 't2', 'ACCEPTED');",
+        )
+        .unwrap();
+        let payloads = collect(&dir, "b", Path::new("b.json"), "{}");
+        store(&conn, 1, "b", &payloads).unwrap();
+        store(&conn, 2, "b", &payloads).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(latest_push(&conn, "1").unwrap().unwrap().0, 2);
+        let loaded = load(&conn, 2).unwrap();
+        assert_eq!(loaded[0].kind, "source");
+        assert_eq!(loaded[0].bytes, b"{\"uuid\":\"b\",  \"x\":1}\r");
+        assert_eq!(loaded[1].kind, "firstbase");
+        // Same bytes in both pushes: one blob each.
+        let blobs: i64 = conn
+            .query_row("SELECT COUNT(*) FROM raw_blob", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(blobs, 2);
+    }
+}
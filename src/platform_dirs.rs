@@ -0,0 +1,192 @@
+//! Where config, caches, databases and output live when the binary is not
+//! run from a working directory of its own. The platform's application
+//! directories:
+//!
+//! | | config (`config.toml`) | data (caches, `db/`, output) |
+//! |---|---|---|
+//! | Linux | `$XDG_CONFIG_HOME` or `~/.config` | `$XDG_DATA_HOME` or `~/.local/share` |
+//! | macOS | `~/Library/Application Support` | the same |
+//! | Windows | `%APPDATA%` | `%LOCALAPPDATA%` |
+//!
+//! each with an `eudamed2firstbase` subdirectory. Overrides, strongest first:
+//! `EUDAMED2FIRSTBASE_CONFIG` (the config file), `EUDAMED2FIRSTBASE_HOME`
+//! (one directory for everything). Existing layouts keep working: a
+//! `config.toml` in the current directory still wins, and an existing
+//! `~/eudamed2firstbase/` (the old GUI data directory) stays the data
+//! directory (`download::app_data_dir`).
+//!
+//! The CLI resolves its relative paths (`ndjson/`, `xml/`, `firstbase_json/`,
+//! `db/` …) against the data directory unless started in a working directory
+//! that already has them (`enter_working_dir`).
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+pub const APP: &str = "eudamed2firstbase";
+/// One directory for config, data and output.
+pub const HOME_ENV: &str = "EUDAMED2FIRSTBASE_HOME";
+/// Path of the config file.
+pub const CONFIG_ENV: &str = "EUDAMED2FIRSTBASE_CONFIG";
+
+/// Entries that make the current directory a working directory of its own.
+const WORKING_DIR_MARKERS: &[&str] = &[
+    "config.toml",
+    "ndjson",
+    "xml",
+    "eudamed_json",
+    "firstbase_json",
+    "db",
+];
+
+struct Dirs {
+    config: PathBuf,
+    data: PathBuf,
+}
+
+/// The application directories of `os` from the environment `env`.
+fn platform(os: &str, env: &dyn Fn(&str) -> Option<OsString>) -> Option<Dirs> {
+    let var = |name: &str| env(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let (config, data) = match os {
+        "windows" => {
+            let roaming = var("APPDATA")?;
+            let local = var("LOCALAPPDATA").unwrap_or_else(|| roaming.clone());
+            (roaming, local)
+        }
+        "macos" => {
+            let support = var("HOME")?.join("Library").join("Application Support");
+            (support.clone(), support)
+        }
+        _ => {
+            let home = var("HOME");
+            let config = var("XDG_CONFIG_HOME").or_else(|| Some(home.clone()?.join(".config")))?;
+            let data = var("XDG_DATA_HOME")
+                .or_else(|| Some(home.clone()?.join(".local").join("share")))?;
+            (config, data)
+        }
+    };
+    Some(Dirs {
+        config: config.join(APP),
+        data: data.join(APP),
+    })
+}
+
+fn current() -> Option<Dirs> {
+    platform(std::env::consts::OS, &|name| std::env::var_os(name))
+}
+
+fn home_override() -> Option<PathBuf> {
+    std::env::var_os(HOME_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The platform data directory, or `EUDAMED2FIRSTBASE_HOME`.
+pub fn data_dir() -> Option<PathBuf> {
+    home_override().or_else(|| current().map(|d| d.data))
+}
+
+/// The config file: `EUDAMED2FIRSTBASE_CONFIG`, else the first existing of
+/// `EUDAMED2FIRSTBASE_HOME`, `./config.toml`, the platform config directory
+/// and the data directory; else the platform location (a missing file loads
+/// the built-in defaults).
+pub fn config_file() -> PathBuf {
+    if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|v| !v.is_empty()) {
+        return PathBuf::from(path);
+    }
+    if let Some(home) = home_override() {
+        return home.join("config.toml");
+    }
+    let candidates = [
+        Some(PathBuf::from("config.toml")),
+        current().map(|d| d.config.join("config.toml")),
+        Some(crate::download::app_data_dir().join("config.toml")),
+    ];
+    let fallback = current()
+        .map(|d| d.config.join("config.toml"))
+        .unwrap_or_else(|| PathBuf::from("config.toml"));
+    candidates
+        .into_iter()
+        .flatten()
+        .find(|p| p.exists())
+        .unwrap_or(fallback)
+}
+
+/// For the CLI: stay in the current directory if it is a working directory
+/// of its own (or `EUDAMED2FIRSTBASE_HOME` is `.`), else change to the data
+/// directory. Arguments naming existing files or directories are made
+/// absolute first, so `detail ./export.ndjson` still finds its input.
+pub fn enter_working_dir(args: Vec<String>) -> Vec<String> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let target = match home_override() {
+        Some(home) => home,
+        None if is_working_dir(&cwd) => return args,
+        None => crate::download::app_data_dir(),
+    };
+    if target == cwd || target == Path::new(".") {
+        return args;
+    }
+    if std::fs::create_dir_all(&target).is_err() || std::env::set_current_dir(&target).is_err() {
+        return args;
+    }
+    eprintln!("Working directory: {}", target.display());
+    args.into_iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let path = cwd.join(&arg);
+            if i > 0 && !arg.starts_with("--") && Path::new(&arg).is_relative() && path.exists() {
+                path.to_string_lossy().into_owned()
+            } else {
+                arg
+            }
+        })
+        .collect()
+}
+
+fn is_working_dir(dir: &Path) -> bool {
+    WORKING_DIR_MARKERS.iter().any(|m| dir.join(m).exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platform_directories() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| OsString::from(v))
+            }
+        };
+
+        let linux = platform("linux", &env(&[("HOME", "/home/u")])).unwrap();
+        assert_eq!(linux.config, Path::new("/home/u/.config/eudamed2firstbase"));
+        assert_eq!(
+            linux.data,
+            Path::new("/home/u/.local/share/eudamed2firstbase")
+        );
+        let xdg = platform(
+            "linux",
+            &env(&[("HOME", "/home/u"), ("XDG_CONFIG_HOME", "/cfg")]),
+        )
+        .unwrap();
+        assert_eq!(xdg.config, Path::new("/cfg/eudamed2firstbase"));
+
+        let mac = platform("macos", &env(&[("HOME", "/Users/u")])).unwrap();
+        assert_eq!(mac.config, mac.data);
+        assert!(mac
+            .data
+            .ends_with("Library/Application Support/eudamed2firstbase"));
+
+        let windows = platform(
+            "windows",
+            &env(&[("APPDATA", "C:/r"), ("LOCALAPPDATA", "C:/l")]),
+        )
+        .unwrap();
+        assert_eq!(windows.config, Path::new("C:/r/eudamed2firstbase"));
+        assert_eq!(windows.data, Path::new("C:/l/eudamed2firstbase"));
+
+        assert!(platform("linux", &env(&[])).is_none());
+    }
+}
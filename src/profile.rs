@@ -0,0 +1,243 @@
+//! GDSN datapool serialization profiles (`export --profile`).
+//! The converters build one TradeItem model serialized with GS1 CH firstbase
+//! naming; a profile re-shapes that JSON for another datapool: the top-level
+//! wrapper, attribute name casing and renames, firstbase-only keys to drop,
+//! `{"Value": ..}` code wrappers, and code-list value mappings.
+//!
+//! Built-in profiles are TOML (same format as a custom `--profile file.toml`)
+//! and are starting points — check attribute names against the target
+//! datapool's import specification and override in a file where they differ.
+//! They only change the envelope and the key naming and map no codes: no
+//! code-list differences to firstbase are known for atrify or 1WorldSync
+//! (all three use the GDSN code lists), so datapool-specific values go into
+//! the `[codes.<Attribute>]` tables of a custom profile.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::report::firstbase_docs;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    /// Replacement for the top-level `DraftItem` key; empty = unwrap it.
+    #[serde(default = "default_wrapper")]
+    pub wrapper: String,
+    /// `pascal` (firstbase, unchanged) or `camel` (GDSN BMS style).
+    #[serde(default)]
+    pub key_case: String,
+    /// Collapse `{"Value": "X"}` code objects to plain `"X"`.
+    #[serde(default)]
+    pub flatten_code_values: bool,
+    /// Keys (firstbase naming) removed wherever they occur.
+    #[serde(default)]
+    pub drop: Vec<String>,
+    /// Explicit key renames (firstbase name → target name); win over `key_case`.
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+    /// Code-list mappings per attribute (firstbase name → {source → target}).
+    #[serde(default)]
+    pub codes: HashMap<String, HashMap<String, String>>,
+}
+
+fn default_wrapper() -> String {
+    "DraftItem".to_string()
+}
+
+const FIRSTBASE: &str = r#"
+name = "firstbase"
+"#;
+
+// Envelope and key naming only; no `[codes]` (see the module docs).
+const ATRIFY: &str = r#"
+name = "atrify"
+wrapper = "catalogueItem"
+key_case = "camel"
+drop = ["Identifier"]
+
+[rename]
+Gtin = "gtin"
+UdidDeviceCount = "udidDeviceCount"
+"#;
+
+const ONEWORLDSYNC: &str = r#"
+name = "1worldsync"
+wrapper = "tradeItemDocument"
+key_case = "camel"
+flatten_code_values = true
+drop = ["Identifier"]
+
+[rename]
+Gtin = "gtin"
+CatalogueItemChildItemLink = "childTradeItem"
+"#;
+
+impl Profile {
+    /// Built-in profile by name, or a custom profile read from a TOML file.
+    pub fn load(name_or_path: &str) -> anyhow::Result<Profile> {
+        let content = match name_or_path.to_ascii_lowercase().as_str() {
+            "firstbase" => FIRSTBASE.to_string(),
+            "atrify" => ATRIFY.to_string(),
+            "1worldsync" | "oneworldsync" => ONEWORLDSYNC.to_string(),
+            _ if Path::new(name_or_path).is_file() => std::fs::read_to_string(name_or_path)
+                .with_context(|| format!("Failed to read profile {}", name_or_path))?,
+            _ => bail!(
+                "Unknown profile '{}' (expected firstbase, atrify, 1worldsync or a .toml file)",
+                name_or_path
+            ),
+        };
+        let profile: Profile = toml::from_str(&content)
+            .with_context(|| format!("Invalid profile {}", name_or_path))?;
+        if !matches!(profile.key_case.as_str(), "" | "pascal" | "camel") {
+            bail!(
+                "Unknown key_case '{}' in profile {} (expected pascal or camel)",
+                profile.key_case,
+                name_or_path
+            );
+        }
+        Ok(profile)
+    }
+
+    fn target_key(&self, key: &str) -> String {
+        if let Some(renamed) = self.rename.get(key) {
+            return renamed.clone();
+        }
+        if key == "DraftItem" {
+            return self.wrapper.clone();
+        }
+        if self.key_case == "camel" {
+            let mut chars = key.chars();
+            if let Some(first) = chars.next() {
+                return first.to_lowercase().chain(chars).collect();
+            }
+        }
+        key.to_string()
+    }
+
+    fn convert(&self, key: Option<&str>, value: serde_json::Value) -> serde_json::Value {
+        use serde_json::Value;
+        let codes = key.and_then(|k| self.codes.get(k));
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(code)) = map.get("Value").filter(|_| map.len() == 1) {
+                    let code = codes
+                        .and_then(|c| c.get(code))
+                        .cloned()
+                        .unwrap_or_else(|| code.clone());
+                    if self.flatten_code_values {
+                        return Value::String(code);
+                    }
+                    let mut out = serde_json::Map::new();
+                    out.insert(self.target_key("Value"), Value::String(code));
+                    return Value::Object(out);
+                }
+                let mut out = serde_json::Map::new();
+                for (k, v) in map {
+                    if self.drop.iter().any(|d| d == &k) {
+                        continue;
+                    }
+                    let v = self.convert(Some(&k), v);
+                    if k == "DraftItem" && self.wrapper.is_empty() {
+                        if let Value::Object(inner) = v {
+                            out.extend(inner);
+                        }
+                        continue;
+                    }
+                    out.insert(self.target_key(&k), v);
+                }
+                Value::Object(out)
+            }
+            // Arrays keep the parent key so code mappings apply per element.
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.convert(key, v)).collect())
+            }
+            Value::String(s) => match codes.and_then(|c| c.get(&s)) {
+                Some(mapped) => Value::String(mapped.clone()),
+                None => Value::String(s),
+            },
+            other => other,
+        }
+    }
+
+    /// Re-shape one firstbase document (`{"DraftItem": ...}`) for this profile.
+    pub fn apply(&self, doc: serde_json::Value) -> serde_json::Value {
+        self.convert(None, doc)
+    }
+}
+
+/// Re-serialize every per-UUID document in `firstbase_dir` (plus `processed/`
+/// when `include_processed`) with `profile` into `out_dir/<uuid>.json`.
+/// Returns the number of documents written.
+pub fn export_profile(
+    profile: &Profile,
+    firstbase_dir: &Path,
+    include_processed: bool,
+    out_dir: &Path,
+) -> anyhow::Result<usize> {
+    let files = firstbase_docs(firstbase_dir, include_processed);
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    let written = files
+        .par_iter()
+        .filter(|p| {
+            let Ok(content) = std::fs::read_to_string(p) else {
+                return false;
+            };
+            let Ok(doc) = serde_json::from_str::<serde_json::Value>(&content) else {
+                eprintln!("  Skipping unparsable {}", p.display());
+                return false;
+            };
+            crate::write_json(
+                &out_dir.join(p.file_name().unwrap_or_default()),
+                &profile.apply(doc),
+            )
+            .is_ok()
+        })
+        .count();
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_reshape_keys_wrappers_and_codes() {
+        let doc = serde_json::json!({"DraftItem": {
+            "TradeItem": {"Gtin": "08800042702694",
+                          "TradeItemUnitDescriptorCode": {"Value": "BASE_UNIT_OR_EACH"}},
+            "Identifier": "Draft_x"}});
+
+        let firstbase = Profile::load("firstbase").unwrap();
+        assert_eq!(firstbase.apply(doc.clone()), doc);
+
+        // The built-ins map no codes; that takes a custom profile.
+        for name in ["firstbase", "atrify", "1worldsync"] {
+            assert!(Profile::load(name).unwrap().codes.is_empty(), "{}", name);
+        }
+
+        let atrify = Profile::load("atrify").unwrap();
+        assert_eq!(
+            atrify.apply(doc.clone()),
+            serde_json::json!({"catalogueItem": {"tradeItem": {"gtin": "08800042702694",
+                "tradeItemUnitDescriptorCode": {"value": "BASE_UNIT_OR_EACH"}}}})
+        );
+
+        let mut custom = Profile::load("1worldsync").unwrap();
+        custom.wrapper.clear();
+        custom.codes.insert(
+            "TradeItemUnitDescriptorCode".to_string(),
+            HashMap::from([("BASE_UNIT_OR_EACH".to_string(), "EA".to_string())]),
+        );
+        assert_eq!(
+            custom.apply(doc),
+            serde_json::json!({"tradeItem": {"gtin": "08800042702694",
+                "tradeItemUnitDescriptorCode": "EA"}})
+        );
+    }
+}
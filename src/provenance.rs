@@ -0,0 +1,295 @@
+//! Source provenance of converted documents.
+//! Each per-UUID firstbase document gets a sidecar
+//! `firstbase_json/provenance/<uuid>.json` naming the EUDAMED record it was
+//! converted from (input file, NDJSON line, UUID, download time), so any
+//! attribute in the output can be traced back to its exact source record.
+//! The GS1 document itself stays untouched — firstbase rejects unknown keys.
+//! The same facts are stored per version in the history DB (`device_history`).
+//!
+//! `trace` goes down to single attributes for the GUI provenance inspector:
+//! each value of the document is looked up in the inputs the converter had —
+//! saved override, `config.toml` default, the detail record, the cached
+//! Basic UDI-DI (merged in), the listing row — and attributed to the first
+//! one holding the same value. What matches none of them was mapped or
+//! derived by the converter (code lists, flags, quantities).
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Sidecar directory below the firstbase output directory.
+pub const PROVENANCE_DIR: &str = "provenance";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub uuid: String,
+    pub source_file: String,
+    /// 1-based line of the record in an NDJSON input; `None` for
+    /// one-record-per-file JSON inputs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_line: Option<u32>,
+    /// When the source file was downloaded (its modification time, UTC).
+    pub downloaded_at: String,
+    pub converted_at: String,
+}
+
+impl Provenance {
+    pub fn new(uuid: &str, source_file: &Path, source_line: Option<u32>) -> Self {
+        Provenance {
+            uuid: uuid.to_string(),
+            source_file: source_file.display().to_string(),
+            source_line,
+            downloaded_at: file_timestamp(source_file),
+            converted_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        }
+    }
+
+    /// `file` or `file:line`.
+    pub fn location(&self) -> String {
+        match self.source_line {
+            Some(line) => format!("{}:{}", self.source_file, line),
+            None => self.source_file.clone(),
+        }
+    }
+}
+
+/// Modification time of `path` as `%Y-%m-%dT%H:%M:%SZ`, or "" if unavailable.
+pub fn file_timestamp(path: &Path) -> String {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| {
+            chrono::DateTime::<chrono::Utc>::from(t)
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Write the sidecar for `prov.uuid` below `output_dir`.
+pub fn write(output_dir: &Path, prov: &Provenance) -> anyhow::Result<()> {
+    let dir = output_dir.join(PROVENANCE_DIR);
+    std::fs::create_dir_all(&dir)?;
+    crate::write_json(&dir.join(format!("{}.json", prov.uuid)), prov)
+}
+
+/// Sidecar for `uuid` below `firstbase_dir`, if one was written.
+pub fn read(firstbase_dir: &Path, uuid: &str) -> Option<Provenance> {
+    let path = firstbase_dir
+        .join(PROVENANCE_DIR)
+        .join(format!("{}.json", uuid));
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Where an attribute of a converted document came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Override,
+    Config,
+    Detail,
+    BasicUdi,
+    Listing,
+    Converter,
+}
+
+impl Origin {
+    pub fn label(self) -> &'static str {
+        match self {
+            Origin::Override => "override",
+            Origin::Config => "config default",
+            Origin::Detail => "detail",
+            Origin::BasicUdi => "Basic UDI-DI (merge)",
+            Origin::Listing => "listing",
+            Origin::Converter => "converter (mapped/derived)",
+        }
+    }
+}
+
+/// The inputs of one device's conversion, as far as they are still on disk.
+#[derive(Debug, Default)]
+pub struct Sources {
+    pub detail: Option<serde_json::Value>,
+    pub basic_udi: Option<serde_json::Value>,
+    /// The device's `listing_cache` row as an object of column → value.
+    pub listing: Option<serde_json::Value>,
+    pub config: Vec<(&'static str, String)>,
+    pub overrides: crate::overrides::Overrides,
+}
+
+fn listing_row(db_path: &Path, uuid: &str) -> Option<serde_json::Value> {
+    use rusqlite::types::ValueRef;
+    if !db_path.exists() {
+        return None;
+    }
+    let conn = rusqlite::Connection::open(db_path).ok()?;
+    let mut stmt = conn
+        .prepare("SELECT * FROM listing_cache WHERE uuid = ?1")
+        .ok()?;
+    let names: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    stmt.query_row([uuid], |row| {
+        let mut map = serde_json::Map::new();
+        for (i, name) in names.iter().enumerate() {
+            let value = match row.get_ref(i)? {
+                ValueRef::Null => continue,
+                ValueRef::Integer(n) => serde_json::Value::from(n),
+                ValueRef::Real(f) => serde_json::Value::from(f),
+                ValueRef::Text(t) | ValueRef::Blob(t) => {
+                    serde_json::Value::from(String::from_utf8_lossy(t).to_string())
+                }
+            };
+            map.insert(name.clone(), value);
+        }
+        Ok(serde_json::Value::Object(map))
+    })
+    .ok()
+}
+
+impl Sources {
+    /// Inputs of `uuid` below the data directory: the source record and
+    /// Basic UDI-DI as the payload archive finds them, the listing row in the
+    /// version DB, `config.toml` and the saved overrides.
+    pub fn load(data_dir: &Path, uuid: &str) -> Sources {
+        let mut detail = None;
+        let mut basic_udi = None;
+        for payload in crate::payload_archive::collect(data_dir, uuid, Path::new(""), "") {
+            let value = serde_json::from_slice(&payload.bytes).ok();
+            match payload.kind.as_str() {
+                "source" => detail = value,
+                "basic" => basic_udi = value,
+                _ => {}
+            }
+        }
+        let config = crate::config::load_config(&data_dir.join("config.toml"))
+            .map(|c| {
+                vec![
+                    ("provider.gln", c.provider.gln),
+                    ("provider.party_name", c.provider.party_name),
+                    ("provider.publish_gln", c.provider.publish_gln),
+                    ("target_market.country_code", c.target_market.country_code),
+                    ("gpc.segment_code", c.gpc.segment_code),
+                    ("gpc.family_code", c.gpc.family_code),
+                    ("gpc.class_code", c.gpc.class_code),
+                    ("gpc.category_code", c.gpc.category_code),
+                    ("gpc.category_name", c.gpc.category_name),
+                ]
+            })
+            .unwrap_or_default();
+        Sources {
+            detail,
+            basic_udi,
+            listing: listing_row(&data_dir.join("db/version_tracking.db"), uuid),
+            config,
+            overrides: crate::overrides::load(data_dir, uuid),
+        }
+    }
+}
+
+/// One attribute of the document with its origin; `source` is the pointer
+/// (or config key / overrides file) holding the same value.
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub pointer: String,
+    pub value: String,
+    pub origin: Origin,
+    pub source: String,
+}
+
+/// Value text → first pointer holding it in `record`.
+fn value_index(record: Option<&serde_json::Value>) -> HashMap<String, String> {
+    let mut all = Vec::new();
+    if let Some(record) = record {
+        crate::overrides::leaves(record, String::new(), &mut all);
+    }
+    let mut index = HashMap::new();
+    for (pointer, value) in all {
+        index
+            .entry(crate::overrides::text_of(&value))
+            .or_insert(pointer);
+    }
+    index
+}
+
+/// Origin of every scalar attribute of `doc`. Booleans and values shorter
+/// than three characters are left to the converter: a `true` or `1` in the
+/// inputs says nothing about where the output's came from.
+pub fn trace(doc: &serde_json::Value, sources: &Sources) -> Vec<Attribute> {
+    let indexes = [
+        (Origin::Detail, value_index(sources.detail.as_ref())),
+        (Origin::BasicUdi, value_index(sources.basic_udi.as_ref())),
+        (Origin::Listing, value_index(sources.listing.as_ref())),
+    ];
+    let mut all = Vec::new();
+    crate::overrides::leaves(doc, String::new(), &mut all);
+    all.into_iter()
+        .map(|(pointer, value)| {
+            let text = crate::overrides::text_of(&value);
+            let (origin, source) = if sources.overrides.contains_key(&pointer) {
+                (Origin::Override, "overrides".to_string())
+            } else if value.is_boolean() || text.chars().count() < 3 {
+                (Origin::Converter, String::new())
+            } else if let Some((key, _)) = sources.config.iter().find(|(_, v)| *v == text) {
+                (Origin::Config, key.to_string())
+            } else {
+                indexes
+                    .iter()
+                    .find_map(|(origin, index)| index.get(&text).map(|p| (*origin, p.clone())))
+                    .unwrap_or((Origin::Converter, String::new()))
+            };
+            Attribute {
+                pointer,
+                value: text,
+                origin,
+                source,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_are_traced_to_the_first_input_holding_them() {
+        let doc = serde_json::json!({"DraftItem": {"TradeItem": {
+            "Gtin": "07612345678901",
+            "Name": "Stent 8mm",
+            "Gmn": "B-07612345678901",
+            "Srn": "CH-MF-000012345",
+            "Gln": "7612345000480",
+            "Flag": true,
+            "Status": "ON_MARKET"
+        }}});
+        let mut sources = Sources {
+            detail: Some(serde_json::json!({"primaryDi": {"code": "07612345678901"},
+                "tradeName": {"texts": [{"text": "Stent"}]}})),
+            basic_udi: Some(serde_json::json!({"basicUdi": {"code": "B-07612345678901"}})),
+            listing: Some(serde_json::json!({"srn": "CH-MF-000012345",
+                "primary_di": "07612345678901"})),
+            config: vec![("provider.gln", "7612345000480".to_string())],
+            ..Default::default()
+        };
+        sources
+            .overrides
+            .insert("/DraftItem/TradeItem/Name".to_string(), "Stent 8mm".into());
+        let origins: HashMap<String, (Origin, String)> = trace(&doc, &sources)
+            .into_iter()
+            .map(|a| {
+                (
+                    a.pointer.rsplit('/').next().unwrap().to_string(),
+                    (a.origin, a.source),
+                )
+            })
+            .collect();
+        assert_eq!(
+            origins["Gtin"],
+            (Origin::Detail, "/primaryDi/code".to_string())
+        );
+        assert_eq!(origins["Name"].0, Origin::Override);
+        assert_eq!(origins["Gmn"].0, Origin::BasicUdi);
+        assert_eq!(origins["Srn"].0, Origin::Listing);
+        assert_eq!(origins["Gln"], (Origin::Config, "provider.gln".to_string()));
+        assert_eq!(origins["Flag"].0, Origin::Converter);
+        assert_eq!(origins["Status"].0, Origin::Converter);
+    }
+}
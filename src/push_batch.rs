@@ -0,0 +1,286 @@
+//! Adaptive CreateMany chunk size for `push_to_firstbase`. Instead of a fixed
+//! 100 items, each chunk's round trip (submit + RequestStatus polling until a
+//! verdict) and outcome decide the size of the next one, between `MIN` and
+//! `MAX` in steps of `STEP`:
+//!
+//! - no verdict from GS1 (transport failure, poll timeout): halve — a large
+//!   chunk is what runs into GS1's processing timeout, and all of its items
+//!   have to be retried;
+//! - slow verdict (over `SLOW`, half the poll budget): one step down;
+//! - fast verdict (under `FAST`) with few rejections: one step up.
+//!
+//! Many rejected items keep the size: rejections are data errors, not load,
+//! but a bad chunk should not grow the next one either.
+//!
+//! `[push.batch]` in config.toml sets the first size and the bounds
+//! (`min_size = max_size` pins it), the largest request body — a chunk whose
+//! items would exceed it is cut short, and an HTTP 413 from GS1 halves it —
+//! and the pause between two chunks — the only wait between them (8 s by
+//! default, `pause_secs = 0` for none).
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::Batch;
+
+pub const MIN: usize = 25;
+pub const MAX: usize = 200;
+pub const START: usize = 100;
+const STEP: usize = 25;
+/// Largest CreateMany request body by default: 4 MB, some 400 documents of
+/// the usual 10 kB.
+pub const MAX_PAYLOAD_BYTES: usize = 4_000_000;
+
+/// Resolved `[push.batch]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Items of the first chunk.
+    pub start: usize,
+    pub min: usize,
+    pub max: usize,
+    /// Largest request body, in bytes.
+    pub max_payload_bytes: usize,
+    /// Wait between two chunks, CreateMany and AddMany alike.
+    pub pause: Duration,
+}
+
+const DEFAULT: Limits = Limits {
+    start: START,
+    min: MIN,
+    max: MAX,
+    max_payload_bytes: MAX_PAYLOAD_BYTES,
+    pause: Duration::from_secs(8),
+};
+
+impl Default for Limits {
+    fn default() -> Self {
+        DEFAULT
+    }
+}
+
+impl fmt::Display for Limits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} item(s), {}-{} adaptive, at most {} bytes",
+            self.start, self.min, self.max, self.max_payload_bytes
+        )?;
+        if !self.pause.is_zero() {
+            write!(f, ", {}s between chunks", self.pause.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+/// `[push.batch]` over the built-in values; bounds are kept consistent
+/// (at least one item, `min <= start <= max`), and a `size` outside the
+/// default bounds widens them.
+pub fn limits(batch: &Batch) -> Limits {
+    let start = batch.size.unwrap_or(DEFAULT.start).max(1);
+    let min = batch.min_size.unwrap_or(DEFAULT.min.min(start)).max(1);
+    let max = batch.max_size.unwrap_or(DEFAULT.max.max(start)).max(min);
+    Limits {
+        start: start.clamp(min, max),
+        min,
+        max,
+        max_payload_bytes: batch.max_payload_bytes.unwrap_or(DEFAULT.max_payload_bytes),
+        pause: batch
+            .pause_secs
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map_or(DEFAULT.pause, Duration::from_secs_f64),
+    }
+}
+
+/// How many of the leading items, of these serialized sizes, fit a request
+/// body of `max_bytes`; at least one, so an oversized item is still sent
+/// (and rejected) alone.
+pub fn fit(item_bytes: impl IntoIterator<Item = usize>, max_bytes: usize) -> usize {
+    // The envelope: {"DocumentCommand":"…","Items":[…]} and the commas.
+    let mut total = 64;
+    let mut n = 0;
+    for bytes in item_bytes {
+        total += bytes + 1;
+        if n > 0 && total > max_bytes {
+            break;
+        }
+        n += 1;
+    }
+    n
+}
+
+/// A verdict slower than this shrinks the next chunk (the push gives a chunk
+/// up after `[push.poll] timeout_secs`, 360 s by default).
+const SLOW: Duration = Duration::from_secs(180);
+/// A verdict faster than this grows the next chunk.
+const FAST: Duration = Duration::from_secs(60);
+/// Share of rejected items above which a fast chunk does not grow the next.
+const GROW_MAX_ERROR_RATE: f64 = 0.1;
+
+/// What happened to one chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkResult {
+    pub items: usize,
+    /// Submit until the final RequestStatus answer (or giving up).
+    pub elapsed: Duration,
+    /// GS1 errors reported for the chunk's items.
+    pub errors: u32,
+    /// GS1 never gave the chunk a verdict.
+    pub unconfirmed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchSizer {
+    size: usize,
+    limits: Limits,
+}
+
+impl Default for BatchSizer {
+    fn default() -> Self {
+        BatchSizer::new(Limits::default())
+    }
+}
+
+impl BatchSizer {
+    pub fn new(limits: Limits) -> Self {
+        BatchSizer {
+            size: limits.start,
+            limits,
+        }
+    }
+
+    /// Items of the next chunk.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Adapt to the outcome of the chunk just pushed. Returns the new size
+    /// when it changed, with the reason (for the log).
+    pub fn record(&mut self, chunk: &ChunkResult) -> Option<(usize, &'static str)> {
+        let error_rate = chunk.errors as f64 / chunk.items.max(1) as f64;
+        let (size, reason) = if chunk.unconfirmed {
+            ((self.size / 2) / STEP * STEP, "no verdict from GS1")
+        } else if chunk.elapsed > SLOW {
+            (self.size - STEP, "slow verdict")
+        } else if chunk.elapsed < FAST
+            && error_rate <= GROW_MAX_ERROR_RATE
+            // A short last chunk says nothing about a bigger one.
+            && chunk.items >= self.size
+        {
+            (self.size + STEP, "fast verdict")
+        } else {
+            return None;
+        };
+        let size = size.clamp(self.limits.min, self.limits.max);
+        if size == self.size {
+            return None;
+        }
+        self.size = size;
+        Some((size, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_follows_verdict_time_and_failures() {
+        let chunk = |items, secs, errors, unconfirmed| ChunkResult {
+            items,
+            elapsed: Duration::from_secs(secs),
+            errors,
+            unconfirmed,
+        };
+        let mut sizer = BatchSizer::new(Limits::default());
+        assert_eq!(
+            sizer.record(&chunk(100, 30, 0, false)),
+            Some((125, "fast verdict"))
+        );
+        // Too many rejections, or a short last chunk: no growth.
+        assert_eq!(sizer.record(&chunk(125, 30, 40, false)), None);
+        assert_eq!(sizer.record(&chunk(60, 30, 0, false)), None);
+        assert_eq!(sizer.record(&chunk(125, 90, 0, false)), None);
+        assert_eq!(
+            sizer.record(&chunk(125, 200, 0, false)),
+            Some((100, "slow verdict"))
+        );
+        assert_eq!(
+            sizer.record(&chunk(100, 360, 0, true)),
+            Some((50, "no verdict from GS1"))
+        );
+        assert_eq!(
+            sizer.record(&chunk(50, 360, 0, true)),
+            Some((25, "no verdict from GS1"))
+        );
+        assert_eq!(sizer.record(&chunk(25, 360, 0, true)), None);
+        assert_eq!(sizer.size(), MIN);
+        for _ in 0..20 {
+            sizer.record(&chunk(sizer.size(), 10, 0, false));
+        }
+        assert_eq!(sizer.size(), MAX);
+    }
+
+    #[test]
+    fn limits_from_config_and_payload_fit() {
+        let batch = Batch {
+            size: Some(500),
+            max_size: Some(50),
+            min_size: Some(0),
+            pause_secs: Some(2.5),
+            ..Default::default()
+        };
+        let limits = limits(&batch);
+        assert_eq!((limits.start, limits.min, limits.max), (50, 1, 50));
+        assert_eq!(limits.max_payload_bytes, MAX_PAYLOAD_BYTES);
+        assert_eq!(limits.pause, Duration::from_millis(2500));
+        assert_eq!(super::limits(&Batch::default()), Limits::default());
+        let small = super::limits(&Batch {
+            size: Some(10),
+            ..Default::default()
+        });
+        assert_eq!((small.start, small.min, small.max), (10, 10, MAX));
+
+        // A pinned size stays put whatever GS1 does.
+        let mut sizer = BatchSizer::new(super::limits(&Batch {
+            min_size: Some(40),
+            max_size: Some(40),
+            ..Default::default()
+        }));
+        assert_eq!(sizer.size(), 40);
+        let slow = ChunkResult {
+            items: 40,
+            elapsed: Duration::from_secs(300),
+            errors: 0,
+            unconfirmed: true,
+        };
+        assert_eq!(sizer.record(&slow), None);
+
+        assert_eq!(fit([400, 400, 400], 1_000), 2);
+        assert_eq!(fit([400, 400, 400], 10_000), 3);
+        // An item over the limit still goes, alone.
+        assert_eq!(fit([5_000, 400], 1_000), 1);
+        assert_eq!(fit([], 1_000), 0);
+    }
+
+    #[test]
+    fn pause_is_the_only_wait_between_chunks() {
+        assert_eq!(Limits::default().pause, Duration::from_secs(8));
+        let pause = |secs| {
+            super::limits(&Batch {
+                pause_secs: Some(secs),
+                ..Default::default()
+            })
+            .pause
+        };
+        // Shorter than the default, down to none: nothing else adds to it.
+        assert_eq!(pause(1.5), Duration::from_millis(1500));
+        assert_eq!(pause(0.0), Duration::ZERO);
+        assert_eq!(pause(-1.0), Duration::from_secs(8));
+        assert!(!super::limits(&Batch {
+            pause_secs: Some(0.0),
+            ..Default::default()
+        })
+        .to_string()
+        .contains("between chunks"));
+    }
+}
@@ -0,0 +1,317 @@
+//! Checkpoint of a running firstbase push, for `push --resume`.
+//!
+//! `push_to_firstbase` records every CreateMany chunk before sending it
+//! (`SENDING`, with its UUIDs), its RequestIdentifier once GS1 took it
+//! (`SUBMITTED`) and the terminal RequestStatus body once polled (`DONE`;
+//! `FAILED` when no RequestIdentifier came back). The checkpoint is cleared
+//! once the push has logged its results and moved the accepted files, so one
+//! left behind means the process died in between (sleep, network loss, kill).
+//! `gui::resume_push_to_firstbase` continues it: DONE chunks take the
+//! recorded verdict, SUBMITTED ones are polled again, SENDING and FAILED
+//! chunks — GS1 confirmed nothing for them — are sent once more, then the
+//! items no chunk reached follow. Nothing GS1 confirmed is sent twice.
+//!
+//! One checkpoint per environment (`Settings::firstbase_label`); a new push
+//! there replaces an interrupted one.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rusqlite::{params, Connection};
+use serde_json::Value;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS push_checkpoint (
+        firstbase_env TEXT PRIMARY KEY,
+        started_at TEXT NOT NULL,
+        uuids TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS push_checkpoint_chunk (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        firstbase_env TEXT NOT NULL,
+        uuids TEXT NOT NULL,
+        state TEXT NOT NULL,
+        request_id TEXT NOT NULL DEFAULT '',
+        response TEXT NOT NULL DEFAULT '',
+        updated_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_push_checkpoint_chunk_env ON push_checkpoint_chunk(firstbase_env);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// About to be sent; no answer from GS1 recorded.
+    Sending,
+    /// GS1 returned a RequestIdentifier; no terminal status yet.
+    Submitted,
+    /// Terminal RequestStatus recorded.
+    Done,
+    /// GS1 refused the chunk without a RequestIdentifier.
+    Failed,
+}
+
+impl State {
+    fn as_str(self) -> &'static str {
+        match self {
+            State::Sending => "SENDING",
+            State::Submitted => "SUBMITTED",
+            State::Done => "DONE",
+            State::Failed => "FAILED",
+        }
+    }
+
+    /// Whether a resumed push sends a chunk in this state again: GS1 gave
+    /// no RequestIdentifier for it.
+    pub fn resend(self) -> bool {
+        matches!(self, State::Sending | State::Failed)
+    }
+
+    fn parse(s: &str) -> State {
+        match s {
+            "SUBMITTED" => State::Submitted,
+            "DONE" => State::Done,
+            "FAILED" => State::Failed,
+            _ => State::Sending,
+        }
+    }
+}
+
+/// One recorded CreateMany chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub id: i64,
+    pub uuids: Vec<String>,
+    pub state: State,
+    pub request_id: String,
+    /// Terminal RequestStatus body of a `Done` chunk.
+    pub response: Option<Value>,
+}
+
+/// An interrupted push.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub started_at: String,
+    /// Scope of the push: the UUIDs it planned.
+    pub uuids: Vec<String>,
+    /// In the order they were sent.
+    pub chunks: Vec<Chunk>,
+}
+
+pub fn ensure_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+/// Drop the checkpoint of `env`.
+pub fn clear(conn: &Connection, env: &str) -> anyhow::Result<()> {
+    ensure_schema(conn)?;
+    conn.execute(
+        "DELETE FROM push_checkpoint_chunk WHERE firstbase_env = ?1",
+        params![env],
+    )?;
+    conn.execute(
+        "DELETE FROM push_checkpoint WHERE firstbase_env = ?1",
+        params![env],
+    )?;
+    Ok(())
+}
+
+/// Start the checkpoint of a push of `uuids` to `env`, replacing any left.
+pub fn begin(conn: &Connection, env: &str, uuids: &[&str], at: &str) -> anyhow::Result<()> {
+    clear(conn, env)?;
+    conn.execute(
+        "INSERT INTO push_checkpoint (firstbase_env, started_at, uuids) VALUES (?1, ?2, ?3)",
+        params![env, at, uuids.join("\n")],
+    )?;
+    Ok(())
+}
+
+/// The interrupted push of `env`, if any.
+pub fn load(conn: &Connection, env: &str) -> anyhow::Result<Option<Checkpoint>> {
+    ensure_schema(conn)?;
+    let head = conn.query_row(
+        "SELECT started_at, uuids FROM push_checkpoint WHERE firstbase_env = ?1",
+        params![env],
+        |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)),
+    );
+    let (started_at, uuids) = match head {
+        Ok(head) => head,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let lines = |s: &str| -> Vec<String> {
+        s.lines()
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let mut stmt = conn.prepare(
+        "SELECT id, uuids, state, request_id, response FROM push_checkpoint_chunk
+         WHERE firstbase_env = ?1 ORDER BY id",
+    )?;
+    let chunks = stmt
+        .query_map(params![env], |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .into_iter()
+        .map(|(id, uuids, state, request_id, response)| Chunk {
+            id,
+            uuids: lines(&uuids),
+            state: State::parse(&state),
+            request_id,
+            response: serde_json::from_str(&response).ok(),
+        })
+        .collect();
+    Ok(Some(Checkpoint {
+        started_at,
+        uuids: lines(&uuids),
+        chunks,
+    }))
+}
+
+/// Record a chunk of `uuids` about to be sent; returns its id.
+pub fn sending(conn: &Connection, env: &str, uuids: &[&str], at: &str) -> anyhow::Result<i64> {
+    conn.execute(
+        "INSERT INTO push_checkpoint_chunk (firstbase_env, uuids, state, updated_at) VALUES (?1, ?2, ?3, ?4)",
+        params![env, uuids.join("\n"), State::Sending.as_str(), at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Chunk `id` now holds `uuids` (cut short by a 413).
+pub fn resize(conn: &Connection, id: i64, uuids: &[&str]) -> anyhow::Result<()> {
+    conn.execute(
+        "UPDATE push_checkpoint_chunk SET uuids = ?2 WHERE id = ?1",
+        params![id, uuids.join("\n")],
+    )?;
+    Ok(())
+}
+
+fn set_state(
+    conn: &Connection,
+    id: i64,
+    state: State,
+    request_id: &str,
+    response: &str,
+    at: &str,
+) -> anyhow::Result<()> {
+    conn.execute(
+        "UPDATE push_checkpoint_chunk SET state = ?2, request_id = ?3, response = ?4, updated_at = ?5 WHERE id = ?1",
+        params![id, state.as_str(), request_id, response, at],
+    )?;
+    Ok(())
+}
+
+pub fn submitted(conn: &Connection, id: i64, request_id: &str, at: &str) -> anyhow::Result<()> {
+    set_state(conn, id, State::Submitted, request_id, "", at)
+}
+
+/// Chunk `id` got the terminal RequestStatus `response`.
+pub fn done(
+    conn: &Connection,
+    id: i64,
+    request_id: &str,
+    response: &Value,
+    at: &str,
+) -> anyhow::Result<()> {
+    set_state(conn, id, State::Done, request_id, &response.to_string(), at)
+}
+
+pub fn failed(conn: &Connection, id: i64, at: &str) -> anyhow::Result<()> {
+    set_state(conn, id, State::Failed, "", "", at)
+}
+
+/// The order of a resumed push of the planned `uuids`: the items of the
+/// recorded `chunks` first, chunk by chunk, then the rest in plan order.
+/// Returns the new order as indices into `uuids` and each recorded chunk
+/// that still has items by its `(start, end)` in that order.
+pub fn replay(uuids: &[&str], chunks: &[Chunk]) -> (Vec<usize>, BTreeMap<usize, (usize, Chunk)>) {
+    let position: HashMap<&str, usize> = uuids.iter().enumerate().map(|(i, u)| (*u, i)).collect();
+    let mut placed = vec![false; uuids.len()];
+    let mut order = Vec::with_capacity(uuids.len());
+    let mut recorded = BTreeMap::new();
+    for chunk in chunks {
+        let start = order.len();
+        for uuid in &chunk.uuids {
+            if let Some(&i) = position.get(uuid.as_str()) {
+                if !placed[i] {
+                    placed[i] = true;
+                    order.push(i);
+                }
+            }
+        }
+        if order.len() > start {
+            recorded.insert(start, (order.len(), chunk.clone()));
+        }
+    }
+    order.extend((0..uuids.len()).filter(|&i| !placed[i]));
+    (order, recorded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoint_round_trip_and_replay_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        assert_eq!(load(&conn, "Test").unwrap(), None);
+
+        begin(&conn, "Test", &["u-1", "u-2", "u-3", "u-4", "u-5"], "t0").unwrap();
+        let first = sending(&conn, "Test", &["u-2", "u-1"], "t1").unwrap();
+        submitted(&conn, first, "req-1", "t2").unwrap();
+        done(
+            &conn,
+            first,
+            "req-1",
+            &serde_json::json!({"Status": "Done"}),
+            "t3",
+        )
+        .unwrap();
+        let second = sending(&conn, "Test", &["u-3", "u-4"], "t4").unwrap();
+        resize(&conn, second, &["u-3"]).unwrap();
+        let third = sending(&conn, "Test", &["u-5"], "t5").unwrap();
+        failed(&conn, third, "t6").unwrap();
+        // Another environment's push is separate.
+        begin(&conn, "Production", &["u-9"], "t5").unwrap();
+
+        let cp = load(&conn, "Test").unwrap().unwrap();
+        assert_eq!(cp.started_at, "t0");
+        assert_eq!(cp.uuids.len(), 5);
+        assert_eq!(cp.chunks.len(), 3);
+        assert_eq!(cp.chunks[0].state, State::Done);
+        assert_eq!(cp.chunks[0].request_id, "req-1");
+        assert_eq!(
+            cp.chunks[0].response,
+            Some(serde_json::json!({"Status": "Done"}))
+        );
+        assert_eq!(cp.chunks[1].state, State::Sending);
+        assert_eq!(cp.chunks[1].uuids, ["u-3"]);
+        assert_eq!(cp.chunks[2].state, State::Failed);
+        // Only the chunk GS1 took a request for is not sent again.
+        let resent: Vec<bool> = cp.chunks.iter().map(|c| c.state.resend()).collect();
+        assert_eq!(resent, [false, true, true]);
+
+        // u-1 is gone from the new plan, u-6 is new.
+        let planned = ["u-6", "u-5", "u-4", "u-3", "u-2"];
+        let (order, recorded) = replay(&planned, &cp.chunks);
+        let reordered: Vec<&str> = order.iter().map(|&i| planned[i]).collect();
+        assert_eq!(reordered, ["u-2", "u-3", "u-5", "u-6", "u-4"]);
+        assert_eq!(recorded.len(), 3);
+        assert_eq!(recorded[&0].0, 1);
+        assert_eq!(recorded[&1].0, 2);
+        assert_eq!(recorded[&1].1.id, second);
+        assert_eq!(recorded[&2].0, 3);
+        assert_eq!(recorded[&2].1.id, third);
+
+        clear(&conn, "Test").unwrap();
+        assert_eq!(load(&conn, "Test").unwrap(), None);
+        assert!(load(&conn, "Production").unwrap().is_some());
+    }
+}
@@ -0,0 +1,186 @@
+//! Live per-chunk state of a GUI push (the "Push progress" section).
+//! `push_to_firstbase` reports each CreateMany chunk — submitted, polling,
+//! accepted/rejected counts and the final RequestStatus response — into a
+//! shared `Monitor`, and waits between chunks while the user has paused the
+//! push. The monitor also carries the cancel request of the GUI job it belongs
+//! to (see `jobs`), checked by the download, convert and push steps. The CLI
+//! passes no monitor.
+
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkState {
+    Queued,
+    Submitting,
+    Polling,
+    Done,
+    /// Document-level rejection (whole chunk rejected).
+    Rejected,
+    /// No RequestIdentifier, or GS1 never processed the chunk.
+    Failed,
+}
+
+impl ChunkState {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChunkState::Queued => "queued",
+            ChunkState::Submitting => "submitting",
+            ChunkState::Polling => "polling",
+            ChunkState::Done => "done",
+            ChunkState::Rejected => "rejected",
+            ChunkState::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub items: usize,
+    pub state: ChunkState,
+    pub request_id: String,
+    /// Number of RequestStatus/Get polls so far.
+    pub polls: u32,
+    pub accepted: u32,
+    /// GS1 errors reported for the chunk.
+    pub errors: u32,
+    /// Pretty-printed final (or error) response body.
+    pub response: String,
+}
+
+impl Chunk {
+    fn queued(items: usize) -> Chunk {
+        Chunk {
+            items,
+            state: ChunkState::Queued,
+            request_id: String::new(),
+            polls: 0,
+            accepted: 0,
+            errors: 0,
+            response: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Dashboard {
+    pub env: String,
+    pub chunks: Vec<Chunk>,
+    /// Pause before the next chunk is submitted.
+    pub pause_requested: bool,
+    /// The push is currently waiting on `pause_requested`.
+    pub paused: bool,
+    pub finished: bool,
+    /// The job was cancelled; workers stop at the next check.
+    pub cancel_requested: bool,
+}
+
+impl Dashboard {
+    pub fn items(&self) -> usize {
+        self.chunks.iter().map(|c| c.items).sum()
+    }
+
+    pub fn accepted(&self) -> u32 {
+        self.chunks.iter().map(|c| c.accepted).sum()
+    }
+
+    pub fn errors(&self) -> u32 {
+        self.chunks.iter().map(|c| c.errors).sum()
+    }
+
+    /// Chunks that reached a final state.
+    pub fn completed(&self) -> usize {
+        self.chunks
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c.state,
+                    ChunkState::Done | ChunkState::Rejected | ChunkState::Failed
+                )
+            })
+            .count()
+    }
+}
+
+/// Handle shared between the GUI and the push worker.
+#[derive(Debug, Default, Clone)]
+pub struct Monitor(Arc<Mutex<Dashboard>>);
+
+impl Monitor {
+    fn with<R>(&self, f: impl FnOnce(&mut Dashboard) -> R) -> R {
+        let mut dashboard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut dashboard)
+    }
+
+    pub fn snapshot(&self) -> Dashboard {
+        self.with(|d| d.clone())
+    }
+
+    /// New push: one queued chunk per entry of `chunk_sizes`. Keeps a pause
+    /// requested before the push started.
+    pub fn start(&self, env: &str, chunk_sizes: impl Iterator<Item = usize>) {
+        self.with(|d| {
+            d.env = env.to_string();
+            d.chunks = chunk_sizes.map(Chunk::queued).collect();
+            d.paused = false;
+            d.finished = false;
+        });
+    }
+
+    /// The chunk size changed (`push_batch`): re-plan the `remaining` items
+    /// from chunk `from` on in chunks of `size`.
+    pub fn replan(&self, from: usize, remaining: usize, size: usize) {
+        self.with(|d| {
+            d.chunks.truncate(from);
+            let mut left = remaining;
+            while left > 0 {
+                let items = left.min(size);
+                d.chunks.push(Chunk::queued(items));
+                left -= items;
+            }
+        });
+    }
+
+    pub fn update(&self, chunk: usize, f: impl FnOnce(&mut Chunk)) {
+        self.with(|d| {
+            if let Some(c) = d.chunks.get_mut(chunk) {
+                f(c);
+            }
+        });
+    }
+
+    pub fn finish(&self) {
+        self.with(|d| {
+            d.finished = true;
+            d.paused = false;
+        });
+    }
+
+    pub fn set_pause(&self, pause: bool) {
+        self.with(|d| d.pause_requested = pause);
+    }
+
+    /// Ask the job to stop; also ends a pause so the worker sees it.
+    pub fn cancel(&self) {
+        self.with(|d| d.cancel_requested = true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.with(|d| d.cancel_requested)
+    }
+
+    /// Block the push worker while a pause is requested. Returns true if it
+    /// waited (the caller refreshes its token, which may have expired).
+    pub fn wait_while_paused(&self, log: &dyn Fn(&str)) -> bool {
+        if !self.with(|d| d.pause_requested) {
+            return false;
+        }
+        log("[Push] Paused — press Resume to submit the next chunk");
+        self.with(|d| d.paused = true);
+        while self.with(|d| d.pause_requested && !d.cancel_requested) {
+            std::thread::sleep(std::time::Duration::from_millis(250));
+        }
+        self.with(|d| d.paused = false);
+        log("[Push] Resumed");
+        true
+    }
+}
@@ -0,0 +1,237 @@
+//! Named firstbase environments of the CLI pushes (`--env <name>`).
+//!
+//! `test` and `production` are built in: GS1's two endpoints and the logins
+//! of `login` / the GUI credential screen. `[push.environments.<name>]` in
+//! config.toml overrides their base URL, credentials or GLNs, or adds another
+//! environment (a GS1 staging endpoint, a second account). The name is the
+//! `firstbase_env` of the push log, session and retry queue rows (`Test` /
+//! `Production` for the built-ins, as the GUI writes them), so ACCEPTED state
+//! is tracked per environment.
+//!
+//! Without `--env`, FIRSTBASE_ENV picks the environment, else `test`. Each
+//! value comes from the profile, else the FIRSTBASE_* variables, the keychain
+//! (`firstbase:<name>`, then the shared login) and `[provider]`.
+
+use anyhow::Result;
+
+use crate::config::{Config, PushEnvironment};
+use crate::credentials::{self, Credential};
+use crate::gui::{FirstbaseEnv, FirstbaseProfile, Settings};
+
+/// The environment a CLI push goes to.
+#[derive(Clone)]
+pub struct Target {
+    /// `firstbase_env` of its push log rows.
+    pub label: String,
+    /// Test or Production class: RequestStatus polling, log folders and the
+    /// confirmations production asks for.
+    pub env: FirstbaseEnv,
+    pub api_base: String,
+    /// Product API base URL (`readback`); empty when none is configured.
+    pub product_api_base: String,
+    /// DataSource GLN.
+    pub gln: String,
+    /// Recipient GLN; empty when none is configured.
+    pub publish_gln: String,
+    /// Login of the profile, when it sets one.
+    credential: Credential,
+    /// Keychain entry of the login (`firstbase:<scope>`).
+    keychain_scope: String,
+}
+
+impl Target {
+    /// Settings of a push to this environment without its login (dry runs).
+    pub fn settings(&self) -> Settings {
+        Settings {
+            publish_to_gln: self.publish_gln.clone(),
+            provider_gln: self.gln.clone(),
+            firstbase_env: self.env.clone(),
+            firstbase_profile: Some(FirstbaseProfile {
+                label: self.label.clone(),
+                api_base: self.api_base.clone(),
+                product_api_base: self.product_api_base.clone(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// `settings` with the login: the profile's, else FIRSTBASE_EMAIL /
+    /// FIRSTBASE_PASSWORD, else the keychain.
+    pub fn login_settings(&self) -> Result<Settings> {
+        let credential = if self.credential.is_complete() {
+            self.credential.clone()
+        } else {
+            credentials::resolve_env(credentials::Kind::Firstbase, &self.keychain_scope)
+        };
+        if !credential.is_complete() {
+            anyhow::bail!(
+                "No firstbase login for {}: set FIRSTBASE_EMAIL and FIRSTBASE_PASSWORD, run `login` or add email / password to [push.environments.{}]",
+                self.label,
+                self.keychain_scope
+            );
+        }
+        Ok(Settings {
+            firstbase_email: credential.id,
+            firstbase_password: credential.secret,
+            ..self.settings()
+        })
+    }
+}
+
+/// The name given with `--env`, else FIRSTBASE_ENV.
+pub fn selected(args: &[String]) -> Option<String> {
+    args.iter()
+        .position(|a| a == "--env")
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+        .or_else(|| std::env::var("FIRSTBASE_ENV").ok())
+        .filter(|name| !name.is_empty())
+}
+
+fn builtin(name: &str) -> Option<FirstbaseEnv> {
+    match name.to_ascii_lowercase().as_str() {
+        "test" => Some(FirstbaseEnv::Test),
+        "production" | "prod" => Some(FirstbaseEnv::Production),
+        _ => None,
+    }
+}
+
+/// Environment `name` (None = test) with its GLNs; the login is looked up
+/// by `Target::login_settings`.
+pub fn resolve(config: &Config, name: Option<&str>) -> Result<Target> {
+    let name = name.unwrap_or("test");
+    let class = builtin(name);
+    let found = config.push.environments.iter().find(|(key, _)| {
+        key.eq_ignore_ascii_case(name) || (class.is_some() && builtin(key) == class)
+    });
+    let fallback = PushEnvironment::default();
+    let (label, profile) = match (&class, found) {
+        (Some(env), found) => (env.label().to_string(), found.map_or(&fallback, |(_, p)| p)),
+        (None, Some((key, p))) => (key.clone(), p),
+        (None, None) => {
+            let known: Vec<&str> = ["test", "production"]
+                .into_iter()
+                .chain(
+                    config
+                        .push
+                        .environments
+                        .keys()
+                        .map(String::as_str)
+                        .filter(|k| builtin(k).is_none()),
+                )
+                .collect();
+            anyhow::bail!(
+                "Unknown firstbase environment '{}' (known: {}); add [push.environments.{}] to config.toml",
+                name,
+                known.join(", "),
+                name
+            );
+        }
+    };
+    let env = match (&class, profile.production) {
+        (Some(env), _) => env.clone(),
+        (None, Some(true)) => FirstbaseEnv::Production,
+        (None, _) => FirstbaseEnv::Test,
+    };
+    let api_base = match (&profile.api_base, &class) {
+        (Some(url), _) => url.trim_end_matches('/').to_string(),
+        (None, Some(env)) => env.api_base().to_string(),
+        (None, None) => {
+            anyhow::bail!("[push.environments.{}] needs api_base", label)
+        }
+    };
+    // A profile with its own api_base is another endpoint: the Product API
+    // of the built-in would not hold its items.
+    let product_api_base = match (&profile.product_api_base, &profile.api_base, &class) {
+        (Some(url), _, _) => url.trim_end_matches('/').to_string(),
+        (None, None, Some(env)) => env.product_api_base().to_string(),
+        (None, _, _) => String::new(),
+    };
+    let keychain_scope = match &class {
+        Some(env) => env.key().to_string(),
+        None => label.clone(),
+    };
+    let publish_gln = profile
+        .publish_gln
+        .clone()
+        .or_else(|| std::env::var("FIRSTBASE_PUBLISH_GLN").ok())
+        .filter(|g| !g.is_empty())
+        .unwrap_or_else(|| config.provider.publish_gln.clone());
+    Ok(Target {
+        label,
+        env,
+        api_base,
+        product_api_base,
+        gln: profile
+            .gln
+            .clone()
+            .unwrap_or_else(|| config.provider.gln.clone()),
+        publish_gln,
+        credential: Credential {
+            id: profile.email.clone().unwrap_or_default(),
+            secret: profile.password.clone().unwrap_or_default(),
+        },
+        keychain_scope,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environments_resolve_from_profiles_and_builtins() {
+        let mut config = crate::config::test_config();
+        config.provider.gln = "7612345000015".to_string();
+        config.provider.publish_gln = "7612345000022".to_string();
+        config.push = toml::from_str(
+            r#"
+            [environments.staging]
+            api_base = "https://staging.example.ch/"
+            email = "ops@example.ch"
+            password = "secret"
+            publish_gln = "7612345000039"
+
+            [environments.production]
+            gln = "7612345000046"
+            "#,
+        )
+        .unwrap();
+
+        let staging = resolve(&config, Some("staging")).unwrap();
+        assert_eq!(staging.label, "staging");
+        assert_eq!(staging.keychain_scope, "staging");
+        assert!(staging.env == FirstbaseEnv::Test);
+        assert_eq!(staging.api_base, "https://staging.example.ch");
+        assert_eq!(staging.product_api_base, "");
+        assert_eq!(staging.gln, "7612345000015");
+        assert_eq!(staging.publish_gln, "7612345000039");
+        // The profile's own login; no keychain lookup.
+        let settings = staging.login_settings().unwrap();
+        assert_eq!(settings.firstbase_email, "ops@example.ch");
+        assert_eq!(settings.firstbase_label(), "staging");
+        assert_eq!(settings.firstbase_api_base(), "https://staging.example.ch");
+
+        // Built-in, refined by its profile; aliases and case fold.
+        let prod = resolve(&config, Some("PROD")).unwrap();
+        assert_eq!(
+            (prod.label.as_str(), prod.keychain_scope.as_str()),
+            ("Production", "production")
+        );
+        assert_eq!(prod.api_base, FirstbaseEnv::Production.api_base());
+        assert_eq!(
+            prod.product_api_base,
+            FirstbaseEnv::Production.product_api_base()
+        );
+        assert_eq!(prod.gln, "7612345000046");
+
+        let test = resolve(&config, None).unwrap();
+        assert_eq!(test.label, "Test");
+        assert_eq!(test.api_base, FirstbaseEnv::Test.api_base());
+
+        let Err(err) = resolve(&config, Some("acceptance")) else {
+            panic!("acceptance is not configured");
+        };
+        assert!(err.to_string().contains("known: test, production, staging"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Push history export (`export --push-log`).
+//! Dumps every `push_log` row — all columns, plus the push session it belongs
+//! to and the GS1 error explanations recorded for that GTIN in the session
+//! (`push_error`: code, attribute, description) — as CSV or JSON, so
+//! compliance teams can archive the submission evidence outside the app.
+//! With a shared PostgreSQL store the rows come from there (`storage.rs`).
+
+use std::path::Path;
+
+use anyhow::Context;
+use rusqlite::Connection;
+use serde::Serialize;
+
+#[derive(Debug, Default, Serialize)]
+pub struct PushLogRow {
+    pub id: i64,
+    pub uuid: String,
+    pub gtin: String,
+    pub pushed_at: String,
+    pub request_id: String,
+    pub status: String,
+    pub error_code: String,
+    pub error_msg: String,
+    pub publish_gln: String,
+    pub firstbase_env: String,
+    /// `push_session.id` of the push (matched on `session_ts = pushed_at`), 0 if unknown.
+    pub session_id: i64,
+    /// `code [attribute]: description` per `push_error` row, joined with " | ".
+    pub errors: String,
+}
+
+const HEADER: &str = "id,uuid,gtin,pushed_at,request_id,status,error_code,error_msg,publish_gln,firstbase_env,session_id,errors";
+
+fn table_exists(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type='table' AND name=?1",
+        [table],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+/// All `push_log` rows, oldest first. The session/error join is skipped on a
+/// DB that never saw a GUI push (no `push_session`/`push_error` tables yet),
+/// and `firstbase_env` is empty on legacy DBs without that column.
+pub fn push_log_rows(conn: &Connection) -> anyhow::Result<Vec<PushLogRow>> {
+    let joined = table_exists(conn, "push_session") && table_exists(conn, "push_error");
+    let has_env: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('push_log') WHERE name='firstbase_env'",
+            [],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|n| n > 0)
+        .unwrap_or(false);
+    let env = if has_env { "p.firstbase_env" } else { "''" };
+    let sql = if joined {
+        format!(
+            "SELECT p.id, p.uuid, p.gtin, p.pushed_at, COALESCE(p.request_id,''), p.status,
+                COALESCE(p.error_code,''), COALESCE(p.error_msg,''), COALESCE(p.publish_gln,''),
+                {env}, COALESCE(s.id, 0),
+                COALESCE((SELECT GROUP_CONCAT(
+                            e.error_code ||
+                            CASE WHEN e.attribute_name != '' THEN ' [' || e.attribute_name || ']' ELSE '' END ||
+                            ': ' || e.error_description, ' | ')
+                          FROM push_error e
+                          WHERE e.session_id = s.id AND e.gtin = p.gtin AND p.gtin != ''), '')
+         FROM push_log p
+         LEFT JOIN push_session s ON s.session_ts = p.pushed_at
+         ORDER BY p.id"
+        )
+    } else {
+        format!(
+            "SELECT p.id, p.uuid, p.gtin, p.pushed_at, COALESCE(p.request_id,''), p.status,
+                COALESCE(p.error_code,''), COALESCE(p.error_msg,''), COALESCE(p.publish_gln,''),
+                {env}, 0, ''
+         FROM push_log p ORDER BY p.id"
+        )
+    };
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(PushLogRow {
+                id: r.get(0)?,
+                uuid: r.get(1)?,
+                gtin: r.get(2)?,
+                pushed_at: r.get(3)?,
+                request_id: r.get(4)?,
+                status: r.get(5)?,
+                error_code: r.get(6)?,
+                error_msg: r.get(7)?,
+                publish_gln: r.get(8)?,
+                firstbase_env: r.get(9)?,
+                session_id: r.get(10)?,
+                errors: r.get(11)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+fn csv_field(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Write the push history `rows` to `out_path` as CSV, or JSON when `json`.
+pub fn write_push_log(rows: &[PushLogRow], out_path: &Path, json: bool) -> anyhow::Result<()> {
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if json {
+        crate::write_json(out_path, rows)?;
+    } else {
+        let mut csv = format!("{}\r\n", HEADER);
+        for r in rows {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{}\r\n",
+                r.id,
+                csv_field(&r.uuid),
+                csv_field(&r.gtin),
+                csv_field(&r.pushed_at),
+                csv_field(&r.request_id),
+                csv_field(&r.status),
+                csv_field(&r.error_code),
+                csv_field(&r.error_msg),
+                csv_field(&r.publish_gln),
+                csv_field(&r.firstbase_env),
+                r.session_id,
+                csv_field(&r.errors)
+            ));
+        }
+        std::fs::write(out_path, csv)
+            .with_context(|| format!("Failed to write {}", out_path.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Advisory lock against concurrent pushes to one firstbase environment.
+//!
+//! Two pushes at once (two users on a shared data directory, GUI + CLI, a
+//! cron `check` overlapping a manual run) interleave their push_log rows,
+//! replace each other's checkpoint (`push_checkpoint.rs`) and submit the same
+//! devices twice. `push_to_firstbase`, `resume_push_to_firstbase` and
+//! `withdraw::run` therefore hold `db/push-<env>.lock` for their whole run:
+//! an OS file lock (`File::try_lock`), released when the holder exits —
+//! also when it crashes, so there is no stale lock to clean up. The file
+//! keeps who holds it for the refusal message.
+//!
+//! Keyed by `Settings::firstbase_label`: a Test push does not block a
+//! Production one. The lock covers one data directory; pushes from other
+//! machines into a shared PostgreSQL push_log (`[storage]`) are not seen.
+
+use std::fs::{File, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// Held for as long as it lives.
+#[derive(Debug)]
+pub struct PushLock {
+    file: File,
+}
+
+impl Drop for PushLock {
+    fn drop(&mut self) {
+        // Only what is left behind for the next holder to overwrite; closing
+        // the file releases the lock.
+        let _ = self.file.set_len(0);
+    }
+}
+
+fn lock_path(dir: &Path, env: &str) -> PathBuf {
+    let key: String = env
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("push-{}.lock", key))
+}
+
+/// Take the push lock of `env` for `what` ("push", "withdraw" …), or fail
+/// with who holds it.
+pub fn acquire(env: &str, what: &str) -> anyhow::Result<PushLock> {
+    let db_dir = crate::download::app_data_dir().join("db");
+    std::fs::create_dir_all(&db_dir)?;
+    acquire_in(&db_dir, env, what)
+}
+
+fn acquire_in(dir: &Path, env: &str, what: &str) -> anyhow::Result<PushLock> {
+    let path = lock_path(dir, env);
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let mut holder = String::new();
+            let _ = file.read_to_string(&mut holder);
+            let holder = holder.trim();
+            anyhow::bail!(
+                "another push to {} is in progress ({}) — wait for it to finish; {} holds the lock until then",
+                env,
+                if holder.is_empty() { "holder unknown" } else { holder },
+                path.display()
+            );
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(anyhow::anyhow!("cannot lock {}: {}", path.display(), e));
+        }
+    }
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let holder = format!(
+        "{} by pid {}{} since {}",
+        what,
+        std::process::id(),
+        if user.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", user)
+        },
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(holder.as_bytes())?;
+    file.flush()?;
+    Ok(PushLock { file })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_push_to_the_same_environment_is_refused() {
+        let dir = std::env::temp_dir().join(format!("e2fb-push-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let held = acquire_in(&dir, "Test", "push").unwrap();
+        let err = acquire_in(&dir, "Test", "withdraw")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("another push to Test is in progress"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains(&format!("push by pid {}", std::process::id())),
+            "{}",
+            err
+        );
+        // Other environments are independent.
+        let other = acquire_in(&dir, "Production", "push").unwrap();
+
+        drop(held);
+        acquire_in(&dir, "Test", "push").unwrap();
+        drop(other);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,115 @@
+//! NDJSON lines that fail to parse are quarantined rather than lost: each one
+//! goes into `firstbase_json/quarantine_<stem>_<time>.ndjson` as
+//! `{"source_file", "source_line", "error", "raw"}`, and the conversion goes
+//! on. `raw` is the line exactly as read, so the records can be inspected,
+//! fixed and fed in again (`jq -r .raw quarantine_*.ndjson > fixed.ndjson`).
+//! The file is only created once a line fails.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub source_file: String,
+    pub source_line: u32,
+    pub error: String,
+    pub raw: String,
+}
+
+/// Quarantine file of one NDJSON input.
+pub struct Quarantine {
+    source_file: String,
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    count: usize,
+}
+
+impl Quarantine {
+    pub fn new(output_dir: &Path, input_path: &Path) -> Self {
+        let path = output_dir.join(format!(
+            "quarantine_{}_{}.ndjson",
+            crate::ndjson_input::stem(input_path),
+            chrono::Local::now().format("%H.%M_%d.%m.%Y")
+        ));
+        Quarantine {
+            source_file: input_path.display().to_string(),
+            path,
+            writer: None,
+            count: 0,
+        }
+    }
+
+    /// Quarantine line `line_num` (`raw`) that failed with `error`.
+    pub fn add(&mut self, line_num: u32, raw: &str, error: &str) -> anyhow::Result<()> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let file = File::create(&self.path)
+                    .with_context(|| format!("Failed to create {}", self.path.display()))?;
+                self.writer.insert(BufWriter::new(file))
+            }
+        };
+        let entry = Entry {
+            source_file: self.source_file.clone(),
+            source_line: line_num,
+            error: error.to_string(),
+            raw: raw.to_string(),
+        };
+        serde_json::to_writer(&mut *writer, &entry)?;
+        writer.write_all(b"\n")?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Flush the file; its path and line count, `None` when nothing failed.
+    pub fn finish(self) -> anyhow::Result<Option<(PathBuf, usize)>> {
+        match self.writer {
+            Some(mut writer) => {
+                writer.flush()?;
+                Ok(Some((self.path, self.count)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_lines_are_kept_verbatim() {
+        let dir = std::env::temp_dir().join(format!("quarantine_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = Path::new("ndjson/eudamed_10k.ndjson.gz");
+
+        assert!(Quarantine::new(&dir, input).finish().unwrap().is_none());
+
+        let mut quarantine = Quarantine::new(&dir, input);
+        quarantine
+            .add(7, r#"{"uuid":"a", "#, "EOF while parsing")
+            .unwrap();
+        quarantine.add(9, "not json", "expected value").unwrap();
+        let (path, count) = quarantine.finish().unwrap().unwrap();
+        assert_eq!(count, 2);
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("quarantine_eudamed_10k_"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<Entry> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries[0].raw, r#"{"uuid":"a", "#);
+        assert_eq!(entries[0].source_line, 7);
+        assert_eq!(entries[1].error, "expected value");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,700 @@
+//! Readback of accepted pushes (`readback` subcommand, `push --readback`).
+//!
+//! ACCEPTED only says GS1 validated the CreateMany item, not that every field
+//! landed as sent. The readback fetches the item as firstbase stores it from
+//! the Product API — `SdpCatalogueItemSync/All` lists the own items of the
+//! DataSource GLN (keys `<gln>_<gtin>_<target market>_<recipient>_live`),
+//! `SdpCatalogueItem/Many` returns their TradeItems — and diffs it against the
+//! TradeItem of the document as pushed (`payload_archive`, else the copy in
+//! `processed/`). `diff` walks the sent fields only, so what firstbase adds is
+//! ignored; a sent field is
+//! - `Dropped` when it is missing or empty in the stored item,
+//! - `Normalized` when it holds the same value written differently (type,
+//!   case, whitespace, number or date format),
+//! - `Changed` otherwise.
+//!
+//! Each device gets a `readback` row (MATCH / NORMALIZED / DIFFERS /
+//! NOT_FOUND) with the differences, next to its `push_log` row.
+
+use std::time::{Duration, Instant};
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+
+use crate::gui::{self, Settings};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS readback (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        push_log_id INTEGER NOT NULL,
+        uuid TEXT NOT NULL,
+        gtin TEXT NOT NULL DEFAULT '',
+        firstbase_env TEXT NOT NULL DEFAULT '',
+        checked_at TEXT NOT NULL,
+        status TEXT NOT NULL,
+        dropped INTEGER NOT NULL DEFAULT 0,
+        normalized INTEGER NOT NULL DEFAULT 0,
+        changed INTEGER NOT NULL DEFAULT 0,
+        details TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX IF NOT EXISTS idx_readback_uuid ON readback(uuid);
+";
+
+/// Longest value shown in a difference.
+const MAX_VALUE: usize = 80;
+
+/// `SdpCatalogueItem/Many` ids per request (the API takes up to 1000).
+const MANY_CHUNK: usize = 100;
+
+/// Gap between Sync / Many requests: GS1 allows 15 a minute.
+const PACE: Duration = Duration::from_secs(4);
+
+pub fn ensure_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Dropped,
+    Normalized,
+    Changed,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Dropped => "dropped",
+            Kind::Normalized => "normalized",
+            Kind::Changed => "changed",
+        }
+    }
+
+    /// Weight when matching array elements: a normalized value is closer
+    /// than a changed one.
+    fn cost(self) -> usize {
+        match self {
+            Kind::Normalized => 1,
+            Kind::Dropped | Kind::Changed => 2,
+        }
+    }
+}
+
+/// One sent field that did not come back as sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// `TradeItemDescriptionModule/…/DescriptionShort[1]/Value`; array
+    /// indices are those of the sent document.
+    pub path: String,
+    pub kind: Kind,
+    pub sent: String,
+    /// Empty for a dropped field.
+    pub received: String,
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            Kind::Dropped => write!(f, "dropped {}: {}", self.path, self.sent),
+            kind => write!(
+                f,
+                "{} {}: {} → {}",
+                kind.as_str(),
+                self.path,
+                self.sent,
+                self.received
+            ),
+        }
+    }
+}
+
+fn is_empty(v: &Value) -> bool {
+    match v {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.iter().all(is_empty),
+        Value::Object(o) => o.values().all(is_empty),
+        _ => false,
+    }
+}
+
+fn show(v: &Value) -> String {
+    let text = match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.chars().count() > MAX_VALUE {
+        format!("{}…", text.chars().take(MAX_VALUE).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn instant(s: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|d| d.naive_utc())
+        .ok()
+        .or_else(|| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").ok())
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Two scalars holding the same value in different notation.
+fn same_value(sent: &Value, received: &Value) -> bool {
+    let (a, b) = (show(sent), show(received));
+    let fold = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    a == b
+        || fold(&a) == fold(&b)
+        || matches!((a.trim().parse::<f64>(), b.trim().parse::<f64>()), (Ok(x), Ok(y)) if x == y)
+        || matches!((instant(a.trim()), instant(b.trim())), (Some(x), Some(y)) if x == y)
+}
+
+fn walk(path: &str, sent: &Value, received: Option<&Value>, out: &mut Vec<Difference>) {
+    if is_empty(sent) {
+        return;
+    }
+    let Some(received) = received.filter(|r| !is_empty(r)) else {
+        out.push(Difference {
+            path: path.to_string(),
+            kind: Kind::Dropped,
+            sent: show(sent),
+            received: String::new(),
+        });
+        return;
+    };
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}/{}", path, key)
+        }
+    };
+    match (sent, received) {
+        (Value::Object(s), Value::Object(r)) => {
+            for (key, value) in s {
+                walk(&join(key), value, r.get(key), out);
+            }
+        }
+        (Value::Array(s), Value::Array(r)) => elements(path, s, r, out),
+        // A list stored as its bare element.
+        (Value::Array(s), r) => elements(path, s, std::slice::from_ref(r), out),
+        (s, r) if s == r => {}
+        (s, r) if !(s.is_object() || r.is_object() || r.is_array()) && same_value(s, r) => out
+            .push(Difference {
+                path: path.to_string(),
+                kind: Kind::Normalized,
+                sent: show(s),
+                received: show(r),
+            }),
+        (s, r) => out.push(Difference {
+            path: path.to_string(),
+            kind: Kind::Changed,
+            sent: show(s),
+            received: show(r),
+        }),
+    }
+}
+
+/// Match each sent element to the closest received one not yet taken, as
+/// firstbase does not keep the order of repeated elements.
+fn elements(path: &str, sent: &[Value], received: &[Value], out: &mut Vec<Difference>) {
+    let mut taken = vec![false; received.len()];
+    for (i, s) in sent.iter().enumerate() {
+        if is_empty(s) {
+            continue;
+        }
+        let path = format!("{}[{}]", path, i);
+        let best = (0..received.len())
+            .filter(|&j| !taken[j])
+            .map(|j| {
+                let mut found = Vec::new();
+                walk(&path, s, Some(&received[j]), &mut found);
+                let cost: usize = found.iter().map(|d| d.kind.cost()).sum();
+                (cost, j, found)
+            })
+            .min_by_key(|(cost, j, _)| (*cost, *j));
+        match best {
+            Some((_, j, found)) => {
+                taken[j] = true;
+                out.extend(found);
+            }
+            None => walk(&path, s, None, out),
+        }
+    }
+}
+
+/// The sent fields of `sent` that `received` dropped, normalized or changed.
+pub fn diff(sent: &Value, received: &Value) -> Vec<Difference> {
+    let mut out = Vec::new();
+    walk("", sent, Some(received), &mut out);
+    out
+}
+
+/// Verdict on one device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Match,
+    /// Only normalized fields.
+    Normalized,
+    Differs,
+    NotFound,
+}
+
+impl Status {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Match => "MATCH",
+            Status::Normalized => "NORMALIZED",
+            Status::Differs => "DIFFERS",
+            Status::NotFound => "NOT_FOUND",
+        }
+    }
+
+    fn of(differences: &[Difference]) -> Status {
+        if differences.is_empty() {
+            Status::Match
+        } else if differences.iter().all(|d| d.kind == Kind::Normalized) {
+            Status::Normalized
+        } else {
+            Status::Differs
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub matched: u32,
+    pub normalized: u32,
+    pub differs: u32,
+    pub not_found: u32,
+    /// Devices without an ACCEPTED push or its document.
+    pub skipped: u32,
+}
+
+impl Outcome {
+    /// Some device did not land as sent.
+    pub fn failed(&self) -> bool {
+        self.differs > 0 || self.not_found > 0
+    }
+}
+
+/// An ACCEPTED push to read back.
+struct Pushed {
+    push_log_id: i64,
+    uuid: String,
+    gtin: String,
+    /// `DraftItem/TradeItem` as sent.
+    trade_item: Value,
+}
+
+/// The ACCEPTED `push_log` rows of `env` to read back: the latest of each of
+/// `ids` (UUID or GTIN) pushed at or after `since`, else all of the latest
+/// push.
+fn accepted(
+    conn: &Connection,
+    env: &str,
+    ids: &[String],
+    since: Option<&str>,
+    log: &dyn Fn(&str),
+) -> anyhow::Result<Vec<(i64, String, String)>> {
+    let mut rows: Vec<(i64, String, String)> = Vec::new();
+    if ids.is_empty() {
+        let mut stmt = conn.prepare(
+            "SELECT id, uuid, gtin FROM push_log
+             WHERE firstbase_env = ?1 AND status = 'ACCEPTED' AND pushed_at = (
+                 SELECT MAX(pushed_at) FROM push_log
+                 WHERE firstbase_env = ?1 AND status = 'ACCEPTED' AND pushed_at >= ?2)
+             ORDER BY id",
+        )?;
+        rows = stmt
+            .query_map(params![env, since.unwrap_or("")], |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+    }
+    for id in ids {
+        let row = conn.query_row(
+            "SELECT id, uuid, gtin FROM push_log
+             WHERE (uuid = ?1 OR gtin = ?1) AND firstbase_env = ?2
+               AND status = 'ACCEPTED' AND pushed_at >= ?3
+             ORDER BY id DESC LIMIT 1",
+            params![id, env, since.unwrap_or("")],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        );
+        match row {
+            Ok(row) => rows.push(row),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                log(&format!("  SKIP {}: no ACCEPTED push on {}", id, env))
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    rows.retain(|(_, uuid, _)| seen.insert(uuid.clone()));
+    Ok(rows)
+}
+
+/// The TradeItem pushed with `push_log_id`: its archived payload, else the
+/// document in `processed/`.
+fn sent_trade_item(conn: &Connection, push_log_id: i64, uuid: &str) -> Option<Value> {
+    let archived = crate::payload_archive::load(conn, push_log_id)
+        .ok()
+        .and_then(|payloads| payloads.into_iter().find(|p| p.kind == "firstbase"))
+        .and_then(|p| serde_json::from_slice::<Value>(&p.bytes).ok());
+    let doc = archived.or_else(|| {
+        let path = crate::download::app_data_dir()
+            .join("firstbase_json")
+            .join("processed")
+            .join(format!("{}.json", uuid));
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+    })?;
+    doc.pointer("/DraftItem/TradeItem").cloned()
+}
+
+/// Product API id of `gtin` among the Sync keys `(id, private key)`: live,
+/// in `target_market`, preferably published to `recipient`.
+fn item_id<'a>(
+    keys: &'a [(String, String)],
+    gtin: &str,
+    target_market: &str,
+    recipient: &str,
+) -> Option<&'a str> {
+    keys.iter()
+        .filter_map(|(id, key)| {
+            let parts: Vec<&str> = key.split('_').collect();
+            (parts.get(1) == Some(&gtin)).then_some((id, parts))
+        })
+        .min_by_key(|(_, parts)| {
+            (
+                parts.get(2) != Some(&target_market),
+                parts.last() != Some(&"live"),
+                parts.get(3) != Some(&recipient),
+            )
+        })
+        .map(|(id, _)| id.as_str())
+}
+
+/// Read back the ACCEPTED pushes of `ids` (UUID or GTIN; none = the latest
+/// push) in the environment of `settings`, pushed at or after `since`.
+pub fn run(
+    settings: &Settings,
+    ids: &[String],
+    since: Option<&str>,
+    log: &dyn Fn(&str),
+) -> anyhow::Result<Outcome> {
+    let api_base = settings.firstbase_product_api_base();
+    let env_label = settings.firstbase_label();
+    if api_base.is_empty() {
+        anyhow::bail!(
+            "No Product API for {}: add product_api_base to [push.environments.{}]",
+            env_label,
+            env_label
+        );
+    }
+    let db_dir = crate::download::app_data_dir().join("db");
+    std::fs::create_dir_all(&db_dir)?;
+    let conn = crate::version_db::open_db(&db_dir.join("version_tracking.db"))?;
+    gui::ensure_push_log_schema(&conn);
+    ensure_schema(&conn)?;
+
+    let mut outcome = Outcome::default();
+    let mut pushed = Vec::new();
+    for (push_log_id, uuid, gtin) in accepted(&conn, env_label, ids, since, log)? {
+        match sent_trade_item(&conn, push_log_id, &uuid) {
+            Some(trade_item) => pushed.push(Pushed {
+                push_log_id,
+                uuid,
+                gtin,
+                trade_item,
+            }),
+            None => {
+                outcome.skipped += 1;
+                log(&format!("  SKIP {} {}: no pushed document", gtin, uuid));
+            }
+        }
+    }
+    if pushed.is_empty() {
+        log(&format!("[Readback] Nothing to read back on {}", env_label));
+        return Ok(outcome);
+    }
+
+    let http_agent = crate::http::agent_for(api_base);
+    let http_post = |url: &str, auth: &str, body: &str| -> anyhow::Result<String> {
+        let mut req = http_agent
+            .post(url)
+            .header("Content-Type", "application/json");
+        if !auth.is_empty() {
+            req = req.header("Authorization", &format!("bearer {}", auth));
+        }
+        let mut resp = req.send(body.as_bytes())?;
+        let status = resp.status();
+        let resp_body = resp.body_mut().read_to_string()?;
+        if status.as_u16() >= 400 {
+            Err(anyhow::anyhow!("http {}: {}", status, resp_body))
+        } else {
+            Ok(resp_body)
+        }
+    };
+    log(&format!(
+        "[Readback] Reading {} device(s) back from {} ({})",
+        pushed.len(),
+        env_label,
+        api_base
+    ));
+    let token = gui::firstbase_token(
+        &http_post,
+        api_base,
+        &settings.firstbase_email,
+        &settings.firstbase_password,
+        &settings.provider_gln,
+    )?;
+    let last = std::cell::Cell::new(None::<Instant>);
+    let paced_post = |endpoint: &str, body: &Value| -> anyhow::Result<Value> {
+        let mut attempt = 1;
+        loop {
+            if let Some(wait) = last.get().and_then(|t| PACE.checked_sub(t.elapsed())) {
+                std::thread::sleep(wait);
+            }
+            last.set(Some(Instant::now()));
+            match http_post(
+                &format!("{}/{}", api_base, endpoint),
+                &token,
+                &body.to_string(),
+            ) {
+                Err(e) if e.to_string().contains("429") && attempt < 3 => {
+                    log(&format!(
+                        "  429 rate limited — waiting 60s (attempt {}/3)",
+                        attempt
+                    ));
+                    std::thread::sleep(Duration::from_secs(60));
+                    attempt += 1;
+                }
+                result => return Ok(serde_json::from_str(&result?)?),
+            }
+        }
+    };
+
+    // Keys of all own items of the DataSource, page by page.
+    let mut keys: Vec<(String, String)> = Vec::new();
+    let mut offset = String::new();
+    loop {
+        let mut request = json!({ "BatchSize": 100000, "DataSource": settings.provider_gln });
+        if !offset.is_empty() {
+            request["Offset"] = json!(offset);
+        }
+        let page = paced_post("SdpCatalogueItemSync/All", &request)?;
+        let items = page
+            .get("Items")
+            .and_then(|v| v.as_array())
+            .map_or(&[][..], |a| a.as_slice());
+        keys.extend(items.iter().filter_map(|k| {
+            Some((
+                k.get("Id")?.as_str()?.to_string(),
+                k.get("PrivateKey")?.as_str()?.to_string(),
+            ))
+        }));
+        offset = page
+            .get("NextOffset")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        if offset.is_empty() || items.is_empty() {
+            break;
+        }
+    }
+    log(&format!(
+        "[Readback] {} own item(s) of DataSource {}",
+        keys.len(),
+        settings.provider_gln
+    ));
+
+    let ids: Vec<Option<String>> = pushed
+        .iter()
+        .map(|p| {
+            let target_market = p
+                .trade_item
+                .pointer("/TargetMarket/TargetMarketCountryCode/Value")
+                .and_then(|v| v.as_str())
+                .unwrap_or("097");
+            item_id(&keys, &p.gtin, target_market, &settings.publish_to_gln).map(str::to_string)
+        })
+        .collect();
+    let wanted: Vec<&String> = ids.iter().flatten().collect();
+    let mut stored: std::collections::HashMap<String, Value> = std::collections::HashMap::new();
+    for chunk in wanted.chunks(MANY_CHUNK) {
+        let response = paced_post("SdpCatalogueItem/Many", &json!({ "Ids": chunk }))?;
+        for item in response
+            .get("Items")
+            .and_then(|v| v.as_array())
+            .map_or(&[][..], |a| a.as_slice())
+        {
+            if let (Some(id), Some(trade_item)) = (
+                item.pointer("/CatalogueItemInfo/Id")
+                    .and_then(|v| v.as_str()),
+                item.get("TradeItem"),
+            ) {
+                stored.insert(id.to_string(), trade_item.clone());
+            }
+        }
+    }
+
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    for (p, id) in pushed.iter().zip(&ids) {
+        let received = id.as_ref().and_then(|id| stored.get(id));
+        let differences = received.map_or_else(Vec::new, |r| diff(&p.trade_item, r));
+        let status = match received {
+            None => Status::NotFound,
+            Some(_) => Status::of(&differences),
+        };
+        let count = |kind| differences.iter().filter(|d| d.kind == kind).count();
+        let (dropped, normalized, changed) = (
+            count(Kind::Dropped),
+            count(Kind::Normalized),
+            count(Kind::Changed),
+        );
+        match status {
+            Status::Match => outcome.matched += 1,
+            Status::Normalized => outcome.normalized += 1,
+            Status::Differs => outcome.differs += 1,
+            Status::NotFound => outcome.not_found += 1,
+        }
+        match status {
+            Status::Match => log(&format!("  MATCH {} {}", p.gtin, p.uuid)),
+            Status::NotFound => log(&format!(
+                "  NOT_FOUND {} {}: not among the own items of {}",
+                p.gtin, p.uuid, settings.provider_gln
+            )),
+            _ => log(&format!(
+                "  {} {} {}: {} dropped, {} normalized, {} changed",
+                status.as_str(),
+                p.gtin,
+                p.uuid,
+                dropped,
+                normalized,
+                changed
+            )),
+        }
+        for d in &differences {
+            log(&format!("    {}", d));
+        }
+        let details: Vec<String> = differences.iter().map(|d| d.to_string()).collect();
+        if let Err(e) = conn.execute(
+            "INSERT INTO readback (push_log_id,uuid,gtin,firstbase_env,checked_at,status,dropped,normalized,changed,details) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10)",
+            params![
+                p.push_log_id,
+                p.uuid,
+                p.gtin,
+                env_label,
+                now,
+                status.as_str(),
+                dropped,
+                normalized,
+                changed,
+                details.join("\n")
+            ],
+        ) {
+            log(&format!("[Readback] readback write failed for {}: {}", p.uuid, e));
+        }
+    }
+    log(&format!(
+        "[Readback] Done: {} match, {} normalized, {} differ, {} not found, {} skipped",
+        outcome.matched, outcome.normalized, outcome.differs, outcome.not_found, outcome.skipped
+    ));
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_dropped_normalized_and_changed_fields() {
+        let sent = json!({
+            "Gtin": "07640000000010",
+            "IsBrandBankPublication": false,
+            "TradeItemDescriptionModule": { "TradeItemDescriptionInformation": {
+                "DescriptionShort": [
+                    { "LanguageCode": "de", "Value": "Katheter" },
+                    { "LanguageCode": "en", "Value": "Catheter  12 Fr" }
+                ],
+                "AdditionalTradeItemDescription": [{ "LanguageCode": "en", "Value": "Long" }]
+            }},
+            "HealthcareItemInformationModule": { "HealthcareItemInformation": {
+                "DoesTradeItemContainLatex": "FALSE"
+            }},
+            "TradeItemSynchronisationDates": {
+                "LastChangedDateTime": "2026-07-01T10:00:00",
+                "DiscontinuedDateTime": ""
+            },
+            "NetContent": [{ "Value": "1.50", "MeasurementUnitCode": "H87" }],
+            "TargetSector": ["UDI_REGISTRY"]
+        });
+        // Stored reordered, with fields firstbase adds; the long description
+        // and the target sector are gone.
+        let received = json!({
+            "Gtin": "07640000000010",
+            "IsBrandBankPublication": false,
+            "TradeItemDescriptionModule": { "TradeItemDescriptionInformation": {
+                "DescriptionShort": [
+                    { "LanguageCode": "en", "Value": "catheter 12 Fr" },
+                    { "LanguageCode": "de", "Value": "Katheter" }
+                ]
+            }},
+            "HealthcareItemInformationModule": { "HealthcareItemInformation": {
+                "DoesTradeItemContainLatex": false
+            }},
+            "TradeItemSynchronisationDates": {
+                "LastChangedDateTime": "2026-07-01T10:00:00Z",
+                "EffectiveDateTime": "2026-07-01T10:00:00Z"
+            },
+            "NetContent": [{ "Value": 1.5, "MeasurementUnitCode": "EA" }],
+            "TargetSector": []
+        });
+        let found: Vec<String> = diff(&sent, &received)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "normalized HealthcareItemInformationModule/HealthcareItemInformation/DoesTradeItemContainLatex: FALSE → false",
+                "changed NetContent[0]/MeasurementUnitCode: H87 → EA",
+                "normalized NetContent[0]/Value: 1.50 → 1.5",
+                "dropped TargetSector: [\"UDI_REGISTRY\"]",
+                "dropped TradeItemDescriptionModule/TradeItemDescriptionInformation/AdditionalTradeItemDescription: [{\"LanguageCode\":\"en\",\"Value\":\"Long\"}]",
+                "normalized TradeItemDescriptionModule/TradeItemDescriptionInformation/DescriptionShort[1]/Value: Catheter  12 Fr → catheter 12 Fr",
+                "normalized TradeItemSynchronisationDates/LastChangedDateTime: 2026-07-01T10:00:00 → 2026-07-01T10:00:00Z",
+            ]
+        );
+        assert_eq!(Status::of(&diff(&sent, &received)), Status::Differs);
+        assert_eq!(Status::of(&diff(&sent, &sent)), Status::Match);
+
+        let keys = [
+            (
+                "a".to_string(),
+                "7612345000480_07640000000010_756_7612345000527_live".to_string(),
+            ),
+            (
+                "b".to_string(),
+                "7612345000480_07640000000010_097_7612345000527_live".to_string(),
+            ),
+            (
+                "c".to_string(),
+                "7612345000480_07640000000010_097__live".to_string(),
+            ),
+        ];
+        assert_eq!(
+            item_id(&keys, "07640000000010", "097", "7612345000527"),
+            Some("b")
+        );
+        assert_eq!(item_id(&keys, "07640000000010", "756", ""), Some("a"));
+        assert_eq!(item_id(&keys, "07640000000027", "097", ""), None);
+    }
+}
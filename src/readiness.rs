@@ -0,0 +1,302 @@
+//! Registry readiness per GTIN and firstbase environment (`status` command and
+//! the GUI "Registry readiness" section). Each converted device is placed on
+//! the path converted → validated → pushed → accepted → published (or
+//! rejected), combining the documents in `firstbase_json/` (+ `processed/`),
+//! the offline validation findings of `report.rs` and the latest `push_log`
+//! row per environment. The result is persisted to `gtin_readiness` so other
+//! tools can read it without recomputing.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use rayon::prelude::*;
+use rusqlite::{params, Connection};
+
+use crate::report::{findings, firstbase_docs, str_at};
+
+/// Environments always reported, even before the first push to them.
+pub const ENVIRONMENTS: [&str; 2] = ["Test", "Production"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Converted, but the offline checks report findings.
+    Converted,
+    /// Converted and free of validation findings.
+    Validated,
+    /// Pushed without a final GS1 status.
+    Pushed,
+    Rejected,
+    Accepted,
+    /// Accepted and published (AddMany) to the recipient GLN.
+    Published,
+    /// Withdrawn from its target market (`withdraw`).
+    Withdrawn,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 7] = [
+        Stage::Converted,
+        Stage::Validated,
+        Stage::Pushed,
+        Stage::Rejected,
+        Stage::Accepted,
+        Stage::Published,
+        Stage::Withdrawn,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Stage::Converted => "converted",
+            Stage::Validated => "validated",
+            Stage::Pushed => "pushed",
+            Stage::Rejected => "rejected",
+            Stage::Accepted => "accepted",
+            Stage::Published => "published",
+            Stage::Withdrawn => "withdrawn",
+        }
+    }
+}
+
+/// Readiness of one device in one environment.
+#[derive(Debug, Clone)]
+pub struct Readiness {
+    pub gtin: String,
+    pub uuid: String,
+    pub env: String,
+    pub stage: Stage,
+    /// Validation findings, "; "-joined.
+    pub findings: String,
+    /// The document awaits a push (still in `firstbase_json/`).
+    pub pending: bool,
+    /// Latest push in this environment: time and status ("" if never pushed).
+    pub pushed_at: String,
+    pub push_status: String,
+}
+
+struct Doc {
+    uuid: String,
+    gtin: String,
+    findings: Vec<&'static str>,
+    pending: bool,
+}
+
+struct LastPush {
+    status: String,
+    pushed_at: String,
+    publish_gln: String,
+}
+
+/// Final statuses of a device that was accepted at some point.
+const LIVE: [&str; 3] = ["ACCEPTED", "UNPUBLISHED", "WITHDRAWN"];
+
+fn stage_of(doc: &Doc, push: Option<&LastPush>) -> Stage {
+    let converted = if doc.findings.is_empty() {
+        Stage::Validated
+    } else {
+        Stage::Converted
+    };
+    match push {
+        None => converted,
+        // Accepted files move to processed/; a pending one was re-converted since.
+        Some(p) if LIVE.contains(&p.status.as_str()) && doc.pending => converted,
+        Some(p) if p.status == "ACCEPTED" && !p.publish_gln.is_empty() => Stage::Published,
+        // Publication deleted: Live, but not published any more.
+        Some(p) if p.status == "ACCEPTED" || p.status == "UNPUBLISHED" => Stage::Accepted,
+        Some(p) if p.status == "WITHDRAWN" => Stage::Withdrawn,
+        Some(p) if p.status == "REJECTED" => Stage::Rejected,
+        Some(_) => Stage::Pushed,
+    }
+}
+
+/// Latest push per (uuid, environment). Rows from before the environment was
+/// logged count as "(unknown)".
+fn last_pushes(conn: &Connection) -> anyhow::Result<HashMap<(String, String), LastPush>> {
+    let mut stmt = conn.prepare(
+        "SELECT uuid, COALESCE(firstbase_env, ''), status, pushed_at, COALESCE(publish_gln, '')
+         FROM push_log
+         -- A failed withdrawal leaves the device where it was.
+         WHERE status NOT IN ('WITHDRAW_FAILED', 'UNPUBLISH_FAILED')
+         ORDER BY id",
+    )?;
+    let mut pushes = HashMap::new();
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, String>(0)?,
+            r.get::<_, String>(1)?,
+            LastPush {
+                status: r.get(2)?,
+                pushed_at: r.get(3)?,
+                publish_gln: r.get(4)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (uuid, env, push) = row?;
+        let env = if env.is_empty() {
+            "(unknown)".to_string()
+        } else {
+            env
+        };
+        pushes.insert((uuid, env), push);
+    }
+    Ok(pushes)
+}
+
+/// Compute the readiness of every converted device in every environment.
+pub fn compute(conn: &Connection, firstbase_dir: &Path) -> anyhow::Result<Vec<Readiness>> {
+    let mut docs: Vec<Doc> = firstbase_docs(firstbase_dir, true)
+        .par_iter()
+        .filter_map(|p| {
+            let doc: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(p).ok()?).ok()?;
+            Some(Doc {
+                uuid: p.file_stem()?.to_string_lossy().to_string(),
+                gtin: str_at(&doc, "/DraftItem/TradeItem/Gtin").to_string(),
+                findings: findings(&doc),
+                pending: p.parent() == Some(firstbase_dir),
+            })
+        })
+        .collect();
+    // A device pending again after a push exists in both dirs: the pending copy counts.
+    docs.sort_by(|a, b| (&a.uuid, !a.pending).cmp(&(&b.uuid, !b.pending)));
+    docs.dedup_by(|b, a| a.uuid == b.uuid);
+
+    let pushes = last_pushes(conn)?;
+    let mut envs: Vec<String> = ENVIRONMENTS.iter().map(|e| e.to_string()).collect();
+    for (_, env) in pushes.keys() {
+        if !envs.contains(env) {
+            envs.push(env.clone());
+        }
+    }
+
+    let mut rows = Vec::new();
+    for doc in &docs {
+        for env in &envs {
+            let push = pushes.get(&(doc.uuid.clone(), env.clone()));
+            rows.push(Readiness {
+                gtin: doc.gtin.clone(),
+                uuid: doc.uuid.clone(),
+                env: env.clone(),
+                stage: stage_of(doc, push),
+                findings: doc.findings.join("; "),
+                pending: doc.pending,
+                pushed_at: push.map(|p| p.pushed_at.clone()).unwrap_or_default(),
+                push_status: push.map(|p| p.status.clone()).unwrap_or_default(),
+            });
+        }
+    }
+    Ok(rows)
+}
+
+/// Replace the persisted `gtin_readiness` table with `rows`.
+pub fn persist(conn: &Connection, rows: &[Readiness]) -> anyhow::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS gtin_readiness (
+            uuid TEXT NOT NULL,
+            env TEXT NOT NULL,
+            gtin TEXT NOT NULL DEFAULT '',
+            stage TEXT NOT NULL,
+            findings TEXT NOT NULL DEFAULT '',
+            pending INTEGER NOT NULL DEFAULT 0,
+            pushed_at TEXT NOT NULL DEFAULT '',
+            push_status TEXT NOT NULL DEFAULT '',
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (uuid, env)
+        );
+        CREATE INDEX IF NOT EXISTS idx_gtin_readiness_gtin ON gtin_readiness(gtin);
+        DELETE FROM gtin_readiness;",
+    )?;
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    for r in rows {
+        tx.execute(
+            "INSERT INTO gtin_readiness
+                (uuid, env, gtin, stage, findings, pending, pushed_at, push_status, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                r.uuid,
+                r.env,
+                r.gtin,
+                r.stage.label(),
+                r.findings,
+                r.pending,
+                r.pushed_at,
+                r.push_status,
+                now
+            ],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Compute and persist; returns the rows.
+pub fn refresh(conn: &Connection, firstbase_dir: &Path) -> anyhow::Result<Vec<Readiness>> {
+    let rows = compute(conn, firstbase_dir)?;
+    persist(conn, &rows)?;
+    Ok(rows)
+}
+
+/// Device count per environment and stage.
+pub fn counts(rows: &[Readiness]) -> BTreeMap<String, BTreeMap<Stage, usize>> {
+    let mut counts: BTreeMap<String, BTreeMap<Stage, usize>> = BTreeMap::new();
+    for r in rows {
+        *counts
+            .entry(r.env.clone())
+            .or_default()
+            .entry(r.stage)
+            .or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_follows_last_push_and_pending_copy() {
+        let doc = |pending, findings: Vec<&'static str>| Doc {
+            uuid: "u".to_string(),
+            gtin: "1".to_string(),
+            findings,
+            pending,
+        };
+        let push = |status: &str, gln: &str| LastPush {
+            status: status.to_string(),
+            pushed_at: String::new(),
+            publish_gln: gln.to_string(),
+        };
+        assert_eq!(stage_of(&doc(true, vec!["x"]), None), Stage::Converted);
+        assert_eq!(stage_of(&doc(true, vec![]), None), Stage::Validated);
+        let published = push("ACCEPTED", "7612345000527");
+        assert_eq!(
+            stage_of(&doc(false, vec![]), Some(&published)),
+            Stage::Published
+        );
+        assert_eq!(
+            stage_of(&doc(true, vec![]), Some(&published)),
+            Stage::Validated
+        );
+        let accepted = push("ACCEPTED", "");
+        assert_eq!(
+            stage_of(&doc(false, vec![]), Some(&accepted)),
+            Stage::Accepted
+        );
+        let rejected = push("REJECTED", "");
+        assert_eq!(
+            stage_of(&doc(true, vec![]), Some(&rejected)),
+            Stage::Rejected
+        );
+        let unpublished = push("UNPUBLISHED", "7612345000527");
+        assert_eq!(
+            stage_of(&doc(false, vec![]), Some(&unpublished)),
+            Stage::Accepted
+        );
+        let withdrawn = push("WITHDRAWN", "7612345000527");
+        assert_eq!(
+            stage_of(&doc(false, vec![]), Some(&withdrawn)),
+            Stage::Withdrawn
+        );
+    }
+}
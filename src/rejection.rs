@@ -0,0 +1,222 @@
+//! Rejections in a firstbase RequestStatus response, and the field-level hint
+//! for each.
+//!
+//! `parse` reads the `Gs1ResponseMessage/GS1Response` array of a terminal
+//! RequestStatus: `TransactionResponse` items were accepted; the
+//! `AttributeException`s under `TransactionException` and `GS1Exception`
+//! (CommandException → DocumentException) are per-item rejections; a
+//! `GS1Error` directly on a `GS1Exception` fails the whole batch document
+//! (e.g. G361 from one schema-invalid item). `hint` turns a rejection into
+//! "attribute X on GTIN Y violates rule Z: …", explained from the triage
+//! knowledge base where the code is known; `push_to_firstbase` logs it under
+//! the `REJECTED` line and stores it in `push_error.hint` and
+//! `push_log.error_msg`.
+
+use serde_json::Value;
+
+/// Longest GS1 error description kept.
+const MAX_DESCRIPTION: usize = 200;
+
+/// One GS1 error of one item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    /// DocumentIdentifier (`Draft_<uuid>`) of the rejected item.
+    pub document: String,
+    pub gtin: String,
+    pub code: String,
+    /// `AttributeName`; `(document-level)` / `(batch transport)` for errors
+    /// of a whole batch attributed to each of its items.
+    pub attribute: String,
+    pub description: String,
+}
+
+impl Rejection {
+    /// The field-level hint of this rejection.
+    pub fn hint(&self) -> String {
+        let rule = match crate::triage::explain(&self.code) {
+            Some(text) => format!("{}: {}", self.code, text),
+            None if self.description.is_empty() => self.code.clone(),
+            None => format!("{}: {}", self.code, self.description),
+        };
+        match self.attribute.as_str() {
+            "" | "(document-level)" => format!(
+                "the batch document of GTIN {} violates rule {}",
+                self.gtin, rule
+            ),
+            "(batch transport)" => format!(
+                "the batch of GTIN {} was not processed by GS1 ({})",
+                self.gtin, rule
+            ),
+            attribute => format!(
+                "attribute {} on GTIN {} violates rule {}",
+                attribute, self.gtin, rule
+            ),
+        }
+    }
+}
+
+/// What a terminal RequestStatus says about a batch.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Response {
+    /// `TransactionResponse` entries.
+    pub accepted: u32,
+    /// Their TransactionIdentifiers.
+    pub accepted_ids: Vec<String>,
+    pub rejections: Vec<Rejection>,
+    /// `(code, description)` of errors failing the whole batch document.
+    pub document_errors: Vec<(String, String)>,
+}
+
+fn array<'a>(v: &'a Value, key: &str) -> &'a [Value] {
+    v.get(key)
+        .and_then(|v| v.as_array())
+        .map_or(&[], |a| a.as_slice())
+}
+
+fn text<'a>(v: &'a Value, key: &str) -> &'a str {
+    v.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn description(err: &Value) -> String {
+    text(err, "ErrorDescription")
+        .chars()
+        .take(MAX_DESCRIPTION)
+        .collect()
+}
+
+/// The AttributeExceptions of a CommandException list; `fallback` stands in
+/// for a missing DocumentIdentifier.
+fn attribute_exceptions(commands: &[Value], fallback: &str, out: &mut Vec<Rejection>) {
+    for de in commands
+        .iter()
+        .flat_map(|ce| array(ce, "DocumentException"))
+    {
+        let document = de
+            .pointer("/DocumentIdentifier/Value")
+            .and_then(|v| v.as_str())
+            .unwrap_or(fallback);
+        for ae in array(de, "AttributeException") {
+            for err in array(ae, "GS1Error") {
+                out.push(Rejection {
+                    document: document.to_string(),
+                    gtin: text(ae, "Gtin").to_string(),
+                    code: text(err, "ErrorCode").to_string(),
+                    attribute: text(ae, "AttributeName").to_string(),
+                    description: description(err),
+                });
+            }
+        }
+    }
+}
+
+/// Parse the `Gs1ResponseMessage/GS1Response` array of a RequestStatus.
+pub fn parse(gs1_response: &Value) -> Response {
+    let mut response = Response::default();
+    for r in gs1_response.as_array().map_or(&[][..], |a| a.as_slice()) {
+        for t in array(r, "TransactionResponse") {
+            response.accepted += 1;
+            if let Some(ident) = t
+                .pointer("/TransactionIdentifier/Value")
+                .and_then(|v| v.as_str())
+            {
+                response.accepted_ids.push(ident.to_string());
+            }
+        }
+        for exc in array(r, "TransactionException") {
+            let ident = exc
+                .pointer("/TransactionIdentifier/Value")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            attribute_exceptions(
+                array(exc, "CommandException"),
+                ident,
+                &mut response.rejections,
+            );
+        }
+        for exc in array(r, "GS1Exception") {
+            for err in array(exc, "GS1Error") {
+                response
+                    .document_errors
+                    .push((text(err, "ErrorCode").to_string(), description(err)));
+            }
+            attribute_exceptions(array(exc, "CommandException"), "", &mut response.rejections);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exceptions_become_rejections_with_field_hints() {
+        let gs1: Value = serde_json::from_str(
+            r#"[
+              {"TransactionResponse": [{"TransactionIdentifier": {"Value": "Draft_u-1"}}]},
+              {"TransactionException": [{
+                "TransactionIdentifier": {"Value": "Draft_u-2"},
+                "CommandException": [{"DocumentException": [{
+                  "AttributeException": [{
+                    "Gtin": "07640000000027",
+                    "AttributeName": "descriptionShort",
+                    "GS1Error": [
+                      {"ErrorCode": "097.078", "ErrorDescription": "languageCode repeated"},
+                      {"ErrorCode": "097.999", "ErrorDescription": "Something new"}
+                    ]
+                  }]
+                }]}]
+              }]},
+              {"GS1Exception": [{
+                "GS1Error": [{"ErrorCode": "G361", "ErrorDescription": "XSD validation failed"}],
+                "CommandException": [{"DocumentException": [{
+                  "DocumentIdentifier": {"Value": "Draft_u-3"},
+                  "AttributeException": [{
+                    "Gtin": "07640000000034",
+                    "AttributeName": "discontinuedDateTime",
+                    "GS1Error": [{"ErrorCode": "G485"}]
+                  }]
+                }]}]
+              }]}
+            ]"#,
+        )
+        .unwrap();
+        let response = parse(&gs1);
+        assert_eq!(response.accepted, 1);
+        assert_eq!(response.accepted_ids, ["Draft_u-1"]);
+        assert_eq!(
+            response.document_errors,
+            [("G361".to_string(), "XSD validation failed".to_string())]
+        );
+        let r = &response.rejections;
+        assert_eq!(r.len(), 3);
+        // No DocumentIdentifier: the transaction's identifier stands in.
+        assert_eq!(r[0].document, "Draft_u-2");
+        assert_eq!(
+            r[0].hint(),
+            "attribute descriptionShort on GTIN 07640000000027 violates rule 097.078: \
+             At most one description per languageCode."
+        );
+        // Unknown code: GS1's own description.
+        assert_eq!(
+            r[1].hint(),
+            "attribute descriptionShort on GTIN 07640000000027 violates rule 097.999: Something new"
+        );
+        assert_eq!(r[2].document, "Draft_u-3");
+        assert!(r[2]
+            .hint()
+            .starts_with("attribute discontinuedDateTime on GTIN 07640000000034 violates rule G485: Protected field"));
+
+        let batch = Rejection {
+            attribute: "(document-level)".to_string(),
+            code: "G361".to_string(),
+            description: "XSD validation failed".to_string(),
+            ..r[0].clone()
+        };
+        assert_eq!(
+            batch.hint(),
+            "the batch document of GTIN 07640000000027 violates rule G361: XSD validation failed"
+        );
+        assert_eq!(parse(&Value::Null), Response::default());
+    }
+}
@@ -0,0 +1,676 @@
+//! PDF batch summary report (`report` subcommand).
+//! Summarises the converted firstbase documents (device counts, validation
+//! findings, codes the mappings passed through unmapped) together with the
+//! last push session from the version DB, plus where the documents came from
+//! (provenance sidecars: input files and download times), and renders it as a plain text PDF
+//! suitable for attaching to a regulatory submission or customer deliverable.
+//! The PDF is written by hand (built-in Helvetica, WinAnsi) — no extra crate.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+/// Aggregated facts for one report.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub source: String,
+    pub devices: usize,
+    pub unreadable: usize,
+    pub by_regulation: BTreeMap<String, usize>,
+    pub by_risk_class: BTreeMap<String, usize>,
+    pub by_status: BTreeMap<String, usize>,
+    pub findings: BTreeMap<&'static str, usize>,
+    /// (attribute, value) → occurrences of a code that is not a GS1 value.
+    pub unmapped: BTreeMap<(&'static str, String), usize>,
+    /// Source input (NDJSON file, or directory of per-device JSON) → documents.
+    pub sources: BTreeMap<String, usize>,
+    /// Documents without a provenance sidecar (converted by an older build).
+    pub untraced: usize,
+    /// Oldest and newest download time of the traced source records.
+    pub downloaded: Option<(String, String)>,
+    pub push: Option<PushSummary>,
+}
+
+#[derive(Debug)]
+pub struct PushSummary {
+    pub session_id: i64,
+    pub at: String,
+    pub env: String,
+    pub accepted: i64,
+    pub rejected: i64,
+    /// Most frequent GS1 error codes of that session: (code, count).
+    pub top_errors: Vec<(String, i64)>,
+}
+
+#[derive(Default)]
+struct DocFacts {
+    regulation: String,
+    risk_class: String,
+    status: String,
+    findings: Vec<&'static str>,
+    unmapped: Vec<(&'static str, String)>,
+    provenance: Option<crate::provenance::Provenance>,
+}
+
+const GS1_STATUS_CODES: &[&str] = &[
+    "ON_MARKET",
+    "NO_LONGER_PLACED_ON_MARKET",
+    "NOT_INTENDED_FOR_EU_MARKET",
+];
+
+const GS1_PI_CODES: &[&str] = &[
+    "SERIAL_NUMBER",
+    "BATCH_NUMBER",
+    "MANUFACTURING_DATE",
+    "EXPIRATION_DATE",
+    "SOFTWARE_IDENTIFICATION",
+];
+
+pub(crate) fn str_at<'a>(v: &'a serde_json::Value, pointer: &str) -> &'a str {
+    v.pointer(pointer).and_then(|x| x.as_str()).unwrap_or("")
+}
+
+pub(crate) fn array_at<'a>(v: &'a serde_json::Value, pointer: &str) -> &'a [serde_json::Value] {
+    v.pointer(pointer)
+        .and_then(|x| x.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+/// The base-unit TradeItem: for a packaging hierarchy the top-level item is the
+/// outermost package, and device attributes live on the innermost child.
+pub(crate) fn base_unit(doc: &serde_json::Value) -> &serde_json::Value {
+    let mut item = doc
+        .pointer("/DraftItem")
+        .unwrap_or(&serde_json::Value::Null);
+    while item.pointer("/TradeItem/IsTradeItemABaseUnit") != Some(&serde_json::Value::Bool(true)) {
+        match item.pointer("/CatalogueItemChildItemLink/0/CatalogueItem") {
+            Some(child) => item = child,
+            None => break,
+        }
+    }
+    item.get("TradeItem").unwrap_or(&serde_json::Value::Null)
+}
+
+/// The validation findings of one document (empty = passes the offline checks).
+pub(crate) fn findings(doc: &serde_json::Value) -> Vec<&'static str> {
+    inspect(doc).findings
+}
+
+/// Attribute the fix of a finding belongs to (override editor filter).
+fn finding_field(finding: &str) -> &'static str {
+    match finding {
+        "No GlobalModelNumber" => "GlobalModelInformation",
+        "No manufacturer (EMA) contact" => "TradeItemContactInformation",
+        "No TradeItemDescription" => "TradeItemDescription",
+        "No risk class" => "AdditionalTradeItemClassification",
+        _ => "Gtin",
+    }
+}
+
+/// Findings and code-list misses of one document as (rule, offending value,
+/// attribute to fix) — the GUI validation panel groups them by rule.
+pub(crate) fn issues(doc: &serde_json::Value) -> Vec<(String, String, &'static str)> {
+    let facts = inspect(doc);
+    let mut issues: Vec<(String, String, &'static str)> = facts
+        .findings
+        .iter()
+        .map(|f| (f.to_string(), String::new(), finding_field(f)))
+        .collect();
+    for (field, value) in facts.unmapped {
+        let attribute = if field.starts_with("Risk class") {
+            "AdditionalTradeItemClassification"
+        } else {
+            field
+        };
+        issues.push((
+            format!("{} not in the GS1 code list", field),
+            value,
+            attribute,
+        ));
+    }
+    issues
+}
+
+fn inspect(doc: &serde_json::Value) -> DocFacts {
+    // GTIN, model and contacts are checked on the pushed (top-level) item,
+    // everything device-specific on the base unit.
+    let top = doc
+        .pointer("/DraftItem/TradeItem")
+        .unwrap_or(&serde_json::Value::Null);
+    let ti = base_unit(doc);
+    let mut facts = DocFacts {
+        regulation: str_at(
+            ti,
+            "/RegulatedTradeItemModule/RegulatoryInformation/0/RegulatoryAct",
+        )
+        .to_string(),
+        ..Default::default()
+    };
+
+    let gtin = str_at(top, "/Gtin");
+    if gtin.is_empty() || !gtin.chars().all(|c| c.is_ascii_digit()) {
+        facts
+            .findings
+            .push("GTIN missing or not numeric (not pushable)");
+    }
+    if str_at(top, "/GlobalModelInformation/0/GlobalModelNumber").is_empty() {
+        facts.findings.push("No GlobalModelNumber");
+    }
+    if !array_at(top, "/TradeItemContactInformation")
+        .iter()
+        .any(|c| str_at(c, "/ContactTypeCode/Value") == "EMA")
+    {
+        facts.findings.push("No manufacturer (EMA) contact");
+    }
+    if array_at(
+        ti,
+        "/TradeItemDescriptionModule/TradeItemDescriptionInformation/TradeItemDescription",
+    )
+    .is_empty()
+    {
+        facts.findings.push("No TradeItemDescription");
+    }
+
+    let value = risk_class(ti);
+    if !value.is_empty() {
+        facts.risk_class = value.to_string();
+        if !(value.starts_with("EU_CLASS_") || value.starts_with("IVDD_") || value == "AIMDD") {
+            facts
+                .unmapped
+                .push(("Risk class (system 76/85)", value.to_string()));
+        }
+    }
+    if facts.risk_class.is_empty() {
+        facts.findings.push("No risk class");
+    }
+
+    let mdi = "/MedicalDeviceTradeItemModule/MedicalDeviceInformation";
+    let status = str_at(ti, &format!("{}/EUMedicalDeviceStatusCode/Value", mdi));
+    facts.status = status.to_string();
+    if !status.is_empty() && !GS1_STATUS_CODES.contains(&status) {
+        facts
+            .unmapped
+            .push(("EUMedicalDeviceStatusCode", status.to_string()));
+    }
+    for pi in array_at(ti, &format!("{}/UDIProductionIdentifierTypeCode", mdi)) {
+        let code = str_at(pi, "/Value");
+        if !GS1_PI_CODES.contains(&code) {
+            facts
+                .unmapped
+                .push(("UDIProductionIdentifierTypeCode", code.to_string()));
+        }
+    }
+    for shc in array_at(
+        ti,
+        "/HealthcareItemInformationModule/HealthcareItemInformation/ClinicalStorageHandlingInformation",
+    ) {
+        let code = str_at(shc, "/ClinicalStorageHandlingTypeCode/Value");
+        let is_gs1 = code.len() == 5
+            && code.starts_with("SHC")
+            && code[3..].chars().all(|c| c.is_ascii_digit());
+        if !is_gs1 {
+            facts
+                .unmapped
+                .push(("ClinicalStorageHandlingTypeCode", code.to_string()));
+        }
+    }
+    facts
+}
+
+/// Risk class of a trade item: classification system 76 (MDR/IVDR) or 85
+/// (legacy MDD/AIMDD/IVDD devices).
+pub(crate) fn risk_class(trade_item: &serde_json::Value) -> &str {
+    array_at(
+        trade_item,
+        "/GdsnTradeItemClassification/AdditionalTradeItemClassification",
+    )
+    .iter()
+    .find(|c| {
+        matches!(
+            str_at(c, "/AdditionalTradeItemClassificationSystemCode/Value"),
+            "76" | "85"
+        )
+    })
+    .map(|c| {
+        str_at(
+            c,
+            "/AdditionalTradeItemClassificationValue/0/AdditionalTradeItemClassificationCodeValue",
+        )
+    })
+    .unwrap_or("")
+}
+
+/// Per-UUID firstbase documents in `firstbase_dir` (plus `processed/` when
+/// `include_processed`). Shared by the report and the export commands.
+pub(crate) fn firstbase_docs(firstbase_dir: &Path, include_processed: bool) -> Vec<PathBuf> {
+    let mut dirs = vec![firstbase_dir.to_path_buf()];
+    if include_processed {
+        dirs.push(firstbase_dir.join("processed"));
+    }
+    dirs.iter()
+        .filter_map(|d| std::fs::read_dir(d).ok())
+        .flat_map(|it| it.filter_map(|e| e.ok()).map(|e| e.path()))
+        .filter(|p| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            // Batch arrays (firstbase_<stem>_<date>.json) duplicate the per-UUID files.
+            name.ends_with(".json") && !name.starts_with("firstbase_")
+        })
+        .collect()
+}
+
+/// Scan the firstbase documents in `firstbase_dir` (plus `processed/` when
+/// `include_processed`) and, if `conn` is given, the latest push session.
+pub fn collect_summary(
+    firstbase_dir: &Path,
+    include_processed: bool,
+    conn: Option<&rusqlite::Connection>,
+) -> Summary {
+    let mut dirs = vec![firstbase_dir.to_path_buf()];
+    if include_processed {
+        dirs.push(firstbase_dir.join("processed"));
+    }
+    let files = firstbase_docs(firstbase_dir, include_processed);
+
+    let facts: Vec<Option<DocFacts>> = files
+        .par_iter()
+        .map(|p| {
+            let content = std::fs::read_to_string(p).ok()?;
+            let doc: serde_json::Value = serde_json::from_str(&content).ok()?;
+            let mut facts = inspect(&doc);
+            let uuid = p.file_stem()?.to_string_lossy();
+            facts.provenance = crate::provenance::read(firstbase_dir, &uuid);
+            Some(facts)
+        })
+        .collect();
+
+    let mut summary = Summary {
+        source: dirs
+            .iter()
+            .map(|d| d.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" + "),
+        ..Default::default()
+    };
+    for f in facts {
+        let Some(f) = f else {
+            summary.unreadable += 1;
+            continue;
+        };
+        summary.devices += 1;
+        let label = |s: String| {
+            if s.is_empty() {
+                "(none)".to_string()
+            } else {
+                s
+            }
+        };
+        *summary
+            .by_regulation
+            .entry(label(f.regulation))
+            .or_insert(0) += 1;
+        *summary
+            .by_risk_class
+            .entry(label(f.risk_class))
+            .or_insert(0) += 1;
+        *summary.by_status.entry(label(f.status)).or_insert(0) += 1;
+        for finding in f.findings {
+            *summary.findings.entry(finding).or_insert(0) += 1;
+        }
+        for key in f.unmapped {
+            *summary.unmapped.entry(key).or_insert(0) += 1;
+        }
+        match f.provenance {
+            Some(p) => {
+                let source = if p.source_line.is_some() {
+                    p.source_file
+                } else {
+                    Path::new(&p.source_file)
+                        .parent()
+                        .map(|d| d.display().to_string())
+                        .unwrap_or(p.source_file)
+                };
+                *summary.sources.entry(source).or_insert(0) += 1;
+                if !p.downloaded_at.is_empty() {
+                    let range = summary
+                        .downloaded
+                        .get_or_insert_with(|| (p.downloaded_at.clone(), p.downloaded_at.clone()));
+                    if p.downloaded_at < range.0 {
+                        range.0 = p.downloaded_at.clone();
+                    }
+                    if p.downloaded_at > range.1 {
+                        range.1 = p.downloaded_at;
+                    }
+                }
+            }
+            None => summary.untraced += 1,
+        }
+    }
+
+    summary.push = conn.and_then(last_push_summary);
+    summary
+}
+
+fn last_push_summary(conn: &rusqlite::Connection) -> Option<PushSummary> {
+    let (session_id, at, env, accepted, rejected) = conn
+        .query_row(
+            "SELECT id, session_ts, COALESCE(firstbase_env,''), total_accepted, total_rejected \
+             FROM push_session ORDER BY id DESC LIMIT 1",
+            [],
+            |r| {
+                Ok((
+                    r.get::<_, i64>(0)?,
+                    r.get::<_, String>(1)?,
+                    r.get::<_, String>(2)?,
+                    r.get::<_, i64>(3)?,
+                    r.get::<_, i64>(4)?,
+                ))
+            },
+        )
+        .ok()?;
+    let top_errors = conn
+        .prepare(
+            "SELECT error_code, COUNT(*) AS n FROM push_error WHERE session_id = ?1 \
+             GROUP BY error_code ORDER BY n DESC LIMIT 10",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([session_id], |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .unwrap_or_default();
+    Some(PushSummary {
+        session_id,
+        at,
+        env,
+        accepted,
+        rejected,
+        top_errors,
+    })
+}
+
+enum Line {
+    Title(String),
+    Heading(String),
+    Text(String),
+    Blank,
+}
+
+fn counts_lines<K: std::fmt::Display>(lines: &mut Vec<Line>, map: &BTreeMap<K, usize>) {
+    let mut sorted: Vec<_> = map.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1));
+    for (k, n) in sorted {
+        lines.push(Line::Text(format!("{:>8}   {}", n, k)));
+    }
+}
+
+fn summary_lines(summary: &Summary) -> Vec<Line> {
+    let mut lines = vec![
+        Line::Title("eudamed2firstbase - Batch Summary Report".to_string()),
+        Line::Text(format!(
+            "Generated {} by eudamed2firstbase v{}",
+            chrono::Local::now().format("%d.%m.%Y %H:%M"),
+            env!("CARGO_PKG_VERSION")
+        )),
+        Line::Text(format!("Source: {}", summary.source)),
+        Line::Blank,
+        Line::Heading("Devices".to_string()),
+        Line::Text(format!("{:>8}   converted documents", summary.devices)),
+    ];
+    if summary.unreadable > 0 {
+        lines.push(Line::Text(format!(
+            "{:>8}   unreadable files",
+            summary.unreadable
+        )));
+    }
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("By regulation".to_string()));
+    counts_lines(&mut lines, &summary.by_regulation);
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("By risk class".to_string()));
+    counts_lines(&mut lines, &summary.by_risk_class);
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("By device status".to_string()));
+    counts_lines(&mut lines, &summary.by_status);
+
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("Provenance".to_string()));
+    counts_lines(&mut lines, &summary.sources);
+    if summary.untraced > 0 {
+        lines.push(Line::Text(format!(
+            "{:>8}   without provenance",
+            summary.untraced
+        )));
+    }
+    if let Some((oldest, newest)) = &summary.downloaded {
+        lines.push(Line::Text(format!(
+            "Source records downloaded {} - {}",
+            oldest, newest
+        )));
+    }
+
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("Validation findings".to_string()));
+    if summary.findings.is_empty() {
+        lines.push(Line::Text("none".to_string()));
+    } else {
+        counts_lines(&mut lines, &summary.findings);
+    }
+
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("Push results (last session)".to_string()));
+    match &summary.push {
+        Some(p) => {
+            lines.push(Line::Text(format!(
+                "Session {} at {} ({})",
+                p.session_id,
+                p.at,
+                if p.env.is_empty() { "env n/a" } else { &p.env }
+            )));
+            lines.push(Line::Text(format!("{:>8}   accepted", p.accepted)));
+            lines.push(Line::Text(format!("{:>8}   rejected", p.rejected)));
+            for (code, n) in &p.top_errors {
+                lines.push(Line::Text(format!("{:>8}   error {}", n, code)));
+            }
+        }
+        None => lines.push(Line::Text("no push session recorded".to_string())),
+    }
+
+    lines.push(Line::Blank);
+    lines.push(Line::Heading("Unmapped codes".to_string()));
+    if summary.unmapped.is_empty() {
+        lines.push(Line::Text("none".to_string()));
+    } else {
+        let labelled: BTreeMap<String, usize> = summary
+            .unmapped
+            .iter()
+            .map(|((attr, value), n)| (format!("{} = {:?}", attr, value), *n))
+            .collect();
+        counts_lines(&mut lines, &labelled);
+    }
+    lines
+}
+
+/// Encode text for a PDF string literal in WinAnsiEncoding.
+fn pdf_text(s: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            '—' | '–' => out.push(b'-'),
+            c if (c as u32) < 0x100 => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
+
+/// Split `text` into pieces of at most `max` characters, breaking after the
+/// last space or `/` that fits and hard-breaking words that don't.
+fn wrap(text: &str, max: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest: Vec<char> = text.chars().collect();
+    while rest.len() > max {
+        let cut = rest[..max]
+            .iter()
+            .rposition(|c| *c == ' ' || *c == '/')
+            .filter(|&i| i > 0)
+            .map_or(max, |i| i + 1);
+        out.push(
+            rest[..cut]
+                .iter()
+                .collect::<String>()
+                .trim_end()
+                .to_string(),
+        );
+        rest.drain(..cut);
+    }
+    out.push(rest.into_iter().collect());
+    out
+}
+
+/// Render lines onto A4 pages and return the complete PDF file.
+fn render_pdf(lines: &[Line]) -> Vec<u8> {
+    const TOP: f32 = 790.0;
+    const BOTTOM: f32 = 50.0;
+    const LEFT: f32 = 50.0;
+    // Text width between the margins; 0.6 em is the Courier advance and
+    // at least the average Helvetica one, so wrapped lines stay on the page.
+    const WIDTH: f32 = 595.0 - 2.0 * LEFT;
+
+    // Lay out lines into page content streams.
+    let mut pages: Vec<Vec<u8>> = Vec::new();
+    let mut content: Vec<u8> = Vec::new();
+    let mut y = TOP;
+    for line in lines {
+        let (font, size, leading, text) = match line {
+            Line::Title(t) => ("F2", 16.0, 24.0, t.as_str()),
+            Line::Heading(t) => ("F2", 12.0, 18.0, t.as_str()),
+            Line::Text(t) => ("F3", 9.0, 12.0, t.as_str()),
+            Line::Blank => ("F1", 9.0, 8.0, ""),
+        };
+        for piece in wrap(text, (WIDTH / (0.6 * size)) as usize) {
+            if y - leading < BOTTOM {
+                pages.push(std::mem::take(&mut content));
+                y = TOP;
+            }
+            y -= leading;
+            if !piece.is_empty() {
+                content.extend_from_slice(
+                    format!("BT /{} {} Tf {} {} Td (", font, size, LEFT, y).as_bytes(),
+                );
+                content.extend_from_slice(&pdf_text(&piece));
+                content.extend_from_slice(b") Tj ET\n");
+            }
+        }
+    }
+    pages.push(content);
+
+    // Objects: 1 catalog, 2 pages, 3-5 fonts, then (page, content) pairs.
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        Vec::new(), // pages tree, filled below
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    let mut kids = Vec::new();
+    let page_count = pages.len();
+    for (i, stream) in pages.into_iter().enumerate() {
+        let page_id = objects.len() + 1;
+        let content_id = page_id + 1;
+        kids.push(format!("{} 0 R", page_id));
+        // Footer with page number.
+        let mut stream = stream;
+        stream.extend_from_slice(
+            format!(
+                "BT /F1 8 Tf {} 30 Td (Page {} / {}) Tj ET\n",
+                LEFT,
+                i + 1,
+                page_count
+            )
+            .as_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> \
+                 /Contents {} 0 R >>",
+                content_id
+            )
+            .into_bytes(),
+        );
+        let mut obj = format!("<< /Length {} >>\nstream\n", stream.len()).into_bytes();
+        obj.extend_from_slice(&stream);
+        obj.extend_from_slice(b"endstream");
+        objects.push(obj);
+    }
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        kids.len()
+    )
+    .into_bytes();
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, obj) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(obj);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref_at = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n", objects.len() + 1).as_bytes());
+    pdf.extend_from_slice(b"0000000000 65535 f \n");
+    for off in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", off).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_at
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+/// Write the summary as a PDF to `path`.
+pub fn write_pdf(summary: &Summary, path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, render_pdf(&summary_lines(summary)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_lines_are_wrapped_at_the_page_width() {
+        let path = format!("Source: /data/{}/eudamed.ndjson", "x".repeat(200));
+        let pdf = render_pdf(&[Line::Text(path.clone())]);
+        let pdf = String::from_utf8_lossy(&pdf);
+        let shown: Vec<&str> = pdf
+            .lines()
+            .filter_map(|l| l.strip_prefix("BT /F3 9 Tf "))
+            .map(|l| &l[l.find('(').unwrap() + 1..l.rfind(") Tj").unwrap()])
+            .collect();
+        // 495 pt / (0.6 * 9 pt) = 91 Courier characters per line.
+        assert!(shown.len() > 1);
+        assert!(shown.iter().all(|t| t.chars().count() <= 91));
+        assert_eq!(shown.concat(), path);
+        assert_eq!(wrap("ab cd", 3), vec!["ab", "cd"]);
+    }
+}
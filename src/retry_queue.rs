@@ -0,0 +1,194 @@
+//! Retry queue of rejected firstbase items (`push --retry`). Every item a
+//! push gets back REJECTED — per-item GS1 errors, a document-level reject of
+//! its batch or a batch GS1 never processed — is queued per environment with
+//! the document exactly as sent (gzipped) and its error codes; an accepted
+//! push of the device removes it again. `push --retry` pushes only the queued
+//! devices: their current `firstbase_json/<uuid>.json` (a fix by re-conversion
+//! or an override is picked up), else the queued document is written back
+//! there first.
+
+use rusqlite::{params, Connection};
+
+use crate::version_db::{compress_json, decompress_json};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS retry_queue (
+        firstbase_env TEXT NOT NULL,
+        uuid TEXT NOT NULL,
+        gtin TEXT NOT NULL,
+        error_codes TEXT NOT NULL DEFAULT '',
+        payload BLOB NOT NULL,
+        queued_at TEXT NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 1,
+        PRIMARY KEY (firstbase_env, uuid)
+    );
+";
+
+/// One queued device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub uuid: String,
+    pub gtin: String,
+    /// Distinct GS1 error codes of the last rejection.
+    pub error_codes: Vec<String>,
+    /// Time of the last rejection.
+    pub queued_at: String,
+    /// Rejected pushes so far.
+    pub attempts: u32,
+}
+
+pub fn ensure_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+/// Queue a rejected device of `env` (`Settings::firstbase_label`), or update it
+/// with the latest document and codes and count the attempt.
+pub fn enqueue(
+    conn: &Connection,
+    env: &str,
+    uuid: &str,
+    gtin: &str,
+    error_codes: &[&str],
+    payload: &str,
+    at: &str,
+) -> anyhow::Result<()> {
+    ensure_schema(conn)?;
+    let mut codes: Vec<&str> = error_codes.to_vec();
+    codes.sort_unstable();
+    codes.dedup();
+    conn.execute(
+        "INSERT INTO retry_queue (firstbase_env, uuid, gtin, error_codes, payload, queued_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (firstbase_env, uuid) DO UPDATE SET
+            gtin = excluded.gtin, error_codes = excluded.error_codes,
+            payload = excluded.payload, queued_at = excluded.queued_at,
+            attempts = attempts + 1",
+        params![env, uuid, gtin, codes.join(","), compress_json(payload), at],
+    )?;
+    Ok(())
+}
+
+/// Drop a device of `env` from the queue (it was accepted); whether it was queued.
+pub fn remove(conn: &Connection, env: &str, uuid: &str) -> anyhow::Result<bool> {
+    ensure_schema(conn)?;
+    Ok(conn.execute(
+        "DELETE FROM retry_queue WHERE firstbase_env = ?1 AND uuid = ?2",
+        params![env, uuid],
+    )? > 0)
+}
+
+/// The queued devices of `env`, oldest rejection first.
+pub fn entries(conn: &Connection, env: &str) -> anyhow::Result<Vec<Entry>> {
+    ensure_schema(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT uuid, gtin, error_codes, queued_at, attempts FROM retry_queue
+         WHERE firstbase_env = ?1 ORDER BY queued_at, uuid",
+    )?;
+    let rows = stmt
+        .query_map(params![env], |r| {
+            let codes: String = r.get(2)?;
+            Ok(Entry {
+                uuid: r.get(0)?,
+                gtin: r.get(1)?,
+                error_codes: codes
+                    .split(',')
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                queued_at: r.get(3)?,
+                attempts: r.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// The document of a queued device as it was last sent.
+pub fn payload(conn: &Connection, env: &str, uuid: &str) -> anyhow::Result<Option<String>> {
+    ensure_schema(conn)?;
+    let blob = conn.query_row(
+        "SELECT payload FROM retry_queue WHERE firstbase_env = ?1 AND uuid = ?2",
+        params![env, uuid],
+        |r| r.get::<_, Vec<u8>>(0),
+    );
+    match blob {
+        Ok(blob) => decompress_json(&blob)
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("Corrupt queued document of {}", uuid)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejections_queue_per_environment_until_accepted() {
+        let conn = Connection::open_in_memory().unwrap();
+        let doc = r#"{"DraftItem":{"Identifier":"Draft_u-1"}}"#;
+        enqueue(
+            &conn,
+            "Test",
+            "u-1",
+            "07640000000010",
+            &["097.025", "G361", "097.025"],
+            doc,
+            "2026-10-01T08:00:00Z",
+        )
+        .unwrap();
+        enqueue(
+            &conn,
+            "Test",
+            "u-2",
+            "07640000000027",
+            &[],
+            "{}",
+            "2026-10-02T08:00:00Z",
+        )
+        .unwrap();
+        enqueue(
+            &conn,
+            "Production",
+            "u-1",
+            "07640000000010",
+            &["G485"],
+            doc,
+            "2026-10-03T08:00:00Z",
+        )
+        .unwrap();
+
+        let test = entries(&conn, "Test").unwrap();
+        assert_eq!(test.len(), 2);
+        assert_eq!(test[0].uuid, "u-1");
+        assert_eq!(test[0].error_codes, ["097.025", "G361"]);
+        assert_eq!(test[0].attempts, 1);
+        assert!(test[1].error_codes.is_empty());
+        assert_eq!(payload(&conn, "Test", "u-1").unwrap().as_deref(), Some(doc));
+
+        // Rejected again after a fix: latest codes, one more attempt.
+        enqueue(
+            &conn,
+            "Test",
+            "u-1",
+            "07640000000010",
+            &["097.013"],
+            "{}",
+            "2026-10-04T08:00:00Z",
+        )
+        .unwrap();
+        let again = &entries(&conn, "Test").unwrap()[1];
+        assert_eq!((again.uuid.as_str(), again.attempts), ("u-1", 2));
+        assert_eq!(again.error_codes, ["097.013"]);
+
+        assert!(remove(&conn, "Test", "u-1").unwrap());
+        assert!(!remove(&conn, "Test", "u-1").unwrap());
+        assert_eq!(entries(&conn, "Test").unwrap().len(), 1);
+        assert_eq!(
+            entries(&conn, "Production").unwrap()[0].error_codes,
+            ["G485"]
+        );
+        assert_eq!(payload(&conn, "Test", "u-1").unwrap(), None);
+    }
+}
@@ -0,0 +1,267 @@
+//! Local full-text search over the converted devices (`search` subcommand and
+//! the GUI search box). An SQLite FTS5 table in `db/search_index.db` holds per
+//! device the GTINs, trade names, descriptions, manufacturer/AR names and SRNs,
+//! EMDN code and reference/model numbers, so a lookup is an index query
+//! instead of a linear scan over thousands of JSON files.
+//!
+//! The EUDAMED JSON convert updates the index per converted device; `search
+//! --reindex` rebuilds it from `firstbase_json/` (+ `processed/`).
+
+use std::path::Path;
+
+use anyhow::Context;
+use rayon::prelude::*;
+use rusqlite::{params, Connection};
+
+use crate::report::{array_at, base_unit, firstbase_docs, str_at};
+
+/// Index database path, relative to the data directory.
+pub const INDEX_DB_PATH: &str = "db/search_index.db";
+
+const SCHEMA: &str = "CREATE VIRTUAL TABLE IF NOT EXISTS devices USING fts5(
+    uuid UNINDEXED, gtin, trade_name, description, manufacturer, srn, emdn, reference,
+    tokenize = 'unicode61 remove_diacritics 2'
+);";
+
+/// One search result.
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub uuid: String,
+    pub gtin: String,
+    pub trade_name: String,
+    pub manufacturer: String,
+    pub emdn: String,
+}
+
+/// Searchable text of one firstbase document.
+#[derive(Debug, Default)]
+struct Fields {
+    gtin: String,
+    trade_name: String,
+    description: String,
+    manufacturer: String,
+    srn: String,
+    emdn: String,
+    reference: String,
+}
+
+/// Open (or create) the index database.
+pub fn open_index(path: &Path) -> anyhow::Result<Connection> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let conn = Connection::open(path)
+        .with_context(|| format!("Failed to open search index at {}", path.display()))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+    conn.execute_batch(SCHEMA)?;
+    Ok(conn)
+}
+
+fn values(items: &[serde_json::Value], pointer: &str) -> Vec<String> {
+    items
+        .iter()
+        .map(|i| str_at(i, pointer).to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn fields_of(doc: &serde_json::Value) -> Fields {
+    let top = doc
+        .pointer("/DraftItem/TradeItem")
+        .unwrap_or(&serde_json::Value::Null);
+    let base = base_unit(doc);
+    let desc = "/TradeItemDescriptionModule/TradeItemDescriptionInformation";
+    let mut gtins = vec![str_at(top, "/Gtin").to_string()];
+    if str_at(base, "/Gtin") != gtins[0] {
+        gtins.push(str_at(base, "/Gtin").to_string());
+    }
+    let mut description = values(
+        array_at(base, &format!("{}/AdditionalTradeItemDescription", desc)),
+        "/Value",
+    );
+    description.extend(values(
+        array_at(base, &format!("{}/DescriptionShort", desc)),
+        "/Value",
+    ));
+    for model in array_at(base, "/GlobalModelInformation") {
+        description.extend(values(array_at(model, "/GlobalModelDescription"), "/Value"));
+    }
+    let mut contacts: Vec<&serde_json::Value> = array_at(top, "/TradeItemContactInformation")
+        .iter()
+        .collect();
+    if !std::ptr::eq(top, base) {
+        contacts.extend(array_at(base, "/TradeItemContactInformation"));
+    }
+    let mut manufacturers: Vec<String> = contacts
+        .iter()
+        .map(|c| str_at(c, "/ContactName").to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    manufacturers.dedup();
+    let mut srns: Vec<String> = contacts
+        .iter()
+        .flat_map(|c| values(array_at(c, "/AdditionalPartyIdentification"), "/Value"))
+        .collect();
+    srns.dedup();
+    let emdn = array_at(
+        base,
+        "/GdsnTradeItemClassification/AdditionalTradeItemClassification",
+    )
+    .iter()
+    .filter(|c| str_at(c, "/AdditionalTradeItemClassificationSystemCode/Value") == "88")
+    .map(|c| {
+        str_at(
+            c,
+            "/AdditionalTradeItemClassificationValue/0/AdditionalTradeItemClassificationCodeValue",
+        )
+        .to_string()
+    })
+    .collect::<Vec<_>>();
+    let mut reference = values(
+        array_at(base, "/AdditionalTradeItemIdentification"),
+        "/Value",
+    );
+    reference.extend(values(
+        array_at(base, "/GlobalModelInformation"),
+        "/GlobalModelNumber",
+    ));
+
+    Fields {
+        gtin: gtins.join(" "),
+        trade_name: values(
+            array_at(base, &format!("{}/TradeItemDescription", desc)),
+            "/Value",
+        )
+        .join(" / "),
+        description: description.join(" / "),
+        manufacturer: manufacturers.join(" / "),
+        srn: srns.join(" "),
+        emdn: emdn.join(" "),
+        reference: reference.join(" "),
+    }
+}
+
+fn insert(conn: &Connection, uuid: &str, f: &Fields) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO devices (uuid, gtin, trade_name, description, manufacturer, srn, emdn, reference)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            uuid,
+            f.gtin,
+            f.trade_name,
+            f.description,
+            f.manufacturer,
+            f.srn,
+            f.emdn,
+            f.reference
+        ],
+    )
+}
+
+/// Add or replace one converted document in the index.
+pub fn index_document(
+    conn: &Connection,
+    uuid: &str,
+    doc: &serde_json::Value,
+) -> anyhow::Result<()> {
+    conn.execute("DELETE FROM devices WHERE uuid = ?1", params![uuid])?;
+    insert(conn, uuid, &fields_of(doc))?;
+    Ok(())
+}
+
+/// Rebuild the index at `index_path` from every per-UUID document in
+/// `firstbase_dir` and its `processed/`. Returns the number of indexed devices.
+pub fn rebuild(firstbase_dir: &Path, index_path: &Path) -> anyhow::Result<usize> {
+    let files = firstbase_docs(firstbase_dir, true);
+    let rows: Vec<(String, Fields)> = files
+        .par_iter()
+        .filter_map(|p| {
+            let doc: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(p).ok()?).ok()?;
+            let uuid = p.file_stem()?.to_string_lossy().to_string();
+            Some((uuid, fields_of(&doc)))
+        })
+        .collect();
+
+    let mut conn = open_index(index_path)?;
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM devices", [])?;
+    // A device pending again after a push exists in both dirs; index it once.
+    let mut seen = std::collections::HashSet::new();
+    for (uuid, fields) in &rows {
+        if seen.insert(uuid.as_str()) {
+            insert(&tx, uuid, fields)?;
+        }
+    }
+    tx.commit()?;
+    conn.execute("INSERT INTO devices(devices) VALUES('optimize')", [])?;
+    Ok(seen.len())
+}
+
+/// FTS5 query for free text: every word must match as a prefix, so
+/// `grasp livsmed` finds "ArtiSential Graspers" by LivsMed, and an SRN like
+/// `KR-MF-0000` matches as a phrase.
+fn match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|w| format!("\"{}\"*", w.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Best `limit` matches for `query`, most relevant first.
+pub fn search(conn: &Connection, query: &str, limit: usize) -> anyhow::Result<Vec<Hit>> {
+    let expr = match_expression(query);
+    if expr.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT uuid, gtin, trade_name, manufacturer, emdn FROM devices
+         WHERE devices MATCH ?1 ORDER BY bm25(devices, 0.0, 10.0, 5.0, 1.0, 3.0, 5.0, 2.0, 5.0)
+         LIMIT ?2",
+    )?;
+    let hits = stmt
+        .query_map(params![expr, limit as i64], |r| {
+            Ok(Hit {
+                uuid: r.get(0)?,
+                gtin: r.get(1)?,
+                trade_name: r.get(2)?,
+                manufacturer: r.get(3)?,
+                emdn: r.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(hits)
+}
+
+/// Number of indexed devices.
+pub fn count(conn: &Connection) -> anyhow::Result<u64> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM devices", [], |r| r.get(0))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_search_over_names_and_srns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        let doc = serde_json::json!({"DraftItem": {"TradeItem": {
+            "Gtin": "08800000000001",
+            "IsTradeItemABaseUnit": true,
+            "TradeItemDescriptionModule": {"TradeItemDescriptionInformation": {
+                "TradeItemDescription": [{"Value": "ArtiSential Graspers"}]}},
+            "TradeItemContactInformation": [{
+                "ContactName": "LivsMed Inc.",
+                "AdditionalPartyIdentification": [{"Value": "KR-MF-000011855"}]}]
+        }}});
+        index_document(&conn, "u1", &doc).unwrap();
+        index_document(&conn, "u1", &doc).unwrap();
+
+        assert_eq!(count(&conn).unwrap(), 1);
+        assert_eq!(search(&conn, "grasp livs", 10).unwrap()[0].uuid, "u1");
+        assert_eq!(search(&conn, "KR-MF-000011855", 10).unwrap().len(), 1);
+        assert!(search(&conn, "stent", 10).unwrap().is_empty());
+    }
+}
@@ -8,6 +8,8 @@
 
 use anyhow::{anyhow, Context, Result};
 
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
 /// Mint a read-only Sheets access token for the service account (no `sub`
 /// impersonation — the sheet is shared with the SA itself).
 fn sheets_access_token(pem_key: &str, service_email: &str) -> Result<String> {
@@ -30,7 +32,7 @@ fn sheets_access_token(pem_key: &str, service_email: &str) -> Result<String> {
     let claims = Claims {
         iss: service_email.to_string(),
         scope: "https://www.googleapis.com/auth/spreadsheets.readonly".to_string(),
-        aud: "https://oauth2.googleapis.com/token".to_string(),
+        aud: TOKEN_URL.to_string(),
         iat: now,
         exp: now + 3600,
     };
@@ -40,10 +42,7 @@ fn sheets_access_token(pem_key: &str, service_email: &str) -> Result<String> {
         .context("Failed to load service-account private key (PEM)")?;
     let jwt = encode(&header, &claims, &key)?;
 
-    let agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .build()
-        .new_agent();
+    let agent = crate::http::agent_for(TOKEN_URL);
 
     let form_body = format!(
         "grant_type={}&assertion={}",
@@ -51,7 +50,7 @@ fn sheets_access_token(pem_key: &str, service_email: &str) -> Result<String> {
     );
 
     let mut resp = agent
-        .post("https://oauth2.googleapis.com/token")
+        .post(TOKEN_URL)
         .header("Content-Type", "application/x-www-form-urlencoded")
         .send(form_body.as_bytes())?;
 
@@ -109,13 +108,10 @@ fn fetch_first_column(config: &crate::config::Config, range: &str) -> Result<Vec
     let url = format!(
         "https://sheets.googleapis.com/v4/spreadsheets/{}/values/{}",
         sheet.spreadsheet_id,
-        urlencode(range)
+        crate::http::urlencode(range)
     );
 
-    let agent = ureq::Agent::config_builder()
-        .http_status_as_error(false)
-        .build()
-        .new_agent();
+    let agent = crate::http::agent_for(&url);
 
     let mut resp = agent
         .get(&url)
@@ -167,33 +163,24 @@ pub fn fetch_srns(config: &crate::config::Config) -> Result<Vec<String>> {
 
 /// Fetch the customer GTIN worklist from the configured Google Sheet
 /// (`[sheet] gtin_range`, default the `eudamed2firstbase_GTIN` tab),
-/// de-duplicated, preserving sheet order. Invalid/non-numeric cells (header,
-/// notes) are dropped.
+/// as 14-digit GTINs (`gtin::from_worklist`, so a cell in number format that
+/// lost its leading zeros still matches), de-duplicated, preserving sheet
+/// order. Invalid/non-numeric cells (header, notes) are dropped.
 pub fn fetch_gtins(config: &crate::config::Config) -> Result<Vec<String>> {
     let cells = fetch_first_column(config, &config.sheet.gtin_range)?;
     let mut seen = std::collections::HashSet::new();
     let mut out = Vec::new();
     for cell in cells {
-        if is_gtin(&cell) && seen.insert(cell.clone()) {
-            out.push(cell);
+        if !is_gtin(&cell) {
+            continue;
         }
-    }
-    Ok(out)
-}
-
-/// Minimal percent-encoding for the A1 range path segment (covers `!`, space,
-/// `:` and anything non-alphanumeric except `-._~`).
-fn urlencode(s: &str) -> String {
-    let mut out = String::with_capacity(s.len() * 3);
-    for b in s.bytes() {
-        match b {
-            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
-                out.push(b as char)
+        if let Some(gtin) = crate::gtin::from_worklist(&cell) {
+            if seen.insert(gtin.clone()) {
+                out.push(gtin);
             }
-            _ => out.push_str(&format!("%{:02X}", b)),
         }
     }
-    out
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -227,7 +214,7 @@ mod tests {
     #[test]
     fn url_encodes_range() {
         assert_eq!(
-            urlencode("eudamed2firstbase_SRN!B1:B"),
+            crate::http::urlencode("eudamed2firstbase_SRN!B1:B"),
             "eudamed2firstbase_SRN%21B1%3AB"
         );
     }
@@ -0,0 +1,331 @@
+//! Snapshot comparison report (`compare-snapshots <old> <new>`).
+//! Compares two EUDAMED downloads — each either a snapshot root with
+//! `detail/` (+ optional `basic/`) or a flat directory of per-UUID JSON
+//! files — and lists the devices added, removed and modified between them,
+//! grouped by manufacturer, as CH-REPs need it for their monthly overview.
+//! Devices are matched by UUID (file stem); a device counts as modified when
+//! its detail or Basic UDI-DI JSON differs, with the changed top-level keys.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde_json::Value;
+
+/// One device of a snapshot.
+#[derive(Debug)]
+struct Device {
+    gtin: String,
+    trade_name: String,
+    version: String,
+    /// `name (SRN)` from the device-level file or the Basic UDI-DI.
+    manufacturer: String,
+    detail: Value,
+    basic: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl Change {
+    fn symbol(self) -> &'static str {
+        match self {
+            Change::Added => "+",
+            Change::Removed => "-",
+            Change::Modified => "~",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Modified => "modified",
+        }
+    }
+}
+
+/// One reported device.
+#[derive(Debug)]
+pub struct Entry {
+    pub change: Change,
+    pub uuid: String,
+    pub gtin: String,
+    pub trade_name: String,
+    pub old_version: String,
+    pub new_version: String,
+    /// Changed top-level keys (`basic:` prefix for the Basic UDI-DI).
+    pub changed: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Comparison {
+    pub old_dir: PathBuf,
+    pub new_dir: PathBuf,
+    pub old_devices: usize,
+    pub new_devices: usize,
+    pub unchanged: usize,
+    /// Manufacturer → entries, sorted by change kind then GTIN.
+    pub by_manufacturer: BTreeMap<String, Vec<Entry>>,
+}
+
+impl Comparison {
+    pub fn count(&self, change: Change) -> usize {
+        self.by_manufacturer
+            .values()
+            .flatten()
+            .filter(|e| e.change == change)
+            .count()
+    }
+}
+
+fn text_of(v: &Value, pointer: &str) -> String {
+    match v.pointer(pointer) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn manufacturer_of(v: &Value) -> Option<String> {
+    let name = text_of(v, "/manufacturer/name");
+    let srn = text_of(v, "/manufacturer/srn");
+    match (name.is_empty(), srn.is_empty()) {
+        (true, true) => None,
+        (false, true) => Some(name),
+        (true, false) => Some(srn),
+        (false, false) => Some(format!("{} ({})", name, srn)),
+    }
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Load a snapshot directory keyed by UUID.
+fn load_snapshot(dir: &Path) -> anyhow::Result<BTreeMap<String, Device>> {
+    let detail_dir = if dir.join("detail").is_dir() {
+        dir.join("detail")
+    } else {
+        dir.to_path_buf()
+    };
+    let basic_dir = dir.join("basic");
+    let mut devices = BTreeMap::new();
+    for entry in std::fs::read_dir(&detail_dir)
+        .with_context(|| format!("Failed to read {}", detail_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().map(|e| e != "json").unwrap_or(true) {
+            continue;
+        }
+        let Some(detail) = read_json(&path) else {
+            eprintln!("  Skipping unreadable {}", path.display());
+            continue;
+        };
+        let uuid = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let basic = read_json(&basic_dir.join(format!("{}.json", uuid)));
+        // UDI-DI level files carry a multilingual tradeName, device-level ones deviceName.
+        let trade_name = match text_of(&detail, "/tradeName/texts/0/text") {
+            t if t.is_empty() => text_of(&detail, "/deviceName"),
+            t => t,
+        };
+        let manufacturer = manufacturer_of(&detail)
+            .or_else(|| basic.as_ref().and_then(manufacturer_of))
+            .unwrap_or_else(|| "(unknown manufacturer)".to_string());
+        devices.insert(
+            uuid,
+            Device {
+                gtin: text_of(&detail, "/primaryDi/code"),
+                trade_name,
+                version: text_of(&detail, "/versionNumber"),
+                manufacturer,
+                detail,
+                basic,
+            },
+        );
+    }
+    Ok(devices)
+}
+
+fn changed_keys(old: &Value, new: &Value, prefix: &str, out: &mut Vec<String>) {
+    let empty = serde_json::Map::new();
+    let old_map = old.as_object().unwrap_or(&empty);
+    let new_map = new.as_object().unwrap_or(&empty);
+    let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        if old_map.get(key) != new_map.get(key) {
+            out.push(format!("{}{}", prefix, key));
+        }
+    }
+}
+
+/// Compare the snapshots in `old_dir` and `new_dir`.
+pub fn compare(old_dir: &Path, new_dir: &Path) -> anyhow::Result<Comparison> {
+    let old = load_snapshot(old_dir)?;
+    let new = load_snapshot(new_dir)?;
+    let mut cmp = Comparison {
+        old_dir: old_dir.to_path_buf(),
+        new_dir: new_dir.to_path_buf(),
+        old_devices: old.len(),
+        new_devices: new.len(),
+        ..Default::default()
+    };
+
+    for (uuid, n) in &new {
+        let entry = match old.get(uuid) {
+            None => Entry {
+                change: Change::Added,
+                uuid: uuid.clone(),
+                gtin: n.gtin.clone(),
+                trade_name: n.trade_name.clone(),
+                old_version: String::new(),
+                new_version: n.version.clone(),
+                changed: Vec::new(),
+            },
+            Some(o) => {
+                let mut changed = Vec::new();
+                changed_keys(&o.detail, &n.detail, "", &mut changed);
+                if o.basic != n.basic {
+                    changed_keys(
+                        o.basic.as_ref().unwrap_or(&Value::Null),
+                        n.basic.as_ref().unwrap_or(&Value::Null),
+                        "basic:",
+                        &mut changed,
+                    );
+                }
+                if changed.is_empty() {
+                    cmp.unchanged += 1;
+                    continue;
+                }
+                Entry {
+                    change: Change::Modified,
+                    uuid: uuid.clone(),
+                    gtin: n.gtin.clone(),
+                    trade_name: n.trade_name.clone(),
+                    old_version: o.version.clone(),
+                    new_version: n.version.clone(),
+                    changed,
+                }
+            }
+        };
+        cmp.by_manufacturer
+            .entry(n.manufacturer.clone())
+            .or_default()
+            .push(entry);
+    }
+    for (uuid, o) in &old {
+        if new.contains_key(uuid) {
+            continue;
+        }
+        cmp.by_manufacturer
+            .entry(o.manufacturer.clone())
+            .or_default()
+            .push(Entry {
+                change: Change::Removed,
+                uuid: uuid.clone(),
+                gtin: o.gtin.clone(),
+                trade_name: o.trade_name.clone(),
+                old_version: o.version.clone(),
+                new_version: String::new(),
+                changed: Vec::new(),
+            });
+    }
+    for entries in cmp.by_manufacturer.values_mut() {
+        entries.sort_by(|a, b| (a.change, &a.gtin, &a.uuid).cmp(&(b.change, &b.gtin, &b.uuid)));
+    }
+    Ok(cmp)
+}
+
+/// Plain-text report, one section per manufacturer.
+pub fn render_text(cmp: &Comparison) -> String {
+    let mut out = format!(
+        "EUDAMED snapshot comparison ({})\n\
+         Old: {} ({} devices)\n\
+         New: {} ({} devices)\n\
+         {} added, {} removed, {} modified, {} unchanged\n",
+        chrono::Local::now().format("%d.%m.%Y %H:%M"),
+        cmp.old_dir.display(),
+        cmp.old_devices,
+        cmp.new_dir.display(),
+        cmp.new_devices,
+        cmp.count(Change::Added),
+        cmp.count(Change::Removed),
+        cmp.count(Change::Modified),
+        cmp.unchanged,
+    );
+    for (manufacturer, entries) in &cmp.by_manufacturer {
+        let n = |c: Change| entries.iter().filter(|e| e.change == c).count();
+        out.push_str(&format!(
+            "\n== {}  (+{} -{} ~{})\n",
+            manufacturer,
+            n(Change::Added),
+            n(Change::Removed),
+            n(Change::Modified)
+        ));
+        for e in entries {
+            let v = |s: &str| {
+                if s.is_empty() {
+                    "v-".to_string()
+                } else {
+                    format!("v{}", s)
+                }
+            };
+            let version = match e.change {
+                Change::Added => v(&e.new_version),
+                Change::Removed => v(&e.old_version),
+                Change::Modified => format!("{} -> {}", v(&e.old_version), v(&e.new_version)),
+            };
+            out.push_str(&format!(
+                "  {} {}  {}  {}  {}{}\n",
+                e.change.symbol(),
+                if e.gtin.is_empty() { "-" } else { &e.gtin },
+                e.uuid,
+                e.trade_name,
+                version,
+                if e.changed.is_empty() {
+                    String::new()
+                } else {
+                    format!("  [{}]", e.changed.join(", "))
+                }
+            ));
+        }
+    }
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// CSV report, one row per added/removed/modified device.
+pub fn render_csv(cmp: &Comparison) -> String {
+    let mut csv = String::from(
+        "change,manufacturer,gtin,uuid,trade_name,old_version,new_version,changed_fields\r\n",
+    );
+    for (manufacturer, entries) in &cmp.by_manufacturer {
+        for e in entries {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\r\n",
+                e.change.label(),
+                csv_field(manufacturer),
+                csv_field(&e.gtin),
+                csv_field(&e.uuid),
+                csv_field(&e.trade_name),
+                csv_field(&e.old_version),
+                csv_field(&e.new_version),
+                csv_field(&e.changed.join(" "))
+            ));
+        }
+    }
+    csv
+}
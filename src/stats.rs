@@ -0,0 +1,170 @@
+//! Figures for the GUI "Statistics" section, read from the local databases:
+//! devices per risk class and per EUDAMED status (the latest `device_history`
+//! version of each device), the push acceptance rate per day (`push_log`) and
+//! the most frequent GS1 rejection codes (`push_error`). Tables a fresh install
+//! has not created yet just give empty charts.
+
+use rusqlite::{Connection, OptionalExtension};
+
+/// Pushes of one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Day {
+    /// `YYYY-MM-DD`
+    pub day: String,
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+impl Day {
+    /// Accepted share in percent.
+    pub fn rate(&self) -> f32 {
+        let total = self.accepted + self.rejected;
+        if total == 0 {
+            0.0
+        } else {
+            self.accepted as f32 * 100.0 / total as f32
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub risk_class: Vec<(String, usize)>,
+    pub status: Vec<(String, usize)>,
+    /// Oldest first.
+    pub acceptance: Vec<Day>,
+    /// Most frequent first, at most `TOP_CODES`.
+    pub rejection_codes: Vec<(String, usize)>,
+}
+
+const TOP_CODES: usize = 10;
+
+fn has_table(conn: &Connection, table: &str) -> anyhow::Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [table],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// (label, count) rows of a `SELECT label, COUNT(*) ...` query; empty labels
+/// become "(none)".
+fn counts(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> anyhow::Result<Vec<(String, usize)>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, |row| {
+        let label: Option<String> = row.get(0)?;
+        let count: i64 = row.get(1)?;
+        Ok((
+            label
+                .filter(|l| !l.is_empty())
+                .unwrap_or_else(|| "(none)".to_string()),
+            count as usize,
+        ))
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Statistics of the push environment `env` ("" = all environments; the
+/// device counts do not depend on it). `conn` comes from
+/// `version_db::open_db`, which adds `push_log.firstbase_env` to old DBs.
+pub fn load(conn: &Connection, env: &str) -> anyhow::Result<Stats> {
+    let mut stats = Stats::default();
+    if has_table(conn, "device_history")? {
+        let latest = "FROM device_history WHERE id IN \
+                      (SELECT MAX(id) FROM device_history GROUP BY uuid)";
+        stats.risk_class = counts(
+            conn,
+            &format!(
+                "SELECT risk_class, COUNT(*) {} GROUP BY risk_class ORDER BY 2 DESC",
+                latest
+            ),
+            [],
+        )?;
+        stats.status = counts(
+            conn,
+            &format!(
+                "SELECT device_status, COUNT(*) {} GROUP BY device_status ORDER BY 2 DESC",
+                latest
+            ),
+            [],
+        )?;
+    }
+    if has_table(conn, "push_log")? {
+        let mut stmt = conn.prepare(
+            "SELECT substr(pushed_at, 1, 10) AS day,
+                    SUM(status = 'ACCEPTED'), SUM(status = 'REJECTED')
+             FROM push_log WHERE ?1 = '' OR upper(firstbase_env) = upper(?1)
+             GROUP BY day ORDER BY day",
+        )?;
+        stats.acceptance = stmt
+            .query_map([env], |row| {
+                Ok(Day {
+                    day: row.get(0)?,
+                    accepted: row.get::<_, i64>(1)? as usize,
+                    rejected: row.get::<_, i64>(2)? as usize,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+    }
+    if has_table(conn, "push_error")? && has_table(conn, "push_session")? {
+        stats.rejection_codes = counts(
+            conn,
+            &format!(
+                "SELECT e.error_code, COUNT(*) FROM push_error e
+                 JOIN push_session s ON s.id = e.session_id
+                 WHERE ?1 = '' OR upper(s.firstbase_env) = upper(?1)
+                 GROUP BY e.error_code ORDER BY 2 DESC, 1 LIMIT {}",
+                TOP_CODES
+            ),
+            [env],
+        )?;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_version_and_environment_decide_the_counts() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE device_history (id INTEGER PRIMARY KEY, uuid TEXT,
+                 risk_class TEXT, device_status TEXT);
+             INSERT INTO device_history (uuid, risk_class, device_status) VALUES
+                 ('a', 'CLASS_I', 'ON_MARKET'), ('a', 'CLASS_IIA', 'ON_MARKET'),
+                 ('b', 'CLASS_IIA', ''), ('c', 'CLASS_III', 'NO_LONGER_PLACED_ON_THE_MARKET');
+             CREATE TABLE push_log (uuid TEXT, pushed_at TEXT, status TEXT, firstbase_env TEXT);
+             INSERT INTO push_log VALUES
+                 ('a', '2026-10-01T08:00:00', 'ACCEPTED', 'Test'),
+                 ('b', '2026-10-01T08:00:00', 'REJECTED', 'Test'),
+                 ('c', '2026-10-02T08:00:00', 'ACCEPTED', 'Production');
+             CREATE TABLE push_session (id INTEGER PRIMARY KEY, firstbase_env TEXT);
+             CREATE TABLE push_error (session_id INTEGER, error_code TEXT);
+             INSERT INTO push_session VALUES (1, 'Test'), (2, 'Production');
+             INSERT INTO push_error VALUES (1, '097.020'), (1, '097.020'), (2, 'G485');",
+        )
+        .unwrap();
+
+        let all = load(&conn, "").unwrap();
+        assert_eq!(
+            all.risk_class,
+            vec![("CLASS_IIA".to_string(), 2), ("CLASS_III".to_string(), 1)]
+        );
+        assert!(all.status.contains(&("(none)".to_string(), 1)));
+        assert_eq!(all.acceptance.len(), 2);
+        assert_eq!(all.acceptance[0].rate(), 50.0);
+
+        let test = load(&conn, "TEST").unwrap();
+        assert_eq!(test.acceptance.len(), 1);
+        assert_eq!(test.rejection_codes, vec![("097.020".to_string(), 2)]);
+    }
+}
@@ -0,0 +1,199 @@
+//! RequestStatus polling of a firstbase push (`[push.poll]` in config.toml).
+//!
+//! After CreateMany and AddMany, `push_to_firstbase` asks
+//! `RequestStatus/Get` until GS1 has a verdict. The waits between the polls
+//! grow exponentially: `interval_secs` before the first poll, times `factor`
+//! each time up to `max_interval_secs`, each spread by ±`jitter` so pushes
+//! started together do not poll in lockstep. After `timeout_secs` of waiting
+//! the request is given up and its chunk counts as unconfirmed. A small chunk
+//! thus gets its verdict after seconds, and a slow one is not asked every few
+//! seconds for minutes.
+//!
+//! The policy is layered, later wins: built-in defaults (5 s, × 1.5, at most
+//! 60 s, ±10%, 360 s timeout), `[push.poll]`, then `[push.poll.test]` or
+//! `[push.poll.production]` for the environment pushed to.
+
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::time::Duration;
+
+use crate::config::{Poll, PollPolicy};
+
+/// Resolved polling policy; times in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Strategy {
+    pub interval: f64,
+    pub factor: f64,
+    pub max_interval: f64,
+    pub timeout: u64,
+    pub jitter: f64,
+}
+
+const DEFAULT: Strategy = Strategy {
+    interval: 5.0,
+    factor: 1.5,
+    max_interval: 60.0,
+    timeout: 360,
+    jitter: 0.1,
+};
+
+/// Shortest wait between two polls, whatever the config says.
+const MIN_INTERVAL: f64 = 1.0;
+
+impl Default for Strategy {
+    fn default() -> Self {
+        DEFAULT
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "first after {}s, x{} up to {}s, ±{:.0}%, timeout {}s",
+            self.interval,
+            self.factor,
+            self.max_interval,
+            self.jitter * 100.0,
+            self.timeout
+        )
+    }
+}
+
+fn layer(strategy: &mut Strategy, over: &PollPolicy) {
+    if let Some(v) = over.interval_secs {
+        strategy.interval = v;
+    }
+    if let Some(v) = over.factor {
+        strategy.factor = v;
+    }
+    if let Some(v) = over.max_interval_secs {
+        strategy.max_interval = v;
+    }
+    if let Some(v) = over.timeout_secs {
+        strategy.timeout = v;
+    }
+    if let Some(v) = over.jitter {
+        strategy.jitter = v;
+    }
+}
+
+/// The strategy for environment `env` (`FirstbaseEnv::key`: `test` or
+/// `production`). Values that would poll without waiting or shrink the waits
+/// are raised to the nearest sensible one.
+pub fn resolve(poll: &Poll, env: &str) -> Strategy {
+    let mut strategy = DEFAULT;
+    layer(&mut strategy, &poll.defaults);
+    layer(
+        &mut strategy,
+        match env {
+            "production" => &poll.production,
+            _ => &poll.test,
+        },
+    );
+    strategy.interval = strategy.interval.max(MIN_INTERVAL);
+    strategy.factor = strategy.factor.max(1.0);
+    strategy.max_interval = strategy.max_interval.max(strategy.interval);
+    strategy.jitter = strategy.jitter.clamp(0.0, 1.0);
+    strategy
+}
+
+impl Strategy {
+    /// The polls of one request: `(poll number from 1, wait before it)`,
+    /// until the timeout is used up; the last wait is cut to what is left.
+    pub fn schedule(&self) -> Schedule {
+        Schedule {
+            strategy: *self,
+            poll: 0,
+            next: self.interval,
+            waited: 0.0,
+        }
+    }
+}
+
+/// Iterator of `Strategy::schedule`.
+pub struct Schedule {
+    strategy: Strategy,
+    poll: u32,
+    /// Unjittered wait before the next poll.
+    next: f64,
+    waited: f64,
+}
+
+impl Iterator for Schedule {
+    type Item = (u32, Duration);
+
+    fn next(&mut self) -> Option<(u32, Duration)> {
+        let left = self.strategy.timeout as f64 - self.waited;
+        if left <= 0.0 {
+            return None;
+        }
+        let base = self.next;
+        self.next = (self.next * self.strategy.factor).min(self.strategy.max_interval);
+        // Uniform in [-1, 1]; a fresh RandomState is seeded differently each time.
+        let spread = RandomState::new().hash_one(self.poll) as f64 / u64::MAX as f64 * 2.0 - 1.0;
+        let wait = (base * (1.0 + self.strategy.jitter * spread))
+            .max(MIN_INTERVAL.min(left))
+            .min(left);
+        self.waited += wait;
+        self.poll += 1;
+        Some((self.poll, Duration::from_secs_f64(wait)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_grow_to_the_cap_and_stop_at_the_timeout() {
+        let poll: Poll = toml::from_str(
+            r#"
+            factor = 2.0
+            jitter = 0.0
+            [production]
+            timeout_secs = 600
+            max_interval_secs = 30
+            "#,
+        )
+        .unwrap();
+        let test = resolve(&poll, "test");
+        assert_eq!(test.timeout, 360);
+        assert_eq!(test.max_interval, 60.0);
+        let production = resolve(&poll, "production");
+        assert_eq!(production.timeout, 600);
+        assert_eq!(production.factor, 2.0);
+
+        let waits: Vec<f64> = production
+            .schedule()
+            .map(|(_, wait)| wait.as_secs_f64())
+            .collect();
+        assert_eq!(&waits[..4], [5.0, 10.0, 20.0, 30.0]);
+        assert!(waits[4..waits.len() - 1].iter().all(|w| *w == 30.0));
+        assert!((waits.iter().sum::<f64>() - 600.0).abs() < 1e-6);
+        assert_eq!(production.schedule().last().unwrap().0, waits.len() as u32);
+
+        let jittered = resolve(&Poll::default(), "test");
+        assert_eq!(jittered, Strategy::default());
+        let unjittered = Strategy {
+            jitter: 0.0,
+            ..jittered
+        };
+        let bases: Vec<_> = unjittered.schedule().collect();
+        let waits: Vec<_> = jittered.schedule().collect();
+        // All but the last wait, which is cut to the timeout.
+        for ((_, wait), (_, base)) in waits
+            .iter()
+            .zip(&bases)
+            .take(waits.len().min(bases.len()) - 1)
+        {
+            let (wait, base) = (wait.as_secs_f64(), base.as_secs_f64());
+            assert!(wait >= base * 0.9 - 1e-6 && wait <= base * 1.1 + 1e-6);
+        }
+
+        let eager: Poll = toml::from_str("interval_secs = 0.0\nfactor = 0.5").unwrap();
+        let eager = resolve(&eager, "test");
+        assert_eq!((eager.interval, eager.factor), (MIN_INTERVAL, 1.0));
+        assert!(eager.schedule().count() <= 360);
+    }
+}
@@ -0,0 +1,394 @@
+//! Storage backend for the records a team shares across machines: the push
+//! log and the conversion history (`conversion_runs` + `device_history`).
+//! `[storage] backend` in config.toml selects the implementation:
+//! - `sqlite` (default): the local `db/version_tracking.db`, as before.
+//! - `postgres`: one PostgreSQL database for every machine running the
+//!   pipeline (build with `--features postgres`). Conversion history goes
+//!   there instead of the local DB; pushes are still logged locally too,
+//!   because retries, status and the GUI read the local push tables.
+//!
+//! Machine-local caches (`udi_versions`, `listing_cache`, actors, archived
+//! push payloads) always stay in SQLite.
+
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use crate::config;
+use crate::push_history::{self, PushLogRow};
+use crate::version_db::{self, ConversionRun, DeviceHistoryRecord, RunTotals};
+
+/// Shared push log + conversion history.
+pub trait Store {
+    /// Backend name for log lines.
+    fn name(&self) -> &'static str;
+    /// Start a conversion run and return its id.
+    fn begin_run(&mut self, mode: &str, input_dir: &Path) -> Result<i64>;
+    /// Append a device version of a run.
+    fn record_device_version(&mut self, rec: &DeviceHistoryRecord) -> Result<()>;
+    /// Stamp the end and totals of a run.
+    fn finish_run(&mut self, run_id: i64, totals: &RunTotals) -> Result<()>;
+    /// Every version of a device (UUID or GTIN), oldest first, with the
+    /// converter version of its run.
+    fn device_history(&mut self, uuid_or_gtin: &str) -> Result<Vec<(DeviceHistoryRecord, String)>>;
+    /// Most recent conversion runs, newest first.
+    fn recent_runs(&mut self, limit: u32) -> Result<Vec<ConversionRun>>;
+    /// Append one pushed device (`row.id` is ignored).
+    fn log_push(&mut self, row: &PushLogRow) -> Result<()>;
+    /// All push log rows, oldest first.
+    fn push_log_rows(&mut self) -> Result<Vec<PushLogRow>>;
+}
+
+/// The local version DB.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(SqliteStore {
+            conn: version_db::open_db(path)?,
+        })
+    }
+}
+
+impl Store for SqliteStore {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn begin_run(&mut self, mode: &str, input_dir: &Path) -> Result<i64> {
+        version_db::begin_run(&self.conn, mode, input_dir)
+    }
+
+    fn record_device_version(&mut self, rec: &DeviceHistoryRecord) -> Result<()> {
+        version_db::record_device_version(&self.conn, rec)
+    }
+
+    fn finish_run(&mut self, run_id: i64, totals: &RunTotals) -> Result<()> {
+        version_db::finish_run(&self.conn, run_id, totals)
+    }
+
+    fn device_history(&mut self, uuid_or_gtin: &str) -> Result<Vec<(DeviceHistoryRecord, String)>> {
+        version_db::device_history(&self.conn, uuid_or_gtin)
+    }
+
+    fn recent_runs(&mut self, limit: u32) -> Result<Vec<ConversionRun>> {
+        version_db::recent_runs(&self.conn, limit)
+    }
+
+    fn log_push(&mut self, row: &PushLogRow) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO push_log (uuid, gtin, pushed_at, request_id, status, error_code,
+                error_msg, publish_gln, firstbase_env)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                row.uuid,
+                row.gtin,
+                row.pushed_at,
+                row.request_id,
+                row.status,
+                row.error_code,
+                row.error_msg,
+                row.publish_gln,
+                row.firstbase_env
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn push_log_rows(&mut self) -> Result<Vec<PushLogRow>> {
+        push_history::push_log_rows(&self.conn)
+    }
+}
+
+/// Open the configured store; `sqlite_path` is the local version DB.
+pub fn open(storage: &config::Storage, sqlite_path: &Path) -> Result<Box<dyn Store>> {
+    match storage.backend.as_str() {
+        "" | "sqlite" => Ok(Box::new(SqliteStore::open(sqlite_path)?)),
+        "postgres" => open_postgres(storage),
+        other => anyhow::bail!(
+            "Unknown [storage] backend \"{}\" (expected sqlite or postgres)",
+            other
+        ),
+    }
+}
+
+/// The configured store when it is shared, i.e. not the local SQLite DB the
+/// caller already writes to.
+pub fn open_shared(storage: &config::Storage) -> Result<Option<Box<dyn Store>>> {
+    if storage.is_local() {
+        return Ok(None);
+    }
+    open(storage, Path::new("")).map(Some)
+}
+
+#[cfg(not(feature = "postgres"))]
+fn open_postgres(_storage: &config::Storage) -> Result<Box<dyn Store>> {
+    anyhow::bail!("[storage] backend = \"postgres\" needs a build with `--features postgres`")
+}
+
+#[cfg(feature = "postgres")]
+fn open_postgres(storage: &config::Storage) -> Result<Box<dyn Store>> {
+    Ok(Box::new(PostgresStore::connect(&storage.postgres_url)?))
+}
+
+/// Shared PostgreSQL database. Same tables and columns as the SQLite ones
+/// (`BIGSERIAL` ids, `BYTEA` documents); the push log carries the session id
+/// and error explanations of the pushing machine inline.
+#[cfg(feature = "postgres")]
+pub struct PostgresStore {
+    client: postgres::Client,
+}
+
+#[cfg(feature = "postgres")]
+const POSTGRES_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS conversion_runs (
+        id BIGSERIAL PRIMARY KEY,
+        started_at TEXT NOT NULL,
+        finished_at TEXT,
+        mode TEXT NOT NULL DEFAULT '',
+        input_dir TEXT NOT NULL DEFAULT '',
+        input_files BIGINT DEFAULT 0,
+        converted BIGINT DEFAULT 0,
+        skipped BIGINT DEFAULT 0,
+        errors BIGINT DEFAULT 0,
+        converter_version TEXT NOT NULL DEFAULT '',
+        output_hash TEXT NOT NULL DEFAULT ''
+    );
+    CREATE TABLE IF NOT EXISTS device_history (
+        id BIGSERIAL PRIMARY KEY,
+        run_id BIGINT NOT NULL,
+        uuid TEXT NOT NULL,
+        gtin TEXT NOT NULL DEFAULT '',
+        seen_at TEXT NOT NULL,
+        source_file TEXT NOT NULL DEFAULT '',
+        source_line INTEGER,
+        downloaded_at TEXT NOT NULL DEFAULT '',
+        detail_hash TEXT NOT NULL DEFAULT '',
+        udi_version INTEGER,
+        budi_version INTEGER,
+        change_summary TEXT NOT NULL DEFAULT '',
+        risk_class TEXT NOT NULL DEFAULT '',
+        regulatory_act TEXT NOT NULL DEFAULT '',
+        device_status TEXT NOT NULL DEFAULT '',
+        output_hash TEXT NOT NULL DEFAULT '',
+        document BYTEA
+    );
+    CREATE INDEX IF NOT EXISTS idx_device_history_uuid ON device_history(uuid);
+    CREATE INDEX IF NOT EXISTS idx_device_history_gtin ON device_history(gtin);
+    CREATE TABLE IF NOT EXISTS push_log (
+        id BIGSERIAL PRIMARY KEY,
+        uuid TEXT NOT NULL,
+        gtin TEXT NOT NULL DEFAULT '',
+        pushed_at TEXT NOT NULL,
+        request_id TEXT NOT NULL DEFAULT '',
+        status TEXT NOT NULL,
+        error_code TEXT NOT NULL DEFAULT '',
+        error_msg TEXT NOT NULL DEFAULT '',
+        publish_gln TEXT NOT NULL DEFAULT '',
+        firstbase_env TEXT NOT NULL DEFAULT '',
+        session_id BIGINT NOT NULL DEFAULT 0,
+        errors TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX IF NOT EXISTS idx_push_log_uuid ON push_log(uuid);
+    CREATE INDEX IF NOT EXISTS idx_push_log_pushed_at ON push_log(pushed_at);
+";
+
+#[cfg(feature = "postgres")]
+impl PostgresStore {
+    pub fn connect(url: &str) -> Result<Self> {
+        if url.is_empty() {
+            anyhow::bail!("[storage] postgres_url is empty");
+        }
+        let mut client = postgres::Client::connect(url, postgres::NoTls)
+            .map_err(|e| anyhow::anyhow!("Failed to connect to PostgreSQL: {}", e))?;
+        client.batch_execute(POSTGRES_SCHEMA)?;
+        Ok(PostgresStore { client })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Store for PostgresStore {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn begin_run(&mut self, mode: &str, input_dir: &Path) -> Result<i64> {
+        let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let row = self.client.query_one(
+            "INSERT INTO conversion_runs (started_at, mode, input_dir, converter_version)
+             VALUES ($1, $2, $3, $4) RETURNING id",
+            &[
+                &now,
+                &mode,
+                &input_dir.display().to_string(),
+                &env!("CARGO_PKG_VERSION"),
+            ],
+        )?;
+        Ok(row.get(0))
+    }
+
+    fn record_device_version(&mut self, rec: &DeviceHistoryRecord) -> Result<()> {
+        self.client.execute(
+            "INSERT INTO device_history (
+                run_id, uuid, gtin, seen_at, source_file, detail_hash, udi_version,
+                budi_version, change_summary, risk_class, regulatory_act, device_status,
+                output_hash, source_line, downloaded_at, document
+             ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16)",
+            &[
+                &rec.run_id,
+                &rec.uuid,
+                &rec.gtin,
+                &rec.seen_at,
+                &rec.source_file,
+                &rec.detail_hash,
+                &rec.udi_version.map(|v| v as i32),
+                &rec.budi_version.map(|v| v as i32),
+                &rec.change_summary,
+                &rec.risk_class,
+                &rec.regulatory_act,
+                &rec.device_status,
+                &rec.output_hash,
+                &rec.source_line.map(|v| v as i32),
+                &rec.downloaded_at,
+                &rec.document,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn finish_run(&mut self, run_id: i64, totals: &RunTotals) -> Result<()> {
+        let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        self.client.execute(
+            "UPDATE conversion_runs SET finished_at = $1, input_files = $2, converted = $3,
+                skipped = $4, errors = $5, output_hash = $6
+             WHERE id = $7",
+            &[
+                &now,
+                &(totals.input_files as i64),
+                &(totals.converted as i64),
+                &(totals.skipped as i64),
+                &(totals.errors as i64),
+                &totals.output_hash,
+                &run_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn device_history(&mut self, uuid_or_gtin: &str) -> Result<Vec<(DeviceHistoryRecord, String)>> {
+        let rows = self.client.query(
+            "SELECT h.run_id, h.uuid, h.gtin, h.seen_at, h.source_file, h.detail_hash,
+                    h.udi_version, h.budi_version, h.change_summary, h.risk_class,
+                    h.regulatory_act, h.device_status, h.output_hash,
+                    COALESCE(r.converter_version, ''), h.source_line, h.downloaded_at, h.document
+             FROM device_history h LEFT JOIN conversion_runs r ON r.id = h.run_id
+             WHERE h.uuid = $1 OR h.gtin = $1
+             ORDER BY h.id",
+            &[&uuid_or_gtin],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                (
+                    DeviceHistoryRecord {
+                        run_id: row.get(0),
+                        uuid: row.get(1),
+                        gtin: row.get(2),
+                        seen_at: row.get(3),
+                        source_file: row.get(4),
+                        detail_hash: row.get(5),
+                        udi_version: row.get::<_, Option<i32>>(6).map(|v| v as u32),
+                        budi_version: row.get::<_, Option<i32>>(7).map(|v| v as u32),
+                        change_summary: row.get(8),
+                        risk_class: row.get(9),
+                        regulatory_act: row.get(10),
+                        device_status: row.get(11),
+                        output_hash: row.get(12),
+                        source_line: row.get::<_, Option<i32>>(14).map(|v| v as u32),
+                        downloaded_at: row.get(15),
+                        document: row.get(16),
+                    },
+                    row.get(13),
+                )
+            })
+            .collect())
+    }
+
+    fn recent_runs(&mut self, limit: u32) -> Result<Vec<ConversionRun>> {
+        let rows = self.client.query(
+            "SELECT id, started_at, COALESCE(finished_at, ''), mode, input_dir, converter_version,
+                    input_files, converted, skipped, errors, output_hash
+             FROM conversion_runs ORDER BY id DESC LIMIT $1",
+            &[&i64::from(limit)],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| ConversionRun {
+                id: row.get(0),
+                started_at: row.get(1),
+                finished_at: row.get(2),
+                mode: row.get(3),
+                input_dir: row.get(4),
+                converter_version: row.get(5),
+                totals: RunTotals {
+                    input_files: row.get::<_, Option<i64>>(6).unwrap_or(0) as u64,
+                    converted: row.get::<_, Option<i64>>(7).unwrap_or(0) as u64,
+                    skipped: row.get::<_, Option<i64>>(8).unwrap_or(0) as u64,
+                    errors: row.get::<_, Option<i64>>(9).unwrap_or(0) as u64,
+                    output_hash: row.get(10),
+                },
+            })
+            .collect())
+    }
+
+    fn log_push(&mut self, row: &PushLogRow) -> Result<()> {
+        self.client.execute(
+            "INSERT INTO push_log (uuid, gtin, pushed_at, request_id, status, error_code,
+                error_msg, publish_gln, firstbase_env, session_id, errors)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            &[
+                &row.uuid,
+                &row.gtin,
+                &row.pushed_at,
+                &row.request_id,
+                &row.status,
+                &row.error_code,
+                &row.error_msg,
+                &row.publish_gln,
+                &row.firstbase_env,
+                &row.session_id,
+                &row.errors,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn push_log_rows(&mut self) -> Result<Vec<PushLogRow>> {
+        let rows = self.client.query(
+            "SELECT id, uuid, gtin, pushed_at, request_id, status, error_code, error_msg,
+                    publish_gln, firstbase_env, session_id, errors
+             FROM push_log ORDER BY id",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|r| PushLogRow {
+                id: r.get(0),
+                uuid: r.get(1),
+                gtin: r.get(2),
+                pushed_at: r.get(3),
+                request_id: r.get(4),
+                status: r.get(5),
+                error_code: r.get(6),
+                error_msg: r.get(7),
+                publish_gln: r.get(8),
+                firstbase_env: r.get(9),
+                session_id: r.get(10),
+                errors: r.get(11),
+            })
+            .collect())
+    }
+}
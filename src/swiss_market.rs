@@ -0,0 +1,216 @@
+//! Swiss market availability and CH-REP for target market 756. EUDAMED only
+//! lists EU/EEA markets and EU authorised representatives, so for the Swiss
+//! datapool both come from `[swiss_market]`:
+//!
+//! - Availability rule: `eu` (default) lists Switzerland as an additional
+//!   market of every base unit on an EU market; `always` also gives a base
+//!   unit without EU market data Switzerland as its original market (not
+//!   systems / procedure packs, 097.086); `none` adds nothing.
+//! - Start: the `start_date` of the manufacturer's `[swiss_market.by_srn]`
+//!   entry (the date swissdamed has), else `[swiss_market] start_date`, else
+//!   the start of the EU original placing.
+//! - CH-REP: an EAR contact identified by its CHRN, from the SRN's entry or
+//!   the default `ch_rep`, on every level with contacts; Swiss manufacturers
+//!   (`CH-MF-…`) need none.
+//!
+//! Other target markets are left alone. Idempotent, so it can run again after
+//! the listing merge brings the manufacturer SRN.
+
+use crate::config::{ChAvailability, ChRep, Config};
+use crate::dates;
+use crate::firstbase::*;
+
+const SWITZERLAND: &str = "756";
+
+/// Add Swiss availability and the CH-REP to every level of `doc`.
+pub fn apply_document(doc: &mut FirstbaseDocument, config: &Config) {
+    fn children(links: &mut [CatalogueItemChildItemLink], config: &Config, srn: Option<&str>) {
+        for link in links {
+            apply_with(&mut link.catalogue_item.trade_item, config, srn);
+            children(&mut link.catalogue_item.children, config, srn);
+        }
+    }
+    if config.target_market.country_code != SWITZERLAND {
+        return;
+    }
+    let srn = crate::parties::manufacturer_srn(&doc.trade_item).map(str::to_string);
+    apply_with(&mut doc.trade_item, config, srn.as_deref());
+    children(&mut doc.children, config, srn.as_deref());
+}
+
+fn apply_with(item: &mut TradeItem, config: &Config, srn: Option<&str>) {
+    let swiss = &config.swiss_market;
+    let by_srn = srn.and_then(|srn| swiss.by_srn.get(srn));
+
+    if item.is_base_unit {
+        let start_date = by_srn
+            .and_then(|m| m.start_date.as_deref())
+            .or(swiss.start_date.as_deref())
+            .map(|d| dates::day_at(&config.dates, d, dates::START_OF_DAY));
+        let is_pack = item
+            .medical_device_module
+            .info
+            .system_or_procedure_pack_type
+            .is_some();
+        availability(
+            &mut item.sales_module,
+            swiss.availability,
+            start_date,
+            is_pack,
+        );
+    }
+
+    let swiss_manufacturer = srn.is_some_and(|s| s.starts_with("CH-"));
+    let ch_rep = by_srn
+        .and_then(|m| m.ch_rep.as_ref())
+        .or(swiss.ch_rep.as_ref());
+    if let Some(ch_rep) = ch_rep.filter(|r| !r.chrn.is_empty()) {
+        if !swiss_manufacturer && !item.contact_information.is_empty() {
+            add_ch_rep(&mut item.contact_information, ch_rep);
+        }
+    }
+}
+
+fn availability(
+    sales: &mut Option<SalesInformationModule>,
+    rule: ChAvailability,
+    start_date: Option<String>,
+    is_pack: bool,
+) {
+    let country = |start: String| SalesConditionCountry {
+        country_code: CodeValue {
+            value: SWITZERLAND.into(),
+        },
+        end_datetime: None,
+        start_datetime: start,
+    };
+    match (rule, sales) {
+        (ChAvailability::None, _) => {}
+        (_, Some(module)) => {
+            let conditions = &mut module.sales.conditions;
+            let listed = conditions
+                .iter()
+                .flat_map(|c| &c.countries)
+                .any(|c| c.country_code.value == SWITZERLAND);
+            if listed || conditions.is_empty() {
+                return;
+            }
+            let start = start_date.unwrap_or_else(|| {
+                conditions
+                    .iter()
+                    .find(|c| c.condition_code.value == "ORIGINAL_PLACED")
+                    .and_then(|c| c.countries.first())
+                    .map(|c| c.start_datetime.clone())
+                    .unwrap_or_default()
+            });
+            match conditions
+                .iter_mut()
+                .find(|c| c.condition_code.value == "ADDITIONAL_MARKET_AVAILABILITY")
+            {
+                Some(additional) => additional.countries.push(country(start)),
+                None => conditions.push(TargetMarketSalesCondition {
+                    condition_code: CodeValue {
+                        value: "ADDITIONAL_MARKET_AVAILABILITY".into(),
+                    },
+                    countries: vec![country(start)],
+                }),
+            }
+            crate::ordering::sales_conditions(conditions);
+        }
+        (ChAvailability::Always, sales @ None) if !is_pack => {
+            *sales = Some(SalesInformationModule {
+                sales: SalesInformation {
+                    conditions: vec![TargetMarketSalesCondition {
+                        condition_code: CodeValue {
+                            value: "ORIGINAL_PLACED".into(),
+                        },
+                        countries: vec![country(start_date.unwrap_or_default())],
+                    }],
+                },
+            });
+        }
+        (_, None) => {}
+    }
+}
+
+fn add_ch_rep(contacts: &mut Vec<TradeItemContactInformation>, ch_rep: &ChRep) {
+    let present = contacts
+        .iter()
+        .flat_map(|c| &c.party_identification)
+        .any(|p| p.type_code == "CHRN");
+    if present {
+        return;
+    }
+    contacts.push(TradeItemContactInformation {
+        contact_type: CodeValue {
+            value: "EAR".into(),
+        },
+        party_identification: vec![AdditionalPartyIdentification {
+            type_code: "CHRN".to_string(),
+            value: ch_rep.chrn.clone(),
+        }],
+        contact_name: ch_rep.name.clone(),
+        addresses: Vec::new(),
+        communication_channels: Vec::new(),
+    });
+    crate::ordering::contacts(contacts);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(code: &'static str, country: &'static str) -> TargetMarketSalesCondition {
+        TargetMarketSalesCondition {
+            condition_code: CodeValue { value: code.into() },
+            countries: vec![SalesConditionCountry {
+                country_code: CodeValue {
+                    value: country.into(),
+                },
+                end_datetime: None,
+                start_datetime: "2021-05-26T13:00:00+00:00".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn switzerland_joins_the_eu_markets() {
+        let mut sales = Some(SalesInformationModule {
+            sales: SalesInformation {
+                conditions: vec![condition("ORIGINAL_PLACED", "276")],
+            },
+        });
+        availability(&mut sales, ChAvailability::Eu, None, false);
+        availability(&mut sales, ChAvailability::Eu, None, false);
+        let conditions = &sales.as_ref().unwrap().sales.conditions;
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(
+            conditions[1].condition_code.value,
+            "ADDITIONAL_MARKET_AVAILABILITY"
+        );
+        assert_eq!(conditions[1].countries.len(), 1);
+        assert_eq!(
+            conditions[1].countries[0].start_datetime,
+            "2021-05-26T13:00:00+00:00"
+        );
+
+        let mut none = None;
+        availability(&mut none, ChAvailability::Eu, None, false);
+        assert!(none.is_none());
+        availability(&mut none, ChAvailability::Always, None, true);
+        assert!(none.is_none());
+        availability(&mut none, ChAvailability::Always, None, false);
+        let conditions = &none.unwrap().sales.conditions;
+        assert_eq!(conditions[0].condition_code.value, "ORIGINAL_PLACED");
+        assert_eq!(conditions[0].countries[0].country_code.value, "756");
+
+        let mut contacts = Vec::new();
+        let ch_rep = ChRep {
+            chrn: "CHRN-AR-20000807".to_string(),
+            name: None,
+        };
+        add_ch_rep(&mut contacts, &ch_rep);
+        add_ch_rep(&mut contacts, &ch_rep);
+        assert_eq!(contacts.len(), 1);
+    }
+}
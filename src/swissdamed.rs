@@ -290,6 +290,15 @@ fn extract_spp_type(code: &str) -> String {
     }
 }
 
+/// Swissdamed registers for the Swiss market: "all languages" texts go out
+/// in its languages.
+fn swiss_languages() -> Vec<String> {
+    crate::config::market_languages("756")
+        .iter()
+        .map(|l| l.to_string())
+        .collect()
+}
+
 /// Map language texts from EUDAMED MultiLangText
 fn map_lang_texts(texts: &[(String, String)]) -> Vec<LangText> {
     texts
@@ -437,6 +446,7 @@ fn map_critical_warnings(device: &ApiDeviceDetail) -> Vec<CriticalWarningDto> {
 /// Map EUDAMED device + BUDI to Swissdamed MDR DTO
 pub fn to_mdr_dto(device: &ApiDeviceDetail, basic_udi: &BasicUdiDiData) -> MdrDto {
     let uuid = device.uuid.clone().unwrap_or_default();
+    let languages = swiss_languages();
 
     MdrDto {
         correlation_id: uuid,
@@ -485,9 +495,11 @@ pub fn to_mdr_dto(device: &ApiDeviceDetail, basic_udi: &BasicUdiDiData) -> MdrDt
                 .unwrap_or_default(),
         },
         udi_di: MdrUdiDiDto {
-            trade_names: map_lang_texts(&device.trade_name_texts()),
+            trade_names: map_lang_texts(&device.trade_name_texts(&languages)),
             reference_number: device.reference.clone().unwrap_or_default(),
-            additional_description: map_lang_texts(&device.additional_description_texts()),
+            additional_description: map_lang_texts(
+                &device.additional_description_texts(&languages),
+            ),
             website: device.additional_information_url.clone(),
             sterile: device.sterile.unwrap_or(false),
             sterilization: device.sterilization.unwrap_or(false),
@@ -516,6 +528,7 @@ pub fn to_mdr_dto(device: &ApiDeviceDetail, basic_udi: &BasicUdiDiData) -> MdrDt
 /// Map EUDAMED device + BUDI to Swissdamed SPP DTO
 pub fn to_spp_dto(device: &ApiDeviceDetail, basic_udi: &BasicUdiDiData) -> SppDto {
     let uuid = device.uuid.clone().unwrap_or_default();
+    let languages = swiss_languages();
 
     SppDto {
         correlation_id: uuid,
@@ -547,7 +560,7 @@ pub fn to_spp_dto(device: &ApiDeviceDetail, basic_udi: &BasicUdiDiData) -> SppDt
                 .map(|c| extract_spp_type(c))
                 .unwrap_or_else(|| "PROCEDURE_PACK".to_string()),
             medicinal_purpose: {
-                let texts = map_lang_texts(&basic_udi.medical_purpose_texts());
+                let texts = map_lang_texts(&basic_udi.medical_purpose_texts(&languages));
                 if texts.is_empty() {
                     // XSD requires at least one medicinalPurpose entry for SPP
                     vec![LangText {
@@ -565,9 +578,11 @@ pub fn to_spp_dto(device: &ApiDeviceDetail, basic_udi: &BasicUdiDiData) -> SppDt
                 .unwrap_or_default(),
         },
         udi_di: SppUdiDiDto {
-            trade_names: map_lang_texts(&device.trade_name_texts()),
+            trade_names: map_lang_texts(&device.trade_name_texts(&languages)),
             reference_number: device.reference.clone().unwrap_or_default(),
-            additional_description: map_lang_texts(&device.additional_description_texts()),
+            additional_description: map_lang_texts(
+                &device.additional_description_texts(&languages),
+            ),
             website: device.additional_information_url.clone(),
             sterile: device.sterile.unwrap_or(false),
             sterilization: device.sterilization.unwrap_or(false),
@@ -0,0 +1,177 @@
+use serde::Deserialize;
+
+/// One device record of a swissdamed export: the M2M registration payload
+/// (`correlationId`, `basicUdi`, `udiDi`) of the MDR, IVDR or SPP endpoint,
+/// as written by the `swissdamed` command and returned by the M2M API.
+/// All fields are optional so the three endpoint shapes share one model.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SwissdamedRecord {
+    pub correlation_id: Option<String>,
+    /// `MDR`, `IVDR`, `MDD`, `AIMDD` or `IVDD`, when the export names it;
+    /// otherwise derived (see `legislation`).
+    pub legislation: Option<String>,
+    /// Post-market status (`ON_THE_MARKET`, `NO_LONGER_ON_THE_MARKET`, …).
+    pub market_status: Option<String>,
+    #[serde(default)]
+    pub basic_udi: SwissBasicUdi,
+    #[serde(default)]
+    pub udi_di: SwissUdiDi,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SwissBasicUdi {
+    pub identifier: Option<DiCode>,
+    pub device_name: Option<String>,
+    pub model_name: Option<String>,
+    /// `CLASS_IIA`, `CLASS_C`, …
+    pub risk_class: Option<String>,
+    /// `DEVICE`, `KIT`, `SYSTEM`, `PROCEDURE_PACK`, …
+    #[serde(rename = "type")]
+    pub device_type: Option<String>,
+    pub animal_tissues_cells: Option<bool>,
+    pub human_tissues_cells: Option<bool>,
+    pub active: Option<bool>,
+    pub administering_medicine: Option<bool>,
+    pub human_product_check: Option<bool>,
+    pub implantable: Option<bool>,
+    pub measuring_function: Option<bool>,
+    pub medicinal_product_check: Option<bool>,
+    pub reusable: Option<bool>,
+    pub class_iib_implantable_exceptions: Option<bool>,
+    /// Manufacturer SRN (MDR / IVDR).
+    pub mf_actor_code: Option<String>,
+    /// System / procedure pack producer SRN (SPP).
+    pub pr_actor_code: Option<String>,
+    #[serde(default)]
+    pub medicinal_purpose: Vec<SwissText>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SwissUdiDi {
+    pub identifier: Option<DiCode>,
+    pub secondary_identifier: Option<DiCode>,
+    pub direct_marking_identifier: Option<DiCode>,
+    pub unit_of_use_identifier: Option<DiCode>,
+    #[serde(default)]
+    pub trade_names: Vec<SwissText>,
+    pub reference_number: Option<String>,
+    #[serde(default)]
+    pub additional_description: Vec<SwissText>,
+    pub website: Option<String>,
+    pub sterile: Option<bool>,
+    pub sterilization: Option<bool>,
+    #[serde(default)]
+    pub nomenclature_codes: Vec<String>,
+    #[serde(default)]
+    pub storage_handling_conditions: Vec<StorageHandlingCondition>,
+    #[serde(default)]
+    pub critical_warnings: Vec<CriticalWarning>,
+    /// `BATCH_NUMBER`, `SERIAL_NUMBER`, …
+    #[serde(default)]
+    pub production_identifiers: Vec<String>,
+    #[serde(default)]
+    pub packages: Vec<Package>,
+    pub base_quantity: Option<u32>,
+    /// -1: no limit given.
+    pub number_of_reuses: Option<i32>,
+    pub latex: Option<bool>,
+    pub reprocessed: Option<bool>,
+    pub annex_xvi_applicable: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiCode {
+    #[serde(default, deserialize_with = "crate::gtin::owned_code")]
+    pub di_code: Option<String>,
+    /// `GS1`, `HIBC`, `ICCBBA`, `IFA`.
+    pub issuing_entity_code: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SwissText {
+    pub language: Option<String>,
+    pub text_value: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageHandlingCondition {
+    pub storage_handling_condition_value: Option<String>,
+    #[serde(default)]
+    pub comments: Vec<SwissText>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticalWarning {
+    pub warning_value: Option<String>,
+    #[serde(default)]
+    pub comments: Vec<SwissText>,
+}
+
+/// A package level: `numberOfItems` of `childOf` in `identifier`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+    pub identifier: Option<DiCode>,
+    pub child_of: Option<DiCode>,
+    pub number_of_items: Option<u32>,
+}
+
+impl DiCode {
+    pub fn code(&self) -> Option<&str> {
+        self.di_code.as_deref().filter(|c| !c.is_empty())
+    }
+}
+
+impl SwissdamedRecord {
+    /// System or procedure pack: the SPP endpoint's producer (`prActorCode`).
+    pub fn is_spp(&self) -> bool {
+        self.basic_udi.pr_actor_code.is_some()
+    }
+
+    /// The regulatory act: as exported, else IVDR for the classes A–D and
+    /// MDR for everything else (SPP included).
+    pub fn legislation(&self) -> String {
+        if let Some(act) = self.legislation.as_deref().filter(|a| !a.is_empty()) {
+            return act.to_uppercase();
+        }
+        match self.basic_udi.risk_class.as_deref() {
+            Some("CLASS_A" | "CLASS_B" | "CLASS_C" | "CLASS_D") => "IVDR".into(),
+            _ => "MDR".into(),
+        }
+    }
+
+    /// UDI-DI code of the device.
+    pub fn udi_di_code(&self) -> Option<&str> {
+        self.udi_di.identifier.as_ref()?.code()
+    }
+
+    /// Output name: the correlation ID (the EUDAMED UUID when the record came
+    /// from `swissdamed`), else the UDI-DI, else `fallback`.
+    pub fn stem(&self, fallback: &str) -> String {
+        self.correlation_id
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .or_else(|| self.udi_di_code())
+            .unwrap_or(fallback)
+            .to_string()
+    }
+}
+
+/// Parse a swissdamed export file: one record, or an array of records.
+pub fn parse_swissdamed_json(json_str: &str) -> anyhow::Result<Vec<SwissdamedRecord>> {
+    let value: serde_json::Value = serde_json::from_str(json_str)?;
+    Ok(match value {
+        serde_json::Value::Array(items) => items
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?,
+        other => vec![serde_json::from_value(other)?],
+    })
+}
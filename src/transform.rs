@@ -1,9 +1,11 @@
+use crate::avp;
 use crate::config::Config;
+use crate::dates;
 use crate::eudamed::*;
 use crate::firstbase::*;
 use crate::mappings;
+use crate::ordering;
 use anyhow::{Context, Result};
-use chrono::Utc;
 use std::collections::HashMap;
 
 pub fn transform(response: &PullResponse, config: &Config) -> Result<FirstbaseDocument> {
@@ -27,6 +29,7 @@ pub fn transform(response: &PullResponse, config: &Config) -> Result<FirstbaseDo
         .as_ref()
         .and_then(|id| id.di_code.as_deref())
         .unwrap_or("");
+    let _warnings = crate::warnings::device_scope(base_unit_di);
 
     // Build the base unit trade item (with all device detail)
     let base_trade_item = build_base_unit(basic_udi, udidi, config)?;
@@ -38,17 +41,20 @@ pub fn transform(response: &PullResponse, config: &Config) -> Result<FirstbaseDo
         // No packages - base unit is the root and highest level
         let mut item = base_trade_item;
         item.is_despatch_unit = true;
-        return Ok(FirstbaseDocument {
+        ordering::trade_item(&mut item);
+        let mut document = FirstbaseDocument {
             trade_item: item,
             children: vec![],
             identifier: format!("Draft_{}", uuid::Uuid::new_v4()),
-        });
+        };
+        finish_document(&mut document, config);
+        return Ok(document);
     }
 
     // Build nested structure from outermost package down to base unit
     // Pass base unit contacts so package DIs get EMA/EAR for SRN filtering
     let base_contacts = base_trade_item.contact_information.clone();
-    build_nested_document(
+    let mut document = build_nested_document(
         &hierarchy,
         &top_gtin,
         base_unit_di,
@@ -56,7 +62,31 @@ pub fn transform(response: &PullResponse, config: &Config) -> Result<FirstbaseDo
         basic_udi_di,
         config,
         &base_contacts,
-    )
+    )?;
+    crate::gpc::apply_to_packages(&mut document);
+    crate::discontinued::apply_to_packages(&mut document);
+    ordering::document(&mut document);
+    finish_document(&mut document, config);
+    Ok(document)
+}
+
+/// The passes every converter runs on its finished document, on all levels,
+/// in this order. A new post-transform pass goes here, not into each path.
+pub fn finish_document(document: &mut FirstbaseDocument, config: &Config) {
+    crate::swiss_market::apply_document(document, config);
+    crate::actor_contacts::apply_document(document, config);
+    crate::parties::apply_document(document, config);
+}
+
+/// `finish_document` for a trade item without packaging levels.
+pub fn finish_trade_item(item: TradeItem, config: &Config) -> TradeItem {
+    let mut document = FirstbaseDocument {
+        trade_item: item,
+        children: Vec::new(),
+        identifier: String::new(),
+    };
+    finish_document(&mut document, config);
+    document.trade_item
 }
 
 #[derive(Debug)]
@@ -238,13 +268,13 @@ fn build_packaging_trade_item(
 
     TradeItem {
         is_brand_bank_publication: false,
-        target_sector: vec!["UDI_REGISTRY".to_string()],
+        target_sector: vec!["UDI_REGISTRY".into()],
         chemical_regulation_module: None,
         healthcare_item_module: None,
         medical_device_module: MedicalDeviceTradeItemModule {
             info: MedicalDeviceInformation {
                 eu_status: CodeValue {
-                    value: "ON_MARKET".to_string(),
+                    value: "ON_MARKET".into(),
                 },
                 ..Default::default()
             },
@@ -258,15 +288,17 @@ fn build_packaging_trade_item(
         is_despatch_unit: is_top_level,
         is_orderable_unit: true,
         unit_descriptor: CodeValue {
-            value: descriptor.to_string(),
+            value: descriptor.to_string().into(),
         },
         trade_channel_code: vec![CodeValue {
-            value: "UDI_REGISTRY".to_string(),
+            value: "UDI_REGISTRY".into(),
         }],
         information_provider: InformationProvider {
             gln: config.provider.gln.clone(),
             party_name: config.provider.party_name.clone(),
         },
+        brand_owner: None,
+        manufacturer_of_trade_item: Vec::new(),
         classification: GdsnClassification {
             segment_code: config.gpc.segment_code.clone(),
             class_code: config.gpc.class_code.clone(),
@@ -289,12 +321,12 @@ fn build_packaging_trade_item(
         }),
         target_market: TargetMarketObj {
             country_code: CodeValue {
-                value: config.target_market.country_code.clone(),
+                value: config.target_market.country_code.clone().into(),
             },
         },
         contact_information: pkg_contacts,
         synchronisation_dates: {
-            let now_str = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+            let now_str = dates::now(&config.dates);
             TradeItemSynchronisationDates {
                 last_change: now_str.clone(),
                 effective: now_str.clone(),
@@ -308,6 +340,7 @@ fn build_packaging_trade_item(
         additional_identification: vec![],
         referenced_trade_items: Vec::new(),
         trade_item_information: Vec::new(),
+        avp_list: None,
     }
 }
 
@@ -332,27 +365,25 @@ fn build_base_unit(
     let mut classifications = Vec::new();
 
     // MDN codes (system 88) - sorted alphabetically
-    if let Some(ref mdn) = udidi.mdn_codes {
-        let mut codes: Vec<&str> = mdn.split_whitespace().collect();
-        codes.sort();
-        for code in codes {
-            classifications.push(AdditionalClassification {
-                system_code: CodeValue {
-                    value: "88".to_string(),
-                },
-                values: vec![AdditionalClassificationValue {
-                    code_value: code.to_string(),
-                }],
-            });
-        }
+    let mut emdn_codes: Vec<&str> = udidi
+        .mdn_codes
+        .as_deref()
+        .map(|mdn| mdn.split_whitespace().collect())
+        .unwrap_or_default();
+    emdn_codes.sort();
+    for &code in &emdn_codes {
+        classifications.push(AdditionalClassification {
+            system_code: CodeValue { value: "88".into() },
+            values: vec![AdditionalClassificationValue {
+                code_value: code.to_string(),
+            }],
+        });
     }
 
     // Risk class (system 76)
     if !risk_class.is_empty() {
         classifications.push(AdditionalClassification {
-            system_code: CodeValue {
-                value: "76".to_string(),
-            },
+            system_code: CodeValue { value: "76".into() },
             values: vec![AdditionalClassificationValue {
                 code_value: mappings::risk_class_to_gs1(risk_class).to_string(),
             }],
@@ -366,7 +397,7 @@ fn build_base_unit(
     if let Some(ref mf) = basic_udi.mf_actor_code {
         contacts.push(TradeItemContactInformation {
             contact_type: CodeValue {
-                value: "EMA".to_string(),
+                value: "EMA".into(),
             },
             party_identification: vec![AdditionalPartyIdentification {
                 type_code: "SRN".to_string(),
@@ -382,7 +413,7 @@ fn build_base_unit(
     if let Some(ref ar) = basic_udi.ar_actor_code {
         contacts.push(TradeItemContactInformation {
             contact_type: CodeValue {
-                value: "EAR".to_string(),
+                value: "EAR".into(),
             },
             party_identification: vec![AdditionalPartyIdentification {
                 type_code: "SRN".to_string(),
@@ -399,7 +430,7 @@ fn build_base_unit(
         if let Some(ref org) = pd.organisation {
             let mut pd_contact = TradeItemContactInformation {
                 contact_type: CodeValue {
-                    value: "EPD".to_string(),
+                    value: "EPD".into(),
                 },
                 party_identification: vec![],
                 contact_name: org.org_name.clone(),
@@ -412,11 +443,11 @@ fn build_base_unit(
                     .country
                     .as_deref()
                     .map(mappings::country_alpha2_to_numeric)
-                    .unwrap_or("");
+                    .unwrap_or_default();
                 pd_contact.addresses.push(StructuredAddress {
                     city: addr.city.clone().unwrap_or_default(),
                     country_code: CodeValue {
-                        value: country_numeric.to_string(),
+                        value: country_numeric,
                     },
                     postal_code: addr.post_code.clone().unwrap_or_default(),
                     street: addr.street.clone().unwrap_or_default(),
@@ -429,7 +460,7 @@ fn build_base_unit(
             if let Some(ref email) = org.email {
                 channels.push(CommunicationChannel {
                     channel_code: CodeValue {
-                        value: "EMAIL".to_string(),
+                        value: "EMAIL".into(),
                     },
                     value: email.clone(),
                 });
@@ -437,7 +468,7 @@ fn build_base_unit(
             if let Some(ref phone) = org.phone {
                 channels.push(CommunicationChannel {
                     channel_code: CodeValue {
-                        value: "TELEPHONE".to_string(),
+                        value: "TELEPHONE".into(),
                     },
                     value: phone.clone(),
                 });
@@ -459,25 +490,28 @@ fn build_base_unit(
         .map(|s| {
             s.split_whitespace()
                 .map(|id| CodeValue {
-                    value: mappings::production_identifier_to_gs1(id).to_string(),
+                    value: mappings::production_identifier_to_gs1(id)
+                        .to_string()
+                        .into(),
                 })
                 .collect()
         })
         .unwrap_or_default();
-    production_ids.sort_by(|a, b| prod_id_sort_key(&a.value).cmp(&prod_id_sort_key(&b.value)));
+    ordering::production_identifiers(&mut production_ids);
 
     // Annex XVI types (now Vec<String> directly)
     let annex_xvi: Vec<CodeValue> = udidi
         .annex_xvi_types
         .iter()
-        .map(|t| CodeValue { value: t.clone() })
+        .map(|t| CodeValue {
+            value: t.clone().into(),
+        })
         .collect();
 
     // Multi-component type
-    let multi_component = basic_udi
-        .device_kind
-        .as_ref()
-        .map(|t| CodeValue { value: t.clone() });
+    let multi_component = basic_udi.device_kind.as_ref().map(|t| CodeValue {
+        value: t.clone().into(),
+    });
 
     // Status (now Option<String> directly)
     let status = udidi
@@ -491,14 +525,14 @@ fn build_base_unit(
         if n == 0 {
             ReusabilityInformation {
                 reusability_type: CodeValue {
-                    value: "SINGLE_USE".to_string(),
+                    value: "SINGLE_USE".into(),
                 },
                 max_cycles: None,
             }
         } else {
             ReusabilityInformation {
                 reusability_type: CodeValue {
-                    value: "LIMITED_REUSABLE".to_string(),
+                    value: "LIMITED_REUSABLE".into(),
                 },
                 max_cycles: Some(n),
             }
@@ -517,7 +551,7 @@ fn build_base_unit(
                 } else {
                     "NOT_STERILISED"
                 }
-                .to_string(),
+                .into(),
             }],
             prior_to_use: vec![CodeValue {
                 value: if sterilization {
@@ -525,7 +559,7 @@ fn build_base_unit(
                 } else {
                     "NO_STERILISATION_REQUIRED"
                 }
-                .to_string(),
+                .into(),
             }],
         })
     };
@@ -607,7 +641,7 @@ fn build_base_unit(
                     None
                 },
                 file_type: CodeValue {
-                    value: "IFU".to_string(),
+                    value: "IFU".into(),
                 },
                 format_name: if is_pdf {
                     Some("Pdf".to_string())
@@ -626,11 +660,17 @@ fn build_base_unit(
         info: vec![RegulatoryInformation {
             act: mappings::regulation_from_risk_class(risk_class).to_string(),
             agency: "EU".to_string(),
+            ..Default::default()
         }],
     });
 
     // Sales information (market info - now Vec<MarketInfo> directly)
-    let sales_module = transform_market_info(udidi);
+    let mut sales_module = transform_market_info(udidi, &config.dates);
+    let discontinued = crate::discontinued::is_no_longer(status).then(|| {
+        let date = crate::discontinued::date(&config.dates, None, sales_module.as_ref());
+        crate::discontinued::close_markets(&mut sales_module, &date);
+        date
+    });
 
     // Global model info
     let model_desc = basic_udi
@@ -639,7 +679,7 @@ fn build_base_unit(
         .and_then(|m| m.name.as_ref())
         .map(|n| {
             vec![LangValue {
-                language_code: "en".to_string(),
+                language_code: "en".into(),
                 value: n.clone(),
             }]
         })
@@ -662,7 +702,7 @@ fn build_base_unit(
 
     Ok(TradeItem {
         is_brand_bank_publication: false,
-        target_sector: vec!["UDI_REGISTRY".to_string()],
+        target_sector: vec!["UDI_REGISTRY".into()],
         chemical_regulation_module: chem_module,
         healthcare_item_module: healthcare_module,
         medical_device_module: MedicalDeviceTradeItemModule {
@@ -693,7 +733,7 @@ fn build_base_unit(
                 is_professional_testing: None,
                 is_companion_diagnostic: None,
                 eu_status: CodeValue {
-                    value: status.to_string(),
+                    value: status.to_string().into(),
                 },
                 reusability,
                 sterility,
@@ -708,37 +748,32 @@ fn build_base_unit(
         is_despatch_unit: false, // set to true later if no packaging hierarchy
         is_orderable_unit: true,
         unit_descriptor: CodeValue {
-            value: "BASE_UNIT_OR_EACH".to_string(),
+            value: "BASE_UNIT_OR_EACH".into(),
         },
         trade_channel_code: vec![CodeValue {
-            value: "UDI_REGISTRY".to_string(),
+            value: "UDI_REGISTRY".into(),
         }],
         information_provider: InformationProvider {
             gln: config.provider.gln.clone(),
             party_name: config.provider.party_name.clone(),
         },
-        classification: GdsnClassification {
-            segment_code: config.gpc.segment_code.clone(),
-            class_code: config.gpc.class_code.clone(),
-            family_code: config.gpc.family_code.clone(),
-            category_code: config.gpc.category_code.clone(),
-            category_name: config.gpc.category_name.clone(),
-            additional_classifications: classifications,
-        },
+        brand_owner: None,
+        manufacturer_of_trade_item: Vec::new(),
+        classification: crate::gpc::classification(config, base_di, &emdn_codes, classifications),
         next_lower_level: None,
         target_market: TargetMarketObj {
             country_code: CodeValue {
-                value: config.target_market.country_code.clone(),
+                value: config.target_market.country_code.clone().into(),
             },
         },
         contact_information: contacts,
         synchronisation_dates: {
-            let now_str = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+            let now_str = dates::now(&config.dates);
             TradeItemSynchronisationDates {
                 last_change: now_str.clone(),
                 effective: now_str.clone(),
                 publication: now_str,
-                discontinued: None,
+                discontinued,
             }
         },
         // Only a valid GS1 GMN may go into globalModelNumber (097.116).
@@ -747,6 +782,22 @@ fn build_base_unit(
         additional_identification: additional_ids,
         referenced_trade_items: Vec::new(),
         trade_item_information: Vec::new(),
+        avp_list: avp::build(
+            &config.avp,
+            &[
+                (
+                    "primary_di",
+                    udidi.identifier.as_ref().and_then(|i| i.di_code.as_deref()),
+                ),
+                (
+                    "basic_udi",
+                    basic_udi
+                        .identifier
+                        .as_ref()
+                        .and_then(|i| i.di_code.as_deref()),
+                ),
+            ],
+        ),
     })
 }
 
@@ -756,22 +807,22 @@ fn transform_lang_names(names: &Option<Vec<LanguageSpecificName>>) -> Vec<LangVa
         .map(|n| {
             n.iter()
                 .filter_map(|name| {
-                    let raw_lang = name.language.as_deref()?.to_lowercase();
-                    let lang = if raw_lang == "any" {
-                        "en".to_string()
+                    let lang = name.language.as_deref()?;
+                    let lang = if lang.eq_ignore_ascii_case("any") {
+                        "en"
                     } else {
-                        raw_lang
+                        lang
                     };
                     let val = name.text_value.as_deref()?;
                     Some(LangValue {
-                        language_code: lang,
+                        language_code: mappings::language_code(lang),
                         value: val.to_string(),
                     })
                 })
                 .collect()
         })
         .unwrap_or_default();
-    result.sort_by(|a, b| lang_sort_key(&a.language_code).cmp(&lang_sort_key(&b.language_code)));
+    ordering::languages(&mut result);
     result
 }
 
@@ -780,37 +831,20 @@ fn transform_lang_names_vec(names: &[LanguageSpecificName]) -> Vec<LangValue> {
         .iter()
         .filter_map(|name| {
             let val = name.text_value.as_deref()?;
-            let raw_lang = name
-                .language
-                .as_deref()
-                .map(|l| l.to_lowercase())
-                .unwrap_or_else(|| "en".to_string());
-            let lang = if raw_lang == "any" {
-                "en".to_string()
-            } else {
-                raw_lang
+            let lang = match name.language.as_deref() {
+                Some(l) if !l.eq_ignore_ascii_case("any") => l,
+                _ => "en",
             };
             Some(LangValue {
-                language_code: lang,
+                language_code: mappings::language_code(lang),
                 value: val.to_string(),
             })
         })
         .collect();
-    result.sort_by(|a, b| lang_sort_key(&a.language_code).cmp(&lang_sort_key(&b.language_code)));
+    ordering::languages(&mut result);
     result
 }
 
-/// Sort languages in priority order: en, fr, de, it, then alphabetical
-fn lang_sort_key(lang: &str) -> u8 {
-    match lang {
-        "en" => 0,
-        "fr" => 1,
-        "de" => 2,
-        "it" => 3,
-        _ => 4,
-    }
-}
-
 fn transform_storage_handling(udidi: &MdrUdidiData) -> Vec<ClinicalStorageHandling> {
     udidi
         .storage_handling_conditions
@@ -821,7 +855,9 @@ fn transform_storage_handling(udidi: &MdrUdidiData) -> Vec<ClinicalStorageHandli
             let descriptions = transform_lang_names_vec(&cond.comments);
 
             ClinicalStorageHandling {
-                type_code: CodeValue { value: gs1_code },
+                type_code: CodeValue {
+                    value: gs1_code.into(),
+                },
                 descriptions,
             }
         })
@@ -843,14 +879,12 @@ fn transform_clinical_sizes(udidi: &MdrUdidiData) -> Vec<ClinicalSizeOutput> {
             let raw_mu = size.value_unit.as_deref().unwrap_or("");
             let characteristic_code = mappings::mu_code_to_characteristic_code(raw_mu);
             let unit = if characteristic_code.is_some() {
-                ""
+                Code::default()
             } else {
                 mappings::measurement_unit_to_gs1(raw_mu)
             };
             let characteristic_codes = match characteristic_code {
-                Some(code) => vec![CodeValue {
-                    value: code.to_string(),
-                }],
+                Some(code) => vec![CodeValue { value: code.into() }],
                 None => Vec::new(),
             };
 
@@ -869,18 +903,18 @@ fn transform_clinical_sizes(udidi: &MdrUdidiData) -> Vec<ClinicalSizeOutput> {
                     ClinicalSizeOutput {
                         descriptions: Vec::new(),
                         type_code: CodeValue {
-                            value: gs1_type.to_string(),
+                            value: gs1_type.to_string().into(),
                         },
                         values: vec![MeasurementValue {
-                            unit_code: unit.to_string(),
+                            unit_code: unit.clone(),
                             value: min_val,
                         }],
                         maximums: vec![MeasurementValue {
-                            unit_code: unit.to_string(),
+                            unit_code: unit.clone(),
                             value: max_val,
                         }],
                         precision: CodeValue {
-                            value: "RANGE".to_string(),
+                            value: "RANGE".into(),
                         },
                         text: None,
                         characteristic_codes: characteristic_codes.clone(),
@@ -890,7 +924,7 @@ fn transform_clinical_sizes(udidi: &MdrUdidiData) -> Vec<ClinicalSizeOutput> {
                     let descriptions = if gs1_type == "DEVICE_SIZE_TEXT_SPECIFY" {
                         let desc = size.text.as_deref().unwrap_or("Other");
                         vec![LangValue {
-                            language_code: "en".to_string(),
+                            language_code: "en".into(),
                             value: desc.to_string(),
                         }]
                     } else {
@@ -899,12 +933,12 @@ fn transform_clinical_sizes(udidi: &MdrUdidiData) -> Vec<ClinicalSizeOutput> {
                     ClinicalSizeOutput {
                         descriptions,
                         type_code: CodeValue {
-                            value: gs1_type.to_string(),
+                            value: gs1_type.to_string().into(),
                         },
                         values: vec![],
                         maximums: vec![],
                         precision: CodeValue {
-                            value: "TEXT".to_string(),
+                            value: "TEXT".into(),
                         },
                         text: size.text.clone(),
                         characteristic_codes: characteristic_codes.clone(),
@@ -919,15 +953,15 @@ fn transform_clinical_sizes(udidi: &MdrUdidiData) -> Vec<ClinicalSizeOutput> {
                     ClinicalSizeOutput {
                         descriptions: Vec::new(),
                         type_code: CodeValue {
-                            value: gs1_type.to_string(),
+                            value: gs1_type.to_string().into(),
                         },
                         values: vec![MeasurementValue {
-                            unit_code: unit.to_string(),
+                            unit_code: unit.clone(),
                             value: val,
                         }],
                         maximums: vec![],
                         precision: CodeValue {
-                            value: "VALUE".to_string(),
+                            value: "VALUE".into(),
                         },
                         text: None,
                         characteristic_codes: characteristic_codes.clone(),
@@ -948,7 +982,7 @@ fn transform_warnings(udidi: &MdrUdidiData) -> Vec<ClinicalWarningOutput> {
 
             ClinicalWarningOutput {
                 agency_code: CodeValue {
-                    value: "EUDAMED".to_string(),
+                    value: "EUDAMED".into(),
                 },
                 warning_code: code.to_string(),
                 descriptions,
@@ -1009,7 +1043,7 @@ fn transform_substances(
                         descriptions: descriptions.clone(),
                         cmr_type: None,
                         chemical_type: CodeValue {
-                            value: chemical_type_code.to_string(),
+                            value: chemical_type_code.to_string().into(),
                         },
                     });
                 }
@@ -1023,7 +1057,7 @@ fn transform_substances(
                         descriptions: descriptions.clone(),
                         cmr_type: None,
                         chemical_type: CodeValue {
-                            value: chemical_type_code.to_string(),
+                            value: chemical_type_code.to_string().into(),
                         },
                     });
                 }
@@ -1037,7 +1071,7 @@ fn transform_substances(
                     descriptions,
                     cmr_type: None,
                     chemical_type: CodeValue {
-                        value: chemical_type_code.to_string(),
+                        value: chemical_type_code.to_string().into(),
                     },
                 });
             }
@@ -1060,9 +1094,9 @@ fn transform_substances(
                         identifier_ref: None,
                         chemical_name: None,
                         descriptions,
-                        cmr_type: cmr_type.map(|t| CodeValue { value: t }),
+                        cmr_type: cmr_type.map(|t| CodeValue { value: t.into() }),
                         chemical_type: CodeValue {
-                            value: chemical_type_code.to_string(),
+                            value: chemical_type_code.to_string().into(),
                         },
                     }],
                 }],
@@ -1076,9 +1110,9 @@ fn transform_substances(
                         identifier_ref: None,
                         chemical_name: substance.inn.clone(),
                         descriptions: vec![],
-                        cmr_type: cmr_type.map(|t| CodeValue { value: t }),
+                        cmr_type: cmr_type.map(|t| CodeValue { value: t.into() }),
                         chemical_type: CodeValue {
-                            value: chemical_type_code.to_string(),
+                            value: chemical_type_code.to_string().into(),
                         },
                     }],
                 }],
@@ -1089,37 +1123,15 @@ fn transform_substances(
     if chem_infos.is_empty() {
         None
     } else {
-        // Sort: WHO first, then ECHA; within each agency sort by chemical type
-        chem_infos.sort_by(|a, b| {
-            let a_key = substance_sort_key(&a.agency, &a.regulations);
-            let b_key = substance_sort_key(&b.agency, &b.regulations);
-            a_key.cmp(&b_key)
-        });
+        ordering::chemicals(&mut chem_infos);
         Some(ChemicalRegulationInformationModule { infos: chem_infos })
     }
 }
 
-fn substance_sort_key(agency: &str, regulations: &[ChemicalRegulation]) -> (u8, u8) {
-    let agency_key = match agency {
-        "WHO" => 0,
-        "ECHA" => 1,
-        _ => 2,
-    };
-    let type_key = regulations
-        .first()
-        .and_then(|r| r.chemicals.first())
-        .map(|c| match c.chemical_type.value.as_str() {
-            "MEDICINAL_PRODUCT" => 0,
-            "HUMAN_PRODUCT" => 1,
-            "ENDOCRINE_SUBSTANCE" => 0,
-            "CMR_SUBSTANCE" => 1,
-            _ => 2,
-        })
-        .unwrap_or(2);
-    (agency_key, type_key)
-}
-
-fn transform_market_info(udidi: &MdrUdidiData) -> Option<SalesInformationModule> {
+fn transform_market_info(
+    udidi: &MdrUdidiData,
+    zones: &crate::config::Dates,
+) -> Option<SalesInformationModule> {
     if udidi.market_infos.is_empty() {
         return None;
     }
@@ -1147,16 +1159,16 @@ fn transform_market_info(udidi: &MdrUdidiData) -> Option<SalesInformationModule>
             let start = mi.start_date.as_deref().unwrap_or("");
             let end = mi.end_date.as_deref();
 
-            let start_dt = convert_date_to_datetime(start, false);
-            let end_dt = end.map(|d| convert_date_to_datetime(d, true));
+            let start_dt = dates::day_at(zones, start, dates::START_OF_DAY);
+            let end_dt = end.map(|d| dates::day_at(zones, d, dates::END_OF_DAY));
 
             TargetMarketSalesCondition {
                 condition_code: CodeValue {
-                    value: condition_code.to_string(),
+                    value: condition_code.into(),
                 },
                 countries: vec![SalesConditionCountry {
                     country_code: CodeValue {
-                        value: numeric_country.to_string(),
+                        value: numeric_country,
                     },
                     end_datetime: end_dt,
                     start_datetime: start_dt,
@@ -1165,58 +1177,13 @@ fn transform_market_info(udidi: &MdrUdidiData) -> Option<SalesInformationModule>
         })
         .collect();
 
-    // Sort: ORIGINAL_PLACED first, then by country code
-    conditions.sort_by(|a, b| {
-        let a_orig = a.condition_code.value == "ORIGINAL_PLACED";
-        let b_orig = b.condition_code.value == "ORIGINAL_PLACED";
-        b_orig.cmp(&a_orig).then_with(|| {
-            let a_cc = a
-                .countries
-                .first()
-                .map(|c| &c.country_code.value)
-                .map(|s| s.as_str())
-                .unwrap_or("");
-            let b_cc = b
-                .countries
-                .first()
-                .map(|c| &c.country_code.value)
-                .map(|s| s.as_str())
-                .unwrap_or("");
-            a_cc.cmp(b_cc)
-        })
-    });
+    ordering::sales_conditions(&mut conditions);
 
     Some(SalesInformationModule {
         sales: SalesInformation { conditions },
     })
 }
 
-/// Convert EUDAMED date "2026-02-03+01:00" to datetime.
-/// Start dates use T13:00:00+00:00, end dates use T21:00:00+00:00.
-fn convert_date_to_datetime(date_str: &str, is_end_date: bool) -> String {
-    let date_part = if date_str.contains('+') && !date_str.contains('T') {
-        date_str.split('+').next().unwrap_or(date_str)
-    } else if date_str.contains('T') {
-        return date_str.to_string();
-    } else {
-        date_str
-    };
-    let time = if is_end_date { "21:00:00" } else { "13:00:00" };
-    format!("{}T{}+00:00", date_part, time)
-}
-
-/// Sort production identifiers: SERIAL_NUMBER, MANUFACTURING_DATE, BATCH_NUMBER, ...
-fn prod_id_sort_key(id: &str) -> u8 {
-    match id {
-        "SERIAL_NUMBER" => 0,
-        "MANUFACTURING_DATE" => 1,
-        "BATCH_NUMBER" => 2,
-        "EXPIRATION_DATE" => 3,
-        "SOFTWARE_IDENTIFICATION" => 4,
-        _ => 5,
-    }
-}
-
 fn generate_uuid() -> String {
     uuid::Uuid::new_v4().to_string()
 }
@@ -1,27 +1,28 @@
 use crate::api_json::ApiDevice;
+use crate::avp;
 use crate::config::Config;
+use crate::dates;
 use crate::firstbase::*;
 use crate::mappings;
-use chrono::Utc;
+use crate::tri_state::TriState;
 
 /// Transform an API device listing record into a firstbase TradeItem.
 /// This is a "best-effort" mapping from the flat listing data - the listing
 /// has limited fields compared to the full DTX XML / detail endpoint.
 pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
-    let now = Utc::now();
-    let now_str = now.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let now_str = dates::now(&config.dates);
 
-    let gtin = device.primary_di.clone().unwrap_or_default();
-    let basic_udi = device.basic_udi.clone().unwrap_or_default();
+    let gtin = device.primary_di.as_deref().unwrap_or_default().to_string();
+    let _warnings = crate::warnings::device_scope(&gtin);
+    let basic_udi = device.basic_udi.as_deref().unwrap_or_default();
+    let version = avp::json_text(device.version_number.as_ref());
 
     // Risk class → AdditionalTradeItemClassification (system 76)
     let mut additional_classifications = Vec::new();
     if let Some(rc) = device.risk_class_code() {
         let gs1_risk = mappings::risk_class_to_gs1(&rc);
         additional_classifications.push(AdditionalClassification {
-            system_code: CodeValue {
-                value: "76".to_string(),
-            },
+            system_code: CodeValue { value: "76".into() },
             values: vec![AdditionalClassificationValue {
                 code_value: gs1_risk.to_string(),
             }],
@@ -33,19 +34,21 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
         .status_code()
         .map(|s| mappings::device_status_to_gs1(&s).to_string())
         .unwrap_or_default();
+    let discontinued = crate::discontinued::is_no_longer(&status_code)
+        .then(|| crate::discontinued::date(&config.dates, None, None));
 
     // Manufacturer contact info
     let mut contacts = Vec::new();
     if let Some(ref mf_srn) = device.manufacturer_srn {
         contacts.push(TradeItemContactInformation {
             contact_type: CodeValue {
-                value: "EMA".to_string(),
+                value: "EMA".into(),
             },
             party_identification: vec![AdditionalPartyIdentification {
                 type_code: "SRN".to_string(),
-                value: mf_srn.clone(),
+                value: mf_srn.to_string(),
             }],
-            contact_name: device.manufacturer_name.clone(),
+            contact_name: device.manufacturer_name.as_deref().map(str::to_string),
             addresses: Vec::new(),
             communication_channels: Vec::new(),
         });
@@ -55,13 +58,16 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
     if let Some(ref ar_srn) = device.authorised_representative_srn {
         contacts.push(TradeItemContactInformation {
             contact_type: CodeValue {
-                value: "EAR".to_string(),
+                value: "EAR".into(),
             },
             party_identification: vec![AdditionalPartyIdentification {
                 type_code: "SRN".to_string(),
-                value: ar_srn.clone(),
+                value: ar_srn.to_string(),
             }],
-            contact_name: device.authorised_representative_name.clone(),
+            contact_name: device
+                .authorised_representative_name
+                .as_deref()
+                .map(str::to_string),
             addresses: Vec::new(),
             communication_channels: Vec::new(),
         });
@@ -74,13 +80,13 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
         .map(|tn| TradeItemDescriptionModule {
             info: TradeItemDescriptionInformation {
                 description_short: vec![LangValue {
-                    language_code: "en".to_string(),
+                    language_code: "en".into(),
                     value: crate::firstbase::truncate_short_description(tn),
                 }],
                 additional_descriptions: Vec::new(),
                 descriptions: vec![LangValue {
-                    language_code: "en".to_string(),
-                    value: tn.clone(),
+                    language_code: "en".into(),
+                    value: tn.to_string(),
                 }],
             },
         });
@@ -91,30 +97,23 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
         if reference != "-" && !reference.is_empty() {
             additional_identification.push(AdditionalTradeItemIdentification {
                 type_code: "MANUFACTURER_PART_NUMBER".to_string(),
-                value: reference.clone(),
+                value: reference.to_string(),
             });
         }
     }
 
-    // Sterile field - in the listing it's sometimes a number (0.0/1.0) or null
-    let sterile_bool = match &device.sterile {
-        Some(serde_json::Value::Bool(b)) => Some(*b),
-        Some(serde_json::Value::Number(n)) => n.as_f64().map(|f| f != 0.0),
-        _ => None,
-    };
-
-    let sterility = sterile_bool.map(|s| SterilityInformation {
+    let sterility = TriState::flag(device.sterile).map(|s| SterilityInformation {
         manufacturer_sterilisation: vec![CodeValue {
-            value: if s { "UNSPECIFIED" } else { "NOT_STERILISED" }.to_string(),
+            value: if s { "UNSPECIFIED" } else { "NOT_STERILISED" }.into(),
         }],
         prior_to_use: vec![CodeValue {
-            value: "NO_STERILISATION_REQUIRED".to_string(),
+            value: "NO_STERILISATION_REQUIRED".into(),
         }],
     });
 
-    TradeItem {
+    let mut item = TradeItem {
         is_brand_bank_publication: false,
-        target_sector: vec!["UDI_REGISTRY".to_string()],
+        target_sector: vec!["UDI_REGISTRY".into()],
         chemical_regulation_module: None,
         healthcare_item_module: None,
         medical_device_module: MedicalDeviceTradeItemModule {
@@ -142,7 +141,9 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
                 is_near_patient_testing: None,
                 is_professional_testing: None,
                 is_companion_diagnostic: None,
-                eu_status: CodeValue { value: status_code },
+                eu_status: CodeValue {
+                    value: status_code.into(),
+                },
                 reusability: None,
                 sterility,
             },
@@ -156,15 +157,17 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
         is_despatch_unit: true, // BASE_UNIT_OR_EACH is highest level = despatch unit
         is_orderable_unit: true,
         unit_descriptor: CodeValue {
-            value: "BASE_UNIT_OR_EACH".to_string(),
+            value: "BASE_UNIT_OR_EACH".into(),
         },
         trade_channel_code: vec![CodeValue {
-            value: "UDI_REGISTRY".to_string(),
+            value: "UDI_REGISTRY".into(),
         }],
         information_provider: InformationProvider {
             gln: config.provider.gln.clone(),
             party_name: config.provider.party_name.clone(),
         },
+        brand_owner: None,
+        manufacturer_of_trade_item: Vec::new(),
         classification: GdsnClassification {
             segment_code: config.gpc.segment_code.clone(),
             class_code: config.gpc.class_code.clone(),
@@ -176,7 +179,7 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
         next_lower_level: None,
         target_market: TargetMarketObj {
             country_code: CodeValue {
-                value: config.target_market.country_code.clone(),
+                value: config.target_market.country_code.clone().into(),
             },
         },
         contact_information: contacts,
@@ -184,13 +187,24 @@ pub fn transform_api_device(device: &ApiDevice, config: &Config) -> TradeItem {
             last_change: now_str.clone(),
             effective: now_str.clone(),
             publication: now_str,
-            discontinued: None,
+            discontinued,
         },
         // Only a valid GS1 GMN may go into globalModelNumber (097.116).
-        global_model_info: GlobalModelInformation::build(&basic_udi, Vec::new()),
+        global_model_info: GlobalModelInformation::build(basic_udi, Vec::new()),
         gtin,
         additional_identification,
         referenced_trade_items: Vec::new(),
         trade_item_information: Vec::new(),
-    }
+        avp_list: avp::build(
+            &config.avp,
+            &[
+                ("uuid", device.uuid.as_deref()),
+                ("version", version.as_deref()),
+                ("primary_di", device.primary_di.as_deref()),
+                ("basic_udi", Some(basic_udi)),
+            ],
+        ),
+    };
+    crate::ordering::trade_item(&mut item);
+    crate::transform::finish_trade_item(item, config)
 }
@@ -1,10 +1,12 @@
 use crate::api_detail::{
     ApiDeviceDetail, BasicUdiDiData, CmrSubstance, ContainedItemNode, Substance,
 };
+use crate::avp;
+use crate::certificates;
 use crate::config::Config;
+use crate::dates;
 use crate::firstbase::*;
 use crate::mappings;
-use chrono::Utc;
 
 /// GDSN limits additionalTradeItemIdentificationValue to 80 characters.
 fn truncate_id(s: String) -> String {
@@ -22,33 +24,22 @@ pub fn transform_detail_device(
     config: &Config,
     basic_udi: Option<&BasicUdiDiData>,
 ) -> TradeItem {
-    let now = Utc::now();
-    let now_str = now.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let now_str = dates::now(&config.dates);
 
     // Use version_date for effectiveDateTime; lastChangeDateTime uses current time (avoids SYS25 on re-uploads)
     let effective_date = device
         .version_date
-        .as_ref()
-        .filter(|d| !d.is_empty())
-        .cloned()
+        .as_deref()
+        .and_then(|d| dates::eudamed_timestamp(&config.dates, d))
         .unwrap_or_else(|| now_str.clone());
 
     let gtin = device.gtin();
+    let _warnings = crate::warnings::device_scope(&gtin);
 
     // --- Device status ---
     let eudamed_status = device.status_code().unwrap_or_default();
     let status_code = mappings::device_status_to_gs1(&eudamed_status).to_string();
 
-    // discontinuedDateTime: today+1 day when NO_LONGER_ON_THE_MARKET
-    let discontinued = if eudamed_status == "NO_LONGER_PLACED_ON_THE_MARKET"
-        || eudamed_status == "NO_LONGER_ON_THE_MARKET"
-    {
-        let tomorrow = now + chrono::Duration::days(1);
-        Some(tomorrow.format("%Y-%m-%dT%H:%M:%S").to_string())
-    } else {
-        None
-    };
-
     // --- Regulatory act (needed early for legacy detection) ---
     // Prefer legislation field (more accurate: distinguishes MDD from MDR for same risk classes)
     // Fall back to risk class inference
@@ -66,11 +57,11 @@ pub fn transform_detail_device(
 
     // 097.096: Since 2026-03-10, downgraded from error to warning — legacy devices publishable
     if is_legacy {
-        eprintln!(
+        crate::log_console::diag(&format!(
             "Info: {} is a legacy {} device (097.096 now warning only)",
             device.uuid.as_deref().unwrap_or("unknown"),
             reg_act
-        );
+        ));
     }
 
     // --- Production identifiers ---
@@ -82,7 +73,9 @@ pub fn transform_detail_device(
     } else {
         raw_production_ids
             .iter()
-            .map(|id| CodeValue { value: id.clone() })
+            .map(|id| CodeValue {
+                value: id.clone().into(),
+            })
             .collect()
     };
 
@@ -92,7 +85,7 @@ pub fn transform_detail_device(
         .any(|id| id == "SOFTWARE_IDENTIFICATION")
     {
         Some(CodeValue {
-            value: "SOFTWARE".to_string(),
+            value: "SOFTWARE".into(),
         })
     } else {
         None
@@ -124,9 +117,7 @@ pub fn transform_detail_device(
         ]
         .iter()
         .filter(|(flag, _)| *flag == Some(true))
-        .map(|(_, code)| CodeValue {
-            value: code.to_string(),
-        })
+        .map(|&(_, code)| CodeValue { value: code.into() })
         .collect()
     };
 
@@ -174,7 +165,7 @@ pub fn transform_detail_device(
             .and_then(|m| m.name.clone());
         contacts.push(TradeItemContactInformation {
             contact_type: CodeValue {
-                value: contact_type_code.to_string(),
+                value: contact_type_code.into(),
             },
             party_identification: vec![AdditionalPartyIdentification {
                 type_code: "SRN".to_string(),
@@ -195,7 +186,7 @@ pub fn transform_detail_device(
                 if let Some(ref ar_srn) = ar.srn {
                     contacts.push(TradeItemContactInformation {
                         contact_type: CodeValue {
-                            value: "EAR".to_string(),
+                            value: "EAR".into(),
                         },
                         party_identification: vec![AdditionalPartyIdentification {
                             type_code: "SRN".to_string(),
@@ -211,29 +202,30 @@ pub fn transform_detail_device(
     }
 
     // --- Trade name / description ---
-    let trade_names = device.trade_name_texts();
-    let additional_descs = device.additional_description_texts();
+    let languages = config.target_market.text_languages();
+    let trade_names = device.trade_name_texts(&languages);
+    let additional_descs = device.additional_description_texts(&languages);
     let description_module = if !trade_names.is_empty() || !additional_descs.is_empty() {
         Some(TradeItemDescriptionModule {
             info: TradeItemDescriptionInformation {
                 description_short: trade_names
                     .iter()
                     .map(|(lang, text)| LangValue {
-                        language_code: lang.clone(),
+                        language_code: mappings::language_code(lang),
                         value: crate::firstbase::truncate_short_description(text),
                     })
                     .collect(),
                 descriptions: trade_names
                     .iter()
                     .map(|(lang, text)| LangValue {
-                        language_code: lang.clone(),
+                        language_code: mappings::language_code(lang),
                         value: text.clone(),
                     })
                     .collect(),
                 additional_descriptions: additional_descs
                     .iter()
                     .map(|(lang, text)| LangValue {
-                        language_code: lang.clone(),
+                        language_code: mappings::language_code(lang),
                         value: text.clone(),
                     })
                     .collect(),
@@ -324,9 +316,12 @@ pub fn transform_detail_device(
         .as_ref()
         .map(|rc| mappings::risk_class_refdata_to_gs1(rc).to_string())
         .unwrap_or_else(|| {
-            eprintln!(
-                "WARNING: No riskClass for {} — BUDI cache miss? Using EU_CLASS_I",
-                device.uuid.as_deref().unwrap_or("unknown")
+            crate::warnings::warn(
+                crate::warnings::Warning::NoRiskClass,
+                &format!(
+                    "No riskClass for {} — BUDI cache miss? Using EU_CLASS_I",
+                    device.uuid.as_deref().unwrap_or("unknown")
+                ),
             );
             "EU_CLASS_I".to_string()
         });
@@ -341,20 +336,24 @@ pub fn transform_detail_device(
     };
     all_classifications.push(AdditionalClassification {
         system_code: CodeValue {
-            value: risk_class_system,
+            value: risk_class_system.into(),
         },
         values: vec![AdditionalClassificationValue {
             code_value: risk_class_gs1.clone(),
         }],
     });
 
+    let emdn_codes: Vec<&str> = device
+        .cnd_nomenclatures
+        .iter()
+        .flatten()
+        .filter_map(|cnd| cnd.code.as_deref())
+        .collect();
     if let Some(ref cnds) = device.cnd_nomenclatures {
         for cnd in cnds {
             if let Some(ref code) = cnd.code {
                 all_classifications.push(AdditionalClassification {
-                    system_code: CodeValue {
-                        value: "88".to_string(),
-                    },
+                    system_code: CodeValue { value: "88".into() },
                     values: vec![AdditionalClassificationValue {
                         code_value: code.clone(),
                     }],
@@ -366,8 +365,14 @@ pub fn transform_detail_device(
     // --- Healthcare item module (clinical sizes, storage, warnings, latex, tissue) ---
     // 097.078: all description fields must use consistent language codes
     let primary_lang = trade_names.first().map(|(l, _)| l.as_str()).unwrap_or("en");
-    let healthcare_module =
-        build_healthcare_module(device, basic_udi, is_ivdr, primary_lang, is_system_or_pack);
+    let healthcare_module = build_healthcare_module(
+        device,
+        basic_udi,
+        is_ivdr,
+        primary_lang,
+        is_system_or_pack,
+        &languages,
+    );
 
     // --- Chemical regulation module (substances) ---
     // Per Maik/EUDAMED: medicinalProduct (FLD-UDID-158) drives the medicinal
@@ -389,7 +394,7 @@ pub fn transform_detail_device(
                 media_source_gln: None,
                 mime_type: None,
                 file_type: CodeValue {
-                    value: "IFU".to_string(),
+                    value: "IFU".into(),
                 },
                 format_name: None,
                 file_name: None,
@@ -399,22 +404,36 @@ pub fn transform_detail_device(
         }
     });
 
+    // Permit fields from the certificate the device is placed on the market
+    // under, when EUDAMED lists one
+    let certificates = basic_udi
+        .and_then(|b| b.device_certificate_info_list_for_display.as_deref())
+        .unwrap_or_default();
     let regulated_trade_item_module = Some(RegulatedTradeItemModule {
-        info: vec![RegulatoryInformation {
-            act: reg_act.clone(),
-            agency: "EU".to_string(),
-        }],
+        info: vec![certificates::regulatory_information(&reg_act, certificates)],
     });
 
     // --- Sales module (market availability with ORIGINAL_PLACED distinction) ---
     // 097.021: NOT_INTENDED_FOR_EU_MARKET must not have country/sales data
     // 097.086: MDR SPP must NOT have targetMarketSalesConditions
-    let sales_module = if eudamed_status == "NOT_INTENDED_FOR_EU_MARKET" || is_system_or_pack {
+    let mut sales_module = if eudamed_status == "NOT_INTENDED_FOR_EU_MARKET" || is_system_or_pack {
         None
     } else {
-        build_sales_module(device, basic_udi)
+        build_sales_module(device, basic_udi, &config.dates)
     };
 
+    // discontinuedDateTime when NO_LONGER_ON_THE_MARKET: latest market end,
+    // else the status date (see `discontinued.rs`)
+    let discontinued = crate::discontinued::is_no_longer(&eudamed_status).then(|| {
+        let status_date = device
+            .device_status
+            .as_ref()
+            .and_then(|s| s.status_date.as_deref());
+        let date = crate::discontinued::date(&config.dates, status_date, sales_module.as_ref());
+        crate::discontinued::close_markets(&mut sales_module, &date);
+        date
+    });
+
     // --- Direct marking DI ---
     // 097.095: Legacy devices must not have directPartMarkingIdentifier
     let direct_marking = if is_legacy {
@@ -424,7 +443,7 @@ pub fn transform_detail_device(
     };
 
     // --- Certification module (097.101: MDR Class III needs certificate) ---
-    let certification_module = build_certification_module(basic_udi);
+    let certification_module = certificates::certification_module(certificates);
 
     // 097.101: MDR + EU_CLASS_III requires MDR_TECHNICAL_DOCUMENTATION or MDR_TYPE_EXAMINATION
     if reg_act == "MDR" && risk_class_gs1 == "EU_CLASS_III" {
@@ -435,8 +454,8 @@ pub fn transform_detail_device(
             })
         });
         if !has_required_cert {
-            eprintln!("Warning: {} is MDR Class III but has no MDR_TECHNICAL_DOCUMENTATION or MDR_TYPE_EXAMINATION certificate (097.101)",
-                device.uuid.as_deref().unwrap_or("unknown"));
+            crate::warnings::warn(crate::warnings::Warning::MdrCertificate, &format!("{} is MDR Class III but has no MDR_TECHNICAL_DOCUMENTATION or MDR_TYPE_EXAMINATION certificate (097.101)",
+                device.uuid.as_deref().unwrap_or("unknown")));
         }
     }
 
@@ -451,16 +470,20 @@ pub fn transform_detail_device(
             cm.infos.iter().any(|ci| ci.standard.starts_with("MDD_"))
         });
         if !has_mdd_cert {
-            eprintln!(
-                "Warning: {} is MDD {} but has no MDD certificate (097.105)",
-                device.uuid.as_deref().unwrap_or("unknown"),
-                risk_class_gs1
+            crate::warnings::warn(
+                crate::warnings::Warning::MddCertificate,
+                &format!(
+                    "{} is MDD {} but has no MDD certificate (097.105)",
+                    device.uuid.as_deref().unwrap_or("unknown"),
+                    risk_class_gs1
+                ),
             );
         }
     }
 
     // --- Unit of Use DI (FLD-UDDI-135) ---
     let trade_item_information = build_unit_of_use(device);
+    let version = device.version_number.map(|v| v.to_string());
 
     // --- Related devices (REPLACED/REPLACED_BY) ---
     let referenced_trade_items = build_referenced_trade_items(device);
@@ -475,7 +498,7 @@ pub fn transform_detail_device(
 
     TradeItem {
         is_brand_bank_publication: false,
-        target_sector: vec!["UDI_REGISTRY".to_string()],
+        target_sector: vec!["UDI_REGISTRY".into()],
         chemical_regulation_module,
         healthcare_item_module: healthcare_module,
         medical_device_module: MedicalDeviceTradeItemModule {
@@ -567,7 +590,8 @@ pub fn transform_detail_device(
                         value: basic_udi
                             .and_then(|b| b.multi_component_raw_code())
                             .map(|c| mappings::multi_component_to_gs1(&c).to_string())
-                            .unwrap_or_else(|| "DEVICE".to_string()),
+                            .unwrap_or_else(|| "DEVICE".to_string())
+                            .into(),
                     })
                 },
                 system_or_procedure_pack_type: if is_system_or_pack {
@@ -575,7 +599,8 @@ pub fn transform_detail_device(
                         value: basic_udi
                             .and_then(|b| b.multi_component_raw_code())
                             .map(|c| mappings::spp_type_to_gs1(&c).to_string())
-                            .unwrap_or_else(|| "PROCEDURE_PACK".to_string()),
+                            .unwrap_or_else(|| "PROCEDURE_PACK".to_string())
+                            .into(),
                     })
                 } else {
                     None
@@ -584,7 +609,7 @@ pub fn transform_detail_device(
                 // Source: BUDI medicalPurpose (NOT additionalDescription — those are separate fields)
                 system_or_procedure_pack_purpose: if is_system_or_pack {
                     let purpose_texts = basic_udi
-                        .map(|b| b.medical_purpose_texts())
+                        .map(|b| b.medical_purpose_texts(&languages))
                         .unwrap_or_default();
                     if purpose_texts.is_empty() {
                         // Fallback: use device name from BUDI
@@ -594,14 +619,14 @@ pub fn transform_detail_device(
                             .cloned()
                             .unwrap_or_else(|| device.primary_di_code());
                         vec![LangValue {
-                            language_code: primary_lang.to_string(),
+                            language_code: mappings::language_code(primary_lang),
                             value: name,
                         }]
                     } else {
                         purpose_texts
                             .iter()
                             .map(|(lang, text)| LangValue {
-                                language_code: lang.clone(),
+                                language_code: mappings::language_code(lang),
                                 value: text.clone(),
                             })
                             .collect()
@@ -623,7 +648,7 @@ pub fn transform_detail_device(
                 is_professional_testing: if is_ivdr { Some(false) } else { None },
                 is_companion_diagnostic: if is_ivdr { Some(false) } else { None },
                 eu_status: CodeValue {
-                    value: status_code.clone(),
+                    value: status_code.clone().into(),
                 },
                 reusability,
                 sterility,
@@ -638,27 +663,27 @@ pub fn transform_detail_device(
         is_despatch_unit: true, // BASE_UNIT_OR_EACH is highest level = despatch unit
         is_orderable_unit: true,
         unit_descriptor: CodeValue {
-            value: "BASE_UNIT_OR_EACH".to_string(),
+            value: "BASE_UNIT_OR_EACH".into(),
         },
         trade_channel_code: vec![CodeValue {
-            value: "UDI_REGISTRY".to_string(),
+            value: "UDI_REGISTRY".into(),
         }],
         information_provider: InformationProvider {
             gln: config.provider.gln.clone(),
             party_name: config.provider.party_name.clone(),
         },
-        classification: GdsnClassification {
-            segment_code: config.gpc.segment_code.clone(),
-            class_code: config.gpc.class_code.clone(),
-            family_code: config.gpc.family_code.clone(),
-            category_code: config.gpc.category_code.clone(),
-            category_name: config.gpc.category_name.clone(),
-            additional_classifications: all_classifications,
-        },
+        brand_owner: None,
+        manufacturer_of_trade_item: Vec::new(),
+        classification: crate::gpc::classification(
+            config,
+            device.uuid.as_deref().unwrap_or(""),
+            &emdn_codes,
+            all_classifications,
+        ),
         next_lower_level: None,
         target_market: TargetMarketObj {
             country_code: CodeValue {
-                value: config.target_market.country_code.clone(),
+                value: config.target_market.country_code.clone().into(),
             },
         },
         contact_information: contacts,
@@ -683,7 +708,7 @@ pub fn transform_detail_device(
                 .filter(|n| !n.is_empty())
                 .map(|n| {
                     vec![LangValue {
-                        language_code: "en".to_string(),
+                        language_code: "en".into(),
                         value: n.clone(),
                     }]
                 })
@@ -694,6 +719,24 @@ pub fn transform_detail_device(
         additional_identification,
         referenced_trade_items,
         trade_item_information,
+        avp_list: avp::build(
+            &config.avp,
+            &[
+                ("uuid", device.uuid.as_deref()),
+                ("version", version.as_deref()),
+                ("version_date", device.version_date.as_deref()),
+                (
+                    "primary_di",
+                    device.primary_di.as_ref().and_then(|d| d.code.as_deref()),
+                ),
+                (
+                    "basic_udi",
+                    basic_udi
+                        .and_then(|b| b.basic_udi.as_ref())
+                        .and_then(|d| d.code.as_deref()),
+                ),
+            ],
+        ),
     }
 }
 
@@ -703,17 +746,17 @@ fn build_sterility(device: &ApiDeviceDetail, _config: &Config) -> Option<Sterili
 
     let manufacturer_sterilisation = vec![CodeValue {
         value: if sterile {
-            "UNSPECIFIED".to_string()
+            "UNSPECIFIED".into()
         } else {
-            "NOT_STERILISED".to_string()
+            "NOT_STERILISED".into()
         },
     }];
 
     let prior_to_use = vec![CodeValue {
         value: if sterilization {
-            "UNSPECIFIED".to_string()
+            "UNSPECIFIED".into()
         } else {
-            "NO_STERILISATION_REQUIRED".to_string()
+            "NO_STERILISATION_REQUIRED".into()
         },
     }];
 
@@ -729,7 +772,7 @@ fn build_reusability(device: &ApiDeviceDetail) -> Option<ReusabilityInformation>
     if single_use {
         Some(ReusabilityInformation {
             reusability_type: CodeValue {
-                value: "SINGLE_USE".to_string(),
+                value: "SINGLE_USE".into(),
             },
             max_cycles: None,
         })
@@ -738,14 +781,14 @@ fn build_reusability(device: &ApiDeviceDetail) -> Option<ReusabilityInformation>
         if max.is_some() {
             Some(ReusabilityInformation {
                 reusability_type: CodeValue {
-                    value: "LIMITED_REUSABLE".to_string(),
+                    value: "LIMITED_REUSABLE".into(),
                 },
                 max_cycles: max,
             })
         } else {
             Some(ReusabilityInformation {
                 reusability_type: CodeValue {
-                    value: "REUSABLE".to_string(),
+                    value: "REUSABLE".into(),
                 },
                 max_cycles: None,
             })
@@ -811,7 +854,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
                 let country_numeric = actor
                     .country_iso2_code
                     .as_ref()
-                    .map(|c| mappings::country_alpha2_to_numeric(c).to_string())
+                    .map(|c| mappings::country_alpha2_to_numeric(c))
                     .unwrap_or_default();
                 addresses.push(StructuredAddress {
                     city,
@@ -834,7 +877,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
                     channels.push(TargetMarketCommunicationChannel {
                         channels: vec![CommunicationChannel {
                             channel_code: CodeValue {
-                                value: "TELEPHONE".to_string(),
+                                value: "TELEPHONE".into(),
                             },
                             value: phone.clone(),
                         }],
@@ -846,7 +889,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
                     channels.push(TargetMarketCommunicationChannel {
                         channels: vec![CommunicationChannel {
                             channel_code: CodeValue {
-                                value: "EMAIL".to_string(),
+                                value: "EMAIL".into(),
                             },
                             value: email.clone(),
                         }],
@@ -856,7 +899,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
 
             contacts.push(TradeItemContactInformation {
                 contact_type: CodeValue {
-                    value: "EPD".to_string(),
+                    value: "EPD".into(),
                 },
                 party_identification: party_ids,
                 contact_name: actor.name.clone(),
@@ -869,7 +912,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
             if let Some((street, number, postal, city)) = org.structured_address() {
                 let country_numeric = org
                     .country_iso2()
-                    .map(|c| mappings::country_alpha2_to_numeric(&c).to_string())
+                    .map(|c| mappings::country_alpha2_to_numeric(&c))
                     .unwrap_or_default();
                 addresses.push(StructuredAddress {
                     city,
@@ -892,7 +935,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
                     channels.push(TargetMarketCommunicationChannel {
                         channels: vec![CommunicationChannel {
                             channel_code: CodeValue {
-                                value: "TELEPHONE".to_string(),
+                                value: "TELEPHONE".into(),
                             },
                             value: phone.clone(),
                         }],
@@ -904,7 +947,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
                     channels.push(TargetMarketCommunicationChannel {
                         channels: vec![CommunicationChannel {
                             channel_code: CodeValue {
-                                value: "EMAIL".to_string(),
+                                value: "EMAIL".into(),
                             },
                             value: email.clone(),
                         }],
@@ -914,7 +957,7 @@ fn build_contacts(device: &ApiDeviceDetail) -> Vec<TradeItemContactInformation>
 
             contacts.push(TradeItemContactInformation {
                 contact_type: CodeValue {
-                    value: "EPD".to_string(),
+                    value: "EPD".into(),
                 },
                 party_identification: Vec::new(),
                 contact_name: org.name.clone(),
@@ -933,10 +976,11 @@ fn build_healthcare_module(
     is_ivdr: bool,
     primary_lang: &str,
     is_system_or_pack: bool,
+    languages: &[String],
 ) -> Option<HealthcareItemInformationModule> {
     let clinical_sizes = build_clinical_sizes(device);
-    let storage_handling = build_storage_handling(device, primary_lang);
-    let clinical_warnings = build_clinical_warnings(device);
+    let storage_handling = build_storage_handling(device, primary_lang, languages);
+    let clinical_warnings = build_clinical_warnings(device, languages);
     let contains_latex = Some(
         device
             .latex
@@ -1019,9 +1063,7 @@ fn build_clinical_sizes(device: &ApiDeviceDetail) -> Vec<ClinicalSizeOutput> {
                 .as_deref()
                 .and_then(mappings::mu_code_to_characteristic_code)
             {
-                Some(code) => vec![CodeValue {
-                    value: code.to_string(),
-                }],
+                Some(code) => vec![CodeValue { value: code.into() }],
                 None => Vec::new(),
             };
 
@@ -1045,19 +1087,19 @@ fn build_clinical_sizes(device: &ApiDeviceDetail) -> Vec<ClinicalSizeOutput> {
 
             if let Some(v) = cs.value {
                 values.push(MeasurementValue {
-                    unit_code: unit_code.clone(),
+                    unit_code: unit_code.clone().into(),
                     value: v,
                 });
             } else if let Some(min) = cs.minimum_value {
                 values.push(MeasurementValue {
-                    unit_code: unit_code.clone(),
+                    unit_code: unit_code.clone().into(),
                     value: min,
                 });
             }
 
             if let Some(max) = cs.maximum_value {
                 maximums.push(MeasurementValue {
-                    unit_code: unit_code.clone(),
+                    unit_code: unit_code.clone().into(),
                     value: max,
                 });
             }
@@ -1066,7 +1108,7 @@ fn build_clinical_sizes(device: &ApiDeviceDetail) -> Vec<ClinicalSizeOutput> {
             let descriptions = if gs1_type == "DEVICE_SIZE_TEXT_SPECIFY" {
                 let desc = cs.text.as_deref().unwrap_or("Other");
                 vec![LangValue {
-                    language_code: "en".to_string(),
+                    language_code: "en".into(),
                     value: desc.to_string(),
                 }]
             } else {
@@ -1079,12 +1121,12 @@ fn build_clinical_sizes(device: &ApiDeviceDetail) -> Vec<ClinicalSizeOutput> {
             Some(ClinicalSizeOutput {
                 descriptions,
                 type_code: CodeValue {
-                    value: gs1_type.to_string(),
+                    value: gs1_type.to_string().into(),
                 },
                 values,
                 maximums,
                 precision: CodeValue {
-                    value: precision_code.to_string(),
+                    value: precision_code.to_string().into(),
                 },
                 text: cs.text.clone(),
                 characteristic_codes,
@@ -1096,6 +1138,7 @@ fn build_clinical_sizes(device: &ApiDeviceDetail) -> Vec<ClinicalSizeOutput> {
 fn build_storage_handling(
     device: &ApiDeviceDetail,
     primary_lang: &str,
+    languages: &[String],
 ) -> Vec<ClinicalStorageHandling> {
     let conditions = match device.storage_handling_conditions.as_ref() {
         Some(c) if !c.is_empty() => c,
@@ -1109,7 +1152,7 @@ fn build_storage_handling(
             let shc_code = extract_shc_code(type_code_raw);
             let gs1_code = mappings::storage_handling_to_gs1(&shc_code);
 
-            let mut descriptions = extract_descriptions(&shc.description);
+            let mut descriptions = extract_descriptions(&shc.description, languages);
             // 097.074 / BR-UDID-028: these SHC codes require a description
             // 097.078: fallback language must match primary language of other descriptions
             let needs_description = matches!(
@@ -1128,20 +1171,25 @@ fn build_storage_handling(
             );
             if descriptions.is_empty() && needs_description {
                 descriptions.push(LangValue {
-                    language_code: primary_lang.to_string(),
+                    language_code: mappings::language_code(primary_lang),
                     value: gs1_code.clone(),
                 });
             }
 
             Some(ClinicalStorageHandling {
-                type_code: CodeValue { value: gs1_code },
+                type_code: CodeValue {
+                    value: gs1_code.into(),
+                },
                 descriptions,
             })
         })
         .collect()
 }
 
-fn build_clinical_warnings(device: &ApiDeviceDetail) -> Vec<ClinicalWarningOutput> {
+fn build_clinical_warnings(
+    device: &ApiDeviceDetail,
+    languages: &[String],
+) -> Vec<ClinicalWarningOutput> {
     let warnings = match device.critical_warnings.as_ref() {
         Some(w) if !w.is_empty() => w,
         _ => return Vec::new(),
@@ -1153,11 +1201,11 @@ fn build_clinical_warnings(device: &ApiDeviceDetail) -> Vec<ClinicalWarningOutpu
             let type_code_raw = cw.type_code.as_ref()?;
             let cw_code = extract_last_segment(type_code_raw).to_uppercase();
 
-            let descriptions = extract_descriptions(&cw.description);
+            let descriptions = extract_descriptions(&cw.description, languages);
 
             Some(ClinicalWarningOutput {
                 agency_code: CodeValue {
-                    value: "EUDAMED".to_string(),
+                    value: "EUDAMED".into(),
                 },
                 warning_code: cw_code,
                 descriptions,
@@ -1170,6 +1218,7 @@ fn build_clinical_warnings(device: &ApiDeviceDetail) -> Vec<ClinicalWarningOutpu
 fn build_sales_module(
     device: &ApiDeviceDetail,
     basic_udi: Option<&BasicUdiDiData>,
+    zones: &crate::config::Dates,
 ) -> Option<SalesInformationModule> {
     // Determine which country is the "original placed" market
     let original_iso2 = device
@@ -1198,11 +1247,16 @@ fn build_sales_module(
             }
             let numeric = mappings::country_alpha2_to_numeric(iso2);
             let country = SalesConditionCountry {
-                country_code: CodeValue {
-                    value: numeric.to_string(),
-                },
-                start_datetime: ma.start_date.clone().unwrap_or_default(),
-                end_datetime: ma.end_date.clone(),
+                country_code: CodeValue { value: numeric },
+                start_datetime: ma
+                    .start_date
+                    .as_deref()
+                    .map(|d| dates::day_at(zones, d, dates::START_OF_DAY))
+                    .unwrap_or_default(),
+                end_datetime: ma
+                    .end_date
+                    .as_deref()
+                    .map(|d| dates::day_at(zones, d, dates::END_OF_DAY)),
             };
 
             if original_iso2 == Some(iso2.as_str()) {
@@ -1220,9 +1274,7 @@ fn build_sales_module(
             if mappings::is_valid_gdsn_market_country(iso2) {
                 let numeric = mappings::country_alpha2_to_numeric(iso2);
                 original_countries.push(SalesConditionCountry {
-                    country_code: CodeValue {
-                        value: numeric.to_string(),
-                    },
+                    country_code: CodeValue { value: numeric },
                     start_datetime: String::new(),
                     end_datetime: None,
                 });
@@ -1252,9 +1304,7 @@ fn build_sales_module(
             .unwrap_or_else(|| "DE".to_string());
         let numeric = mappings::country_alpha2_to_numeric(&fallback_iso2);
         original_countries.push(SalesConditionCountry {
-            country_code: CodeValue {
-                value: numeric.to_string(),
-            },
+            country_code: CodeValue { value: numeric },
             start_datetime: String::new(),
             end_datetime: None,
         });
@@ -1273,7 +1323,7 @@ fn build_sales_module(
     if !original_countries.is_empty() {
         conditions.push(TargetMarketSalesCondition {
             condition_code: CodeValue {
-                value: "ORIGINAL_PLACED".to_string(),
+                value: "ORIGINAL_PLACED".into(),
             },
             countries: original_countries,
         });
@@ -1281,7 +1331,7 @@ fn build_sales_module(
     if !additional_countries.is_empty() {
         conditions.push(TargetMarketSalesCondition {
             condition_code: CodeValue {
-                value: "ADDITIONAL_MARKET_AVAILABILITY".to_string(),
+                value: "ADDITIONAL_MARKET_AVAILABILITY".into(),
             },
             countries: additional_countries,
         });
@@ -1315,8 +1365,14 @@ fn build_direct_marking(device: &ApiDeviceDetail) -> Vec<DirectPartMarking> {
 
     // 097.118: GS1 direct marking DI must be exactly 14 digits
     if agency == "GS1" && (code.len() != 14 || !code.chars().all(|c| c.is_ascii_digit())) {
-        eprintln!("Warning: {} has invalid GS1 direct marking DI '{}' (not 14 digits), skipping (097.118)",
-            device.uuid.as_deref().unwrap_or("unknown"), code);
+        crate::warnings::warn(
+            crate::warnings::Warning::DirectMarkingDi,
+            &format!(
+                "{} has invalid GS1 direct marking DI '{}' (not 14 digits), skipping (097.118)",
+                device.uuid.as_deref().unwrap_or("unknown"),
+                code
+            ),
+        );
         return Vec::new();
     }
 
@@ -1381,125 +1437,13 @@ fn build_referenced_trade_items(device: &ApiDeviceDetail) -> Vec<ReferencedTrade
     };
     vec![ReferencedTradeItem {
         type_code: CodeValue {
-            value: type_code.to_string(),
+            value: type_code.into(),
         },
         gtin,
     }]
 }
 
 /// Build chemical regulation module from substances.
-/// Build certification module from Basic UDI-DI certificate list.
-/// Maps MDR/IVDR certificate types to GS1 CertificationStandard codes.
-fn build_certification_module(
-    basic_udi: Option<&BasicUdiDiData>,
-) -> Option<CertificationInformationModule> {
-    let certs = basic_udi?
-        .device_certificate_info_list_for_display
-        .as_ref()?;
-    let mut infos = Vec::new();
-
-    for cert in certs {
-        let type_code = cert.certificate_type.as_ref()?.code.as_ref()?;
-        let suffix = type_code.rsplit('.').next().unwrap_or(type_code);
-
-        // Map EUDAMED certificate types to GS1 CertificationStandard
-        // DeviceCertificateInfo (manufacturer-provided) + CertificateLink (NB-provided)
-        let standard = match suffix {
-            "technical-documentation" => {
-                if type_code.contains("mdr") {
-                    "MDR_TECHNICAL_DOCUMENTATION"
-                } else if type_code.contains("ivdr") {
-                    "IVDR_TECHNICAL_DOCUMENTATION"
-                } else {
-                    continue;
-                }
-            }
-            "type-examination" => {
-                if type_code.contains("mdr") {
-                    "MDR_TYPE_EXAMINATION"
-                } else if type_code.contains("ivdr") {
-                    "IVDR_TYPE_EXAMINATION"
-                } else {
-                    continue;
-                }
-            }
-            // NB-provided MDR/IVDR certificates (CertificateLink: FLD-UDID-360)
-            "quality-management-system" => {
-                if type_code.contains("mdr") {
-                    "MDR_QUALITY_MANAGEMENT_SYSTEM"
-                } else if type_code.contains("ivdr") {
-                    "IVDR_QUALITY_MANAGEMENT_SYSTEM"
-                } else {
-                    continue;
-                }
-            }
-            "quality-assurance" => {
-                if type_code.contains("mdr") {
-                    "MDR_QUALITY_ASSURANCE"
-                } else if type_code.contains("ivdr") {
-                    "IVDR_QUALITY_ASSURANCE"
-                } else {
-                    continue;
-                }
-            }
-            // MDD legacy certificates (097.105)
-            "ii-4" => "MDD_II_4",
-            "ii-excluding-4" => "MDD_II_EX_4",
-            "iii" if type_code.contains("mdd") => "MDD_III",
-            "iv" => "MDD_IV",
-            "v" => "MDD_V",
-            "vi" => "MDD_VI",
-            _ => continue,
-        };
-
-        let nb = cert.notified_body.as_ref();
-        let nb_number = nb.and_then(|n| n.srn.clone());
-        infos.push(CertificationInformation {
-            // 097.042: additionalCertificationOrganisationIdentifier with EU_NOTIFIED_BODY_NUMBER
-            additional_org_ids: nb_number
-                .map(|num| {
-                    vec![AdditionalPartyIdentification {
-                        type_code: "EU_NOTIFIED_BODY_NUMBER".to_string(),
-                        value: num,
-                    }]
-                })
-                .unwrap_or_default(),
-            agency: nb.and_then(|n| n.name.clone()),
-            organisation_identifier: None,
-            standard: standard.to_string(),
-            certifications: {
-                let mut cs = Vec::new();
-                // FLD-UDID-347/346: startingValidityDate, fallback to issueDate
-                let start = cert
-                    .starting_validity_date
-                    .clone()
-                    .or_else(|| cert.issue_date.clone());
-                if cert.certificate_number.is_some()
-                    || cert.certificate_expiry.is_some()
-                    || start.is_some()
-                {
-                    cs.push(Certification {
-                        // FLD-UDID-61/344 (097.105): CertificationValue = certificate number
-                        value: cert.certificate_number.clone(),
-                        // FLD-UDID-62/345: CertificationIdentification = revision number
-                        identification: cert.certificate_revision.clone(),
-                        // FLD-UDID-64/348: CertificationEffectiveEndDateTime = expiry date
-                        effective_end: cert.certificate_expiry.clone(),
-                        effective_start: start,
-                    });
-                }
-                cs
-            },
-        });
-    }
-
-    if infos.is_empty() {
-        None
-    } else {
-        Some(CertificationInformationModule { infos })
-    }
-}
-
 fn build_chemical_regulation_module(
     device: &ApiDeviceDetail,
     is_legacy: bool,
@@ -1611,7 +1555,7 @@ fn build_substance_chemical(sub: &Substance, chemical_type: &str) -> RegulatedCh
             .or_else(|| inn.clone())
             .unwrap_or_else(|| chemical_type.to_string());
         vec![LangValue {
-            language_code: "en".to_string(),
+            language_code: "en".into(),
             value: desc,
         }]
     } else {
@@ -1624,7 +1568,7 @@ fn build_substance_chemical(sub: &Substance, chemical_type: &str) -> RegulatedCh
         descriptions,
         cmr_type: None,
         chemical_type: CodeValue {
-            value: chemical_type.to_string(),
+            value: chemical_type.to_string().into(),
         },
     }
 }
@@ -1666,7 +1610,7 @@ fn build_cmr_chemical(sub: &CmrSubstance) -> RegulatedChemical {
         .as_ref()
         .and_then(|t| t.code.as_ref())
         .map(|c| CodeValue {
-            value: mappings::cmr_type_to_gs1(c),
+            value: mappings::cmr_type_to_gs1(c).into(),
         });
 
     // 097.081/097.080: CMR_SUBSTANCE always needs description with languageCode "en"
@@ -1676,7 +1620,7 @@ fn build_cmr_chemical(sub: &CmrSubstance) -> RegulatedChemical {
             .map(|n| n.trim().to_string())
             .unwrap_or_else(|| "CMR_SUBSTANCE".to_string());
         vec![LangValue {
-            language_code: "en".to_string(),
+            language_code: "en".into(),
             value: desc,
         }]
     };
@@ -1687,7 +1631,7 @@ fn build_cmr_chemical(sub: &CmrSubstance) -> RegulatedChemical {
         descriptions,
         cmr_type,
         chemical_type: CodeValue {
-            value: "CMR_SUBSTANCE".to_string(),
+            value: "CMR_SUBSTANCE".into(),
         },
     }
 }
@@ -1731,43 +1675,16 @@ fn extract_last_segment(code: &str) -> String {
     code.rsplit('.').next().unwrap_or(code).to_string()
 }
 
-/// Extract multilang descriptions from a MultiLangText
-fn extract_descriptions(mlt: &Option<crate::api_detail::MultiLangText>) -> Vec<LangValue> {
-    let raw: Vec<(String, String)> = mlt
-        .as_ref()
-        .and_then(|t| t.texts.as_ref())
-        .map(|texts| {
-            texts
-                .iter()
-                .filter_map(|lt| {
-                    let text = lt.text.clone()?;
-                    if text.is_empty() {
-                        return None;
-                    }
-                    // language: null → default to "en" (same as allLanguagesApplicable)
-                    let lang = lt
-                        .language
-                        .as_ref()
-                        .and_then(|l| l.iso_code.clone())
-                        .unwrap_or_else(|| "en".to_string());
-                    Some((lang, text))
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-    // Merge duplicate languages with " / " (097.078: at most one iteration per languageCode)
-    let mut map: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
-    for (lang, text) in raw {
-        map.entry(lang)
-            .and_modify(|existing| {
-                existing.push_str(" / ");
-                existing.push_str(&text);
-            })
-            .or_insert(text);
-    }
-    map.into_iter()
+/// Extract multilang descriptions from a MultiLangText; a text without
+/// language counts as "all languages applicable" (see `api_detail::lang_texts`)
+fn extract_descriptions(
+    mlt: &Option<crate::api_detail::MultiLangText>,
+    languages: &[String],
+) -> Vec<LangValue> {
+    crate::api_detail::lang_texts(mlt.as_ref(), languages, true)
+        .into_iter()
         .map(|(lang, text)| LangValue {
-            language_code: lang,
+            language_code: mappings::language_code(&lang),
             value: text,
         })
         .collect()
@@ -1859,11 +1776,14 @@ pub fn transform_detail_document(
 
     if levels.is_empty() {
         // No packaging — simple document, base unit is despatch unit
-        return FirstbaseDocument {
+        let mut document = FirstbaseDocument {
             trade_item: base_trade_item,
             children: Vec::new(),
             identifier: format!("Draft_{}", stem),
         };
+        crate::ordering::document(&mut document);
+        crate::transform::finish_document(&mut document, config);
+        return document;
     }
 
     // Base unit is no longer the despatch unit when packages exist
@@ -1895,7 +1815,7 @@ pub fn transform_detail_document(
         .filter(|n| !n.is_empty())
         .map(|n| {
             vec![LangValue {
-                language_code: "en".to_string(),
+                language_code: "en".into(),
                 value: n.clone(),
             }]
         })
@@ -1956,11 +1876,11 @@ pub fn transform_detail_document(
             }],
         };
 
-        let now_str = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        let now_str = dates::now(&config.dates);
 
         let pkg_trade_item = TradeItem {
             is_brand_bank_publication: false,
-            target_sector: vec!["UDI_REGISTRY".to_string()],
+            target_sector: vec!["UDI_REGISTRY".into()],
             chemical_regulation_module: None,
             healthcare_item_module: None,
             // Package level inherits eu_status from the base unit. Hardcoding ON_MARKET
@@ -1985,6 +1905,7 @@ pub fn transform_detail_document(
                     info: vec![RegulatoryInformation {
                         act: pkg_reg_act,
                         agency: "EU".to_string(),
+                        ..Default::default()
                     }],
                 })
             },
@@ -1994,15 +1915,17 @@ pub fn transform_detail_document(
             is_despatch_unit: is_outermost,
             is_orderable_unit: true,
             unit_descriptor: CodeValue {
-                value: descriptor.to_string(),
+                value: descriptor.into(),
             },
             trade_channel_code: vec![CodeValue {
-                value: "UDI_REGISTRY".to_string(),
+                value: "UDI_REGISTRY".into(),
             }],
             information_provider: InformationProvider {
                 gln: config.provider.gln.clone(),
                 party_name: config.provider.party_name.clone(),
             },
+            brand_owner: None,
+            manufacturer_of_trade_item: Vec::new(),
             classification: GdsnClassification {
                 segment_code: config.gpc.segment_code.clone(),
                 class_code: config.gpc.class_code.clone(),
@@ -2014,7 +1937,7 @@ pub fn transform_detail_document(
             next_lower_level: Some(next_lower),
             target_market: TargetMarketObj {
                 country_code: CodeValue {
-                    value: config.target_market.country_code.clone(),
+                    value: config.target_market.country_code.clone().into(),
                 },
             },
             contact_information: pkg_contacts.clone(),
@@ -2044,6 +1967,7 @@ pub fn transform_detail_document(
                 .unwrap_or_default(),
             referenced_trade_items: Vec::new(),
             trade_item_information: Vec::new(),
+            avp_list: None,
         };
 
         inner_link = CatalogueItemChildItemLink {
@@ -2059,9 +1983,13 @@ pub fn transform_detail_document(
     // The outermost package is the top-level trade item
     let top_catalogue = inner_link.catalogue_item;
 
-    FirstbaseDocument {
+    let mut document = FirstbaseDocument {
         trade_item: top_catalogue.trade_item,
         children: top_catalogue.children,
         identifier: format!("Draft_{}", stem),
-    }
+    };
+    crate::gpc::apply_to_packages(&mut document);
+    crate::ordering::document(&mut document);
+    crate::transform::finish_document(&mut document, config);
+    document
 }
@@ -1,24 +1,51 @@
+use crate::api_detail::{self, MultiLangText};
+use crate::avp;
 use crate::config::Config;
+use crate::dates;
+use crate::device_category::{self, Category};
 use crate::eudamed_json::EudamedDevice;
 use crate::firstbase::*;
 use crate::mappings;
-use chrono::Utc;
 
 /// Transform an EUDAMED JSON device record into a firstbase TradeItem.
 pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> TradeItem {
-    let now = Utc::now();
-    let now_str = now.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let now_str = dates::now(&config.dates);
 
     let basic_udi = device.basic_udi_code();
+    // Device-level records have no GTIN yet
+    let _warnings = crate::warnings::device_scope(&basic_udi);
+    let version = avp::json_text(device.version_number.as_ref());
+
+    // "refdata.applicable-legislation.mdr" → "MDR"
+    let reg_act = device
+        .legislation
+        .as_ref()
+        .and_then(|l| l.code.as_deref())
+        .map(|c| c.rsplit('.').next().unwrap_or(c).to_uppercase());
+
+    // System / procedure pack under MDR: SPP branch (see device_category)
+    let is_mdr = reg_act.as_deref() == Some("MDR");
+    let spp_type = match device_category::classify(
+        device.device_criterion.as_deref(),
+        device.container_type.as_deref(),
+    ) {
+        Category::SystemOrProcedurePack { type_code } if is_mdr => Some(type_code),
+        _ => None,
+    };
+
+    // Notified body certificates: permit of the regulatory information and
+    // the certification module (see certificates.rs)
+    let certificates = device
+        .device_certificate_info_list_for_display
+        .as_deref()
+        .unwrap_or_default();
 
     // Risk class → AdditionalTradeItemClassification (system 76)
     let mut additional_classifications = Vec::new();
     if let Some(rc) = device.risk_class_code() {
         let gs1_risk = mappings::risk_class_to_gs1(&rc);
         additional_classifications.push(AdditionalClassification {
-            system_code: CodeValue {
-                value: "76".to_string(),
-            },
+            system_code: CodeValue { value: "76".into() },
             values: vec![AdditionalClassificationValue {
                 code_value: gs1_risk.to_string(),
             }],
@@ -35,7 +62,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                     addresses.push(StructuredAddress {
                         city: String::new(),
                         country_code: CodeValue {
-                            value: mfr.country_iso2_code.clone().unwrap_or_default(),
+                            value: mfr.country_iso2_code.clone().unwrap_or_default().into(),
                         },
                         postal_code: String::new(),
                         street: addr.clone(),
@@ -49,7 +76,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 if !email.is_empty() {
                     comm_channels.push(CommunicationChannel {
                         channel_code: CodeValue {
-                            value: "EMAIL".to_string(),
+                            value: "EMAIL".into(),
                         },
                         value: email.clone(),
                     });
@@ -59,7 +86,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 if !phone.is_empty() {
                     comm_channels.push(CommunicationChannel {
                         channel_code: CodeValue {
-                            value: "TELEPHONE".to_string(),
+                            value: "TELEPHONE".into(),
                         },
                         value: phone.clone(),
                     });
@@ -74,9 +101,10 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 }]
             };
 
+            // 097.016: SPP under MDR ⇒ ContactType EPP
             contacts.push(TradeItemContactInformation {
                 contact_type: CodeValue {
-                    value: "EMA".to_string(),
+                    value: if spp_type.is_some() { "EPP" } else { "EMA" }.into(),
                 },
                 party_identification: vec![AdditionalPartyIdentification {
                     type_code: "SRN".to_string(),
@@ -98,7 +126,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                     addresses.push(StructuredAddress {
                         city: String::new(),
                         country_code: CodeValue {
-                            value: String::new(),
+                            value: Code::default(),
                         },
                         postal_code: String::new(),
                         street: addr.clone(),
@@ -112,7 +140,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 if !email.is_empty() {
                     comm_channels.push(CommunicationChannel {
                         channel_code: CodeValue {
-                            value: "EMAIL".to_string(),
+                            value: "EMAIL".into(),
                         },
                         value: email.clone(),
                     });
@@ -122,7 +150,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 if !phone.is_empty() {
                     comm_channels.push(CommunicationChannel {
                         channel_code: CodeValue {
-                            value: "TELEPHONE".to_string(),
+                            value: "TELEPHONE".into(),
                         },
                         value: phone.clone(),
                     });
@@ -139,7 +167,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
 
             contacts.push(TradeItemContactInformation {
                 contact_type: CodeValue {
-                    value: "EAR".to_string(),
+                    value: "EAR".into(),
                 },
                 party_identification: vec![AdditionalPartyIdentification {
                     type_code: "SRN".to_string(),
@@ -159,11 +187,11 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
         .map(|name| TradeItemDescriptionModule {
             info: TradeItemDescriptionInformation {
                 description_short: vec![LangValue {
-                    language_code: "en".to_string(),
+                    language_code: "en".into(),
                     value: crate::firstbase::truncate_short_description(name),
                 }],
                 descriptions: vec![LangValue {
-                    language_code: "en".to_string(),
+                    language_code: "en".into(),
                     value: name.clone(),
                 }],
                 additional_descriptions: Vec::new(),
@@ -175,7 +203,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
         let sterilization = device.sterilization.unwrap_or(false);
         SterilityInformation {
             manufacturer_sterilisation: vec![CodeValue {
-                value: if s { "UNSPECIFIED" } else { "NOT_STERILISED" }.to_string(),
+                value: if s { "UNSPECIFIED" } else { "NOT_STERILISED" }.into(),
             }],
             prior_to_use: vec![CodeValue {
                 value: if sterilization {
@@ -183,7 +211,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 } else {
                     "NO_STERILISATION_REQUIRED"
                 }
-                .to_string(),
+                .into(),
             }],
         }
     });
@@ -192,14 +220,14 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
     let reusability = if device.reusable == Some(false) {
         Some(ReusabilityInformation {
             reusability_type: CodeValue {
-                value: "SINGLE_USE".to_string(),
+                value: "SINGLE_USE".into(),
             },
             max_cycles: None,
         })
     } else if device.reusable == Some(true) {
         Some(ReusabilityInformation {
             reusability_type: CodeValue {
-                value: "LIMITED_REUSABLE".to_string(),
+                value: "LIMITED_REUSABLE".into(),
             },
             max_cycles: None,
         })
@@ -207,14 +235,47 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
         None
     };
 
-    TradeItem {
+    // 097.049: SPP requires a medical purpose; fallback deviceName
+    let spp_purpose = if spp_type.is_some() {
+        let purpose = device
+            .medical_purpose
+            .clone()
+            .and_then(|v| serde_json::from_value::<MultiLangText>(v).ok());
+        let mut texts = api_detail::lang_texts(
+            purpose.as_ref(),
+            &config.target_market.text_languages(),
+            false,
+        );
+        if texts.is_empty() {
+            texts.extend(
+                device
+                    .device_name
+                    .clone()
+                    .filter(|n| !n.is_empty())
+                    .map(|n| ("en".to_string(), n)),
+            );
+        }
+        texts
+            .into_iter()
+            .map(|(lang, text)| LangValue {
+                language_code: mappings::language_code(&lang),
+                value: text,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    // Device flags are not used for systems / procedure packs
+    let flag = |value: Option<bool>| value.filter(|_| spp_type.is_none());
+
+    let mut item = TradeItem {
         is_brand_bank_publication: false,
-        target_sector: vec!["UDI_REGISTRY".to_string()],
+        target_sector: vec!["UDI_REGISTRY".into()],
         chemical_regulation_module: None,
         healthcare_item_module: None,
         medical_device_module: MedicalDeviceTradeItemModule {
             info: MedicalDeviceInformation {
-                is_implantable: device.implantable.map(|b| {
+                is_implantable: flag(device.implantable).map(|b| {
                     if b {
                         "TRUE".to_string()
                     } else {
@@ -224,18 +285,18 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 is_exempt_from_implant_obligations: None,
                 device_count: None,
                 direct_marking: Vec::new(),
-                measuring_function: device.measuring_function,
+                measuring_function: flag(device.measuring_function),
                 is_active: None,
-                administer_medicine: device.administering_medicine,
-                is_medicinal_product: device.medicinal_product,
+                administer_medicine: flag(device.administering_medicine),
+                is_medicinal_product: flag(device.medicinal_product),
                 is_reprocessed: None,
                 is_reusable_surgical: None,
                 production_identifier_types: Vec::new(),
                 annex_xvi_types: Vec::new(),
                 special_device_type: None,
                 multi_component_type: None,
-                system_or_procedure_pack_type: None,
-                system_or_procedure_pack_purpose: Vec::new(),
+                system_or_procedure_pack_type: spp_type.map(|t| CodeValue { value: t.into() }),
+                system_or_procedure_pack_purpose: spp_purpose,
                 is_new_device: None,
                 is_reagent: None,
                 is_instrument: None,
@@ -244,30 +305,37 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
                 is_professional_testing: None,
                 is_companion_diagnostic: None,
                 eu_status: CodeValue {
-                    value: String::new(),
+                    value: Code::default(),
                 },
                 reusability,
                 sterility,
             },
         },
-        certification_module: None,
+        certification_module: crate::certificates::certification_module(certificates),
         referenced_file_module: None,
-        regulated_trade_item_module: None,
+        regulated_trade_item_module: reg_act.map(|act| RegulatedTradeItemModule {
+            info: vec![crate::certificates::regulatory_information(
+                &act,
+                certificates,
+            )],
+        }),
         sales_module: None,
         description_module,
         is_base_unit: true,
         is_despatch_unit: true, // BASE_UNIT_OR_EACH is highest level = despatch unit
         is_orderable_unit: true,
         unit_descriptor: CodeValue {
-            value: "BASE_UNIT_OR_EACH".to_string(),
+            value: "BASE_UNIT_OR_EACH".into(),
         },
         trade_channel_code: vec![CodeValue {
-            value: "UDI_REGISTRY".to_string(),
+            value: "UDI_REGISTRY".into(),
         }],
         information_provider: InformationProvider {
             gln: config.provider.gln.clone(),
             party_name: config.provider.party_name.clone(),
         },
+        brand_owner: None,
+        manufacturer_of_trade_item: Vec::new(),
         classification: GdsnClassification {
             segment_code: config.gpc.segment_code.clone(),
             class_code: config.gpc.class_code.clone(),
@@ -279,7 +347,7 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
         next_lower_level: None,
         target_market: TargetMarketObj {
             country_code: CodeValue {
-                value: config.target_market.country_code.clone(),
+                value: config.target_market.country_code.clone().into(),
             },
         },
         contact_information: contacts,
@@ -287,7 +355,8 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
             last_change: now_str.clone(),
             effective: device
                 .version_date
-                .clone()
+                .as_deref()
+                .and_then(|d| dates::eudamed_timestamp(&config.dates, d))
                 .unwrap_or_else(|| now_str.clone()),
             publication: now_str,
             discontinued: None,
@@ -298,5 +367,16 @@ pub fn transform_eudamed_device(device: &EudamedDevice, config: &Config) -> Trad
         additional_identification: Vec::new(),
         referenced_trade_items: Vec::new(),
         trade_item_information: Vec::new(),
-    }
+        avp_list: avp::build(
+            &config.avp,
+            &[
+                ("uuid", device.uuid.as_deref()),
+                ("version", version.as_deref()),
+                ("version_date", device.version_date.as_deref()),
+                ("basic_udi", Some(&basic_udi)),
+            ],
+        ),
+    };
+    crate::ordering::trade_item(&mut item);
+    crate::transform::finish_trade_item(item, config)
 }
@@ -0,0 +1,287 @@
+use serde_json::{json, Value};
+
+use crate::api_detail::{ApiDeviceDetail, BasicUdiDiData};
+use crate::config::Config;
+use crate::firstbase::FirstbaseDocument;
+use crate::swissdamed_json::{DiCode, SwissText, SwissdamedRecord};
+
+/// Transform a swissdamed record into a firstbase document.
+///
+/// swissdamed registers the EUDAMED data model almost 1:1 (see
+/// `swissdamed.rs`, which maps the other way), so the record is turned back
+/// into an EUDAMED UDI-DI detail and Basic UDI-DI record and goes through
+/// `transform_detail_document`: packaging, texts, status and the GS1 rules
+/// stay those of the EUDAMED conversion. swissdamed has no device status in
+/// the registration itself; without a `marketStatus` the device is on the
+/// market. Clinical sizes and substances are not carried over.
+pub fn transform_swissdamed_document(
+    record: &SwissdamedRecord,
+    config: &Config,
+    stem: &str,
+) -> anyhow::Result<FirstbaseDocument> {
+    let (detail, basic_udi) = to_eudamed(record)?;
+    Ok(crate::transform_detail::transform_detail_document(
+        &detail,
+        config,
+        Some(&basic_udi),
+        stem,
+    ))
+}
+
+/// The EUDAMED detail and Basic UDI-DI records of a swissdamed record.
+pub fn to_eudamed(record: &SwissdamedRecord) -> anyhow::Result<(ApiDeviceDetail, BasicUdiDiData)> {
+    let detail: ApiDeviceDetail = serde_json::from_value(detail_json(record))?;
+    let basic_udi: BasicUdiDiData = serde_json::from_value(basic_udi_json(record))?;
+    Ok((detail, basic_udi))
+}
+
+/// swissdamed issuing entity → EUDAMED refdata code.
+fn issuing_agency(entity: Option<&str>) -> String {
+    let suffix = match entity.unwrap_or("GS1").to_ascii_uppercase().as_str() {
+        "HIBC" | "HIBCC" => "hibcc".to_string(),
+        other => other.to_ascii_lowercase(),
+    };
+    format!("refdata.issuing-agency.{}", suffix)
+}
+
+/// `CLASS_IIA` → `refdata.<list>.class-iia`.
+fn refdata(list: &str, value: &str) -> String {
+    format!(
+        "refdata.{}.{}",
+        list,
+        value.to_ascii_lowercase().replace('_', "-")
+    )
+}
+
+fn di(code: Option<&DiCode>) -> Value {
+    match code.and_then(|c| c.code().map(|v| (v, c))) {
+        Some((value, code)) => json!({
+            "code": value,
+            "issuingAgency": { "code": issuing_agency(code.issuing_entity_code.as_deref()) },
+        }),
+        None => Value::Null,
+    }
+}
+
+fn texts(texts: &[SwissText]) -> Value {
+    let texts: Vec<Value> = texts
+        .iter()
+        .filter_map(|t| {
+            let text = t.text_value.as_deref().filter(|t| !t.is_empty())?;
+            Some(json!({
+                "language": t.language.as_deref().map(|l| json!({ "isoCode": l.to_ascii_lowercase() })),
+                "text": text,
+            }))
+        })
+        .collect();
+    if texts.is_empty() {
+        Value::Null
+    } else {
+        json!({ "texts": texts })
+    }
+}
+
+/// The flat `packages` list as EUDAMED's `containedItem` chain, from the
+/// UDI-DI outwards: each level is the package whose `childOf` is the level
+/// below.
+fn contained_item(record: &SwissdamedRecord) -> Value {
+    let Some(base) = record.udi_di_code() else {
+        return Value::Null;
+    };
+    let mut chain = Vec::new();
+    let mut child = base.to_string();
+    while let Some((code, count)) = record.udi_di.packages.iter().find_map(|p| {
+        let code = p.identifier.as_ref()?.code()?;
+        let parent_of = p.child_of.as_ref()?.code()?;
+        (parent_of == child && !chain.iter().any(|(c, _): &(String, u32)| c == code))
+            .then(|| (code.to_string(), p.number_of_items.unwrap_or(1)))
+    }) {
+        child = code.clone();
+        chain.push((code, count));
+    }
+    if chain.is_empty() {
+        return Value::Null;
+    }
+    let mut node = Value::Null;
+    for (code, count) in chain.into_iter().rev() {
+        node = json!({
+            "itemIdentifier": { "code": code },
+            "numberOfItems": count,
+            "containedItems": if node.is_null() { json!([]) } else { json!([node]) },
+        });
+    }
+    json!({
+        "itemIdentifier": { "code": base },
+        "containedItems": [node],
+    })
+}
+
+fn detail_json(record: &SwissdamedRecord) -> Value {
+    let udi = &record.udi_di;
+    let pi = |name: &str| udi.production_identifiers.iter().any(|p| p == name);
+    let reuses = udi.number_of_reuses.filter(|n| *n >= 0);
+    let status = record.market_status.as_deref().unwrap_or("ON_THE_MARKET");
+    json!({
+        "uuid": record.correlation_id,
+        "primaryDi": di(udi.identifier.as_ref()),
+        "secondaryDi": di(udi.secondary_identifier.as_ref()),
+        "directMarking": udi.direct_marking_identifier.is_some(),
+        "directMarkingDi": di(udi.direct_marking_identifier.as_ref()),
+        "unitOfUse": di(udi.unit_of_use_identifier.as_ref()),
+        "reference": udi.reference_number,
+        "baseQuantity": udi.base_quantity,
+        "tradeName": texts(&udi.trade_names),
+        "additionalDescription": texts(&udi.additional_description),
+        "additionalInformationUrl": udi.website,
+        "sterile": udi.sterile,
+        "sterilization": udi.sterilization,
+        "latex": udi.latex,
+        "reprocessed": udi.reprocessed,
+        "maxNumberOfReuses": reuses,
+        "maxNumberOfReusesApplicable": reuses.is_some(),
+        "udiPiType": {
+            "batchNumber": pi("BATCH_NUMBER"),
+            "serializationNumber": pi("SERIAL_NUMBER"),
+            "manufacturingDate": pi("MANUFACTURING_DATE"),
+            "expirationDate": pi("EXPIRATION_DATE"),
+            "softwareIdentification": pi("SOFTWARE_IDENTIFICATION"),
+        },
+        "storageApplicable": !udi.storage_handling_conditions.is_empty(),
+        "storageHandlingConditions": udi.storage_handling_conditions.iter().filter_map(|c| {
+            Some(json!({
+                "typeCode": format!(
+                    "refdata.storage-handling-conditions-type.{}",
+                    c.storage_handling_condition_value.as_deref()?
+                ),
+                "description": texts(&c.comments),
+            }))
+        }).collect::<Vec<_>>(),
+        "criticalWarningsApplicable": !udi.critical_warnings.is_empty(),
+        "criticalWarnings": udi.critical_warnings.iter().filter_map(|w| {
+            Some(json!({
+                "typeCode": format!("refdata.critical-warnings-type.{}", w.warning_value.as_deref()?),
+                "description": texts(&w.comments),
+            }))
+        }).collect::<Vec<_>>(),
+        "cndNomenclatures": udi.nomenclature_codes.iter().map(|c| json!({ "code": c })).collect::<Vec<_>>(),
+        "annexXVIApplicable": udi.annex_xvi_applicable,
+        "deviceStatus": { "type": { "code": refdata("device-model-status", status) } },
+        "containedItem": contained_item(record),
+    })
+}
+
+fn basic_udi_json(record: &SwissdamedRecord) -> Value {
+    let basic = &record.basic_udi;
+    let spp = record.is_spp();
+    let srn = if spp {
+        &basic.pr_actor_code
+    } else {
+        &basic.mf_actor_code
+    };
+    let device_type =
+        basic
+            .device_type
+            .as_deref()
+            .unwrap_or(if spp { "PROCEDURE_PACK" } else { "DEVICE" });
+    json!({
+        "basicUdi": di(basic.identifier.as_ref()),
+        "deviceName": basic.device_name,
+        "deviceModel": basic.model_name,
+        "riskClass": basic.risk_class.as_deref().map(|c| json!({ "code": refdata("risk-class", c) })),
+        "legislation": { "code": refdata("applicable-legislation", &record.legislation()) },
+        "multiComponent": {
+            "code": refdata("multi-component", device_type),
+            "criterion": if spp { "SPP" } else { "STANDARD" },
+        },
+        "animalTissues": basic.animal_tissues_cells,
+        "humanTissues": basic.human_tissues_cells,
+        "active": basic.active,
+        "administeringMedicine": basic.administering_medicine,
+        "humanProduct": basic.human_product_check,
+        "implantable": basic.implantable,
+        "measuringFunction": basic.measuring_function,
+        "medicinalProduct": basic.medicinal_product_check,
+        "reusable": basic.reusable,
+        "sutures": basic.class_iib_implantable_exceptions,
+        "manufacturer": srn.as_deref().map(|srn| json!({ "srn": srn })),
+        "medicalPurpose": texts(&basic.medicinal_purpose),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swissdamed_json::parse_swissdamed_json;
+
+    #[test]
+    fn mdr_record_converts_with_its_packages() {
+        let config = crate::config::test_config();
+        let records = parse_swissdamed_json(
+            r#"[{
+                "correlationId": "c0ffee00-0000-0000-0000-000000000001",
+                "basicUdi": {
+                    "identifier": {"diCode": "76400000ABC123", "issuingEntityCode": "GS1"},
+                    "deviceName": "Wound dressing", "modelName": "WD-10",
+                    "type": "DEVICE", "riskClass": "CLASS_IIA",
+                    "implantable": false, "reusable": false,
+                    "mfActorCode": "CH-MF-000012345"
+                },
+                "udiDi": {
+                    "identifier": {"diCode": "07640000000010", "issuingEntityCode": "GS1"},
+                    "tradeNames": [{"language": "DE", "textValue": "Wundverband"}],
+                    "referenceNumber": "WD-10-S",
+                    "sterile": true, "sterilization": false,
+                    "nomenclatureCodes": ["M040101"],
+                    "productionIdentifiers": ["BATCH_NUMBER"],
+                    "packages": [
+                        {"identifier": {"diCode": "07640000000034"},
+                         "childOf": {"diCode": "07640000000027"}, "numberOfItems": 5},
+                        {"identifier": {"diCode": "07640000000027"},
+                         "childOf": {"diCode": "07640000000010"}, "numberOfItems": 10}
+                    ],
+                    "baseQuantity": 1, "numberOfReuses": -1
+                }
+            }]"#,
+        )
+        .unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.legislation(), "MDR");
+        assert_eq!(record.stem("file"), "c0ffee00-0000-0000-0000-000000000001");
+
+        let (detail, basic_udi) = to_eudamed(record).unwrap();
+        assert_eq!(detail.gtin(), "07640000000010");
+        assert_eq!(detail.status_code().as_deref(), Some("ON_THE_MARKET"));
+        assert_eq!(basic_udi.regulatory_act().as_deref(), Some("MDR"));
+        assert_eq!(
+            basic_udi.risk_class_code().as_deref(),
+            Some("refdata.risk-class.class-iia")
+        );
+        assert!(!basic_udi.is_spp());
+
+        let document = transform_swissdamed_document(record, &config, "stem").unwrap();
+        let json = serde_json::to_value(&document).unwrap();
+        // Outermost package on top, 5 × 10 × base unit
+        assert_eq!(json["TradeItem"]["Gtin"], "07640000000034");
+        let inner = &json["CatalogueItemChildItemLink"][0];
+        assert_eq!(inner["Quantity"], 5);
+        assert_eq!(
+            inner["CatalogueItem"]["TradeItem"]["Gtin"],
+            "07640000000027"
+        );
+        let base = &inner["CatalogueItem"]["CatalogueItemChildItemLink"][0];
+        assert_eq!(base["Quantity"], 10);
+        assert_eq!(base["CatalogueItem"]["TradeItem"]["Gtin"], "07640000000010");
+
+        let ivdr = parse_swissdamed_json(
+            r#"{"basicUdi": {"riskClass": "CLASS_C"}, "udiDi": {"identifier": {"diCode": "07640000000058"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(ivdr[0].legislation(), "IVDR");
+        assert_eq!(ivdr[0].stem("file"), "07640000000058");
+        let spp =
+            parse_swissdamed_json(r#"{"basicUdi": {"prActorCode": "CH-PR-000000001"}}"#).unwrap();
+        assert!(to_eudamed(&spp[0]).unwrap().1.is_spp());
+        assert_eq!(issuing_agency(Some("HIBC")), "refdata.issuing-agency.hibcc");
+    }
+}
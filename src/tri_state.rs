@@ -0,0 +1,140 @@
+//! Yes / no / not applicable flags of EUDAMED records that do not arrive as
+//! plain JSON booleans: the listing sends `sterile` as `true`, `1.0` or
+//! `null`, device-level exports carry `"NOT_APPLICABLE"` or refdata codes for
+//! IVD flags. `lenient` reads all of these into one `Option<TriState>`; a
+//! value it does not recognise is `None` (unknown), never a parse error that
+//! would cost the whole record.
+
+use std::fmt;
+
+use serde::de::{Deserializer, Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriState {
+    Yes,
+    No,
+    NotApplicable,
+}
+
+impl TriState {
+    /// The flag as a boolean; not applicable has none.
+    pub fn as_bool(self) -> Option<bool> {
+        match self {
+            TriState::Yes => Some(true),
+            TriState::No => Some(false),
+            TriState::NotApplicable => None,
+        }
+    }
+
+    /// `bool`-typed firstbase fields from an optional flag.
+    pub fn flag(value: Option<TriState>) -> Option<bool> {
+        value.and_then(TriState::as_bool)
+    }
+
+    fn from_text(text: &str) -> Option<TriState> {
+        // Refdata codes: "refdata.<list>.not-applicable" → "not-applicable"
+        let text = text.trim().rsplit('.').next().unwrap_or_default();
+        let norm = text.to_ascii_lowercase().replace(['-', ' ', '/'], "_");
+        match norm.as_str() {
+            "true" | "yes" | "y" | "1" => Some(TriState::Yes),
+            "false" | "no" | "n" | "0" => Some(TriState::No),
+            "not_applicable" | "na" | "n_a" => Some(TriState::NotApplicable),
+            _ => None,
+        }
+    }
+}
+
+/// `deserialize_with` for tri-state flags: booleans, numbers (0 = no,
+/// anything else = yes), yes/no/not-applicable strings and refdata codes.
+/// Objects and arrays are skipped as unknown.
+pub fn lenient<'de, D: Deserializer<'de>>(d: D) -> Result<Option<TriState>, D::Error> {
+    struct Flag;
+
+    impl<'de> Visitor<'de> for Flag {
+        type Value = Option<TriState>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a yes/no/not applicable flag")
+        }
+
+        fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+
+        fn visit_bool<E: Error>(self, b: bool) -> Result<Self::Value, E> {
+            Ok(Some(if b { TriState::Yes } else { TriState::No }))
+        }
+
+        fn visit_i64<E: Error>(self, n: i64) -> Result<Self::Value, E> {
+            self.visit_bool(n != 0)
+        }
+
+        fn visit_u64<E: Error>(self, n: u64) -> Result<Self::Value, E> {
+            self.visit_bool(n != 0)
+        }
+
+        fn visit_f64<E: Error>(self, n: f64) -> Result<Self::Value, E> {
+            self.visit_bool(n != 0.0)
+        }
+
+        fn visit_str<E: Error>(self, s: &str) -> Result<Self::Value, E> {
+            Ok(TriState::from_text(s))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            while seq.next_element::<IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+    }
+
+    d.deserialize_option(Flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_of_every_shape() {
+        #[derive(serde::Deserialize)]
+        struct Record {
+            #[serde(default, deserialize_with = "lenient")]
+            flag: Option<TriState>,
+        }
+        let flag = |json: &str| {
+            serde_json::from_str::<Record>(&format!(r#"{{"flag":{}}}"#, json))
+                .unwrap()
+                .flag
+        };
+        assert_eq!(flag("true"), Some(TriState::Yes));
+        assert_eq!(flag("1.0"), Some(TriState::Yes));
+        assert_eq!(flag("0"), Some(TriState::No));
+        assert_eq!(flag("0.0"), Some(TriState::No));
+        assert_eq!(flag(r#""NO""#), Some(TriState::No));
+        assert_eq!(flag(r#""NOT_APPLICABLE""#), Some(TriState::NotApplicable));
+        assert_eq!(
+            flag(r#""refdata.applicability.not-applicable""#),
+            Some(TriState::NotApplicable)
+        );
+        assert_eq!(flag("null"), None);
+        assert_eq!(flag(r#""maybe""#), None);
+        assert_eq!(flag(r#"{"code":"x","nested":[1,{"a":null}]}"#), None);
+        assert!(serde_json::from_str::<Record>("{}").unwrap().flag.is_none());
+
+        assert_eq!(TriState::flag(Some(TriState::No)), Some(false));
+        assert_eq!(TriState::flag(Some(TriState::NotApplicable)), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Rejection triage (GUI "Rejection triage" section): the open GS1 rejections
+//! — the `push_error` rows of each GTIN's latest rejected session, unless the
+//! GTIN was ACCEPTED in that environment since — grouped by error code, with
+//! the explanation from the knowledge base below and the user's decision per
+//! (error code, GTIN). Decisions are stored in `triage_decision` and survive
+//! re-pushes: a GTIN rejected again for the same code keeps its decision.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Known GS1 validation codes: what they mean for EUDAMED-derived data and
+/// where the fix belongs (see README "Validation Error Fixes Applied").
+const KNOWLEDGE: &[(&str, &str)] = &[
+    ("097.002", "Legacy devices (MDD/AIMDD/IVDD) must use risk class system 85, not 76 (BR-DTX-UDID-002)."),
+    ("097.003", "Risk class system 76 missing. The converter always emits it; re-convert the device."),
+    ("097.005", "Risk class value does not match the code list of its system (MDR/IVDR: EU_CLASS_*, legacy: system 85 codes)."),
+    ("097.006", "MANUFACTURER_PART_NUMBER is mandatory in additionalTradeItemIdentification."),
+    ("097.009", "EMA (manufacturer) contact with SRN required. Usually a missing Basic UDI-DI cache file: download the Basic UDI-DI and re-convert."),
+    ("097.010", "multiComponent / tissue information missing. Comes from the Basic UDI-DI."),
+    ("097.011", "MDR boolean fields missing. Comes from the Basic UDI-DI."),
+    ("097.013", "uDIProductionIdentifierTypeCode required for MDR/IVDR (from udiPiType); forbidden for legacy devices."),
+    ("097.015", "Implantable EU_CLASS_IIB device needs IsDeviceExemptFromImplantObligations."),
+    ("097.016", "System/procedure pack under MDR: the contact must be EPP with SRN."),
+    ("097.020", "ON_MARKET needs exactly one ORIGINAL_PLACED country. Fix the market info in EUDAMED or override the country."),
+    ("097.021", "NOT_INTENDED_FOR_EU_MARKET must not carry country / sales data."),
+    ("097.022", "Class I implantable conflict. Data quality issue in EUDAMED, fix at source."),
+    ("097.025", "MODEL_NUMBER or globalModelDescription (en) required. Typically an incomplete Basic UDI-DI: force-reload and re-convert."),
+    ("097.026", "Actor contactTypeCode missing (EMA)."),
+    ("097.042", "Notified body must be identified by EU_NOTIFIED_BODY_NUMBER."),
+    ("097.046", "IVDR boolean fields (reagent, instrument, self-testing, …) missing."),
+    ("097.047", "IVDR IsNewDevice missing."),
+    ("097.054", "Non-EU manufacturer needs an EAR (authorised representative) contact. Register the AR in EUDAMED."),
+    ("097.070", "DEVICE_SIZE_TEXT_SPECIFY needs a ClinicalSizeDescription (BR-UDID-722)."),
+    ("097.072", "additionalDescription missing for the multi-component type."),
+    ("097.074", "Storage/handling code requires a description (BR-UDID-028). Add the text in EUDAMED or override it."),
+    ("097.078", "At most one description per languageCode."),
+    ("097.080", "CMR / endocrine substance needs regulatedChemicalDescription."),
+    ("097.081", "Endocrine substance needs a description even with CAS/EC identifiers."),
+    ("097.087", "Secondary DI type code must match the issuing agency (BR-UDID-020)."),
+    ("097.091", "SOFTWARE_IDENTIFICATION requires SpecialDeviceTypeCode SOFTWARE (BR-DTX-UDI-104)."),
+    ("097.095", "Field forbidden for legacy devices (BR-DTX-UDID-089)."),
+    ("097.101", "MDR/IVDR certificate required for this risk class. Certificates come from the Basic UDI-DI."),
+    ("097.105", "MDD certificate (type and CertificationValue) required for this legacy device."),
+    ("097.118", "GS1 direct marking DI must have exactly 14 digits (BR-UDID-003)."),
+    ("G485", "Protected field changed on an existing item (e.g. discontinuedDateTime). Usually ignore: the item is already registered."),
+    ("G541", "Invalid code list value (e.g. country 826 for UK/NI). Waits for a GDSN release; ignore or override."),
+    ("G572", "lastChangeDateTime in the future (timezone mismatch). Re-convert and re-push."),
+    ("G641", "A referenced trade item points to the item itself."),
+    ("SYS25", "GTIN already registered by another item (e.g. a legacy/MDR twin). Usually ignore."),
+];
+
+/// Knowledge-base explanation of a GS1 error code.
+pub fn explain(code: &str) -> Option<&'static str> {
+    KNOWLEDGE
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, text)| *text)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Corrected in EUDAMED; the next download + convert picks it up.
+    FixedAtSource,
+    /// Corrected by a field override before the next push.
+    Override,
+    /// Accepted as is (not fixable, or not worth fixing).
+    Ignore,
+}
+
+impl Decision {
+    pub const ALL: [Decision; 3] = [
+        Decision::FixedAtSource,
+        Decision::Override,
+        Decision::Ignore,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Decision::FixedAtSource => "fixed_at_source",
+            Decision::Override => "override",
+            Decision::Ignore => "ignore",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Decision> {
+        Decision::ALL.into_iter().find(|d| d.as_str() == s)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Decision::FixedAtSource => "fixed at source",
+            Decision::Override => "override",
+            Decision::Ignore => "ignore",
+        }
+    }
+}
+
+/// One open rejection of one device.
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub uuid: String,
+    pub gtin: String,
+    pub env: String,
+    pub session_ts: String,
+    pub attribute: String,
+    pub description: String,
+    pub decision: Option<Decision>,
+}
+
+/// All open rejections with one error code.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub code: String,
+    pub explanation: Option<&'static str>,
+    pub items: Vec<Item>,
+}
+
+impl Group {
+    pub fn undecided(&self) -> usize {
+        self.items.iter().filter(|i| i.decision.is_none()).count()
+    }
+}
+
+fn ensure_table(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS triage_decision (
+            error_code TEXT NOT NULL,
+            gtin TEXT NOT NULL,
+            decision TEXT NOT NULL,
+            decided_at TEXT NOT NULL,
+            PRIMARY KEY (error_code, gtin)
+        );",
+    )?;
+    Ok(())
+}
+
+fn decisions(conn: &Connection) -> anyhow::Result<HashMap<(String, String), Decision>> {
+    ensure_table(conn)?;
+    let mut stmt = conn.prepare("SELECT error_code, gtin, decision FROM triage_decision")?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, String>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut map = HashMap::new();
+    for row in rows {
+        let (code, gtin, decision) = row?;
+        if let Some(d) = Decision::parse(&decision) {
+            map.insert((code, gtin), d);
+        }
+    }
+    Ok(map)
+}
+
+/// Open rejections grouped by error code, largest group first. Empty when
+/// nothing was ever pushed from this machine.
+pub fn load(conn: &Connection) -> anyhow::Result<Vec<Group>> {
+    let pushed: Option<String> = conn
+        .query_row(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'push_error'",
+            [],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if pushed.is_none() {
+        return Ok(Vec::new());
+    }
+    let decided = decisions(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT e.uuid, e.gtin, e.error_code, e.attribute_name, e.error_description,
+                s.firstbase_env, s.session_ts
+         FROM push_error e JOIN push_session s ON s.id = e.session_id
+         WHERE e.gtin != ''
+           AND e.session_id = (
+               SELECT MAX(e2.session_id) FROM push_error e2
+               JOIN push_session s2 ON s2.id = e2.session_id
+               WHERE e2.gtin = e.gtin AND s2.firstbase_env = s.firstbase_env)
+           AND NOT EXISTS (
+               SELECT 1 FROM push_log p
+               WHERE p.gtin = e.gtin AND p.status = 'ACCEPTED'
+                 AND COALESCE(p.firstbase_env, '') = s.firstbase_env
+                 AND p.pushed_at > s.session_ts)
+         ORDER BY e.error_code, e.gtin, s.firstbase_env",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, String>(2)?,
+            Item {
+                uuid: r.get(0)?,
+                gtin: r.get(1)?,
+                attribute: r.get(3)?,
+                description: r.get(4)?,
+                env: r.get(5)?,
+                session_ts: r.get(6)?,
+                decision: None,
+            },
+        ))
+    })?;
+    let mut by_code: BTreeMap<String, Vec<Item>> = BTreeMap::new();
+    for row in rows {
+        let (code, mut item) = row?;
+        item.decision = decided.get(&(code.clone(), item.gtin.clone())).copied();
+        by_code.entry(code).or_default().push(item);
+    }
+    let mut groups: Vec<Group> = by_code
+        .into_iter()
+        .map(|(code, items)| Group {
+            explanation: explain(&code),
+            code,
+            items,
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.items.len()));
+    Ok(groups)
+}
+
+/// Record (or with `None` clear) the decision for `gtin` under `code`.
+pub fn decide(
+    conn: &Connection,
+    code: &str,
+    gtin: &str,
+    decision: Option<Decision>,
+) -> anyhow::Result<()> {
+    ensure_table(conn)?;
+    match decision {
+        Some(d) => conn.execute(
+            "INSERT OR REPLACE INTO triage_decision (error_code, gtin, decision, decided_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                code,
+                gtin,
+                d.as_str(),
+                chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+            ],
+        )?,
+        None => conn.execute(
+            "DELETE FROM triage_decision WHERE error_code = ?1 AND gtin = ?2",
+            params![code, gtin],
+        )?,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_rejections_grouped_with_decisions() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE push_session (id INTEGER PRIMARY KEY, session_ts TEXT, firstbase_env TEXT);
+             CREATE TABLE push_error (session_id INTEGER, uuid TEXT, gtin TEXT, error_code TEXT,
+                 attribute_name TEXT, error_description TEXT);
+             CREATE TABLE push_log (gtin TEXT, status TEXT, pushed_at TEXT, firstbase_env TEXT);
+             INSERT INTO push_session VALUES (1, '2026-01-01T00:00:00Z', 'Test');
+             INSERT INTO push_session VALUES (2, '2026-02-01T00:00:00Z', 'Test');
+             INSERT INTO push_error VALUES (1, 'a', '1', '097.009', 'contact', 'old');
+             INSERT INTO push_error VALUES (2, 'a', '1', '097.025', 'modelNumber', 'new');
+             INSERT INTO push_error VALUES (2, 'b', '2', '097.025', 'modelNumber', 'new');
+             INSERT INTO push_error VALUES (1, 'c', '3', '097.020', 'country', 'fixed');
+             INSERT INTO push_log VALUES ('3', 'ACCEPTED', '2026-02-01T00:00:00Z', 'Test');",
+        )
+        .unwrap();
+        decide(&conn, "097.025", "2", Some(Decision::Ignore)).unwrap();
+        let groups = load(&conn).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].code, "097.025");
+        assert!(groups[0].explanation.is_some());
+        assert_eq!(groups[0].items.len(), 2);
+        assert_eq!(groups[0].undecided(), 1);
+        assert_eq!(groups[0].items[1].decision, Some(Decision::Ignore));
+    }
+}
@@ -5,6 +5,9 @@
 //! asset URL so the in-app updater can fetch it without extra round
 //! trips — letting users jump straight to the freshest GitHub release
 //! instead of waiting on Microsoft Store / App Store certification.
+//! The release notes of every newer release come along, so the banner can
+//! show which converter fixes the installed version is missing. The GUI
+//! repeats the check every `RECHECK` while it stays open.
 
 use serde::Deserialize;
 
@@ -12,12 +15,18 @@ const REPO: &str = "zdavatz/eudamed2firstbase";
 const TAG_PREFIX: &str = "v";
 const USER_AGENT: &str = "eudamed2firstbase-update-check";
 
+/// How often a running GUI checks again.
+pub const RECHECK: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
 #[derive(Deserialize)]
 struct GithubRelease {
     tag_name: String,
     html_url: String,
     #[serde(default)]
     prerelease: bool,
+    /// Release notes (Markdown).
+    #[serde(default)]
+    body: Option<String>,
     #[serde(default)]
     assets: Vec<GithubAsset>,
 }
@@ -38,6 +47,9 @@ pub struct UpdateInfo {
     /// None when the release page hasn't published the artifact for this
     /// target yet.
     pub download_url: Option<String>,
+    /// (tag, release notes) of every release newer than the running one,
+    /// newest first.
+    pub notes: Vec<(String, String)>,
 }
 
 impl UpdateInfo {
@@ -90,10 +102,7 @@ fn find_asset(assets: &[GithubAsset]) -> Option<String> {
 pub fn check_latest(current: &str) -> Option<UpdateInfo> {
     let cur = parse_version(current)?;
     let url = format!("https://api.github.com/repos/{}/releases?per_page=30", REPO);
-    let agent: ureq::Agent = ureq::Agent::config_builder()
-        .timeout_global(Some(std::time::Duration::from_secs(15)))
-        .build()
-        .into();
+    let agent = crate::http::agent_for(&url);
     let mut resp = agent
         .get(&url)
         .header("User-Agent", USER_AGENT)
@@ -102,28 +111,61 @@ pub fn check_latest(current: &str) -> Option<UpdateInfo> {
         .ok()?;
     let body = resp.body_mut().read_to_string().ok()?;
     let releases: Vec<GithubRelease> = serde_json::from_str(&body).ok()?;
+    newest(releases, cur)
+}
+
+/// The newest stable release above `cur`, with the notes of all of them.
+fn newest(releases: Vec<GithubRelease>, cur: (u32, u32, u32)) -> Option<UpdateInfo> {
+    let mut newer: Vec<((u32, u32, u32), GithubRelease)> = releases
+        .into_iter()
+        .filter(|r| !r.prerelease)
+        .filter_map(|r| {
+            let v = parse_version(r.tag_name.strip_prefix(TAG_PREFIX)?)?;
+            (v > cur).then_some((v, r))
+        })
+        .collect();
+    newer.sort_by_key(|(v, _)| std::cmp::Reverse(*v));
+    let notes = newer
+        .iter()
+        .map(|(_, r)| (r.tag_name.clone(), r.body.clone().unwrap_or_default()))
+        .collect();
+    let (version, best) = newer.into_iter().next()?;
+    Some(UpdateInfo {
+        version,
+        url: best.html_url,
+        download_url: find_asset(&best.assets),
+        notes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut best: Option<UpdateInfo> = None;
-    for r in releases {
-        if r.prerelease {
-            continue;
-        }
-        let Some(stripped) = r.tag_name.strip_prefix(TAG_PREFIX) else {
-            continue;
-        };
-        let Some(v) = parse_version(stripped) else {
-            continue;
-        };
-        if v <= cur {
-            continue;
-        }
-        if best.as_ref().map_or(true, |b| v > b.version) {
-            best = Some(UpdateInfo {
-                version: v,
-                url: r.html_url,
-                download_url: find_asset(&r.assets),
-            });
-        }
+    #[test]
+    fn newest_stable_release_carries_the_notes_of_all_newer_ones() {
+        let releases: Vec<GithubRelease> = serde_json::from_str(
+            r#"[
+                {"tag_name": "v1.2.0", "html_url": "u120", "body": "Fix 097.025"},
+                {"tag_name": "v1.3.0", "html_url": "u130", "prerelease": true},
+                {"tag_name": "v1.1.9", "html_url": "u119", "body": null},
+                {"tag_name": "v1.1.0", "html_url": "u110", "body": "old"},
+                {"tag_name": "nightly", "html_url": "n"}
+            ]"#,
+        )
+        .unwrap();
+        let info = newest(releases, (1, 1, 0)).unwrap();
+        assert_eq!(
+            (info.pretty(), info.url.as_str()),
+            ("v1.2.0".to_string(), "u120")
+        );
+        assert_eq!(
+            info.notes,
+            vec![
+                ("v1.2.0".to_string(), "Fix 097.025".to_string()),
+                ("v1.1.9".to_string(), String::new())
+            ]
+        );
+        assert!(newest(Vec::new(), (1, 1, 0)).is_none());
     }
-    best
 }
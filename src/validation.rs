@@ -0,0 +1,124 @@
+//! Pre-flight validation panel of the GUI: the offline checks of `report.rs`
+//! (findings and code-list misses) over the documents waiting for a push,
+//! grouped by rule. Each affected device links to the override editor,
+//! pre-filtered on the attribute the rule is about.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::report::{firstbase_docs, issues, str_at};
+
+/// One device failing a rule.
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub uuid: String,
+    pub gtin: String,
+    /// Offending value, for code-list misses.
+    pub value: String,
+    pub path: PathBuf,
+}
+
+/// All devices failing one rule.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub rule: String,
+    /// Override editor filter for the fix.
+    pub field: &'static str,
+    pub issues: Vec<Issue>,
+}
+
+/// Check the pending documents in `firstbase_dir` (not `processed/`); rules
+/// with the most devices first.
+pub fn scan(firstbase_dir: &Path) -> Vec<Rule> {
+    let found: Vec<(String, &'static str, Issue)> = firstbase_docs(firstbase_dir, false)
+        .par_iter()
+        .flat_map_iter(|path| {
+            let doc: serde_json::Value = std::fs::read_to_string(path)
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+            let uuid = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let gtin = str_at(&doc, "/DraftItem/TradeItem/Gtin").to_string();
+            issues(&doc)
+                .into_iter()
+                .map(|(rule, value, field)| {
+                    (
+                        rule,
+                        field,
+                        Issue {
+                            uuid: uuid.clone(),
+                            gtin: gtin.clone(),
+                            value,
+                            path: path.clone(),
+                        },
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let mut rules: BTreeMap<String, Rule> = BTreeMap::new();
+    for (rule, field, issue) in found {
+        rules
+            .entry(rule.clone())
+            .or_insert_with(|| Rule {
+                rule,
+                field,
+                issues: Vec::new(),
+            })
+            .issues
+            .push(issue);
+    }
+    let mut rules: Vec<Rule> = rules.into_values().collect();
+    for rule in &mut rules {
+        rule.issues.sort_by(|a, b| a.gtin.cmp(&b.gtin));
+    }
+    rules.sort_by_key(|r| std::cmp::Reverse(r.issues.len()));
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_are_grouped_by_rule() {
+        let dir = std::env::temp_dir().join(format!("e2f_validation_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("processed")).unwrap();
+        let doc = |gtin: &str, status: &str| {
+            serde_json::json!({"DraftItem": {"TradeItem": {
+                "Gtin": gtin,
+                "MedicalDeviceTradeItemModule": {"MedicalDeviceInformation": {
+                    "EUMedicalDeviceStatusCode": {"Value": status}}}
+            }}})
+            .to_string()
+        };
+        std::fs::write(dir.join("a.json"), doc("07612345678901", "ON_MARKET")).unwrap();
+        std::fs::write(dir.join("b.json"), doc("07612345678902", "ARCHIVED")).unwrap();
+        std::fs::write(dir.join("processed/c.json"), doc("x", "ARCHIVED")).unwrap();
+
+        let rules = scan(&dir);
+        let no_model = rules
+            .iter()
+            .find(|r| r.rule == "No GlobalModelNumber")
+            .unwrap();
+        assert_eq!(no_model.issues.len(), 2);
+        assert_eq!(no_model.field, "GlobalModelInformation");
+        let status = rules
+            .iter()
+            .find(|r| r.rule.starts_with("EUMedicalDeviceStatusCode"))
+            .unwrap();
+        assert_eq!(
+            (status.issues.len(), status.issues[0].value.as_str()),
+            (1, "ARCHIVED")
+        );
+        assert!(rules
+            .iter()
+            .all(|r| r.rule != "GTIN missing or not numeric (not pushable)"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -13,6 +13,8 @@ pub struct VersionRecord {
     pub gtin: String,
     /// SHA256 hash of the full Detail API JSON response
     pub detail_hash: String,
+    /// SHA256 hash of the Basic UDI-DI JSON ('' when not merged)
+    pub budi_hash: String,
     // UDI-DI root (from Detail API)
     pub udi_version: Option<u32>,
     pub udi_date: Option<String>,
@@ -57,6 +59,9 @@ pub struct ChangeSet {
     pub market_changed: bool,
     pub status_changed: bool,
     pub designer_changed: bool,
+    /// Source payload (detail or Basic UDI-DI JSON) differs although no
+    /// tracked section version moved.
+    pub payload_changed: bool,
 }
 
 impl ChangeSet {
@@ -71,6 +76,7 @@ impl ChangeSet {
             || self.market_changed
             || self.status_changed
             || self.designer_changed
+            || self.payload_changed
     }
 
     pub fn summary(&self) -> String {
@@ -105,6 +111,9 @@ impl ChangeSet {
         if self.designer_changed {
             parts.push("DESIGNER");
         }
+        if self.payload_changed {
+            parts.push("PAYLOAD");
+        }
         if parts.is_empty() {
             "UNCHANGED".to_string()
         } else {
@@ -214,7 +223,46 @@ pub fn open_db(path: &Path) -> Result<Connection> {
             last_synced TEXT NOT NULL DEFAULT ''
         );
         CREATE INDEX IF NOT EXISTS idx_actors_country ON actors(country_iso2);
-        CREATE INDEX IF NOT EXISTS idx_actors_role ON actors(role_name);",
+        CREATE INDEX IF NOT EXISTS idx_actors_role ON actors(role_name);
+
+        -- Conversion history: one row per convert run, one per device version
+        -- produced by a run (new or changed source), so a GTIN's attribute
+        -- changes can be traced to the run and converter version that made them.
+        CREATE TABLE IF NOT EXISTS conversion_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            mode TEXT NOT NULL DEFAULT '',
+            input_dir TEXT NOT NULL DEFAULT '',
+            input_files INTEGER DEFAULT 0,
+            converted INTEGER DEFAULT 0,
+            skipped INTEGER DEFAULT 0,
+            errors INTEGER DEFAULT 0,
+            converter_version TEXT NOT NULL DEFAULT '',
+            output_hash TEXT NOT NULL DEFAULT ''
+        );
+
+        CREATE TABLE IF NOT EXISTS device_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id INTEGER NOT NULL,
+            uuid TEXT NOT NULL,
+            gtin TEXT NOT NULL DEFAULT '',
+            seen_at TEXT NOT NULL,
+            source_file TEXT NOT NULL DEFAULT '',
+            source_line INTEGER,
+            downloaded_at TEXT NOT NULL DEFAULT '',
+            detail_hash TEXT NOT NULL DEFAULT '',
+            udi_version INTEGER,
+            budi_version INTEGER,
+            change_summary TEXT NOT NULL DEFAULT '',
+            risk_class TEXT NOT NULL DEFAULT '',
+            regulatory_act TEXT NOT NULL DEFAULT '',
+            device_status TEXT NOT NULL DEFAULT '',
+            output_hash TEXT NOT NULL DEFAULT '',
+            document BLOB
+        );
+        CREATE INDEX IF NOT EXISTS idx_device_history_uuid ON device_history(uuid);
+        CREATE INDEX IF NOT EXISTS idx_device_history_gtin ON device_history(gtin);",
     )?;
 
     // Additive migration: a DB created by an earlier build already has `actors`
@@ -223,6 +271,12 @@ pub fn open_db(path: &Path) -> Result<Connection> {
     add_column_if_missing(&conn, "actors", "geographical_address")?;
     add_column_if_missing(&conn, "actors", "country_type")?;
     add_column_if_missing(&conn, "actors", "abbreviated_name")?;
+    add_column_if_missing(&conn, "udi_versions", "budi_hash")?;
+    add_column_decl_if_missing(&conn, "device_history", "source_line", "INTEGER")?;
+    add_column_if_missing(&conn, "device_history", "downloaded_at")?;
+    add_column_decl_if_missing(&conn, "device_history", "document", "BLOB")?;
+    // Added by the GUI push on its first run; `storage::SqliteStore` writes it too.
+    add_column_if_missing(&conn, "push_log", "firstbase_env")?;
 
     Ok(conn)
 }
@@ -231,6 +285,11 @@ pub fn open_db(path: &Path) -> Result<Connection> {
 /// No-op when already present (checked via `PRAGMA table_info`), so it's safe to
 /// run on every `open_db`.
 fn add_column_if_missing(conn: &Connection, table: &str, col: &str) -> Result<()> {
+    add_column_decl_if_missing(conn, table, col, "TEXT NOT NULL DEFAULT ''")
+}
+
+/// Like `add_column_if_missing`, with the column type/constraints given as `decl`.
+fn add_column_decl_if_missing(conn: &Connection, table: &str, col: &str, decl: &str) -> Result<()> {
     let exists: bool = {
         let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
         let names: Vec<String> = stmt
@@ -239,10 +298,7 @@ fn add_column_if_missing(conn: &Connection, table: &str, col: &str) -> Result<()
         names.iter().any(|n| n == col)
     };
     if !exists {
-        conn.execute(
-            &format!("ALTER TABLE {table} ADD COLUMN {col} TEXT NOT NULL DEFAULT ''"),
-            [],
-        )?;
+        conn.execute(&format!("ALTER TABLE {table} ADD COLUMN {col} {decl}"), [])?;
     }
     Ok(())
 }
@@ -318,6 +374,42 @@ pub fn upsert_actor(conn: &Connection, a: &ActorRecord) -> Result<()> {
     Ok(())
 }
 
+/// Every row of the `actors` table.
+pub fn load_actors(conn: &Connection) -> Result<Vec<ActorRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT srn, name, role_name, actor_status, country_iso2, country_name,
+                eudamed_identifier, email, telephone, street_name, building_number,
+                postal_zone, city_name, geographical_address, country_type,
+                abbreviated_name, date_of_registration, uuid
+         FROM actors",
+    )?;
+    let actors = stmt
+        .query_map([], |r| {
+            Ok(ActorRecord {
+                srn: r.get(0)?,
+                name: r.get(1)?,
+                role_name: r.get(2)?,
+                actor_status: r.get(3)?,
+                country_iso2: r.get(4)?,
+                country_name: r.get(5)?,
+                eudamed_identifier: r.get(6)?,
+                email: r.get(7)?,
+                telephone: r.get(8)?,
+                street_name: r.get(9)?,
+                building_number: r.get(10)?,
+                postal_zone: r.get(11)?,
+                city_name: r.get(12)?,
+                geographical_address: r.get(13)?,
+                country_type: r.get(14)?,
+                abbreviated_name: r.get(15)?,
+                date_of_registration: r.get(16)?,
+                uuid: r.get(17)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(actors)
+}
+
 /// Count rows in the `actors` table.
 pub fn count_actors(conn: &Connection) -> Result<u64> {
     let n: i64 = conn.query_row("SELECT COUNT(*) FROM actors", [], |r| r.get(0))?;
@@ -326,8 +418,13 @@ pub fn count_actors(conn: &Connection) -> Result<u64> {
 
 /// Compute SHA256 hash of a JSON string
 pub fn hash_json(json: &str) -> String {
+    hash_bytes(json.as_bytes())
+}
+
+/// SHA256 of raw bytes, lowercase hex.
+pub fn hash_bytes(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(json.as_bytes());
+    hasher.update(bytes);
     format!("{:x}", hasher.finalize())
 }
 
@@ -339,7 +436,7 @@ pub fn get_version(conn: &Connection, uuid: &str) -> Result<Option<VersionRecord
                 mfr_version, mfr_date, ar_version, ar_date,
                 cert_versions, pkg_version, pkg_date,
                 market_version, market_date, device_status, status_date,
-                designer_version, designer_date, last_synced
+                designer_version, designer_date, last_synced, budi_hash
          FROM udi_versions WHERE uuid = ?1",
     )?;
 
@@ -367,6 +464,7 @@ pub fn get_version(conn: &Connection, uuid: &str) -> Result<Option<VersionRecord
                 designer_version: row.get(18)?,
                 designer_date: row.get(19)?,
                 last_synced: row.get(20)?,
+                budi_hash: row.get(21)?,
             })
         })
         .ok();
@@ -383,8 +481,8 @@ pub fn upsert_version(conn: &Connection, rec: &VersionRecord) -> Result<()> {
             mfr_version, mfr_date, ar_version, ar_date,
             cert_versions, pkg_version, pkg_date,
             market_version, market_date, device_status, status_date,
-            designer_version, designer_date, last_synced
-        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,?21)
+            designer_version, designer_date, last_synced, budi_hash
+        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,?21,?22)
         ON CONFLICT(uuid) DO UPDATE SET
             gtin=excluded.gtin, detail_hash=excluded.detail_hash,
             udi_version=excluded.udi_version, udi_date=excluded.udi_date,
@@ -396,7 +494,9 @@ pub fn upsert_version(conn: &Connection, rec: &VersionRecord) -> Result<()> {
             market_version=excluded.market_version, market_date=excluded.market_date,
             device_status=excluded.device_status, status_date=excluded.status_date,
             designer_version=excluded.designer_version, designer_date=excluded.designer_date,
-            last_synced=excluded.last_synced",
+            last_synced=excluded.last_synced,
+            budi_hash=CASE WHEN excluded.budi_hash = '' THEN udi_versions.budi_hash
+                           ELSE excluded.budi_hash END",
         params![
             rec.uuid,
             rec.gtin,
@@ -419,6 +519,7 @@ pub fn upsert_version(conn: &Connection, rec: &VersionRecord) -> Result<()> {
             rec.designer_version,
             rec.designer_date,
             rec.last_synced,
+            rec.budi_hash,
         ],
     )?;
     Ok(())
@@ -438,7 +539,12 @@ pub fn delete_version(conn: &Connection, uuid: &str) -> Result<()> {
 }
 
 /// Compare a new version record against the stored one and return what changed.
-/// Fast path: if detail_hash matches, nothing changed.
+/// Fast path: if detail_hash and budi_hash match, nothing changed. A payload
+/// that changed without any section version moving is still a change
+/// (`payload_changed`), so edits EUDAMED doesn't version are not missed.
+/// An empty budi_hash on either side (row indexed from the detail file only,
+/// or no Basic UDI-DI cached) counts as matching; a stored empty one is
+/// backfilled from `new_rec`.
 pub fn detect_changes(conn: &Connection, new_rec: &VersionRecord) -> Result<ChangeSet> {
     let old = match get_version(conn, &new_rec.uuid)? {
         Some(old) => old,
@@ -450,12 +556,23 @@ pub fn detect_changes(conn: &Connection, new_rec: &VersionRecord) -> Result<Chan
         }
     };
 
-    // Fast path: hash unchanged → skip detailed comparison
-    if old.detail_hash == new_rec.detail_hash {
+    // Fast path: hashes unchanged → skip detailed comparison
+    let budi_same = old.budi_hash.is_empty()
+        || new_rec.budi_hash.is_empty()
+        || old.budi_hash == new_rec.budi_hash;
+    if old.detail_hash == new_rec.detail_hash && budi_same {
+        // Rows from before budi_hash existed get it now, so later Basic UDI-DI
+        // edits are caught without a one-off mass re-conversion.
+        if old.budi_hash.is_empty() && !new_rec.budi_hash.is_empty() {
+            conn.execute(
+                "UPDATE udi_versions SET budi_hash = ?2 WHERE uuid = ?1",
+                params![new_rec.uuid, new_rec.budi_hash],
+            )?;
+        }
         return Ok(ChangeSet::default());
     }
 
-    Ok(ChangeSet {
+    let mut changes = ChangeSet {
         is_new: false,
         udi_changed: old.udi_version != new_rec.udi_version || old.udi_date != new_rec.udi_date,
         budi_changed: old.budi_version != new_rec.budi_version
@@ -470,7 +587,10 @@ pub fn detect_changes(conn: &Connection, new_rec: &VersionRecord) -> Result<Chan
             || old.status_date != new_rec.status_date,
         designer_changed: old.designer_version != new_rec.designer_version
             || old.designer_date != new_rec.designer_date,
-    })
+        payload_changed: false,
+    };
+    changes.payload_changed = !changes.has_any_change();
+    Ok(changes)
 }
 
 /// Extract version info from raw Detail API JSON (serde_json::Value).
@@ -570,6 +690,8 @@ pub fn merge_budi_versions(rec: &mut VersionRecord, budi_json: &str) {
         Err(_) => return,
     };
 
+    rec.budi_hash = hash_json(budi_json);
+
     // Basic UDI-DI root
     rec.budi_version = val
         .get("versionNumber")
@@ -619,6 +741,223 @@ pub fn merge_budi_versions(rec: &mut VersionRecord, budi_json: &str) {
     }
 }
 
+/// One device version produced by a conversion run (`device_history` row).
+#[derive(Debug, Default, Clone)]
+pub struct DeviceHistoryRecord {
+    pub run_id: i64,
+    pub uuid: String,
+    pub gtin: String,
+    pub seen_at: String,
+    pub source_file: String,
+    /// 1-based NDJSON line of the source record (`None` for per-file JSON).
+    pub source_line: Option<u32>,
+    /// Modification time of the source file, i.e. when it was downloaded.
+    pub downloaded_at: String,
+    pub detail_hash: String,
+    pub udi_version: Option<u32>,
+    pub budi_version: Option<u32>,
+    /// `ChangeSet::summary()` of the source against the previous version.
+    pub change_summary: String,
+    pub risk_class: String,
+    pub regulatory_act: String,
+    pub device_status: String,
+    /// SHA256 of the firstbase JSON written for this version.
+    pub output_hash: String,
+    /// The firstbase document of this version, compact JSON gzipped
+    /// (`compress_json`), for field-level diffs between versions.
+    pub document: Option<Vec<u8>>,
+}
+
+/// Totals of a finished conversion run.
+#[derive(Debug, Default, Clone)]
+pub struct RunTotals {
+    pub input_files: u64,
+    pub converted: u64,
+    pub skipped: u64,
+    pub errors: u64,
+    /// SHA256 over the sorted per-device output hashes of the run.
+    pub output_hash: String,
+}
+
+/// Start a `conversion_runs` row and return its id.
+pub fn begin_run(conn: &Connection, mode: &str, input_dir: &Path) -> Result<i64> {
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    conn.execute(
+        "INSERT INTO conversion_runs (started_at, mode, input_dir, converter_version)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            now,
+            mode,
+            input_dir.display().to_string(),
+            env!("CARGO_PKG_VERSION")
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Stamp `finished_at` and the run totals.
+pub fn finish_run(conn: &Connection, run_id: i64, totals: &RunTotals) -> Result<()> {
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    conn.execute(
+        "UPDATE conversion_runs SET finished_at = ?1, input_files = ?2, converted = ?3,
+            skipped = ?4, errors = ?5, output_hash = ?6
+         WHERE id = ?7",
+        params![
+            now,
+            totals.input_files as i64,
+            totals.converted as i64,
+            totals.skipped as i64,
+            totals.errors as i64,
+            totals.output_hash,
+            run_id
+        ],
+    )?;
+    Ok(())
+}
+
+/// Gzip bytes for storage in a BLOB column.
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing to a Vec can't fail.
+    let _ = encoder.write_all(bytes);
+    encoder.finish().unwrap_or_default()
+}
+
+/// Inverse of `compress`; `None` for a corrupt blob.
+pub fn decompress(blob: &[u8]) -> Option<Vec<u8>> {
+    use std::io::Read;
+    let mut bytes = Vec::new();
+    flate2::read::GzDecoder::new(blob)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    Some(bytes)
+}
+
+/// Gzip a JSON string for storage in a BLOB column.
+pub fn compress_json(json: &str) -> Vec<u8> {
+    compress(json.as_bytes())
+}
+
+/// Inverse of `compress_json`; `None` for a corrupt blob.
+pub fn decompress_json(blob: &[u8]) -> Option<String> {
+    String::from_utf8(decompress(blob)?).ok()
+}
+
+/// Append a device version to `device_history`.
+pub fn record_device_version(conn: &Connection, rec: &DeviceHistoryRecord) -> Result<()> {
+    conn.execute(
+        "INSERT INTO device_history (
+            run_id, uuid, gtin, seen_at, source_file, detail_hash, udi_version,
+            budi_version, change_summary, risk_class, regulatory_act, device_status,
+            output_hash, source_line, downloaded_at, document
+         ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16)",
+        params![
+            rec.run_id,
+            rec.uuid,
+            rec.gtin,
+            rec.seen_at,
+            rec.source_file,
+            rec.detail_hash,
+            rec.udi_version,
+            rec.budi_version,
+            rec.change_summary,
+            rec.risk_class,
+            rec.regulatory_act,
+            rec.device_status,
+            rec.output_hash,
+            rec.source_line,
+            rec.downloaded_at,
+            rec.document,
+        ],
+    )?;
+    Ok(())
+}
+
+/// All recorded versions of a device, matched by UUID or GTIN, oldest first,
+/// each with the converter version of the run that produced it.
+pub fn device_history(
+    conn: &Connection,
+    uuid_or_gtin: &str,
+) -> Result<Vec<(DeviceHistoryRecord, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT h.run_id, h.uuid, h.gtin, h.seen_at, h.source_file, h.detail_hash,
+                h.udi_version, h.budi_version, h.change_summary, h.risk_class,
+                h.regulatory_act, h.device_status, h.output_hash,
+                COALESCE(r.converter_version, ''), h.source_line, h.downloaded_at, h.document
+         FROM device_history h LEFT JOIN conversion_runs r ON r.id = h.run_id
+         WHERE h.uuid = ?1 OR h.gtin = ?1
+         ORDER BY h.id",
+    )?;
+    let rows = stmt
+        .query_map(params![uuid_or_gtin], |row| {
+            Ok((
+                DeviceHistoryRecord {
+                    run_id: row.get(0)?,
+                    uuid: row.get(1)?,
+                    gtin: row.get(2)?,
+                    seen_at: row.get(3)?,
+                    source_file: row.get(4)?,
+                    detail_hash: row.get(5)?,
+                    udi_version: row.get(6)?,
+                    budi_version: row.get(7)?,
+                    change_summary: row.get(8)?,
+                    risk_class: row.get(9)?,
+                    regulatory_act: row.get(10)?,
+                    device_status: row.get(11)?,
+                    output_hash: row.get(12)?,
+                    source_line: row.get(14)?,
+                    downloaded_at: row.get(15)?,
+                    document: row.get(16)?,
+                },
+                row.get(13)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// A `conversion_runs` row.
+#[derive(Debug, Default, Clone)]
+pub struct ConversionRun {
+    pub id: i64,
+    pub started_at: String,
+    pub finished_at: String,
+    pub mode: String,
+    pub input_dir: String,
+    pub converter_version: String,
+    pub totals: RunTotals,
+}
+
+/// Most recent conversion runs, newest first.
+pub fn recent_runs(conn: &Connection, limit: u32) -> Result<Vec<ConversionRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, started_at, COALESCE(finished_at, ''), mode, input_dir, converter_version,
+                input_files, converted, skipped, errors, output_hash
+         FROM conversion_runs ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt
+        .query_map(params![limit], |row| {
+            Ok(ConversionRun {
+                id: row.get(0)?,
+                started_at: row.get(1)?,
+                finished_at: row.get(2)?,
+                mode: row.get(3)?,
+                input_dir: row.get(4)?,
+                converter_version: row.get(5)?,
+                totals: RunTotals {
+                    input_files: row.get::<_, i64>(6)? as u64,
+                    converted: row.get::<_, i64>(7)? as u64,
+                    skipped: row.get::<_, i64>(8)? as u64,
+                    errors: row.get::<_, i64>(9)? as u64,
+                    output_hash: row.get(10)?,
+                },
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
 /// Get total count of tracked UDI-DIs
 pub fn count_records(conn: &Connection) -> Result<u64> {
     let count: u64 = conn.query_row("SELECT COUNT(*) FROM udi_versions", [], |row| row.get(0))?;
@@ -642,6 +981,28 @@ pub fn stats(conn: &Connection) -> Result<(u64, u64, u64)> {
     Ok((total, with_gtin, with_budi))
 }
 
+/// UUIDs of `uuids` with at least one ACCEPTED push in `env_label` — items
+/// firstbase already has. Empty on legacy DBs without `firstbase_env`.
+pub fn accepted_in_env(
+    conn: &Connection,
+    uuids: &[&str],
+    env_label: &str,
+) -> std::collections::HashSet<String> {
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT 1 FROM push_log WHERE uuid = ?1 AND status = 'ACCEPTED' AND firstbase_env = ?2 LIMIT 1",
+    ) else {
+        return std::collections::HashSet::new();
+    };
+    uuids
+        .iter()
+        .filter(|uuid| {
+            stmt.exists(rusqlite::params![uuid, env_label])
+                .unwrap_or(false)
+        })
+        .map(|uuid| uuid.to_string())
+        .collect()
+}
+
 /// Filter out UUIDs that are NO_LONGER_ON_THE_MARKET in `listing_cache` AND
 /// already have at least one ACCEPTED entry in `push_log` for the given env.
 /// Used by repush flows to suppress G485 noise — re-pushing a NO_LONGER device
@@ -0,0 +1,243 @@
+//! Conversion warnings with stable codes, so a known and accepted warning can
+//! be silenced without losing sight of new ones. Codes are grouped by what
+//! they are about and never reused:
+//!
+//! - `W00x` code lists (countries, SPP types)
+//! - `W01x` texts and languages
+//! - `W02x` classification and certificates
+//! - `W03x` identifiers
+//!
+//! `warn` logs `Warning [W001]: …` (via `log_console::diag`) and collects the
+//! warning for `firstbase_json/warnings_<time>.txt`, written at the end of
+//! the run with a count per code. `[warnings] suppress` silences codes for
+//! every device, `[warnings.suppress_by_gtin]` for single GTINs; the device
+//! is the one the current thread is transforming (`device_scope`).
+//! Suppressed warnings are only counted.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Warning {
+    UnmappedCountry,
+    SppTypeCode,
+    Language,
+    NoRiskClass,
+    MdrCertificate,
+    MddCertificate,
+    DirectMarkingDi,
+}
+
+impl Warning {
+    pub const ALL: [Warning; 7] = [
+        Warning::UnmappedCountry,
+        Warning::SppTypeCode,
+        Warning::Language,
+        Warning::NoRiskClass,
+        Warning::MdrCertificate,
+        Warning::MddCertificate,
+        Warning::DirectMarkingDi,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Warning::UnmappedCountry => "W001",
+            Warning::SppTypeCode => "W002",
+            Warning::Language => "W014",
+            Warning::NoRiskClass => "W021",
+            Warning::MdrCertificate => "W022",
+            Warning::MddCertificate => "W023",
+            Warning::DirectMarkingDi => "W031",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Warning::UnmappedCountry => "country code without GS1 numeric code, passed through",
+            Warning::SppTypeCode => "unexpected SPP type code, PROCEDURE_PACK used",
+            Warning::Language => "language code missing or not ISO 639-1, passed through",
+            Warning::NoRiskClass => "no risk class, EU_CLASS_I used",
+            Warning::MdrCertificate => "MDR Class III without technical documentation / type examination certificate (097.101)",
+            Warning::MddCertificate => "MDD Class IIa/IIb/III without MDD certificate (097.105)",
+            Warning::DirectMarkingDi => "GS1 direct marking DI not 14 digits, left out (097.118)",
+        }
+    }
+}
+
+/// Codes silenced globally and per GTIN (`[warnings]`).
+#[derive(Default)]
+struct Suppression {
+    all: HashSet<String>,
+    by_gtin: HashMap<String, HashSet<String>>,
+}
+
+impl Suppression {
+    fn new(config: &crate::config::Warnings) -> Suppression {
+        let codes = |codes: &[String]| {
+            codes
+                .iter()
+                .map(|c| c.trim().to_ascii_uppercase())
+                .collect()
+        };
+        Suppression {
+            all: codes(&config.suppress),
+            by_gtin: config
+                .suppress_by_gtin
+                .iter()
+                .map(|(gtin, c)| (gtin.clone(), codes(c)))
+                .collect(),
+        }
+    }
+
+    fn covers(&self, warning: Warning, device: &str) -> bool {
+        let code = warning.code();
+        self.all.contains(code)
+            || self
+                .by_gtin
+                .get(device)
+                .is_some_and(|codes| codes.contains(code))
+    }
+}
+
+static SUPPRESSION: RwLock<Option<Suppression>> = RwLock::new(None);
+
+/// Warnings of this run: code, device, message.
+static REPORT: Mutex<Vec<(Warning, String, String)>> = Mutex::new(Vec::new());
+static SUPPRESSED: Mutex<BTreeMap<Warning, usize>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// GTIN (else UUID) of the device this thread is transforming.
+    static DEVICE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Take the suppression rules of `config`; called by `config::load_config`.
+pub fn configure(config: &crate::config::Warnings) {
+    if let Ok(mut s) = SUPPRESSION.write() {
+        *s = Some(Suppression::new(config));
+    }
+}
+
+/// Attributes the warnings of this thread to `device` until dropped.
+pub struct DeviceScope(Option<String>);
+
+impl Drop for DeviceScope {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        DEVICE.with(|d| *d.borrow_mut() = previous);
+    }
+}
+
+pub fn device_scope(device: &str) -> DeviceScope {
+    DeviceScope(DEVICE.with(|d| d.borrow_mut().replace(device.to_string())))
+}
+
+fn current_device() -> String {
+    DEVICE.with(|d| d.borrow().clone()).unwrap_or_default()
+}
+
+/// Whether `warning` is silenced for the current device.
+pub fn is_suppressed(warning: Warning) -> bool {
+    SUPPRESSION
+        .read()
+        .ok()
+        .and_then(|s| Some(s.as_ref()?.covers(warning, &current_device())))
+        .unwrap_or(false)
+}
+
+/// Log and collect `warning`, unless suppressed for the current device.
+pub fn warn(warning: Warning, message: &str) {
+    if is_suppressed(warning) {
+        if let Ok(mut suppressed) = SUPPRESSED.lock() {
+            *suppressed.entry(warning).or_insert(0) += 1;
+        }
+        return;
+    }
+    crate::log_console::diag(&format!("Warning [{}]: {}", warning.code(), message));
+    if let Ok(mut report) = REPORT.lock() {
+        report.push((warning, current_device(), message.to_string()));
+    }
+}
+
+/// Write this run's warnings to `<output_dir>/warnings_<time>.txt`
+/// (a legend with the count per code, then tab-separated code, device,
+/// message) and reset them; the path and a
+/// summary line (`W001 ×3, W014 ×1; 2 suppressed`), `None` when there were
+/// none.
+pub fn write_report(output_dir: &Path) -> anyhow::Result<Option<(PathBuf, String)>> {
+    let rows = match REPORT.lock() {
+        Ok(mut rows) => std::mem::take(&mut *rows),
+        Err(_) => return Ok(None),
+    };
+    let suppressed: usize = SUPPRESSED
+        .lock()
+        .map(|mut s| std::mem::take(&mut *s).into_values().sum())
+        .unwrap_or(0);
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    let mut counts: BTreeMap<Warning, usize> = BTreeMap::new();
+    for (warning, _, _) in &rows {
+        *counts.entry(*warning).or_insert(0) += 1;
+    }
+    let mut summary = counts
+        .iter()
+        .map(|(w, n)| format!("{} ×{}", w.code(), n))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if suppressed > 0 {
+        summary.push_str(&format!("; {} suppressed", suppressed));
+    }
+
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(format!(
+        "warnings_{}.txt",
+        chrono::Local::now().format("%H.%M_%d.%m.%Y")
+    ));
+    // Legend first: one `# code count description` line per code.
+    let lines: Vec<String> = counts
+        .iter()
+        .map(|(w, n)| format!("# {}\t{}\t{}", w.code(), n, w.description()))
+        .chain(
+            rows.iter()
+                .map(|(w, device, message)| format!("{}\t{}\t{}", w.code(), device, message)),
+        )
+        .collect();
+    std::fs::write(&path, lines.join("\n") + "\n")?;
+    Ok(Some((path, summary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_suppressible_per_gtin() {
+        let codes: HashSet<&str> = Warning::ALL.iter().map(|w| w.code()).collect();
+        assert_eq!(codes.len(), Warning::ALL.len());
+        assert_eq!(Warning::UnmappedCountry.code(), "W001");
+        assert_eq!(Warning::Language.code(), "W014");
+
+        let suppression = Suppression::new(
+            &toml::from_str(
+                r#"
+                suppress = ["w031"]
+                [suppress_by_gtin]
+                "04012345678901" = ["W001"]
+                "#,
+            )
+            .unwrap(),
+        );
+        assert!(suppression.covers(Warning::DirectMarkingDi, ""));
+        assert!(!suppression.covers(Warning::UnmappedCountry, ""));
+        assert!(suppression.covers(Warning::UnmappedCountry, "04012345678901"));
+        assert!(!suppression.covers(Warning::Language, "04012345678901"));
+
+        {
+            let _scope = device_scope("04012345678901");
+            assert_eq!(current_device(), "04012345678901");
+        }
+        assert_eq!(current_device(), "");
+    }
+}
@@ -0,0 +1,407 @@
+//! Withdrawal of devices published in error (`withdraw` subcommand).
+//!
+//! Two corrections, both asynchronous firstbase requests confirmed through
+//! RequestStatus like a push (`[push.poll]`):
+//! - `Kind::Unpublish` — `CatalogueItemPublication/DeleteMany`: removes the
+//!   publication to a recipient GLN (published to the wrong GLN). The item
+//!   stays Live in the data pool.
+//! - `Kind::Withdraw` — `CatalogueItem/Live/WithdrawMany`: withdraws the item
+//!   hierarchy from the recipients of a target market (wrong target market).
+//!
+//! Identifier, GTIN and target market come from the document the device was
+//! pushed from (`firstbase_json/processed/<uuid>.json`, else the pending
+//! copy); the target market can be overridden for a document converted with
+//! the wrong one. Every device gets a `push_log` row: `WITHDRAWN` /
+//! `UNPUBLISHED` when GS1 confirmed, `WITHDRAW_FAILED` / `UNPUBLISH_FAILED`
+//! with the error codes and `rejection` hints otherwise.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use serde_json::{json, Value};
+
+use crate::gui::{self, Settings};
+use crate::{push_batch, push_lock, rejection};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Withdraw,
+    Unpublish,
+}
+
+impl Kind {
+    fn endpoint(self) -> &'static str {
+        match self {
+            Kind::Withdraw => "CatalogueItem/Live/WithdrawMany",
+            Kind::Unpublish => "CatalogueItemPublication/DeleteMany",
+        }
+    }
+
+    /// `push_log.status` of a confirmed request.
+    pub fn status(self) -> &'static str {
+        match self {
+            Kind::Withdraw => "WITHDRAWN",
+            Kind::Unpublish => "UNPUBLISHED",
+        }
+    }
+
+    /// `push_log.status` of a rejected or unconfirmed request.
+    pub fn failed_status(self) -> &'static str {
+        match self {
+            Kind::Withdraw => "WITHDRAW_FAILED",
+            Kind::Unpublish => "UNPUBLISH_FAILED",
+        }
+    }
+}
+
+/// One device to withdraw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub uuid: String,
+    /// `DraftItem/Identifier` of the pushed document.
+    pub ident: String,
+    pub gtin: String,
+    pub target_market: String,
+}
+
+/// The document device `uuid` was pushed from: the accepted copy in
+/// `processed/`, else the pending one.
+pub fn document(firstbase_dir: &Path, uuid: &str) -> Option<PathBuf> {
+    let name = format!("{}.json", uuid);
+    [
+        firstbase_dir.join("processed").join(&name),
+        firstbase_dir.join(&name),
+    ]
+    .into_iter()
+    .find(|p| p.exists())
+}
+
+/// The target of the device of firstbase document `path` (named
+/// `<uuid>.json`); `target_market` replaces the document's.
+pub fn target(path: &Path, target_market: Option<&str>) -> anyhow::Result<Target> {
+    let uuid = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let doc: Value = serde_json::from_str(
+        &std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+    )
+    .with_context(|| format!("{} is not JSON", path.display()))?;
+    let at = |pointer: &str| doc.pointer(pointer).and_then(|v| v.as_str());
+    let gtin = at("/DraftItem/TradeItem/Gtin")
+        .filter(|g| !g.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("{} has no GTIN", path.display()))?;
+    Ok(Target {
+        ident: at("/DraftItem/Identifier")
+            .map_or_else(|| format!("Draft_{}", uuid), str::to_string),
+        uuid,
+        gtin: gtin.to_string(),
+        target_market: target_market
+            .or(at(
+                "/DraftItem/TradeItem/TargetMarket/TargetMarketCountryCode/Value",
+            ))
+            .unwrap_or("097")
+            .to_string(),
+    })
+}
+
+/// The request item of `target` for `kind`; `recipient` is the data
+/// recipient GLN.
+pub fn item(kind: Kind, target: &Target, data_source: &str, recipient: &str) -> Value {
+    let mut item = json!({
+        "Identifier": target.ident,
+        "DataSource": data_source,
+        "Gtin": target.gtin,
+        "TargetMarket": target.target_market,
+    });
+    item[match kind {
+        Kind::Withdraw => "DataRecipients",
+        Kind::Unpublish => "PublishToGln",
+    }] = json!([recipient]);
+    item
+}
+
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub confirmed: u32,
+    pub failed: u32,
+}
+
+/// Withdraw or unpublish `targets` with the login, GLNs and environment of
+/// `settings`; `publish_to_gln` is the recipient to withdraw from.
+pub fn run(
+    settings: &Settings,
+    kind: Kind,
+    targets: &[Target],
+    log: &dyn Fn(&str),
+) -> anyhow::Result<Outcome> {
+    let api_base = settings.firstbase_api_base();
+    let env_label = settings.firstbase_label();
+    // Its push_log rows and requests must not interleave with a push.
+    let _lock = push_lock::acquire(env_label, "withdraw")?;
+    let config = crate::config::load_config(&crate::platform_dirs::config_file()).ok();
+    let polling = config
+        .as_ref()
+        .map(|c| crate::status_poll::resolve(&c.push.poll, settings.firstbase_env.key()))
+        .unwrap_or_default();
+    let limits = config
+        .as_ref()
+        .map(|c| push_batch::limits(&c.push.batch))
+        .unwrap_or_default();
+    let http_agent = crate::http::agent_for(api_base);
+    let http_post = |url: &str, auth: &str, body: &str| -> anyhow::Result<String> {
+        let mut req = http_agent
+            .post(url)
+            .header("Content-Type", "application/json");
+        if !auth.is_empty() {
+            req = req.header("Authorization", &format!("bearer {}", auth));
+        }
+        let mut resp = req.send(body.as_bytes())?;
+        let status = resp.status();
+        let resp_body = resp.body_mut().read_to_string()?;
+        if status.as_u16() >= 400 {
+            Err(anyhow::anyhow!("http {}: {}", status, resp_body))
+        } else {
+            Ok(resp_body)
+        }
+    };
+    log(&format!(
+        "[Withdraw] {} {} device(s) via {} on {} ({}), recipient {}",
+        match kind {
+            Kind::Withdraw => "Withdrawing",
+            Kind::Unpublish => "Unpublishing",
+        },
+        targets.len(),
+        kind.endpoint(),
+        env_label,
+        api_base,
+        settings.publish_to_gln
+    ));
+    let token = gui::firstbase_token(
+        &http_post,
+        api_base,
+        &settings.firstbase_email,
+        &settings.firstbase_password,
+        &settings.provider_gln,
+    )?;
+
+    let db_dir = crate::download::app_data_dir().join("db");
+    std::fs::create_dir_all(&db_dir)?;
+    let conn = crate::version_db::open_db(&db_dir.join("version_tracking.db"))?;
+    gui::ensure_push_log_schema(&conn);
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let mut outcome = Outcome::default();
+    for (bi, batch) in targets.chunks(limits.start).enumerate() {
+        if bi > 0 && !limits.pause.is_zero() {
+            std::thread::sleep(limits.pause);
+        }
+        let items: Vec<Value> = batch
+            .iter()
+            .map(|t| item(kind, t, &settings.provider_gln, &settings.publish_to_gln))
+            .collect();
+        let payload = json!({ "Items": items });
+        let url = format!("{}/{}", api_base, kind.endpoint());
+        let mut submitted = Err(anyhow::anyhow!("not submitted"));
+        for attempt in 1..=3 {
+            submitted = http_post(&url, &token, &payload.to_string());
+            match &submitted {
+                Err(e) if e.to_string().contains("429") && attempt < 3 => {
+                    log(&format!(
+                        "  429 rate limited — waiting 60s (attempt {}/3)",
+                        attempt
+                    ));
+                    std::thread::sleep(std::time::Duration::from_secs(60));
+                }
+                _ => break,
+            }
+        }
+        let req_id = submitted.map_err(|e| e.to_string()).and_then(|body| {
+            serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|b| b.get("RequestIdentifier")?.as_str().map(str::to_string))
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| format!("no RequestIdentifier in {}", body))
+        });
+
+        // Per batch: the GS1 rejections, or why there is no verdict at all.
+        let verdict: Result<rejection::Response, (String, String)> = match &req_id {
+            Err(e) => Err(("BATCH_FAILED".to_string(), e.chars().take(300).collect())),
+            Ok(req_id) => {
+                log(&format!("[Withdraw] Batch {}: {}", bi + 1, req_id));
+                let mut verdict = Err((
+                    "BATCH_UNCONFIRMED".to_string(),
+                    "No terminal status from RequestStatus/Get (poll timeout or network error)"
+                        .to_string(),
+                ));
+                for (poll, wait) in polling.schedule() {
+                    std::thread::sleep(wait);
+                    let poll_body = json!({
+                        "RequestIdentifier": req_id,
+                        "IncludeGs1Response": true,
+                    });
+                    let Ok(body) = http_post(
+                        &format!("{}/RequestStatus/Get", api_base),
+                        &token,
+                        &poll_body.to_string(),
+                    )
+                    .and_then(|r| Ok(serde_json::from_str::<Value>(&r)?)) else {
+                        continue;
+                    };
+                    let status = body
+                        .get("Status")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown");
+                    if status != "Done" && status != "Failed" {
+                        continue;
+                    }
+                    log(&format!("  Poll {}: {}", poll, status));
+                    let gs1 = body.pointer("/Gs1ResponseMessage/GS1Response");
+                    verdict = if status == "Failed"
+                        && gs1.and_then(|v| v.as_array()).is_none_or(|r| r.is_empty())
+                    {
+                        Err((
+                            "BATCH_FAILED".to_string(),
+                            body.get("ErrorDetails")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("(no ErrorDetails)")
+                                .replace(['\r', '\n'], " ")
+                                .chars()
+                                .take(300)
+                                .collect(),
+                        ))
+                    } else {
+                        Ok(gs1.map(rejection::parse).unwrap_or_default())
+                    };
+                    break;
+                }
+                verdict
+            }
+        };
+
+        for target in batch {
+            let rejections: Vec<rejection::Rejection> = match &verdict {
+                Ok(response) => {
+                    let mut own: Vec<_> = response
+                        .rejections
+                        .iter()
+                        .filter(|r| r.gtin == target.gtin || r.document == target.ident)
+                        .cloned()
+                        .collect();
+                    own.extend(response.document_errors.iter().map(|(code, desc)| {
+                        rejection::Rejection {
+                            document: target.ident.clone(),
+                            gtin: target.gtin.clone(),
+                            code: code.clone(),
+                            attribute: "(document-level)".to_string(),
+                            description: desc.clone(),
+                        }
+                    }));
+                    own
+                }
+                Err((code, desc)) => vec![rejection::Rejection {
+                    document: target.ident.clone(),
+                    gtin: target.gtin.clone(),
+                    code: code.clone(),
+                    attribute: "(batch transport)".to_string(),
+                    description: desc.clone(),
+                }],
+            };
+            let status = if rejections.is_empty() {
+                outcome.confirmed += 1;
+                log(&format!(
+                    "  {} {} {}",
+                    kind.status(),
+                    target.gtin,
+                    target.uuid
+                ));
+                kind.status()
+            } else {
+                outcome.failed += 1;
+                for r in &rejections {
+                    log(&format!(
+                        "  REJECTED {} {} {}: {}",
+                        r.gtin, r.code, r.attribute, r.description
+                    ));
+                    log(&format!("    → {}", r.hint()));
+                }
+                kind.failed_status()
+            };
+            let mut codes: Vec<&str> = rejections.iter().map(|r| r.code.as_str()).collect();
+            codes.sort_unstable();
+            codes.dedup();
+            let hints: Vec<String> = rejections.iter().map(|r| r.hint()).collect();
+            if let Err(e) = conn.execute(
+                "INSERT INTO push_log (uuid,gtin,pushed_at,request_id,status,error_code,error_msg,publish_gln,firstbase_env) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9)",
+                rusqlite::params![
+                    target.uuid,
+                    target.gtin,
+                    now,
+                    req_id.as_deref().unwrap_or(""),
+                    status,
+                    codes.join(","),
+                    hints.join(" | "),
+                    settings.publish_to_gln,
+                    env_label
+                ],
+            ) {
+                log(&format!("[Withdraw] push_log write failed for {}: {}", target.uuid, e));
+            }
+        }
+    }
+    log(&format!(
+        "[Withdraw] Done: {} {}, {} failed (push_log)",
+        outcome.confirmed,
+        kind.status().to_lowercase(),
+        outcome.failed
+    ));
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_come_from_the_pushed_document() {
+        let dir = std::env::temp_dir().join(format!("withdraw_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("processed")).unwrap();
+        let doc = |gtin: &str, tm: &str| {
+            json!({ "DraftItem": {
+                "Identifier": "Draft_u-1",
+                "TradeItem": {
+                    "Gtin": gtin,
+                    "TargetMarket": { "TargetMarketCountryCode": { "Value": tm } }
+                }
+            }})
+            .to_string()
+        };
+        // The accepted copy in processed/ wins over a re-converted pending one.
+        std::fs::write(dir.join("processed/u-1.json"), doc("07640000000010", "756")).unwrap();
+        std::fs::write(dir.join("u-1.json"), doc("07640000000010", "097")).unwrap();
+        let path = document(&dir, "u-1").unwrap();
+        assert_eq!(path, dir.join("processed/u-1.json"));
+        assert!(document(&dir, "u-2").is_none());
+        let t = target(&path, None).unwrap();
+        assert_eq!((t.uuid.as_str(), t.ident.as_str()), ("u-1", "Draft_u-1"));
+        assert_eq!(t.target_market, "756");
+        assert_eq!(target(&path, Some("276")).unwrap().target_market, "276");
+
+        assert_eq!(
+            item(Kind::Withdraw, &t, "7612345000480", "7612345000527"),
+            json!({
+                "Identifier": "Draft_u-1",
+                "DataSource": "7612345000480",
+                "Gtin": "07640000000010",
+                "TargetMarket": "756",
+                "DataRecipients": ["7612345000527"],
+            })
+        );
+        let unpublish = item(Kind::Unpublish, &t, "7612345000480", "7612345000527");
+        assert_eq!(unpublish["PublishToGln"], json!(["7612345000527"]));
+        assert!(unpublish.get("DataRecipients").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
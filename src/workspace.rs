@@ -0,0 +1,115 @@
+//! Tabs of the GUI workspace. Each tab is one dataset — the working set in
+//! `firstbase_json/` or any other folder of converted documents (a snapshot of
+//! last month, a copy from another machine) — paired with a firstbase
+//! environment, and keeps its own device table, readiness counts and push
+//! dashboard. Pushes only run from the working-set tabs: the push pipeline
+//! always reads `firstbase_json/`. The tab list is saved in `settings.json`.
+
+use std::path::PathBuf;
+
+use crate::device_table;
+use crate::gui::FirstbaseEnv;
+use crate::packaging;
+use crate::provenance;
+use crate::push_dashboard;
+use crate::readiness;
+
+/// What a tab shows; persisted.
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TabSpec {
+    pub name: String,
+    /// Folder of converted documents; `None` = the working set.
+    #[serde(default)]
+    pub dataset: Option<PathBuf>,
+    #[serde(default)]
+    pub env: FirstbaseEnv,
+}
+
+impl TabSpec {
+    /// The always-present first tab.
+    pub fn working_set(env: FirstbaseEnv) -> TabSpec {
+        TabSpec {
+            name: "Working set".to_string(),
+            dataset: None,
+            env,
+        }
+    }
+
+    pub fn env_label(&self) -> &'static str {
+        match self.env {
+            FirstbaseEnv::Test => "Test",
+            FirstbaseEnv::Production => "Production",
+        }
+    }
+}
+
+/// One device opened from the table.
+pub struct DeviceDetail {
+    pub title: String,
+    /// Pretty-printed document.
+    pub json: String,
+    pub path: PathBuf,
+    /// `None` when the document could not be read.
+    pub packaging: Option<packaging::Node>,
+    /// Converted from (sidecar), if recorded.
+    pub source: Option<provenance::Provenance>,
+    /// Origin of every attribute (provenance inspector).
+    pub attributes: Vec<provenance::Attribute>,
+    /// Pointer filter of the inspector.
+    pub attribute_filter: String,
+}
+
+/// Per-tab UI state.
+pub struct Tab {
+    pub spec: TabSpec,
+    /// Loaded on first open of the "Devices" section and on Reload.
+    pub device_rows: Option<Vec<device_table::DeviceRow>>,
+    pub device_view: device_table::View,
+    /// Indices into `device_rows` after search/filter/sort.
+    pub device_visible: Vec<usize>,
+    /// UUIDs ticked in the device table; kept across filter changes.
+    pub selected: std::collections::BTreeSet<String>,
+    /// Document shown in the detail window.
+    pub device_detail: Option<DeviceDetail>,
+    /// Device count per environment and stage, from the last refresh.
+    pub readiness_counts:
+        std::collections::BTreeMap<String, std::collections::BTreeMap<readiness::Stage, usize>>,
+    pub readiness_status: String,
+    /// Per-chunk state of this tab's current/last firstbase push.
+    pub push_monitor: push_dashboard::Monitor,
+    /// Chunk whose response is shown in the drill-down window.
+    pub push_chunk_detail: Option<usize>,
+}
+
+impl Tab {
+    pub fn new(spec: TabSpec) -> Tab {
+        Tab {
+            spec,
+            device_rows: None,
+            device_view: device_table::View::default(),
+            device_visible: Vec::new(),
+            selected: Default::default(),
+            device_detail: None,
+            readiness_counts: Default::default(),
+            readiness_status: String::new(),
+            push_monitor: push_dashboard::Monitor::default(),
+            push_chunk_detail: None,
+        }
+    }
+
+    pub fn is_working_set(&self) -> bool {
+        self.spec.dataset.is_none()
+    }
+
+    /// The folder this tab's documents are read from.
+    pub fn firstbase_dir(&self) -> PathBuf {
+        self.spec
+            .dataset
+            .clone()
+            .unwrap_or_else(|| crate::download::app_data_dir().join("firstbase_json"))
+    }
+
+    pub fn title(&self) -> String {
+        format!("{} · {}", self.spec.name, self.spec.env_label())
+    }
+}